@@ -0,0 +1,314 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Materialized adjacency indexes of relationship types
+//!
+//! An [`AdjacencyIndex`] stores the edges of one relationship type as a Lance dataset
+//! with one row per source node, sorted by its key, holding the node's outgoing edges
+//! in a `neighbors` list column: the compressed sparse row layout, with the list offsets
+//! as row offsets. The source key column has a BTree index, so an expansion from a few
+//! nodes reads their rows instead of scanning the edge table.
+//!
+//! Registered with [`LanceCatalog::with_adjacency_index`], the index replaces the edge
+//! table in the scans of its relationship type for as long as it was built from the
+//! edge dataset version the catalog reads; a stale index is ignored until
+//! [`AdjacencyIndex::refresh`] brings it up to date. Refreshing after appends to the
+//! edge dataset reads only the new fragments, so the index keeps up with continuous
+//! ingestion; the edges of a source node may then span several rows until
+//! [`AdjacencyIndex::rebuild`] regroups them.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use arrow_array::RecordBatch;
+use arrow_schema::Schema;
+use datafusion::dataframe::DataFrame;
+use datafusion::functions::core::expr_fn::named_struct;
+use datafusion::logical_expr::{col, lit, ExprFunctionExt};
+use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
+use datafusion::physical_plan::SendableRecordBatchStream;
+use datafusion::prelude::SessionContext;
+use datafusion_functions_aggregate::array_agg::array_agg;
+use futures::StreamExt;
+use lance::dataset::{InsertBuilder, WriteMode, WriteParams};
+use lance::table::format::Fragment;
+use lance::Dataset;
+use lance_index::optimize::OptimizeOptions;
+use lance_index::scalar::ScalarIndexParams;
+use lance_index::{DatasetIndexExt, IndexType};
+
+use crate::config::{GraphConfig, RelationshipMapping};
+use crate::error::{GraphError, Result};
+use crate::lance_catalog::LanceCatalog;
+use crate::lance_table::LanceTable;
+
+/// List column holding the outgoing edges of each source node
+pub const NEIGHBORS_COLUMN: &str = "neighbors";
+
+const RELATIONSHIP_TYPE_KEY: &str = "lance_graph.adjacency.relationship_type";
+const EDGE_TABLE_KEY: &str = "lance_graph.adjacency.edge_table";
+const EDGE_VERSION_KEY: &str = "lance_graph.adjacency.edge_version";
+
+/// Precomputed adjacency lists of one relationship type, persisted as a Lance dataset
+#[derive(Debug, Clone)]
+pub struct AdjacencyIndex {
+    dataset: Arc<Dataset>,
+    rel_type: String,
+    edge_table: String,
+    edge_version: u64,
+}
+
+impl AdjacencyIndex {
+    /// Build the index of `rel_type` from its edge dataset in `catalog`, writing it to
+    /// `uri` and replacing any index there
+    pub async fn build(
+        catalog: &LanceCatalog,
+        config: &GraphConfig,
+        rel_type: &str,
+        uri: &str,
+    ) -> Result<Self> {
+        let (mapping, edges) = edge_dataset(catalog, config, rel_type)?;
+        let edge_table = mapping.table_name().to_string();
+        let edge_version = edges.version().version;
+
+        let ctx = SessionContext::new();
+        let rows = ctx.read_table(Arc::new(LanceTable::new(edges)))?;
+        let lists = adjacency_lists(rows, mapping)?;
+        let stream = lists.execute_stream().await?;
+        let metadata = HashMap::from([
+            (RELATIONSHIP_TYPE_KEY.to_string(), rel_type.to_string()),
+            (EDGE_TABLE_KEY.to_string(), edge_table.clone()),
+            (EDGE_VERSION_KEY.to_string(), edge_version.to_string()),
+        ]);
+        let schema = Arc::new(Schema::new_with_metadata(
+            stream.schema().fields().clone(),
+            metadata,
+        ));
+        let batches = {
+            let schema = schema.clone();
+            stream.map(move |batch| -> datafusion::common::Result<RecordBatch> {
+                Ok(RecordBatch::try_new(
+                    schema.clone(),
+                    batch?.columns().to_vec(),
+                )?)
+            })
+        };
+        let params = WriteParams {
+            mode: WriteMode::Overwrite,
+            ..Default::default()
+        };
+        let batches: SendableRecordBatchStream =
+            Box::pin(RecordBatchStreamAdapter::new(schema, batches));
+        let mut dataset = InsertBuilder::new(uri)
+            .with_params(&params)
+            .execute_stream(batches)
+            .await?;
+        if let [key] = mapping.source_id_fields().as_slice() {
+            dataset
+                .create_index(
+                    &[*key],
+                    IndexType::BTree,
+                    None,
+                    &ScalarIndexParams::default(),
+                    true,
+                )
+                .await?;
+        }
+
+        Ok(Self {
+            dataset: Arc::new(dataset),
+            rel_type: rel_type.to_string(),
+            edge_table,
+            edge_version,
+        })
+    }
+
+    /// Open an index built by [`AdjacencyIndex::build`]
+    pub async fn open(uri: &str) -> Result<Self> {
+        let dataset = Dataset::open(uri).await?;
+        let metadata = &dataset.schema().metadata;
+        let entry = |key: &str| {
+            metadata
+                .get(key)
+                .cloned()
+                .ok_or_else(|| GraphError::ConfigError {
+                    message: format!("Dataset at '{}' is not an adjacency index", uri),
+                    location: snafu::Location::new(file!(), line!(), column!()),
+                })
+        };
+        let rel_type = entry(RELATIONSHIP_TYPE_KEY)?;
+        let edge_table = entry(EDGE_TABLE_KEY)?;
+        let edge_version =
+            entry(EDGE_VERSION_KEY)?
+                .parse()
+                .map_err(|_| GraphError::ConfigError {
+                    message: format!("Adjacency index at '{}' has an invalid edge version", uri),
+                    location: snafu::Location::new(file!(), line!(), column!()),
+                })?;
+        Ok(Self {
+            dataset: Arc::new(dataset),
+            rel_type,
+            edge_table,
+            edge_version,
+        })
+    }
+
+    /// Bring the index up to date with the edges in `catalog`
+    ///
+    /// When the edge dataset has only gained fragments since the index was built, only
+    /// those are read: their edges are appended as further rows of their source nodes
+    /// and the key index is extended over them. Any other change, such as a deletion or
+    /// an update, rebuilds the index.
+    pub async fn refresh(&self, catalog: &LanceCatalog, config: &GraphConfig) -> Result<Self> {
+        if self.is_current(catalog) {
+            return Ok(self.clone());
+        }
+        let (mapping, edges) = edge_dataset(catalog, config, &self.rel_type)?;
+        let Some(appended) = self.appended_fragments(&edges).await else {
+            return self.rebuild(catalog, config).await;
+        };
+
+        let mut scanner = edges.scan();
+        scanner.with_fragments(appended);
+        let ctx = SessionContext::new();
+        let rows = ctx.read_batch(scanner.try_into_batch().await?)?;
+        let lists: Vec<RecordBatch> = adjacency_lists(rows, mapping)?
+            .collect()
+            .await?
+            .into_iter()
+            .filter(|batch| batch.num_rows() > 0)
+            .collect();
+
+        let mut dataset = if lists.is_empty() {
+            Dataset::clone(&self.dataset)
+        } else {
+            let params = WriteParams {
+                mode: WriteMode::Append,
+                ..Default::default()
+            };
+            let mut dataset = InsertBuilder::new(self.dataset.clone())
+                .with_params(&params)
+                .execute(lists)
+                .await?;
+            dataset
+                .optimize_indices(&OptimizeOptions::default())
+                .await?;
+            dataset
+        };
+        let edge_version = edges.version().version;
+        dataset
+            .update_schema_metadata([(EDGE_VERSION_KEY, edge_version.to_string().as_str())])
+            .await?;
+        Ok(Self {
+            dataset: Arc::new(dataset),
+            edge_version,
+            ..self.clone()
+        })
+    }
+
+    /// Build the index again from all the edges in `catalog`
+    pub async fn rebuild(&self, catalog: &LanceCatalog, config: &GraphConfig) -> Result<Self> {
+        Self::build(catalog, config, &self.rel_type, self.dataset.uri()).await
+    }
+
+    /// The fragments `edges` gained since the version the index was built from, or
+    /// `None` if a fragment of that version changed or that version is gone
+    async fn appended_fragments(&self, edges: &Dataset) -> Option<Vec<Fragment>> {
+        let built = edges.checkout_version(self.edge_version).await.ok()?;
+        let current = edges.fragments();
+        if !built.fragments().iter().all(|f| current.contains(f)) {
+            return None;
+        }
+        let built_ids: HashSet<u64> = built.fragments().iter().map(|f| f.id).collect();
+        Some(
+            current
+                .iter()
+                .filter(|f| !built_ids.contains(&f.id))
+                .cloned()
+                .collect(),
+        )
+    }
+
+    /// Whether the index was built from the edge dataset version `catalog` reads
+    pub fn is_current(&self, catalog: &LanceCatalog) -> bool {
+        catalog.relationship_version(&self.edge_table) == Some(self.edge_version)
+    }
+
+    /// The relationship type whose edges the index holds
+    pub fn relationship_type(&self) -> &str {
+        &self.rel_type
+    }
+
+    /// The version of the edge dataset the index was built from
+    pub fn edge_version(&self) -> u64 {
+        self.edge_version
+    }
+
+    /// The Lance dataset holding the adjacency lists
+    pub fn dataset(&self) -> Arc<Dataset> {
+        self.dataset.clone()
+    }
+}
+
+/// The mapping of `rel_type` and the edge dataset `catalog` holds for it
+fn edge_dataset<'a>(
+    catalog: &LanceCatalog,
+    config: &'a GraphConfig,
+    rel_type: &str,
+) -> Result<(&'a RelationshipMapping, Arc<Dataset>)> {
+    let mapping =
+        config
+            .get_relationship_mapping(rel_type)
+            .ok_or_else(|| GraphError::ConfigError {
+                message: format!("No relationship mapping for '{}'", rel_type),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            })?;
+    if mapping.adjacency_column.is_some() {
+        return Err(GraphError::ConfigError {
+            message: format!(
+                "Relationship '{}' is already stored as an adjacency column",
+                rel_type
+            ),
+            location: snafu::Location::new(file!(), line!(), column!()),
+        });
+    }
+    let edges = catalog
+        .relationship_dataset(mapping.table_name())
+        .ok_or_else(|| GraphError::ConfigError {
+            message: format!("No dataset registered for '{}'", mapping.table_name()),
+            location: snafu::Location::new(file!(), line!(), column!()),
+        })?;
+    Ok((mapping, edges))
+}
+
+/// The edge rows of the relationship type grouped into one row per source node, sorted
+/// by its key, with the other columns of its edges in a list ordered by target key
+fn adjacency_lists(rows: DataFrame, mapping: &RelationshipMapping) -> Result<DataFrame> {
+    let rows = match mapping.type_discriminator() {
+        Some((column, value)) => rows.filter(col(column).eq(lit(value)))?,
+        None => rows,
+    };
+    let keys = mapping.source_id_fields();
+    let neighbor = named_struct(
+        rows.schema()
+            .fields()
+            .iter()
+            .map(|field| field.name().as_str())
+            .filter(|name| !keys.contains(name))
+            .flat_map(|name| [lit(name), col(name)])
+            .collect(),
+    );
+    let neighbors = array_agg(neighbor)
+        .order_by(
+            mapping
+                .target_id_fields()
+                .into_iter()
+                .map(|key| col(key).sort(true, false))
+                .collect(),
+        )
+        .build()?
+        .alias(NEIGHBORS_COLUMN);
+    Ok(rows
+        .aggregate(keys.iter().map(|key| col(*key)).collect(), vec![neighbors])?
+        .sort(keys.iter().map(|key| col(*key).sort(true, false)).collect())?)
+}
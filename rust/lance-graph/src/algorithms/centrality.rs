@@ -0,0 +1,417 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Degree and betweenness centrality, the `graph.degree` and `graph.betweenness`
+//! procedures
+//!
+//! Both read the edge tables into an in-memory [`Projection`]. Degrees count the edges
+//! leaving, entering or touching each node, or sum a weight property of them.
+//! Betweenness runs Brandes' algorithm from every node, or from a seeded sample of
+//! source nodes whose scores are scaled up to estimate the exact ones; the graph is
+//! taken as simple and directed, so parallel relationships and self-loops add no
+//! paths. Both yield nodes from highest score to lowest.
+
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use arrow::array::{ArrayRef, AsArray, Float64Array, RecordBatch, StructArray};
+use arrow::datatypes::Int64Type;
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use async_trait::async_trait;
+use datafusion::scalar::ScalarValue;
+
+use super::projection::AlgorithmGraph;
+use super::{config_field, config_struct, Projection, SplitMix64};
+use crate::error::{GraphError, Result};
+use crate::procedures::{Procedure, ProcedureContext, ProcedureParameter};
+
+const DEGREE: &str = "graph.degree";
+const BETWEENNESS: &str = "graph.betweenness";
+
+/// `graph.degree([config])`: the `nodeId` and `degree` of every node of the projection
+#[derive(Debug)]
+pub(crate) struct DegreeCentrality;
+
+/// `graph.betweenness([config])`: the `nodeId` and betweenness `score` of every node of
+/// the projection
+#[derive(Debug)]
+pub(crate) struct BetweennessCentrality;
+
+/// Edges a degree counts
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum DegreeDirection {
+    #[default]
+    Out,
+    In,
+    Total,
+}
+
+/// Options of a `graph.degree` run, from the `config` map
+#[derive(Debug, Default, Clone, PartialEq)]
+struct DegreeOptions {
+    direction: DegreeDirection,
+    weight: Option<String>,
+}
+
+/// Options of a `graph.betweenness` run, from the `config` map
+#[derive(Debug, Default, Clone, PartialEq)]
+struct BetweennessOptions {
+    /// Number of source nodes to sample, all when `None`
+    sampling_size: Option<usize>,
+    sampling_seed: u64,
+}
+
+fn output_schema(key_type: DataType, score: &str) -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("nodeId", key_type, false),
+        Field::new(score, DataType::Float64, false),
+    ]))
+}
+
+#[async_trait]
+impl Procedure for DegreeCentrality {
+    fn name(&self) -> &str {
+        DEGREE
+    }
+
+    fn parameters(&self) -> Vec<ProcedureParameter> {
+        vec![AlgorithmGraph::config_parameter(vec![
+            Field::new("direction", DataType::Utf8, true),
+            Field::new("weightProperty", DataType::Utf8, true),
+        ])]
+    }
+
+    fn output_schema(&self, ctx: &ProcedureContext) -> Result<SchemaRef> {
+        self.output_schema_for(ctx, &[])
+    }
+
+    fn output_schema_for(
+        &self,
+        ctx: &ProcedureContext,
+        args: &[Option<ScalarValue>],
+    ) -> Result<SchemaRef> {
+        let config = args.first().and_then(Option::as_ref);
+        let options = DegreeOptions::try_from_config(config)?;
+        let graph =
+            AlgorithmGraph::from_config_weighted(DEGREE, ctx, config, options.weight.as_deref())?;
+        Ok(output_schema(graph.key_type(DEGREE)?, "degree"))
+    }
+
+    async fn call(&self, ctx: &ProcedureContext, args: Vec<ScalarValue>) -> Result<RecordBatch> {
+        let known: Vec<Option<ScalarValue>> = args.iter().cloned().map(Some).collect();
+        let schema = self.output_schema_for(ctx, &known)?;
+        let options = DegreeOptions::try_from_config(args.first())?;
+        let session = ctx.session().cloned().unwrap_or_default();
+        let graph = AlgorithmGraph::from_config_weighted(
+            DEGREE,
+            ctx,
+            args.first(),
+            options.weight.as_deref(),
+        )?;
+        let (projection, weights) = match options.weight {
+            Some(_) => graph.read_weighted(&session).await?,
+            None => {
+                let projection = graph.read(&session).await?;
+                let weights = vec![1.0; projection.edges.len()];
+                (projection, weights)
+            }
+        };
+
+        let degrees = degrees(
+            projection.nodes.len(),
+            &projection.edges,
+            &weights,
+            options.direction,
+        );
+        let (node_ids, degrees) = ranked(schema.field(0).data_type(), &projection.nodes, &degrees)?;
+        Ok(RecordBatch::try_new(
+            schema,
+            vec![node_ids, Arc::new(degrees)],
+        )?)
+    }
+}
+
+#[async_trait]
+impl Procedure for BetweennessCentrality {
+    fn name(&self) -> &str {
+        BETWEENNESS
+    }
+
+    fn parameters(&self) -> Vec<ProcedureParameter> {
+        vec![AlgorithmGraph::config_parameter(vec![
+            Field::new("samplingSize", DataType::Int64, true),
+            Field::new("samplingSeed", DataType::Int64, true),
+        ])]
+    }
+
+    fn output_schema(&self, ctx: &ProcedureContext) -> Result<SchemaRef> {
+        self.output_schema_for(ctx, &[])
+    }
+
+    fn output_schema_for(
+        &self,
+        ctx: &ProcedureContext,
+        args: &[Option<ScalarValue>],
+    ) -> Result<SchemaRef> {
+        let graph =
+            AlgorithmGraph::from_config(BETWEENNESS, ctx, args.first().and_then(Option::as_ref))?;
+        Ok(output_schema(graph.key_type(BETWEENNESS)?, "score"))
+    }
+
+    async fn call(&self, ctx: &ProcedureContext, args: Vec<ScalarValue>) -> Result<RecordBatch> {
+        let known: Vec<Option<ScalarValue>> = args.iter().cloned().map(Some).collect();
+        let schema = self.output_schema_for(ctx, &known)?;
+        let options = BetweennessOptions::try_from_config(args.first())?;
+        let session = ctx.session().cloned().unwrap_or_default();
+        let graph = AlgorithmGraph::from_config(BETWEENNESS, ctx, args.first())?;
+        let Projection { nodes, edges } = graph.read(&session).await?;
+
+        let n = nodes.len();
+        let sources = match options.sampling_size {
+            Some(size) if size < n => sample(n, size, options.sampling_seed),
+            _ => (0..n).collect(),
+        };
+        let mut scores = betweenness(n, &edges, &sources);
+        if sources.len() < n {
+            let scale = n as f64 / sources.len() as f64;
+            scores.iter_mut().for_each(|score| *score *= scale);
+        }
+        let (node_ids, scores) = ranked(schema.field(0).data_type(), &nodes, &scores)?;
+        Ok(RecordBatch::try_new(
+            schema,
+            vec![node_ids, Arc::new(scores)],
+        )?)
+    }
+}
+
+fn invalid(procedure: &str, message: &str) -> GraphError {
+    GraphError::ExecutionError {
+        message: format!("{}: {}", procedure, message),
+        location: snafu::Location::new(file!(), line!(), column!()),
+    }
+}
+
+fn string_option(config: &StructArray, name: &str) -> Option<String> {
+    config_field(config, name).map(|c| c.as_string::<i32>().value(0).to_string())
+}
+
+impl DegreeOptions {
+    /// The direction and weight property given in `config`
+    fn try_from_config(config: Option<&ScalarValue>) -> Result<Self> {
+        let mut options = Self::default();
+        let Some(config) = config_struct(DEGREE, config)? else {
+            return Ok(options);
+        };
+        if let Some(direction) = string_option(config, "direction") {
+            options.direction = match direction.to_lowercase().as_str() {
+                "out" => DegreeDirection::Out,
+                "in" => DegreeDirection::In,
+                "total" => DegreeDirection::Total,
+                _ => {
+                    return Err(invalid(
+                        DEGREE,
+                        &format!(
+                            "direction must be 'out', 'in' or 'total', not '{}'",
+                            direction
+                        ),
+                    ))
+                }
+            };
+        }
+        options.weight = string_option(config, "weightProperty");
+        Ok(options)
+    }
+}
+
+impl BetweennessOptions {
+    /// The source node sampling given in `config`
+    fn try_from_config(config: Option<&ScalarValue>) -> Result<Self> {
+        let mut options = Self::default();
+        let Some(config) = config_struct(BETWEENNESS, config)? else {
+            return Ok(options);
+        };
+        let int =
+            |name: &str| config_field(config, name).map(|c| c.as_primitive::<Int64Type>().value(0));
+        if let Some(size) = int("samplingSize") {
+            if size <= 0 {
+                return Err(invalid(BETWEENNESS, "samplingSize must be positive"));
+            }
+            options.sampling_size = Some(size as usize);
+        }
+        if let Some(seed) = int("samplingSeed") {
+            options.sampling_seed = seed as u64;
+        }
+        Ok(options)
+    }
+}
+
+/// The node keys and their scores, from highest score to lowest and by key among equals
+fn ranked(
+    key_type: &DataType,
+    nodes: &[ScalarValue],
+    scores: &[f64],
+) -> Result<(ArrayRef, Float64Array)> {
+    let mut order: Vec<usize> = (0..nodes.len()).collect();
+    order.sort_by(|&a, &b| {
+        scores[b]
+            .total_cmp(&scores[a])
+            .then_with(|| nodes[a].partial_cmp(&nodes[b]).unwrap_or(Ordering::Equal))
+    });
+    let keys = if order.is_empty() {
+        arrow::array::new_empty_array(key_type)
+    } else {
+        ScalarValue::iter_to_array(order.iter().map(|&node| nodes[node].clone()))?
+    };
+    let scores = Float64Array::from_iter_values(order.iter().map(|&node| scores[node]));
+    Ok((keys, scores))
+}
+
+/// Summed weight of the edges each node counts in `direction`; a self-loop counts once
+/// each way
+fn degrees(
+    node_count: usize,
+    edges: &[(usize, usize)],
+    weights: &[f64],
+    direction: DegreeDirection,
+) -> Vec<f64> {
+    let mut degrees = vec![0.0; node_count];
+    for (&(from, to), &weight) in edges.iter().zip(weights) {
+        if direction != DegreeDirection::In {
+            degrees[from] += weight;
+        }
+        if direction != DegreeDirection::Out {
+            degrees[to] += weight;
+        }
+    }
+    degrees
+}
+
+/// `size` distinct nodes out of `node_count`, drawn by a shuffle seeded with `seed`
+fn sample(node_count: usize, size: usize, seed: u64) -> Vec<usize> {
+    let mut random = SplitMix64::new(seed);
+    let mut nodes: Vec<usize> = (0..node_count).collect();
+    for i in 0..size.min(node_count) {
+        let j = i + (random.next_u64() % (node_count - i) as u64) as usize;
+        nodes.swap(i, j);
+    }
+    nodes.truncate(size);
+    nodes
+}
+
+/// Brandes' betweenness of every node, summing the shortest paths from `sources`
+fn betweenness(node_count: usize, edges: &[(usize, usize)], sources: &[usize]) -> Vec<f64> {
+    let mut adjacency = vec![Vec::new(); node_count];
+    for &(from, to) in edges {
+        if from != to {
+            adjacency[from].push(to);
+        }
+    }
+    for targets in &mut adjacency {
+        targets.sort_unstable();
+        targets.dedup();
+    }
+
+    let mut scores = vec![0.0; node_count];
+    let mut distance = vec![usize::MAX; node_count];
+    // Number of shortest paths from the source, and the share of them through each node
+    let mut paths = vec![0.0f64; node_count];
+    let mut dependency = vec![0.0f64; node_count];
+    let mut predecessors: Vec<Vec<usize>> = vec![Vec::new(); node_count];
+    let mut visited: Vec<usize> = Vec::new();
+    let mut queue = VecDeque::new();
+
+    for &source in sources {
+        for &node in &visited {
+            distance[node] = usize::MAX;
+            paths[node] = 0.0;
+            dependency[node] = 0.0;
+            predecessors[node].clear();
+        }
+        visited.clear();
+
+        distance[source] = 0;
+        paths[source] = 1.0;
+        queue.push_back(source);
+        while let Some(node) = queue.pop_front() {
+            visited.push(node);
+            for &next in &adjacency[node] {
+                if distance[next] == usize::MAX {
+                    distance[next] = distance[node] + 1;
+                    queue.push_back(next);
+                }
+                if distance[next] == distance[node] + 1 {
+                    paths[next] += paths[node];
+                    predecessors[next].push(node);
+                }
+            }
+        }
+
+        // Farthest nodes first, passing their dependency back to their predecessors
+        for &node in visited.iter().rev() {
+            for &predecessor in &predecessors[node] {
+                dependency[predecessor] +=
+                    paths[predecessor] / paths[node] * (1.0 + dependency[node]);
+            }
+            if node != source {
+                scores[node] += dependency[node];
+            }
+        }
+    }
+    scores
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_degrees() {
+        let edges = [(0, 1), (0, 2), (1, 2), (2, 2)];
+        let weights = [1.0, 2.0, 0.5, 4.0];
+        assert_eq!(
+            degrees(3, &edges, &[1.0; 4], DegreeDirection::Out),
+            vec![2.0, 1.0, 1.0]
+        );
+        assert_eq!(
+            degrees(3, &edges, &[1.0; 4], DegreeDirection::In),
+            vec![0.0, 1.0, 3.0]
+        );
+        assert_eq!(
+            degrees(3, &edges, &weights, DegreeDirection::Total),
+            vec![3.0, 1.5, 10.5]
+        );
+    }
+
+    #[test]
+    fn test_betweenness_of_a_path() {
+        // 0 -> 1 -> 2 -> 3: node 1 lies on 0-2 and 0-3, node 2 on 0-3 and 1-3
+        let edges = [(0, 1), (1, 2), (2, 3)];
+        let all: Vec<usize> = (0..4).collect();
+        assert_eq!(betweenness(4, &edges, &all), vec![0.0, 2.0, 2.0, 0.0]);
+    }
+
+    #[test]
+    fn test_betweenness_splits_between_shortest_paths() {
+        // Two shortest paths from 0 to 3, through 1 and through 2; 0 -> 1 twice
+        let edges = [(0, 1), (0, 1), (0, 2), (1, 3), (2, 3), (3, 3)];
+        let all: Vec<usize> = (0..4).collect();
+        assert_eq!(betweenness(4, &edges, &all), vec![0.0, 0.5, 0.5, 0.0]);
+        // From node 0 alone, every path counted is one from 0
+        assert_eq!(betweenness(4, &edges, &[0]), vec![0.0, 0.5, 0.5, 0.0]);
+        assert_eq!(betweenness(4, &edges, &[1]), vec![0.0; 4]);
+    }
+
+    #[test]
+    fn test_sample_is_seeded() {
+        let first = sample(100, 10, 42);
+        assert_eq!(first.len(), 10);
+        assert_eq!(first, sample(100, 10, 42));
+        assert_ne!(first, sample(100, 10, 43));
+        let mut distinct = first.clone();
+        distinct.sort_unstable();
+        distinct.dedup();
+        assert_eq!(distinct.len(), 10);
+        assert_eq!(sample(3, 5, 0).len(), 3);
+    }
+}
@@ -0,0 +1,504 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Community detection, the `graph.louvain` and `graph.labelPropagation` procedures
+//!
+//! Both run over an in-memory [`Projection`] of the edge tables, read as an undirected
+//! graph in which parallel relationships add up to a heavier edge. Louvain moves nodes
+//! between communities while that raises the modularity of the partition, then merges
+//! each community into a single node and repeats on the smaller graph. Label
+//! propagation has every node adopt the label most of its neighbors carry, until no
+//! label changes, updating nodes that share no edge at the same time. Nodes are visited
+//! in a fixed order and ties go to the smallest community, so results are
+//! deterministic.
+//!
+//! Communities are numbered from 0 in the order of their smallest node key, and every
+//! row carries the modularity of the whole partition.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use arrow::array::{AsArray, Float64Array, RecordBatch};
+use arrow::datatypes::{Float64Type, Int64Type};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use async_trait::async_trait;
+use datafusion::scalar::ScalarValue;
+
+use super::projection::AlgorithmGraph;
+use super::{config_field, config_struct, numbered_by_key, Projection};
+use crate::error::{GraphError, Result};
+use crate::procedures::{Procedure, ProcedureContext, ProcedureParameter};
+
+/// How communities are found
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CommunityAlgorithm {
+    /// Greedy modularity optimization over successively merged graphs
+    Louvain,
+    /// Nodes adopting the most common label among their neighbors
+    LabelPropagation,
+}
+
+/// `graph.louvain([config])` and `graph.labelPropagation([config])`: the `nodeId`,
+/// `communityId` and partition `modularity` of every node of the projection
+#[derive(Debug)]
+pub(crate) struct CommunityDetection {
+    algorithm: CommunityAlgorithm,
+}
+
+impl CommunityDetection {
+    pub(crate) fn new(algorithm: CommunityAlgorithm) -> Self {
+        Self { algorithm }
+    }
+}
+
+/// Options of one run, from the `config` map
+#[derive(Debug, Clone, PartialEq)]
+struct CommunityOptions {
+    /// Passes over the nodes, per level for Louvain
+    max_iterations: usize,
+    /// Louvain levels, each merging the communities of the last
+    max_levels: usize,
+    /// Least modularity gain for Louvain to go on
+    tolerance: f64,
+}
+
+impl Default for CommunityOptions {
+    fn default() -> Self {
+        Self {
+            max_iterations: 10,
+            max_levels: 10,
+            tolerance: 1e-7,
+        }
+    }
+}
+
+#[async_trait]
+impl Procedure for CommunityDetection {
+    fn name(&self) -> &str {
+        match self.algorithm {
+            CommunityAlgorithm::Louvain => "graph.louvain",
+            CommunityAlgorithm::LabelPropagation => "graph.labelPropagation",
+        }
+    }
+
+    fn parameters(&self) -> Vec<ProcedureParameter> {
+        let mut fields = vec![Field::new("maxIterations", DataType::Int64, true)];
+        if self.algorithm == CommunityAlgorithm::Louvain {
+            fields.push(Field::new("maxLevels", DataType::Int64, true));
+            fields.push(Field::new("tolerance", DataType::Float64, true));
+        }
+        vec![AlgorithmGraph::config_parameter(fields)]
+    }
+
+    fn output_schema(&self, ctx: &ProcedureContext) -> Result<SchemaRef> {
+        self.output_schema_for(ctx, &[])
+    }
+
+    fn output_schema_for(
+        &self,
+        ctx: &ProcedureContext,
+        args: &[Option<ScalarValue>],
+    ) -> Result<SchemaRef> {
+        let input =
+            AlgorithmGraph::from_config(self.name(), ctx, args.first().and_then(Option::as_ref))?;
+        Ok(Arc::new(Schema::new(vec![
+            Field::new("nodeId", input.key_type(self.name())?, false),
+            Field::new("communityId", DataType::Int64, false),
+            Field::new("modularity", DataType::Float64, false),
+        ])))
+    }
+
+    async fn call(&self, ctx: &ProcedureContext, args: Vec<ScalarValue>) -> Result<RecordBatch> {
+        let known: Vec<Option<ScalarValue>> = args.iter().cloned().map(Some).collect();
+        let schema = self.output_schema_for(ctx, &known)?;
+        let options = self.options(args.first())?;
+        let session = ctx.session().cloned().unwrap_or_default();
+        let input = AlgorithmGraph::from_config(self.name(), ctx, args.first())?;
+        let Projection { nodes, edges } = input.read(&session).await?;
+
+        let graph = WeightedGraph::undirected(nodes.len(), &edges);
+        let labels = match self.algorithm {
+            CommunityAlgorithm::Louvain => louvain(&graph, &options),
+            CommunityAlgorithm::LabelPropagation => label_propagation(&graph, &options),
+        };
+        let modularity = graph.modularity(&labels);
+        let (node_ids, community_ids) =
+            numbered_by_key(schema.field(0).data_type(), &nodes, &labels)?;
+        let modularity = Float64Array::from_value(modularity, nodes.len());
+        Ok(RecordBatch::try_new(
+            schema,
+            vec![node_ids, Arc::new(community_ids), Arc::new(modularity)],
+        )?)
+    }
+}
+
+impl CommunityDetection {
+    /// The iteration limits and tolerance given in `config`
+    fn options(&self, config: Option<&ScalarValue>) -> Result<CommunityOptions> {
+        let mut options = CommunityOptions::default();
+        let Some(config) = config_struct(self.name(), config)? else {
+            return Ok(options);
+        };
+        let field = |name: &str| config_field(config, name);
+        let positive = |name: &str| match field(name) {
+            None => Ok(None),
+            Some(value) => match value.as_primitive::<Int64Type>().value(0) {
+                n if n > 0 => Ok(Some(n as usize)),
+                _ => Err(self.invalid(&format!("{} must be positive", name))),
+            },
+        };
+
+        if let Some(iterations) = positive("maxIterations")? {
+            options.max_iterations = iterations;
+        }
+        if let Some(levels) = positive("maxLevels")? {
+            options.max_levels = levels;
+        }
+        if let Some(tolerance) = field("tolerance") {
+            options.tolerance = tolerance.as_primitive::<Float64Type>().value(0);
+            if options.tolerance.is_nan() || options.tolerance < 0.0 {
+                return Err(self.invalid("tolerance must not be negative"));
+            }
+        }
+        Ok(options)
+    }
+
+    fn invalid(&self, message: &str) -> GraphError {
+        GraphError::ExecutionError {
+            message: format!("{}: {}", self.name(), message),
+            location: snafu::Location::new(file!(), line!(), column!()),
+        }
+    }
+}
+
+/// An undirected graph with weighted edges, as a symmetric adjacency matrix
+///
+/// A self-loop counts twice towards the degree of its node, as it would as two
+/// directed edges, so that merging a community into one node keeps its degree.
+#[derive(Debug, Clone, PartialEq)]
+struct WeightedGraph {
+    /// Neighbors of each node and the weight of the edge to each, by neighbor
+    adjacency: Vec<Vec<(usize, f64)>>,
+}
+
+impl WeightedGraph {
+    /// The graph of `edges` ignoring their direction, each of weight one
+    fn undirected(node_count: usize, edges: &[(usize, usize)]) -> Self {
+        Self::from_weights(
+            node_count,
+            edges.iter().map(|&(from, to)| ((from, to), 1.0)),
+        )
+    }
+
+    fn from_weights(
+        node_count: usize,
+        edges: impl IntoIterator<Item = ((usize, usize), f64)>,
+    ) -> Self {
+        let mut weights: Vec<HashMap<usize, f64>> = vec![HashMap::new(); node_count];
+        for ((from, to), weight) in edges {
+            *weights[from].entry(to).or_default() += weight;
+            *weights[to].entry(from).or_default() += weight;
+        }
+        let adjacency = weights
+            .into_iter()
+            .map(|neighbors| {
+                let mut neighbors: Vec<(usize, f64)> = neighbors.into_iter().collect();
+                neighbors.sort_by_key(|&(neighbor, _)| neighbor);
+                neighbors
+            })
+            .collect();
+        Self { adjacency }
+    }
+
+    fn node_count(&self) -> usize {
+        self.adjacency.len()
+    }
+
+    fn degree(&self, node: usize) -> f64 {
+        self.adjacency[node].iter().map(|&(_, weight)| weight).sum()
+    }
+
+    /// Newman's modularity of the partition of the nodes into `labels`, 0 without edges
+    fn modularity(&self, labels: &[usize]) -> f64 {
+        let total: f64 = (0..self.node_count()).map(|node| self.degree(node)).sum();
+        if total == 0.0 {
+            return 0.0;
+        }
+        let mut internal = vec![0.0; self.node_count()];
+        let mut degrees = vec![0.0; self.node_count()];
+        for (node, neighbors) in self.adjacency.iter().enumerate() {
+            for &(neighbor, weight) in neighbors {
+                degrees[labels[node]] += weight;
+                if labels[neighbor] == labels[node] {
+                    internal[labels[node]] += weight;
+                }
+            }
+        }
+        internal
+            .iter()
+            .zip(&degrees)
+            .map(|(internal, degree)| internal / total - (degree / total).powi(2))
+            .sum()
+    }
+
+    /// The graph with one node per community of `labels`, numbered densely
+    fn merge(&self, labels: &[usize]) -> Self {
+        let count = labels.iter().max().map_or(0, |max| max + 1);
+        // Each edge appears under both of its ends; halving keeps the weights
+        let edges = self
+            .adjacency
+            .iter()
+            .enumerate()
+            .flat_map(|(node, neighbors)| {
+                neighbors.iter().map(move |&(neighbor, weight)| {
+                    ((labels[node], labels[neighbor]), weight / 2.0)
+                })
+            });
+        Self::from_weights(count, edges)
+    }
+}
+
+/// Labels numbered densely from 0 in the order they first appear
+fn renumber(labels: &[usize]) -> Vec<usize> {
+    let mut numbers: HashMap<usize, usize> = HashMap::new();
+    labels
+        .iter()
+        .map(|&label| {
+            let next = numbers.len();
+            *numbers.entry(label).or_insert(next)
+        })
+        .collect()
+}
+
+/// Louvain communities of the nodes of `graph`, numbered densely
+fn louvain(graph: &WeightedGraph, options: &CommunityOptions) -> Vec<usize> {
+    let mut labels: Vec<usize> = (0..graph.node_count()).collect();
+    let mut level = graph.clone();
+    for _ in 0..options.max_levels {
+        let before = level.modularity(&(0..level.node_count()).collect::<Vec<_>>());
+        let communities = local_moves(&level, options);
+        let gain = level.modularity(&communities) - before;
+        let merged = communities.iter().max().map_or(0, |max| max + 1);
+        if merged == level.node_count() || gain < options.tolerance {
+            break;
+        }
+        for label in labels.iter_mut() {
+            *label = communities[*label];
+        }
+        level = level.merge(&communities);
+    }
+    labels
+}
+
+/// Move single nodes to the neighboring community that raises modularity most, pass
+/// after pass until none moves; communities renumbered densely
+fn local_moves(graph: &WeightedGraph, options: &CommunityOptions) -> Vec<usize> {
+    let n = graph.node_count();
+    let degrees: Vec<f64> = (0..n).map(|node| graph.degree(node)).collect();
+    let total: f64 = degrees.iter().sum();
+    let mut community: Vec<usize> = (0..n).collect();
+    if total == 0.0 {
+        return community;
+    }
+    // Summed degree of the nodes of each community
+    let mut community_degree = degrees.clone();
+    // Weight from the current node to each community, and the communities touched
+    let mut links = vec![0.0; n];
+    let mut touched: Vec<usize> = Vec::new();
+
+    for _ in 0..options.max_iterations {
+        let mut moved = false;
+        for node in 0..n {
+            let current = community[node];
+            for &(neighbor, weight) in &graph.adjacency[node] {
+                if neighbor == node {
+                    continue;
+                }
+                let target = community[neighbor];
+                if links[target] == 0.0 {
+                    touched.push(target);
+                }
+                links[target] += weight;
+            }
+            community_degree[current] -= degrees[node];
+
+            // Gain of joining a community, up to a factor common to all
+            let gain = |target: usize, links: &[f64]| {
+                links[target] - community_degree[target] * degrees[node] / total
+            };
+            let mut best = current;
+            let mut best_gain = gain(current, &links);
+            touched.sort_unstable();
+            for &target in &touched {
+                let target_gain = gain(target, &links);
+                if target_gain > best_gain + 1e-12 {
+                    best = target;
+                    best_gain = target_gain;
+                }
+            }
+
+            community_degree[best] += degrees[node];
+            if best != current {
+                community[node] = best;
+                moved = true;
+            }
+            for target in touched.drain(..) {
+                links[target] = 0.0;
+            }
+        }
+        if !moved {
+            break;
+        }
+    }
+    renumber(&community)
+}
+
+/// Label propagation communities of the nodes of `graph`, numbered densely
+///
+/// Semi-synchronous: nodes are split into classes without an edge inside, by a greedy
+/// coloring, and the nodes of a class all update from the labels of the last. A node
+/// keeps its label while no other is more common among its neighbors, and otherwise
+/// takes the smallest of the most common ones.
+fn label_propagation(graph: &WeightedGraph, options: &CommunityOptions) -> Vec<usize> {
+    let n = graph.node_count();
+    let mut color = vec![usize::MAX; n];
+    let mut classes: Vec<Vec<usize>> = Vec::new();
+    for node in 0..n {
+        let taken: Vec<usize> = graph.adjacency[node]
+            .iter()
+            .map(|&(neighbor, _)| color[neighbor])
+            .collect();
+        color[node] = (0..).find(|c| !taken.contains(c)).unwrap_or_default();
+        if color[node] == classes.len() {
+            classes.push(Vec::new());
+        }
+        classes[color[node]].push(node);
+    }
+
+    let mut labels: Vec<usize> = (0..n).collect();
+    let mut votes = vec![0.0; n];
+    let mut touched: Vec<usize> = Vec::new();
+    let mut updates: Vec<(usize, usize)> = Vec::new();
+    for _ in 0..options.max_iterations {
+        let mut changed = false;
+        for class in &classes {
+            for &node in class {
+                for &(neighbor, weight) in &graph.adjacency[node] {
+                    let label = labels[neighbor];
+                    if votes[label] == 0.0 {
+                        touched.push(label);
+                    }
+                    votes[label] += weight;
+                }
+                let mut best = labels[node];
+                let mut best_votes = votes[best];
+                touched.sort_unstable();
+                for &label in &touched {
+                    if votes[label] > best_votes {
+                        best = label;
+                        best_votes = votes[label];
+                    }
+                }
+                if best != labels[node] {
+                    updates.push((node, best));
+                }
+                for label in touched.drain(..) {
+                    votes[label] = 0.0;
+                }
+            }
+            changed |= !updates.is_empty();
+            for (node, label) in updates.drain(..) {
+                labels[node] = label;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+    renumber(&labels)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two triangles, 0-1-2 and 3-4-5, joined by the edge 2-3
+    fn barbell() -> WeightedGraph {
+        WeightedGraph::undirected(6, &[(0, 1), (1, 2), (2, 0), (3, 4), (4, 5), (5, 3), (2, 3)])
+    }
+
+    #[test]
+    fn test_modularity() {
+        let graph = barbell();
+        assert_eq!(graph.modularity(&[0; 6]), 0.0);
+        // Each triangle holds 3 of the 7 edges and half of the degree
+        let expected = 2.0 * (6.0 / 14.0 - 0.25);
+        assert!((graph.modularity(&[0, 0, 0, 1, 1, 1]) - expected).abs() < 1e-12);
+        assert_eq!(WeightedGraph::undirected(2, &[]).modularity(&[0, 1]), 0.0);
+    }
+
+    #[test]
+    fn test_merge_keeps_degrees_and_modularity() {
+        let graph = barbell();
+        let labels = [0, 0, 0, 1, 1, 1];
+        let merged = graph.merge(&labels);
+        assert_eq!(
+            merged.adjacency,
+            vec![vec![(0, 6.0), (1, 1.0)], vec![(0, 1.0), (1, 6.0)]]
+        );
+        assert_eq!(merged.degree(0), 7.0);
+        assert!((merged.modularity(&[0, 1]) - graph.modularity(&labels)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_louvain_splits_the_barbell() {
+        let labels = louvain(&barbell(), &CommunityOptions::default());
+        assert_eq!(labels, vec![0, 0, 0, 1, 1, 1]);
+        // Isolated nodes stay alone
+        let graph = WeightedGraph::undirected(3, &[(0, 1)]);
+        assert_eq!(louvain(&graph, &CommunityOptions::default()), vec![0, 0, 1]);
+    }
+
+    #[test]
+    fn test_louvain_merges_levels() {
+        // A ring of 20 triangles: pairs of neighboring triangles make a better
+        // partition than single ones, which only a later level can find
+        let mut edges = Vec::new();
+        for t in 0..20 {
+            let base = 3 * t;
+            edges.extend([(base, base + 1), (base + 1, base + 2), (base + 2, base)]);
+            edges.push((base + 2, (base + 3) % 60));
+        }
+        let graph = WeightedGraph::undirected(60, &edges);
+        let triangles: Vec<usize> = (0..60).map(|node| node / 3).collect();
+        let options = CommunityOptions {
+            max_levels: 1,
+            ..Default::default()
+        };
+        assert_eq!(louvain(&graph, &options), triangles);
+
+        let labels = louvain(&graph, &CommunityOptions::default());
+        assert_eq!(labels, (0..60).map(|node| node / 6).collect::<Vec<_>>());
+        assert!(graph.modularity(&labels) > graph.modularity(&triangles));
+    }
+
+    #[test]
+    fn test_label_propagation() {
+        let labels = label_propagation(&barbell(), &CommunityOptions::default());
+        assert_eq!(labels, vec![0, 0, 0, 1, 1, 1]);
+        // Two 4-cliques joined by an edge
+        let mut edges = Vec::new();
+        for base in [0, 4] {
+            for a in base..base + 4 {
+                edges.extend((a + 1..base + 4).map(|b| (a, b)));
+            }
+        }
+        edges.push((3, 4));
+        let graph = WeightedGraph::undirected(8, &edges);
+        let labels = label_propagation(&graph, &CommunityOptions::default());
+        assert_eq!(labels, vec![0, 0, 0, 0, 1, 1, 1, 1]);
+        let graph = WeightedGraph::undirected(4, &[(0, 1), (2, 3)]);
+        let labels = label_propagation(&graph, &CommunityOptions::default());
+        assert_eq!(labels, vec![0, 0, 1, 1]);
+    }
+}
@@ -0,0 +1,439 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Connected components, the `graph.wcc` and `graph.scc` procedures
+//!
+//! Components are computed over an in-memory [`Projection`] of the edge tables: weakly
+//! connected components with a union-find over the edges, strongly connected ones with
+//! an iterative Tarjan search, both linear in the number of edges. Components are
+//! numbered from 0 in the order of their smallest node key.
+//!
+//! The components can also be written back: `writeProperty` stores each node's
+//! component as a property of the Lance dataset of `writeLabel`, replacing the values
+//! of an earlier run but never a key or other column, and `writeDataset` writes the
+//! `nodeId`/`componentId` rows to a Lance dataset of their own. Catalogs opened on those datasets afterwards read the new version.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use arrow::array::{Array, ArrayRef, AsArray, Int64Array, RecordBatch};
+use arrow::compute::cast;
+use arrow::datatypes::Int64Type;
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use async_trait::async_trait;
+use datafusion::datasource::source_as_provider;
+use datafusion::scalar::ScalarValue;
+use lance::dataset::transaction::{Operation, Transaction};
+use lance::dataset::{
+    BatchUDF, CommitBuilder, InsertBuilder, NewColumnTransform, WriteMode, WriteParams,
+};
+use lance::Dataset;
+
+use super::projection::AlgorithmGraph;
+use super::{config_field, config_struct, numbered_by_key, Projection};
+use crate::error::{GraphError, Result};
+use crate::lance_table::LanceTable;
+use crate::procedures::{Procedure, ProcedureContext, ProcedureParameter};
+
+/// Metadata key marking the columns written by `writeProperty`, the only ones a later
+/// run may replace
+const COMPONENT_METADATA_KEY: &str = "lance-graph:component";
+
+/// Which nodes share a component
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Connectivity {
+    /// Nodes connected ignoring the direction of relationships
+    Weak,
+    /// Nodes that reach each other following the direction of relationships
+    Strong,
+}
+
+/// `graph.wcc([config])` and `graph.scc([config])`: the `nodeId` and `componentId` of
+/// every node of the projection
+#[derive(Debug)]
+pub(crate) struct ConnectedComponents {
+    connectivity: Connectivity,
+}
+
+impl ConnectedComponents {
+    pub(crate) fn new(connectivity: Connectivity) -> Self {
+        Self { connectivity }
+    }
+}
+
+/// Options of one run, from the `config` map
+#[derive(Debug, Default, Clone, PartialEq)]
+struct ComponentOptions {
+    write_label: Option<String>,
+    write_property: Option<String>,
+    write_dataset: Option<String>,
+}
+
+#[async_trait]
+impl Procedure for ConnectedComponents {
+    fn name(&self) -> &str {
+        match self.connectivity {
+            Connectivity::Weak => "graph.wcc",
+            Connectivity::Strong => "graph.scc",
+        }
+    }
+
+    fn parameters(&self) -> Vec<ProcedureParameter> {
+        vec![AlgorithmGraph::config_parameter(vec![
+            Field::new("writeLabel", DataType::Utf8, true),
+            Field::new("writeProperty", DataType::Utf8, true),
+            Field::new("writeDataset", DataType::Utf8, true),
+        ])]
+    }
+
+    fn output_schema(&self, ctx: &ProcedureContext) -> Result<SchemaRef> {
+        self.output_schema_for(ctx, &[])
+    }
+
+    fn output_schema_for(
+        &self,
+        ctx: &ProcedureContext,
+        args: &[Option<ScalarValue>],
+    ) -> Result<SchemaRef> {
+        let graph =
+            AlgorithmGraph::from_config(self.name(), ctx, args.first().and_then(Option::as_ref))?;
+        Ok(Arc::new(Schema::new(vec![
+            Field::new("nodeId", graph.key_type(self.name())?, false),
+            Field::new("componentId", DataType::Int64, false),
+        ])))
+    }
+
+    async fn call(&self, ctx: &ProcedureContext, args: Vec<ScalarValue>) -> Result<RecordBatch> {
+        let known: Vec<Option<ScalarValue>> = args.iter().cloned().map(Some).collect();
+        let schema = self.output_schema_for(ctx, &known)?;
+        let options = self.options(args.first())?;
+        let session = ctx.session().cloned().unwrap_or_default();
+        let graph = AlgorithmGraph::from_config(self.name(), ctx, args.first())?;
+        let Projection { nodes, edges } = graph.read(&session).await?;
+
+        let labels = match self.connectivity {
+            Connectivity::Weak => weak_components(nodes.len(), &edges),
+            Connectivity::Strong => strong_components(nodes.len(), &edges),
+        };
+        let (node_ids, component_ids) =
+            numbered_by_key(schema.field(0).data_type(), &nodes, &labels)?;
+        let batch = RecordBatch::try_new(schema, vec![node_ids, Arc::new(component_ids)])?;
+
+        if let Some(property) = &options.write_property {
+            self.write_property(ctx, &options, property, &batch).await?;
+        }
+        if let Some(uri) = &options.write_dataset {
+            let params = WriteParams {
+                mode: WriteMode::Overwrite,
+                ..Default::default()
+            };
+            InsertBuilder::new(uri.as_str())
+                .with_params(&params)
+                .execute(vec![batch.clone()])
+                .await?;
+        }
+        Ok(batch)
+    }
+}
+
+impl ConnectedComponents {
+    /// Where `config` asks for the components to be written
+    fn options(&self, config: Option<&ScalarValue>) -> Result<ComponentOptions> {
+        let mut options = ComponentOptions::default();
+        let Some(config) = config_struct(self.name(), config)? else {
+            return Ok(options);
+        };
+        let string = |name: &str| {
+            config_field(config, name).map(|c| c.as_string::<i32>().value(0).to_string())
+        };
+
+        options.write_label = string("writeLabel");
+        options.write_property = string("writeProperty");
+        options.write_dataset = string("writeDataset");
+        if options.write_property.is_some() != options.write_label.is_some() {
+            return Err(self.invalid("writeProperty and writeLabel must be given together"));
+        }
+        Ok(options)
+    }
+
+    /// Store the component of every node of `batch` as `property` of the nodes of the
+    /// write label, replacing the column behind the property if an earlier run wrote it
+    ///
+    /// Key columns and columns the procedures did not write are never overwritten. A
+    /// replaced column is swapped for the new one in a single commit, so readers see
+    /// either the old components or the new ones.
+    async fn write_property(
+        &self,
+        ctx: &ProcedureContext,
+        options: &ComponentOptions,
+        property: &str,
+        batch: &RecordBatch,
+    ) -> Result<()> {
+        let label = options.write_label.as_deref().unwrap_or_default();
+        let mapping = ctx
+            .config()
+            .get_node_mapping(label)
+            .ok_or_else(|| self.invalid(&format!("unknown node label '{}'", label)))?;
+        let [key] = mapping.id_fields()[..] else {
+            return Err(self.invalid(&format!(
+                "cannot write to '{}', whose nodes are keyed by several columns",
+                label
+            )));
+        };
+        let column = mapping.column_for(property);
+        if column == key {
+            return Err(self.invalid(&format!(
+                "cannot write to '{}', the key column of '{}'",
+                column, label
+            )));
+        }
+        let source = ctx.catalog().and_then(|catalog| catalog.node_source(label));
+        let provider = source
+            .map(|source| source_as_provider(&source))
+            .transpose()?;
+        let dataset: Arc<Dataset> = provider
+            .as_ref()
+            .and_then(|provider| provider.as_any().downcast_ref::<LanceTable>())
+            .map(LanceTable::dataset)
+            .ok_or_else(|| {
+                self.invalid(&format!(
+                    "nodes of '{}' are not stored in a Lance dataset",
+                    label
+                ))
+            })?;
+
+        // Written over the latest version, which an earlier run may have committed
+        let mut dataset = Dataset::clone(&dataset);
+        dataset.checkout_latest().await?;
+        let replaced = dataset.schema().field(column);
+        if replaced.is_some_and(|field| !field.metadata.contains_key(COMPONENT_METADATA_KEY)) {
+            return Err(self.invalid(&format!(
+                "cannot write to '{}', a column of '{}' not written by a components procedure",
+                column, label
+            )));
+        }
+        let id_type = dataset
+            .schema()
+            .field(key)
+            .map(|field| field.data_type())
+            .ok_or_else(|| self.invalid(&format!("'{}' has no column '{}'", label, key)))?;
+        let keys: ArrayRef = cast(batch.column(0), &id_type)?;
+        let components = batch.column(1).as_primitive::<Int64Type>();
+        let by_key = (0..keys.len())
+            .map(|i| Ok((ScalarValue::try_from_array(&keys, i)?, components.value(i))))
+            .collect::<Result<HashMap<_, _>>>()?;
+
+        // Added row by row from the key column rather than merged, since merging leaves
+        // the nodes without a component null, which Lance cannot write for integers. A
+        // replacement is staged under another name and renamed when it is committed.
+        let staged = match replaced {
+            Some(_) => format!("_{}_{}", column, dataset.manifest().version + 1),
+            None => column.to_string(),
+        };
+        let marker = HashMap::from([(COMPONENT_METADATA_KEY.to_string(), self.name().to_string())]);
+        let output_schema = Arc::new(Schema::new(vec![Field::new(
+            &staged,
+            DataType::Int64,
+            true,
+        )
+        .with_metadata(marker)]));
+        let schema = output_schema.clone();
+        let by_key = Arc::new(by_key);
+        let transform = || {
+            let (by_key, schema) = (by_key.clone(), schema.clone());
+            let mapper = move |rows: &RecordBatch| -> lance::Result<RecordBatch> {
+                let stored = rows.column(0);
+                let written = (0..stored.len())
+                    .map(|i| {
+                        Ok(by_key
+                            .get(&ScalarValue::try_from_array(stored, i)?)
+                            .copied())
+                    })
+                    .collect::<datafusion::common::Result<Int64Array>>()?;
+                Ok(RecordBatch::try_new(
+                    schema.clone(),
+                    vec![Arc::new(written)],
+                )?)
+            };
+            NewColumnTransform::BatchUDF(BatchUDF {
+                mapper: Box::new(mapper),
+                output_schema: output_schema.clone(),
+                result_checkpoint: None,
+            })
+        };
+        let read = Some(vec![key.to_string()]);
+        if replaced.is_none() {
+            dataset.add_columns(transform(), read, None).await?;
+            return Ok(());
+        }
+
+        let mut fragments = Vec::new();
+        let mut schema = None;
+        for fragment in dataset.get_fragments() {
+            let (written, merged) = fragment
+                .add_columns(transform(), read.clone(), None)
+                .await?;
+            fragments.push(written);
+            schema = Some(merged);
+        }
+        // A dataset without fragments has no values to replace
+        let Some(mut schema) = schema else {
+            return Ok(());
+        };
+        schema.fields.retain(|field| field.name != column);
+        for field in schema
+            .fields
+            .iter_mut()
+            .filter(|field| field.name == staged)
+        {
+            field.name = column.to_string();
+        }
+        let transaction = Transaction::new(
+            dataset.manifest().version,
+            Operation::Merge { fragments, schema },
+            None,
+            None,
+        );
+        CommitBuilder::new(Arc::new(dataset))
+            .execute(transaction)
+            .await?;
+        Ok(())
+    }
+
+    fn invalid(&self, message: &str) -> GraphError {
+        GraphError::ExecutionError {
+            message: format!("{}: {}", self.name(), message),
+            location: snafu::Location::new(file!(), line!(), column!()),
+        }
+    }
+}
+
+/// Component of each node ignoring edge direction, as the index of a representative
+fn weak_components(node_count: usize, edges: &[(usize, usize)]) -> Vec<usize> {
+    let mut parent: Vec<usize> = (0..node_count).collect();
+    fn root(parent: &mut [usize], mut node: usize) -> usize {
+        while parent[node] != node {
+            parent[node] = parent[parent[node]];
+            node = parent[node];
+        }
+        node
+    }
+    for &(from, to) in edges {
+        let (a, b) = (root(&mut parent, from), root(&mut parent, to));
+        if a != b {
+            parent[a.max(b)] = a.min(b);
+        }
+    }
+    (0..node_count)
+        .map(|node| root(&mut parent, node))
+        .collect()
+}
+
+/// Strongly connected component of each node, as the index of its component in the
+/// order Tarjan's algorithm completes them
+fn strong_components(node_count: usize, edges: &[(usize, usize)]) -> Vec<usize> {
+    // Outgoing edges of each node, as ranges of `targets`
+    let mut offsets = vec![0usize; node_count + 1];
+    for &(from, _) in edges {
+        offsets[from + 1] += 1;
+    }
+    for node in 0..node_count {
+        offsets[node + 1] += offsets[node];
+    }
+    let mut targets = vec![0usize; edges.len()];
+    let mut filled = offsets.clone();
+    for &(from, to) in edges {
+        targets[filled[from]] = to;
+        filled[from] += 1;
+    }
+
+    const UNVISITED: usize = usize::MAX;
+    let mut index = vec![UNVISITED; node_count];
+    let mut low = vec![0usize; node_count];
+    let mut on_stack = vec![false; node_count];
+    let mut stack: Vec<usize> = Vec::new();
+    let mut component = vec![0usize; node_count];
+    let mut components = 0;
+    let mut visited = 0;
+    // Search stack of (node, next edge to follow)
+    let mut search: Vec<(usize, usize)> = Vec::new();
+
+    for start in 0..node_count {
+        if index[start] != UNVISITED {
+            continue;
+        }
+        search.push((start, offsets[start]));
+        index[start] = visited;
+        low[start] = visited;
+        visited += 1;
+        stack.push(start);
+        on_stack[start] = true;
+
+        while let Some((node, next)) = search.last().copied() {
+            if next < offsets[node + 1] {
+                let target = targets[next];
+                if let Some(top) = search.last_mut() {
+                    top.1 += 1;
+                }
+                if index[target] == UNVISITED {
+                    index[target] = visited;
+                    low[target] = visited;
+                    visited += 1;
+                    stack.push(target);
+                    on_stack[target] = true;
+                    search.push((target, offsets[target]));
+                } else if on_stack[target] {
+                    low[node] = low[node].min(index[target]);
+                }
+                continue;
+            }
+            search.pop();
+            if let Some(&(parent, _)) = search.last() {
+                low[parent] = low[parent].min(low[node]);
+            }
+            if low[node] == index[node] {
+                while let Some(member) = stack.pop() {
+                    on_stack[member] = false;
+                    component[member] = components;
+                    if member == node {
+                        break;
+                    }
+                }
+                components += 1;
+            }
+        }
+    }
+    component
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Nodes grouped by component, each group and the groups in order
+    fn groups(labels: Vec<usize>) -> Vec<Vec<usize>> {
+        let mut groups: Vec<Vec<usize>> = Vec::new();
+        for (node, &label) in labels.iter().enumerate() {
+            match groups.iter_mut().find(|g| labels[g[0]] == label) {
+                Some(group) => group.push(node),
+                None => groups.push(vec![node]),
+            }
+        }
+        groups
+    }
+
+    #[test]
+    fn test_weak_components_ignore_direction() {
+        let labels = weak_components(6, &[(0, 1), (2, 1), (3, 4)]);
+        assert_eq!(groups(labels), vec![vec![0, 1, 2], vec![3, 4], vec![5]]);
+    }
+
+    #[test]
+    fn test_strong_components_follow_direction() {
+        // 0 -> 1 -> 2 -> 0 is a cycle; 2 -> 3 -> 4 -> 3 leads to a second one
+        let labels = strong_components(6, &[(0, 1), (1, 2), (2, 0), (2, 3), (3, 4), (4, 3)]);
+        assert_eq!(groups(labels), vec![vec![0, 1, 2], vec![3, 4], vec![5]]);
+        let labels = strong_components(3, &[(0, 1), (1, 2)]);
+        assert_eq!(groups(labels), vec![vec![0], vec![1], vec![2]]);
+    }
+}
@@ -0,0 +1,422 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Graph algorithms
+//!
+//! The algorithm procedures of [`crate::procedures`], such as `graph.pageRank` and
+//! `graph.bfs`, the in-memory [`projection::GraphProjection`]s they can run on, and
+//! [`subgraph::SubgraphQuery`], which extracts the part of a graph around some nodes.
+//! The algorithms read the edges of relationship tables through the helpers of this
+//! module: the edge tables of the types a call names, the node keys those tables
+//! share, and their edges read into a dense in-memory projection.
+
+mod centrality;
+mod communities;
+mod components;
+mod page_rank;
+pub mod projection;
+mod random_walk;
+mod shortest_path;
+mod similarity;
+pub mod subgraph;
+mod topological;
+mod traversal;
+mod triangles;
+
+pub(crate) use centrality::{BetweennessCentrality, DegreeCentrality};
+pub(crate) use communities::{CommunityAlgorithm, CommunityDetection};
+pub(crate) use components::{ConnectedComponents, Connectivity};
+pub(crate) use page_rank::PageRank;
+pub(crate) use random_walk::RandomWalk;
+pub(crate) use shortest_path::DijkstraShortestPath;
+pub(crate) use similarity::NodeSimilarity;
+pub(crate) use topological::{OrderOutput, TopologicalOrder};
+pub(crate) use traversal::{GraphTraversal, TraversalOrder};
+pub(crate) use triangles::{TriangleOutput, Triangles};
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use arrow::array::{Array, ArrayRef, AsArray, Int64Array, StructArray};
+use arrow::datatypes::Float64Type;
+use arrow_schema::DataType;
+use datafusion::dataframe::DataFrame;
+use datafusion::execution::context::SessionContext;
+use datafusion::logical_expr::{cast, col, lit, LogicalPlan};
+use datafusion::scalar::ScalarValue;
+
+use crate::datafusion_planner::DataFusionPlanner;
+use crate::error::{GraphError, Result};
+use crate::procedures::ProcedureContext;
+use crate::source_catalog::GraphSourceCatalog;
+
+/// The edges of one relationship type, read from `from` to `to`
+pub(crate) struct EdgeTable {
+    pub(crate) rel_type: String,
+    pub(crate) plan: LogicalPlan,
+    pub(crate) from: String,
+    pub(crate) to: String,
+}
+
+/// One edge read from an [`EdgeTable`]
+pub(crate) struct Edge {
+    pub(crate) from: ScalarValue,
+    pub(crate) to: ScalarValue,
+    pub(crate) weight: Option<f64>,
+}
+
+/// The catalog of the graph's tables `procedure` reads its edges from
+fn catalog(procedure: &str, ctx: &ProcedureContext) -> Result<Arc<dyn GraphSourceCatalog>> {
+    ctx.catalog().cloned().ok_or_else(|| GraphError::PlanError {
+        message: format!("{} needs a catalog of the graph's tables", procedure),
+        location: snafu::Location::new(file!(), line!(), column!()),
+    })
+}
+
+/// The edges of each relationship type, in both directions for undirected ones
+pub(crate) fn edge_tables(
+    procedure: &str,
+    ctx: &ProcedureContext,
+    rel_types: &[String],
+) -> Result<Vec<EdgeTable>> {
+    let planner = DataFusionPlanner::with_catalog(ctx.config().clone(), catalog(procedure, ctx)?);
+    let mut edges = Vec::new();
+    for rel_type in rel_types {
+        let mapping = ctx
+            .config()
+            .get_relationship_mapping(rel_type)
+            .ok_or_else(|| GraphError::PlanError {
+                message: format!("{}: unknown relationship type '{}'", procedure, rel_type),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            })?;
+        if mapping.source_id_fields().len() > 1 {
+            return Err(GraphError::PlanError {
+                message: format!(
+                    "{}: relationship type '{}' has a composite key",
+                    procedure, rel_type
+                ),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            });
+        }
+        let plan = planner.relationship_edges(rel_type)?;
+        let (source, target) = (&mapping.source_id_field, &mapping.target_id_field);
+        if mapping.undirected {
+            edges.push(EdgeTable {
+                rel_type: rel_type.clone(),
+                plan: plan.clone(),
+                from: target.clone(),
+                to: source.clone(),
+            });
+        }
+        edges.push(EdgeTable {
+            rel_type: rel_type.clone(),
+            plan,
+            from: source.clone(),
+            to: target.clone(),
+        });
+    }
+    Ok(edges)
+}
+
+/// The edge tables of the listed relationship types, or of every mapped relationship
+/// type with a table when none are listed
+pub(crate) fn traversable_edge_tables(
+    procedure: &str,
+    ctx: &ProcedureContext,
+    rel_types: Vec<String>,
+) -> Result<Vec<EdgeTable>> {
+    if !rel_types.is_empty() {
+        return edge_tables(procedure, ctx, &rel_types);
+    }
+    // Only types without a table are left out; any other failure is the caller's
+    let planner = DataFusionPlanner::with_catalog(ctx.config().clone(), catalog(procedure, ctx)?);
+    let mut stored: Vec<String> = ctx
+        .config()
+        .relationship_mappings
+        .keys()
+        .filter(|rel_type| planner.has_relationship_table(rel_type))
+        .cloned()
+        .collect();
+    stored.sort();
+    edge_tables(procedure, ctx, &stored)
+}
+
+/// The edge tables to read, with the column of the weight property in each: those of
+/// the listed relationship types, or of every mapped type with the property
+///
+/// The weight is `None` while planning a call whose weight property is not a literal,
+/// and then no type is left out.
+pub(crate) fn weighted_edge_tables(
+    procedure: &str,
+    ctx: &ProcedureContext,
+    rel_types: Vec<String>,
+    weight: Option<&str>,
+) -> Result<Vec<(EdgeTable, String)>> {
+    let mut weighted = Vec::new();
+    for edge in traversable_edge_tables(procedure, ctx, rel_types)? {
+        let column = match weight {
+            Some(weight) => ctx
+                .config()
+                .get_relationship_mapping(&edge.rel_type)
+                .map_or(weight, |mapping| mapping.column_for(weight))
+                .to_string(),
+            None => String::new(),
+        };
+        if weight.is_none() || edge.plan.schema().has_column_with_unqualified_name(&column) {
+            weighted.push((edge, column));
+        }
+    }
+    match weight {
+        Some(weight) if weighted.is_empty() => Err(GraphError::PlanError {
+            message: format!(
+                "{}: no relationship type has the property '{}'",
+                procedure, weight
+            ),
+            location: snafu::Location::new(file!(), line!(), column!()),
+        }),
+        _ => Ok(weighted),
+    }
+}
+
+/// Type of the node keys all the edge tables share
+pub(crate) fn key_type<'a>(
+    procedure: &str,
+    edges: impl IntoIterator<Item = &'a EdgeTable>,
+) -> Result<DataType> {
+    let mut key_type: Option<DataType> = None;
+    for edge in edges {
+        for key in [&edge.from, &edge.to] {
+            let field = edge
+                .plan
+                .schema()
+                .field_with_unqualified_name(key)
+                .map_err(|e| GraphError::PlanError {
+                    message: format!("{}: {}", procedure, e),
+                    location: snafu::Location::new(file!(), line!(), column!()),
+                })?;
+            match &key_type {
+                Some(data_type) if data_type != field.data_type() => {
+                    return Err(GraphError::PlanError {
+                        message: format!(
+                            "{}: relationship types have node keys of types {} and {}",
+                            procedure,
+                            data_type,
+                            field.data_type()
+                        ),
+                        location: snafu::Location::new(file!(), line!(), column!()),
+                    })
+                }
+                Some(_) => {}
+                None => key_type = Some(field.data_type().clone()),
+            }
+        }
+    }
+    key_type.ok_or_else(|| GraphError::PlanError {
+        message: format!("{} needs at least one relationship type", procedure),
+        location: snafu::Location::new(file!(), line!(), column!()),
+    })
+}
+
+/// The edges of `edge` leaving `nodes`, or all of them, with the value of its `weight`
+/// column if given; edges with a null endpoint are left out
+pub(crate) async fn read_edges(
+    session: &SessionContext,
+    edge: &EdgeTable,
+    nodes: Option<&[ScalarValue]>,
+    weight: Option<&str>,
+) -> Result<Vec<Edge>> {
+    let mut columns = vec![col(edge.from.as_str()), col(edge.to.as_str())];
+    if let Some(weight) = weight {
+        columns.push(cast(col(weight), DataType::Float64));
+    }
+    let mut rows = DataFrame::new(session.state(), edge.plan.clone());
+    if let Some(nodes) = nodes {
+        let keys = nodes.iter().cloned().map(lit).collect();
+        rows = rows.filter(col(edge.from.as_str()).in_list(keys, false))?;
+    }
+    let batches = rows.select(columns)?.collect().await?;
+    let mut edges = Vec::new();
+    for batch in &batches {
+        let (from, to) = (batch.column(0), batch.column(1));
+        let weights = weight.map(|_| batch.column(2).as_primitive::<Float64Type>());
+        for row in 0..batch.num_rows() {
+            if from.is_null(row) || to.is_null(row) {
+                continue;
+            }
+            edges.push(Edge {
+                from: ScalarValue::try_from_array(from, row)?,
+                to: ScalarValue::try_from_array(to, row)?,
+                weight: weights.and_then(|w| w.is_valid(row).then(|| w.value(row))),
+            });
+        }
+    }
+    Ok(edges)
+}
+
+/// An in-memory projection of edge tables: every node key that ends an edge, and the
+/// edges as pairs of indexes into those keys
+pub(crate) struct Projection {
+    pub(crate) nodes: Vec<ScalarValue>,
+    pub(crate) edges: Vec<(usize, usize)>,
+}
+
+impl Projection {
+    /// Read every edge of `tables`, numbering nodes in order of first appearance
+    pub(crate) async fn read(session: &SessionContext, tables: &[EdgeTable]) -> Result<Self> {
+        let tables: Vec<(&EdgeTable, Option<&str>)> =
+            tables.iter().map(|table| (table, None)).collect();
+        let (projection, _) = Self::read_with(session, &tables).await?;
+        Ok(projection)
+    }
+
+    /// Read every edge of `tables` with the value of the weight column given for its
+    /// table, one weight per edge; edges without a weight are left out
+    pub(crate) async fn read_weighted(
+        session: &SessionContext,
+        tables: &[(EdgeTable, String)],
+    ) -> Result<(Self, Vec<f64>)> {
+        let tables: Vec<(&EdgeTable, Option<&str>)> = tables
+            .iter()
+            .map(|(table, weight)| (table, Some(weight.as_str())))
+            .collect();
+        Self::read_with(session, &tables).await
+    }
+
+    async fn read_with(
+        session: &SessionContext,
+        tables: &[(&EdgeTable, Option<&str>)],
+    ) -> Result<(Self, Vec<f64>)> {
+        let mut nodes: Vec<ScalarValue> = Vec::new();
+        let mut indexes: HashMap<ScalarValue, usize> = HashMap::new();
+        let mut index = |key: ScalarValue| {
+            *indexes.entry(key.clone()).or_insert_with(|| {
+                nodes.push(key);
+                nodes.len() - 1
+            })
+        };
+        let mut edges = Vec::new();
+        let mut weights = Vec::new();
+        for &(table, weight) in tables {
+            for edge in read_edges(session, table, None, weight).await? {
+                let edge_weight = match (weight, edge.weight) {
+                    (Some(_), None) => continue,
+                    (_, edge_weight) => edge_weight.unwrap_or(1.0),
+                };
+                edges.push((index(edge.from), index(edge.to)));
+                weights.push(edge_weight);
+            }
+        }
+        Ok((Self { nodes, edges }, weights))
+    }
+}
+
+/// A seeded pseudo-random generator (SplitMix64), so that sampled results can be
+/// reproduced
+pub(crate) struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `[0, 1)`
+    pub(crate) fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// The string items of a list
+pub(crate) fn strings(value: &ScalarValue) -> Option<Vec<String>> {
+    let ScalarValue::List(list) = value else {
+        return None;
+    };
+    if list.is_null(0) {
+        return None;
+    }
+    let values = list.value(0);
+    let values = values.as_string_opt::<i32>()?;
+    values
+        .iter()
+        .map(|value| value.map(str::to_string))
+        .collect()
+}
+
+/// The `config` struct of a call to an algorithm procedure, `None` when it is not
+/// given or null
+pub(crate) fn config_struct<'a>(
+    procedure: &str,
+    config: Option<&'a ScalarValue>,
+) -> Result<Option<&'a StructArray>> {
+    match config {
+        Some(ScalarValue::Struct(config)) if !config.is_null(0) => Ok(Some(config.as_ref())),
+        Some(ScalarValue::Struct(_) | ScalarValue::Null) | None => Ok(None),
+        Some(_) => Err(GraphError::ExecutionError {
+            message: format!("{}: config must be a map of options", procedure),
+            location: snafu::Location::new(file!(), line!(), column!()),
+        }),
+    }
+}
+
+/// The `name` option of a `config` struct, `None` when it is null
+pub(crate) fn config_field<'a>(config: &'a StructArray, name: &str) -> Option<&'a ArrayRef> {
+    config.column_by_name(name).filter(|c| c.is_valid(0))
+}
+
+/// The `relationshipTypes` option of a `config` struct, empty when it is null
+pub(crate) fn config_relationship_types(config: &StructArray) -> Vec<String> {
+    let Some(rel_types) = config_field(config, "relationshipTypes") else {
+        return Vec::new();
+    };
+    let rel_types = rel_types.as_list::<i32>().value(0);
+    rel_types
+        .as_string::<i32>()
+        .iter()
+        .flatten()
+        .map(str::to_string)
+        .collect()
+}
+
+/// The order of `nodes` by key, and their keys in that order
+pub(crate) fn sorted_keys(
+    key_type: &DataType,
+    nodes: &[ScalarValue],
+) -> Result<(Vec<usize>, ArrayRef)> {
+    let mut order: Vec<usize> = (0..nodes.len()).collect();
+    order.sort_by(|&a, &b| nodes[a].partial_cmp(&nodes[b]).unwrap_or(Ordering::Equal));
+    let keys = if order.is_empty() {
+        arrow::array::new_empty_array(key_type)
+    } else {
+        ScalarValue::iter_to_array(order.iter().map(|&node| nodes[node].clone()))?
+    };
+    Ok((order, keys))
+}
+
+/// The node keys in key order, with the label of each numbered from 0 in the order of
+/// the smallest key labelled with it
+pub(crate) fn numbered_by_key(
+    key_type: &DataType,
+    nodes: &[ScalarValue],
+    labels: &[usize],
+) -> Result<(ArrayRef, Int64Array)> {
+    let (order, keys) = sorted_keys(key_type, nodes)?;
+    let mut numbering = vec![None; labels.iter().max().map_or(0, |max| max + 1)];
+    let mut next = 0i64;
+    let numbers = Int64Array::from_iter_values(order.iter().map(|&node| {
+        *numbering[labels[node]].get_or_insert_with(|| {
+            next += 1;
+            next - 1
+        })
+    }));
+    Ok((keys, numbers))
+}
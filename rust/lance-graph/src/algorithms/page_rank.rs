@@ -0,0 +1,224 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! PageRank centrality, the `graph.pageRank` procedure
+//!
+//! The edges of the relationship tables are read once into an in-memory
+//! [`Projection`], where nodes are dense indexes. Scores are then iterated over the projection until they change by less than
+//! the tolerance or the iteration limit is reached. The rank of nodes without outgoing
+//! edges is spread evenly over all nodes, so the scores always sum to one.
+
+use std::sync::Arc;
+
+use arrow::array::{AsArray, Float64Array, RecordBatch};
+use arrow::datatypes::{Float64Type, Int64Type};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use async_trait::async_trait;
+use datafusion::scalar::ScalarValue;
+
+use super::projection::AlgorithmGraph;
+use super::{config_field, config_struct, Projection};
+use crate::error::{GraphError, Result};
+use crate::procedures::{Procedure, ProcedureContext, ProcedureParameter};
+
+const NAME: &str = "graph.pageRank";
+
+/// `graph.pageRank(config)`: the `nodeId` and `score` of every node of the projection
+#[derive(Debug)]
+pub(crate) struct PageRank;
+
+/// Options of one run, from the `config` map
+#[derive(Debug, Clone, PartialEq)]
+struct PageRankOptions {
+    damping_factor: f64,
+    max_iterations: usize,
+    tolerance: f64,
+}
+
+impl Default for PageRankOptions {
+    fn default() -> Self {
+        Self {
+            damping_factor: 0.85,
+            max_iterations: 20,
+            tolerance: 1e-7,
+        }
+    }
+}
+
+#[async_trait]
+impl Procedure for PageRank {
+    fn name(&self) -> &str {
+        NAME
+    }
+
+    fn parameters(&self) -> Vec<ProcedureParameter> {
+        vec![AlgorithmGraph::config_parameter(vec![
+            Field::new("dampingFactor", DataType::Float64, true),
+            Field::new("maxIterations", DataType::Int64, true),
+            Field::new("tolerance", DataType::Float64, true),
+        ])]
+    }
+
+    fn output_schema(&self, ctx: &ProcedureContext) -> Result<SchemaRef> {
+        self.output_schema_for(ctx, &[])
+    }
+
+    fn output_schema_for(
+        &self,
+        ctx: &ProcedureContext,
+        args: &[Option<ScalarValue>],
+    ) -> Result<SchemaRef> {
+        let graph = AlgorithmGraph::from_config(NAME, ctx, args.first().and_then(Option::as_ref))?;
+        Ok(Arc::new(Schema::new(vec![
+            Field::new("nodeId", graph.key_type(NAME)?, false),
+            Field::new("score", DataType::Float64, false),
+        ])))
+    }
+
+    async fn call(&self, ctx: &ProcedureContext, args: Vec<ScalarValue>) -> Result<RecordBatch> {
+        let known: Vec<Option<ScalarValue>> = args.iter().cloned().map(Some).collect();
+        let schema = self.output_schema_for(ctx, &known)?;
+        let options = PageRankOptions::try_from_config(args.first())?;
+        let session = ctx.session().cloned().unwrap_or_default();
+        let graph = AlgorithmGraph::from_config(NAME, ctx, args.first())?;
+        let Projection { nodes, edges } = graph.read(&session).await?;
+
+        let scores = page_rank(nodes.len(), &edges, &options);
+        let mut ranked: Vec<usize> = (0..nodes.len()).collect();
+        ranked.sort_by(|&a, &b| scores[b].total_cmp(&scores[a]).then(a.cmp(&b)));
+        let node_ids = if ranked.is_empty() {
+            arrow::array::new_empty_array(schema.field(0).data_type())
+        } else {
+            ScalarValue::iter_to_array(ranked.iter().map(|&i| nodes[i].clone()))?
+        };
+        let scores = Float64Array::from_iter_values(ranked.iter().map(|&i| scores[i]));
+        Ok(RecordBatch::try_new(
+            schema,
+            vec![node_ids, Arc::new(scores)],
+        )?)
+    }
+}
+
+impl PageRankOptions {
+    /// The damping factor and stopping criteria given in `config`
+    fn try_from_config(config: Option<&ScalarValue>) -> Result<Self> {
+        let invalid = |message: &str| GraphError::ExecutionError {
+            message: format!("{}: {}", NAME, message),
+            location: snafu::Location::new(file!(), line!(), column!()),
+        };
+        let mut options = Self::default();
+        let Some(config) = config_struct(NAME, config)? else {
+            return Ok(options);
+        };
+        let field = |name: &str| config_field(config, name);
+
+        if let Some(damping) = field("dampingFactor") {
+            options.damping_factor = damping.as_primitive::<Float64Type>().value(0);
+            if !(0.0..1.0).contains(&options.damping_factor) {
+                return Err(invalid("dampingFactor must be at least 0 and less than 1"));
+            }
+        }
+        if let Some(iterations) = field("maxIterations") {
+            match iterations.as_primitive::<Int64Type>().value(0) {
+                n if n > 0 => options.max_iterations = n as usize,
+                _ => return Err(invalid("maxIterations must be positive")),
+            }
+        }
+        if let Some(tolerance) = field("tolerance") {
+            options.tolerance = tolerance.as_primitive::<Float64Type>().value(0);
+            if options.tolerance.is_nan() || options.tolerance < 0.0 {
+                return Err(invalid("tolerance must not be negative"));
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// PageRank scores of the `node_count` nodes of `edges`, summing to one
+///
+/// Iterates until the scores change by less than the tolerance in total (L1 norm) or
+/// for at most the maximum number of iterations.
+fn page_rank(node_count: usize, edges: &[(usize, usize)], options: &PageRankOptions) -> Vec<f64> {
+    if node_count == 0 {
+        return Vec::new();
+    }
+    let n = node_count as f64;
+    let mut out_degree = vec![0usize; node_count];
+    for &(from, _) in edges {
+        out_degree[from] += 1;
+    }
+
+    let d = options.damping_factor;
+    let mut scores = vec![1.0 / n; node_count];
+    for _ in 0..options.max_iterations {
+        let dangling: f64 = (0..node_count)
+            .filter(|&node| out_degree[node] == 0)
+            .map(|node| scores[node])
+            .sum();
+        let base = (1.0 - d) / n + d * dangling / n;
+        let mut next = vec![base; node_count];
+        for &(from, to) in edges {
+            next[to] += d * scores[from] / out_degree[from] as f64;
+        }
+        let change: f64 = next.iter().zip(&scores).map(|(a, b)| (a - b).abs()).sum();
+        scores = next;
+        if change < options.tolerance {
+            break;
+        }
+    }
+    scores
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_rank_of_a_cycle_is_uniform() {
+        let scores = page_rank(3, &[(0, 1), (1, 2), (2, 0)], &PageRankOptions::default());
+        for score in scores {
+            assert!((score - 1.0 / 3.0).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_page_rank_spreads_dangling_rank() {
+        // Two nodes link to a sink, whose rank flows back to all three
+        let options = PageRankOptions {
+            max_iterations: 100,
+            ..Default::default()
+        };
+        let scores = page_rank(3, &[(0, 2), (1, 2)], &options);
+        assert!((scores.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        assert!(scores[2] > scores[0]);
+        assert!((scores[0] - scores[1]).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_page_rank_of_an_unlinked_node_is_the_teleport_share() {
+        // A cycle of three with a fourth node linking into it
+        let scores = page_rank(
+            4,
+            &[(0, 1), (1, 2), (2, 0), (3, 2)],
+            &PageRankOptions::default(),
+        );
+        assert!((scores.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        assert!((scores[3] - 0.15 / 4.0).abs() < 1e-9);
+        assert!(scores[2] > scores[0] && scores[0] > scores[1]);
+    }
+
+    #[test]
+    fn test_page_rank_stops_at_max_iterations() {
+        let options = PageRankOptions {
+            max_iterations: 1,
+            tolerance: 0.0,
+            ..Default::default()
+        };
+        // One step from uniform scores: the sink gains the rank of both sources
+        let scores = page_rank(3, &[(0, 2), (1, 2)], &options);
+        let third = 1.0 / 3.0;
+        let base = 0.15 / 3.0 + 0.85 * third / 3.0;
+        assert!((scores[0] - base).abs() < 1e-12);
+        assert!((scores[2] - (base + 0.85 * 2.0 * third)).abs() < 1e-12);
+    }
+}
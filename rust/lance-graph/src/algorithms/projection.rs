@@ -0,0 +1,590 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! In-memory graph projections
+//!
+//! A [`GraphProjection`] reads the relationships of some types, and optionally the nodes
+//! of some labels, into memory once: node keys numbered densely, and the outgoing
+//! relationships of each node in compressed sparse row (CSR) layout. Registered on a
+//! query with [`CypherQuery::with_projection`](crate::query::CypherQuery::with_projection),
+//! it is read by the algorithm procedures given its name as their `projection` option
+//! instead of their relationship tables, so an analytics session scans its datasets
+//! once for any number of algorithm calls:
+//!
+//! ```ignore
+//! let projection = GraphProjection::builder(config.clone())
+//!     .with_relationship_types(["KNOWS"])
+//!     .build(catalog.clone())
+//!     .await?;
+//! let query = CypherQuery::new(
+//!     "CALL graph.pageRank({projection: 'social'}) YIELD nodeId, score RETURN nodeId, score",
+//! )?
+//! .with_config(config)
+//! .with_projection("social", Arc::new(projection));
+//! ```
+//!
+//! A projection is a snapshot: writes to the tables it was read from are not seen until
+//! it is built again.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::Arc;
+
+use arrow::array::{Array, AsArray};
+use arrow_schema::{DataType, Field, Fields};
+use datafusion::dataframe::DataFrame;
+use datafusion::execution::context::SessionContext;
+use datafusion::logical_expr::{col, LogicalPlanBuilder};
+use datafusion::scalar::ScalarValue;
+
+use super::{
+    config_field, config_relationship_types, config_struct, key_type, traversable_edge_tables,
+    weighted_edge_tables, EdgeTable, Projection,
+};
+use crate::config::GraphConfig;
+use crate::error::{GraphError, Result};
+use crate::procedures::{ProcedureContext, ProcedureParameter};
+use crate::source_catalog::GraphSourceCatalog;
+
+const NAME: &str = "graph projection";
+
+/// Nodes and relationships of a graph held in memory, for algorithms to run on
+/// repeatedly
+pub struct GraphProjection {
+    node_labels: Vec<String>,
+    rel_types: Vec<String>,
+    weight_property: Option<String>,
+    key_type: DataType,
+    nodes: Vec<ScalarValue>,
+    indexes: HashMap<ScalarValue, usize>,
+    /// The relationships leaving node `i` lead to `targets[offsets[i]..offsets[i + 1]]`
+    offsets: Vec<usize>,
+    targets: Vec<usize>,
+    /// Weight of each relationship, in the order of `targets`
+    weights: Option<Vec<f64>>,
+}
+
+/// Builds a [`GraphProjection`]
+#[derive(Debug, Clone)]
+pub struct GraphProjectionBuilder {
+    config: GraphConfig,
+    node_labels: Vec<String>,
+    rel_types: Vec<String>,
+    weight_property: Option<String>,
+}
+
+impl GraphProjection {
+    /// Project the graph of `config`: every relationship type with a table and the
+    /// nodes ending its relationships, unless narrowed by the builder
+    pub fn builder(config: GraphConfig) -> GraphProjectionBuilder {
+        GraphProjectionBuilder {
+            config,
+            node_labels: Vec::new(),
+            rel_types: Vec::new(),
+            weight_property: None,
+        }
+    }
+
+    /// Labels whose nodes were projected; empty when the nodes are those ending a
+    /// relationship
+    pub fn node_labels(&self) -> &[String] {
+        &self.node_labels
+    }
+
+    /// Relationship types projected
+    pub fn relationship_types(&self) -> &[String] {
+        &self.rel_types
+    }
+
+    /// Property the relationships are weighted by, if any
+    pub fn weight_property(&self) -> Option<&str> {
+        self.weight_property.as_deref()
+    }
+
+    /// Type of the node keys
+    pub fn key_type(&self) -> &DataType {
+        &self.key_type
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn relationship_count(&self) -> usize {
+        self.targets.len()
+    }
+
+    /// Whether the node with key `node` was projected
+    pub fn contains(&self, node: &ScalarValue) -> bool {
+        self.indexes.contains_key(node)
+    }
+
+    /// Keys of the nodes the relationships leaving `node` lead to, one per relationship
+    pub fn neighbors(&self, node: &ScalarValue) -> impl Iterator<Item = &ScalarValue> {
+        let range = match self.indexes.get(node) {
+            Some(&i) => self.offsets[i]..self.offsets[i + 1],
+            None => 0..0,
+        };
+        self.targets[range].iter().map(|&to| &self.nodes[to])
+    }
+
+    /// The `(node, depth)` of every node within `max_depth` hops of `start_nodes`, once
+    /// each at its shortest depth and level by level, as `graph.bfs` yields them
+    pub fn breadth_first(
+        &self,
+        start_nodes: &[ScalarValue],
+        max_depth: usize,
+    ) -> Vec<(ScalarValue, usize)> {
+        let mut visited = vec![false; self.nodes.len()];
+        let mut reached = Vec::new();
+        let mut frontier = Vec::new();
+        for start in start_nodes {
+            if let Some(&i) = self.indexes.get(start) {
+                if !std::mem::replace(&mut visited[i], true) {
+                    reached.push((start.clone(), 0));
+                    frontier.push(i);
+                }
+            }
+        }
+        for depth in 1..=max_depth {
+            let mut next = Vec::new();
+            for &node in &frontier {
+                for &to in &self.targets[self.offsets[node]..self.offsets[node + 1]] {
+                    if !std::mem::replace(&mut visited[to], true) {
+                        reached.push((self.nodes[to].clone(), depth));
+                        next.push(to);
+                    }
+                }
+            }
+            frontier = next;
+        }
+        reached
+    }
+
+    /// The projection as the node keys and edge list the algorithms take
+    pub(crate) fn projection(&self) -> Projection {
+        let edges = (0..self.nodes.len())
+            .flat_map(|from| {
+                self.targets[self.offsets[from]..self.offsets[from + 1]]
+                    .iter()
+                    .map(move |&to| (from, to))
+            })
+            .collect();
+        Projection {
+            nodes: self.nodes.clone(),
+            edges,
+        }
+    }
+
+    /// Weights of the edges of [`GraphProjection::projection`], in order
+    pub(crate) fn weights(&self) -> Option<&[f64]> {
+        self.weights.as_deref()
+    }
+
+    /// Lay out `edges`, pairs of indexes into `nodes`, by source node
+    fn from_edges(
+        key_type: DataType,
+        nodes: Vec<ScalarValue>,
+        edges: &[(usize, usize)],
+        weights: Option<Vec<f64>>,
+    ) -> Self {
+        let mut offsets = vec![0usize; nodes.len() + 1];
+        for &(from, _) in edges {
+            offsets[from + 1] += 1;
+        }
+        let mut total = 0;
+        for offset in &mut offsets {
+            total += *offset;
+            *offset = total;
+        }
+        let mut next = offsets.clone();
+        let mut targets = vec![0usize; edges.len()];
+        let mut sorted_weights = weights.as_ref().map(|_| vec![0.0; edges.len()]);
+        for (edge, &(from, to)) in edges.iter().enumerate() {
+            let slot = next[from];
+            next[from] += 1;
+            targets[slot] = to;
+            if let (Some(sorted), Some(weights)) = (&mut sorted_weights, &weights) {
+                sorted[slot] = weights[edge];
+            }
+        }
+        let indexes = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, node)| (node.clone(), i))
+            .collect();
+        Self {
+            node_labels: Vec::new(),
+            rel_types: Vec::new(),
+            weight_property: None,
+            key_type,
+            nodes,
+            indexes,
+            offsets,
+            targets,
+            weights: sorted_weights,
+        }
+    }
+}
+
+impl fmt::Debug for GraphProjection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GraphProjection")
+            .field("node_labels", &self.node_labels)
+            .field("rel_types", &self.rel_types)
+            .field("weight_property", &self.weight_property)
+            .field("node_count", &self.node_count())
+            .field("relationship_count", &self.relationship_count())
+            .finish()
+    }
+}
+
+impl GraphProjectionBuilder {
+    /// Project the nodes of these labels, including those without relationships, and
+    /// only the relationships between them
+    pub fn with_node_labels(mut self, labels: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.node_labels = labels.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Project only the relationships of these types
+    pub fn with_relationship_types(
+        mut self,
+        rel_types: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.rel_types = rel_types.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Weight the relationships by this property, for the algorithms taking weights;
+    /// relationships without it are left out
+    pub fn with_weight_property(mut self, property: impl Into<String>) -> Self {
+        self.weight_property = Some(property.into());
+        self
+    }
+
+    /// Read the projection from the tables of `catalog`
+    pub async fn build(self, catalog: Arc<dyn GraphSourceCatalog>) -> Result<GraphProjection> {
+        let ctx = ProcedureContext::new(self.config.clone(), Some(catalog.clone()));
+        let session = SessionContext::new();
+        let tables = traversable_edge_tables(NAME, &ctx, self.rel_types.clone())?;
+        let key_type = key_type(NAME, &tables)?;
+        let mut rel_types: Vec<String> = Vec::new();
+        for table in &tables {
+            if !rel_types.contains(&table.rel_type) {
+                rel_types.push(table.rel_type.clone());
+            }
+        }
+        let (read, weights) = match &self.weight_property {
+            Some(weight) => {
+                let tables = weighted_edge_tables(NAME, &ctx, rel_types.clone(), Some(weight))?;
+                let (read, weights) = Projection::read_weighted(&session, &tables).await?;
+                (read, Some(weights))
+            }
+            None => (Projection::read(&session, &tables).await?, None),
+        };
+
+        let mut projection = if self.node_labels.is_empty() {
+            GraphProjection::from_edges(key_type, read.nodes, &read.edges, weights)
+        } else {
+            let nodes = self
+                .label_nodes(catalog.as_ref(), &session, &key_type)
+                .await?;
+            let indexes: HashMap<&ScalarValue, usize> = nodes
+                .iter()
+                .enumerate()
+                .map(|(i, node)| (node, i))
+                .collect();
+            // Relationships between the nodes, renumbered
+            let mut edges = Vec::new();
+            let mut kept = Vec::new();
+            for (edge, &(from, to)) in read.edges.iter().enumerate() {
+                if let (Some(&from), Some(&to)) =
+                    (indexes.get(&read.nodes[from]), indexes.get(&read.nodes[to]))
+                {
+                    edges.push((from, to));
+                    kept.push(edge);
+                }
+            }
+            let weights = weights.map(|w| kept.iter().map(|&edge| w[edge]).collect());
+            GraphProjection::from_edges(key_type, nodes, &edges, weights)
+        };
+        projection.node_labels = self.node_labels;
+        projection.rel_types = rel_types;
+        projection.weight_property = self.weight_property;
+        Ok(projection)
+    }
+
+    /// Keys of the nodes of the labels, without repeats, cast to `key_type`
+    async fn label_nodes(
+        &self,
+        catalog: &dyn GraphSourceCatalog,
+        session: &SessionContext,
+        key_type: &DataType,
+    ) -> Result<Vec<ScalarValue>> {
+        let invalid = |message: String| GraphError::PlanError {
+            message: format!("{}: {}", NAME, message),
+            location: snafu::Location::new(file!(), line!(), column!()),
+        };
+        let mut seen = HashSet::new();
+        let mut nodes = Vec::new();
+        for label in &self.node_labels {
+            let mapping = self
+                .config
+                .get_node_mapping(label)
+                .ok_or_else(|| invalid(format!("unknown node label '{}'", label)))?;
+            let [key] = mapping.id_fields()[..] else {
+                return Err(invalid(format!(
+                    "node label '{}' has a composite key",
+                    label
+                )));
+            };
+            let source = catalog
+                .node_source(label)
+                .ok_or_else(|| invalid(format!("no table for node label '{}'", label)))?;
+            let plan = LogicalPlanBuilder::scan(label.as_str(), source, None)?.build()?;
+            let batches = DataFrame::new(session.state(), plan)
+                .select(vec![col(key)])?
+                .collect()
+                .await?;
+            for batch in &batches {
+                let keys = batch.column(0);
+                for row in (0..keys.len()).filter(|&row| keys.is_valid(row)) {
+                    let key = ScalarValue::try_from_array(keys, row)?
+                        .cast_to(key_type)
+                        .map_err(|e| {
+                            invalid(format!(
+                                "keys of '{}' are not {} keys: {}",
+                                label, key_type, e
+                            ))
+                        })?;
+                    if seen.insert(key.clone()) {
+                        nodes.push(key);
+                    }
+                }
+            }
+        }
+        Ok(nodes)
+    }
+}
+
+/// The graph an algorithm procedure runs on: the projection named by its `projection`
+/// option, or else the edge tables of its relationship types, read on every call
+pub(crate) enum AlgorithmGraph {
+    Projected(Arc<GraphProjection>),
+    Tables(Vec<EdgeTable>),
+    /// Edge tables with the column of the weight property in each
+    WeightedTables(Vec<(EdgeTable, String)>),
+}
+
+impl AlgorithmGraph {
+    /// The `config` parameter of an algorithm procedure: the `relationshipTypes` or
+    /// `projection` selecting the graph, then the procedure's own `fields`
+    pub(crate) fn config_parameter(fields: Vec<Field>) -> ProcedureParameter {
+        let mut all = vec![
+            Field::new(
+                "relationshipTypes",
+                DataType::new_list(DataType::Utf8, true),
+                true,
+            ),
+            Field::new("projection", DataType::Utf8, true),
+        ];
+        all.extend(fields);
+        let config = DataType::Struct(Fields::from(all));
+        let default = ScalarValue::try_from(&config).unwrap_or(ScalarValue::Null);
+        ProcedureParameter::new("config", config).with_default(default)
+    }
+
+    /// The graph the `config` of a call selects: its projection or the edge tables of
+    /// its relationship types, all of them when neither is given
+    pub(crate) fn from_config(
+        procedure: &str,
+        ctx: &ProcedureContext,
+        config: Option<&ScalarValue>,
+    ) -> Result<Self> {
+        let (projection, rel_types) = Self::selection(procedure, config)?;
+        Self::resolve(procedure, ctx, projection.as_deref(), rel_types)
+    }
+
+    /// The graph the `config` of a call selects, with its relationships weighted by
+    /// `weight` if given
+    pub(crate) fn from_config_weighted(
+        procedure: &str,
+        ctx: &ProcedureContext,
+        config: Option<&ScalarValue>,
+        weight: Option<&str>,
+    ) -> Result<Self> {
+        let (projection, rel_types) = Self::selection(procedure, config)?;
+        Self::resolve_weighted(procedure, ctx, projection.as_deref(), rel_types, weight)
+    }
+
+    fn selection(
+        procedure: &str,
+        config: Option<&ScalarValue>,
+    ) -> Result<(Option<String>, Vec<String>)> {
+        let Some(config) = config_struct(procedure, config)? else {
+            return Ok((None, Vec::new()));
+        };
+        let projection =
+            config_field(config, "projection").map(|c| c.as_string::<i32>().value(0).to_string());
+        Ok((projection, config_relationship_types(config)))
+    }
+
+    fn resolve(
+        procedure: &str,
+        ctx: &ProcedureContext,
+        projection: Option<&str>,
+        rel_types: Vec<String>,
+    ) -> Result<Self> {
+        match projection {
+            Some(name) => Self::projected(procedure, ctx, name, &rel_types),
+            None => Ok(Self::Tables(traversable_edge_tables(
+                procedure, ctx, rel_types,
+            )?)),
+        }
+    }
+
+    /// The graph with the relationships weighted by `weight`, if given; `weight` is
+    /// `None` also while planning a call whose weight property is not a literal
+    fn resolve_weighted(
+        procedure: &str,
+        ctx: &ProcedureContext,
+        projection: Option<&str>,
+        rel_types: Vec<String>,
+        weight: Option<&str>,
+    ) -> Result<Self> {
+        let Some(name) = projection else {
+            let tables = weighted_edge_tables(procedure, ctx, rel_types, weight)?;
+            return Ok(match weight {
+                Some(_) => Self::WeightedTables(tables),
+                None => Self::Tables(tables.into_iter().map(|(table, _)| table).collect()),
+            });
+        };
+        let graph = Self::projected(procedure, ctx, name, &rel_types)?;
+        if let (Self::Projected(projection), Some(weight)) = (&graph, weight) {
+            if projection.weight_property() != Some(weight) {
+                return Err(GraphError::PlanError {
+                    message: format!(
+                        "{}: projection '{}' is not weighted by '{}'",
+                        procedure, name, weight
+                    ),
+                    location: snafu::Location::new(file!(), line!(), column!()),
+                });
+            }
+        }
+        Ok(graph)
+    }
+
+    fn projected(
+        procedure: &str,
+        ctx: &ProcedureContext,
+        name: &str,
+        rel_types: &[String],
+    ) -> Result<Self> {
+        let invalid = |message: String| GraphError::PlanError {
+            message: format!("{}: {}", procedure, message),
+            location: snafu::Location::new(file!(), line!(), column!()),
+        };
+        if !rel_types.is_empty() {
+            return Err(invalid(
+                "relationshipTypes cannot be given with a projection".to_string(),
+            ));
+        }
+        let projection = ctx
+            .projection(name)
+            .ok_or_else(|| invalid(format!("unknown projection '{}'", name)))?;
+        Ok(Self::Projected(projection.clone()))
+    }
+
+    /// Type of the node keys
+    pub(crate) fn key_type(&self, procedure: &str) -> Result<DataType> {
+        match self {
+            Self::Projected(projection) => Ok(projection.key_type().clone()),
+            Self::Tables(tables) => key_type(procedure, tables),
+            Self::WeightedTables(tables) => key_type(procedure, tables.iter().map(|(t, _)| t)),
+        }
+    }
+
+    /// The nodes and edges of the graph
+    pub(crate) async fn read(&self, session: &SessionContext) -> Result<Projection> {
+        match self {
+            Self::Projected(projection) => Ok(projection.projection()),
+            Self::Tables(tables) => Projection::read(session, tables).await,
+            Self::WeightedTables(tables) => Ok(Projection::read_weighted(session, tables).await?.0),
+        }
+    }
+
+    /// The nodes and edges of the graph with the weight of each edge; 1 when the graph
+    /// is not weighted
+    pub(crate) async fn read_weighted(
+        &self,
+        session: &SessionContext,
+    ) -> Result<(Projection, Vec<f64>)> {
+        match self {
+            Self::WeightedTables(tables) => Projection::read_weighted(session, tables).await,
+            Self::Projected(projection) if projection.weights().is_some() => Ok((
+                projection.projection(),
+                projection.weights().unwrap_or_default().to_vec(),
+            )),
+            _ => {
+                let read = self.read(session).await?;
+                let weights = vec![1.0; read.edges.len()];
+                Ok((read, weights))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(k: i64) -> ScalarValue {
+        ScalarValue::Int64(Some(k))
+    }
+
+    /// Nodes 10 to 14: 10 -> 11 (twice), 10 -> 12, 12 -> 13 and 13 -> 10, weighted by
+    /// their order; 14 is isolated
+    fn projection() -> GraphProjection {
+        let nodes = (10..=14).map(key).collect();
+        let edges = [(2, 3), (0, 1), (3, 0), (0, 2), (0, 1)];
+        let weights = vec![1.0, 2.0, 3.0, 4.0, 5.0];
+        GraphProjection::from_edges(DataType::Int64, nodes, &edges, Some(weights))
+    }
+
+    #[test]
+    fn test_compressed_sparse_rows() {
+        let projection = projection();
+        assert_eq!(projection.offsets, vec![0, 3, 3, 4, 5, 5]);
+        assert_eq!(projection.targets, vec![1, 2, 1, 3, 0]);
+        assert_eq!(projection.weights(), Some(&[2.0, 4.0, 5.0, 1.0, 3.0][..]));
+        assert_eq!(projection.relationship_count(), 5);
+        assert_eq!(
+            projection.projection().edges,
+            vec![(0, 1), (0, 2), (0, 1), (2, 3), (3, 0)]
+        );
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let projection = projection();
+        let neighbors: Vec<&ScalarValue> = projection.neighbors(&key(10)).collect();
+        assert_eq!(neighbors, vec![&key(11), &key(12), &key(11)]);
+        assert_eq!(projection.neighbors(&key(14)).count(), 0);
+        assert_eq!(projection.neighbors(&key(99)).count(), 0);
+        assert!(projection.contains(&key(14)));
+    }
+
+    #[test]
+    fn test_breadth_first() {
+        let projection = projection();
+        assert_eq!(
+            projection.breadth_first(&[key(12), key(99)], 2),
+            vec![(key(12), 0), (key(13), 1), (key(10), 2)]
+        );
+        assert_eq!(
+            projection.breadth_first(&[key(10), key(13)], 1),
+            vec![(key(10), 0), (key(13), 0), (key(11), 1), (key(12), 1)]
+        );
+    }
+}
@@ -0,0 +1,333 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Biased random walks, the `graph.randomWalk` procedure
+//!
+//! Walks follow the edges of an in-memory [`Projection`] of the relationship tables,
+//! choosing each step the way node2vec does: having come from `t` to `v`, the walk
+//! returns to `t` with weight `1/p`, moves to a neighbor of `t` with weight 1 and
+//! moves farther away with weight `1/q`. `p = q = 1` gives uniform walks. A seeded
+//! generator makes the walks reproducible; a walk ends early at a node without
+//! outgoing edges.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use arrow::array::{Array, ArrayRef, AsArray, ListArray, RecordBatch};
+use arrow::buffer::OffsetBuffer;
+use arrow::datatypes::Int64Type;
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use async_trait::async_trait;
+use datafusion::scalar::ScalarValue;
+
+use super::projection::AlgorithmGraph;
+use super::{config_field, config_struct, Projection, SplitMix64};
+use crate::error::{GraphError, Result};
+use crate::procedures::{Procedure, ProcedureContext, ProcedureParameter};
+
+const NAME: &str = "graph.randomWalk";
+
+/// `graph.randomWalk(startNodes, steps [, p, q, config])`: the `startNode` and `walk`
+/// of every walk taken
+#[derive(Debug)]
+pub(crate) struct RandomWalk;
+
+/// Options of one call, from the `config` map
+#[derive(Debug, Clone, PartialEq)]
+struct WalkOptions {
+    walks_per_node: usize,
+    seed: u64,
+}
+
+impl Default for WalkOptions {
+    fn default() -> Self {
+        Self {
+            walks_per_node: 1,
+            seed: 0,
+        }
+    }
+}
+
+/// The node2vec parameters of a walk
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct WalkBias {
+    /// Return parameter: the higher, the less likely a walk steps back
+    p: f64,
+    /// In-out parameter: the higher, the more a walk stays close to where it came from
+    q: f64,
+}
+
+#[async_trait]
+impl Procedure for RandomWalk {
+    fn name(&self) -> &str {
+        NAME
+    }
+
+    fn parameters(&self) -> Vec<ProcedureParameter> {
+        vec![
+            ProcedureParameter::new("startNodes", DataType::new_list(DataType::Utf8, true)),
+            ProcedureParameter::new("steps", DataType::Int64),
+            ProcedureParameter::new("p", DataType::Float64)
+                .with_default(ScalarValue::Float64(Some(1.0))),
+            ProcedureParameter::new("q", DataType::Float64)
+                .with_default(ScalarValue::Float64(Some(1.0))),
+            AlgorithmGraph::config_parameter(vec![
+                Field::new("walksPerNode", DataType::Int64, true),
+                Field::new("seed", DataType::Int64, true),
+            ]),
+        ]
+    }
+
+    fn output_schema(&self, ctx: &ProcedureContext) -> Result<SchemaRef> {
+        self.output_schema_for(ctx, &[])
+    }
+
+    fn output_schema_for(
+        &self,
+        ctx: &ProcedureContext,
+        args: &[Option<ScalarValue>],
+    ) -> Result<SchemaRef> {
+        let graph = AlgorithmGraph::from_config(NAME, ctx, args.get(4).and_then(Option::as_ref))?;
+        let key_type = graph.key_type(NAME)?;
+        Ok(Arc::new(Schema::new(vec![
+            Field::new("startNode", key_type.clone(), false),
+            Field::new("walk", DataType::new_list(key_type, true), false),
+        ])))
+    }
+
+    async fn call(&self, ctx: &ProcedureContext, args: Vec<ScalarValue>) -> Result<RecordBatch> {
+        let known: Vec<Option<ScalarValue>> = args.iter().cloned().map(Some).collect();
+        let schema = self.output_schema_for(ctx, &known)?;
+        let key_type = schema.field(0).data_type().clone();
+        let start_nodes = match args.first() {
+            Some(ScalarValue::List(list)) if !list.is_null(0) => {
+                let values = list.value(0);
+                (0..values.len())
+                    .filter(|&i| values.is_valid(i))
+                    .map(|i| ScalarValue::try_from_array(&values, i)?.cast_to(&key_type))
+                    .collect::<datafusion::common::Result<Vec<_>>>()
+                    .map_err(|e| invalid(&format!("start node is not a {} key: {}", key_type, e)))?
+            }
+            _ => return Err(invalid("startNodes must be a list of node keys")),
+        };
+        let steps = match args.get(1) {
+            Some(ScalarValue::Int64(Some(steps))) if *steps >= 0 => *steps as usize,
+            _ => return Err(invalid("steps must be a non-negative integer")),
+        };
+        let parameter = |i: usize, name: &str| match args.get(i) {
+            Some(ScalarValue::Float64(Some(value))) if *value > 0.0 => Ok(*value),
+            None => Ok(1.0),
+            _ => Err(invalid(&format!("{} must be a positive number", name))),
+        };
+        let bias = WalkBias {
+            p: parameter(2, "p")?,
+            q: parameter(3, "q")?,
+        };
+        let options = WalkOptions::try_from_config(args.get(4))?;
+
+        let session = ctx.session().cloned().unwrap_or_default();
+        let graph = AlgorithmGraph::from_config(NAME, ctx, args.get(4))?;
+        let Projection { mut nodes, edges } = graph.read(&session).await?;
+        let mut indexes: HashMap<ScalarValue, usize> = nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| (node.clone(), index))
+            .collect();
+        let starts: Vec<usize> = if start_nodes.is_empty() {
+            // Every node, in key order
+            let mut all: Vec<usize> = (0..nodes.len()).collect();
+            all.sort_by(|&a, &b| {
+                nodes[a]
+                    .partial_cmp(&nodes[b])
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            });
+            all
+        } else {
+            // Start nodes without edges still make walks of their own
+            start_nodes
+                .into_iter()
+                .map(|node| {
+                    *indexes.entry(node.clone()).or_insert_with(|| {
+                        nodes.push(node);
+                        nodes.len() - 1
+                    })
+                })
+                .collect()
+        };
+
+        let adjacency = adjacency(nodes.len(), &edges);
+        let mut random = SplitMix64::new(options.seed);
+        let mut walks: Vec<Vec<usize>> = Vec::new();
+        for &start in &starts {
+            for _ in 0..options.walks_per_node {
+                walks.push(walk(&adjacency, start, steps, bias, &mut random));
+            }
+        }
+
+        let keys = |indexes: Vec<usize>| -> Result<ArrayRef> {
+            Ok(if indexes.is_empty() {
+                arrow::array::new_empty_array(&key_type)
+            } else {
+                ScalarValue::iter_to_array(indexes.into_iter().map(|i| nodes[i].clone()))?
+            })
+        };
+        let start_nodes = keys(walks.iter().map(|walk| walk[0]).collect())?;
+        let lengths: Vec<usize> = walks.iter().map(Vec::len).collect();
+        let walks = ListArray::try_new(
+            Arc::new(Field::new_list_field(key_type.clone(), true)),
+            OffsetBuffer::from_lengths(lengths),
+            keys(walks.into_iter().flatten().collect())?,
+            None,
+        )?;
+        Ok(RecordBatch::try_new(
+            schema,
+            vec![start_nodes, Arc::new(walks)],
+        )?)
+    }
+}
+
+fn invalid(message: &str) -> GraphError {
+    GraphError::ExecutionError {
+        message: format!("{}: {}", NAME, message),
+        location: snafu::Location::new(file!(), line!(), column!()),
+    }
+}
+
+impl WalkOptions {
+    /// The number of walks per node and the seed given in `config`
+    fn try_from_config(config: Option<&ScalarValue>) -> Result<Self> {
+        let mut options = Self::default();
+        let Some(config) = config_struct(NAME, config)? else {
+            return Ok(options);
+        };
+        let field = |name: &str| config_field(config, name);
+
+        if let Some(walks) = field("walksPerNode") {
+            match walks.as_primitive::<Int64Type>().value(0) {
+                n if n > 0 => options.walks_per_node = n as usize,
+                _ => return Err(invalid("walksPerNode must be positive")),
+            }
+        }
+        if let Some(seed) = field("seed") {
+            options.seed = seed.as_primitive::<Int64Type>().value(0) as u64;
+        }
+        Ok(options)
+    }
+}
+
+/// Sorted distinct targets of the edges leaving each node
+fn adjacency(node_count: usize, edges: &[(usize, usize)]) -> Vec<Vec<usize>> {
+    let mut adjacency = vec![Vec::new(); node_count];
+    for &(from, to) in edges {
+        adjacency[from].push(to);
+    }
+    for targets in &mut adjacency {
+        targets.sort_unstable();
+        targets.dedup();
+    }
+    adjacency
+}
+
+/// A walk of at most `steps` steps from `start`, the start included
+fn walk(
+    adjacency: &[Vec<usize>],
+    start: usize,
+    steps: usize,
+    bias: WalkBias,
+    random: &mut SplitMix64,
+) -> Vec<usize> {
+    let mut walk = vec![start];
+    let mut weights: Vec<f64> = Vec::new();
+    for _ in 0..steps {
+        let current = walk[walk.len() - 1];
+        let neighbors = &adjacency[current];
+        if neighbors.is_empty() {
+            break;
+        }
+        let next = match walk.len().checked_sub(2).map(|i| walk[i]) {
+            // The first step is uniform
+            None => neighbors[(random.next_u64() % neighbors.len() as u64) as usize],
+            Some(previous) => {
+                weights.clear();
+                weights.extend(neighbors.iter().map(|&neighbor| {
+                    if neighbor == previous {
+                        1.0 / bias.p
+                    } else if adjacency[previous].binary_search(&neighbor).is_ok() {
+                        1.0
+                    } else {
+                        1.0 / bias.q
+                    }
+                }));
+                let mut target = random.next_f64() * weights.iter().sum::<f64>();
+                let mut chosen = neighbors[neighbors.len() - 1];
+                for (&neighbor, &weight) in neighbors.iter().zip(&weights) {
+                    if target < weight {
+                        chosen = neighbor;
+                        break;
+                    }
+                    target -= weight;
+                }
+                chosen
+            }
+        };
+        walk.push(next);
+    }
+    walk
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const UNBIASED: WalkBias = WalkBias { p: 1.0, q: 1.0 };
+
+    #[test]
+    fn test_walks_follow_edges() {
+        // 0 -> 1 -> 2 -> 0, and 2 -> 3 which leads nowhere
+        let adjacency = adjacency(4, &[(0, 1), (1, 2), (2, 0), (2, 3), (0, 1)]);
+        assert_eq!(adjacency[0], vec![1]);
+        let mut random = SplitMix64::new(7);
+        for _ in 0..20 {
+            let path = walk(&adjacency, 0, 10, UNBIASED, &mut random);
+            assert_eq!(path[..3], [0, 1, 2]);
+            for pair in path.windows(2) {
+                assert!(adjacency[pair[0]].contains(&pair[1]));
+            }
+            // Only a walk that reached the dead end stops early
+            assert!(path.len() == 11 || path.last() == Some(&3));
+        }
+        assert_eq!(walk(&adjacency, 3, 5, UNBIASED, &mut random), vec![3]);
+        assert_eq!(walk(&adjacency, 0, 0, UNBIASED, &mut random), vec![0]);
+    }
+
+    #[test]
+    fn test_walks_are_seeded() {
+        let adjacency = adjacency(3, &[(0, 1), (0, 2), (1, 0), (1, 2), (2, 0), (2, 1)]);
+        let walks = |seed| {
+            let mut random = SplitMix64::new(seed);
+            walk(&adjacency, 0, 50, UNBIASED, &mut random)
+        };
+        assert_eq!(walks(1), walks(1));
+        assert_ne!(walks(1), walks(2));
+    }
+
+    #[test]
+    fn test_return_parameter_biases_walks() {
+        // A star: walks from the center go out to a leaf and can only come back
+        let edges: Vec<(usize, usize)> = (1..5).flat_map(|leaf| [(0, leaf), (leaf, 0)]).collect();
+        let star = adjacency(5, &edges);
+        let mut random = SplitMix64::new(3);
+        let path = walk(&star, 0, 4, UNBIASED, &mut random);
+        assert_eq!(path.len(), 5);
+        assert_eq!((path[0], path[2], path[4]), (0, 0, 0));
+
+        // On a triangle, a high return parameter keeps walks from stepping back
+        let triangle = adjacency(3, &[(0, 1), (0, 2), (1, 0), (1, 2), (2, 0), (2, 1)]);
+        let bias = WalkBias { p: 1e9, q: 1.0 };
+        let mut random = SplitMix64::new(5);
+        let path = walk(&triangle, 0, 30, bias, &mut random);
+        for steps in path.windows(3) {
+            assert_ne!(steps[0], steps[2]);
+        }
+    }
+}
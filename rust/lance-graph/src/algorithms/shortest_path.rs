@@ -0,0 +1,330 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Weighted shortest paths, the `graph.shortestPath.dijkstra` procedure
+//!
+//! Dijkstra's algorithm over the edges of the mapped relationship tables, weighted by a
+//! relationship property. Edges are read lazily: when the search settles a node whose
+//! edges it has not read, it reads those of every node waiting in its queue with one
+//! query per relationship type, filtered by source key like the frontiers of
+//! [`graph.bfs`](super::traversal).
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::sync::Arc;
+
+use arrow::array::{
+    Array, ArrayRef, Float64Array, ListArray, RecordBatch, StringArray, StructArray,
+};
+use arrow::buffer::OffsetBuffer;
+use arrow_schema::{DataType, Field, Fields, Schema, SchemaRef};
+use async_trait::async_trait;
+use datafusion::execution::context::SessionContext;
+use datafusion::scalar::ScalarValue;
+
+use super::{key_type, read_edges, strings, weighted_edge_tables, EdgeTable};
+use crate::error::{GraphError, Result};
+use crate::procedures::{Procedure, ProcedureContext, ProcedureParameter};
+
+const NAME: &str = "graph.shortestPath.dijkstra";
+
+/// `graph.shortestPath.dijkstra(source, target, weightProperty [, relTypes])`: the
+/// `nodes`, `relationships` and total `cost` of a path of least total weight
+#[derive(Debug)]
+pub(crate) struct DijkstraShortestPath;
+
+/// An edge of a shortest path, as traversed
+#[derive(Debug, Clone)]
+struct PathEdge {
+    rel_type: String,
+    from: ScalarValue,
+    to: ScalarValue,
+    weight: f64,
+}
+
+/// A node waiting in the queue at a tentative distance; the heap pops the nearest,
+/// then the earliest queued
+#[derive(Debug, PartialEq)]
+struct Queued {
+    cost: f64,
+    seq: usize,
+}
+
+impl Eq for Queued {}
+
+impl Ord for Queued {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .cost
+            .total_cmp(&self.cost)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+impl PartialOrd for Queued {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[async_trait]
+impl Procedure for DijkstraShortestPath {
+    fn name(&self) -> &str {
+        NAME
+    }
+
+    fn parameters(&self) -> Vec<ProcedureParameter> {
+        vec![
+            ProcedureParameter::new("source", DataType::Utf8),
+            ProcedureParameter::new("target", DataType::Utf8),
+            ProcedureParameter::new("weightProperty", DataType::Utf8),
+            ProcedureParameter::new("relTypes", DataType::new_list(DataType::Utf8, true))
+                .with_default(ScalarValue::List(ScalarValue::new_list_nullable(
+                    &[],
+                    &DataType::Utf8,
+                ))),
+        ]
+    }
+
+    fn output_schema(&self, ctx: &ProcedureContext) -> Result<SchemaRef> {
+        self.output_schema_for(ctx, &[])
+    }
+
+    fn output_schema_for(
+        &self,
+        ctx: &ProcedureContext,
+        args: &[Option<ScalarValue>],
+    ) -> Result<SchemaRef> {
+        let weight = match args.get(2) {
+            Some(Some(ScalarValue::Utf8(Some(weight)))) => Some(weight.as_str()),
+            _ => None,
+        };
+        let rel_types = match args.get(3) {
+            Some(Some(value)) => strings(value).ok_or_else(|| GraphError::PlanError {
+                message: format!("{}: relTypes must be a list of strings", NAME),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            })?,
+            _ => Vec::new(),
+        };
+        let edges: Vec<EdgeTable> = weighted_edge_tables(NAME, ctx, rel_types, weight)?
+            .into_iter()
+            .map(|(edge, _)| edge)
+            .collect();
+        Ok(output_schema(key_type(NAME, &edges)?))
+    }
+
+    async fn call(&self, ctx: &ProcedureContext, args: Vec<ScalarValue>) -> Result<RecordBatch> {
+        let known: Vec<Option<ScalarValue>> = args.iter().cloned().map(Some).collect();
+        let schema = self.output_schema_for(ctx, &known)?;
+        let key_type = match schema.field(0).data_type() {
+            DataType::List(item) => item.data_type().clone(),
+            other => other.clone(),
+        };
+        let invalid = |message: String| GraphError::ExecutionError {
+            message: format!("{}: {}", NAME, message),
+            location: snafu::Location::new(file!(), line!(), column!()),
+        };
+        let node = |i: usize, name: &str| match args.get(i) {
+            Some(value) if !value.is_null() => value
+                .cast_to(&key_type)
+                .map_err(|e| invalid(format!("{} is not a {} key: {}", name, key_type, e))),
+            _ => Err(invalid(format!("{} must be a node key", name))),
+        };
+        let (source, target) = (node(0, "source")?, node(1, "target")?);
+        let weight = match args.get(2) {
+            Some(ScalarValue::Utf8(Some(weight))) => weight.as_str(),
+            _ => return Err(invalid("weightProperty must be a string".to_string())),
+        };
+        let rel_types = args.get(3).and_then(strings).unwrap_or_default();
+        let edges = weighted_edge_tables(NAME, ctx, rel_types, Some(weight))?;
+
+        let session = ctx.session().cloned().unwrap_or_default();
+        match dijkstra(&session, &edges, &source, &target).await? {
+            Some(path) => path_batch(schema, &key_type, &source, &path),
+            None => Ok(RecordBatch::new_empty(schema)),
+        }
+    }
+}
+
+fn output_schema(key_type: DataType) -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("nodes", DataType::new_list(key_type.clone(), true), false),
+        Field::new(
+            "relationships",
+            DataType::new_list(DataType::Struct(relationship_fields(key_type)), true),
+            false,
+        ),
+        Field::new("cost", DataType::Float64, false),
+    ]))
+}
+
+/// Fields of each relationship of a path: its type, the nodes it leads from and to in
+/// path order, and its weight
+fn relationship_fields(key_type: DataType) -> Fields {
+    Fields::from(vec![
+        Field::new("type", DataType::Utf8, false),
+        Field::new("source", key_type.clone(), false),
+        Field::new("target", key_type, false),
+        Field::new("weight", DataType::Float64, false),
+    ])
+}
+
+/// The edges of a path of least total weight from `source` to `target`, or `None` if
+/// `target` is unreachable
+///
+/// Edges without a weight are not traversed; a negative weight fails the search.
+async fn dijkstra(
+    session: &SessionContext,
+    edges: &[(EdgeTable, String)],
+    source: &ScalarValue,
+    target: &ScalarValue,
+) -> Result<Option<Vec<PathEdge>>> {
+    let mut adjacency: HashMap<ScalarValue, Vec<PathEdge>> = HashMap::new();
+    let mut distances: HashMap<ScalarValue, f64> = HashMap::from([(source.clone(), 0.0)]);
+    let mut previous: HashMap<ScalarValue, PathEdge> = HashMap::new();
+    let mut settled: HashSet<ScalarValue> = HashSet::new();
+    let mut queued: Vec<ScalarValue> = vec![source.clone()];
+    let mut heap = BinaryHeap::from([Queued { cost: 0.0, seq: 0 }]);
+
+    while let Some(Queued { cost, seq }) = heap.pop() {
+        let node = queued[seq].clone();
+        if !settled.insert(node.clone()) {
+            continue;
+        }
+        if &node == target {
+            let mut path = Vec::new();
+            let mut at = node;
+            while let Some(edge) = previous.get(&at) {
+                at = edge.from.clone();
+                path.push(edge.clone());
+            }
+            path.reverse();
+            return Ok(Some(path));
+        }
+
+        if !adjacency.contains_key(&node) {
+            let mut unread: Vec<ScalarValue> = std::iter::once(node.clone())
+                .chain(heap.iter().map(|waiting| queued[waiting.seq].clone()))
+                .filter(|waiting| !settled.contains(waiting) || waiting == &node)
+                .filter(|waiting| !adjacency.contains_key(waiting))
+                .collect();
+            unread.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+            unread.dedup();
+            for waiting in &unread {
+                adjacency.insert(waiting.clone(), Vec::new());
+            }
+            for (table, weight) in edges {
+                for edge in read_edges(session, table, Some(&unread), Some(weight)).await? {
+                    let Some(w) = edge.weight else { continue };
+                    if w < 0.0 {
+                        return Err(GraphError::ExecutionError {
+                            message: format!(
+                                "{}: relationship '{}' from {} to {} has negative weight {}",
+                                NAME, table.rel_type, edge.from, edge.to, w
+                            ),
+                            location: snafu::Location::new(file!(), line!(), column!()),
+                        });
+                    }
+                    adjacency
+                        .entry(edge.from.clone())
+                        .or_default()
+                        .push(PathEdge {
+                            rel_type: table.rel_type.clone(),
+                            from: edge.from,
+                            to: edge.to,
+                            weight: w,
+                        });
+                }
+            }
+        }
+
+        for edge in &adjacency[&node] {
+            let distance = cost + edge.weight;
+            if settled.contains(&edge.to)
+                || distances
+                    .get(&edge.to)
+                    .is_some_and(|&known| known <= distance)
+            {
+                continue;
+            }
+            distances.insert(edge.to.clone(), distance);
+            previous.insert(edge.to.clone(), edge.clone());
+            heap.push(Queued {
+                cost: distance,
+                seq: queued.len(),
+            });
+            queued.push(edge.to.clone());
+        }
+    }
+    Ok(None)
+}
+
+/// The one row describing `path`
+fn path_batch(
+    schema: SchemaRef,
+    key_type: &DataType,
+    source: &ScalarValue,
+    path: &[PathEdge],
+) -> Result<RecordBatch> {
+    let nodes: Vec<ScalarValue> = std::iter::once(source.clone())
+        .chain(path.iter().map(|edge| edge.to.clone()))
+        .collect();
+    let nodes: ArrayRef = ScalarValue::new_list_nullable(&nodes, key_type);
+
+    let keys = |values: Vec<ScalarValue>| -> Result<ArrayRef> {
+        Ok(if values.is_empty() {
+            arrow::array::new_empty_array(key_type)
+        } else {
+            ScalarValue::iter_to_array(values)?
+        })
+    };
+    let relationships = StructArray::try_new(
+        relationship_fields(key_type.clone()),
+        vec![
+            Arc::new(StringArray::from_iter_values(
+                path.iter().map(|edge| edge.rel_type.as_str()),
+            )),
+            keys(path.iter().map(|edge| edge.from.clone()).collect())?,
+            keys(path.iter().map(|edge| edge.to.clone()).collect())?,
+            Arc::new(Float64Array::from_iter_values(
+                path.iter().map(|edge| edge.weight),
+            )),
+        ],
+        None,
+    )?;
+    let relationships = ListArray::try_new(
+        Arc::new(Field::new_list_field(
+            relationships.data_type().clone(),
+            true,
+        )),
+        OffsetBuffer::from_lengths([path.len()]),
+        Arc::new(relationships),
+        None,
+    )?;
+    let cost = path.iter().map(|edge| edge.weight).sum::<f64>();
+
+    Ok(RecordBatch::try_new(
+        schema,
+        vec![
+            nodes,
+            Arc::new(relationships),
+            Arc::new(Float64Array::from(vec![cost])),
+        ],
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_queue_pops_nearest_then_earliest() {
+        let mut heap = BinaryHeap::from([
+            Queued { cost: 2.0, seq: 0 },
+            Queued { cost: 1.0, seq: 2 },
+            Queued { cost: 1.0, seq: 1 },
+        ]);
+        let order: Vec<usize> = std::iter::from_fn(|| heap.pop().map(|q| q.seq)).collect();
+        assert_eq!(order, vec![1, 2, 0]);
+    }
+}
@@ -0,0 +1,313 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Node similarity, the `graph.nodeSimilarity` procedure
+//!
+//! Nodes are compared by the sets of nodes their relationships lead to, read into an
+//! in-memory [`Projection`]: people by the products they bought, say. Only pairs with a
+//! neighbor in common are compared, found through the nodes that lead to each
+//! neighbor, and scored with the Jaccard index (shared neighbors over all of either)
+//! or the overlap coefficient (shared neighbors over those of the smaller set).
+
+use std::cmp::Ordering;
+use std::sync::Arc;
+
+use arrow::array::{AsArray, Float64Array, RecordBatch};
+use arrow::datatypes::{Float64Type, Int64Type};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use async_trait::async_trait;
+use datafusion::scalar::ScalarValue;
+
+use super::projection::AlgorithmGraph;
+use super::{config_field, config_struct, Projection};
+use crate::error::{GraphError, Result};
+use crate::procedures::{Procedure, ProcedureContext, ProcedureParameter};
+
+const NAME: &str = "graph.nodeSimilarity";
+
+/// `graph.nodeSimilarity([config])`: the `node1`, `node2` and `similarity` of the most
+/// similar nodes of each node
+#[derive(Debug)]
+pub(crate) struct NodeSimilarity;
+
+/// How the neighbor sets of two nodes are compared
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum SimilarityMetric {
+    #[default]
+    Jaccard,
+    Overlap,
+}
+
+impl SimilarityMetric {
+    /// Similarity of sets of `a` and `b` nodes with `shared` nodes in common
+    fn score(self, shared: usize, a: usize, b: usize) -> f64 {
+        let denominator = match self {
+            SimilarityMetric::Jaccard => a + b - shared,
+            SimilarityMetric::Overlap => a.min(b),
+        };
+        shared as f64 / denominator as f64
+    }
+}
+
+/// Options of one run, from the `config` map
+#[derive(Debug, Clone, PartialEq)]
+struct SimilarityOptions {
+    metric: SimilarityMetric,
+    /// Most similar nodes kept per node
+    top_k: usize,
+    /// Least similarity of a pair to keep
+    cutoff: f64,
+    /// Fewest neighbors of a node to compare it
+    degree_cutoff: usize,
+}
+
+impl Default for SimilarityOptions {
+    fn default() -> Self {
+        Self {
+            metric: SimilarityMetric::default(),
+            top_k: 10,
+            cutoff: 0.0,
+            degree_cutoff: 1,
+        }
+    }
+}
+
+#[async_trait]
+impl Procedure for NodeSimilarity {
+    fn name(&self) -> &str {
+        NAME
+    }
+
+    fn parameters(&self) -> Vec<ProcedureParameter> {
+        vec![AlgorithmGraph::config_parameter(vec![
+            Field::new("similarityMetric", DataType::Utf8, true),
+            Field::new("topK", DataType::Int64, true),
+            Field::new("similarityCutoff", DataType::Float64, true),
+            Field::new("degreeCutoff", DataType::Int64, true),
+        ])]
+    }
+
+    fn output_schema(&self, ctx: &ProcedureContext) -> Result<SchemaRef> {
+        self.output_schema_for(ctx, &[])
+    }
+
+    fn output_schema_for(
+        &self,
+        ctx: &ProcedureContext,
+        args: &[Option<ScalarValue>],
+    ) -> Result<SchemaRef> {
+        let graph = AlgorithmGraph::from_config(NAME, ctx, args.first().and_then(Option::as_ref))?;
+        let key_type = graph.key_type(NAME)?;
+        Ok(Arc::new(Schema::new(vec![
+            Field::new("node1", key_type.clone(), false),
+            Field::new("node2", key_type, false),
+            Field::new("similarity", DataType::Float64, false),
+        ])))
+    }
+
+    async fn call(&self, ctx: &ProcedureContext, args: Vec<ScalarValue>) -> Result<RecordBatch> {
+        let known: Vec<Option<ScalarValue>> = args.iter().cloned().map(Some).collect();
+        let schema = self.output_schema_for(ctx, &known)?;
+        let options = SimilarityOptions::try_from_config(args.first())?;
+        let session = ctx.session().cloned().unwrap_or_default();
+        let graph = AlgorithmGraph::from_config(NAME, ctx, args.first())?;
+        let Projection { nodes, edges } = graph.read(&session).await?;
+
+        let mut pairs = similar_pairs(nodes.len(), &edges, &options);
+        // By first node key, then from most similar to least and by second node key
+        let by_key =
+            |a: usize, b: usize| nodes[a].partial_cmp(&nodes[b]).unwrap_or(Ordering::Equal);
+        pairs.sort_by(|x, y| {
+            by_key(x.0, y.0)
+                .then(y.2.total_cmp(&x.2))
+                .then_with(|| by_key(x.1, y.1))
+        });
+
+        if pairs.is_empty() {
+            return Ok(RecordBatch::new_empty(schema));
+        }
+        let node1 = ScalarValue::iter_to_array(pairs.iter().map(|p| nodes[p.0].clone()))?;
+        let node2 = ScalarValue::iter_to_array(pairs.iter().map(|p| nodes[p.1].clone()))?;
+        let similarity = Float64Array::from_iter_values(pairs.iter().map(|p| p.2));
+        Ok(RecordBatch::try_new(
+            schema,
+            vec![node1, node2, Arc::new(similarity)],
+        )?)
+    }
+}
+
+impl SimilarityOptions {
+    /// The metric, cutoffs and number of neighbors kept given in `config`
+    fn try_from_config(config: Option<&ScalarValue>) -> Result<Self> {
+        let invalid = |message: &str| GraphError::ExecutionError {
+            message: format!("{}: {}", NAME, message),
+            location: snafu::Location::new(file!(), line!(), column!()),
+        };
+        let mut options = Self::default();
+        let Some(config) = config_struct(NAME, config)? else {
+            return Ok(options);
+        };
+        let field = |name: &str| config_field(config, name);
+
+        if let Some(metric) = field("similarityMetric") {
+            let metric = metric.as_string::<i32>().value(0);
+            options.metric = match metric.to_lowercase().as_str() {
+                "jaccard" => SimilarityMetric::Jaccard,
+                "overlap" => SimilarityMetric::Overlap,
+                _ => {
+                    return Err(invalid(&format!(
+                        "similarityMetric must be 'jaccard' or 'overlap', not '{}'",
+                        metric
+                    )))
+                }
+            };
+        }
+        if let Some(top_k) = field("topK") {
+            match top_k.as_primitive::<Int64Type>().value(0) {
+                k if k > 0 => options.top_k = k as usize,
+                _ => return Err(invalid("topK must be positive")),
+            }
+        }
+        if let Some(cutoff) = field("similarityCutoff") {
+            options.cutoff = cutoff.as_primitive::<Float64Type>().value(0);
+            if !(0.0..=1.0).contains(&options.cutoff) {
+                return Err(invalid("similarityCutoff must be between 0 and 1"));
+            }
+        }
+        if let Some(degree) = field("degreeCutoff") {
+            match degree.as_primitive::<Int64Type>().value(0) {
+                d if d > 0 => options.degree_cutoff = d as usize,
+                _ => return Err(invalid("degreeCutoff must be positive")),
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// The `top_k` most similar nodes of every node with at least `degree_cutoff` distinct
+/// neighbors, as `(node, other, similarity)`, each pair in both orders
+fn similar_pairs(
+    node_count: usize,
+    edges: &[(usize, usize)],
+    options: &SimilarityOptions,
+) -> Vec<(usize, usize, f64)> {
+    let mut neighbors = vec![Vec::new(); node_count];
+    for &(from, to) in edges {
+        neighbors[from].push(to);
+    }
+    for list in &mut neighbors {
+        list.sort_unstable();
+        list.dedup();
+    }
+    let compared = |node: usize| neighbors[node].len() >= options.degree_cutoff;
+    // The compared nodes leading to each neighbor
+    let mut sources = vec![Vec::new(); node_count];
+    for (node, list) in neighbors.iter().enumerate() {
+        if compared(node) {
+            for &neighbor in list {
+                sources[neighbor].push(node);
+            }
+        }
+    }
+
+    let mut pairs = Vec::new();
+    let mut shared = vec![0usize; node_count];
+    let mut touched: Vec<usize> = Vec::new();
+    for node in (0..node_count).filter(|&node| compared(node)) {
+        for &neighbor in &neighbors[node] {
+            for &other in &sources[neighbor] {
+                if other != node {
+                    if shared[other] == 0 {
+                        touched.push(other);
+                    }
+                    shared[other] += 1;
+                }
+            }
+        }
+        let mut similar: Vec<(usize, f64)> = touched
+            .drain(..)
+            .map(|other| {
+                let count = std::mem::take(&mut shared[other]);
+                let score =
+                    options
+                        .metric
+                        .score(count, neighbors[node].len(), neighbors[other].len());
+                (other, score)
+            })
+            .filter(|&(_, score)| score >= options.cutoff)
+            .collect();
+        similar.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        similar.truncate(options.top_k);
+        pairs.extend(
+            similar
+                .into_iter()
+                .map(|(other, score)| (node, other, score)),
+        );
+    }
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Buyers 0, 1 and 2 of products 3 to 6: 0 bought 3, 4 and 5; 1 bought 3 and 4
+    /// (twice); 2 bought 5 and 6
+    fn purchases() -> Vec<(usize, usize)> {
+        vec![
+            (0, 3),
+            (0, 4),
+            (0, 5),
+            (1, 3),
+            (1, 4),
+            (1, 4),
+            (2, 5),
+            (2, 6),
+        ]
+    }
+
+    #[test]
+    fn test_metrics() {
+        assert_eq!(SimilarityMetric::Jaccard.score(2, 3, 2), 2.0 / 3.0);
+        assert_eq!(SimilarityMetric::Overlap.score(2, 3, 2), 1.0);
+        assert_eq!(SimilarityMetric::Jaccard.score(1, 3, 2), 0.25);
+    }
+
+    #[test]
+    fn test_similar_pairs() {
+        let pairs = similar_pairs(7, &purchases(), &SimilarityOptions::default());
+        assert_eq!(
+            pairs,
+            vec![
+                (0, 1, 2.0 / 3.0),
+                (0, 2, 0.25),
+                (1, 0, 2.0 / 3.0),
+                (2, 0, 0.25),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_similar_pairs_options() {
+        let options = SimilarityOptions {
+            metric: SimilarityMetric::Overlap,
+            top_k: 1,
+            ..Default::default()
+        };
+        let pairs = similar_pairs(7, &purchases(), &options);
+        assert_eq!(pairs, vec![(0, 1, 1.0), (1, 0, 1.0), (2, 0, 0.5)]);
+
+        let options = SimilarityOptions {
+            cutoff: 0.5,
+            degree_cutoff: 2,
+            ..Default::default()
+        };
+        let pairs = similar_pairs(7, &purchases(), &options);
+        assert_eq!(pairs, vec![(0, 1, 2.0 / 3.0), (1, 0, 2.0 / 3.0)]);
+        let options = SimilarityOptions {
+            degree_cutoff: 3,
+            ..Default::default()
+        };
+        assert!(similar_pairs(7, &purchases(), &options).is_empty());
+    }
+}
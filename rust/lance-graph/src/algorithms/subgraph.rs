@@ -0,0 +1,554 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Bounded subgraph extraction
+//!
+//! A [`SubgraphQuery`] extracts the subgraph induced by the nodes within `k` hops of a
+//! set of seed nodes: those nodes, and every relationship between two of them. The
+//! neighborhood is expanded level by level as `graph.bfs` expands it, each hop reading
+//! the relationships of the nodes it newly reached with a query filtered by their keys;
+//! the relationships of the last level are read only to close the subgraph. The
+//! [`Subgraph`] is returned as a pair of Arrow tables, which [`Subgraph::write_to`]
+//! writes to Lance datasets, and the `graph.subgraph` procedure yields it as lists.
+//!
+//! ```ignore
+//! let subgraph = SubgraphQuery::new(config, [ScalarValue::from(42i64)], 2)
+//!     .with_relationship_types(["KNOWS"])
+//!     .execute_with_catalog(catalog)
+//!     .await?;
+//! subgraph
+//!     .write_to("nodes.lance", "relationships.lance", WriteMode::Create)
+//!     .await?;
+//! ```
+
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use arrow::array::{Array, ArrayRef, AsArray, Int64Array, ListArray, RecordBatch};
+use arrow::array::{StringArray, StructArray};
+use arrow::buffer::OffsetBuffer;
+use arrow_schema::{DataType, Field, Fields, Schema, SchemaRef};
+use async_trait::async_trait;
+use datafusion::execution::context::SessionContext;
+use datafusion::scalar::ScalarValue;
+use lance::dataset::{InsertBuilder, WriteMode, WriteParams};
+use lance::Dataset;
+
+use super::{
+    config_field, config_relationship_types, config_struct, key_type, read_edges,
+    traversable_edge_tables, EdgeTable,
+};
+use crate::ast::RelationshipDirection;
+use crate::config::GraphConfig;
+use crate::error::{GraphError, Result};
+use crate::procedures::{Procedure, ProcedureContext, ProcedureParameter};
+use crate::source_catalog::GraphSourceCatalog;
+
+const NAME: &str = "graph.subgraph";
+
+/// The nodes within a number of hops of seed nodes, and the relationships between them
+#[derive(Debug, Clone)]
+pub struct SubgraphQuery {
+    config: GraphConfig,
+    seeds: Vec<ScalarValue>,
+    max_hops: usize,
+    rel_types: Vec<String>,
+    direction: RelationshipDirection,
+}
+
+impl SubgraphQuery {
+    /// Extract the nodes within `max_hops` hops of the `seeds` node keys, following
+    /// relationships of every mapped type in either direction
+    pub fn new(
+        config: GraphConfig,
+        seeds: impl IntoIterator<Item = ScalarValue>,
+        max_hops: usize,
+    ) -> Self {
+        Self {
+            config,
+            seeds: seeds.into_iter().collect(),
+            max_hops,
+            rel_types: Vec::new(),
+            direction: RelationshipDirection::Undirected,
+        }
+    }
+
+    /// Follow and keep only relationships of these types
+    pub fn with_relationship_types(
+        mut self,
+        rel_types: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.rel_types = rel_types.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Expand only along relationships leaving (`Outgoing`) or entering (`Incoming`) the
+    /// nodes reached; relationships between the nodes are kept either way
+    pub fn with_direction(mut self, direction: RelationshipDirection) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    /// Extract the subgraph from the tables of `catalog`
+    pub async fn execute_with_catalog(
+        &self,
+        catalog: Arc<dyn GraphSourceCatalog>,
+    ) -> Result<Subgraph> {
+        let ctx = ProcedureContext::new(self.config.clone(), Some(catalog));
+        extract(&SessionContext::new(), &ctx, self).await
+    }
+}
+
+/// An extracted subgraph
+#[derive(Debug, Clone)]
+pub struct Subgraph {
+    /// The `node` key of every node and its `depth`, the hops from the nearest seed, by
+    /// depth and then by key
+    pub nodes: RecordBatch,
+    /// The `type` and the `source` and `target` node keys of every relationship between
+    /// two of the nodes, by type and then by keys; parallel relationships of a type
+    /// appear once
+    pub relationships: RecordBatch,
+}
+
+impl Subgraph {
+    /// Write the nodes and relationships to the Lance datasets at `nodes_uri` and
+    /// `relationships_uri`, returning the datasets written
+    pub async fn write_to(
+        &self,
+        nodes_uri: &str,
+        relationships_uri: &str,
+        mode: WriteMode,
+    ) -> Result<(Dataset, Dataset)> {
+        let params = WriteParams {
+            mode,
+            ..Default::default()
+        };
+        let nodes = InsertBuilder::new(nodes_uri)
+            .with_params(&params)
+            .execute(vec![self.nodes.clone()])
+            .await?;
+        let relationships = InsertBuilder::new(relationships_uri)
+            .with_params(&params)
+            .execute(vec![self.relationships.clone()])
+            .await?;
+        Ok((nodes, relationships))
+    }
+}
+
+fn node_schema(key_type: DataType) -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("node", key_type, false),
+        Field::new("depth", DataType::Int64, false),
+    ]))
+}
+
+/// Fields of each relationship: its type and the nodes it leads from and to
+fn relationship_fields(key_type: DataType) -> Fields {
+    Fields::from(vec![
+        Field::new("type", DataType::Utf8, false),
+        Field::new("source", key_type.clone(), false),
+        Field::new("target", key_type, false),
+    ])
+}
+
+/// The edge tables of the relationship types, read from source to target keys as
+/// stored, each with whether its type is undirected
+fn stored_edge_tables(
+    ctx: &ProcedureContext,
+    rel_types: Vec<String>,
+) -> Result<Vec<(EdgeTable, bool)>> {
+    let mut stored = Vec::new();
+    for table in traversable_edge_tables(NAME, ctx, rel_types)? {
+        let Some(mapping) = ctx.config().get_relationship_mapping(&table.rel_type) else {
+            continue;
+        };
+        let ([source], [_]) = (
+            &mapping.source_id_fields()[..],
+            &mapping.target_id_fields()[..],
+        ) else {
+            return Err(GraphError::PlanError {
+                message: format!(
+                    "{}: relationship type '{}' has a composite key",
+                    NAME, table.rel_type
+                ),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            });
+        };
+        // Undirected types are read both ways anyway
+        if table.from == *source {
+            stored.push((table, mapping.undirected));
+        }
+    }
+    Ok(stored)
+}
+
+async fn extract(
+    session: &SessionContext,
+    ctx: &ProcedureContext,
+    query: &SubgraphQuery,
+) -> Result<Subgraph> {
+    let tables = stored_edge_tables(ctx, query.rel_types.clone())?;
+    // Each table read from its target keys, for the relationships entering a node
+    let entering: Vec<EdgeTable> = tables.iter().map(|(table, _)| reversed(table)).collect();
+    let key_type = key_type(NAME, &entering)?;
+    let seeds = query
+        .seeds
+        .iter()
+        .map(|seed| seed.cast_to(&key_type))
+        .collect::<datafusion::common::Result<Vec<_>>>()
+        .map_err(|e| GraphError::ExecutionError {
+            message: format!("{}: seed is not a {} key: {}", NAME, key_type, e),
+            location: snafu::Location::new(file!(), line!(), column!()),
+        })?;
+
+    let mut extraction = Extraction::new(&seeds, query.max_hops);
+    while !extraction.frontier.is_empty() {
+        for (index, (table, undirected)) in tables.iter().enumerate() {
+            if query.direction != RelationshipDirection::Incoming || *undirected {
+                for edge in read_edges(session, table, Some(&extraction.frontier), None).await? {
+                    extraction.visit(index, edge.from, edge.to, true);
+                }
+            }
+            if query.direction != RelationshipDirection::Outgoing || *undirected {
+                let table = &entering[index];
+                for edge in read_edges(session, table, Some(&extraction.frontier), None).await? {
+                    extraction.visit(index, edge.to, edge.from, false);
+                }
+            }
+        }
+        extraction.advance();
+    }
+    let rel_types: Vec<&str> = tables
+        .iter()
+        .map(|(table, _)| table.rel_type.as_str())
+        .collect();
+    extraction.into_subgraph(&rel_types, &key_type)
+}
+
+/// `table` read from its target keys
+fn reversed(table: &EdgeTable) -> EdgeTable {
+    EdgeTable {
+        rel_type: table.rel_type.clone(),
+        plan: table.plan.clone(),
+        from: table.to.clone(),
+        to: table.from.clone(),
+    }
+}
+
+/// The nodes and relationships found so far, level by level
+struct Extraction {
+    max_hops: usize,
+    depth: usize,
+    /// Nodes whose relationships the current level reads
+    frontier: Vec<ScalarValue>,
+    next: Vec<ScalarValue>,
+    depths: HashMap<ScalarValue, usize>,
+    /// Relationships as the index of their table and their source and target keys
+    relationships: HashSet<(usize, ScalarValue, ScalarValue)>,
+}
+
+impl Extraction {
+    fn new(seeds: &[ScalarValue], max_hops: usize) -> Self {
+        let mut depths = HashMap::new();
+        let frontier = seeds
+            .iter()
+            .filter(|seed| depths.insert((*seed).clone(), 0).is_none())
+            .cloned()
+            .collect();
+        Self {
+            max_hops,
+            depth: 0,
+            frontier,
+            next: Vec::new(),
+            depths,
+            relationships: HashSet::new(),
+        }
+    }
+
+    /// Record a relationship of table `table` read from a frontier node: its `source`
+    /// if `forward`, else its `target`
+    fn visit(&mut self, table: usize, source: ScalarValue, target: ScalarValue, forward: bool) {
+        let other = if forward { &target } else { &source };
+        if !self.depths.contains_key(other) {
+            if self.depth == self.max_hops {
+                return;
+            }
+            self.depths.insert(other.clone(), self.depth + 1);
+            self.next.push(other.clone());
+        }
+        self.relationships.insert((table, source, target));
+    }
+
+    /// Move on to the nodes the current level reached, if within `max_hops`
+    fn advance(&mut self) {
+        self.frontier = if self.depth < self.max_hops {
+            std::mem::take(&mut self.next)
+        } else {
+            Vec::new()
+        };
+        self.depth += 1;
+    }
+
+    fn into_subgraph(self, rel_types: &[&str], key_type: &DataType) -> Result<Subgraph> {
+        let by_key = |a: &ScalarValue, b: &ScalarValue| a.partial_cmp(b).unwrap_or(Ordering::Equal);
+        let mut nodes: Vec<(ScalarValue, usize)> = self.depths.into_iter().collect();
+        nodes.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| by_key(&a.0, &b.0)));
+        let mut relationships: Vec<(usize, ScalarValue, ScalarValue)> =
+            self.relationships.into_iter().collect();
+        relationships.sort_by(|a, b| {
+            rel_types[a.0]
+                .cmp(rel_types[b.0])
+                .then_with(|| by_key(&a.1, &b.1))
+                .then_with(|| by_key(&a.2, &b.2))
+        });
+
+        let keys = |values: Vec<ScalarValue>| -> Result<ArrayRef> {
+            Ok(if values.is_empty() {
+                arrow::array::new_empty_array(key_type)
+            } else {
+                ScalarValue::iter_to_array(values)?
+            })
+        };
+        let depths = Int64Array::from_iter_values(nodes.iter().map(|&(_, depth)| depth as i64));
+        let nodes = RecordBatch::try_new(
+            node_schema(key_type.clone()),
+            vec![
+                keys(nodes.into_iter().map(|(node, _)| node).collect())?,
+                Arc::new(depths),
+            ],
+        )?;
+        let types = StringArray::from_iter_values(relationships.iter().map(|r| rel_types[r.0]));
+        let sources = keys(relationships.iter().map(|r| r.1.clone()).collect())?;
+        let targets = keys(relationships.into_iter().map(|r| r.2).collect())?;
+        let relationships = RecordBatch::try_new(
+            Arc::new(Schema::new(relationship_fields(key_type.clone()))),
+            vec![Arc::new(types), sources, targets],
+        )?;
+        Ok(Subgraph {
+            nodes,
+            relationships,
+        })
+    }
+}
+
+/// `graph.subgraph(seeds, maxHops [, config])`: the `nodes` and `relationships` of the
+/// subgraph within `maxHops` hops of the seed nodes, as one row of lists
+#[derive(Debug)]
+pub(crate) struct ExtractSubgraph;
+
+fn config_fields() -> Fields {
+    Fields::from(vec![
+        Field::new(
+            "relationshipTypes",
+            DataType::new_list(DataType::Utf8, true),
+            true,
+        ),
+        Field::new("direction", DataType::Utf8, true),
+    ])
+}
+
+/// Relationship types and direction from the `config` struct
+fn options(config: Option<&ScalarValue>) -> Result<(Vec<String>, RelationshipDirection)> {
+    let mut direction = RelationshipDirection::Undirected;
+    let Some(config) = config_struct(NAME, config)? else {
+        return Ok((Vec::new(), direction));
+    };
+    if let Some(value) = config_field(config, "direction") {
+        let value = value.as_string::<i32>().value(0);
+        direction = match value.to_lowercase().as_str() {
+            "out" => RelationshipDirection::Outgoing,
+            "in" => RelationshipDirection::Incoming,
+            "both" => RelationshipDirection::Undirected,
+            _ => {
+                return Err(invalid(&format!(
+                    "direction must be 'out', 'in' or 'both', not '{}'",
+                    value
+                )))
+            }
+        };
+    }
+    Ok((config_relationship_types(config), direction))
+}
+
+fn invalid(message: &str) -> GraphError {
+    GraphError::ExecutionError {
+        message: format!("{}: {}", NAME, message),
+        location: snafu::Location::new(file!(), line!(), column!()),
+    }
+}
+
+#[async_trait]
+impl Procedure for ExtractSubgraph {
+    fn name(&self) -> &str {
+        NAME
+    }
+
+    fn parameters(&self) -> Vec<ProcedureParameter> {
+        let config = DataType::Struct(config_fields());
+        let default = ScalarValue::try_from(&config).unwrap_or(ScalarValue::Null);
+        vec![
+            ProcedureParameter::new("seeds", DataType::new_list(DataType::Utf8, true)),
+            ProcedureParameter::new("maxHops", DataType::Int64),
+            ProcedureParameter::new("config", config).with_default(default),
+        ]
+    }
+
+    fn output_schema(&self, ctx: &ProcedureContext) -> Result<SchemaRef> {
+        self.output_schema_for(ctx, &[])
+    }
+
+    fn output_schema_for(
+        &self,
+        ctx: &ProcedureContext,
+        args: &[Option<ScalarValue>],
+    ) -> Result<SchemaRef> {
+        let (rel_types, _) = options(args.get(2).and_then(Option::as_ref))?;
+        let tables = traversable_edge_tables(NAME, ctx, rel_types)?;
+        let key_type = key_type(NAME, &tables)?;
+        Ok(Arc::new(Schema::new(vec![
+            Field::new("nodes", DataType::new_list(key_type.clone(), true), false),
+            Field::new(
+                "relationships",
+                DataType::new_list(DataType::Struct(relationship_fields(key_type)), true),
+                false,
+            ),
+        ])))
+    }
+
+    async fn call(&self, ctx: &ProcedureContext, args: Vec<ScalarValue>) -> Result<RecordBatch> {
+        let known: Vec<Option<ScalarValue>> = args.iter().cloned().map(Some).collect();
+        let schema = self.output_schema_for(ctx, &known)?;
+        let seeds = match args.first() {
+            Some(ScalarValue::List(list)) if !list.is_null(0) => {
+                let values = list.value(0);
+                (0..values.len())
+                    .filter(|&i| values.is_valid(i))
+                    .map(|i| ScalarValue::try_from_array(&values, i))
+                    .collect::<datafusion::common::Result<Vec<_>>>()?
+            }
+            _ => return Err(invalid("seeds must be a list of node keys")),
+        };
+        let max_hops = match args.get(1) {
+            Some(ScalarValue::Int64(Some(hops))) if *hops >= 0 => *hops as usize,
+            _ => return Err(invalid("maxHops must be a non-negative integer")),
+        };
+        let (rel_types, direction) = options(args.get(2))?;
+        let query = SubgraphQuery {
+            config: ctx.config().clone(),
+            seeds,
+            max_hops,
+            rel_types,
+            direction,
+        };
+        let session = ctx.session().cloned().unwrap_or_default();
+        let Subgraph {
+            nodes,
+            relationships,
+        } = extract(&session, ctx, &query).await?;
+
+        let list = |values: ArrayRef| -> Result<ArrayRef> {
+            Ok(Arc::new(ListArray::try_new(
+                Arc::new(Field::new_list_field(values.data_type().clone(), true)),
+                OffsetBuffer::from_lengths([values.len()]),
+                values,
+                None,
+            )?))
+        };
+        let relationships: ArrayRef = Arc::new(StructArray::from(relationships));
+        Ok(RecordBatch::try_new(
+            schema,
+            vec![list(nodes.column(0).clone())?, list(relationships)?],
+        )?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `(node, depth)` pairs and `(source, target)` pairs of a subgraph
+    type Extracted = (Vec<(i64, usize)>, Vec<(i64, i64)>);
+
+    /// Extract from `edges` of one type, reading them the way [`extract`] does
+    fn extract_from(
+        edges: &[(i64, i64)],
+        seeds: &[i64],
+        max_hops: usize,
+        direction: RelationshipDirection,
+    ) -> Extracted {
+        let key = |k: i64| ScalarValue::Int64(Some(k));
+        let seeds: Vec<ScalarValue> = seeds.iter().map(|&k| key(k)).collect();
+        let mut extraction = Extraction::new(&seeds, max_hops);
+        while !extraction.frontier.is_empty() {
+            let frontier: HashSet<ScalarValue> = extraction.frontier.iter().cloned().collect();
+            for &(source, target) in edges {
+                if direction != RelationshipDirection::Incoming && frontier.contains(&key(source)) {
+                    extraction.visit(0, key(source), key(target), true);
+                }
+                if direction != RelationshipDirection::Outgoing && frontier.contains(&key(target)) {
+                    extraction.visit(0, key(source), key(target), false);
+                }
+            }
+            extraction.advance();
+        }
+        let subgraph = extraction
+            .into_subgraph(&["KNOWS"], &DataType::Int64)
+            .unwrap();
+        let ints = |column: &ArrayRef| -> Vec<i64> {
+            column
+                .as_primitive::<arrow::datatypes::Int64Type>()
+                .values()
+                .to_vec()
+        };
+        let nodes = ints(subgraph.nodes.column(0))
+            .into_iter()
+            .zip(
+                ints(subgraph.nodes.column(1))
+                    .into_iter()
+                    .map(|d| d as usize),
+            )
+            .collect();
+        let relationships = ints(subgraph.relationships.column(1))
+            .into_iter()
+            .zip(ints(subgraph.relationships.column(2)))
+            .collect();
+        (nodes, relationships)
+    }
+
+    /// A path 1 -> 2 -> 3 -> 4 -> 5, with 3 -> 1 and 4 -> 2 closing cycles
+    const EDGES: &[(i64, i64)] = &[(1, 2), (2, 3), (3, 4), (4, 5), (3, 1), (4, 2)];
+
+    #[test]
+    fn test_induced_subgraph_within_hops() {
+        let (nodes, relationships) =
+            extract_from(EDGES, &[1], 1, RelationshipDirection::Undirected);
+        assert_eq!(nodes, vec![(1, 0), (2, 1), (3, 1)]);
+        // 2 -> 3 joins two nodes of the last level
+        assert_eq!(relationships, vec![(1, 2), (2, 3), (3, 1)]);
+
+        let (nodes, relationships) =
+            extract_from(EDGES, &[1], 2, RelationshipDirection::Undirected);
+        assert_eq!(nodes, vec![(1, 0), (2, 1), (3, 1), (4, 2)]);
+        assert_eq!(relationships, vec![(1, 2), (2, 3), (3, 1), (3, 4), (4, 2)]);
+    }
+
+    #[test]
+    fn test_directed_expansion() {
+        let (nodes, relationships) = extract_from(EDGES, &[1], 2, RelationshipDirection::Outgoing);
+        assert_eq!(nodes, vec![(1, 0), (2, 1), (3, 2)]);
+        assert_eq!(relationships, vec![(1, 2), (2, 3), (3, 1)]);
+
+        let (nodes, _) = extract_from(EDGES, &[2], 1, RelationshipDirection::Incoming);
+        assert_eq!(nodes, vec![(2, 0), (1, 1), (4, 1)]);
+    }
+
+    #[test]
+    fn test_seeds_only() {
+        let (nodes, relationships) =
+            extract_from(EDGES, &[4, 2, 4, 9], 0, RelationshipDirection::Undirected);
+        assert_eq!(nodes, vec![(2, 0), (4, 0), (9, 0)]);
+        assert_eq!(relationships, vec![(4, 2)]);
+    }
+}
@@ -0,0 +1,227 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Topological ordering, the `graph.topologicalSort` and `graph.findCycle` procedures
+//!
+//! Both read the edge tables into an in-memory [`Projection`] and order it with Kahn's
+//! algorithm, taking the ready node of smallest key first so the order is
+//! deterministic. Nodes left unordered all lie on or behind a cycle: each of them has
+//! an unordered predecessor, so walking back through those predecessors comes round to
+//! a node already walked through, closing a cycle.
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::sync::Arc;
+
+use arrow::array::{ArrayRef, Int64Array, RecordBatch};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use async_trait::async_trait;
+use datafusion::scalar::ScalarValue;
+
+use super::projection::AlgorithmGraph;
+use super::{sorted_keys, Projection};
+use crate::error::{GraphError, Result};
+use crate::procedures::{Procedure, ProcedureContext, ProcedureParameter};
+
+/// What the procedure yields of the ordering
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum OrderOutput {
+    /// A row per node in topological order, failing on a cyclic graph
+    Order,
+    /// A row per node of one cycle, none when the graph is acyclic
+    Cycle,
+}
+
+/// `graph.topologicalSort([config])`: the `nodeId` and `position` of every node of the
+/// projection in topological order, and `graph.findCycle([config])`: the `nodeId` and
+/// `position` of the nodes of a cycle, in the order of its relationships
+#[derive(Debug)]
+pub(crate) struct TopologicalOrder {
+    output: OrderOutput,
+}
+
+impl TopologicalOrder {
+    pub(crate) fn new(output: OrderOutput) -> Self {
+        Self { output }
+    }
+}
+
+#[async_trait]
+impl Procedure for TopologicalOrder {
+    fn name(&self) -> &str {
+        match self.output {
+            OrderOutput::Order => "graph.topologicalSort",
+            OrderOutput::Cycle => "graph.findCycle",
+        }
+    }
+
+    fn parameters(&self) -> Vec<ProcedureParameter> {
+        vec![AlgorithmGraph::config_parameter(Vec::new())]
+    }
+
+    fn output_schema(&self, ctx: &ProcedureContext) -> Result<SchemaRef> {
+        self.output_schema_for(ctx, &[])
+    }
+
+    fn output_schema_for(
+        &self,
+        ctx: &ProcedureContext,
+        args: &[Option<ScalarValue>],
+    ) -> Result<SchemaRef> {
+        let graph =
+            AlgorithmGraph::from_config(self.name(), ctx, args.first().and_then(Option::as_ref))?;
+        Ok(Arc::new(Schema::new(vec![
+            Field::new("nodeId", graph.key_type(self.name())?, false),
+            Field::new("position", DataType::Int64, false),
+        ])))
+    }
+
+    async fn call(&self, ctx: &ProcedureContext, args: Vec<ScalarValue>) -> Result<RecordBatch> {
+        let known: Vec<Option<ScalarValue>> = args.iter().cloned().map(Some).collect();
+        let schema = self.output_schema_for(ctx, &known)?;
+        let session = ctx.session().cloned().unwrap_or_default();
+        let graph = AlgorithmGraph::from_config(self.name(), ctx, args.first())?;
+        let Projection { nodes, edges } = graph.read(&session).await?;
+
+        let (by_key, _) = sorted_keys(schema.field(0).data_type(), &nodes)?;
+        let mut ranks = vec![0; nodes.len()];
+        for (rank, &node) in by_key.iter().enumerate() {
+            ranks[node] = rank;
+        }
+        let order = topological_order(&ranks, &edges);
+        let cycle = find_cycle(&ranks, &edges, &order);
+        let sequence = match (self.output, cycle) {
+            (OrderOutput::Order, None) => order,
+            (OrderOutput::Order, Some(cycle)) => {
+                let mut path: Vec<String> =
+                    cycle.iter().map(|&node| nodes[node].to_string()).collect();
+                path.push(nodes[cycle[0]].to_string());
+                return Err(GraphError::ExecutionError {
+                    message: format!(
+                        "{}: the graph has a cycle: {}",
+                        self.name(),
+                        path.join(" -> ")
+                    ),
+                    location: snafu::Location::new(file!(), line!(), column!()),
+                });
+            }
+            (OrderOutput::Cycle, cycle) => cycle.unwrap_or_default(),
+        };
+
+        let node_ids = if sequence.is_empty() {
+            arrow::array::new_empty_array(schema.field(0).data_type())
+        } else {
+            ScalarValue::iter_to_array(sequence.iter().map(|&node| nodes[node].clone()))?
+        };
+        let positions: ArrayRef = Arc::new(Int64Array::from_iter_values(
+            (0..sequence.len()).map(|position| position as i64),
+        ));
+        Ok(RecordBatch::try_new(schema, vec![node_ids, positions])?)
+    }
+}
+
+/// The nodes in topological order, taking the ready node of least rank first; the
+/// nodes on or behind a cycle are left out
+fn topological_order(ranks: &[usize], edges: &[(usize, usize)]) -> Vec<usize> {
+    let node_count = ranks.len();
+    let mut successors = vec![Vec::new(); node_count];
+    let mut in_degree = vec![0usize; node_count];
+    for &(from, to) in edges {
+        successors[from].push(to);
+        in_degree[to] += 1;
+    }
+    let mut ready: BinaryHeap<Reverse<(usize, usize)>> = (0..node_count)
+        .filter(|&node| in_degree[node] == 0)
+        .map(|node| Reverse((ranks[node], node)))
+        .collect();
+    let mut order = Vec::with_capacity(node_count);
+    while let Some(Reverse((_, node))) = ready.pop() {
+        order.push(node);
+        for &next in &successors[node] {
+            in_degree[next] -= 1;
+            if in_degree[next] == 0 {
+                ready.push(Reverse((ranks[next], next)));
+            }
+        }
+    }
+    order
+}
+
+/// A cycle among the nodes missing from the topological `order`, in the order of its
+/// relationships starting from its node of least rank; `None` when no node is missing
+fn find_cycle(ranks: &[usize], edges: &[(usize, usize)], order: &[usize]) -> Option<Vec<usize>> {
+    let node_count = ranks.len();
+    let mut unordered = vec![true; node_count];
+    for &node in order {
+        unordered[node] = false;
+    }
+    // The unordered predecessor of least rank of each unordered node
+    let mut predecessor: Vec<Option<usize>> = vec![None; node_count];
+    for &(from, to) in edges {
+        if unordered[from] && unordered[to] {
+            let better = match predecessor[to] {
+                Some(current) => ranks[from] < ranks[current],
+                None => true,
+            };
+            if better {
+                predecessor[to] = Some(from);
+            }
+        }
+    }
+
+    let start = (0..node_count)
+        .filter(|&node| unordered[node])
+        .min_by_key(|&node| ranks[node])?;
+    let mut walked: Vec<Option<usize>> = vec![None; node_count];
+    let mut walk = Vec::new();
+    let mut node = start;
+    while walked[node].is_none() {
+        walked[node] = Some(walk.len());
+        walk.push(node);
+        node = predecessor[node]?;
+    }
+    // The walk went against the relationships, from `node` on it closes the cycle
+    let mut cycle = walk.split_off(walked[node]?);
+    cycle.reverse();
+    let first = (0..cycle.len()).min_by_key(|&i| ranks[cycle[i]])?;
+    cycle.rotate_left(first);
+    Some(cycle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_topological_order_takes_least_rank_first() {
+        // 3 -> 1 -> 4 and 2 -> 0 -> 4, with ranks the node indexes
+        let edges = [(3, 1), (1, 4), (2, 0), (0, 4)];
+        assert_eq!(
+            topological_order(&[0, 1, 2, 3, 4], &edges),
+            vec![2, 0, 3, 1, 4]
+        );
+        // Ranked the other way round
+        assert_eq!(
+            topological_order(&[4, 3, 2, 1, 0], &edges),
+            vec![3, 2, 1, 0, 4]
+        );
+    }
+
+    #[test]
+    fn test_find_cycle() {
+        let ranks = [0, 1, 2, 3, 4];
+        // 0 -> 1 leads into the cycle 1 -> 3 -> 2 -> 1, which leads out to 4
+        let edges = [(0, 1), (1, 3), (3, 2), (2, 1), (2, 4)];
+        let order = topological_order(&ranks, &edges);
+        assert_eq!(order, vec![0]);
+        assert_eq!(find_cycle(&ranks, &edges, &order), Some(vec![1, 3, 2]));
+
+        let edges = [(0, 1), (1, 1)];
+        let order = topological_order(&ranks[..2], &edges);
+        assert_eq!(find_cycle(&ranks[..2], &edges, &order), Some(vec![1]));
+
+        let edges = [(0, 1), (1, 2)];
+        let order = topological_order(&ranks[..3], &edges);
+        assert_eq!(find_cycle(&ranks[..3], &edges, &order), None);
+    }
+}
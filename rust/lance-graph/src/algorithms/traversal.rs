@@ -0,0 +1,338 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Breadth-first and depth-first traversals, the `graph.bfs` and `graph.dfs` procedures
+//!
+//! Both expand an iterative frontier: each hop reads the edges leaving the nodes reached
+//! by the previous one, through a query on the relationship tables filtered by their
+//! source keys, so the scans of Lance datasets use the key indexes and skip fragments as
+//! any anchored expansion does. The adjacency lists read up to `maxDepth` hops are then
+//! walked in memory in the order of the traversal.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use arrow::array::{Array, Int64Array, RecordBatch};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use async_trait::async_trait;
+use datafusion::execution::context::SessionContext;
+use datafusion::scalar::ScalarValue;
+
+use super::{edge_tables, key_type, read_edges, strings, EdgeTable};
+use crate::error::{GraphError, Result};
+use crate::procedures::{Procedure, ProcedureContext, ProcedureParameter};
+
+/// Order the reached nodes are yielded in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TraversalOrder {
+    BreadthFirst,
+    DepthFirst,
+}
+
+/// `graph.bfs(startNodes, relTypes, maxDepth)` and `graph.dfs(...)`: the `node` and
+/// `depth` of every node reachable from the start nodes in at most `maxDepth` hops
+#[derive(Debug)]
+pub(crate) struct GraphTraversal {
+    order: TraversalOrder,
+}
+
+impl GraphTraversal {
+    pub(crate) fn new(order: TraversalOrder) -> Self {
+        Self { order }
+    }
+}
+
+/// Arguments of one call
+struct TraversalArguments {
+    start_nodes: Vec<ScalarValue>,
+    rel_types: Vec<String>,
+    max_depth: usize,
+}
+
+/// Outgoing neighbors of the expanded nodes, in key order
+type Adjacency = HashMap<ScalarValue, Vec<ScalarValue>>;
+
+#[async_trait]
+impl Procedure for GraphTraversal {
+    fn name(&self) -> &str {
+        match self.order {
+            TraversalOrder::BreadthFirst => "graph.bfs",
+            TraversalOrder::DepthFirst => "graph.dfs",
+        }
+    }
+
+    fn parameters(&self) -> Vec<ProcedureParameter> {
+        vec![
+            ProcedureParameter::new("startNodes", DataType::new_list(DataType::Utf8, true)),
+            ProcedureParameter::new("relTypes", DataType::new_list(DataType::Utf8, true)),
+            ProcedureParameter::new("maxDepth", DataType::Int64),
+        ]
+    }
+
+    fn output_schema(&self, _ctx: &ProcedureContext) -> Result<SchemaRef> {
+        Err(GraphError::PlanError {
+            message: format!(
+                "{} needs its relationship types to plan its output",
+                self.name()
+            ),
+            location: snafu::Location::new(file!(), line!(), column!()),
+        })
+    }
+
+    fn output_schema_for(
+        &self,
+        ctx: &ProcedureContext,
+        args: &[Option<ScalarValue>],
+    ) -> Result<SchemaRef> {
+        let rel_types = match args.get(1) {
+            Some(Some(value)) => strings(value),
+            _ => None,
+        }
+        .ok_or_else(|| GraphError::PlanError {
+            message: format!(
+                "{} takes its relationship types as a list of string literals",
+                self.name()
+            ),
+            location: snafu::Location::new(file!(), line!(), column!()),
+        })?;
+        let edges = edge_tables(self.name(), ctx, &rel_types)?;
+        Ok(Arc::new(Schema::new(vec![
+            Field::new("node", key_type(self.name(), &edges)?, false),
+            Field::new("depth", DataType::Int64, false),
+        ])))
+    }
+
+    async fn call(&self, ctx: &ProcedureContext, args: Vec<ScalarValue>) -> Result<RecordBatch> {
+        let known: Vec<Option<ScalarValue>> = args.iter().cloned().map(Some).collect();
+        let schema = self.output_schema_for(ctx, &known)?;
+        let args = self.arguments(&args)?;
+        let edges = edge_tables(self.name(), ctx, &args.rel_types)?;
+        let key_type = schema.field(0).data_type();
+        let start_nodes = args
+            .start_nodes
+            .iter()
+            .map(|node| node.cast_to(key_type))
+            .collect::<datafusion::common::Result<Vec<_>>>()
+            .map_err(|e| GraphError::ExecutionError {
+                message: format!(
+                    "{}: start node is not a {} key: {}",
+                    self.name(),
+                    key_type,
+                    e
+                ),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            })?;
+
+        let session = ctx.session().cloned().unwrap_or_default();
+        let adjacency = expand(&session, &edges, &start_nodes, args.max_depth).await?;
+        let reached = match self.order {
+            TraversalOrder::BreadthFirst => breadth_first(&adjacency, &start_nodes, args.max_depth),
+            TraversalOrder::DepthFirst => depth_first(&adjacency, &start_nodes, args.max_depth),
+        };
+        let nodes: Vec<ScalarValue> = reached.iter().map(|(node, _)| node.clone()).collect();
+        let nodes = if nodes.is_empty() {
+            arrow::array::new_empty_array(key_type)
+        } else {
+            ScalarValue::iter_to_array(nodes)?
+        };
+        let depths = Int64Array::from_iter_values(reached.iter().map(|&(_, depth)| depth as i64));
+        Ok(RecordBatch::try_new(schema, vec![nodes, Arc::new(depths)])?)
+    }
+}
+
+impl GraphTraversal {
+    fn arguments(&self, args: &[ScalarValue]) -> Result<TraversalArguments> {
+        let invalid = |message: &str| GraphError::ExecutionError {
+            message: format!("{}: {}", self.name(), message),
+            location: snafu::Location::new(file!(), line!(), column!()),
+        };
+        let start_nodes = match args.first() {
+            Some(ScalarValue::List(list)) if !list.is_null(0) => {
+                let values = list.value(0);
+                (0..values.len())
+                    .filter(|&i| values.is_valid(i))
+                    .map(|i| ScalarValue::try_from_array(&values, i))
+                    .collect::<datafusion::common::Result<Vec<_>>>()?
+            }
+            _ => return Err(invalid("startNodes must be a list of node keys")),
+        };
+        let rel_types = args
+            .get(1)
+            .and_then(strings)
+            .ok_or_else(|| invalid("relTypes must be a list of relationship types"))?;
+        let max_depth = match args.get(2) {
+            Some(ScalarValue::Int64(Some(depth))) if *depth >= 0 => *depth as usize,
+            _ => return Err(invalid("maxDepth must be a non-negative integer")),
+        };
+        Ok(TraversalArguments {
+            start_nodes,
+            rel_types,
+            max_depth,
+        })
+    }
+}
+
+/// Read the neighbors of every node within `max_depth - 1` hops of `start_nodes`, one
+/// query per hop for the nodes it newly reaches
+async fn expand(
+    session: &SessionContext,
+    edges: &[EdgeTable],
+    start_nodes: &[ScalarValue],
+    max_depth: usize,
+) -> Result<Adjacency> {
+    let mut adjacency = Adjacency::new();
+    let mut frontier: Vec<ScalarValue> = unique(start_nodes.iter().cloned());
+    for _ in 0..max_depth {
+        if frontier.is_empty() {
+            break;
+        }
+        for node in &frontier {
+            adjacency.entry(node.clone()).or_default();
+        }
+        for table in edges {
+            for edge in read_edges(session, table, Some(&frontier), None).await? {
+                adjacency.entry(edge.from).or_default().push(edge.to);
+            }
+        }
+        let next = frontier
+            .iter()
+            .flat_map(|node| adjacency[node].iter().cloned())
+            .filter(|neighbor| !adjacency.contains_key(neighbor));
+        frontier = unique(next);
+    }
+    for neighbors in adjacency.values_mut() {
+        neighbors.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        neighbors.dedup();
+    }
+    Ok(adjacency)
+}
+
+/// `nodes` without repeats, in order of first appearance
+fn unique(nodes: impl Iterator<Item = ScalarValue>) -> Vec<ScalarValue> {
+    let mut seen = HashSet::new();
+    nodes.filter(|node| seen.insert(node.clone())).collect()
+}
+
+/// Every reachable node once, at its shortest distance from the start nodes, level by
+/// level
+fn breadth_first(
+    adjacency: &Adjacency,
+    start_nodes: &[ScalarValue],
+    max_depth: usize,
+) -> Vec<(ScalarValue, usize)> {
+    let mut frontier = unique(start_nodes.iter().cloned());
+    let mut visited: HashSet<ScalarValue> = frontier.iter().cloned().collect();
+    let mut reached: Vec<(ScalarValue, usize)> =
+        frontier.iter().map(|node| (node.clone(), 0)).collect();
+    for depth in 1..=max_depth {
+        let mut next = Vec::new();
+        for node in &frontier {
+            for neighbor in adjacency.get(node).into_iter().flatten() {
+                if visited.insert(neighbor.clone()) {
+                    reached.push((neighbor.clone(), depth));
+                    next.push(neighbor.clone());
+                }
+            }
+        }
+        frontier = next;
+    }
+    reached
+}
+
+/// Every reachable node once, in preorder, at the depth it is first visited
+///
+/// A node reached again by a shorter path is expanded again, without being yielded, so
+/// that nodes within `max_depth` hops through it are visited too.
+fn depth_first(
+    adjacency: &Adjacency,
+    start_nodes: &[ScalarValue],
+    max_depth: usize,
+) -> Vec<(ScalarValue, usize)> {
+    let mut shallowest: HashMap<ScalarValue, usize> = HashMap::new();
+    let mut reached = Vec::new();
+    let mut stack: Vec<(ScalarValue, usize)> = Vec::new();
+    for start in start_nodes {
+        stack.push((start.clone(), 0));
+        while let Some((node, depth)) = stack.pop() {
+            match shallowest.get(&node) {
+                Some(&seen) if seen <= depth => continue,
+                Some(_) => {}
+                None => reached.push((node.clone(), depth)),
+            }
+            shallowest.insert(node.clone(), depth);
+            if depth < max_depth {
+                let neighbors = adjacency.get(&node).into_iter().flatten();
+                stack.extend(
+                    neighbors
+                        .rev()
+                        .map(|neighbor| (neighbor.clone(), depth + 1)),
+                );
+            }
+        }
+    }
+    reached
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph(edges: &[(i64, i64)]) -> Adjacency {
+        let mut adjacency = Adjacency::new();
+        for &(from, to) in edges {
+            adjacency
+                .entry(ScalarValue::from(from))
+                .or_default()
+                .push(ScalarValue::from(to));
+        }
+        adjacency
+    }
+
+    fn keys(reached: Vec<(ScalarValue, usize)>) -> Vec<(i64, usize)> {
+        reached
+            .into_iter()
+            .map(|(node, depth)| match node {
+                ScalarValue::Int64(Some(key)) => (key, depth),
+                other => panic!("unexpected key {:?}", other),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_breadth_first_yields_shortest_depths() {
+        let adjacency = graph(&[(1, 2), (1, 3), (2, 4), (3, 4), (4, 5), (5, 1)]);
+        let start = [ScalarValue::from(1i64)];
+        assert_eq!(
+            keys(breadth_first(&adjacency, &start, 3)),
+            vec![(1, 0), (2, 1), (3, 1), (4, 2), (5, 3)]
+        );
+        assert_eq!(keys(breadth_first(&adjacency, &start, 0)), vec![(1, 0)]);
+    }
+
+    #[test]
+    fn test_depth_first_visits_in_preorder() {
+        // 4 and 5 are reached through 2 before 3 is visited
+        let adjacency = graph(&[(1, 2), (1, 3), (2, 4), (3, 4), (4, 5)]);
+        let start = [ScalarValue::from(1i64)];
+        assert_eq!(
+            keys(depth_first(&adjacency, &start, 3)),
+            vec![(1, 0), (2, 1), (4, 2), (5, 3), (3, 1)]
+        );
+    }
+
+    #[test]
+    fn test_depth_first_reexpands_shallower_visits() {
+        // 1 -> 2 -> 3 -> 4 reaches 3 at depth 2 first; 1 -> 3 reaches it at depth 1,
+        // within two hops of 4
+        let adjacency = graph(&[(1, 2), (1, 3), (2, 3), (3, 4)]);
+        let start = [ScalarValue::from(1i64)];
+        assert_eq!(
+            keys(depth_first(&adjacency, &start, 2)),
+            vec![(1, 0), (2, 1), (3, 2), (4, 2)]
+        );
+        assert_eq!(
+            keys(depth_first(&adjacency, &start, 3)),
+            vec![(1, 0), (2, 1), (3, 2), (4, 3)]
+        );
+    }
+}
@@ -0,0 +1,215 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Triangles, the `graph.triangleCount` and `graph.localClusteringCoefficient`
+//! procedures
+//!
+//! Both read the edge tables into an in-memory [`Projection`] taken as a simple
+//! undirected graph: direction, parallel relationships and self-loops are dropped.
+//! Each node keeps the sorted list of its neighbors ranked after it by degree, so every
+//! triangle is found exactly once, by intersecting the lists of the two ends of one of
+//! its edges.
+
+use std::sync::Arc;
+
+use arrow::array::{Float64Array, Int64Array, RecordBatch};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use async_trait::async_trait;
+use datafusion::scalar::ScalarValue;
+
+use super::projection::AlgorithmGraph;
+use super::{sorted_keys, Projection};
+use crate::error::Result;
+use crate::procedures::{Procedure, ProcedureContext, ProcedureParameter};
+
+/// What the procedure yields of the triangles
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TriangleOutput {
+    /// One row with the number of triangles of the graph
+    Global,
+    /// A row per node with its triangles and clustering coefficient
+    Local,
+}
+
+/// `graph.triangleCount([config])`: the `triangleCount` of the projection, and
+/// `graph.localClusteringCoefficient([config])`: the `nodeId`, `triangleCount` and
+/// `coefficient` of every node of the projection
+#[derive(Debug)]
+pub(crate) struct Triangles {
+    output: TriangleOutput,
+}
+
+impl Triangles {
+    pub(crate) fn new(output: TriangleOutput) -> Self {
+        Self { output }
+    }
+}
+
+#[async_trait]
+impl Procedure for Triangles {
+    fn name(&self) -> &str {
+        match self.output {
+            TriangleOutput::Global => "graph.triangleCount",
+            TriangleOutput::Local => "graph.localClusteringCoefficient",
+        }
+    }
+
+    fn parameters(&self) -> Vec<ProcedureParameter> {
+        vec![AlgorithmGraph::config_parameter(Vec::new())]
+    }
+
+    fn output_schema(&self, ctx: &ProcedureContext) -> Result<SchemaRef> {
+        self.output_schema_for(ctx, &[])
+    }
+
+    fn output_schema_for(
+        &self,
+        ctx: &ProcedureContext,
+        args: &[Option<ScalarValue>],
+    ) -> Result<SchemaRef> {
+        let graph =
+            AlgorithmGraph::from_config(self.name(), ctx, args.first().and_then(Option::as_ref))?;
+        let fields = match self.output {
+            TriangleOutput::Global => {
+                vec![Field::new("triangleCount", DataType::Int64, false)]
+            }
+            TriangleOutput::Local => vec![
+                Field::new("nodeId", graph.key_type(self.name())?, false),
+                Field::new("triangleCount", DataType::Int64, false),
+                Field::new("coefficient", DataType::Float64, false),
+            ],
+        };
+        Ok(Arc::new(Schema::new(fields)))
+    }
+
+    async fn call(&self, ctx: &ProcedureContext, args: Vec<ScalarValue>) -> Result<RecordBatch> {
+        let known: Vec<Option<ScalarValue>> = args.iter().cloned().map(Some).collect();
+        let schema = self.output_schema_for(ctx, &known)?;
+        let session = ctx.session().cloned().unwrap_or_default();
+        let graph = AlgorithmGraph::from_config(self.name(), ctx, args.first())?;
+        let Projection { nodes, edges } = graph.read(&session).await?;
+
+        let neighbors = simple_neighbors(nodes.len(), &edges);
+        let triangles = triangles_per_node(&neighbors);
+        match self.output {
+            TriangleOutput::Global => {
+                // Every triangle is counted at each of its three corners
+                let total = triangles.iter().sum::<u64>() / 3;
+                Ok(RecordBatch::try_new(
+                    schema,
+                    vec![Arc::new(Int64Array::from(vec![total as i64]))],
+                )?)
+            }
+            TriangleOutput::Local => {
+                let (order, node_ids) = sorted_keys(schema.field(0).data_type(), &nodes)?;
+                let counts =
+                    Int64Array::from_iter_values(order.iter().map(|&n| triangles[n] as i64));
+                let coefficients = Float64Array::from_iter_values(
+                    order
+                        .iter()
+                        .map(|&n| clustering_coefficient(triangles[n], neighbors[n].len())),
+                );
+                Ok(RecordBatch::try_new(
+                    schema,
+                    vec![node_ids, Arc::new(counts), Arc::new(coefficients)],
+                )?)
+            }
+        }
+    }
+}
+
+/// Sorted distinct neighbors of each node, ignoring direction and self-loops
+fn simple_neighbors(node_count: usize, edges: &[(usize, usize)]) -> Vec<Vec<usize>> {
+    let mut neighbors = vec![Vec::new(); node_count];
+    for &(from, to) in edges {
+        if from != to {
+            neighbors[from].push(to);
+            neighbors[to].push(from);
+        }
+    }
+    for list in &mut neighbors {
+        list.sort_unstable();
+        list.dedup();
+    }
+    neighbors
+}
+
+/// Number of triangles through each node of the simple graph of `neighbors`
+fn triangles_per_node(neighbors: &[Vec<usize>]) -> Vec<u64> {
+    // Rank nodes by degree, then index; each node keeps the neighbors ranked after it,
+    // which bounds the lists by the square root of the number of edges
+    let rank = |node: usize| (neighbors[node].len(), node);
+    let forward: Vec<Vec<usize>> = neighbors
+        .iter()
+        .enumerate()
+        .map(|(node, list)| {
+            list.iter()
+                .copied()
+                .filter(|&neighbor| rank(neighbor) > rank(node))
+                .collect()
+        })
+        .collect();
+
+    let mut triangles = vec![0u64; neighbors.len()];
+    for (a, list) in forward.iter().enumerate() {
+        for &b in list {
+            // Both lists are sorted by index: merge them
+            let (mut i, mut j) = (0, 0);
+            let (left, right) = (list, &forward[b]);
+            while i < left.len() && j < right.len() {
+                match left[i].cmp(&right[j]) {
+                    std::cmp::Ordering::Less => i += 1,
+                    std::cmp::Ordering::Greater => j += 1,
+                    std::cmp::Ordering::Equal => {
+                        for corner in [a, b, left[i]] {
+                            triangles[corner] += 1;
+                        }
+                        i += 1;
+                        j += 1;
+                    }
+                }
+            }
+        }
+    }
+    triangles
+}
+
+/// Share of the pairs of a node's neighbors that are neighbors themselves, 0 for nodes
+/// with fewer than two neighbors
+fn clustering_coefficient(triangles: u64, degree: usize) -> f64 {
+    if degree < 2 {
+        return 0.0;
+    }
+    let pairs = degree as f64 * (degree - 1) as f64 / 2.0;
+    triangles as f64 / pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simple_neighbors() {
+        let neighbors = simple_neighbors(3, &[(0, 1), (1, 0), (1, 1), (2, 1), (0, 1)]);
+        assert_eq!(neighbors, vec![vec![1], vec![0, 2], vec![1]]);
+    }
+
+    #[test]
+    fn test_triangles_per_node() {
+        // A 4-clique holds 4 triangles, 3 through each node; 4 hangs off node 3
+        let edges = [(0, 1), (0, 2), (0, 3), (1, 2), (1, 3), (2, 3), (3, 4)];
+        let neighbors = simple_neighbors(5, &edges);
+        assert_eq!(triangles_per_node(&neighbors), vec![3, 3, 3, 3, 0]);
+
+        let neighbors = simple_neighbors(4, &[(0, 1), (1, 2), (2, 3), (3, 0)]);
+        assert_eq!(triangles_per_node(&neighbors), vec![0; 4]);
+    }
+
+    #[test]
+    fn test_clustering_coefficient() {
+        assert_eq!(clustering_coefficient(3, 3), 1.0);
+        assert_eq!(clustering_coefficient(3, 4), 0.5);
+        assert_eq!(clustering_coefficient(0, 1), 0.0);
+        assert_eq!(clustering_coefficient(0, 0), 0.0);
+    }
+}
@@ -13,10 +13,29 @@ use std::collections::HashMap;
 /// A complete Cypher query
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CypherQuery {
+    /// `EXPLAIN` or `PROFILE` prefix, reporting the plans of the query instead of its
+    /// rows (optional)
+    #[serde(default)]
+    pub explain: Option<ExplainMode>,
+    /// Graph selected with `USE <graph>` (optional)
+    #[serde(default)]
+    pub graph: Option<String>,
     /// MATCH clauses
     pub match_clauses: Vec<MatchClause>,
+    /// UNWIND clauses interleaved with the MATCH clauses (optional)
+    #[serde(default)]
+    pub unwind_clauses: Vec<UnwindClause>,
+    /// CALL clauses interleaved with the MATCH clauses (optional)
+    #[serde(default)]
+    pub call_clauses: Vec<CallClause>,
     /// WHERE clause (optional)
     pub where_clause: Option<WhereClause>,
+    /// WITH clauses chaining further query parts, in query order
+    #[serde(default)]
+    pub with_clauses: Vec<WithClause>,
+    /// Clauses writing to the graph after the reading clauses, in query order
+    #[serde(default)]
+    pub write_clauses: Vec<WriteClause>,
     /// RETURN clause
     pub return_clause: ReturnClause,
     /// LIMIT clause (optional)
@@ -25,13 +44,58 @@ pub struct CypherQuery {
     pub order_by: Option<OrderByClause>,
     /// SKIP/OFFSET clause (optional)
     pub skip: Option<u64>,
+    /// Queries combined with this one, in query order, either all by UNION or all by
+    /// UNION ALL
+    #[serde(default)]
+    pub unions: Vec<UnionClause>,
+}
+
+/// What an `EXPLAIN` or `PROFILE` prefix reports about its query
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExplainMode {
+    /// `EXPLAIN`: the plans, without running the query
+    Explain,
+    /// `PROFILE`: the plans, running the query to count the rows and time of every
+    /// operator
+    Profile,
 }
 
 impl CypherQuery {
+    /// Every MATCH clause of the query, including those following a WITH or UNION and
+    /// those of `EXISTS { }` and `COUNT { }` subqueries
+    pub fn all_match_clauses(&self) -> Box<dyn Iterator<Item = &MatchClause> + '_> {
+        Box::new(
+            self.part_match_clauses()
+                .chain(self.unions.iter().flat_map(|u| u.query.all_match_clauses())),
+        )
+    }
+
+    /// The MATCH clauses of this query part, leaving out those of UNION branches
+    pub fn part_match_clauses(&self) -> Box<dyn Iterator<Item = &MatchClause> + '_> {
+        let where_clauses = self.where_clause.iter().chain(
+            self.with_clauses
+                .iter()
+                .flat_map(|w| w.where_clause.iter().chain(&w.match_where_clause)),
+        );
+        let items = self
+            .return_clause
+            .items
+            .iter()
+            .chain(self.with_clauses.iter().flat_map(|w| &w.items));
+        Box::new(
+            self.match_clauses
+                .iter()
+                .chain(self.with_clauses.iter().flat_map(|w| &w.match_clauses))
+                .chain(where_clauses.flat_map(|w| w.expression.subqueries()))
+                .chain(items.flat_map(|item| item.expression.subqueries()))
+                .flat_map(MatchClause::with_subqueries),
+        )
+    }
+
     /// Extract all node labels referenced in the query
     pub fn get_node_labels(&self) -> Vec<String> {
         let mut labels = Vec::new();
-        for match_clause in &self.match_clauses {
+        for match_clause in self.all_match_clauses() {
             for pattern in &match_clause.patterns {
                 match pattern {
                     GraphPattern::Node(node) => {
@@ -64,7 +128,7 @@ impl CypherQuery {
     /// Extract all relationship types referenced in the query
     pub fn get_relationship_types(&self) -> Vec<String> {
         let mut types = Vec::new();
-        for match_clause in &self.match_clauses {
+        for match_clause in self.all_match_clauses() {
             for pattern in &match_clause.patterns {
                 if let GraphPattern::Path(path) = pattern {
                     for segment in &path.segments {
@@ -86,6 +150,57 @@ impl CypherQuery {
 pub struct MatchClause {
     /// Graph patterns to match
     pub patterns: Vec<GraphPattern>,
+    /// Whether this is an `OPTIONAL MATCH`, keeping rows that find no match with NULLs
+    #[serde(default)]
+    pub optional: bool,
+    /// WHERE clause attached to an `OPTIONAL MATCH`, `EXISTS { }` or `COUNT { }` pattern,
+    /// applied
+    /// with the join onto the preceding clauses
+    #[serde(default)]
+    pub where_clause: Option<WhereClause>,
+}
+
+/// An UNWIND clause expanding a list into one row per element
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnwindClause {
+    /// The list to expand
+    pub expression: ValueExpression,
+    /// Variable bound to each element
+    pub alias: String,
+    /// Number of MATCH clauses of the same query part that run before this UNWIND
+    pub preceding_matches: usize,
+}
+
+/// A CALL clause invoking a procedure: `CALL db.labels() YIELD label AS l WHERE ...`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CallClause {
+    /// Name of the procedure, namespace included (`db.labels`)
+    pub procedure: String,
+    /// Arguments, in parameter order
+    pub arguments: Vec<ValueExpression>,
+    /// Output columns bound by YIELD; `None` for a CALL without YIELD or with `YIELD *`,
+    /// which binds every column under its own name
+    pub yield_items: Option<Vec<YieldItem>>,
+    /// WHERE filtering the yielded rows
+    pub where_clause: Option<WhereClause>,
+    /// Number of MATCH clauses of the same query part that run before this CALL
+    pub preceding_matches: usize,
+}
+
+/// A column yielded by a procedure, optionally renamed with AS
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct YieldItem {
+    /// Output column of the procedure
+    pub column: String,
+    /// Variable the column is bound to, when it differs from the column name
+    pub alias: Option<String>,
+}
+
+impl YieldItem {
+    /// Variable the column is bound to
+    pub fn variable(&self) -> &str {
+        self.alias.as_deref().unwrap_or(&self.column)
+    }
 }
 
 /// A graph pattern (nodes and relationships)
@@ -106,6 +221,23 @@ pub struct NodePattern {
     pub labels: Vec<String>,
     /// Property constraints (e.g., {name: 'John', age: 30})
     pub properties: HashMap<String, PropertyValue>,
+    /// Label condition beyond the required `labels`, e.g. the `!Banned` of
+    /// `(n:Person&!Banned)`
+    #[serde(default)]
+    pub label_expression: Option<LabelExpression>,
+}
+
+/// A label expression in a node pattern or a `n:Label` predicate
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum LabelExpression {
+    /// A single label
+    Label(String),
+    /// `!expr`
+    Not(Box<LabelExpression>),
+    /// `left&right`, also written `left:right`
+    And(Box<LabelExpression>, Box<LabelExpression>),
+    /// `left|right`
+    Or(Box<LabelExpression>, Box<LabelExpression>),
 }
 
 /// A path pattern connecting nodes through relationships
@@ -115,6 +247,21 @@ pub struct PathPattern {
     pub start_node: NodePattern,
     /// Relationships and intermediate nodes
     pub segments: Vec<PathSegment>,
+    /// Variable bound to the whole path (e.g., 'p' in p = (a)-[:KNOWS*]->(b))
+    #[serde(default)]
+    pub variable: Option<String>,
+    /// Set when the path is wrapped in `shortestPath` or `allShortestPaths`
+    #[serde(default)]
+    pub shortest: Option<ShortestPathMode>,
+}
+
+/// Which paths a shortest-path pattern keeps between each pair of endpoints
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ShortestPathMode {
+    /// `shortestPath(...)`: a single path of minimal length
+    Single,
+    /// `allShortestPaths(...)`: every path of minimal length
+    All,
 }
 
 /// A segment of a path (relationship + end node)
@@ -178,6 +325,10 @@ pub enum PropertyValue {
     Parameter(String),
     /// Property reference (e.g., node.property)
     Property(PropertyRef),
+    /// Reference to a bound variable (e.g., `id` in `UNWIND $ids AS id MATCH (n {id: id})`)
+    Variable(String),
+    /// List literal (e.g., `[1, 2, 3]`)
+    List(Vec<PropertyValue>),
 }
 
 /// Reference to a property of a node or relationship
@@ -213,6 +364,10 @@ pub enum BooleanExpression {
     Not(Box<BooleanExpression>),
     /// Property existence check
     Exists(PropertyRef),
+    /// Pattern existence subquery `EXISTS { MATCH pattern WHERE predicate }`, with the
+    /// subquery's WHERE stored in the clause's `where_clause`; a pattern predicate
+    /// `(a)-[:KNOWS]->(b)` is one without a WHERE
+    ExistsSubquery(Box<MatchClause>),
     /// IN clause
     In {
         expression: ValueExpression,
@@ -223,10 +378,36 @@ pub enum BooleanExpression {
         expression: ValueExpression,
         pattern: String,
     },
+    /// String matching (`STARTS WITH`, `ENDS WITH`, `CONTAINS`, `=~`)
+    StringMatch {
+        expression: ValueExpression,
+        operator: StringOperator,
+        pattern: ValueExpression,
+    },
     /// IS NULL pattern matching
     IsNull(ValueExpression),
     /// IS NOT NULL pattern matching
     IsNotNull(ValueExpression),
+    /// Label predicate `n:Person&!Banned`
+    HasLabels {
+        variable: String,
+        labels: LabelExpression,
+    },
+    /// Full-text search `fts(n.bio, 'query terms')`: the text matches any of the terms
+    FullTextSearch {
+        expression: ValueExpression,
+        query: ValueExpression,
+    },
+}
+
+/// String matching operators
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StringOperator {
+    StartsWith,
+    EndsWith,
+    Contains,
+    /// `=~`: the whole string matches a regular expression
+    Regex,
 }
 
 /// Comparison operators
@@ -252,14 +433,55 @@ pub enum ValueExpression {
     /// Function call
     Function {
         name: String,
+        /// Whether DISTINCT was specified, as in `count(DISTINCT x)`
+        #[serde(default)]
+        distinct: bool,
         args: Vec<ValueExpression>,
     },
+    /// CASE expression; the simple form `CASE x WHEN v THEN r` is stored as the
+    /// searched form `CASE WHEN x = v THEN r`
+    Case {
+        /// WHEN condition and THEN result of each branch, tried in order
+        branches: Vec<(BooleanExpression, ValueExpression)>,
+        /// ELSE result; NULL when absent
+        else_result: Option<Box<ValueExpression>>,
+    },
+    /// List of expressions such as `[p.name, p.age]`; lists of literals stay
+    /// `Literal(PropertyValue::List)`
+    List(Vec<ValueExpression>),
+    /// List element `list[index]`; negative indexes count from the end
+    Index {
+        list: Box<ValueExpression>,
+        index: Box<ValueExpression>,
+    },
+    /// Sub-list `list[from..to]`; bounds are optional and `to` is exclusive
+    Slice {
+        list: Box<ValueExpression>,
+        from: Option<Box<ValueExpression>>,
+        to: Option<Box<ValueExpression>>,
+    },
+    /// List comprehension `[variable IN list WHERE predicate | projection]`
+    ListComprehension {
+        /// Variable bound to each element, in scope only inside the comprehension
+        variable: String,
+        list: Box<ValueExpression>,
+        /// Elements are kept only when the predicate holds
+        predicate: Option<Box<BooleanExpression>>,
+        /// Value produced for each kept element; the element itself when absent
+        projection: Option<Box<ValueExpression>>,
+    },
+    /// Pattern count subquery `COUNT { MATCH pattern WHERE predicate }`, with the
+    /// subquery's WHERE stored in the clause's `where_clause`
+    CountSubquery(Box<MatchClause>),
     /// Arithmetic operation
     Arithmetic {
         left: Box<ValueExpression>,
         operator: ArithmeticOperator,
         right: Box<ValueExpression>,
     },
+    /// Map of expressions `{key: value, ...}`, in the order written; map projections
+    /// (`p {.name, key: value}`) are parsed into one
+    Map(Vec<(String, ValueExpression)>),
 }
 
 /// Arithmetic operators
@@ -281,6 +503,109 @@ pub struct ReturnClause {
     pub items: Vec<ReturnItem>,
 }
 
+/// WITH clause projecting the bindings carried into the next query part
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WithClause {
+    /// Whether DISTINCT was specified
+    pub distinct: bool,
+    /// Items carried forward; other bindings go out of scope
+    pub items: Vec<ReturnItem>,
+    /// ORDER BY applied to the projected rows
+    pub order_by: Option<OrderByClause>,
+    /// SKIP applied to the projected rows
+    pub skip: Option<u64>,
+    /// LIMIT applied to the projected rows
+    pub limit: Option<u64>,
+    /// WHERE filtering the projected rows
+    pub where_clause: Option<WhereClause>,
+    /// MATCH clauses of the following query part
+    pub match_clauses: Vec<MatchClause>,
+    /// UNWIND clauses interleaved with those MATCH clauses
+    #[serde(default)]
+    pub unwind_clauses: Vec<UnwindClause>,
+    /// CALL clauses interleaved with those MATCH clauses
+    #[serde(default)]
+    pub call_clauses: Vec<CallClause>,
+    /// WHERE clause following those MATCH clauses
+    pub match_where_clause: Option<WhereClause>,
+}
+
+/// A clause writing to the graph
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum WriteClause {
+    /// `CREATE (a)-[:KNOWS]->(b:Person {name: 'Ann'})`
+    Create(CreateClause),
+    /// `MERGE (n:Person {id: 1}) ON CREATE SET n.seen = 1 ON MATCH SET n.seen = n.seen + 1`
+    Merge(MergeClause),
+    /// `SET n.age = n.age + 1`
+    Set(SetClause),
+    /// `REMOVE n.nickname`
+    Remove(RemoveClause),
+    /// `DETACH DELETE n`
+    Delete(DeleteClause),
+}
+
+/// A CREATE clause adding the nodes and relationships of its patterns once per input row
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CreateClause {
+    /// Patterns to create; nodes whose variable is already bound are not created again
+    pub patterns: Vec<GraphPattern>,
+}
+
+/// A MERGE clause finding its pattern by key, or creating it when missing, once per
+/// input row
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MergeClause {
+    /// Pattern to find or create
+    pub pattern: GraphPattern,
+    /// Properties set when the pattern is created
+    pub on_create: Vec<SetItem>,
+    /// Properties set when the pattern already exists
+    pub on_match: Vec<SetItem>,
+}
+
+/// A SET clause changing properties of bound nodes and relationships once per input row
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SetClause {
+    /// Properties to set
+    pub items: Vec<SetItem>,
+}
+
+/// A REMOVE clause clearing properties of bound nodes and relationships
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RemoveClause {
+    /// Properties to clear
+    pub properties: Vec<PropertyRef>,
+}
+
+/// A DELETE clause removing bound nodes and relationships
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeleteClause {
+    /// Whether the relationships of deleted nodes are deleted with them; otherwise
+    /// deleting a node that still has relationships fails
+    pub detach: bool,
+    /// Variables of the nodes and relationships to delete
+    pub variables: Vec<String>,
+}
+
+/// `n.property = value`, setting one property
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SetItem {
+    /// Property to set
+    pub property: PropertyRef,
+    /// Its new value
+    pub value: ValueExpression,
+}
+
+/// A query appended with UNION (duplicates removed) or UNION ALL (duplicates kept)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnionClause {
+    /// Whether ALL was specified
+    pub all: bool,
+    /// The appended query; it must return the same columns as the first one
+    pub query: CypherQuery,
+}
+
 /// An item in the RETURN clause
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ReturnItem {
@@ -320,6 +645,7 @@ impl NodePattern {
             variable,
             labels: Vec::new(),
             properties: HashMap::new(),
+            label_expression: None,
         }
     }
 
@@ -334,6 +660,45 @@ impl NodePattern {
         self.properties.insert(key.into(), value);
         self
     }
+
+    /// Label condition a node matching this pattern has to satisfy beyond the label it
+    /// is scanned by: the labels after the first and the label expression
+    pub fn label_condition(&self) -> Option<LabelExpression> {
+        self.labels
+            .iter()
+            .skip(1)
+            .map(|label| LabelExpression::Label(label.clone()))
+            .chain(self.label_expression.clone())
+            .reduce(|left, right| LabelExpression::And(Box::new(left), Box::new(right)))
+    }
+}
+
+impl LabelExpression {
+    /// Labels the expression refers to, in order of appearance
+    pub fn labels(&self) -> Vec<&str> {
+        match self {
+            LabelExpression::Label(label) => vec![label],
+            LabelExpression::Not(inner) => inner.labels(),
+            LabelExpression::And(left, right) | LabelExpression::Or(left, right) => {
+                let mut labels = left.labels();
+                labels.extend(right.labels());
+                labels
+            }
+        }
+    }
+
+    /// Mutable access to the labels the expression refers to
+    pub fn labels_mut(&mut self) -> Vec<&mut String> {
+        match self {
+            LabelExpression::Label(label) => vec![label],
+            LabelExpression::Not(inner) => inner.labels_mut(),
+            LabelExpression::And(left, right) | LabelExpression::Or(left, right) => {
+                let mut labels = left.labels_mut();
+                labels.extend(right.labels_mut());
+                labels
+            }
+        }
+    }
 }
 
 impl RelationshipPattern {
@@ -367,6 +732,419 @@ impl RelationshipPattern {
     }
 }
 
+impl ValueExpression {
+    /// Whether `name` is an aggregate function (`count`, `sum`, `avg`, `min`, `max`, `collect`)
+    pub fn is_aggregate_function(name: &str) -> bool {
+        matches!(
+            name.to_lowercase().as_str(),
+            "count" | "sum" | "avg" | "min" | "max" | "collect"
+        )
+    }
+
+    /// Whether the expression calls an aggregate function anywhere
+    pub fn contains_aggregate(&self) -> bool {
+        match self {
+            ValueExpression::Function { name, args, .. } => {
+                Self::is_aggregate_function(name) || args.iter().any(Self::contains_aggregate)
+            }
+            ValueExpression::Case {
+                branches,
+                else_result,
+            } => {
+                branches.iter().any(|(condition, result)| {
+                    condition.contains_aggregate() || result.contains_aggregate()
+                }) || else_result.as_ref().is_some_and(|e| e.contains_aggregate())
+            }
+            ValueExpression::List(items) => items.iter().any(Self::contains_aggregate),
+            ValueExpression::Index { list, index } => {
+                list.contains_aggregate() || index.contains_aggregate()
+            }
+            ValueExpression::Slice { list, from, to } => {
+                list.contains_aggregate()
+                    || from.as_ref().is_some_and(|e| e.contains_aggregate())
+                    || to.as_ref().is_some_and(|e| e.contains_aggregate())
+            }
+            ValueExpression::ListComprehension {
+                list,
+                predicate,
+                projection,
+                ..
+            } => {
+                list.contains_aggregate()
+                    || predicate.as_ref().is_some_and(|p| p.contains_aggregate())
+                    || projection.as_ref().is_some_and(|e| e.contains_aggregate())
+            }
+            ValueExpression::Arithmetic { left, right, .. } => {
+                left.contains_aggregate() || right.contains_aggregate()
+            }
+            ValueExpression::Map(entries) => {
+                entries.iter().any(|(_, value)| value.contains_aggregate())
+            }
+            _ => false,
+        }
+    }
+
+    /// The expressions and predicates this expression is directly built from
+    pub(crate) fn operands(&self) -> (Vec<&ValueExpression>, Vec<&BooleanExpression>) {
+        match self {
+            ValueExpression::Variable(_)
+            | ValueExpression::Property(_)
+            | ValueExpression::Literal(_)
+            | ValueExpression::CountSubquery(_) => (Vec::new(), Vec::new()),
+            ValueExpression::Function { args, .. } | ValueExpression::List(args) => {
+                (args.iter().collect(), Vec::new())
+            }
+            ValueExpression::Case {
+                branches,
+                else_result,
+            } => (
+                branches
+                    .iter()
+                    .map(|(_, result)| result)
+                    .chain(else_result.as_deref())
+                    .collect(),
+                branches.iter().map(|(condition, _)| condition).collect(),
+            ),
+            ValueExpression::Index { list, index } => (vec![list, index], Vec::new()),
+            ValueExpression::Slice { list, from, to } => (
+                std::iter::once(list.as_ref())
+                    .chain(from.as_deref())
+                    .chain(to.as_deref())
+                    .collect(),
+                Vec::new(),
+            ),
+            ValueExpression::ListComprehension {
+                list,
+                predicate,
+                projection,
+                ..
+            } => (
+                std::iter::once(list.as_ref())
+                    .chain(projection.as_deref())
+                    .collect(),
+                predicate.as_deref().into_iter().collect(),
+            ),
+            ValueExpression::Arithmetic { left, right, .. } => (vec![left, right], Vec::new()),
+            ValueExpression::Map(entries) => {
+                (entries.iter().map(|(_, value)| value).collect(), Vec::new())
+            }
+        }
+    }
+
+    /// Mutable access to the expressions and predicates this expression is directly
+    /// built from
+    fn operands_mut(&mut self) -> (Vec<&mut ValueExpression>, Vec<&mut BooleanExpression>) {
+        match self {
+            ValueExpression::Variable(_)
+            | ValueExpression::Property(_)
+            | ValueExpression::Literal(_)
+            | ValueExpression::CountSubquery(_) => (Vec::new(), Vec::new()),
+            ValueExpression::Function { args, .. } | ValueExpression::List(args) => {
+                (args.iter_mut().collect(), Vec::new())
+            }
+            ValueExpression::Case {
+                branches,
+                else_result,
+            } => {
+                let mut values = Vec::new();
+                let mut predicates = Vec::new();
+                for (condition, result) in branches {
+                    predicates.push(condition);
+                    values.push(result);
+                }
+                values.extend(else_result.as_deref_mut());
+                (values, predicates)
+            }
+            ValueExpression::Index { list, index } => (vec![list, index], Vec::new()),
+            ValueExpression::Slice { list, from, to } => (
+                std::iter::once(list.as_mut())
+                    .chain(from.as_deref_mut())
+                    .chain(to.as_deref_mut())
+                    .collect(),
+                Vec::new(),
+            ),
+            ValueExpression::ListComprehension {
+                list,
+                predicate,
+                projection,
+                ..
+            } => (
+                std::iter::once(list.as_mut())
+                    .chain(projection.as_deref_mut())
+                    .collect(),
+                predicate.as_deref_mut().into_iter().collect(),
+            ),
+            ValueExpression::Arithmetic { left, right, .. } => (vec![left, right], Vec::new()),
+            ValueExpression::Map(entries) => (
+                entries.iter_mut().map(|(_, value)| value).collect(),
+                Vec::new(),
+            ),
+        }
+    }
+
+    /// Whether a label predicate appears anywhere in this expression
+    pub fn contains_label_predicate(&self) -> bool {
+        let (values, predicates) = self.operands();
+        values
+            .into_iter()
+            .any(ValueExpression::contains_label_predicate)
+            || predicates
+                .into_iter()
+                .any(BooleanExpression::contains_label_predicate)
+    }
+
+    /// MATCH clauses of the `EXISTS { }` and `COUNT { }` subqueries in this expression,
+    /// not counting those nested inside other subqueries
+    pub fn subqueries(&self) -> Vec<&MatchClause> {
+        if let ValueExpression::CountSubquery(clause) = self {
+            return vec![clause];
+        }
+        let (values, predicates) = self.operands();
+        values
+            .into_iter()
+            .flat_map(ValueExpression::subqueries)
+            .chain(
+                predicates
+                    .into_iter()
+                    .flat_map(BooleanExpression::subqueries),
+            )
+            .collect()
+    }
+
+    /// Mutable access to the MATCH clauses of the `EXISTS { }` and `COUNT { }` subqueries
+    /// in this expression, not counting those nested inside other subqueries
+    pub fn subqueries_mut(&mut self) -> Vec<&mut MatchClause> {
+        if let ValueExpression::CountSubquery(clause) = self {
+            return vec![clause];
+        }
+        let (values, predicates) = self.operands_mut();
+        values
+            .into_iter()
+            .flat_map(ValueExpression::subqueries_mut)
+            .chain(
+                predicates
+                    .into_iter()
+                    .flat_map(BooleanExpression::subqueries_mut),
+            )
+            .collect()
+    }
+
+    /// The `COUNT { }` subquery expressions in this expression, not counting those
+    /// nested inside other subqueries
+    pub fn count_subqueries_mut(&mut self) -> Vec<&mut ValueExpression> {
+        if matches!(self, ValueExpression::CountSubquery(_)) {
+            return vec![self];
+        }
+        let (values, predicates) = self.operands_mut();
+        values
+            .into_iter()
+            .flat_map(ValueExpression::count_subqueries_mut)
+            .chain(
+                predicates
+                    .into_iter()
+                    .flat_map(BooleanExpression::count_subqueries_mut),
+            )
+            .collect()
+    }
+}
+
+impl MatchClause {
+    /// This clause followed by the clauses of the subqueries in its WHERE, recursively
+    fn with_subqueries(&self) -> Vec<&MatchClause> {
+        let nested = self
+            .where_clause
+            .iter()
+            .flat_map(|w| w.expression.subqueries())
+            .flat_map(MatchClause::with_subqueries);
+        std::iter::once(self).chain(nested).collect()
+    }
+}
+
+impl BooleanExpression {
+    /// The expressions and predicates this predicate is directly built from
+    pub(crate) fn operands(&self) -> (Vec<&ValueExpression>, Vec<&BooleanExpression>) {
+        match self {
+            BooleanExpression::Comparison { left, right, .. } => (vec![left, right], Vec::new()),
+            BooleanExpression::StringMatch {
+                expression,
+                pattern,
+                ..
+            } => (vec![expression, pattern], Vec::new()),
+            BooleanExpression::FullTextSearch { expression, query } => {
+                (vec![expression, query], Vec::new())
+            }
+            BooleanExpression::And(left, right) | BooleanExpression::Or(left, right) => {
+                (Vec::new(), vec![left, right])
+            }
+            BooleanExpression::Not(inner) => (Vec::new(), vec![inner]),
+            BooleanExpression::In { expression, list } => (
+                std::iter::once(expression).chain(list).collect(),
+                Vec::new(),
+            ),
+            BooleanExpression::Like { expression, .. }
+            | BooleanExpression::IsNull(expression)
+            | BooleanExpression::IsNotNull(expression) => (vec![expression], Vec::new()),
+            BooleanExpression::Exists(_)
+            | BooleanExpression::ExistsSubquery(_)
+            | BooleanExpression::HasLabels { .. } => (Vec::new(), Vec::new()),
+        }
+    }
+
+    /// Mutable access to the expressions and predicates this predicate is directly
+    /// built from
+    fn operands_mut(&mut self) -> (Vec<&mut ValueExpression>, Vec<&mut BooleanExpression>) {
+        match self {
+            BooleanExpression::Comparison { left, right, .. } => (vec![left, right], Vec::new()),
+            BooleanExpression::StringMatch {
+                expression,
+                pattern,
+                ..
+            } => (vec![expression, pattern], Vec::new()),
+            BooleanExpression::FullTextSearch { expression, query } => {
+                (vec![expression, query], Vec::new())
+            }
+            BooleanExpression::And(left, right) | BooleanExpression::Or(left, right) => {
+                (Vec::new(), vec![left, right])
+            }
+            BooleanExpression::Not(inner) => (Vec::new(), vec![inner]),
+            BooleanExpression::In { expression, list } => (
+                std::iter::once(expression).chain(list).collect(),
+                Vec::new(),
+            ),
+            BooleanExpression::Like { expression, .. }
+            | BooleanExpression::IsNull(expression)
+            | BooleanExpression::IsNotNull(expression) => (vec![expression], Vec::new()),
+            BooleanExpression::Exists(_)
+            | BooleanExpression::ExistsSubquery(_)
+            | BooleanExpression::HasLabels { .. } => (Vec::new(), Vec::new()),
+        }
+    }
+
+    /// MATCH clauses of the `EXISTS { }` and `COUNT { }` subqueries in this predicate,
+    /// not counting those nested inside other subqueries
+    pub fn subqueries(&self) -> Vec<&MatchClause> {
+        if let BooleanExpression::ExistsSubquery(clause) = self {
+            return vec![clause];
+        }
+        let (values, predicates) = self.operands();
+        values
+            .into_iter()
+            .flat_map(ValueExpression::subqueries)
+            .chain(
+                predicates
+                    .into_iter()
+                    .flat_map(BooleanExpression::subqueries),
+            )
+            .collect()
+    }
+
+    /// Mutable access to the MATCH clauses of the `EXISTS { }` and `COUNT { }`
+    /// subqueries in this predicate, not counting those nested inside other subqueries
+    pub fn subqueries_mut(&mut self) -> Vec<&mut MatchClause> {
+        if let BooleanExpression::ExistsSubquery(clause) = self {
+            return vec![clause];
+        }
+        let (values, predicates) = self.operands_mut();
+        values
+            .into_iter()
+            .flat_map(ValueExpression::subqueries_mut)
+            .chain(
+                predicates
+                    .into_iter()
+                    .flat_map(BooleanExpression::subqueries_mut),
+            )
+            .collect()
+    }
+
+    /// The `COUNT { }` subquery expressions in this predicate, not counting those nested
+    /// inside other subqueries
+    pub fn count_subqueries_mut(&mut self) -> Vec<&mut ValueExpression> {
+        let (values, predicates) = self.operands_mut();
+        values
+            .into_iter()
+            .flat_map(ValueExpression::count_subqueries_mut)
+            .chain(
+                predicates
+                    .into_iter()
+                    .flat_map(BooleanExpression::count_subqueries_mut),
+            )
+            .collect()
+    }
+
+    /// Whether the predicate calls an aggregate function anywhere
+    pub fn contains_aggregate(&self) -> bool {
+        match self {
+            BooleanExpression::Comparison { left, right, .. } => {
+                left.contains_aggregate() || right.contains_aggregate()
+            }
+            BooleanExpression::StringMatch {
+                expression,
+                pattern,
+                ..
+            } => expression.contains_aggregate() || pattern.contains_aggregate(),
+            BooleanExpression::FullTextSearch { expression, query } => {
+                expression.contains_aggregate() || query.contains_aggregate()
+            }
+            BooleanExpression::And(left, right) | BooleanExpression::Or(left, right) => {
+                left.contains_aggregate() || right.contains_aggregate()
+            }
+            BooleanExpression::Not(inner) => inner.contains_aggregate(),
+            BooleanExpression::In { expression, list } => {
+                expression.contains_aggregate()
+                    || list.iter().any(ValueExpression::contains_aggregate)
+            }
+            BooleanExpression::Like { expression, .. }
+            | BooleanExpression::IsNull(expression)
+            | BooleanExpression::IsNotNull(expression) => expression.contains_aggregate(),
+            BooleanExpression::Exists(_)
+            | BooleanExpression::ExistsSubquery(_)
+            | BooleanExpression::HasLabels { .. } => false,
+        }
+    }
+
+    /// The label predicates combined by AND, OR and NOT into this predicate
+    pub fn label_predicates(&self) -> Vec<(&str, &LabelExpression)> {
+        match self {
+            BooleanExpression::HasLabels { variable, labels } => vec![(variable, labels)],
+            BooleanExpression::And(left, right) | BooleanExpression::Or(left, right) => {
+                let mut predicates = left.label_predicates();
+                predicates.extend(right.label_predicates());
+                predicates
+            }
+            BooleanExpression::Not(inner) => inner.label_predicates(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Whether a label predicate appears anywhere in this predicate
+    pub fn contains_label_predicate(&self) -> bool {
+        if matches!(self, BooleanExpression::HasLabels { .. }) {
+            return true;
+        }
+        let (values, predicates) = self.operands();
+        values
+            .into_iter()
+            .any(ValueExpression::contains_label_predicate)
+            || predicates
+                .into_iter()
+                .any(BooleanExpression::contains_label_predicate)
+    }
+
+    /// Mutable access to the label expressions of [`Self::label_predicates`]
+    pub fn label_expressions_mut(&mut self) -> Vec<&mut LabelExpression> {
+        match self {
+            BooleanExpression::HasLabels { labels, .. } => vec![labels],
+            BooleanExpression::And(left, right) | BooleanExpression::Or(left, right) => {
+                let mut expressions = left.label_expressions_mut();
+                expressions.extend(right.label_expressions_mut());
+                expressions
+            }
+            BooleanExpression::Not(inner) => inner.label_expressions_mut(),
+            _ => Vec::new(),
+        }
+    }
+}
+
 impl PropertyRef {
     /// Create a new property reference
     pub fn new<S: Into<String>>(variable: S, property: S) -> Self {
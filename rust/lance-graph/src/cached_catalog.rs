@@ -0,0 +1,417 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Caching decorator for graph source catalogs.
+//!
+//! Resolving a source can be expensive for remote catalogs (for example Lance datasets
+//! on object storage, whose schema comes from the manifest). [`CachedCatalog`]
+//! memoizes resolved table sources, and therefore their schemas, along with the
+//! label and relationship type listings, source descriptions, statistics and adjacency
+//! sources, so repeated planning does not hit the underlying catalog. The catalogs of
+//! named graphs it hands out for `USE` are cached the same way, sharing its entries.
+//! Entries expire after an optional TTL and can be dropped explicitly with
+//! [`CachedCatalog::invalidate`] or [`CachedCatalog::refresh_all`].
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use datafusion::logical_expr::TableSource;
+
+use crate::source_catalog::{GraphSourceCatalog, SourceDescription, SourceKind, SourceStatistics};
+
+/// Cache key: optional graph namespace plus label, relationship type or graph name.
+type CacheKey = (Option<String>, String);
+
+struct CacheEntry<T> {
+    value: T,
+    cached_at: Instant,
+}
+
+type Entries<K, T> = HashMap<K, CacheEntry<T>>;
+
+#[derive(Default)]
+struct CacheState {
+    nodes: Entries<CacheKey, Arc<dyn TableSource>>,
+    relationships: Entries<CacheKey, Arc<dyn TableSource>>,
+    listings: Entries<(Option<String>, SourceKind), Vec<String>>,
+    descriptions: Entries<CacheKey, SourceDescription>,
+    statistics: Entries<CacheKey, SourceStatistics>,
+    adjacency: Entries<CacheKey, Arc<dyn TableSource>>,
+    graphs: Entries<CacheKey, Arc<dyn GraphSourceCatalog>>,
+}
+
+/// A catalog that memoizes the sources resolved by an inner catalog.
+///
+/// Only successful lookups are cached, so a label registered later in the inner
+/// catalog becomes visible on the next lookup. Listings are cached even when empty and
+/// are dropped by any invalidation.
+pub struct CachedCatalog {
+    inner: Arc<dyn GraphSourceCatalog>,
+    /// The named graph `inner` is the catalog of, when handed out by `graph_catalog`
+    graph: Option<String>,
+    ttl: Option<Duration>,
+    state: Arc<Mutex<CacheState>>,
+}
+
+impl CachedCatalog {
+    /// Cache lookups against `inner` until they are invalidated.
+    pub fn new(inner: Arc<dyn GraphSourceCatalog>) -> Self {
+        Self {
+            inner,
+            graph: None,
+            ttl: None,
+            state: Arc::new(Mutex::new(CacheState::default())),
+        }
+    }
+
+    /// Expire cached entries `ttl` after they were resolved.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Drop everything cached about `name` in every graph, and the listings.
+    pub fn invalidate(&self, name: &str) {
+        let mut state = self.lock();
+        state.nodes.retain(|(_, key), _| key != name);
+        state.relationships.retain(|(_, key), _| key != name);
+        state.descriptions.retain(|(_, key), _| key != name);
+        state.statistics.retain(|(_, key), _| key != name);
+        state.adjacency.retain(|(_, key), _| key != name);
+        state.graphs.retain(|(_, key), _| key != name);
+        state.listings.clear();
+    }
+
+    /// Drop every cached entry.
+    pub fn refresh_all(&self) {
+        *self.lock() = CacheState::default();
+    }
+
+    /// Number of cached node and relationship sources.
+    pub fn cached_entries(&self) -> usize {
+        let state = self.lock();
+        state.nodes.len() + state.relationships.len()
+    }
+
+    /// The key of `name` in the graph this catalog is scoped to
+    fn key(&self, name: &str) -> CacheKey {
+        (self.graph.clone(), name.to_string())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, CacheState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn lookup<K: Eq + Hash, T: Clone>(
+        &self,
+        select: fn(&mut CacheState) -> &mut Entries<K, T>,
+        key: K,
+        resolve: impl FnOnce() -> Option<T>,
+    ) -> Option<T> {
+        {
+            let mut state = self.lock();
+            let entries = select(&mut state);
+            if let Some(entry) = entries.get(&key) {
+                let fresh = self.ttl.is_none_or(|ttl| entry.cached_at.elapsed() < ttl);
+                if fresh {
+                    return Some(entry.value.clone());
+                }
+                entries.remove(&key);
+            }
+        }
+
+        // Resolve outside the lock so a slow inner catalog does not block other lookups
+        let value = resolve()?;
+        select(&mut self.lock()).insert(
+            key,
+            CacheEntry {
+                value: value.clone(),
+                cached_at: Instant::now(),
+            },
+        );
+        Some(value)
+    }
+}
+
+impl GraphSourceCatalog for CachedCatalog {
+    fn node_source(&self, label: &str) -> Option<Arc<dyn TableSource>> {
+        self.lookup(
+            |s| &mut s.nodes,
+            self.key(label),
+            || self.inner.node_source(label),
+        )
+    }
+
+    fn relationship_source(&self, rel_type: &str) -> Option<Arc<dyn TableSource>> {
+        self.lookup(
+            |s| &mut s.relationships,
+            self.key(rel_type),
+            || self.inner.relationship_source(rel_type),
+        )
+    }
+
+    fn has_graph(&self, graph: &str) -> bool {
+        self.inner.has_graph(graph)
+    }
+
+    fn list_node_labels(&self) -> Vec<String> {
+        self.lookup(
+            |s| &mut s.listings,
+            (self.graph.clone(), SourceKind::Node),
+            || Some(self.inner.list_node_labels()),
+        )
+        .unwrap_or_default()
+    }
+
+    fn list_relationship_types(&self) -> Vec<String> {
+        self.lookup(
+            |s| &mut s.listings,
+            (self.graph.clone(), SourceKind::Relationship),
+            || Some(self.inner.list_relationship_types()),
+        )
+        .unwrap_or_default()
+    }
+
+    fn describe(&self, name: &str) -> Option<SourceDescription> {
+        self.lookup(
+            |s| &mut s.descriptions,
+            self.key(name),
+            || self.inner.describe(name),
+        )
+    }
+
+    fn statistics(&self, name: &str) -> Option<SourceStatistics> {
+        self.lookup(
+            |s| &mut s.statistics,
+            self.key(name),
+            || self.inner.statistics(name),
+        )
+    }
+
+    fn adjacency_source(&self, rel_type: &str) -> Option<Arc<dyn TableSource>> {
+        self.lookup(
+            |s| &mut s.adjacency,
+            self.key(rel_type),
+            || self.inner.adjacency_source(rel_type),
+        )
+    }
+
+    fn node_source_in(&self, graph: &str, label: &str) -> Option<Arc<dyn TableSource>> {
+        self.lookup(
+            |s| &mut s.nodes,
+            (Some(graph.to_string()), label.to_string()),
+            || self.inner.node_source_in(graph, label),
+        )
+    }
+
+    fn relationship_source_in(&self, graph: &str, rel_type: &str) -> Option<Arc<dyn TableSource>> {
+        self.lookup(
+            |s| &mut s.relationships,
+            (Some(graph.to_string()), rel_type.to_string()),
+            || self.inner.relationship_source_in(graph, rel_type),
+        )
+    }
+
+    fn graph_catalog(&self, graph: &str) -> Option<Arc<dyn GraphSourceCatalog>> {
+        let inner = self.lookup(
+            |s| &mut s.graphs,
+            self.key(graph),
+            || self.inner.graph_catalog(graph),
+        )?;
+        Some(Arc::new(Self {
+            inner,
+            graph: Some(graph.to_string()),
+            ttl: self.ttl,
+            state: self.state.clone(),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source_catalog::{MutableCatalog, NamespacedCatalog, SimpleTableSource};
+    use arrow_schema::{DataType, Field, Schema};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Counts how often the wrapped catalog is consulted.
+    struct CountingCatalog {
+        inner: MutableCatalog,
+        lookups: AtomicUsize,
+    }
+
+    impl GraphSourceCatalog for CountingCatalog {
+        fn node_source(&self, label: &str) -> Option<Arc<dyn TableSource>> {
+            self.lookups.fetch_add(1, Ordering::SeqCst);
+            self.inner.node_source(label)
+        }
+
+        fn relationship_source(&self, rel_type: &str) -> Option<Arc<dyn TableSource>> {
+            self.lookups.fetch_add(1, Ordering::SeqCst);
+            self.inner.relationship_source(rel_type)
+        }
+
+        fn list_node_labels(&self) -> Vec<String> {
+            self.lookups.fetch_add(1, Ordering::SeqCst);
+            self.inner.list_node_labels()
+        }
+
+        fn list_relationship_types(&self) -> Vec<String> {
+            self.lookups.fetch_add(1, Ordering::SeqCst);
+            self.inner.list_relationship_types()
+        }
+
+        fn statistics(&self, name: &str) -> Option<SourceStatistics> {
+            self.lookups.fetch_add(1, Ordering::SeqCst);
+            self.inner
+                .node_source(name)
+                .map(|_| SourceStatistics::with_row_count(2))
+        }
+
+        fn adjacency_source(&self, rel_type: &str) -> Option<Arc<dyn TableSource>> {
+            self.lookups.fetch_add(1, Ordering::SeqCst);
+            self.inner.relationship_source(rel_type)
+        }
+    }
+
+    fn source_with_column(name: &str) -> Arc<dyn TableSource> {
+        Arc::new(SimpleTableSource::new(Arc::new(Schema::new(vec![
+            Field::new(name, DataType::Int64, false),
+        ]))))
+    }
+
+    fn counting_catalog() -> Arc<CountingCatalog> {
+        let inner = MutableCatalog::new();
+        inner.register_node_source("Person", source_with_column("id"));
+        inner.register_relationship_source("KNOWS", source_with_column("src_id"));
+        Arc::new(CountingCatalog {
+            inner,
+            lookups: AtomicUsize::new(0),
+        })
+    }
+
+    #[test]
+    fn test_cached_catalog_memoizes_lookups() {
+        let counting = counting_catalog();
+        let cached = CachedCatalog::new(counting.clone());
+
+        for _ in 0..3 {
+            assert!(cached.node_source("Person").is_some());
+            assert!(cached.relationship_source("KNOWS").is_some());
+        }
+        assert_eq!(counting.lookups.load(Ordering::SeqCst), 2);
+        assert_eq!(cached.cached_entries(), 2);
+
+        // Misses are not cached
+        assert!(cached.node_source("Company").is_none());
+        assert!(cached.node_source("Company").is_none());
+        assert_eq!(counting.lookups.load(Ordering::SeqCst), 4);
+    }
+
+    #[test]
+    fn test_cached_catalog_memoizes_listings_and_descriptions() {
+        let counting = counting_catalog();
+        let cached = CachedCatalog::new(counting.clone());
+
+        for _ in 0..3 {
+            assert_eq!(cached.list_node_labels(), vec!["Person"]);
+            assert_eq!(cached.list_relationship_types(), vec!["KNOWS"]);
+            assert_eq!(cached.describe("Person").unwrap().kind, SourceKind::Node);
+            assert_eq!(cached.statistics("Person").unwrap().row_count, Some(2));
+        }
+        // Describing resolves the node source once
+        assert_eq!(counting.lookups.load(Ordering::SeqCst), 4);
+
+        // A label registered later shows up once the listings are invalidated
+        counting
+            .inner
+            .register_node_source("Company", source_with_column("id"));
+        assert_eq!(cached.list_node_labels(), vec!["Person"]);
+        cached.invalidate("Company");
+        assert_eq!(cached.list_node_labels(), vec!["Company", "Person"]);
+        assert!(cached.describe("Person").is_some());
+        assert_eq!(counting.lookups.load(Ordering::SeqCst), 5);
+
+        cached.refresh_all();
+        assert!(cached.statistics("Person").is_some());
+        assert_eq!(counting.lookups.load(Ordering::SeqCst), 6);
+    }
+
+    #[test]
+    fn test_cached_catalog_invalidate_and_refresh() {
+        let counting = counting_catalog();
+        let cached = CachedCatalog::new(counting.clone());
+        cached.node_source("Person");
+        cached.relationship_source("KNOWS");
+
+        counting
+            .inner
+            .register_node_source("Person", source_with_column("person_id"));
+        let stale = cached.node_source("Person").unwrap();
+        assert_eq!(stale.schema().field(0).name(), "id");
+
+        cached.invalidate("Person");
+        assert_eq!(cached.cached_entries(), 1);
+        let fresh = cached.node_source("Person").unwrap();
+        assert_eq!(fresh.schema().field(0).name(), "person_id");
+
+        cached.refresh_all();
+        assert_eq!(cached.cached_entries(), 0);
+    }
+
+    #[test]
+    fn test_cached_catalog_caches_graph_catalogs() {
+        let counting = counting_catalog();
+        let namespaced = NamespacedCatalog::new().with_graph("hr", counting.clone());
+        let cached = CachedCatalog::new(Arc::new(namespaced));
+        let hr = cached.graph_catalog("hr").unwrap();
+        assert!(cached.graph_catalog("finance").is_none());
+
+        for _ in 0..3 {
+            assert!(hr.node_source("Person").is_some());
+            assert!(hr.adjacency_source("KNOWS").is_some());
+            assert_eq!(hr.list_node_labels(), vec!["Person"]);
+            assert_eq!(hr.statistics("Person").unwrap().row_count, Some(2));
+        }
+        assert_eq!(counting.lookups.load(Ordering::SeqCst), 4);
+
+        // Scoped and qualified lookups share entries, which other graphs do not see
+        assert!(cached.node_source_in("hr", "Person").is_some());
+        assert!(cached
+            .graph_catalog("hr")
+            .unwrap()
+            .node_source("Person")
+            .is_some());
+        assert!(cached.node_source("Person").is_none());
+        assert_eq!(counting.lookups.load(Ordering::SeqCst), 4);
+
+        cached.invalidate("Person");
+        assert!(hr.node_source("Person").is_some());
+        assert!(hr.statistics("Person").is_some());
+        assert_eq!(counting.lookups.load(Ordering::SeqCst), 6);
+
+        cached.refresh_all();
+        assert!(hr.adjacency_source("KNOWS").is_some());
+        assert_eq!(counting.lookups.load(Ordering::SeqCst), 7);
+
+        let expiring = CachedCatalog::new(Arc::new(
+            NamespacedCatalog::new().with_graph("hr", counting.clone()),
+        ))
+        .with_ttl(Duration::ZERO);
+        let hr = expiring.graph_catalog("hr").unwrap();
+        hr.node_source("Person");
+        hr.node_source("Person");
+        assert_eq!(counting.lookups.load(Ordering::SeqCst), 9);
+    }
+
+    #[test]
+    fn test_cached_catalog_ttl_expiry() {
+        let counting = counting_catalog();
+        let cached = CachedCatalog::new(counting.clone()).with_ttl(Duration::ZERO);
+        cached.node_source("Person");
+        cached.node_source("Person");
+        assert_eq!(counting.lookups.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,179 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Cancelling running queries.
+//!
+//! A query given a [`CancellationToken`] with [`CypherQuery::with_cancellation_token`],
+//! or a time limit with [`CypherQuery::with_timeout`], stops once the token is
+//! cancelled or the limit passes. Its scans and joins are dropped, a breadth-first
+//! expansion stops at its next hop, and execution fails with
+//! [`GraphError::Cancelled`]. A result stream yields that error as a
+//! `DataFusionError::External` and then ends.
+//!
+//! [`CypherQuery::with_cancellation_token`]: crate::query::CypherQuery::with_cancellation_token
+//! [`CypherQuery::with_timeout`]: crate::query::CypherQuery::with_timeout
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use datafusion::error::DataFusionError;
+use datafusion::execution::TaskContext;
+use tokio::sync::Notify;
+
+use crate::error::GraphError;
+
+/// Handle cancelling the queries it is given to, from any task or thread
+///
+/// Clones share one state: cancelling any clone cancels them all, and a token stays
+/// cancelled.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+    notify: Arc<Notify>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stop the queries running with this token, and any started with it later
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+        self.notify.notify_waiters();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Wait until the token is cancelled
+    pub async fn cancelled(&self) {
+        loop {
+            let notified = self.notify.notified();
+            if self.is_cancelled() {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+/// When one execution of a query must stop: on cancellation of its token, or at its
+/// deadline
+///
+/// Stored in the session config of the execution's task context, where operators
+/// looping without yielding check it.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Interrupt {
+    token: Option<CancellationToken>,
+    /// The deadline and the timeout it was computed from
+    deadline: Option<(Instant, Duration)>,
+}
+
+impl Interrupt {
+    /// Interrupt for an execution starting now
+    pub(crate) fn new(token: Option<CancellationToken>, timeout: Option<Duration>) -> Self {
+        Self {
+            token,
+            deadline: timeout.map(|timeout| (Instant::now() + timeout, timeout)),
+        }
+    }
+
+    pub(crate) fn is_set(&self) -> bool {
+        self.token.is_some() || self.deadline.is_some()
+    }
+
+    pub(crate) fn is_interrupted(&self) -> bool {
+        self.token
+            .as_ref()
+            .is_some_and(|token| token.is_cancelled())
+            || self
+                .deadline
+                .is_some_and(|(deadline, _)| Instant::now() >= deadline)
+    }
+
+    /// The error an interrupted execution fails with
+    pub(crate) fn error(&self) -> GraphError {
+        let message = match self.deadline {
+            Some((deadline, timeout))
+                if Instant::now() >= deadline
+                    && !self.token.as_ref().is_some_and(|t| t.is_cancelled()) =>
+            {
+                format!("timed out after {:?}", timeout)
+            }
+            _ => "cancelled by its cancellation token".to_string(),
+        };
+        GraphError::Cancelled {
+            message,
+            location: snafu::Location::new(file!(), line!(), column!()),
+        }
+    }
+
+    /// Fail when the execution running in `context` is interrupted
+    pub(crate) fn check(context: &TaskContext) -> std::result::Result<(), DataFusionError> {
+        match context.session_config().get_extension::<Interrupt>() {
+            Some(interrupt) if interrupt.is_interrupted() => {
+                Err(DataFusionError::External(Box::new(interrupt.error())))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Resolve once the execution is interrupted
+    pub(crate) async fn wait(self) {
+        let cancelled = async {
+            match &self.token {
+                Some(token) => token.cancelled().await,
+                None => futures::future::pending().await,
+            }
+        };
+        let expired = async {
+            match self.deadline {
+                Some((deadline, _)) => {
+                    tokio::time::sleep_until(tokio::time::Instant::from_std(deadline)).await
+                }
+                None => futures::future::pending().await,
+            }
+        };
+        futures::pin_mut!(cancelled, expired);
+        futures::future::select(cancelled, expired).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_token_wakes_waiters() {
+        let token = CancellationToken::new();
+        let waiter = tokio::spawn({
+            let token = token.clone();
+            async move { token.cancelled().await }
+        });
+        assert!(!token.is_cancelled());
+        token.cancel();
+        waiter.await.unwrap();
+        assert!(token.is_cancelled());
+        // A cancelled token stays cancelled
+        token.cancelled().await;
+    }
+
+    #[test]
+    fn test_interrupt_reports_timeouts() {
+        assert!(!Interrupt::default().is_interrupted());
+
+        let interrupt = Interrupt::new(None, Some(Duration::ZERO));
+        assert!(interrupt.is_interrupted());
+        assert!(interrupt.error().to_string().contains("timed out"));
+
+        let token = CancellationToken::new();
+        let interrupt = Interrupt::new(Some(token.clone()), Some(Duration::from_secs(60)));
+        assert!(!interrupt.is_interrupted());
+        token.cancel();
+        assert!(interrupt.is_interrupted());
+        assert!(matches!(interrupt.error(), GraphError::Cancelled { .. }));
+    }
+}
@@ -0,0 +1,226 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Serializable graph manifests.
+//!
+//! A [`GraphManifest`] captures a complete graph definition — the [`GraphConfig`]
+//! mappings plus the Lance dataset URI behind every label and relationship type — as
+//! JSON, so graph topologies can be checked into version control and loaded at
+//! startup instead of being wired up with builder code.
+//!
+//! ```json
+//! {
+//!   "config": {
+//!     "node_mappings": {
+//!       "Person": { "label": "Person", "id_field": "id", "property_fields": [], "filter_conditions": null }
+//!     },
+//!     "relationship_mappings": {},
+//!     "default_node_id_field": "id",
+//!     "default_relationship_type_field": "type"
+//!   },
+//!   "node_datasets": { "Person": "s3://bucket/graph/person.lance" },
+//!   "relationship_datasets": {}
+//! }
+//! ```
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::GraphConfig;
+use crate::error::{GraphError, Result};
+use crate::lance_catalog::LanceCatalog;
+
+/// A graph definition: mappings plus the dataset URI behind each label and type.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphManifest {
+    /// Node and relationship mappings
+    pub config: GraphConfig,
+    /// Dataset URI for each node label
+    #[serde(default)]
+    pub node_datasets: BTreeMap<String, String>,
+    /// Dataset URI for each relationship type
+    #[serde(default)]
+    pub relationship_datasets: BTreeMap<String, String>,
+}
+
+impl GraphManifest {
+    pub fn new(config: GraphConfig) -> Self {
+        Self {
+            config,
+            node_datasets: BTreeMap::new(),
+            relationship_datasets: BTreeMap::new(),
+        }
+    }
+
+    /// Set the dataset URI backing a node label.
+    pub fn with_node_dataset(mut self, label: impl Into<String>, uri: impl Into<String>) -> Self {
+        self.node_datasets.insert(label.into(), uri.into());
+        self
+    }
+
+    /// Set the dataset URI backing a relationship type.
+    pub fn with_relationship_dataset(
+        mut self,
+        rel_type: impl Into<String>,
+        uri: impl Into<String>,
+    ) -> Self {
+        self.relationship_datasets
+            .insert(rel_type.into(), uri.into());
+        self
+    }
+
+    /// Check that the config is valid and every dataset has a matching mapping.
+    pub fn validate(&self) -> Result<()> {
+        self.config.validate()?;
+
+        for label in self.node_datasets.keys() {
+            if self.config.get_node_mapping(label).is_none() {
+                return Err(GraphError::ConfigError {
+                    message: format!("Manifest dataset for unknown node label '{}'", label),
+                    location: snafu::Location::new(file!(), line!(), column!()),
+                });
+            }
+        }
+        for rel_type in self.relationship_datasets.keys() {
+            if self.config.get_relationship_mapping(rel_type).is_none() {
+                return Err(GraphError::ConfigError {
+                    message: format!(
+                        "Manifest dataset for unknown relationship type '{}'",
+                        rel_type
+                    ),
+                    location: snafu::Location::new(file!(), line!(), column!()),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Serialize to pretty-printed JSON.
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).map_err(|e| GraphError::ConfigError {
+            message: format!("Failed to serialize graph manifest: {}", e),
+            location: snafu::Location::new(file!(), line!(), column!()),
+        })
+    }
+
+    /// Parse and validate a manifest from JSON.
+    pub fn from_json(json: &str) -> Result<Self> {
+        let manifest: Self = serde_json::from_str(json).map_err(|e| GraphError::ConfigError {
+            message: format!("Invalid graph manifest: {}", e),
+            location: snafu::Location::new(file!(), line!(), column!()),
+        })?;
+        manifest.validate()?;
+        Ok(manifest)
+    }
+
+    /// Write the manifest to a JSON file.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        std::fs::write(path, self.to_json()?).map_err(|e| GraphError::ConfigError {
+            message: format!("Failed to write graph manifest {}: {}", path.display(), e),
+            location: snafu::Location::new(file!(), line!(), column!()),
+        })
+    }
+
+    /// Read and validate a manifest from a JSON file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path).map_err(|e| GraphError::ConfigError {
+            message: format!("Failed to read graph manifest {}: {}", path.display(), e),
+            location: snafu::Location::new(file!(), line!(), column!()),
+        })?;
+        Self::from_json(&json)
+    }
+
+    /// Open every dataset in the manifest and build a Lance catalog over them.
+    pub async fn open_catalog(&self) -> Result<LanceCatalog> {
+        let mut catalog = LanceCatalog::new();
+        for (label, uri) in &self.node_datasets {
+            catalog = catalog.with_node_uri(label, uri).await?;
+        }
+        for (rel_type, uri) in &self.relationship_datasets {
+            catalog = catalog.with_relationship_uri(rel_type, uri).await?;
+        }
+        Ok(catalog)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source_catalog::GraphSourceCatalog;
+    use arrow_array::{Int64Array, RecordBatch, RecordBatchIterator};
+    use arrow_schema::{DataType, Field, Schema};
+    use lance::Dataset;
+    use std::sync::Arc;
+
+    fn config() -> GraphConfig {
+        GraphConfig::builder()
+            .with_node_label("Person", "id")
+            .with_relationship("KNOWS", "src_id", "dst_id")
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_manifest_json_round_trip() {
+        let manifest = GraphManifest::new(config())
+            .with_node_dataset("Person", "s3://bucket/person.lance")
+            .with_relationship_dataset("KNOWS", "s3://bucket/knows.lance");
+
+        let json = manifest.to_json().unwrap();
+        let loaded = GraphManifest::from_json(&json).unwrap();
+        assert_eq!(loaded.node_datasets, manifest.node_datasets);
+        assert_eq!(loaded.relationship_datasets, manifest.relationship_datasets);
+        assert_eq!(
+            loaded
+                .config
+                .get_relationship_mapping("KNOWS")
+                .unwrap()
+                .source_id_field,
+            "src_id"
+        );
+    }
+
+    #[test]
+    fn test_manifest_rejects_unmapped_dataset() {
+        let manifest = GraphManifest::new(config()).with_node_dataset("Company", "company.lance");
+        let err = GraphManifest::from_json(&manifest.to_json().unwrap()).unwrap_err();
+        assert!(err.to_string().contains("Company"));
+
+        assert!(GraphManifest::from_json("{ not json").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_manifest_save_load_and_open() {
+        let dir = tempfile::tempdir().unwrap();
+        let person_uri = dir.path().join("person.lance");
+        let schema = Arc::new(Schema::new(vec![Field::new("id", DataType::Int64, false)]));
+        let batch =
+            RecordBatch::try_new(schema.clone(), vec![Arc::new(Int64Array::from(vec![1, 2]))])
+                .unwrap();
+        Dataset::write(
+            RecordBatchIterator::new(vec![Ok(batch)], schema),
+            person_uri.to_str().unwrap(),
+            None,
+        )
+        .await
+        .unwrap();
+
+        let manifest_path = dir.path().join("graph.json");
+        GraphManifest::new(config())
+            .with_node_dataset("Person", person_uri.to_str().unwrap())
+            .save(&manifest_path)
+            .unwrap();
+
+        let catalog = GraphManifest::load(&manifest_path)
+            .unwrap()
+            .open_catalog()
+            .await
+            .unwrap();
+        assert!(catalog.node_source("Person").is_some());
+        assert!(catalog.relationship_source("KNOWS").is_none());
+    }
+}
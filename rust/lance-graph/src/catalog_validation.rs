@@ -0,0 +1,306 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Up-front validation of a catalog against graph mappings.
+//!
+//! A mapping that names a column missing from its table, or joins columns of
+//! incompatible types, otherwise only fails deep inside DataFusion planning.
+//! [`validate_catalog`] checks every mapping against the resolved schemas and reports
+//! all problems at once.
+
+use std::fmt;
+
+use arrow_schema::{DataType, Schema};
+
+use crate::config::GraphConfig;
+use crate::source_catalog::{GraphSourceCatalog, SourceKind};
+
+/// A single problem found while validating a catalog against a [`GraphConfig`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationError {
+    /// The catalog has no source for a mapped label or relationship type
+    MissingSource { kind: SourceKind, name: String },
+    /// A mapping references a column the source does not have
+    MissingColumn {
+        kind: SourceKind,
+        name: String,
+        column: String,
+        available: Vec<String>,
+    },
+    /// A relationship endpoint column cannot be joined to any node id column
+    IncompatibleType {
+        rel_type: String,
+        column: String,
+        data_type: DataType,
+        node_id_types: Vec<DataType>,
+    },
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::MissingSource { kind, name } => write!(
+                f,
+                "{} '{}' is mapped in the config but has no source in the catalog",
+                kind_name(*kind),
+                name
+            ),
+            ValidationError::MissingColumn {
+                kind,
+                name,
+                column,
+                available,
+            } => write!(
+                f,
+                "{} '{}' maps column '{}', which is not in its source (available: {})",
+                kind_name(*kind),
+                name,
+                column,
+                available.join(", ")
+            ),
+            ValidationError::IncompatibleType {
+                rel_type,
+                column,
+                data_type,
+                node_id_types,
+            } => write!(
+                f,
+                "Relationship type '{}' endpoint column '{}' has type {} which cannot join \
+                 node id types {:?}",
+                rel_type, column, data_type, node_id_types
+            ),
+        }
+    }
+}
+
+fn kind_name(kind: SourceKind) -> &'static str {
+    match kind {
+        SourceKind::Node => "Node label",
+        SourceKind::Relationship => "Relationship type",
+    }
+}
+
+/// Check every mapping in `config` against the schemas resolved by `catalog`.
+///
+/// Returns an empty vector when the catalog is consistent with the config. Errors are
+/// sorted by mapping name so the report is stable.
+pub fn validate_catalog(
+    catalog: &dyn GraphSourceCatalog,
+    config: &GraphConfig,
+) -> Vec<ValidationError> {
+    let mut errors = Vec::new();
+    let mut node_id_types: Vec<DataType> = Vec::new();
+
+    let mut labels: Vec<&String> = config.node_mappings.keys().collect();
+    labels.sort();
+    for label in labels {
+        let mapping = &config.node_mappings[label];
+        let Some(source) = catalog.node_source(label) else {
+            errors.push(ValidationError::MissingSource {
+                kind: SourceKind::Node,
+                name: label.clone(),
+            });
+            continue;
+        };
+        let schema = source.schema();
+
+        let columns = mapping
+            .id_fields()
+            .into_iter()
+            .chain(
+                mapping
+                    .property_fields
+                    .iter()
+                    .map(|p| mapping.column_for(p)),
+            )
+            .chain(mapping.label_column.as_deref());
+        check_columns(&mut errors, SourceKind::Node, label, &schema, columns);
+
+        if let Ok(field) = schema.field_with_name(&mapping.id_field) {
+            if !node_id_types.contains(field.data_type()) {
+                node_id_types.push(field.data_type().clone());
+            }
+        }
+    }
+
+    let mut rel_types: Vec<&String> = config.relationship_mappings.keys().collect();
+    rel_types.sort();
+    for rel_type in rel_types {
+        let mapping = &config.relationship_mappings[rel_type];
+        if let (Some(column), Some(source_label)) =
+            (&mapping.adjacency_column, &mapping.source_label)
+        {
+            // Edge fields live inside the list column; only the column itself is checked
+            if let Some(source) = catalog.node_source(source_label) {
+                check_columns(
+                    &mut errors,
+                    SourceKind::Relationship,
+                    rel_type,
+                    &source.schema(),
+                    std::iter::once(column.as_str()),
+                );
+            }
+            continue;
+        }
+        let Some(source) = catalog.relationship_source(mapping.table_name()) else {
+            errors.push(ValidationError::MissingSource {
+                kind: SourceKind::Relationship,
+                name: rel_type.clone(),
+            });
+            continue;
+        };
+        let schema = source.schema();
+
+        let columns = mapping
+            .source_id_fields()
+            .into_iter()
+            .chain(mapping.target_id_fields())
+            .chain(mapping.type_field.as_deref())
+            .chain(
+                mapping
+                    .property_fields
+                    .iter()
+                    .map(|p| mapping.column_for(p)),
+            );
+        check_columns(
+            &mut errors,
+            SourceKind::Relationship,
+            rel_type,
+            &schema,
+            columns,
+        );
+
+        if node_id_types.is_empty() {
+            continue;
+        }
+        for column in [&mapping.source_id_field, &mapping.target_id_field] {
+            let Ok(field) = schema.field_with_name(column) else {
+                continue;
+            };
+            let joinable = node_id_types
+                .iter()
+                .any(|id_type| join_compatible(field.data_type(), id_type));
+            if !joinable {
+                errors.push(ValidationError::IncompatibleType {
+                    rel_type: rel_type.clone(),
+                    column: column.clone(),
+                    data_type: field.data_type().clone(),
+                    node_id_types: node_id_types.clone(),
+                });
+            }
+        }
+    }
+
+    errors
+}
+
+fn check_columns<'a>(
+    errors: &mut Vec<ValidationError>,
+    kind: SourceKind,
+    name: &str,
+    schema: &Schema,
+    columns: impl Iterator<Item = &'a str>,
+) {
+    for column in columns {
+        if schema.field_with_name(column).is_err() {
+            errors.push(ValidationError::MissingColumn {
+                kind,
+                name: name.to_string(),
+                column: column.to_string(),
+                available: schema.fields().iter().map(|f| f.name().clone()).collect(),
+            });
+        }
+    }
+}
+
+/// Whether DataFusion can equi-join the two types without an explicit cast.
+fn join_compatible(left: &DataType, right: &DataType) -> bool {
+    left == right
+        || (left.is_integer() && right.is_integer())
+        || (is_string(left) && is_string(right))
+}
+
+fn is_string(data_type: &DataType) -> bool {
+    matches!(
+        data_type,
+        DataType::Utf8 | DataType::LargeUtf8 | DataType::Utf8View
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::NodeMapping;
+    use crate::source_catalog::{InMemoryCatalog, SimpleTableSource};
+    use arrow_schema::Field;
+    use std::sync::Arc;
+
+    fn source(fields: Vec<(&str, DataType)>) -> Arc<SimpleTableSource> {
+        Arc::new(SimpleTableSource::new(Arc::new(Schema::new(
+            fields
+                .into_iter()
+                .map(|(name, data_type)| Field::new(name, data_type, false))
+                .collect::<Vec<_>>(),
+        ))))
+    }
+
+    #[test]
+    fn test_validate_consistent_catalog() {
+        let catalog = InMemoryCatalog::new()
+            .with_node_source("Person", source(vec![("id", DataType::Int64)]))
+            .with_relationship_source(
+                "KNOWS",
+                source(vec![
+                    ("src_id", DataType::Int32),
+                    ("dst_id", DataType::Int64),
+                ]),
+            );
+        let config = GraphConfig::builder()
+            .with_node_label("Person", "id")
+            .with_relationship("KNOWS", "src_id", "dst_id")
+            .build()
+            .unwrap();
+        assert!(validate_catalog(&catalog, &config).is_empty());
+    }
+
+    #[test]
+    fn test_validate_reports_every_problem() {
+        let catalog = InMemoryCatalog::new()
+            .with_node_source("Person", source(vec![("person_id", DataType::Int64)]))
+            .with_relationship_source(
+                "KNOWS",
+                source(vec![
+                    ("src_id", DataType::Utf8),
+                    ("dst_id", DataType::Int64),
+                ]),
+            );
+        let config = GraphConfig::builder()
+            .with_node_mapping(
+                NodeMapping::new("Person", "person_id").with_properties(vec!["name".to_string()]),
+            )
+            .with_node_label("Company", "id")
+            .with_relationship("KNOWS", "src_id", "dst_id")
+            .build()
+            .unwrap();
+
+        let errors = validate_catalog(&catalog, &config);
+        assert_eq!(errors.len(), 3, "{:?}", errors);
+        assert_eq!(
+            errors[0],
+            ValidationError::MissingSource {
+                kind: SourceKind::Node,
+                name: "Company".to_string()
+            }
+        );
+        assert!(matches!(
+            &errors[1],
+            ValidationError::MissingColumn { column, available, .. }
+                if column == "name" && available == &vec!["person_id".to_string()]
+        ));
+        assert!(matches!(
+            &errors[2],
+            ValidationError::IncompatibleType { column, .. } if column == "src_id"
+        ));
+        assert!(errors[1].to_string().contains("available: person_id"));
+    }
+}
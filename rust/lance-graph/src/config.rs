@@ -9,6 +9,7 @@ use std::collections::HashMap;
 
 /// Configuration for mapping Lance datasets to property graphs
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct GraphConfig {
     /// Mapping of node labels to their field configurations
     pub node_mappings: HashMap<String, NodeMapping>,
@@ -18,35 +19,112 @@ pub struct GraphConfig {
     pub default_node_id_field: String,
     /// Default relationship type field if not specified in mappings
     pub default_relationship_type_field: String,
+    /// How query labels and relationship types are matched against mapping names
+    #[serde(default)]
+    pub label_resolution: LabelResolution,
+    /// Deepest expansion allowed for variable-length patterns, and the depth explored
+    /// by patterns without an upper bound such as `*` or `*2..`
+    pub max_variable_length_hops: u32,
+    /// How variable-length patterns are expanded
+    #[serde(default)]
+    pub variable_length_expansion: VariableLengthExpansion,
+    /// Whether the patterns of one MATCH clause may bind the same relationship twice
+    #[serde(default)]
+    pub relationship_uniqueness: RelationshipUniqueness,
+    /// Parallelism and batching of query execution
+    #[serde(default)]
+    pub execution: ExecutionConfig,
 }
 
 /// Configuration for mapping node labels to dataset fields
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NodeMapping {
-    /// The node label (e.g., "Person", "Product")
+    /// The node label (e.g., "Person", "Product"); in config files it defaults to the
+    /// mapping's key
+    #[serde(default)]
     pub label: String,
     /// Field name that serves as the node identifier
     pub id_field: String,
+    /// Further identifier fields when node identity spans several columns
+    #[serde(default)]
+    pub additional_id_fields: Vec<String>,
     /// Optional fields that define node properties
+    #[serde(default)]
     pub property_fields: Vec<String>,
-    /// Optional filter conditions for this node type
+    /// Physical column behind each graph property whose name differs from its column
+    #[serde(default)]
+    pub property_aliases: HashMap<String, String>,
+    /// Derived properties defined as SQL expressions over the table's columns
+    #[serde(default)]
+    pub computed_properties: HashMap<String, String>,
+    /// SQL predicate over the table's columns selecting this label's rows, applied to
+    /// every scan of the label
     pub filter_conditions: Option<String>,
+    /// Optional discriminator column when several labels share one table
+    #[serde(default)]
+    pub label_column: Option<String>,
+    /// Value of `label_column` identifying this label (defaults to the label)
+    #[serde(default)]
+    pub label_value: Option<String>,
+    /// Parent label; queries for the parent also match this label's rows
+    #[serde(default)]
+    pub extends: Option<String>,
 }
 
 /// Configuration for mapping relationship types to dataset fields
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RelationshipMapping {
-    /// The relationship type (e.g., "KNOWS", "PURCHASED")
+    /// The relationship type (e.g., "KNOWS", "PURCHASED"); in config files it defaults
+    /// to the mapping's key
+    #[serde(default)]
     pub relationship_type: String,
     /// Field containing the source node ID
     pub source_id_field: String,
     /// Field containing the target node ID
     pub target_id_field: String,
-    /// Optional field containing the relationship type
+    /// Further source key fields, matching the source node's additional id fields
+    #[serde(default)]
+    pub additional_source_id_fields: Vec<String>,
+    /// Further target key fields, matching the target node's additional id fields
+    #[serde(default)]
+    pub additional_target_id_fields: Vec<String>,
+    /// Name of the source table when it differs from the relationship type, so that
+    /// several types can share one edge table
+    #[serde(default)]
+    pub table: Option<String>,
+    /// Optional discriminator column when several types share one table
     pub type_field: Option<String>,
+    /// Value of `type_field` identifying this type (defaults to the relationship type)
+    #[serde(default)]
+    pub type_value: Option<String>,
+    /// Whether each row stores an unordered pair, so the relationship matches in both
+    /// directions regardless of the arrow in the query
+    #[serde(default)]
+    pub undirected: bool,
+    /// List-of-struct column on the source label's table holding one struct per edge,
+    /// used instead of a separate edge table
+    ///
+    /// `source_id_field` then names the node id column and `target_id_field` and the
+    /// property fields name struct fields.
+    #[serde(default)]
+    pub adjacency_column: Option<String>,
+    /// Label of the source node, used when a query leaves that endpoint unlabeled
+    #[serde(default)]
+    pub source_label: Option<String>,
+    /// Label of the target node, used when a query leaves that endpoint unlabeled
+    #[serde(default)]
+    pub target_label: Option<String>,
     /// Optional fields that define relationship properties
+    #[serde(default)]
     pub property_fields: Vec<String>,
-    /// Optional filter conditions for this relationship type
+    /// Physical column behind each graph property whose name differs from its column
+    #[serde(default)]
+    pub property_aliases: HashMap<String, String>,
+    /// Derived properties defined as SQL expressions over the table's columns
+    #[serde(default)]
+    pub computed_properties: HashMap<String, String>,
+    /// SQL predicate over the table's columns selecting this type's rows, applied to
+    /// every scan of the type
     pub filter_conditions: Option<String>,
 }
 
@@ -57,16 +135,203 @@ impl Default for GraphConfig {
             relationship_mappings: HashMap::new(),
             default_node_id_field: "id".to_string(),
             default_relationship_type_field: "type".to_string(),
+            label_resolution: LabelResolution::Exact,
+            max_variable_length_hops: crate::MAX_VARIABLE_LENGTH_HOPS,
+            variable_length_expansion: VariableLengthExpansion::Unrolled,
+            relationship_uniqueness: RelationshipUniqueness::Homomorphism,
+            execution: ExecutionConfig::default(),
         }
     }
 }
 
+/// Strategy for planning variable-length patterns such as `-[:KNOWS*1..5]->`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VariableLengthExpansion {
+    /// One join chain per path length, unioned; a row for every path
+    #[default]
+    Unrolled,
+    /// Breadth-first search from each source, visiting every node once; a row for every
+    /// node reached, however many paths lead to it
+    ///
+    /// Patterns that need each path, binding a relationship or path variable or with a
+    /// minimum length above one, are still unrolled.
+    Frontier,
+}
+
+/// Parallelism and batching of the plans a graph's queries run
+///
+/// Unset settings keep those of the `SessionContext` the query runs in, whose defaults
+/// follow the number of cores of the host.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExecutionConfig {
+    /// Partitions each operator's work is split into
+    pub target_partitions: Option<usize>,
+    /// Rows per batch
+    pub batch_size: Option<usize>,
+    /// Whether joins, aggregations and scans repartition their input
+    pub repartitioning: Repartitioning,
+}
+
+impl ExecutionConfig {
+    /// Split each operator's work into `partitions` partitions
+    pub fn with_target_partitions(mut self, partitions: usize) -> Self {
+        self.target_partitions = Some(partitions);
+        self
+    }
+
+    /// Produce batches of `rows` rows
+    pub fn with_batch_size(mut self, rows: usize) -> Self {
+        self.batch_size = Some(rows);
+        self
+    }
+
+    /// Set whether plans repartition their inputs
+    pub fn with_repartitioning(mut self, repartitioning: Repartitioning) -> Self {
+        self.repartitioning = repartitioning;
+        self
+    }
+}
+
+/// Whether plans redistribute rows across partitions
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Repartitioning {
+    /// Keep the settings of the session
+    #[default]
+    Session,
+    /// Hash-partition join and aggregation inputs on their keys and split scans, so every
+    /// target partition gets work
+    Enabled,
+    /// Run each operator over the partitions of its input as they are; suits hosts with
+    /// few cores, where shuffling rows costs more than it parallelizes
+    Disabled,
+}
+
+/// Whether a match may traverse one relationship more than once
+///
+/// Relationships are told apart by their type and endpoint keys, so parallel
+/// relationships of one type between the same two nodes count as one.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RelationshipUniqueness {
+    /// Patterns are plain joins; `(a)-[:KNOWS]-(b)-[:KNOWS]-(c)` also matches with `c`
+    /// reached back over the relationship from `a`
+    #[default]
+    Homomorphism,
+    /// The relationships of one MATCH clause are pairwise distinct, as in Cypher
+    ///
+    /// The hops of a variable-length relationship are distinct from each other and from
+    /// the relationships bound before it in the clause. Breadth-first expansion under
+    /// [`VariableLengthExpansion::Frontier`] keeps reporting each reachable node.
+    Isomorphism,
+}
+
+/// Policy for matching labels and relationship types written in a query against
+/// the names declared in the mappings
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LabelResolution {
+    /// Names must match exactly
+    #[default]
+    Exact,
+    /// Names match ignoring ASCII case (`person` resolves `Person`)
+    CaseInsensitive,
+    /// Names match after snake_case normalization (`worksFor` resolves `WORKS_FOR`)
+    SnakeCase,
+}
+
+impl LabelResolution {
+    /// The key under which `name` is compared against other names
+    pub fn normalize(&self, name: &str) -> String {
+        match self {
+            LabelResolution::Exact => name.to_string(),
+            LabelResolution::CaseInsensitive => name.to_ascii_lowercase(),
+            LabelResolution::SnakeCase => {
+                let mut normalized = String::with_capacity(name.len() + 4);
+                let mut prev: Option<char> = None;
+                for c in name.chars() {
+                    if c == '-' || c == ' ' || c == '_' {
+                        if !normalized.is_empty() && !normalized.ends_with('_') {
+                            normalized.push('_');
+                        }
+                    } else {
+                        let boundary = c.is_ascii_uppercase()
+                            && prev.is_some_and(|p| p.is_ascii_lowercase() || p.is_ascii_digit());
+                        if boundary && !normalized.ends_with('_') {
+                            normalized.push('_');
+                        }
+                        normalized.push(c.to_ascii_lowercase());
+                    }
+                    prev = Some(c);
+                }
+                normalized.trim_end_matches('_').to_string()
+            }
+        }
+    }
+
+    /// Find the declared name that `name` resolves to, preferring an exact match
+    fn resolve<'a>(
+        &self,
+        name: &str,
+        declared: impl Iterator<Item = &'a String> + Clone,
+    ) -> Option<&'a str> {
+        if let Some(exact) = declared.clone().find(|d| d.as_str() == name) {
+            return Some(exact);
+        }
+        if *self == LabelResolution::Exact {
+            return None;
+        }
+        let key = self.normalize(name);
+        declared
+            .map(String::as_str)
+            .find(|d| self.normalize(d) == key)
+    }
+}
+
 impl GraphConfig {
     /// Create a new builder for GraphConfig
     pub fn builder() -> GraphConfigBuilder {
         GraphConfigBuilder::new()
     }
 
+    /// Parents must be mapped, share the id arity, and not form a cycle
+    fn check_label_parents(&self, label: &str, mapping: &NodeMapping) -> Result<()> {
+        let mut seen = vec![label];
+        let mut current = mapping;
+        while let Some(parent) = &current.extends {
+            let config_error = |message: String| GraphError::ConfigError {
+                message,
+                location: snafu::Location::new(file!(), line!(), column!()),
+            };
+            if seen.contains(&parent.as_str()) {
+                return Err(config_error(format!(
+                    "Label '{}' extends itself through '{}'",
+                    label, parent
+                )));
+            }
+            let Some(parent_mapping) = self.node_mappings.get(parent) else {
+                return Err(config_error(format!(
+                    "Label '{}' extends unmapped label '{}'",
+                    current.label, parent
+                )));
+            };
+            if parent_mapping.id_fields().len() != current.id_fields().len() {
+                return Err(config_error(format!(
+                    "Label '{}' has {} id fields but its parent '{}' has {}",
+                    current.label,
+                    current.id_fields().len(),
+                    parent,
+                    parent_mapping.id_fields().len()
+                )));
+            }
+            seen.push(parent);
+            current = parent_mapping;
+        }
+        Ok(())
+    }
+
     /// Get node mapping for a given label
     pub fn get_node_mapping(&self, label: &str) -> Option<&NodeMapping> {
         self.node_mappings.get(label)
@@ -77,19 +342,162 @@ impl GraphConfig {
         self.relationship_mappings.get(rel_type)
     }
 
+    /// `label` followed by every label extending it, directly or transitively, in name
+    /// order
+    pub fn label_with_descendants<'a>(&'a self, label: &'a str) -> Vec<&'a str> {
+        let mut descendants: Vec<&str> = Vec::new();
+        let mut frontier = vec![label];
+        while let Some(parent) = frontier.pop() {
+            for mapping in self.node_mappings.values() {
+                let child = mapping.label.as_str();
+                if mapping.extends.as_deref() == Some(parent)
+                    && child != label
+                    && !descendants.contains(&child)
+                {
+                    descendants.push(child);
+                    frontier.push(child);
+                }
+            }
+        }
+        descendants.sort();
+        std::iter::once(label).chain(descendants).collect()
+    }
+
+    /// `label` followed by the labels it extends, nearest first
+    pub fn label_with_ancestors<'a>(&'a self, label: &'a str) -> Vec<&'a str> {
+        let mut labels = vec![label];
+        let mut current = label;
+        while let Some(parent) = self
+            .get_node_mapping(current)
+            .and_then(|mapping| mapping.extends.as_deref())
+        {
+            // Validation rejects cycles; stop rather than loop on an unvalidated config
+            if labels.contains(&parent) {
+                break;
+            }
+            labels.push(parent);
+            current = parent;
+        }
+        labels
+    }
+
+    /// Whether any label extends `label`
+    pub fn has_sub_labels(&self, label: &str) -> bool {
+        self.node_mappings
+            .values()
+            .any(|mapping| mapping.extends.as_deref() == Some(label))
+    }
+
+    /// Resolve a label written in a query to its declared name under the resolution policy
+    pub fn resolve_node_label(&self, label: &str) -> Option<&str> {
+        self.label_resolution
+            .resolve(label, self.node_mappings.keys())
+    }
+
+    /// Resolve a relationship type written in a query to its declared name
+    pub fn resolve_relationship_type(&self, rel_type: &str) -> Option<&str> {
+        self.label_resolution
+            .resolve(rel_type, self.relationship_mappings.keys())
+    }
+
+    /// Parse and validate a configuration from JSON
+    ///
+    /// Errors name the offending field path and its line and column.
+    pub fn from_json(json: &str) -> Result<Self> {
+        Self::from_document(&mut serde_json::Deserializer::from_str(json))
+    }
+
+    /// Parse and validate a configuration from YAML
+    ///
+    /// ```yaml
+    /// node_mappings:
+    ///   Person:
+    ///     id_field: person_id
+    ///     property_fields: [name, age]
+    /// relationship_mappings:
+    ///   KNOWS:
+    ///     source_id_field: src_id
+    ///     target_id_field: dst_id
+    /// ```
+    pub fn from_yaml(yaml: &str) -> Result<Self> {
+        Self::from_document(serde_yaml::Deserializer::from_str(yaml))
+    }
+
+    fn from_document<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self> {
+        let mut config: Self = serde_path_to_error::deserialize(deserializer).map_err(|e| {
+            let path = e.path().to_string();
+            GraphError::ConfigError {
+                message: if path == "." {
+                    format!("Invalid graph config: {}", e.inner())
+                } else {
+                    format!("Invalid graph config at `{}`: {}", path, e.inner())
+                },
+                location: snafu::Location::new(file!(), line!(), column!()),
+            }
+        })?;
+
+        // Mappings written in documents may leave their name implied by the key
+        for (label, mapping) in config.node_mappings.iter_mut() {
+            if mapping.label.is_empty() {
+                mapping.label = label.clone();
+            }
+        }
+        for (rel_type, mapping) in config.relationship_mappings.iter_mut() {
+            if mapping.relationship_type.is_empty() {
+                mapping.relationship_type = rel_type.clone();
+            }
+        }
+
+        config.validate()?;
+        Ok(config)
+    }
+
     /// Validate the configuration
     pub fn validate(&self) -> Result<()> {
+        if self.max_variable_length_hops == 0 {
+            return Err(GraphError::ConfigError {
+                message: "max_variable_length_hops must be at least 1".to_string(),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            });
+        }
+        if self.execution.target_partitions == Some(0) || self.execution.batch_size == Some(0) {
+            return Err(GraphError::ConfigError {
+                message: "target_partitions and batch_size must be at least 1".to_string(),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            });
+        }
+
         // Check for conflicting field names
         for (label, mapping) in &self.node_mappings {
+            if mapping.label != *label {
+                return Err(GraphError::ConfigError {
+                    message: format!(
+                        "Node mapping registered as '{}' declares label '{}'",
+                        label, mapping.label
+                    ),
+                    location: snafu::Location::new(file!(), line!(), column!()),
+                });
+            }
             if mapping.id_field.is_empty() {
                 return Err(GraphError::ConfigError {
                     message: format!("Node mapping for '{}' has empty id_field", label),
                     location: snafu::Location::new(file!(), line!(), column!()),
                 });
             }
+            check_property_aliases(label, &mapping.property_aliases, &mapping.id_fields())?;
+            self.check_label_parents(label, mapping)?;
         }
 
         for (rel_type, mapping) in &self.relationship_mappings {
+            if mapping.relationship_type != *rel_type {
+                return Err(GraphError::ConfigError {
+                    message: format!(
+                        "Relationship mapping registered as '{}' declares type '{}'",
+                        rel_type, mapping.relationship_type
+                    ),
+                    location: snafu::Location::new(file!(), line!(), column!()),
+                });
+            }
             if mapping.source_id_field.is_empty() || mapping.target_id_field.is_empty() {
                 return Err(GraphError::ConfigError {
                     message: format!(
@@ -99,12 +507,65 @@ impl GraphConfig {
                     location: snafu::Location::new(file!(), line!(), column!()),
                 });
             }
+            if mapping.adjacency_column.is_some() && mapping.source_label.is_none() {
+                return Err(GraphError::ConfigError {
+                    message: format!(
+                        "Relationship mapping for '{}' reads an adjacency column but declares no source label",
+                        rel_type
+                    ),
+                    location: snafu::Location::new(file!(), line!(), column!()),
+                });
+            }
+            let keys = [mapping.source_id_fields(), mapping.target_id_fields()].concat();
+            check_property_aliases(rel_type, &mapping.property_aliases, &keys)?;
+        }
+
+        // Names that collide under the resolution policy would resolve ambiguously
+        for (kind, names) in [
+            ("node labels", self.node_mappings.keys().collect::<Vec<_>>()),
+            (
+                "relationship types",
+                self.relationship_mappings.keys().collect::<Vec<_>>(),
+            ),
+        ] {
+            let mut seen: HashMap<String, &String> = HashMap::new();
+            for name in names {
+                if let Some(other) = seen.insert(self.label_resolution.normalize(name), name) {
+                    return Err(GraphError::ConfigError {
+                        message: format!(
+                            "Ambiguous {} '{}' and '{}' under {:?} label resolution",
+                            kind, other, name, self.label_resolution
+                        ),
+                        location: snafu::Location::new(file!(), line!(), column!()),
+                    });
+                }
+            }
         }
 
         Ok(())
     }
 }
 
+/// Aliases rename property columns only; key columns keep their names for joins
+fn check_property_aliases(
+    name: &str,
+    aliases: &HashMap<String, String>,
+    keys: &[&str],
+) -> Result<()> {
+    for (property, column) in aliases {
+        if keys.contains(&column.as_str()) || keys.contains(&property.as_str()) {
+            return Err(GraphError::ConfigError {
+                message: format!(
+                    "Mapping for '{}' aliases key column '{}' as '{}'; only property columns can be aliased",
+                    name, column, property
+                ),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            });
+        }
+    }
+    Ok(())
+}
+
 /// Builder for GraphConfig
 #[derive(Debug, Default, Clone)]
 pub struct GraphConfigBuilder {
@@ -112,6 +573,11 @@ pub struct GraphConfigBuilder {
     relationship_mappings: HashMap<String, RelationshipMapping>,
     default_node_id_field: Option<String>,
     default_relationship_type_field: Option<String>,
+    label_resolution: LabelResolution,
+    max_variable_length_hops: Option<u32>,
+    variable_length_expansion: VariableLengthExpansion,
+    relationship_uniqueness: RelationshipUniqueness,
+    execution: ExecutionConfig,
 }
 
 impl GraphConfigBuilder {
@@ -128,8 +594,14 @@ impl GraphConfigBuilder {
             NodeMapping {
                 label: label_str,
                 id_field: id_field.into(),
+                additional_id_fields: Vec::new(),
                 property_fields: Vec::new(),
+                property_aliases: HashMap::new(),
+                computed_properties: HashMap::new(),
                 filter_conditions: None,
+                label_column: None,
+                label_value: None,
+                extends: None,
             },
         );
         self
@@ -155,14 +627,54 @@ impl GraphConfigBuilder {
                 relationship_type: type_str,
                 source_id_field: source_field.into(),
                 target_id_field: target_field.into(),
+                additional_source_id_fields: Vec::new(),
+                additional_target_id_fields: Vec::new(),
+                table: None,
                 type_field: None,
+                type_value: None,
+                undirected: false,
+                adjacency_column: None,
+                source_label: None,
+                target_label: None,
                 property_fields: Vec::new(),
+                property_aliases: HashMap::new(),
+                computed_properties: HashMap::new(),
                 filter_conditions: None,
             },
         );
         self
     }
 
+    /// Add one relationship type per entry of `types`, all backed by the table of
+    /// `template` and told apart by its type field
+    ///
+    /// The template's relationship type names the shared table unless `table` is set,
+    /// and its `type_field` falls back to the default relationship type field.
+    pub fn with_relationship_types<I, S>(mut self, template: RelationshipMapping, types: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let table = template
+            .table
+            .clone()
+            .unwrap_or_else(|| template.relationship_type.clone());
+        let type_field = template.type_field.clone().unwrap_or_else(|| {
+            self.default_relationship_type_field
+                .clone()
+                .unwrap_or_else(|| "type".to_string())
+        });
+        for rel_type in types {
+            let rel_type = rel_type.into();
+            let mut mapping = template.clone();
+            mapping.relationship_type = rel_type.clone();
+            mapping.table = Some(table.clone());
+            mapping.type_field = Some(type_field.clone());
+            self.relationship_mappings.insert(rel_type, mapping);
+        }
+        self
+    }
+
     /// Add a relationship mapping with additional configuration
     pub fn with_relationship_mapping(mut self, mapping: RelationshipMapping) -> Self {
         self.relationship_mappings
@@ -182,6 +694,39 @@ impl GraphConfigBuilder {
         self
     }
 
+    /// Set how query labels and relationship types are resolved
+    pub fn with_label_resolution(mut self, resolution: LabelResolution) -> Self {
+        self.label_resolution = resolution;
+        self
+    }
+
+    /// Limit how many hops variable-length patterns may expand
+    ///
+    /// Defaults to [`crate::MAX_VARIABLE_LENGTH_HOPS`]. Unbounded patterns such as
+    /// `-[:KNOWS*]->` expand up to this depth; explicit ranges beyond it are rejected.
+    pub fn with_max_variable_length_hops(mut self, hops: u32) -> Self {
+        self.max_variable_length_hops = Some(hops);
+        self
+    }
+
+    /// Set how variable-length patterns are expanded
+    pub fn with_variable_length_expansion(mut self, expansion: VariableLengthExpansion) -> Self {
+        self.variable_length_expansion = expansion;
+        self
+    }
+
+    /// Set whether one MATCH clause may bind the same relationship twice
+    pub fn with_relationship_uniqueness(mut self, uniqueness: RelationshipUniqueness) -> Self {
+        self.relationship_uniqueness = uniqueness;
+        self
+    }
+
+    /// Set the parallelism and batching of query execution
+    pub fn with_execution_config(mut self, execution: ExecutionConfig) -> Self {
+        self.execution = execution;
+        self
+    }
+
     /// Build the GraphConfig
     pub fn build(self) -> Result<GraphConfig> {
         let config = GraphConfig {
@@ -193,6 +738,13 @@ impl GraphConfigBuilder {
             default_relationship_type_field: self
                 .default_relationship_type_field
                 .unwrap_or_else(|| "type".to_string()),
+            label_resolution: self.label_resolution,
+            max_variable_length_hops: self
+                .max_variable_length_hops
+                .unwrap_or(crate::MAX_VARIABLE_LENGTH_HOPS),
+            variable_length_expansion: self.variable_length_expansion,
+            relationship_uniqueness: self.relationship_uniqueness,
+            execution: self.execution,
         };
 
         config.validate()?;
@@ -206,9 +758,34 @@ impl NodeMapping {
         Self {
             label: label.into(),
             id_field: id_field.into(),
+            additional_id_fields: Vec::new(),
             property_fields: Vec::new(),
+            property_aliases: HashMap::new(),
+            computed_properties: HashMap::new(),
             filter_conditions: None,
+            label_column: None,
+            label_value: None,
+            extends: None,
+        }
+    }
+
+    /// Identify nodes by several columns, e.g. `(tenant_id, user_id)`
+    ///
+    /// The first field becomes `id_field`; an empty list leaves the mapping unchanged.
+    pub fn with_composite_id(mut self, fields: Vec<String>) -> Self {
+        let mut fields = fields.into_iter();
+        if let Some(first) = fields.next() {
+            self.id_field = first;
+            self.additional_id_fields = fields.collect();
         }
+        self
+    }
+
+    /// All identifier fields, primary first
+    pub fn id_fields(&self) -> Vec<&str> {
+        std::iter::once(self.id_field.as_str())
+            .chain(self.additional_id_fields.iter().map(String::as_str))
+            .collect()
     }
 
     /// Add property fields to the mapping
@@ -217,11 +794,78 @@ impl NodeMapping {
         self
     }
 
-    /// Add filter conditions for this node type
+    /// Only read the rows of the table matching a SQL predicate, e.g. `age >= 18`
     pub fn with_filter<S: Into<String>>(mut self, filter: S) -> Self {
         self.filter_conditions = Some(filter.into());
         self
     }
+
+    /// Expose `column` under the graph property name `property`
+    pub fn with_property_alias<S: Into<String>>(mut self, property: S, column: S) -> Self {
+        self.property_aliases.insert(property.into(), column.into());
+        self
+    }
+
+    /// Physical column backing a graph property
+    pub fn column_for<'a>(&'a self, property: &'a str) -> &'a str {
+        self.property_aliases
+            .get(property)
+            .map_or(property, String::as_str)
+    }
+
+    /// Graph property name under which a physical column is exposed
+    pub fn property_for<'a>(&'a self, column: &'a str) -> &'a str {
+        self.property_aliases
+            .iter()
+            .find(|(_, aliased)| aliased.as_str() == column)
+            .map_or(column, |(property, _)| property.as_str())
+    }
+
+    /// Define `property` as a SQL expression over the table's columns
+    ///
+    /// The expression is planned wherever the property is read, e.g.
+    /// `with_computed_property("age", "2025 - birth_year")`.
+    pub fn with_computed_property<S: Into<String>>(mut self, property: S, sql: S) -> Self {
+        self.computed_properties.insert(property.into(), sql.into());
+        self
+    }
+
+    /// Whether `property` is declared, either as a property field or a computed property
+    pub fn has_property(&self, property: &str) -> bool {
+        self.property_fields.iter().any(|field| field == property)
+            || self.computed_properties.contains_key(property)
+    }
+
+    /// Distinguish this label by a discriminator column in a shared table
+    pub fn with_label_column<S: Into<String>>(mut self, column: S) -> Self {
+        self.label_column = Some(column.into());
+        self
+    }
+
+    /// Set the discriminator value for this label (defaults to the label itself)
+    pub fn with_label_value<S: Into<String>>(mut self, value: S) -> Self {
+        self.label_value = Some(value.into());
+        self
+    }
+
+    /// Declare this label a sub-label of `parent`, e.g. `Employee` extends `Person`
+    ///
+    /// The id fields must have the same arity as the parent's; they are exposed under
+    /// the parent's id names when matched as the parent.
+    pub fn with_extends<S: Into<String>>(mut self, parent: S) -> Self {
+        self.extends = Some(parent.into());
+        self
+    }
+
+    /// Discriminator column and value selecting this label's rows, if any
+    pub fn label_discriminator(&self) -> Option<(&str, &str)> {
+        self.label_column.as_deref().map(|column| {
+            (
+                column,
+                self.label_value.as_deref().unwrap_or(self.label.as_str()),
+            )
+        })
+    }
 }
 
 impl RelationshipMapping {
@@ -231,29 +875,168 @@ impl RelationshipMapping {
             relationship_type: rel_type.into(),
             source_id_field: source_field.into(),
             target_id_field: target_field.into(),
+            additional_source_id_fields: Vec::new(),
+            additional_target_id_fields: Vec::new(),
+            table: None,
             type_field: None,
+            type_value: None,
+            undirected: false,
+            adjacency_column: None,
+            source_label: None,
+            target_label: None,
             property_fields: Vec::new(),
+            property_aliases: HashMap::new(),
+            computed_properties: HashMap::new(),
             filter_conditions: None,
         }
     }
 
+    /// Reference composite node keys from both endpoints
+    ///
+    /// The first field of each list becomes `source_id_field` / `target_id_field`; empty
+    /// lists leave the corresponding endpoint unchanged.
+    pub fn with_composite_endpoints(mut self, source: Vec<String>, target: Vec<String>) -> Self {
+        let mut source = source.into_iter();
+        if let Some(first) = source.next() {
+            self.source_id_field = first;
+            self.additional_source_id_fields = source.collect();
+        }
+        let mut target = target.into_iter();
+        if let Some(first) = target.next() {
+            self.target_id_field = first;
+            self.additional_target_id_fields = target.collect();
+        }
+        self
+    }
+
+    /// All source key fields, primary first
+    pub fn source_id_fields(&self) -> Vec<&str> {
+        std::iter::once(self.source_id_field.as_str())
+            .chain(self.additional_source_id_fields.iter().map(String::as_str))
+            .collect()
+    }
+
+    /// All target key fields, primary first
+    pub fn target_id_fields(&self) -> Vec<&str> {
+        std::iter::once(self.target_id_field.as_str())
+            .chain(self.additional_target_id_fields.iter().map(String::as_str))
+            .collect()
+    }
+
+    /// Treat every row as an unordered pair (e.g. `FRIENDS_WITH` stored once per pair)
+    pub fn with_undirected(mut self, undirected: bool) -> Self {
+        self.undirected = undirected;
+        self
+    }
+
+    /// Read edges from a list-of-struct column on the source node's table
+    ///
+    /// Requires a source label, e.g. `friends: list<struct<friend_id, since>>` on `Person`:
+    ///
+    /// ```
+    /// use lance_graph::config::RelationshipMapping;
+    ///
+    /// let friends = RelationshipMapping::new("FRIEND", "id", "friend_id")
+    ///     .with_endpoint_labels("Person", "Person")
+    ///     .with_adjacency_column("friends")
+    ///     .with_properties(vec!["since".to_string()]);
+    /// ```
+    pub fn with_adjacency_column<S: Into<String>>(mut self, column: S) -> Self {
+        self.adjacency_column = Some(column.into());
+        self
+    }
+
+    /// Declare the node labels this relationship connects
+    ///
+    /// Lets queries leave endpoints unlabeled, as in `MATCH ()-[r:RATED]->() RETURN r.score`.
+    pub fn with_endpoint_labels<S: Into<String>>(mut self, source: S, target: S) -> Self {
+        self.source_label = Some(source.into());
+        self.target_label = Some(target.into());
+        self
+    }
+
     /// Set the type field for this relationship
     pub fn with_type_field<S: Into<String>>(mut self, type_field: S) -> Self {
         self.type_field = Some(type_field.into());
         self
     }
 
+    /// Set the value of the type field that identifies this relationship type
+    pub fn with_type_value<S: Into<String>>(mut self, type_value: S) -> Self {
+        self.type_value = Some(type_value.into());
+        self
+    }
+
+    /// Read rows from a table other than the one named after the relationship type
+    pub fn with_table<S: Into<String>>(mut self, table: S) -> Self {
+        self.table = Some(table.into());
+        self
+    }
+
+    /// Name under which the backing table is registered
+    pub fn table_name(&self) -> &str {
+        self.table.as_deref().unwrap_or(&self.relationship_type)
+    }
+
+    /// The `(column, value)` pair selecting this type's rows, when a type field is set
+    pub fn type_discriminator(&self) -> Option<(&str, &str)> {
+        self.type_field.as_deref().map(|column| {
+            (
+                column,
+                self.type_value
+                    .as_deref()
+                    .unwrap_or(self.relationship_type.as_str()),
+            )
+        })
+    }
+
     /// Add property fields to the mapping
     pub fn with_properties(mut self, fields: Vec<String>) -> Self {
         self.property_fields = fields;
         self
     }
 
-    /// Add filter conditions for this relationship type
+    /// Only read the rows of the table matching a SQL predicate, e.g. `since >= 2020`
     pub fn with_filter<S: Into<String>>(mut self, filter: S) -> Self {
         self.filter_conditions = Some(filter.into());
         self
     }
+
+    /// Expose `column` under the graph property name `property`
+    pub fn with_property_alias<S: Into<String>>(mut self, property: S, column: S) -> Self {
+        self.property_aliases.insert(property.into(), column.into());
+        self
+    }
+
+    /// Physical column backing a graph property
+    pub fn column_for<'a>(&'a self, property: &'a str) -> &'a str {
+        self.property_aliases
+            .get(property)
+            .map_or(property, String::as_str)
+    }
+
+    /// Graph property name under which a physical column is exposed
+    pub fn property_for<'a>(&'a self, column: &'a str) -> &'a str {
+        self.property_aliases
+            .iter()
+            .find(|(_, aliased)| aliased.as_str() == column)
+            .map_or(column, |(property, _)| property.as_str())
+    }
+
+    /// Define `property` as a SQL expression over the table's columns
+    ///
+    /// The expression is planned wherever the property is read, e.g.
+    /// `with_computed_property("age", "2025 - birth_year")`.
+    pub fn with_computed_property<S: Into<String>>(mut self, property: S, sql: S) -> Self {
+        self.computed_properties.insert(property.into(), sql.into());
+        self
+    }
+
+    /// Whether `property` is declared, either as a property field or a computed property
+    pub fn has_property(&self, property: &str) -> bool {
+        self.property_fields.iter().any(|field| field == property)
+            || self.computed_properties.contains_key(property)
+    }
 }
 
 #[cfg(test)]
@@ -280,6 +1063,240 @@ mod tests {
         assert_eq!(works_for_mapping.target_id_field, "company_id");
     }
 
+    #[test]
+    fn test_node_mapping_label_discriminator() {
+        let plain = NodeMapping::new("Person", "id");
+        assert_eq!(plain.label_discriminator(), None);
+
+        let defaulted = NodeMapping::new("Person", "id").with_label_column("kind");
+        assert_eq!(defaulted.label_discriminator(), Some(("kind", "Person")));
+
+        let explicit = defaulted.with_label_value("person");
+        assert_eq!(explicit.label_discriminator(), Some(("kind", "person")));
+    }
+
+    #[test]
+    fn test_label_resolution_policies() {
+        assert_eq!(LabelResolution::Exact.normalize("Person"), "Person");
+        assert_eq!(
+            LabelResolution::CaseInsensitive.normalize("Person"),
+            "person"
+        );
+        for name in [
+            "WORKS_FOR",
+            "worksFor",
+            "WorksFor",
+            "works-for",
+            "works_for",
+        ] {
+            assert_eq!(LabelResolution::SnakeCase.normalize(name), "works_for");
+        }
+
+        let config = GraphConfig::builder()
+            .with_node_label("Person", "id")
+            .with_relationship("WORKS_FOR", "person_id", "company_id")
+            .with_label_resolution(LabelResolution::CaseInsensitive)
+            .build()
+            .unwrap();
+        assert_eq!(config.resolve_node_label("PERSON"), Some("Person"));
+        assert_eq!(
+            config.resolve_relationship_type("works_for"),
+            Some("WORKS_FOR")
+        );
+        assert_eq!(config.resolve_relationship_type("worksFor"), None);
+        assert_eq!(GraphConfig::default().resolve_node_label("person"), None);
+    }
+
+    #[test]
+    fn test_label_resolution_rejects_ambiguous_names() {
+        let result = GraphConfig::builder()
+            .with_node_label("Person", "id")
+            .with_node_label("PERSON", "id")
+            .with_label_resolution(LabelResolution::CaseInsensitive)
+            .build();
+        assert!(result.is_err());
+
+        // Exact matching keeps them distinct
+        assert!(GraphConfig::builder()
+            .with_node_label("Person", "id")
+            .with_node_label("PERSON", "id")
+            .build()
+            .is_ok());
+    }
+
+    #[test]
+    fn test_relationship_types_share_table() {
+        let config = GraphConfig::builder()
+            .with_relationship_types(
+                RelationshipMapping::new("edges", "src", "dst").with_type_field("rel_type"),
+                ["KNOWS", "LIKES"],
+            )
+            .with_relationship("WORKS_FOR", "person_id", "company_id")
+            .build()
+            .unwrap();
+
+        let likes = config.get_relationship_mapping("LIKES").unwrap();
+        assert_eq!(likes.table_name(), "edges");
+        assert_eq!(likes.type_discriminator(), Some(("rel_type", "LIKES")));
+        assert_eq!(likes.source_id_field, "src");
+
+        let works_for = config.get_relationship_mapping("WORKS_FOR").unwrap();
+        assert_eq!(works_for.table_name(), "WORKS_FOR");
+        assert_eq!(works_for.type_discriminator(), None);
+    }
+
+    #[test]
+    fn test_property_aliases() {
+        let person = NodeMapping::new("Person", "id")
+            .with_properties(vec!["name".to_string()])
+            .with_property_alias("name", "full_name");
+        assert_eq!(person.column_for("name"), "full_name");
+        assert_eq!(person.column_for("age"), "age");
+        assert_eq!(person.property_for("full_name"), "name");
+        assert_eq!(person.property_for("id"), "id");
+
+        let aliased_key = GraphConfig::builder()
+            .with_node_mapping(NodeMapping::new("Person", "id").with_property_alias("key", "id"))
+            .build();
+        assert!(aliased_key.is_err());
+    }
+
+    #[test]
+    fn test_computed_properties() {
+        let person = NodeMapping::new("Person", "id")
+            .with_properties(vec!["name".to_string()])
+            .with_computed_property("age", "2025 - birth_year");
+        assert!(person.has_property("name"));
+        assert!(person.has_property("age"));
+        assert!(!person.has_property("birth_year"));
+
+        let json = serde_json::to_string(&person).unwrap();
+        let parsed: NodeMapping = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.computed_properties["age"], "2025 - birth_year");
+    }
+
+    #[test]
+    fn test_config_from_yaml_and_json() {
+        let yaml = r#"
+node_mappings:
+  Person:
+    id_field: person_id
+    property_fields: [name, age]
+relationship_mappings:
+  KNOWS:
+    source_id_field: src_id
+    target_id_field: dst_id
+    undirected: true
+label_resolution: case_insensitive
+"#;
+        let config = GraphConfig::from_yaml(yaml).unwrap();
+        let person = config.get_node_mapping("Person").unwrap();
+        assert_eq!(person.label, "Person");
+        assert_eq!(person.id_field, "person_id");
+        assert_eq!(person.property_fields, vec!["name", "age"]);
+        assert!(config.get_relationship_mapping("KNOWS").unwrap().undirected);
+        assert_eq!(config.label_resolution, LabelResolution::CaseInsensitive);
+        assert_eq!(config.default_node_id_field, "id");
+
+        let json = r#"{"node_mappings": {"Person": {"id_field": "person_id"}}}"#;
+        let config = GraphConfig::from_json(json).unwrap();
+        assert_eq!(config.get_node_mapping("Person").unwrap().label, "Person");
+    }
+
+    #[test]
+    fn test_execution_config() {
+        let yaml = r#"
+node_mappings:
+  Person:
+    id_field: id
+execution:
+  target_partitions: 64
+  repartitioning: disabled
+"#;
+        let config = GraphConfig::from_yaml(yaml).unwrap();
+        assert_eq!(config.execution.target_partitions, Some(64));
+        assert_eq!(config.execution.batch_size, None);
+        assert_eq!(config.execution.repartitioning, Repartitioning::Disabled);
+
+        let err = GraphConfig::builder()
+            .with_execution_config(ExecutionConfig::default().with_batch_size(0))
+            .build()
+            .unwrap_err();
+        assert!(err.to_string().contains("batch_size"), "{}", err);
+    }
+
+    #[test]
+    fn test_config_document_errors_name_the_field() {
+        let yaml = "node_mappings:\n  Person:\n    property_fields: [name]\n";
+        let err = GraphConfig::from_yaml(yaml).unwrap_err().to_string();
+        assert!(err.contains("node_mappings.Person"), "{}", err);
+        assert!(err.contains("missing field `id_field`"), "{}", err);
+        assert!(err.contains("line 3"), "{}", err);
+
+        let json =
+            "{\n  \"relationship_mappings\": {\n    \"KNOWS\": {\"source_id_field\": 1}\n  }\n}";
+        let err = GraphConfig::from_json(json).unwrap_err().to_string();
+        assert!(
+            err.contains("relationship_mappings.KNOWS.source_id_field"),
+            "{}",
+            err
+        );
+        assert!(err.contains("line 3"), "{}", err);
+
+        let mismatched = r#"{"node_mappings": {"Person": {"label": "Human", "id_field": "id"}}}"#;
+        let err = GraphConfig::from_json(mismatched).unwrap_err().to_string();
+        assert!(err.contains("declares label 'Human'"), "{}", err);
+    }
+
+    #[test]
+    fn test_label_hierarchy() {
+        let config = GraphConfig::builder()
+            .with_node_label("Person", "id")
+            .with_node_mapping(NodeMapping::new("Employee", "employee_id").with_extends("Person"))
+            .with_node_mapping(NodeMapping::new("Manager", "id").with_extends("Employee"))
+            .with_node_mapping(NodeMapping::new("Customer", "id").with_extends("Person"))
+            .build()
+            .unwrap();
+        assert_eq!(
+            config.label_with_descendants("Person"),
+            vec!["Person", "Customer", "Employee", "Manager"]
+        );
+        assert_eq!(config.label_with_descendants("Manager"), vec!["Manager"]);
+        assert_eq!(
+            config.label_with_ancestors("Manager"),
+            vec!["Manager", "Employee", "Person"]
+        );
+        assert!(config.has_sub_labels("Employee"));
+        assert!(!config.has_sub_labels("Customer"));
+
+        let cycle = GraphConfig::builder()
+            .with_node_mapping(NodeMapping::new("A", "id").with_extends("B"))
+            .with_node_mapping(NodeMapping::new("B", "id").with_extends("A"))
+            .build();
+        assert!(cycle.unwrap_err().to_string().contains("extends itself"));
+
+        let unmapped = GraphConfig::builder()
+            .with_node_mapping(NodeMapping::new("Employee", "id").with_extends("Person"))
+            .build();
+        assert!(unmapped.unwrap_err().to_string().contains("unmapped label"));
+    }
+
+    #[test]
+    fn test_composite_keys() {
+        let node = NodeMapping::new("User", "id")
+            .with_composite_id(vec!["tenant_id".to_string(), "user_id".to_string()]);
+        assert_eq!(node.id_field, "tenant_id");
+        assert_eq!(node.id_fields(), vec!["tenant_id", "user_id"]);
+        assert_eq!(NodeMapping::new("User", "id").id_fields(), vec!["id"]);
+
+        let rel = RelationshipMapping::new("FOLLOWS", "src", "dst").with_composite_endpoints(
+            vec!["src_tenant".to_string(), "src".to_string()],
+            Vec::new(),
+        );
+        assert_eq!(rel.source_id_fields(), vec!["src_tenant", "src"]);
+        assert_eq!(rel.target_id_fields(), vec!["dst"]);
+    }
+
     #[test]
     fn test_validation_empty_id_field() {
         let mut config = GraphConfig::default();
@@ -288,8 +1305,14 @@ mod tests {
             NodeMapping {
                 label: "Person".to_string(),
                 id_field: "".to_string(),
+                additional_id_fields: Vec::new(),
                 property_fields: Vec::new(),
+                property_aliases: HashMap::new(),
+                computed_properties: HashMap::new(),
                 filter_conditions: None,
+                label_column: None,
+                label_value: None,
+                extends: None,
             },
         );
 
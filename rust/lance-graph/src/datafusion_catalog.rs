@@ -0,0 +1,305 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Bridge between graph source catalogs and DataFusion catalogs.
+//!
+//! - [`GraphSchemaProvider`] / [`GraphCatalogProvider`] expose a [`GraphSourceCatalog`]
+//!   to DataFusion, so node and relationship tables can be queried with plain SQL.
+//! - [`catalog_from_session_context`] goes the other way and builds a graph catalog
+//!   from tables already registered in a `SessionContext`.
+
+use std::any::Any;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use datafusion::catalog::{CatalogProvider, SchemaProvider};
+use datafusion::datasource::{provider_as_source, source_as_provider, TableProvider};
+use datafusion::execution::context::SessionContext;
+use datafusion_common::{DataFusionError, TableReference};
+
+use crate::config::GraphConfig;
+use crate::error::{GraphError, Result};
+use crate::source_catalog::{GraphSourceCatalog, InMemoryCatalog};
+
+/// Exposes the node labels and relationship types of a graph as DataFusion tables.
+///
+/// Table names are the labels and relationship types declared in the [`GraphConfig`].
+/// Only sources backed by a `TableProvider` (for example Lance or `SessionContext`
+/// tables) can be scanned; schema-only sources are reported as a planning error.
+pub struct GraphSchemaProvider {
+    catalog: Arc<dyn GraphSourceCatalog>,
+    config: GraphConfig,
+}
+
+impl GraphSchemaProvider {
+    pub fn new(catalog: Arc<dyn GraphSourceCatalog>, config: GraphConfig) -> Self {
+        Self { catalog, config }
+    }
+
+    fn resolve(&self, name: &str) -> Option<Arc<dyn datafusion::logical_expr::TableSource>> {
+        if self.config.node_mappings.contains_key(name) {
+            if let Some(source) = self.catalog.node_source(name) {
+                return Some(source);
+            }
+        }
+        if self.config.relationship_mappings.contains_key(name) {
+            return self.catalog.relationship_source(name);
+        }
+        None
+    }
+}
+
+impl fmt::Debug for GraphSchemaProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GraphSchemaProvider")
+            .field("tables", &self.table_names())
+            .finish()
+    }
+}
+
+#[async_trait]
+impl SchemaProvider for GraphSchemaProvider {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn table_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .config
+            .node_mappings
+            .keys()
+            .chain(self.config.relationship_mappings.keys())
+            .filter(|name| self.resolve(name).is_some())
+            .cloned()
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    async fn table(
+        &self,
+        name: &str,
+    ) -> std::result::Result<Option<Arc<dyn TableProvider>>, DataFusionError> {
+        match self.resolve(name) {
+            Some(source) => source_as_provider(&source).map(Some).map_err(|_| {
+                DataFusionError::Plan(format!(
+                    "Graph table '{}' has no scannable table provider",
+                    name
+                ))
+            }),
+            None => Ok(None),
+        }
+    }
+
+    fn table_exist(&self, name: &str) -> bool {
+        self.resolve(name).is_some()
+    }
+}
+
+/// A DataFusion catalog with one schema per registered graph.
+#[derive(Debug, Default)]
+pub struct GraphCatalogProvider {
+    schemas: HashMap<String, Arc<GraphSchemaProvider>>,
+}
+
+impl GraphCatalogProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Expose a graph as the schema `name`.
+    pub fn with_graph(
+        mut self,
+        name: impl Into<String>,
+        catalog: Arc<dyn GraphSourceCatalog>,
+        config: GraphConfig,
+    ) -> Self {
+        self.schemas.insert(
+            name.into(),
+            Arc::new(GraphSchemaProvider::new(catalog, config)),
+        );
+        self
+    }
+}
+
+impl CatalogProvider for GraphCatalogProvider {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.schemas.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    fn schema(&self, name: &str) -> Option<Arc<dyn SchemaProvider>> {
+        self.schemas
+            .get(name)
+            .map(|schema| schema.clone() as Arc<dyn SchemaProvider>)
+    }
+}
+
+/// Build a graph catalog from tables registered in a `SessionContext`.
+///
+/// Every node label in `config` must be registered under the same name in `ctx`, and
+/// every relationship type under the name of its table (see
+/// [`RelationshipMapping::table_name`](crate::config::RelationshipMapping::table_name)).
+pub async fn catalog_from_session_context(
+    ctx: &SessionContext,
+    config: &GraphConfig,
+) -> Result<InMemoryCatalog> {
+    let mut catalog = InMemoryCatalog::new();
+
+    for label in config.node_mappings.keys() {
+        let table_provider =
+            registered_table(ctx, label)
+                .await
+                .map_err(|e| GraphError::ConfigError {
+                    message: format!("Node label '{}' not found in SessionContext: {}", label, e),
+                    location: snafu::Location::new(file!(), line!(), column!()),
+                })?;
+        catalog = catalog.with_node_source(label, provider_as_source(table_provider));
+    }
+
+    // Relationship types sharing a table are all read from the one registered source
+    let mut tables = HashSet::new();
+    for (rel_type, mapping) in &config.relationship_mappings {
+        let table = mapping.table_name();
+        if !tables.insert(table) {
+            continue;
+        }
+        let table_provider =
+            registered_table(ctx, table)
+                .await
+                .map_err(|e| GraphError::ConfigError {
+                    message: format!(
+                        "Table '{}' of relationship type '{}' not found in SessionContext: {}",
+                        table, rel_type, e
+                    ),
+                    location: snafu::Location::new(file!(), line!(), column!()),
+                })?;
+        catalog = catalog.with_relationship_source(table, provider_as_source(table_provider));
+    }
+
+    Ok(catalog)
+}
+
+/// The table registered as `name`, either verbatim (as `register_batch` registers it) or
+/// normalized (as `register_table` registers an unquoted string)
+async fn registered_table(
+    ctx: &SessionContext,
+    name: &str,
+) -> datafusion_common::Result<Arc<dyn TableProvider>> {
+    match ctx.table_provider(TableReference::bare(name)).await {
+        Ok(provider) => Ok(provider),
+        Err(_) => ctx.table_provider(name).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source_catalog::SimpleTableSource;
+    use arrow_array::{Int64Array, RecordBatch, StringArray};
+    use arrow_schema::{DataType, Field, Schema};
+    use datafusion::datasource::MemTable;
+
+    fn person_table() -> Arc<dyn TableProvider> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("name", DataType::Utf8, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int64Array::from(vec![1, 2])),
+                Arc::new(StringArray::from(vec!["Alice", "Bob"])),
+            ],
+        )
+        .unwrap();
+        Arc::new(MemTable::try_new(schema, vec![vec![batch]]).unwrap())
+    }
+
+    fn person_config() -> GraphConfig {
+        GraphConfig::builder()
+            .with_node_label("Person", "id")
+            .with_relationship("KNOWS", "src_id", "dst_id")
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_graph_catalog_provider_serves_sql() {
+        let catalog =
+            InMemoryCatalog::new().with_node_source("Person", provider_as_source(person_table()));
+        let provider =
+            GraphCatalogProvider::new().with_graph("social", Arc::new(catalog), person_config());
+        assert_eq!(provider.schema_names(), vec!["social"]);
+        let schema = provider.schema("social").unwrap();
+        // KNOWS is declared in the config but has no source in the catalog
+        assert_eq!(schema.table_names(), vec!["Person"]);
+        assert!(!schema.table_exist("KNOWS"));
+
+        let ctx = SessionContext::new();
+        ctx.register_catalog("graphs", Arc::new(provider));
+        let batches = ctx
+            .sql("SELECT name FROM graphs.social.\"Person\" ORDER BY id")
+            .await
+            .unwrap()
+            .collect()
+            .await
+            .unwrap();
+        let names = batches[0]
+            .column(0)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        assert_eq!(names.value(0), "Alice");
+        assert_eq!(names.value(1), "Bob");
+    }
+
+    #[tokio::test]
+    async fn test_schema_only_source_is_not_scannable() {
+        let catalog = InMemoryCatalog::new().with_node_source(
+            "Person",
+            Arc::new(SimpleTableSource::new(person_table().schema())),
+        );
+        let schema = GraphSchemaProvider::new(Arc::new(catalog), person_config());
+        assert!(schema.table_exist("Person"));
+        assert!(schema.table("Person").await.is_err());
+        assert!(schema.table("Company").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_catalog_from_session_context() {
+        let ctx = SessionContext::new();
+        ctx.register_table("Person", person_table()).unwrap();
+        // Registered under its name as written rather than normalized
+        let knows = ctx
+            .read_table(person_table())
+            .unwrap()
+            .collect()
+            .await
+            .unwrap();
+        ctx.register_batch("KNOWS", knows[0].clone()).unwrap();
+
+        let catalog = catalog_from_session_context(&ctx, &person_config())
+            .await
+            .unwrap();
+        assert!(catalog.node_source("Person").is_some());
+        assert!(catalog.relationship_source("KNOWS").is_some());
+
+        let missing = GraphConfig::builder()
+            .with_node_label("Company", "id")
+            .build()
+            .unwrap();
+        assert!(matches!(
+            catalog_from_session_context(&ctx, &missing).await,
+            Err(GraphError::ConfigError { .. })
+        ));
+    }
+}
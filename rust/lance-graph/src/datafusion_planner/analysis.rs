@@ -5,9 +5,10 @@
 //!
 //! Assigns unique IDs to relationship instances and collects variable-to-label mappings
 
-use crate::ast::RelationshipDirection;
+use crate::ast::{BooleanExpression, PropertyValue, RelationshipDirection, ValueExpression};
 use crate::error::Result;
 use crate::logical_plan::*;
+use datafusion::logical_expr::Expr;
 use std::collections::{HashMap, HashSet};
 
 /// Analysis result containing all metadata needed for planning
@@ -21,6 +22,10 @@ pub struct QueryAnalysis {
 
     /// All datasets required for this query
     pub required_datasets: HashSet<String>,
+
+    /// Properties the query reads of each pattern variable, for pruning the columns its
+    /// scans project; variables read whole, such as returned nodes, are absent
+    pub used_properties: HashMap<String, HashSet<String>>,
 }
 
 /// Represents a single relationship expansion with a unique instance ID
@@ -34,10 +39,34 @@ pub struct RelationshipInstance {
     pub alias: String, // e.g., "friend_of_1", "friend_of_2"
 }
 
+/// A conjunct of a WHERE predicate reading the columns of one variable, to apply where
+/// that variable is scanned rather than above the joins of its pattern
+#[derive(Debug, Clone)]
+pub(crate) struct PushedPredicate {
+    pub variable: String,
+    pub expr: Expr,
+    /// Applied at some scan of the variable
+    pub applied: bool,
+    /// Left out of some scan of the variable, which lacks one of its columns
+    pub missed: bool,
+}
+
+/// A relationship bound under `alias`, of one of `rel_types`
+#[derive(Debug, Clone)]
+pub(crate) struct BoundRelationship {
+    pub alias: String,
+    pub rel_types: Vec<String>,
+}
+
 /// Planning context that tracks state during plan building
 pub struct PlanningContext<'a> {
     pub analysis: &'a QueryAnalysis,
     pub(crate) relationship_instance_idx: HashMap<String, usize>,
+    /// Predicates of the enclosing filters that the scans being built may apply
+    pub(crate) pushed_predicates: Vec<PushedPredicate>,
+    /// Relationships bound so far by each enclosing MATCH clause whose relationships
+    /// must be distinct, innermost last
+    pub(crate) relationship_scopes: Vec<Vec<BoundRelationship>>,
 }
 
 impl<'a> PlanningContext<'a> {
@@ -45,6 +74,28 @@ impl<'a> PlanningContext<'a> {
         Self {
             analysis,
             relationship_instance_idx: HashMap::new(),
+            pushed_predicates: Vec::new(),
+            relationship_scopes: Vec::new(),
+        }
+    }
+
+    /// Run `build` with no predicates pushed into it, for operators whose rows the
+    /// enclosing filters do not select one by one
+    pub(crate) fn isolated<T>(&mut self, build: impl FnOnce(&mut Self) -> T) -> T {
+        let pushed = std::mem::take(&mut self.pushed_predicates);
+        let result = build(self);
+        self.pushed_predicates = pushed;
+        result
+    }
+
+    /// Record a relationship bound by the innermost MATCH clause whose relationships
+    /// must be distinct, if any
+    pub(crate) fn bind_relationship(&mut self, alias: &str, rel_types: &[String]) {
+        if let Some(scope) = self.relationship_scopes.last_mut() {
+            scope.push(BoundRelationship {
+                alias: alias.to_string(),
+                rel_types: rel_types.to_vec(),
+            });
         }
     }
 
@@ -71,10 +122,22 @@ impl<'a> PlanningContext<'a> {
 
 /// Analyze the logical plan to extract metadata
 pub fn analyze(logical_plan: &LogicalOperator) -> Result<QueryAnalysis> {
+    analyze_with_max_hops(logical_plan, crate::MAX_VARIABLE_LENGTH_HOPS)
+}
+
+/// Analyze the logical plan, expanding unbounded variable-length patterns to `max_hops`
+pub fn analyze_with_max_hops(
+    logical_plan: &LogicalOperator,
+    max_hops: u32,
+) -> Result<QueryAnalysis> {
     let mut analysis = QueryAnalysis::default();
     let mut rel_counter: HashMap<String, usize> = HashMap::new();
 
-    analyze_operator(logical_plan, &mut analysis, &mut rel_counter)?;
+    analyze_operator(logical_plan, &mut analysis, &mut rel_counter, max_hops)?;
+
+    let mut usage = PropertyUsage::default();
+    usage.collect_operator(logical_plan);
+    analysis.used_properties.extend(usage.finish());
     Ok(analysis)
 }
 
@@ -83,6 +146,7 @@ fn analyze_operator(
     op: &LogicalOperator,
     analysis: &mut QueryAnalysis,
     rel_counter: &mut HashMap<String, usize>,
+    max_hops: u32,
 ) -> Result<()> {
     match op {
         LogicalOperator::ScanByLabel {
@@ -104,15 +168,15 @@ fn analyze_operator(
             ..
         } => {
             // Recursively analyze input first
-            analyze_operator(input, analysis, rel_counter)?;
+            analyze_operator(input, analysis, rel_counter, max_hops)?;
 
             // Register the target variable with its label from the logical plan
             analysis
                 .var_to_label
                 .insert(target_variable.clone(), target_label.clone());
 
-            // Assign unique instance ID for this relationship
-            if let Some(rel_type) = relationship_types.first() {
+            // Assign unique instance ID for this relationship, once per type it may have
+            for rel_type in relationship_types {
                 let instance_id = rel_counter
                     .entry(rel_type.clone())
                     .and_modify(|c| *c += 1)
@@ -122,7 +186,12 @@ fn analyze_operator(
                 let alias = if let Some(rel_var) = relationship_variable {
                     rel_var.clone()
                 } else {
-                    format!("{}_{}", rel_type.to_lowercase(), instance_id)
+                    // No expression can read an unnamed relationship
+                    let alias = format!("{}_{}", rel_type.to_lowercase(), instance_id);
+                    analysis
+                        .used_properties
+                        .insert(alias.clone(), HashSet::new());
+                    alias
                 };
 
                 analysis.relationship_instances.push(RelationshipInstance {
@@ -149,7 +218,7 @@ fn analyze_operator(
             ..
         } => {
             // Recursively analyze input first
-            analyze_operator(input, analysis, rel_counter)?;
+            analyze_operator(input, analysis, rel_counter, max_hops)?;
 
             // Infer target variable's label from source variable
             // For (a:Person)-[:KNOWS]->(b), b also gets label Person
@@ -159,15 +228,13 @@ fn analyze_operator(
                     .insert(target_variable.clone(), source_label);
             }
 
-            // For variable-length paths, register multiple instances (one per hop)
+            // For variable-length paths, register multiple instances (one per hop and type)
             // We need to register instances for all possible hop counts
-            if let Some(rel_type) = relationship_types.first() {
-                let max_hops = max_length.unwrap_or(crate::MAX_VARIABLE_LENGTH_HOPS);
-                let min_hops = min_length.unwrap_or(1).max(1);
-
-                // Register instances for each hop count we'll generate
-                for hop_count in min_hops..=max_hops {
-                    for _ in 0..hop_count {
+            let max_hops = max_length.unwrap_or(max_hops);
+            let min_hops = min_length.unwrap_or(1).max(1);
+            for hop_count in min_hops..=max_hops {
+                for _ in 0..hop_count {
+                    for rel_type in relationship_types {
                         let instance_id = rel_counter
                             .entry(rel_type.clone())
                             .and_modify(|c| *c += 1)
@@ -190,26 +257,347 @@ fn analyze_operator(
                         });
                     }
                 }
+            }
+            analysis
+                .required_datasets
+                .extend(relationship_types.iter().cloned());
+        }
+        LogicalOperator::ShortestPath {
+            input,
+            target_variable,
+            target_label,
+            relationship_types,
+            ..
+        } => {
+            analyze_operator(input, analysis, rel_counter, max_hops)?;
 
-                analysis.required_datasets.insert(rel_type.clone());
+            // Edges are scanned once per type; no per-hop instances are needed
+            analysis
+                .var_to_label
+                .insert(target_variable.clone(), target_label.clone());
+            analysis.required_datasets.insert(target_label.clone());
+            analysis
+                .required_datasets
+                .extend(relationship_types.iter().cloned());
+        }
+        LogicalOperator::Unwind { input, .. } | LogicalOperator::ProcedureCall { input, .. } => {
+            if let Some(input) = input {
+                analyze_operator(input, analysis, rel_counter, max_hops)?;
+            }
+        }
+        LogicalOperator::With { input, items } => {
+            analyze_operator(input, analysis, rel_counter, max_hops)?;
+
+            // Renamed variables keep the label of the variable they carry
+            for item in items {
+                if let (ValueExpression::Variable(v), Some(alias)) = (&item.expression, &item.alias)
+                {
+                    if let Some(label) = analysis.var_to_label.get(v).cloned() {
+                        analysis.var_to_label.insert(alias.clone(), label);
+                    }
+                }
             }
         }
-        LogicalOperator::Filter { input, .. }
+        LogicalOperator::Filter { input, predicate } => {
+            analyze_operator(input, analysis, rel_counter, max_hops)?;
+            require_predicate_labels(predicate, analysis);
+        }
+        LogicalOperator::BindPath { input, .. }
+        | LogicalOperator::DistinctRelationships { input }
         | LogicalOperator::Project { input, .. }
         | LogicalOperator::Sort { input, .. }
         | LogicalOperator::Limit { input, .. }
         | LogicalOperator::Offset { input, .. }
         | LogicalOperator::Distinct { input } => {
-            analyze_operator(input, analysis, rel_counter)?;
+            analyze_operator(input, analysis, rel_counter, max_hops)?;
         }
         LogicalOperator::Join { left, right, .. } => {
-            analyze_operator(left, analysis, rel_counter)?;
-            analyze_operator(right, analysis, rel_counter)?;
+            analyze_operator(left, analysis, rel_counter, max_hops)?;
+            analyze_operator(right, analysis, rel_counter, max_hops)?;
+        }
+        LogicalOperator::SemiJoin {
+            input,
+            pattern,
+            predicate,
+            ..
+        }
+        | LogicalOperator::PatternCount {
+            input,
+            pattern,
+            predicate,
+            ..
+        } => {
+            analyze_operator(input, analysis, rel_counter, max_hops)?;
+            analyze_operator(pattern, analysis, rel_counter, max_hops)?;
+            if let Some(predicate) = predicate {
+                require_predicate_labels(predicate, analysis);
+            }
+        }
+        // Variables are local to each side; they are analyzed again when the
+        // sides are built, so only the required datasets matter here
+        LogicalOperator::Union { left, right, .. } => {
+            analyze_operator(left, analysis, rel_counter, max_hops)?;
+            analyze_operator(right, analysis, rel_counter, max_hops)?;
         }
     }
     Ok(())
 }
 
+/// Register the tables of the labels a predicate tests nodes for
+fn require_predicate_labels(predicate: &BooleanExpression, analysis: &mut QueryAnalysis) {
+    for (_, labels) in predicate.label_predicates() {
+        analysis
+            .required_datasets
+            .extend(labels.labels().into_iter().map(str::to_string));
+    }
+}
+
+/// Properties read of the variables of a plan, gathered from its expressions
+#[derive(Default)]
+struct PropertyUsage {
+    /// Variables bound by node and relationship patterns
+    bound: HashSet<String>,
+    properties: HashMap<String, HashSet<String>>,
+    /// Variables some expression reads as a whole
+    whole: HashSet<String>,
+    /// `WITH variable AS alias` renames, as (alias, variable), in plan order
+    renames: Vec<(String, String)>,
+    /// Set by subqueries left in expressions, whose patterns are not walked
+    opaque: bool,
+}
+
+impl PropertyUsage {
+    fn collect_operator(&mut self, op: &LogicalOperator) {
+        match op {
+            LogicalOperator::ScanByLabel {
+                variable,
+                properties,
+                ..
+            } => {
+                self.bound.insert(variable.clone());
+                properties.values().for_each(|v| self.collect_literal(v));
+            }
+            LogicalOperator::Filter { input, predicate } => {
+                self.collect_operator(input);
+                self.collect_predicate(predicate);
+            }
+            LogicalOperator::Expand {
+                input,
+                target_variable,
+                relationship_variable,
+                properties,
+                target_properties,
+                ..
+            } => {
+                self.collect_operator(input);
+                self.bound.insert(target_variable.clone());
+                self.bound.extend(relationship_variable.iter().cloned());
+                properties
+                    .values()
+                    .chain(target_properties.values())
+                    .for_each(|v| self.collect_literal(v));
+            }
+            LogicalOperator::VariableLengthExpand {
+                input,
+                target_variable,
+                properties,
+                target_properties,
+                ..
+            } => {
+                self.collect_operator(input);
+                self.bound.insert(target_variable.clone());
+                properties
+                    .values()
+                    .chain(target_properties.values())
+                    .for_each(|v| self.collect_literal(v));
+            }
+            LogicalOperator::ShortestPath {
+                input,
+                target_properties,
+                ..
+            } => {
+                self.collect_operator(input);
+                target_properties
+                    .values()
+                    .for_each(|v| self.collect_literal(v));
+            }
+            // Paths hold the properties of their relationships
+            LogicalOperator::BindPath { input, steps, .. } => {
+                self.collect_operator(input);
+                for step in steps {
+                    if let PathStep::Hop {
+                        relationship_variable,
+                        ..
+                    } = step
+                    {
+                        self.whole.insert(relationship_variable.clone());
+                    }
+                }
+            }
+            LogicalOperator::Project { input, projections } => {
+                self.collect_operator(input);
+                for item in projections {
+                    self.collect_value(&item.expression);
+                }
+            }
+            // Bare variables carried by WITH keep their columns for the next part
+            LogicalOperator::With { input, items } => {
+                self.collect_operator(input);
+                for item in items {
+                    match (&item.expression, &item.alias) {
+                        (ValueExpression::Variable(variable), Some(alias)) => {
+                            self.renames.push((alias.clone(), variable.clone()))
+                        }
+                        (ValueExpression::Variable(_), None) => {}
+                        (expression, _) => self.collect_value(expression),
+                    }
+                }
+            }
+            LogicalOperator::Unwind {
+                input, expression, ..
+            } => {
+                if let Some(input) = input {
+                    self.collect_operator(input);
+                }
+                self.collect_value(expression);
+            }
+            LogicalOperator::ProcedureCall {
+                input, arguments, ..
+            } => {
+                if let Some(input) = input {
+                    self.collect_operator(input);
+                }
+                arguments.iter().for_each(|a| self.collect_value(a));
+            }
+            LogicalOperator::SemiJoin {
+                input,
+                pattern,
+                predicate,
+                ..
+            }
+            | LogicalOperator::PatternCount {
+                input,
+                pattern,
+                predicate,
+                ..
+            } => {
+                self.collect_operator(input);
+                self.collect_operator(pattern);
+                if let Some(predicate) = predicate {
+                    self.collect_predicate(predicate);
+                }
+            }
+            LogicalOperator::Sort { input, sort_items } => {
+                self.collect_operator(input);
+                for item in sort_items {
+                    self.collect_value(&item.expression);
+                }
+            }
+            LogicalOperator::Join { left, right, .. }
+            | LogicalOperator::Union { left, right, .. } => {
+                self.collect_operator(left);
+                self.collect_operator(right);
+            }
+            LogicalOperator::Distinct { input }
+            | LogicalOperator::DistinctRelationships { input }
+            | LogicalOperator::Limit { input, .. }
+            | LogicalOperator::Offset { input, .. } => self.collect_operator(input),
+        }
+    }
+
+    fn collect_value(&mut self, expression: &ValueExpression) {
+        match expression {
+            ValueExpression::Variable(variable) => {
+                self.whole.insert(variable.clone());
+            }
+            ValueExpression::Property(property) => {
+                self.read(&property.variable, &property.property)
+            }
+            ValueExpression::Literal(value) => self.collect_literal(value),
+            ValueExpression::CountSubquery(_) => self.opaque = true,
+            // Entity functions read the identity columns every scan keeps, and count()
+            // of a variable its `id` column
+            ValueExpression::Function { name, args, .. } => {
+                match (name.to_lowercase().as_str(), args.as_slice()) {
+                    ("type" | "labels" | "id" | "elementid", [ValueExpression::Variable(_)]) => {}
+                    ("count", [ValueExpression::Variable(variable)]) => self.read(variable, "id"),
+                    _ => args.iter().for_each(|arg| self.collect_value(arg)),
+                }
+            }
+            other => {
+                let (values, predicates) = other.operands();
+                values.into_iter().for_each(|v| self.collect_value(v));
+                predicates
+                    .into_iter()
+                    .for_each(|p| self.collect_predicate(p));
+            }
+        }
+    }
+
+    fn collect_predicate(&mut self, predicate: &BooleanExpression) {
+        match predicate {
+            BooleanExpression::Exists(property) => {
+                self.read(&property.variable, &property.property)
+            }
+            BooleanExpression::ExistsSubquery(_) => self.opaque = true,
+            // A null test of a variable reads its identity columns
+            BooleanExpression::IsNull(ValueExpression::Variable(_))
+            | BooleanExpression::IsNotNull(ValueExpression::Variable(_))
+            | BooleanExpression::HasLabels { .. } => {}
+            other => {
+                let (values, predicates) = other.operands();
+                values.into_iter().for_each(|v| self.collect_value(v));
+                predicates
+                    .into_iter()
+                    .for_each(|p| self.collect_predicate(p));
+            }
+        }
+    }
+
+    fn collect_literal(&mut self, value: &PropertyValue) {
+        match value {
+            PropertyValue::Property(property) => self.read(&property.variable, &property.property),
+            PropertyValue::Variable(variable) => {
+                self.whole.insert(variable.clone());
+            }
+            PropertyValue::List(items) => items.iter().for_each(|v| self.collect_literal(v)),
+            _ => {}
+        }
+    }
+
+    fn read(&mut self, variable: &str, property: &str) {
+        self.properties
+            .entry(variable.to_string())
+            .or_default()
+            .insert(property.to_string());
+    }
+
+    /// Properties read of each bound variable not read whole, through the aliases
+    /// WITH gave it
+    fn finish(mut self) -> HashMap<String, HashSet<String>> {
+        if self.opaque {
+            return HashMap::new();
+        }
+        // Later renames may alias earlier aliases, so they are followed first
+        for (alias, variable) in std::mem::take(&mut self.renames).into_iter().rev() {
+            if self.whole.contains(&alias) {
+                self.whole.insert(variable);
+            } else if let Some(read) = self.properties.get(&alias).cloned() {
+                self.properties.entry(variable).or_default().extend(read);
+            }
+        }
+        let mut properties = std::mem::take(&mut self.properties);
+        self.bound
+            .into_iter()
+            .filter(|variable| !self.whole.contains(variable))
+            .map(|variable| {
+                let read = properties.remove(&variable).unwrap_or_default();
+                (variable, read)
+            })
+            .collect()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -323,9 +711,11 @@ mod tests {
             relationship_types: vec!["KNOWS".to_string()],
             direction: RelationshipDirection::Outgoing,
             relationship_variable: None,
+            properties: HashMap::new(),
             min_length: Some(1),
             max_length: Some(2),
             target_properties: HashMap::new(),
+            path_variable: None,
         };
 
         let cfg = crate::config::GraphConfig::builder()
@@ -376,6 +766,7 @@ mod tests {
             var_to_label: HashMap::new(),
             relationship_instances: instances,
             required_datasets: HashSet::new(),
+            used_properties: HashMap::new(),
         };
 
         let mut ctx = PlanningContext::new(&analysis);
@@ -391,4 +782,56 @@ mod tests {
         // Third call should error (no more instances)
         assert!(ctx.next_relationship_instance("KNOWS").is_err());
     }
+
+    #[test]
+    fn test_used_properties_follow_with_aliases() {
+        use crate::ast::{PropertyRef, ValueExpression};
+        use crate::logical_plan::ProjectionItem;
+
+        // MATCH (a:Person)-[r:KNOWS]->(b:Person) WITH a AS x, b, r RETURN x.name, b
+        let expand = LogicalOperator::Expand {
+            input: Box::new(LogicalOperator::ScanByLabel {
+                variable: "a".to_string(),
+                label: "Person".to_string(),
+                properties: Default::default(),
+            }),
+            source_variable: "a".to_string(),
+            target_variable: "b".to_string(),
+            target_label: "Person".to_string(),
+            relationship_types: vec!["KNOWS".to_string()],
+            direction: RelationshipDirection::Outgoing,
+            relationship_variable: Some("r".to_string()),
+            properties: Default::default(),
+            target_properties: Default::default(),
+        };
+        let item = |expression: ValueExpression, alias: Option<&str>| ProjectionItem {
+            expression,
+            alias: alias.map(str::to_string),
+        };
+        let with = LogicalOperator::With {
+            input: Box::new(expand),
+            items: vec![
+                item(ValueExpression::Variable("a".into()), Some("x")),
+                item(ValueExpression::Variable("b".into()), None),
+                item(ValueExpression::Variable("r".into()), None),
+            ],
+        };
+        let project = LogicalOperator::Project {
+            input: Box::new(with),
+            projections: vec![
+                item(
+                    ValueExpression::Property(PropertyRef::new("x", "name")),
+                    None,
+                ),
+                item(ValueExpression::Variable("b".into()), None),
+            ],
+        };
+
+        let analysis = analyze(&project).unwrap();
+        let used = &analysis.used_properties;
+        assert_eq!(used["a"], HashSet::from(["name".to_string()]));
+        assert!(used["r"].is_empty());
+        // b is returned whole
+        assert!(!used.contains_key("b"));
+    }
 }
@@ -6,7 +6,10 @@
 use crate::datafusion_planner::DataFusionPlanner;
 use crate::error::Result;
 use crate::logical_plan::*;
-use datafusion::logical_expr::{col, LogicalPlan, LogicalPlanBuilder};
+use datafusion::common::tree_node::{Transformed, TreeNode};
+use datafusion::common::Column;
+use datafusion::logical_expr::utils::find_aggregate_exprs;
+use datafusion::logical_expr::{col, Expr, LogicalPlan, LogicalPlanBuilder};
 
 impl DataFusionPlanner {
     pub(crate) fn build_project_with_aggregates(
@@ -14,49 +17,27 @@ impl DataFusionPlanner {
         input_plan: LogicalPlan,
         projections: &[ProjectionItem],
     ) -> Result<LogicalPlan> {
-        // Separate group expressions (non-aggregates) from aggregate expressions
+        // Separate group expressions (non-aggregates) from aggregate expressions, and
+        // project the aliased results after aggregation
         let mut group_exprs = Vec::new();
-        let mut agg_exprs = Vec::new();
-        // Store computed aliases for aggregates to reuse in final projection
-        let mut agg_aliases = Vec::new();
+        let mut agg_exprs: Vec<Expr> = Vec::new();
+        let mut final_projection = Vec::new();
 
         for p in projections {
             let expr = super::super::expression::to_df_value_expr(&p.expression);
-
-            if super::super::expression::contains_aggregate(&p.expression) {
-                // Aggregate expressions get aliased
-                let alias = if let Some(alias) = &p.alias {
-                    alias.clone()
-                } else {
-                    super::super::expression::to_cypher_column_name(&p.expression)
-                };
-                agg_exprs.push(expr.alias(&alias));
-                agg_aliases.push(alias);
+            let alias = if let Some(alias) = &p.alias {
+                alias.clone()
             } else {
-                // Group expressions: use raw expression for grouping, no alias
-                group_exprs.push(expr);
-            }
-        }
+                super::super::expression::to_cypher_column_name(&p.expression)
+            };
 
-        // After aggregation, add a projection to apply aliases to group columns
-        let mut final_projection = Vec::new();
-        let mut agg_idx = 0;
-        for p in projections {
             if !super::super::expression::contains_aggregate(&p.expression) {
-                // Re-create the expression and apply alias
-                let expr = super::super::expression::to_df_value_expr(&p.expression);
-                let aliased = if let Some(alias) = &p.alias {
-                    expr.alias(alias)
-                } else {
-                    let cypher_name =
-                        super::super::expression::to_cypher_column_name(&p.expression);
-                    expr.alias(cypher_name)
-                };
-                final_projection.push(aliased);
+                // Group expressions: use raw expression for grouping, alias afterwards
+                group_exprs.push(expr.clone());
+                final_projection.push(expr.alias(alias));
             } else {
-                // For aggregates, reference the column using the same alias we computed earlier
-                final_projection.push(col(&agg_aliases[agg_idx]));
-                agg_idx += 1;
+                let expr = self.extract_aggregates(expr, &alias, &mut agg_exprs)?;
+                final_projection.push(expr);
             }
         }
 
@@ -68,6 +49,44 @@ impl DataFusionPlanner {
             .build()
             .map_err(|e| self.plan_error("Failed to build plan", e))
     }
+
+    /// Move the aggregates of `expr` into `agg_exprs`, returning the expression that
+    /// produces `alias` from the aggregation output
+    ///
+    /// Aggregates nested in a larger expression, such as `collect(x)[0]`, are computed by
+    /// the aggregation and the rest of the expression is evaluated on its output.
+    pub(crate) fn extract_aggregates(
+        &self,
+        expr: Expr,
+        alias: &str,
+        agg_exprs: &mut Vec<Expr>,
+    ) -> Result<Expr> {
+        if matches!(expr, Expr::AggregateFunction(_)) {
+            agg_exprs.push(expr.alias(alias));
+            return Ok(col(alias));
+        }
+        for agg in find_aggregate_exprs([&expr]) {
+            let name = agg.schema_name().to_string();
+            if !agg_exprs
+                .iter()
+                .any(|e| matches!(e, Expr::Alias(a) if a.name == name))
+            {
+                agg_exprs.push(agg.alias(name));
+            }
+        }
+        let rebased = expr
+            .transform(|e| {
+                Ok(match e {
+                    Expr::AggregateFunction(_) => Transformed::yes(Expr::Column(
+                        Column::from_name(e.schema_name().to_string()),
+                    )),
+                    e => Transformed::no(e),
+                })
+            })
+            .map_err(|e| self.plan_error("Failed to project after aggregate", e))?
+            .data;
+        Ok(rebased.alias(alias))
+    }
 }
 
 #[cfg(test)]
@@ -90,6 +109,7 @@ mod tests {
             projections: vec![ProjectionItem {
                 expression: ValueExpression::Function {
                     name: "count".to_string(),
+                    distinct: false,
                     args: vec![ValueExpression::Variable("*".to_string())],
                 },
                 alias: Some("total".to_string()),
@@ -116,6 +136,7 @@ mod tests {
             projections: vec![ProjectionItem {
                 expression: ValueExpression::Function {
                     name: "count".to_string(),
+                    distinct: false,
                     args: vec![ValueExpression::Variable("*".to_string())],
                 },
                 alias: None,
@@ -1,13 +1,16 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-FileCopyrightText: Copyright The Lance Authors
 
-//! Basic operations: Filter, Project, Distinct, Sort, Limit, Offset
+//! Basic operations: Filter, Project, Unwind, With, Distinct, Union, Sort, Limit, Offset
 
-use crate::datafusion_planner::analysis::PlanningContext;
+use crate::datafusion_planner::analysis::{PlanningContext, PushedPredicate};
 use crate::datafusion_planner::DataFusionPlanner;
 use crate::error::Result;
 use crate::logical_plan::*;
-use datafusion::logical_expr::{LogicalPlan, LogicalPlanBuilder, SortExpr};
+use datafusion::common::tree_node::TreeNode;
+use datafusion::common::{Column, UnnestOptions};
+use datafusion::logical_expr::utils::{conjunction, split_conjunction_owned};
+use datafusion::logical_expr::{Expr, LogicalPlan, LogicalPlanBuilder, SortExpr};
 
 impl DataFusionPlanner {
     pub(crate) fn build_filter(
@@ -16,8 +19,38 @@ impl DataFusionPlanner {
         input: &LogicalOperator,
         predicate: &crate::ast::BooleanExpression,
     ) -> Result<LogicalPlan> {
-        let input_plan = self.build_operator(ctx, input)?;
-        let expr = super::super::expression::to_df_boolean_expr(predicate);
+        let expr = ctx.isolated(|ctx| self.predicate_expr(ctx, predicate))?;
+        let conjuncts = split_conjunction_owned(expr);
+
+        // Conjuncts on a single variable are offered to the scans of that variable, so
+        // they filter its table before the joins of the pattern
+        let start = ctx.pushed_predicates.len();
+        for conjunct in &conjuncts {
+            if let Some(variable) = pushed_variable(conjunct) {
+                ctx.pushed_predicates.push(PushedPredicate {
+                    variable,
+                    expr: conjunct.clone(),
+                    applied: false,
+                    missed: false,
+                });
+            }
+        }
+        let input_plan = self.build_operator(ctx, input);
+        let pushed = ctx.pushed_predicates.split_off(start);
+        let input_plan = input_plan?;
+
+        // Only conjuncts every scan of their variable applied are dropped
+        let remaining: Vec<Expr> = conjuncts
+            .into_iter()
+            .filter(|conjunct| {
+                !pushed
+                    .iter()
+                    .any(|p| p.expr == *conjunct && p.applied && !p.missed)
+            })
+            .collect();
+        let Some(expr) = conjunction(remaining) else {
+            return Ok(input_plan);
+        };
         LogicalPlanBuilder::from(input_plan)
             .filter(expr)
             .map_err(|e| self.plan_error("Failed to build filter", e))?
@@ -72,6 +105,122 @@ impl DataFusionPlanner {
             .map_err(|e| self.plan_error("Failed to build plan", e))
     }
 
+    /// Expand a list-valued expression into one row per element, bound to `alias`
+    ///
+    /// Rows whose list is empty or NULL produce no output, as in Cypher.
+    pub(crate) fn build_unwind(
+        &self,
+        ctx: &mut PlanningContext,
+        input: Option<&LogicalOperator>,
+        expression: &crate::ast::ValueExpression,
+        alias: &str,
+    ) -> Result<LogicalPlan> {
+        let input_plan = match input {
+            Some(input) => self.build_operator(ctx, input)?,
+            None => LogicalPlanBuilder::empty(true)
+                .build()
+                .map_err(|e| self.plan_error("Failed to build plan", e))?,
+        };
+
+        let mut exprs: Vec<Expr> = input_plan
+            .schema()
+            .columns()
+            .into_iter()
+            .map(Expr::Column)
+            .collect();
+        exprs.push(super::super::expression::to_df_value_expr(expression).alias(alias));
+
+        LogicalPlanBuilder::from(input_plan)
+            .project(exprs)
+            .map_err(|e| self.plan_error("Failed to project UNWIND list", e))?
+            .unnest_column_with_options(
+                Column::from_name(alias),
+                UnnestOptions::new().with_preserve_nulls(false),
+            )
+            .map_err(|e| self.plan_error("Failed to build UNWIND", e))?
+            .build()
+            .map_err(|e| self.plan_error("Failed to build plan", e))
+    }
+
+    /// Project the bindings a WITH clause carries into the next query part
+    ///
+    /// Carried node and relationship variables keep all their `{var}__{prop}` columns,
+    /// renamed to the alias if one is given. Aggregates group by every other item.
+    pub(crate) fn build_with(
+        &self,
+        ctx: &mut PlanningContext,
+        input: &LogicalOperator,
+        items: &[ProjectionItem],
+    ) -> Result<LogicalPlan> {
+        use super::super::expression::{
+            contains_aggregate, to_cypher_column_name, to_df_value_expr,
+        };
+        use crate::ast::ValueExpression;
+
+        let input_plan = self.build_operator(ctx, input)?;
+        let input_columns = input_plan.schema().columns();
+
+        let mut group_exprs = Vec::new();
+        let mut agg_exprs = Vec::new();
+        // Columns produced by the aggregation, in item order
+        let mut outputs = Vec::new();
+        for item in items {
+            let name = item
+                .alias
+                .clone()
+                .unwrap_or_else(|| to_cypher_column_name(&item.expression));
+            if contains_aggregate(&item.expression) {
+                let expr = to_df_value_expr(&item.expression);
+                outputs.push(self.extract_aggregates(expr, &name, &mut agg_exprs)?);
+                continue;
+            }
+
+            let entity_prefix = match &item.expression {
+                ValueExpression::Variable(v) => Some(format!("{}__", v)),
+                _ => None,
+            };
+            let entity_columns: Vec<(Expr, &str)> = entity_prefix
+                .as_deref()
+                .map(|prefix| {
+                    input_columns
+                        .iter()
+                        .filter_map(|column| {
+                            let property = column.name.strip_prefix(prefix)?;
+                            Some((Expr::Column(column.clone()), property))
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            if entity_columns.is_empty() {
+                outputs.push(Expr::Column(Column::from_name(name.clone())));
+                group_exprs.push(to_df_value_expr(&item.expression).alias(name));
+            } else {
+                for (expr, property) in entity_columns {
+                    let column = format!("{}__{}", name, property);
+                    outputs.push(Expr::Column(Column::from_name(column.clone())));
+                    group_exprs.push(expr.alias(column));
+                }
+            }
+        }
+
+        let builder = LogicalPlanBuilder::from(input_plan);
+        let builder = if agg_exprs.is_empty() {
+            builder
+                .project(group_exprs)
+                .map_err(|e| self.plan_error("Failed to build WITH projection", e))?
+        } else {
+            builder
+                .aggregate(group_exprs, agg_exprs)
+                .map_err(|e| self.plan_error("Failed to build WITH aggregate", e))?
+                .project(outputs)
+                .map_err(|e| self.plan_error("Failed to build WITH projection", e))?
+        };
+        builder
+            .build()
+            .map_err(|e| self.plan_error("Failed to build plan", e))
+    }
+
     pub(crate) fn build_distinct(
         &self,
         ctx: &mut PlanningContext,
@@ -85,6 +234,54 @@ impl DataFusionPlanner {
             .map_err(|e| self.plan_error("Failed to build plan", e))
     }
 
+    /// Each side binds its own variables, so each is analyzed and built on its own
+    pub(crate) fn build_union(
+        &self,
+        left: &LogicalOperator,
+        right: &LogicalOperator,
+        all: bool,
+    ) -> Result<LogicalPlan> {
+        let build_side = |side: &LogicalOperator| {
+            let analysis = crate::datafusion_planner::analysis::analyze_with_max_hops(
+                side,
+                self.config.max_variable_length_hops,
+            )?;
+            self.build_operator(&mut PlanningContext::new(&analysis), side)
+        };
+        let left_plan = build_side(left)?;
+        let right_plan = build_side(right)?;
+
+        let column_names = |plan: &LogicalPlan| -> Vec<String> {
+            plan.schema()
+                .fields()
+                .iter()
+                .map(|f| f.name().clone())
+                .collect()
+        };
+        let (left_columns, right_columns) = (column_names(&left_plan), column_names(&right_plan));
+        if left_columns != right_columns {
+            return Err(crate::error::GraphError::PlanError {
+                message: format!(
+                    "All parts of a UNION must return the same columns, found [{}] and [{}]",
+                    left_columns.join(", "),
+                    right_columns.join(", ")
+                ),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            });
+        }
+
+        let builder = LogicalPlanBuilder::from(left_plan);
+        let builder = if all {
+            builder.union(right_plan)
+        } else {
+            builder.union_distinct(right_plan)
+        };
+        builder
+            .map_err(|e| self.plan_error("Failed to build union", e))?
+            .build()
+            .map_err(|e| self.plan_error("Failed to build plan", e))
+    }
+
     pub(crate) fn build_sort(
         &self,
         ctx: &mut PlanningContext,
@@ -93,16 +290,21 @@ impl DataFusionPlanner {
     ) -> Result<LogicalPlan> {
         let input_plan = self.build_operator(ctx, input)?;
 
-        // Convert sort items to DataFusion sort expressions
+        // Convert sort items to DataFusion sort expressions. An item repeating a
+        // projected expression sorts on its output column, so aggregates can be ordered
         let sort_exprs: Vec<SortExpr> = sort_items
             .iter()
             .map(|item| {
-                let expr = super::super::expression::to_df_value_expr(&item.expression);
+                let expr = match projected_column(input, &item.expression) {
+                    Some(name) => Expr::Column(Column::from_name(name)),
+                    None => super::super::expression::to_df_value_expr(&item.expression),
+                };
                 let asc = matches!(item.direction, crate::ast::SortDirection::Ascending);
+                // Null is larger than every value: last ascending, first descending
                 SortExpr {
                     expr,
                     asc,
-                    nulls_first: true,
+                    nulls_first: !asc,
                 }
             })
             .collect();
@@ -143,6 +345,61 @@ impl DataFusionPlanner {
     }
 }
 
+/// The variable a scan can evaluate `expr` on: the one owning every column it reads,
+/// as `{variable}__{property}`, unless it also reads subqueries or aggregates or is
+/// volatile
+fn pushed_variable(expr: &Expr) -> Option<String> {
+    let unpushable = expr.is_volatile()
+        || expr
+            .exists(|e| {
+                Ok(matches!(
+                    e,
+                    Expr::Exists(_)
+                        | Expr::InSubquery(_)
+                        | Expr::ScalarSubquery(_)
+                        | Expr::OuterReferenceColumn(..)
+                        | Expr::AggregateFunction(_)
+                        | Expr::WindowFunction(_)
+                ))
+            })
+            .unwrap_or(true);
+    if unpushable {
+        return None;
+    }
+    let mut variables = expr
+        .column_refs()
+        .into_iter()
+        .map(|column| column.name.split_once("__").map(|(variable, _)| variable));
+    let variable = variables.next()??;
+    (!variable.is_empty() && variables.all(|v| v == Some(variable))).then(|| variable.to_string())
+}
+
+/// Output column of the RETURN or WITH item computing `expression`, if any
+fn projected_column(
+    input: &LogicalOperator,
+    expression: &crate::ast::ValueExpression,
+) -> Option<String> {
+    if matches!(expression, crate::ast::ValueExpression::Variable(_)) {
+        return None;
+    }
+    match input {
+        LogicalOperator::Distinct { input } => projected_column(input, expression),
+        LogicalOperator::Project { projections, .. } => projections
+            .iter()
+            .find(|p| &p.expression == expression)
+            .map(|p| {
+                p.alias.clone().unwrap_or_else(|| {
+                    super::super::expression::to_cypher_column_name(&p.expression)
+                })
+            }),
+        LogicalOperator::With { items, .. } => items
+            .iter()
+            .find(|item| &item.expression == expression)
+            .and_then(|item| item.alias.clone()),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::ast::{
@@ -3,14 +3,23 @@
 
 //! Graph traversal operations: Expand and Variable-Length Expand
 
+use super::path_ops::{path_struct, relationship_fields, relationship_struct};
 use crate::ast::RelationshipDirection;
-use crate::datafusion_planner::analysis::PlanningContext;
+use crate::config::VariableLengthExpansion;
+use crate::datafusion_planner::analysis::{
+    BoundRelationship, PlanningContext, RelationshipInstance,
+};
 use crate::datafusion_planner::join_ops::{SourceJoinParams, TargetJoinParams};
 use crate::datafusion_planner::DataFusionPlanner;
 use crate::error::Result;
 use crate::logical_plan::*;
-use datafusion::logical_expr::{col, LogicalPlan, LogicalPlanBuilder};
+use crate::source_catalog::GraphSourceCatalog;
+use arrow_schema::DataType;
+use datafusion::functions_nested::expr_fn::make_array;
+use datafusion::logical_expr::{cast, ident, lit, Expr, LogicalPlan, LogicalPlanBuilder};
+use datafusion::scalar::ScalarValue;
 use std::collections::HashMap;
+use std::sync::Arc;
 
 impl DataFusionPlanner {
     /// Build a relationship expansion (graph traversal) as a series of joins
@@ -35,11 +44,49 @@ impl DataFusionPlanner {
             return Ok(left_plan);
         };
 
-        let Some(rel_type) = relationship_types.first() else {
-            return Ok(left_plan);
+        // A hop over several types is the union of a hop over each
+        let mut branches = Vec::new();
+        for rel_type in relationship_types {
+            let rel_instance = ctx.next_relationship_instance(rel_type)?;
+            let plan = self.build_expand_of_type(
+                ctx,
+                left_plan.clone(),
+                cat,
+                &rel_instance,
+                source_variable,
+                target_variable,
+                target_label,
+                direction,
+                relationship_properties,
+                target_properties,
+            )?;
+            branches.push((plan, rel_instance.alias));
+        }
+        let (plan, alias) = match branches.len() {
+            0 => return Ok(left_plan),
+            1 => branches.remove(0),
+            _ => self.union_relationship_types(branches)?,
         };
+        ctx.bind_relationship(&alias, relationship_types);
+        Ok(plan)
+    }
 
-        let rel_instance = ctx.next_relationship_instance(rel_type)?;
+    /// Build the joins of an expansion over a single relationship type
+    #[allow(clippy::too_many_arguments)]
+    fn build_expand_of_type(
+        &self,
+        ctx: &mut PlanningContext,
+        left_plan: LogicalPlan,
+        cat: &Arc<dyn GraphSourceCatalog>,
+        rel_instance: &RelationshipInstance,
+        source_variable: &str,
+        target_variable: &str,
+        target_label: &str,
+        direction: &RelationshipDirection,
+        relationship_properties: &HashMap<String, crate::ast::PropertyValue>,
+        target_properties: &HashMap<String, crate::ast::PropertyValue>,
+    ) -> Result<LogicalPlan> {
+        let rel_type = &rel_instance.rel_type;
         let Some(rel_map) = self.config.relationship_mappings.get(rel_type) else {
             return Ok(left_plan);
         };
@@ -52,23 +99,14 @@ impl DataFusionPlanner {
             return Ok(left_plan);
         };
 
-        let Some(rel_source) = cat.relationship_source(&rel_map.relationship_type) else {
+        let Some(rel_source) = self.relationship_table_source(cat.as_ref(), rel_type) else {
             return Ok(left_plan);
         };
 
         // Build relationship scan with qualified columns and property filters
         let rel_scan =
-            self.build_relationship_scan(&rel_instance, rel_source, relationship_properties)?;
-
-        // Join source node with relationship
-        let source_params = SourceJoinParams {
-            source_variable,
-            rel_qualifier: &rel_instance.alias,
-            node_id_field: &node_map.id_field,
-            rel_map,
-            direction,
-        };
-        let builder = self.join_source_to_relationship(left_plan, rel_scan, &source_params)?;
+            self.build_relationship_scan(rel_instance, rel_source, relationship_properties)?;
+        let rel_scan = self.finish_scan(ctx, &rel_instance.alias, rel_scan)?;
 
         // Join relationship with target node using the explicit target_label
         let target_node_map = self.config.node_mappings.get(target_label).ok_or_else(|| {
@@ -78,15 +116,40 @@ impl DataFusionPlanner {
             }
         })?;
 
-        let target_params = TargetJoinParams {
-            target_variable,
-            rel_qualifier: &rel_instance.alias,
-            node_map: target_node_map,
-            rel_map,
-            direction,
-            target_properties,
-        };
-        self.join_relationship_to_target(builder, cat, ctx, &target_params)
+        // One join chain per traversal direction, unioned for undirected matches
+        let mut branches = Vec::new();
+        for (i, direction) in Self::traversal_directions(direction, rel_map)
+            .iter()
+            .enumerate()
+        {
+            let rel_scan = if i == 0 {
+                rel_scan.clone()
+            } else {
+                self.exclude_self_loops(rel_scan.clone(), &rel_instance.alias, rel_map)?
+            };
+
+            // Join source node with relationship
+            let source_params = SourceJoinParams {
+                source_variable,
+                rel_qualifier: &rel_instance.alias,
+                node_id_fields: node_map.id_fields(),
+                rel_map,
+                direction,
+            };
+            let builder =
+                self.join_source_to_relationship(left_plan.clone(), rel_scan, &source_params)?;
+
+            let target_params = TargetJoinParams {
+                target_variable,
+                rel_qualifier: &rel_instance.alias,
+                node_map: target_node_map,
+                rel_map,
+                direction,
+                target_properties,
+            };
+            branches.push(self.join_relationship_to_target(builder, cat, ctx, &target_params)?);
+        }
+        self.union_branches(branches)
     }
 
     /// Build variable-length path expansion using unrolling + UNION strategy
@@ -94,6 +157,11 @@ impl DataFusionPlanner {
     /// For a query like: (a)-[:KNOWS*1..3]->(b)
     /// This generates:
     ///   1-hop plan UNION 2-hop plan UNION 3-hop plan
+    ///
+    /// With a `path_variable`, each plan also records the hops it took in that column
+    /// (see `path_ops`). Inside a MATCH clause whose relationships must be distinct, each
+    /// plan drops the paths repeating a relationship (see `uniqueness_ops`). Under [`VariableLengthExpansion::Frontier`], patterns that do not
+    /// need each path are searched breadth-first instead (see `frontier_ops`).
     #[allow(clippy::too_many_arguments)]
     pub(crate) fn build_variable_length_expand(
         &self,
@@ -105,10 +173,14 @@ impl DataFusionPlanner {
         direction: &RelationshipDirection,
         min_length: Option<u32>,
         max_length: Option<u32>,
+        relationship_properties: &HashMap<String, crate::ast::PropertyValue>,
         target_properties: &HashMap<String, crate::ast::PropertyValue>,
+        path_variable: Option<&str>,
+        relationship_variable: Option<&str>,
     ) -> Result<LogicalPlan> {
+        let hop_limit = self.config.max_variable_length_hops;
         let min_hops = min_length.unwrap_or(1).max(1);
-        let max_hops = max_length.unwrap_or(crate::MAX_VARIABLE_LENGTH_HOPS);
+        let max_hops = max_length.unwrap_or(hop_limit);
 
         // Validate range
         if min_hops > max_hops {
@@ -121,12 +193,11 @@ impl DataFusionPlanner {
             });
         }
 
-        if max_hops > crate::MAX_VARIABLE_LENGTH_HOPS {
+        if max_hops > hop_limit {
             return Err(crate::error::GraphError::UnsupportedFeature {
                 feature: format!(
                     "Variable-length paths with max length > {} (got {})",
-                    crate::MAX_VARIABLE_LENGTH_HOPS,
-                    max_hops
+                    hop_limit, max_hops
                 ),
                 location: snafu::Location::new(file!(), line!(), column!()),
             });
@@ -135,16 +206,45 @@ impl DataFusionPlanner {
         // Build the input plan (source node scan)
         let input_plan = self.build_operator(ctx, input)?;
 
+        if self.config.variable_length_expansion == VariableLengthExpansion::Frontier
+            && path_variable.is_none()
+            && relationship_variable.is_none()
+            && min_hops == 1
+        {
+            let frontier = self.build_frontier_expand(
+                ctx,
+                &input_plan,
+                source_variable,
+                target_variable,
+                relationship_types,
+                direction,
+                max_hops,
+                relationship_properties,
+                target_properties,
+            )?;
+            if let Some(plan) = frontier {
+                return Ok(plan);
+            }
+        }
+
         // Derive expected column names from source and target node schemas
         // This ensures we only project columns that actually belong to source/target nodes
-        let expected_columns =
+        let mut expected_columns =
             self.get_expected_varlength_columns(ctx, source_variable, target_variable)?;
+        // Variables bound before the expansion stay bound
+        expected_columns.extend(
+            input_plan
+                .schema()
+                .fields()
+                .iter()
+                .map(|f| f.name().clone()),
+        );
 
         // Generate a plan for each hop count and UNION them
         let mut plans = Vec::new();
 
         for hop_count in min_hops..=max_hops {
-            let mut plan = self.build_fixed_length_path(
+            let (mut plan, hops) = self.build_fixed_length_path(
                 ctx,
                 input_plan.clone(),
                 source_variable,
@@ -152,19 +252,33 @@ impl DataFusionPlanner {
                 relationship_types,
                 direction,
                 hop_count,
+                relationship_properties,
                 target_properties,
             )?;
+            if let Some(earlier) = ctx.relationship_scopes.last().cloned() {
+                let hops: Vec<BoundRelationship> = hops
+                    .iter()
+                    .map(|(alias, _)| BoundRelationship {
+                        alias: alias.clone(),
+                        rel_types: relationship_types.to_vec(),
+                    })
+                    .collect();
+                plan = self.filter_repeated_relationships(plan, &hops, &earlier)?;
+            }
 
             // Project only source and target columns to ensure consistent schema for UNION
             // This removes intermediate node columns that vary by hop count
             // Use the pre-computed expected column set derived from actual node schemas
-            let projection: Vec<datafusion::logical_expr::Expr> = plan
+            let mut projection: Vec<datafusion::logical_expr::Expr> = plan
                 .schema()
                 .fields()
                 .iter()
                 .filter(|f| expected_columns.contains(f.name().as_str()))
-                .map(|f| col(f.name()))
+                .map(|f| ident(f.name()))
                 .collect();
+            if let Some(path_variable) = path_variable {
+                projection.push(self.path_segment(ctx, &plan, &hops)?.alias(path_variable));
+            }
 
             plan = LogicalPlanBuilder::from(plan)
                 .project(projection)
@@ -204,7 +318,8 @@ impl DataFusionPlanner {
         }
     }
 
-    /// Build a fixed-length path of N hops
+    /// Build a fixed-length path of N hops, along with the relationship alias and target
+    /// variable of each hop
     ///
     /// For hop_count=3: (a)-[:KNOWS]->(temp1)-[:KNOWS]->(temp2)-[:KNOWS]->(b)
     #[allow(clippy::too_many_arguments)]
@@ -217,10 +332,18 @@ impl DataFusionPlanner {
         relationship_types: &[String],
         direction: &RelationshipDirection,
         hop_count: u32,
+        relationship_properties: &HashMap<String, crate::ast::PropertyValue>,
         target_properties: &HashMap<String, crate::ast::PropertyValue>,
-    ) -> Result<LogicalPlan> {
+    ) -> Result<(LogicalPlan, Vec<(String, String)>)> {
+        if relationship_types.is_empty() {
+            return Err(crate::error::GraphError::InvalidPattern {
+                message: "Expand requires at least one relationship type".to_string(),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            });
+        }
         let mut current_plan = input_plan;
         let mut current_source = source_variable.to_string();
+        let mut hops = Vec::new();
 
         for hop_index in 0..hop_count {
             let is_last_hop = hop_index == hop_count - 1;
@@ -240,21 +363,122 @@ impl DataFusionPlanner {
                 &HashMap::new()
             };
 
-            current_plan = self.build_expand_on_plan(
-                ctx,
-                current_plan,
-                &current_source,
-                &current_target,
-                relationship_types,
-                direction,
-                props_to_apply,
-            )?;
+            let mut branches = Vec::new();
+            for rel_type in relationship_types {
+                let rel_instance = ctx.next_relationship_instance(rel_type)?;
+                let plan = self.build_expand_on_plan(
+                    ctx,
+                    current_plan.clone(),
+                    &current_source,
+                    &current_target,
+                    &rel_instance,
+                    direction,
+                    relationship_properties,
+                    props_to_apply,
+                )?;
+                branches.push((plan, rel_instance.alias));
+            }
+            let alias;
+            (current_plan, alias) = if branches.len() == 1 {
+                branches.remove(0)
+            } else {
+                self.union_relationship_types(branches)?
+            };
+            hops.push((alias, current_target.clone()));
 
             // Move to next hop
             current_source = current_target;
         }
 
-        Ok(current_plan)
+        Ok((current_plan, hops))
+    }
+
+    /// The part of a named path taken by the hops of a fixed-length plan: the node ids
+    /// after its source, the relationships and the number of hops
+    fn path_segment(
+        &self,
+        ctx: &PlanningContext,
+        plan: &LogicalPlan,
+        hops: &[(String, String)],
+    ) -> Result<Expr> {
+        let schema = plan.schema();
+        let mut nodes = Vec::new();
+        let mut relationships = Vec::new();
+        for (alias, target) in hops {
+            nodes.push(ident(self.node_id_column(ctx, target)?));
+            let fields = relationship_fields(schema, alias);
+            relationships.push(relationship_struct(schema, alias, &fields));
+        }
+        Ok(path_struct(
+            make_array(nodes),
+            make_array(relationships),
+            lit(hops.len() as i64),
+        ))
+    }
+
+    /// Union the plans of a hop over each of its relationship types
+    ///
+    /// Each plan binds the relationship under the alias of its own instance. The union
+    /// binds it under the first alias, which it returns, with the properties of every
+    /// type: NULL where a type lacks one, cast to the first type seen otherwise.
+    fn union_relationship_types(
+        &self,
+        branches: Vec<(LogicalPlan, String)>,
+    ) -> Result<(LogicalPlan, String)> {
+        let alias = branches
+            .first()
+            .map(|(_, alias)| alias.clone())
+            .unwrap_or_default();
+        let prefix = format!("{}__", alias);
+
+        // The columns of each branch under their names in the union
+        let renamed: Vec<Vec<(Expr, String, DataType)>> = branches
+            .iter()
+            .map(|(plan, branch_alias)| {
+                let branch_prefix = format!("{}__", branch_alias);
+                plan.schema()
+                    .iter()
+                    .map(|(qualifier, field)| {
+                        let name = match field.name().strip_prefix(&branch_prefix) {
+                            Some(property) => format!("{}{}", prefix, property),
+                            None => field.name().clone(),
+                        };
+                        (
+                            Expr::Column((qualifier, field).into()),
+                            name,
+                            field.data_type().clone(),
+                        )
+                    })
+                    .collect()
+            })
+            .collect();
+        let mut columns: Vec<(String, DataType)> = Vec::new();
+        for (_, name, data_type) in renamed.iter().flatten() {
+            if !columns.iter().any(|(n, _)| n == name) {
+                columns.push((name.clone(), data_type.clone()));
+            }
+        }
+
+        let mut plans = Vec::new();
+        for ((plan, _), branch_columns) in branches.into_iter().zip(renamed) {
+            let projection: Vec<Expr> = columns
+                .iter()
+                .map(|(name, data_type)| {
+                    let value = match branch_columns.iter().find(|(_, n, _)| n == name) {
+                        Some((expr, _, _)) => cast(expr.clone(), data_type.clone()),
+                        None => lit(ScalarValue::try_from(data_type).unwrap_or(ScalarValue::Null)),
+                    };
+                    value.alias(name)
+                })
+                .collect();
+            plans.push(
+                LogicalPlanBuilder::from(plan)
+                    .project(projection)
+                    .and_then(|builder| builder.build())
+                    .map_err(|e| self.plan_error("Failed to align relationship types", e))?,
+            );
+        }
+        Ok((self.union_branches(plans)?, alias))
     }
 
     /// Build a single-hop expansion on top of an existing plan
@@ -265,58 +489,64 @@ impl DataFusionPlanner {
         input_plan: LogicalPlan,
         source_variable: &str,
         target_variable: &str,
-        relationship_types: &[String],
+        rel_instance: &RelationshipInstance,
         direction: &RelationshipDirection,
+        relationship_properties: &HashMap<String, crate::ast::PropertyValue>,
         target_properties: &HashMap<String, crate::ast::PropertyValue>,
     ) -> Result<LogicalPlan> {
-        let rel_type =
-            relationship_types
-                .first()
-                .ok_or_else(|| crate::error::GraphError::InvalidPattern {
-                    message: "Expand requires at least one relationship type".to_string(),
-                    location: snafu::Location::new(file!(), line!(), column!()),
-                })?;
-
-        let rel_instance = ctx.next_relationship_instance(rel_type)?;
-        let rel_map = self.get_relationship_mapping(rel_type)?;
+        let rel_map = self.get_relationship_mapping(&rel_instance.rel_type)?;
         let (target_label, node_map) = self.get_target_node_mapping(ctx, target_variable)?;
         let catalog = self.get_catalog()?;
 
-        // Build relationship scan and join
-        let rel_scan = self.build_qualified_relationship_scan(catalog, &rel_instance)?;
-        let mut builder = self.join_relationship_to_input(
-            input_plan,
-            rel_scan,
-            source_variable,
-            &rel_instance,
-            rel_map,
-            node_map,
-            direction,
-        )?;
-
-        // Build target node scan and join
+        // Build relationship and target scans, then one join chain per direction
+        let rel_scan =
+            self.build_qualified_relationship_scan(catalog, rel_instance, relationship_properties)?;
         let target_scan = self.build_qualified_target_scan(
             catalog,
             &target_label,
             target_variable,
             target_properties,
         )?;
-        builder = self.join_target_to_builder(
-            builder,
-            target_scan,
-            target_variable,
-            &rel_instance,
-            rel_map,
-            node_map,
-            direction,
-        )?;
-
-        builder
-            .build()
-            .map_err(|e| crate::error::GraphError::PlanError {
-                message: format!("Failed to build expansion plan: {}", e),
-                location: snafu::Location::new(file!(), line!(), column!()),
-            })
+        let target_scan = self.finish_scan(ctx, target_variable, target_scan)?;
+
+        let mut branches = Vec::new();
+        for (i, direction) in Self::traversal_directions(direction, rel_map)
+            .iter()
+            .enumerate()
+        {
+            let rel_scan = if i == 0 {
+                rel_scan.clone()
+            } else {
+                self.exclude_self_loops(rel_scan.clone(), &rel_instance.alias, rel_map)?
+            };
+            let mut builder = self.join_relationship_to_input(
+                input_plan.clone(),
+                rel_scan,
+                source_variable,
+                rel_instance,
+                rel_map,
+                node_map,
+                direction,
+            )?;
+            builder = self.join_target_to_builder(
+                builder,
+                target_scan.clone(),
+                target_variable,
+                rel_instance,
+                rel_map,
+                node_map,
+                direction,
+            )?;
+            branches.push(
+                builder
+                    .build()
+                    .map_err(|e| crate::error::GraphError::PlanError {
+                        message: format!("Failed to build expansion plan: {}", e),
+                        location: snafu::Location::new(file!(), line!(), column!()),
+                    })?,
+            );
+        }
+        self.union_branches(branches)
     }
 }
 
@@ -391,9 +621,11 @@ mod tests {
             relationship_types: vec!["KNOWS".into()],
             direction: crate::ast::RelationshipDirection::Outgoing,
             relationship_variable: Some("r".into()),
+            properties: HashMap::new(),
             min_length: Some(1),
             max_length: Some(2),
             target_properties: HashMap::new(),
+            path_variable: None,
         };
         let project = LogicalOperator::Project {
             input: Box::new(vlexpand),
@@ -426,9 +658,11 @@ mod tests {
             relationship_types: vec!["KNOWS".into()],
             direction: crate::ast::RelationshipDirection::Outgoing,
             relationship_variable: None,
+            properties: HashMap::new(),
             min_length: Some(1),
             max_length: Some(1),
             target_properties: HashMap::new(),
+            path_variable: None,
         };
         let project = LogicalOperator::Project {
             input: Box::new(vlexpand),
@@ -461,9 +695,11 @@ mod tests {
             relationship_types: vec!["KNOWS".into()],
             direction: crate::ast::RelationshipDirection::Outgoing,
             relationship_variable: None,
+            properties: HashMap::new(),
             min_length: Some(2),
             max_length: Some(3),
             target_properties: HashMap::new(),
+            path_variable: None,
         };
         let project = LogicalOperator::Project {
             input: Box::new(vlexpand),
@@ -504,9 +740,11 @@ mod tests {
             relationship_types: vec!["KNOWS".into()],
             direction: crate::ast::RelationshipDirection::Outgoing,
             relationship_variable: None,
+            properties: HashMap::new(),
             min_length: None, // Should default to 1
             max_length: Some(3),
             target_properties: HashMap::new(),
+            path_variable: None,
         };
         let project = LogicalOperator::Project {
             input: Box::new(vlexpand),
@@ -546,9 +784,11 @@ mod tests {
             relationship_types: vec!["KNOWS".into()],
             direction: crate::ast::RelationshipDirection::Outgoing,
             relationship_variable: None,
+            properties: HashMap::new(),
             min_length: Some(2),
             max_length: None, // Should default to MAX_VARIABLE_LENGTH_HOPS (20)
             target_properties: HashMap::new(),
+            path_variable: None,
         };
         let project = LogicalOperator::Project {
             input: Box::new(vlexpand),
@@ -589,9 +829,11 @@ mod tests {
             relationship_types: vec!["KNOWS".into()],
             direction: crate::ast::RelationshipDirection::Outgoing,
             relationship_variable: None,
+            properties: HashMap::new(),
             min_length: Some(3),
             max_length: Some(2), // Invalid: min > max
             target_properties: HashMap::new(),
+            path_variable: None,
         };
         let project = LogicalOperator::Project {
             input: Box::new(vlexpand),
@@ -632,9 +874,11 @@ mod tests {
             relationship_types: vec!["KNOWS".into()],
             direction: crate::ast::RelationshipDirection::Outgoing,
             relationship_variable: None,
+            properties: HashMap::new(),
             min_length: Some(1),
             max_length: Some(25), // Exceeds MAX_VARIABLE_LENGTH_HOPS
             target_properties: HashMap::new(),
+            path_variable: None,
         };
         let project = LogicalOperator::Project {
             input: Box::new(vlexpand),
@@ -658,6 +902,16 @@ mod tests {
         assert!(result.is_err());
         let err_msg = format!("{:?}", result.unwrap_err());
         assert!(err_msg.contains("Variable-length paths with max length > 20"));
+
+        // Raising the configured limit admits the same pattern
+        let cfg = crate::config::GraphConfig::builder()
+            .with_node_label("Person", "id")
+            .with_relationship("KNOWS", "src_person_id", "dst_person_id")
+            .with_max_variable_length_hops(25)
+            .build()
+            .unwrap();
+        let planner = DataFusionPlanner::with_catalog(cfg, make_catalog());
+        assert!(planner.plan(&project).is_ok());
     }
 
     #[test]
@@ -675,9 +929,11 @@ mod tests {
             relationship_types: vec!["KNOWS".into()],
             direction: crate::ast::RelationshipDirection::Outgoing,
             relationship_variable: None,
+            properties: HashMap::new(),
             min_length: Some(1),
             max_length: Some(2),
             target_properties: HashMap::new(),
+            path_variable: None,
         };
         let filter = LogicalOperator::Filter {
             input: Box::new(vlexpand),
@@ -0,0 +1,221 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Breadth-first variable-length expansion
+//!
+//! Under [`VariableLengthExpansion::Frontier`](crate::config::VariableLengthExpansion),
+//! `(a)-[:KNOWS*1..5]->(b)` becomes a scan of a [`FrontierTable`] fed with the distinct
+//! source ids and the edge list, joined back to the input on the source and to the
+//! target label's table on each reached node. See the `frontier` module for the
+//! expansion itself.
+
+use crate::ast::{PropertyValue, RelationshipDirection, ValueExpression};
+use crate::datafusion_planner::analysis::{PlanningContext, RelationshipInstance};
+use crate::datafusion_planner::expression::to_df_value_expr;
+use crate::datafusion_planner::frontier::{FrontierTable, FROM, NODE, START, TO};
+use crate::datafusion_planner::DataFusionPlanner;
+use crate::error::Result;
+use datafusion::datasource::provider_as_source;
+use datafusion::logical_expr::{cast, ident, Expr, JoinType, LogicalPlan, LogicalPlanBuilder};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+const FRONTIER: &str = "__vl_frontier";
+
+impl DataFusionPlanner {
+    /// Build a variable-length expansion of at most `max_hops` hops as a breadth-first
+    /// search, or return `None` when it must be unrolled
+    ///
+    /// Patterns over composite ids or relationship keys, and patterns with `$parameters`
+    /// in their source rows or relationship properties, are unrolled: the search plans
+    /// its inputs only when it runs, after parameters are bound.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn build_frontier_expand(
+        &self,
+        ctx: &mut PlanningContext,
+        input_plan: &LogicalPlan,
+        source_variable: &str,
+        target_variable: &str,
+        relationship_types: &[String],
+        direction: &RelationshipDirection,
+        max_hops: u32,
+        relationship_properties: &HashMap<String, PropertyValue>,
+        target_properties: &HashMap<String, PropertyValue>,
+    ) -> Result<Option<LogicalPlan>> {
+        let (Some(source_label), Some(target_label)) = (
+            ctx.analysis.var_to_label.get(source_variable).cloned(),
+            ctx.analysis.var_to_label.get(target_variable).cloned(),
+        ) else {
+            return Ok(None);
+        };
+        let (Ok(source_key), Ok(target_key)) = (
+            self.single_id_column(source_variable, &source_label),
+            self.single_id_column(target_variable, &target_label),
+        ) else {
+            return Ok(None);
+        };
+        let Ok(key_field) = input_plan.schema().field_with_unqualified_name(&source_key) else {
+            return Ok(None);
+        };
+        let key_type = key_field.data_type().clone();
+        let Some(edges) = self.build_frontier_edges(
+            relationship_types,
+            direction,
+            relationship_properties,
+            &key_type,
+        )?
+        else {
+            return Ok(None);
+        };
+        let starts = LogicalPlanBuilder::from(input_plan.clone())
+            .project(vec![cast(ident(&source_key), key_type.clone()).alias(START)])
+            .and_then(|builder| builder.distinct())
+            .and_then(|builder| builder.build())
+            .map_err(|e| self.plan_error("Failed to collect expansion sources", e))?;
+        let parameterized = |plan: &LogicalPlan| {
+            plan.get_parameter_types()
+                .map_or(true, |parameters| !parameters.is_empty())
+        };
+        if parameterized(&starts) || parameterized(&edges) {
+            return Ok(None);
+        }
+
+        // Later expansions of the same types take the instances after the unrolled hops
+        for hop_count in 1..=max_hops {
+            for _ in 0..hop_count {
+                for rel_type in relationship_types {
+                    ctx.next_relationship_instance(rel_type)?;
+                }
+            }
+        }
+
+        let frontier = FrontierTable::new(starts, edges, max_hops, key_type);
+        let reached =
+            LogicalPlanBuilder::scan(FRONTIER, provider_as_source(Arc::new(frontier)), None)
+                .and_then(|builder| builder.build())
+                .map_err(|e| self.plan_error("Failed to scan expansion frontier", e))?;
+
+        // A target bound earlier joins on both ends, otherwise the reached nodes are
+        // looked up in the target label's table
+        let target_bound = input_plan
+            .schema()
+            .has_column_with_unqualified_name(&target_key);
+        let mut builder = if target_bound {
+            let mut builder = LogicalPlanBuilder::from(input_plan.clone())
+                .join(
+                    reached,
+                    JoinType::Inner,
+                    (vec![source_key, target_key], vec![START, NODE]),
+                    None,
+                )
+                .map_err(|e| self.plan_error("Failed to join expansion frontier", e))?;
+            for (property, value) in target_properties {
+                let column = ident(format!("{}__{}", target_variable, property));
+                let value = to_df_value_expr(&ValueExpression::Literal(value.clone()));
+                builder = builder
+                    .filter(column.eq(value))
+                    .map_err(|e| self.plan_error("Failed to filter expansion target", e))?;
+            }
+            builder
+        } else {
+            let target_scan = self.build_qualified_target_scan(
+                self.get_catalog()?,
+                &target_label,
+                target_variable,
+                target_properties,
+            )?;
+            let target_scan = self.finish_scan(ctx, target_variable, target_scan)?;
+            let reached = LogicalPlanBuilder::from(target_scan)
+                .join(
+                    reached,
+                    JoinType::Inner,
+                    (vec![target_key], vec![NODE]),
+                    None,
+                )
+                .and_then(|builder| builder.build())
+                .map_err(|e| self.plan_error("Failed to join expansion targets", e))?;
+            LogicalPlanBuilder::from(input_plan.clone())
+                .join(
+                    reached,
+                    JoinType::Inner,
+                    (vec![source_key], vec![START]),
+                    None,
+                )
+                .map_err(|e| self.plan_error("Failed to join expansion frontier", e))?
+        };
+
+        let projection: Vec<Expr> = builder
+            .schema()
+            .columns()
+            .into_iter()
+            .filter(|column| !column.name.starts_with("__vl_"))
+            .map(Expr::Column)
+            .collect();
+        builder = builder
+            .project(projection)
+            .map_err(|e| self.plan_error("Failed to project expansion", e))?;
+        builder
+            .build()
+            .map(Some)
+            .map_err(|e| self.plan_error("Failed to build expansion plan", e))
+    }
+
+    /// Edge list (`__vl_from`, `__vl_to`) over every traversable relationship type and
+    /// direction, with ids cast to `key_type`, or `None` when a type has composite keys
+    fn build_frontier_edges(
+        &self,
+        relationship_types: &[String],
+        direction: &RelationshipDirection,
+        relationship_properties: &HashMap<String, PropertyValue>,
+        key_type: &arrow_schema::DataType,
+    ) -> Result<Option<LogicalPlan>> {
+        let catalog = self.get_catalog()?;
+        let mut branches = Vec::new();
+        for (i, rel_type) in relationship_types.iter().enumerate() {
+            let rel_map = self.get_relationship_mapping(rel_type)?;
+            let (source_fields, target_fields) =
+                (rel_map.source_id_fields(), rel_map.target_id_fields());
+            let ([source_field], [target_field]) =
+                (source_fields.as_slice(), target_fields.as_slice())
+            else {
+                return Ok(None);
+            };
+            let instance = RelationshipInstance {
+                id: i,
+                rel_type: rel_type.clone(),
+                source_var: FROM.to_string(),
+                target_var: TO.to_string(),
+                direction: direction.clone(),
+                alias: format!("__vl_rel_{}", i),
+            };
+            let rel_scan = self.build_qualified_relationship_scan(
+                catalog,
+                &instance,
+                relationship_properties,
+            )?;
+            let source_column =
+                self.qualified_relationship_column(&instance.alias, rel_type, source_field);
+            let target_column =
+                self.qualified_relationship_column(&instance.alias, rel_type, target_field);
+            for direction in Self::traversal_directions(direction, rel_map) {
+                let (from, to) = match direction {
+                    RelationshipDirection::Incoming => (&target_column, &source_column),
+                    _ => (&source_column, &target_column),
+                };
+                branches.push(
+                    LogicalPlanBuilder::from(rel_scan.clone())
+                        .project(vec![
+                            cast(ident(from.as_str()), key_type.clone()).alias(FROM),
+                            cast(ident(to.as_str()), key_type.clone()).alias(TO),
+                        ])
+                        .and_then(|builder| builder.build())
+                        .map_err(|e| self.plan_error("Failed to project edge list", e))?,
+                );
+            }
+        }
+        if branches.is_empty() {
+            return Ok(None);
+        }
+        self.union_branches(branches).map(Some)
+    }
+}
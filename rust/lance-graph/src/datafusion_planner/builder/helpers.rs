@@ -3,6 +3,7 @@
 
 //! Helper utilities for plan building
 
+use crate::ast::ValueExpression;
 use crate::datafusion_planner::DataFusionPlanner;
 use crate::logical_plan::*;
 
@@ -20,6 +21,67 @@ impl DataFusionPlanner {
         vars
     }
 
+    /// Label `variable` is scanned with where `op` binds it, when that is known
+    ///
+    /// Variables carried through WITH, and targets of variable-length expansions, are
+    /// reported unknown.
+    pub(crate) fn bound_label<'a>(op: &'a LogicalOperator, variable: &str) -> Option<&'a str> {
+        match op {
+            LogicalOperator::ScanByLabel {
+                variable: scanned,
+                label,
+                ..
+            } => (scanned == variable).then_some(label.as_str()),
+            LogicalOperator::Expand {
+                input,
+                target_variable,
+                target_label,
+                ..
+            }
+            | LogicalOperator::ShortestPath {
+                input,
+                target_variable,
+                target_label,
+                ..
+            } => {
+                if target_variable == variable {
+                    Some(target_label.as_str())
+                } else {
+                    Self::bound_label(input, variable)
+                }
+            }
+            LogicalOperator::VariableLengthExpand {
+                input,
+                target_variable,
+                ..
+            } => {
+                if target_variable == variable {
+                    None
+                } else {
+                    Self::bound_label(input, variable)
+                }
+            }
+            LogicalOperator::Filter { input, .. }
+            | LogicalOperator::Project { input, .. }
+            | LogicalOperator::BindPath { input, .. }
+            | LogicalOperator::Distinct { input }
+            | LogicalOperator::DistinctRelationships { input }
+            | LogicalOperator::Sort { input, .. }
+            | LogicalOperator::Limit { input, .. }
+            | LogicalOperator::Offset { input, .. }
+            | LogicalOperator::SemiJoin { input, .. }
+            | LogicalOperator::PatternCount { input, .. } => Self::bound_label(input, variable),
+            LogicalOperator::Unwind { input, .. }
+            | LogicalOperator::ProcedureCall { input, .. } => input
+                .as_deref()
+                .and_then(|input| Self::bound_label(input, variable)),
+            LogicalOperator::Join { left, right, .. } => {
+                Self::bound_label(left, variable).or_else(|| Self::bound_label(right, variable))
+            }
+            LogicalOperator::With { .. } | LogicalOperator::Union { .. } => None,
+        }
+    }
+
     /// Recursively collect variables from a logical operator
     ///
     /// Collects both node variables and relationship variables to support
@@ -37,7 +99,33 @@ impl DataFusionPlanner {
             LogicalOperator::Project { input, .. } => {
                 Self::collect_variables(input, vars);
             }
-            LogicalOperator::Distinct { input } => {
+            LogicalOperator::BindPath {
+                input, variable, ..
+            } => {
+                Self::collect_variables(input, vars);
+                vars.push(variable.clone());
+            }
+            // Unwind and procedure calls bind values, not nodes or relationships
+            LogicalOperator::Unwind { input, .. }
+            | LogicalOperator::ProcedureCall { input, .. } => {
+                if let Some(input) = input {
+                    Self::collect_variables(input, vars);
+                }
+            }
+            // With: only the carried variables remain, under their aliases
+            LogicalOperator::With { input, items } => {
+                let mut inner = Vec::new();
+                Self::collect_variables(input, &mut inner);
+                for item in items {
+                    if let ValueExpression::Variable(v) = &item.expression {
+                        if inner.contains(v) {
+                            vars.push(item.alias.clone().unwrap_or_else(|| v.clone()));
+                        }
+                    }
+                }
+            }
+            LogicalOperator::Distinct { input }
+            | LogicalOperator::DistinctRelationships { input } => {
                 Self::collect_variables(input, vars);
             }
             LogicalOperator::Sort { input, .. } => {
@@ -65,6 +153,18 @@ impl DataFusionPlanner {
                     vars.push(rel_var.clone());
                 }
             }
+            LogicalOperator::ShortestPath {
+                input,
+                target_variable,
+                path_variable,
+                ..
+            } => {
+                Self::collect_variables(input, vars);
+                vars.push(target_variable.clone());
+                if let Some(path_var) = path_variable {
+                    vars.push(path_var.clone());
+                }
+            }
             LogicalOperator::VariableLengthExpand {
                 input,
                 source_variable,
@@ -85,6 +185,13 @@ impl DataFusionPlanner {
                 Self::collect_variables(left, vars);
                 Self::collect_variables(right, vars);
             }
+            // Subqueries: the pattern's variables stay inside the subquery
+            LogicalOperator::SemiJoin { input, .. }
+            | LogicalOperator::PatternCount { input, .. } => {
+                Self::collect_variables(input, vars);
+            }
+            // Union: no variable stays bound past the combined RETURN
+            LogicalOperator::Union { .. } => {}
         }
     }
 }
@@ -214,9 +321,11 @@ mod tests {
             relationship_types: vec!["KNOWS".into()],
             direction: RelationshipDirection::Outgoing,
             relationship_variable: None,
+            properties: HashMap::new(),
             min_length: Some(1),
             max_length: Some(3),
             target_properties: HashMap::new(),
+            path_variable: None,
         };
 
         let vars = planner.extract_variables(&varlength);
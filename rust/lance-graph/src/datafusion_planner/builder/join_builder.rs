@@ -7,7 +7,9 @@ use crate::datafusion_planner::analysis::PlanningContext;
 use crate::datafusion_planner::DataFusionPlanner;
 use crate::error::Result;
 use crate::logical_plan::*;
-use datafusion::logical_expr::{LogicalPlan, LogicalPlanBuilder};
+use datafusion::common::Column;
+use datafusion::logical_expr::{Expr, LogicalPlan, LogicalPlanBuilder};
+use std::collections::HashSet;
 
 impl DataFusionPlanner {
     /// Build a join between two logical operators
@@ -59,12 +61,14 @@ impl DataFusionPlanner {
                 }
 
                 // Build inner join with inferred keys
-                let df_join_type = datafusion::logical_expr::JoinType::Inner;
-                LogicalPlanBuilder::from(left_plan)
-                    .join(right_plan, df_join_type, (left_keys, right_keys), None)
-                    .map_err(|e| self.plan_error("Failed to build inner join", e))?
-                    .build()
-                    .map_err(|e| self.plan_error("Failed to build plan", e))
+                self.join_on_keys(
+                    left_plan,
+                    right_plan,
+                    datafusion::logical_expr::JoinType::Inner,
+                    (left_keys, right_keys),
+                    None,
+                    "Failed to build inner join",
+                )
             }
             crate::logical_plan::JoinType::Left
             | crate::logical_plan::JoinType::Right
@@ -95,15 +99,350 @@ impl DataFusionPlanner {
 
                 // Build join with inferred keys
                 // Example: JOIN ON left.b__id = right.b__id
-                LogicalPlanBuilder::from(left_plan)
-                    .join(right_plan, df_join_type, (left_keys, right_keys), None)
-                    .map_err(|e| {
-                        self.plan_error(&format!("Failed to build {:?} join", join_type), e)
-                    })?
-                    .build()
-                    .map_err(|e| self.plan_error("Failed to build plan", e))
+                self.join_on_keys(
+                    left_plan,
+                    right_plan,
+                    df_join_type,
+                    (left_keys, right_keys),
+                    None,
+                    &format!("Failed to build {:?} join", join_type),
+                )
+            }
+        }
+    }
+
+    /// Build a semi-join (anti-join when negated) keeping the input rows that have a
+    /// match in the pattern, joining on their shared variables
+    ///
+    /// The predicate becomes the join filter. Shared variables resolve to the input's
+    /// columns, which hold the same values as the pattern's on matching rows.
+    pub(crate) fn build_semi_join(
+        &self,
+        ctx: &mut PlanningContext,
+        input: &LogicalOperator,
+        pattern: &LogicalOperator,
+        predicate: Option<&crate::ast::BooleanExpression>,
+        negated: bool,
+    ) -> Result<LogicalPlan> {
+        let input_plan = self.build_operator(ctx, input)?;
+        let join_type = if negated {
+            datafusion::logical_expr::JoinType::LeftAnti
+        } else {
+            datafusion::logical_expr::JoinType::LeftSemi
+        };
+        if predicate.is_none() {
+            let edges = ctx
+                .isolated(|ctx| self.build_bound_relationship(ctx, input, &input_plan, pattern))?;
+            if let Some((keys, edges)) = edges {
+                return self.join_on_keys(
+                    input_plan,
+                    edges,
+                    join_type,
+                    (keys.clone(), keys),
+                    None,
+                    "Failed to build EXISTS subquery join",
+                );
+            }
+        }
+        let pattern_plan = ctx.isolated(|ctx| self.build_operator(ctx, pattern))?;
+        let keys = self.infer_join_keys(ctx, input, pattern);
+        let filter = predicate
+            .map(|predicate| self.predicate_expr(ctx, predicate))
+            .transpose()?;
+
+        // A pattern sharing no variables keeps all rows or none
+        if keys.0.is_empty() {
+            return LogicalPlanBuilder::from(input_plan)
+                .join_on(
+                    pattern_plan,
+                    join_type,
+                    filter.or(Some(datafusion::logical_expr::lit(true))),
+                )
+                .map_err(|e| self.plan_error("Failed to build EXISTS subquery join", e))?
+                .build()
+                .map_err(|e| self.plan_error("Failed to build plan", e));
+        }
+        self.join_on_keys(
+            input_plan,
+            pattern_plan,
+            join_type,
+            keys,
+            filter,
+            "Failed to build EXISTS subquery join",
+        )
+    }
+
+    /// Build a single-hop pattern whose endpoints the input binds as just the key
+    /// columns of its relationships, named after the endpoints' key columns
+    ///
+    /// Such a pattern only asks whether a relationship links the input's nodes, so its
+    /// semi-join (or anti-join) reads the relationship table without re-scanning either
+    /// node table. Returns the join keys and the plan, or `None` for patterns that also
+    /// constrain the nodes, the relationship's variable or the endpoints' labels.
+    fn build_bound_relationship(
+        &self,
+        ctx: &mut PlanningContext,
+        input: &LogicalOperator,
+        input_plan: &LogicalPlan,
+        pattern: &LogicalOperator,
+    ) -> Result<Option<(Vec<String>, LogicalPlan)>> {
+        // A lone relationship is distinct from every other of its clause
+        let pattern = match pattern {
+            LogicalOperator::DistinctRelationships { input } => input.as_ref(),
+            pattern => pattern,
+        };
+        let LogicalOperator::Expand {
+            input: source_scan,
+            source_variable,
+            target_variable,
+            target_label,
+            relationship_types,
+            direction,
+            relationship_variable: None,
+            properties,
+            target_properties,
+        } = pattern
+        else {
+            return Ok(None);
+        };
+        let LogicalOperator::ScanByLabel {
+            variable: scanned,
+            label: source_label,
+            properties: source_properties,
+        } = source_scan.as_ref()
+        else {
+            return Ok(None);
+        };
+        let [rel_type] = relationship_types.as_slice() else {
+            return Ok(None);
+        };
+        if scanned != source_variable
+            || source_variable == target_variable
+            || !source_properties.is_empty()
+            || !target_properties.is_empty()
+            || Self::bound_label(input, source_variable) != Some(source_label.as_str())
+            || Self::bound_label(input, target_variable) != Some(target_label.as_str())
+        {
+            return Ok(None);
+        }
+        let (Some(cat), Some(rel_map), Some(source_map), Some(target_map)) = (
+            &self.catalog,
+            self.config.relationship_mappings.get(rel_type),
+            self.config.node_mappings.get(source_label),
+            self.config.node_mappings.get(target_label),
+        ) else {
+            return Ok(None);
+        };
+        let Some(rel_source) = self.relationship_table_source(cat.as_ref(), rel_type) else {
+            return Ok(None);
+        };
+
+        let endpoints = [
+            (source_variable, source_map.id_fields()),
+            (target_variable, target_map.id_fields()),
+        ];
+        let keys: Vec<String> = endpoints
+            .iter()
+            .flat_map(|(variable, id_fields)| {
+                id_fields
+                    .iter()
+                    .map(move |field| format!("{}__{}", variable, field))
+            })
+            .collect();
+        if !keys
+            .iter()
+            .all(|key| input_plan.schema().has_column_with_unqualified_name(key))
+        {
+            return Ok(None);
+        }
+
+        let rel_instance = ctx.next_relationship_instance(rel_type)?;
+        let rel_scan = self.build_relationship_scan(&rel_instance, rel_source, properties)?;
+        let rel_scan = self.finish_scan(ctx, &rel_instance.alias, rel_scan)?;
+
+        // One projection per traversal direction; duplicates do not change a semi-join
+        let mut branches = Vec::new();
+        for direction in Self::traversal_directions(direction, rel_map) {
+            let sides = [
+                Self::get_source_join_keys(&direction, rel_map),
+                Self::get_target_join_keys(&direction, rel_map),
+            ];
+            let mut exprs = Vec::new();
+            for ((variable, id_fields), rel_fields) in endpoints.iter().zip(&sides) {
+                let (node_keys, rel_keys) =
+                    self.qualified_key_pairs(variable, id_fields, &rel_instance.alias, rel_fields)?;
+                for (node_key, rel_key) in node_keys.iter().zip(&rel_keys) {
+                    exprs.push(Expr::Column(Column::from_name(rel_key)).alias(node_key));
+                }
             }
+            let branch = LogicalPlanBuilder::from(rel_scan.clone())
+                .project(exprs)
+                .and_then(|builder| builder.build())
+                .map_err(|e| self.plan_error("Failed to project relationship keys", e))?;
+            branches.push(branch);
         }
+        Ok(Some((keys, self.union_branches(branches)?)))
+    }
+
+    /// Build a left join counting, for each input row, the pattern's rows matching it
+    /// on their shared variables into the column `alias`
+    ///
+    /// Input rows are numbered before the join so that duplicate rows are counted
+    /// separately; the predicate becomes the join filter, as in a semi-join.
+    pub(crate) fn build_pattern_count(
+        &self,
+        ctx: &mut PlanningContext,
+        input: &LogicalOperator,
+        pattern: &LogicalOperator,
+        predicate: Option<&crate::ast::BooleanExpression>,
+        alias: &str,
+    ) -> Result<LogicalPlan> {
+        use datafusion::functions_aggregate::expr_fn::count;
+        use datafusion::functions_window::expr_fn::row_number;
+
+        let input_plan = self.build_operator(ctx, input)?;
+        let pattern_plan = ctx.isolated(|ctx| self.build_operator(ctx, pattern))?;
+        let keys = self.infer_join_keys(ctx, input, pattern);
+        let filter = predicate
+            .map(|predicate| self.predicate_expr(ctx, predicate))
+            .transpose()?;
+        let context = "Failed to build COUNT subquery join";
+
+        let input_columns: Vec<Expr> = input_plan
+            .schema()
+            .columns()
+            .into_iter()
+            .map(Expr::Column)
+            .collect();
+        let row = format!("__{}_row", alias);
+        let input_plan = LogicalPlanBuilder::from(input_plan)
+            .window(vec![row_number().alias(&row)])
+            .map_err(|e| self.plan_error(context, e))?
+            .build()
+            .map_err(|e| self.plan_error("Failed to build plan", e))?;
+
+        // Count a column that is only NULL on input rows without a match
+        let matched = format!("__{}_match", alias);
+        let mut pattern_exprs: Vec<Expr> = pattern_plan
+            .schema()
+            .columns()
+            .into_iter()
+            .map(Expr::Column)
+            .collect();
+        pattern_exprs.push(datafusion::logical_expr::lit(true).alias(&matched));
+        let pattern_plan = LogicalPlanBuilder::from(pattern_plan)
+            .project(pattern_exprs)
+            .map_err(|e| self.plan_error(context, e))?
+            .build()
+            .map_err(|e| self.plan_error("Failed to build plan", e))?;
+
+        let joined = if keys.0.is_empty() {
+            LogicalPlanBuilder::from(input_plan)
+                .join_on(
+                    pattern_plan,
+                    datafusion::logical_expr::JoinType::Left,
+                    filter.or(Some(datafusion::logical_expr::lit(true))),
+                )
+                .map_err(|e| self.plan_error(context, e))?
+                .build()
+                .map_err(|e| self.plan_error("Failed to build plan", e))?
+        } else {
+            self.join_on_keys(
+                input_plan,
+                pattern_plan,
+                datafusion::logical_expr::JoinType::Left,
+                keys,
+                filter,
+                context,
+            )?
+        };
+
+        let mut group_exprs = input_columns.clone();
+        group_exprs.push(Expr::Column(Column::from_name(&row)));
+        let mut outputs = input_columns;
+        outputs.push(Expr::Column(Column::from_name(alias)));
+        LogicalPlanBuilder::from(joined)
+            .aggregate(
+                group_exprs,
+                vec![count(Expr::Column(Column::from_name(&matched))).alias(alias)],
+            )
+            .map_err(|e| self.plan_error(context, e))?
+            .project(outputs)
+            .map_err(|e| self.plan_error(context, e))?
+            .build()
+            .map_err(|e| self.plan_error("Failed to build plan", e))
+    }
+
+    /// Join two plans on the given key columns, keeping one copy of shared columns
+    ///
+    /// Both sides of a join on a shared variable project the same qualified columns
+    /// (e.g. `b__id`, `b__name`). The right side's copies are renamed before joining
+    /// and dropped afterwards, so the output keeps the left side's values - which are
+    /// never NULL-padded in a left outer join. A join filter therefore reads the left
+    /// side's copy of shared columns.
+    fn join_on_keys(
+        &self,
+        left_plan: LogicalPlan,
+        right_plan: LogicalPlan,
+        join_type: datafusion::logical_expr::JoinType,
+        (left_keys, right_keys): (Vec<String>, Vec<String>),
+        filter: Option<Expr>,
+        context: &str,
+    ) -> Result<LogicalPlan> {
+        let left_names: HashSet<String> = left_plan
+            .schema()
+            .fields()
+            .iter()
+            .map(|f| f.name().clone())
+            .collect();
+        let shadowed = |name: &str| format!("__right_{}", name);
+
+        let right_exprs: Vec<Expr> = right_plan
+            .schema()
+            .columns()
+            .into_iter()
+            .map(|column| {
+                if left_names.contains(&column.name) {
+                    let alias = shadowed(&column.name);
+                    Expr::Column(column).alias(alias)
+                } else {
+                    Expr::Column(column)
+                }
+            })
+            .collect();
+        let right_plan = LogicalPlanBuilder::from(right_plan)
+            .project(right_exprs)
+            .map_err(|e| self.plan_error(context, e))?
+            .build()
+            .map_err(|e| self.plan_error("Failed to build plan", e))?;
+        let right_keys: Vec<String> = right_keys
+            .iter()
+            .map(|key| {
+                if left_names.contains(key) {
+                    shadowed(key)
+                } else {
+                    key.clone()
+                }
+            })
+            .collect();
+
+        let joined = LogicalPlanBuilder::from(left_plan)
+            .join(right_plan, join_type, (left_keys, right_keys), filter)
+            .map_err(|e| self.plan_error(context, e))?
+            .build()
+            .map_err(|e| self.plan_error("Failed to build plan", e))?;
+        let output: Vec<Expr> = joined
+            .schema()
+            .columns()
+            .into_iter()
+            .filter(|column| !column.name.starts_with("__right_"))
+            .map(Expr::Column)
+            .collect();
+        LogicalPlanBuilder::from(joined)
+            .project(output)
+            .map_err(|e| self.plan_error(context, e))?
+            .build()
+            .map_err(|e| self.plan_error("Failed to build plan", e))
     }
 
     /// Infer join keys by finding shared variables between left and right plans
@@ -165,12 +504,13 @@ impl DataFusionPlanner {
             if let Some(label) = ctx.analysis.var_to_label.get(var) {
                 // This is a node variable - get the node mapping for its label
                 if let Some(node_map) = self.config.node_mappings.get(label) {
-                    // Generate qualified column names for node ID
+                    // Generate qualified column names for every node ID column
                     // Example: var="b", id_field="id" -> "b__id"
-                    let left_key = format!("{}__{}", var, node_map.id_field);
-                    let right_key = format!("{}__{}", var, node_map.id_field);
-                    left_keys.push(left_key);
-                    right_keys.push(right_key);
+                    for id_field in node_map.id_fields() {
+                        let key = format!("{}__{}", var, id_field);
+                        left_keys.push(key.clone());
+                        right_keys.push(key);
+                    }
                 }
             } else {
                 // Not a node variable - check if it's a relationship variable
@@ -192,15 +532,15 @@ impl DataFusionPlanner {
                         // The columns are qualified as: {alias}__{original_field_name}
                         // Example: var="r", source_id_field="src_person_id"
                         //          -> "r__src_person_id"
-                        let left_src = format!("{}__{}", var, &rel_map.source_id_field);
-                        let right_src = format!("{}__{}", var, &rel_map.source_id_field);
-                        let left_dst = format!("{}__{}", var, &rel_map.target_id_field);
-                        let right_dst = format!("{}__{}", var, &rel_map.target_id_field);
-
-                        left_keys.push(left_src);
-                        right_keys.push(right_src);
-                        left_keys.push(left_dst);
-                        right_keys.push(right_dst);
+                        for field in rel_map
+                            .source_id_fields()
+                            .into_iter()
+                            .chain(rel_map.target_id_fields())
+                        {
+                            let key = format!("{}__{}", var, field);
+                            left_keys.push(key.clone());
+                            right_keys.push(key);
+                        }
                     }
                 }
                 // If not found in either node or relationship variables, skip it
@@ -630,4 +970,50 @@ mod tests {
             "Shared variables should include 'r'"
         );
     }
+
+    #[test]
+    fn test_anti_join_on_bound_endpoints_reads_only_relationships() {
+        use crate::datafusion_planner::test_fixtures::{person_knows_config, person_scan};
+
+        let planner = DataFusionPlanner::with_catalog(person_knows_config(), make_catalog());
+        let expand = |source: &str, target: &str| LogicalOperator::Expand {
+            input: Box::new(person_scan(source)),
+            source_variable: source.to_string(),
+            target_variable: target.to_string(),
+            target_label: "Person".to_string(),
+            relationship_types: vec!["KNOWS".to_string()],
+            direction: crate::ast::RelationshipDirection::Outgoing,
+            relationship_variable: None,
+            properties: Default::default(),
+            target_properties: Default::default(),
+        };
+        // MATCH (a:Person), (b:Person) WHERE NOT (a)-[:KNOWS]->(b)
+        let input = LogicalOperator::Join {
+            left: Box::new(person_scan("a")),
+            right: Box::new(person_scan("b")),
+            join_type: crate::logical_plan::JoinType::Cross,
+        };
+        let anti = LogicalOperator::SemiJoin {
+            input: Box::new(input.clone()),
+            pattern: Box::new(expand("a", "b")),
+            predicate: None,
+            negated: true,
+        };
+
+        let plan = planner.plan(&anti).unwrap().display_indent().to_string();
+        assert!(plan.contains("LeftAnti Join"), "{}", plan);
+        // Two outer node scans and the relationship table; no node re-scans
+        assert_eq!(plan.matches("TableScan").count(), 3, "{}", plan);
+
+        // A pattern binding a new node still re-scans it
+        let semi = LogicalOperator::SemiJoin {
+            input: Box::new(input),
+            pattern: Box::new(expand("a", "c")),
+            predicate: None,
+            negated: false,
+        };
+        let plan = planner.plan(&semi).unwrap().display_indent().to_string();
+        assert!(plan.contains("LeftSemi Join"), "{}", plan);
+        assert_eq!(plan.matches("TableScan").count(), 5, "{}", plan);
+    }
 }
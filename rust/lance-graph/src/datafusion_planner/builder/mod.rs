@@ -6,8 +6,13 @@
 //! Converts logical operators into DataFusion logical plans
 //!
 //! This module is split into several submodules for better organization:
-//! - `basic_ops`: Basic operations (filter, project, sort, limit, offset, distinct)
+//! - `basic_ops`: Basic operations (filter, project, sort, limit, offset, distinct, union)
 //! - `expand_ops`: Graph traversal operations (expand, variable-length expand)
+//! - `path_ops`: Named paths (p = (a)-[...]->(b))
+//! - `procedure_ops`: Procedure calls (CALL ... YIELD)
+//! - `shortest_path_ops`: Shortest path search (shortestPath, allShortestPaths)
+//! - `frontier_ops`: Breadth-first variable-length expansion
+//! - `uniqueness_ops`: Relationship uniqueness within a MATCH clause
 //! - `aggregate_ops`: Aggregation and grouping operations
 //! - `join_builder`: Join inference and building
 //! - `helpers`: Utility functions
@@ -15,8 +20,13 @@
 mod aggregate_ops;
 mod basic_ops;
 mod expand_ops;
+mod frontier_ops;
 mod helpers;
 mod join_builder;
+mod path_ops;
+mod procedure_ops;
+mod shortest_path_ops;
+mod uniqueness_ops;
 
 use super::DataFusionPlanner;
 use crate::error::Result;
@@ -32,19 +42,43 @@ impl DataFusionPlanner {
         ctx: &mut PlanningContext,
         op: &LogicalOperator,
     ) -> Result<LogicalPlan> {
+        if !ctx.pushed_predicates.is_empty() && !passes_predicates(op) {
+            return ctx.isolated(|ctx| self.build_operator(ctx, op));
+        }
         match op {
             LogicalOperator::ScanByLabel {
                 variable,
                 label,
                 properties,
                 ..
-            } => self.build_scan(ctx, variable, label, properties),
+            } => {
+                let scan = self.build_scan(ctx, variable, label, properties)?;
+                self.finish_scan(ctx, variable, scan)
+            }
             LogicalOperator::Filter { input, predicate } => {
                 self.build_filter(ctx, input, predicate)
             }
             LogicalOperator::Project { input, projections } => {
                 self.build_project(ctx, input, projections)
             }
+            LogicalOperator::Unwind {
+                input,
+                expression,
+                alias,
+            } => self.build_unwind(ctx, input.as_deref(), expression, alias),
+            LogicalOperator::ProcedureCall {
+                input,
+                procedure,
+                arguments,
+                yields,
+            } => self.build_procedure_call(
+                ctx,
+                input.as_deref(),
+                procedure,
+                arguments,
+                yields.as_deref(),
+            ),
+            LogicalOperator::With { input, items } => self.build_with(ctx, input, items),
             LogicalOperator::Distinct { input } => self.build_distinct(ctx, input),
             LogicalOperator::Sort { input, sort_items } => self.build_sort(ctx, input, sort_items),
             LogicalOperator::Limit { input, count } => self.build_limit(ctx, input, count),
@@ -76,10 +110,12 @@ impl DataFusionPlanner {
                 target_variable,
                 relationship_types,
                 direction,
+                properties,
                 min_length,
                 max_length,
                 target_properties,
-                ..
+                path_variable,
+                relationship_variable,
             } => self.build_variable_length_expand(
                 ctx,
                 input,
@@ -89,13 +125,83 @@ impl DataFusionPlanner {
                 direction,
                 *min_length,
                 *max_length,
+                properties,
+                target_properties,
+                path_variable.as_deref(),
+                relationship_variable.as_deref(),
+            ),
+            LogicalOperator::ShortestPath {
+                input,
+                source_variable,
+                target_variable,
+                target_label,
+                relationship_types,
+                direction,
+                path_variable,
+                min_length,
+                max_length,
+                target_properties,
+                mode,
+            } => self.build_shortest_path(
+                ctx,
+                input,
+                source_variable,
+                target_variable,
+                target_label,
+                relationship_types,
+                direction,
+                path_variable.as_deref(),
+                (*min_length, *max_length),
                 target_properties,
+                *mode,
             ),
+            LogicalOperator::BindPath {
+                input,
+                variable,
+                start_variable,
+                steps,
+            } => self.build_bind_path(ctx, input, variable, start_variable, steps),
+            LogicalOperator::DistinctRelationships { input } => {
+                self.build_distinct_relationships(ctx, input)
+            }
             LogicalOperator::Join {
                 left,
                 right,
                 join_type,
             } => self.build_join(ctx, left, right, join_type),
+            LogicalOperator::Union { left, right, all } => self.build_union(left, right, *all),
+            LogicalOperator::SemiJoin {
+                input,
+                pattern,
+                predicate,
+                negated,
+            } => self.build_semi_join(ctx, input, pattern, predicate.as_ref(), *negated),
+            LogicalOperator::PatternCount {
+                input,
+                pattern,
+                predicate,
+                alias,
+            } => self.build_pattern_count(ctx, input, pattern, predicate.as_ref(), alias),
+        }
+    }
+}
+
+/// Whether a filter above `op` may move into the scans below it: `op` keeps or drops
+/// each row of the variables it reads with the row, so filtering them first changes
+/// nothing. Patterns of subqueries are built isolated by their operators.
+fn passes_predicates(op: &LogicalOperator) -> bool {
+    match op {
+        LogicalOperator::ScanByLabel { .. }
+        | LogicalOperator::Filter { .. }
+        | LogicalOperator::Expand { .. }
+        | LogicalOperator::VariableLengthExpand { .. }
+        | LogicalOperator::BindPath { .. }
+        | LogicalOperator::DistinctRelationships { .. }
+        | LogicalOperator::SemiJoin { .. }
+        | LogicalOperator::PatternCount { .. } => true,
+        LogicalOperator::Join { join_type, .. } => {
+            matches!(join_type, JoinType::Inner | JoinType::Cross)
         }
+        _ => false,
     }
 }
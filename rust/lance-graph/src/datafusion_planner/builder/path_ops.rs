@@ -0,0 +1,225 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Named paths
+//!
+//! `p = (a)-[:KNOWS]->(b)-[:KNOWS*1..2]->(c)` binds `p` to a struct of the path's node
+//! ids (`nodes`), the properties of its relationships (`relationships`) and its number
+//! of hops (`length`). Single hops read the id and relationship columns they keep bound;
+//! variable-length hops record the part of the path they took in a struct column of the
+//! same shape, whose `nodes` leave out the hop's source.
+//!
+//! Relationships are structs keyed by property name. A path mixing relationship types
+//! gives each the properties of all of them, NULL where a type lacks one.
+
+use crate::datafusion_planner::analysis::PlanningContext;
+use crate::datafusion_planner::scan_ops::RELATIONSHIP_TYPE_PROPERTY;
+use crate::datafusion_planner::DataFusionPlanner;
+use crate::error::{GraphError, Result};
+use crate::logical_plan::{LogicalOperator, PathStep};
+use arrow_schema::{DataType, Field, Fields};
+use datafusion::common::DFSchema;
+use datafusion::functions::core::expr_fn::{get_field, named_struct};
+use datafusion::functions_nested::expr_fn::{array_concat, make_array};
+use datafusion::logical_expr::{cast, ident, lit, Expr, LogicalPlan, LogicalPlanBuilder};
+use datafusion::scalar::ScalarValue;
+
+impl DataFusionPlanner {
+    /// Add the path struct of a named path to its input rows
+    pub(crate) fn build_bind_path(
+        &self,
+        ctx: &mut PlanningContext,
+        input: &LogicalOperator,
+        variable: &str,
+        start_variable: &str,
+        steps: &[PathStep],
+    ) -> Result<LogicalPlan> {
+        let input_plan = self.build_operator(ctx, input)?;
+        let schema = input_plan.schema().clone();
+
+        let mut fields = Vec::new();
+        for step in steps {
+            match step {
+                PathStep::Hop {
+                    relationship_variable,
+                    ..
+                } => merge_fields(
+                    &mut fields,
+                    relationship_fields(&schema, relationship_variable),
+                ),
+                PathStep::Segment { column } => {
+                    if let Some(segment_fields) = segment_relationship_fields(&schema, column) {
+                        merge_fields(
+                            &mut fields,
+                            segment_fields.iter().map(|f| f.as_ref().clone()).collect(),
+                        );
+                    }
+                }
+            }
+        }
+
+        let mut nodes = vec![make_array(vec![ident(
+            self.node_id_column(ctx, start_variable)?,
+        )])];
+        let mut relationships = Vec::new();
+        let mut hops = 0i64;
+        let mut segment_lengths = Vec::new();
+        for step in steps {
+            match step {
+                PathStep::Hop {
+                    relationship_variable,
+                    target_variable,
+                } => {
+                    nodes.push(make_array(vec![ident(
+                        self.node_id_column(ctx, target_variable)?,
+                    )]));
+                    relationships.push(make_array(vec![relationship_struct(
+                        &schema,
+                        relationship_variable,
+                        &fields,
+                    )]));
+                    hops += 1;
+                }
+                PathStep::Segment { column } => {
+                    let same_fields = segment_relationship_fields(&schema, column).is_some_and(
+                        |segment_fields| {
+                            segment_fields.len() == fields.len()
+                                && segment_fields.iter().zip(&fields).all(|(a, b)| {
+                                    a.name() == b.name() && a.data_type() == b.data_type()
+                                })
+                        },
+                    );
+                    if !same_fields {
+                        return Err(GraphError::UnsupportedFeature {
+                            feature: format!(
+                                "Named path '{}' mixing relationship types with different \
+                                 properties around a variable-length hop",
+                                variable
+                            ),
+                            location: snafu::Location::new(file!(), line!(), column!()),
+                        });
+                    }
+                    nodes.push(get_field(ident(column), "nodes"));
+                    relationships.push(get_field(ident(column), "relationships"));
+                    segment_lengths.push(get_field(ident(column), "length"));
+                }
+            }
+        }
+        let length = segment_lengths
+            .into_iter()
+            .fold(lit(hops), |length, segment| length + segment);
+
+        let mut projection: Vec<Expr> = schema
+            .columns()
+            .into_iter()
+            .filter(|column| {
+                !steps.iter().any(
+                    |step| matches!(step, PathStep::Segment { column: c } if *c == column.name),
+                )
+            })
+            .map(Expr::Column)
+            .collect();
+        projection.push(
+            path_struct(array_concat(nodes), array_concat(relationships), length).alias(variable),
+        );
+        LogicalPlanBuilder::from(input_plan)
+            .project(projection)
+            .and_then(|builder| builder.build())
+            .map_err(|e| self.plan_error("Failed to bind named path", e))
+    }
+
+    /// Id column of a node variable of a path, including the intermediate nodes of
+    /// variable-length hops
+    pub(super) fn node_id_column(&self, ctx: &PlanningContext, variable: &str) -> Result<String> {
+        let (label, _) = self.get_target_node_mapping(ctx, variable)?;
+        self.single_id_column(variable, &label)
+    }
+
+    /// Qualified id column of a node variable; paths track a single id per node
+    pub(super) fn single_id_column(&self, variable: &str, label: &str) -> Result<String> {
+        let node_map =
+            self.config
+                .get_node_mapping(label)
+                .ok_or_else(|| GraphError::ConfigError {
+                    message: format!("No mapping found for node label: {}", label),
+                    location: snafu::Location::new(file!(), line!(), column!()),
+                })?;
+        match node_map.id_fields().as_slice() {
+            [id_field] => Ok(format!("{}__{}", variable, id_field)),
+            _ => Err(GraphError::UnsupportedFeature {
+                feature: format!("Paths over label '{}' with a composite id", label),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            }),
+        }
+    }
+}
+
+/// Fields of the relationship structs recorded in a variable-length segment column
+fn segment_relationship_fields(schema: &DFSchema, column: &str) -> Option<Fields> {
+    let field = schema.field_with_unqualified_name(column).ok()?;
+    let DataType::Struct(path_fields) = field.data_type() else {
+        return None;
+    };
+    let (_, relationships) = path_fields.find("relationships")?;
+    match relationships.data_type() {
+        DataType::List(item) => match item.data_type() {
+            DataType::Struct(fields) => Some(fields.clone()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// The path struct (`nodes`, `relationships`, `length`)
+pub(super) fn path_struct(nodes: Expr, relationships: Expr, length: Expr) -> Expr {
+    named_struct(vec![
+        lit("nodes"),
+        nodes,
+        lit("relationships"),
+        relationships,
+        lit("length"),
+        length,
+    ])
+}
+
+/// Properties of the relationship bound to `alias`, read from its `{alias}__{property}`
+/// columns
+pub(super) fn relationship_fields(schema: &DFSchema, alias: &str) -> Vec<Field> {
+    let prefix = format!("{}__", alias);
+    schema
+        .fields()
+        .iter()
+        .filter_map(|field| {
+            let property = field.name().strip_prefix(&prefix)?;
+            (property != RELATIONSHIP_TYPE_PROPERTY)
+                .then(|| Field::new(property, field.data_type().clone(), true))
+        })
+        .collect()
+}
+
+/// Add the fields not yet in `fields`, keeping the first type seen for each name
+pub(super) fn merge_fields(fields: &mut Vec<Field>, more: Vec<Field>) {
+    for field in more {
+        if !fields.iter().any(|f| f.name() == field.name()) {
+            fields.push(field);
+        }
+    }
+}
+
+/// Struct of the relationship bound to `alias` with the given fields, NULL for those
+/// it does not have
+pub(super) fn relationship_struct(schema: &DFSchema, alias: &str, fields: &[Field]) -> Expr {
+    let args = fields
+        .iter()
+        .flat_map(|field| {
+            let column = format!("{}__{}", alias, field.name());
+            let value = if schema.has_column_with_unqualified_name(&column) {
+                cast(ident(column), field.data_type().clone())
+            } else {
+                lit(ScalarValue::try_from(field.data_type()).unwrap_or(ScalarValue::Null))
+            };
+            [lit(field.name().as_str()), value]
+        })
+        .collect();
+    named_struct(args)
+}
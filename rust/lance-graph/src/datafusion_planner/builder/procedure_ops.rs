@@ -0,0 +1,236 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Procedure calls
+//!
+//! `CALL proc(args) YIELD column AS variable` scans a table whose rows the procedure
+//! returns when the plan runs. Arguments are cast to the parameter types here and
+//! evaluated as constants by the scan; the yielded columns are renamed to their
+//! variables and cross joined with the rows of the preceding clauses. Maps passed to
+//! struct parameters, such as `{maxIterations: 10}`, fill the fields of their keys and
+//! leave the others null.
+
+use crate::ast::{PropertyValue, ValueExpression};
+use crate::datafusion_planner::analysis::PlanningContext;
+use crate::datafusion_planner::expression::to_df_value_expr;
+use crate::datafusion_planner::DataFusionPlanner;
+use crate::error::{GraphError, Result};
+use crate::logical_plan::LogicalOperator;
+use crate::procedures::{ProcedureContext, ProcedureTable};
+use arrow_schema::{DataType, Fields};
+use datafusion::common::scalar::ScalarStructBuilder;
+use datafusion::common::{Column, TableReference};
+use datafusion::datasource::{provider_as_source, TableProvider};
+use datafusion::functions::core::expr_fn::named_struct;
+use datafusion::logical_expr::{cast, lit, Expr, LogicalPlan, LogicalPlanBuilder};
+use datafusion::scalar::ScalarValue;
+use std::sync::Arc;
+
+impl DataFusionPlanner {
+    /// Plan the rows of a procedure call, joined to its input rows
+    pub(crate) fn build_procedure_call(
+        &self,
+        ctx: &mut PlanningContext,
+        input: Option<&LogicalOperator>,
+        procedure: &str,
+        arguments: &[ValueExpression],
+        yields: Option<&[(String, String)]>,
+    ) -> Result<LogicalPlan> {
+        let proc = self
+            .procedures
+            .get(procedure)
+            .ok_or_else(|| GraphError::PlanError {
+                message: format!("Unknown procedure '{}'", procedure),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            })?
+            .clone();
+
+        // Parameters with a default may only be left out at the end
+        let parameters = proc.parameters();
+        let required = parameters
+            .iter()
+            .rposition(|p| p.default.is_none())
+            .map_or(0, |i| i + 1);
+        if arguments.len() < required || arguments.len() > parameters.len() {
+            let expected = if required == parameters.len() {
+                required.to_string()
+            } else {
+                format!("{} to {}", required, parameters.len())
+            };
+            return Err(GraphError::PlanError {
+                message: format!(
+                    "Procedure '{}' takes {} argument(s), got {}",
+                    proc.name(),
+                    expected,
+                    arguments.len()
+                ),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            });
+        }
+        let args: Vec<Expr> =
+            arguments
+                .iter()
+                .zip(&parameters)
+                .map(
+                    |(argument, parameter)| match (argument, &parameter.data_type) {
+                        (ValueExpression::Map(entries), DataType::Struct(fields)) => {
+                            options_argument(proc.name(), &parameter.name, entries, fields)
+                        }
+                        _ => Ok(cast(
+                            to_df_value_expr(argument),
+                            parameter.data_type.clone(),
+                        )),
+                    },
+                )
+                .chain(parameters[arguments.len()..].iter().map(|parameter| {
+                    Ok(lit(parameter.default.clone().unwrap_or(ScalarValue::Null)))
+                }))
+                .collect::<Result<_>>()?;
+
+        // Literal arguments are known while planning, for procedures whose columns
+        // depend on them
+        let known: Vec<Option<ScalarValue>> = parameters
+            .iter()
+            .enumerate()
+            .map(|(i, parameter)| match arguments.get(i) {
+                Some(argument) => known_argument(argument, &parameter.data_type),
+                None => parameter.default.clone(),
+            })
+            .collect();
+
+        let context = ProcedureContext::new(self.config.clone(), self.catalog.clone())
+            .with_projections(self.procedures.projections());
+        let table = ProcedureTable::try_new(proc.clone(), context, args, &known)?;
+        let schema = table.schema();
+        let table_name = TableReference::bare(proc.name());
+        let column = |name: &str| Expr::Column(Column::new(Some(table_name.clone()), name));
+
+        let outputs: Vec<Expr> = match yields {
+            Some(yields) => yields
+                .iter()
+                .map(|(name, variable)| {
+                    if schema.column_with_name(name).is_none() {
+                        return Err(GraphError::PlanError {
+                            message: format!(
+                                "Procedure '{}' yields no column '{}'",
+                                proc.name(),
+                                name
+                            ),
+                            location: snafu::Location::new(file!(), line!(), column!()),
+                        });
+                    }
+                    Ok(column(name).alias(variable))
+                })
+                .collect::<Result<_>>()?,
+            None => schema
+                .fields()
+                .iter()
+                .map(|field| column(field.name()).alias(field.name()))
+                .collect(),
+        };
+
+        let call_plan = LogicalPlanBuilder::scan(
+            table_name.clone(),
+            provider_as_source(Arc::new(table)),
+            None,
+        )
+        .and_then(|builder| builder.project(outputs))
+        .and_then(|builder| builder.build())
+        .map_err(|e| self.plan_error("Failed to plan procedure call", e))?;
+        match input {
+            None => Ok(call_plan),
+            Some(input) => {
+                let input_plan = self.build_operator(ctx, input)?;
+                LogicalPlanBuilder::from(input_plan)
+                    .cross_join(call_plan)
+                    .and_then(|builder| builder.build())
+                    .map_err(|e| self.plan_error("Failed to join procedure call", e))
+            }
+        }
+    }
+}
+
+/// A map argument to a struct parameter, such as a map of options: each field takes
+/// the entry of its name, cast to its type, or null when the map has none
+fn options_argument(
+    procedure: &str,
+    parameter: &str,
+    entries: &[(String, ValueExpression)],
+    fields: &Fields,
+) -> Result<Expr> {
+    if let Some((key, _)) = entries.iter().find(|(key, _)| fields.find(key).is_none()) {
+        return Err(GraphError::PlanError {
+            message: format!(
+                "Procedure '{}' has no option '{}' in '{}'; expected one of {}",
+                procedure,
+                key,
+                parameter,
+                fields
+                    .iter()
+                    .map(|field| field.name().as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            location: snafu::Location::new(file!(), line!(), column!()),
+        });
+    }
+    let mut values = Vec::new();
+    for field in fields {
+        let value = match entries.iter().find(|(key, _)| key == field.name()) {
+            Some((_, value)) => cast(to_df_value_expr(value), field.data_type().clone()),
+            None => lit(ScalarValue::try_from(field.data_type())?),
+        };
+        values.extend([lit(field.name().as_str()), value]);
+    }
+    Ok(cast(named_struct(values), DataType::Struct(fields.clone())))
+}
+
+/// The value of an argument written as a literal, lists and maps of literals included,
+/// cast to its parameter's type
+fn known_argument(argument: &ValueExpression, data_type: &DataType) -> Option<ScalarValue> {
+    let items: Vec<ScalarValue> = match argument {
+        ValueExpression::Literal(PropertyValue::List(items)) => {
+            items.iter().map(literal_scalar).collect::<Option<_>>()?
+        }
+        ValueExpression::Map(entries) => {
+            let DataType::Struct(fields) = data_type else {
+                return None;
+            };
+            let mut options = ScalarStructBuilder::new();
+            for field in fields {
+                let value = match entries.iter().find(|(key, _)| key == field.name()) {
+                    Some((_, value)) => known_argument(value, field.data_type())?,
+                    None => ScalarValue::try_from(field.data_type()).ok()?,
+                };
+                options = options.with_scalar(field.clone(), value);
+            }
+            return options.build().ok();
+        }
+        // A single value is not taken for a list of one
+        ValueExpression::Literal(_) if matches!(data_type, DataType::List(_)) => return None,
+        ValueExpression::Literal(value) => return literal_scalar(value)?.cast_to(data_type).ok(),
+        _ => return None,
+    };
+    let DataType::List(item) = data_type else {
+        return None;
+    };
+    let items = items
+        .iter()
+        .map(|value| value.cast_to(item.data_type()))
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .ok()?;
+    Some(ScalarValue::List(ScalarValue::new_list_nullable(
+        &items,
+        item.data_type(),
+    )))
+}
+
+fn literal_scalar(value: &PropertyValue) -> Option<ScalarValue> {
+    match value {
+        PropertyValue::String(s) => Some(ScalarValue::from(s.as_str())),
+        PropertyValue::Integer(i) => Some(ScalarValue::from(*i)),
+        PropertyValue::Float(f) => Some(ScalarValue::from(*f)),
+        PropertyValue::Boolean(b) => Some(ScalarValue::from(*b)),
+        _ => None,
+    }
+}
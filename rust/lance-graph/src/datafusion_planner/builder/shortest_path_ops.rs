@@ -0,0 +1,375 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Shortest path search
+//!
+//! `shortestPath((a)-[:ROAD*]->(b))` is planned as a recursive query: the static term
+//! takes one hop from every distinct source node, and each iteration extends the
+//! frontier by one more hop, never revisiting a node already on the path. Expansion
+//! stops at the maximum length or when the frontier is empty. The resulting paths are
+//! ranked per (source, target) pair and only those of minimal length are kept.
+//!
+//! The path variable is exposed as a struct column with `nodes` (the node ids from
+//! source to target), `relationships` (the properties of the relationships taken) and
+//! `length` (the number of hops), the same shape as other named paths.
+
+use super::path_ops::{merge_fields, path_struct, relationship_fields, relationship_struct};
+use crate::ast::{PropertyValue, RelationshipDirection, ShortestPathMode, ValueExpression};
+use crate::datafusion_planner::analysis::{PlanningContext, RelationshipInstance};
+use crate::datafusion_planner::expression::to_df_value_expr;
+use crate::datafusion_planner::DataFusionPlanner;
+use crate::error::{GraphError, Result};
+use crate::logical_plan::LogicalOperator;
+use datafusion::datasource::cte_worktable::CteWorkTable;
+use datafusion::datasource::provider_as_source;
+use datafusion::functions_nested::expr_fn::{array_append, array_has, make_array};
+use datafusion::functions_window::expr_fn::row_number;
+use datafusion::logical_expr::{
+    col, ident, lit, not, Expr, ExprFunctionExt, JoinType, LogicalPlan, LogicalPlanBuilder,
+};
+use datafusion_functions_aggregate::min_max::min;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+const START: &str = "__sp_start";
+const NODE: &str = "__sp_node";
+const LENGTH: &str = "__sp_length";
+const NODES: &str = "__sp_nodes";
+const REL: &str = "__sp_rel";
+const RELS: &str = "__sp_rels";
+const FROM: &str = "__sp_from";
+const TO: &str = "__sp_to";
+const FRONTIER: &str = "__sp_frontier";
+const SHORTEST: &str = "__sp_shortest";
+const RANK: &str = "__sp_rank";
+const SHORTEST_START: &str = "__sp_shortest_start";
+const SHORTEST_NODE: &str = "__sp_shortest_node";
+
+impl DataFusionPlanner {
+    /// Build a shortest path search between the source and target variables
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn build_shortest_path(
+        &self,
+        ctx: &mut PlanningContext,
+        input: &LogicalOperator,
+        source_variable: &str,
+        target_variable: &str,
+        target_label: &str,
+        relationship_types: &[String],
+        direction: &RelationshipDirection,
+        path_variable: Option<&str>,
+        (min_length, max_length): (Option<u32>, Option<u32>),
+        target_properties: &HashMap<String, PropertyValue>,
+        mode: ShortestPathMode,
+    ) -> Result<LogicalPlan> {
+        let hop_limit = self.config.max_variable_length_hops;
+        let min_hops = min_length.unwrap_or(1).max(1);
+        let max_hops = max_length.unwrap_or(hop_limit);
+        if min_hops > max_hops {
+            return Err(GraphError::InvalidPattern {
+                message: format!(
+                    "Invalid shortest path length range: min {} > max {}",
+                    min_hops, max_hops
+                ),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            });
+        }
+        if max_hops > hop_limit {
+            return Err(GraphError::UnsupportedFeature {
+                feature: format!(
+                    "Shortest paths with max length > {} (got {})",
+                    hop_limit, max_hops
+                ),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            });
+        }
+
+        let input_plan = self.build_operator(ctx, input)?;
+        let source_label = ctx
+            .analysis
+            .var_to_label
+            .get(source_variable)
+            .ok_or_else(|| GraphError::PlanError {
+                message: format!(
+                    "Unknown label for shortest path source '{}'",
+                    source_variable
+                ),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            })?;
+        let source_key = self.single_id_column(source_variable, source_label)?;
+        let target_key = self.single_id_column(target_variable, target_label)?;
+
+        let edges = self.build_edge_list(relationship_types, direction)?;
+        let paths = self.build_path_search(&input_plan, &source_key, edges, max_hops)?;
+        let paths = self.keep_shortest_paths(paths, min_hops, mode)?;
+
+        // Attach the endpoints: a target bound earlier joins on both ends, otherwise the
+        // reached nodes are looked up in the target label's table
+        let target_bound = input_plan
+            .schema()
+            .has_column_with_unqualified_name(&target_key);
+        let mut builder = if target_bound {
+            let mut builder = LogicalPlanBuilder::from(input_plan)
+                .join(
+                    paths,
+                    JoinType::Inner,
+                    (vec![source_key, target_key], vec![START, NODE]),
+                    None,
+                )
+                .map_err(|e| self.plan_error("Failed to join shortest paths", e))?;
+            for (property, value) in target_properties {
+                let column = ident(format!("{}__{}", target_variable, property));
+                let value = to_df_value_expr(&ValueExpression::Literal(value.clone()));
+                builder = builder
+                    .filter(column.eq(value))
+                    .map_err(|e| self.plan_error("Failed to filter shortest path target", e))?;
+            }
+            builder
+        } else {
+            let target_scan = self.build_qualified_target_scan(
+                self.get_catalog()?,
+                target_label,
+                target_variable,
+                target_properties,
+            )?;
+            let reached = LogicalPlanBuilder::from(target_scan)
+                .join(paths, JoinType::Inner, (vec![target_key], vec![NODE]), None)
+                .and_then(|builder| builder.build())
+                .map_err(|e| self.plan_error("Failed to join shortest path targets", e))?;
+            LogicalPlanBuilder::from(input_plan)
+                .join(
+                    reached,
+                    JoinType::Inner,
+                    (vec![source_key], vec![START]),
+                    None,
+                )
+                .map_err(|e| self.plan_error("Failed to join shortest paths", e))?
+        };
+
+        let schema = builder.schema().clone();
+        let mut projection: Vec<Expr> = schema
+            .columns()
+            .into_iter()
+            .filter(|column| !column.name.starts_with("__sp_"))
+            .map(Expr::Column)
+            .collect();
+        if let Some(path_variable) = path_variable {
+            projection.push(path_struct(col(NODES), col(RELS), col(LENGTH)).alias(path_variable));
+        }
+        builder = builder
+            .project(projection)
+            .map_err(|e| self.plan_error("Failed to project shortest paths", e))?;
+        builder
+            .build()
+            .map_err(|e| self.plan_error("Failed to build shortest path plan", e))
+    }
+
+    /// Edge list (`__sp_from`, `__sp_to`, `__sp_rel`) over every traversable relationship
+    /// type and direction
+    fn build_edge_list(
+        &self,
+        relationship_types: &[String],
+        direction: &RelationshipDirection,
+    ) -> Result<LogicalPlan> {
+        if relationship_types.is_empty() {
+            return Err(GraphError::InvalidPattern {
+                message: "shortestPath requires at least one relationship type".to_string(),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            });
+        }
+        let catalog = self.get_catalog()?;
+
+        let mut scans = Vec::new();
+        let mut fields = Vec::new();
+        for (i, rel_type) in relationship_types.iter().enumerate() {
+            let rel_map = self.get_relationship_mapping(rel_type)?;
+            let (source_fields, target_fields) =
+                (rel_map.source_id_fields(), rel_map.target_id_fields());
+            let (source_field, target_field) =
+                match (source_fields.as_slice(), target_fields.as_slice()) {
+                    ([source_field], [target_field]) => (*source_field, *target_field),
+                    _ => {
+                        return Err(GraphError::UnsupportedFeature {
+                            feature: format!(
+                                "Shortest paths over relationship '{}' with composite keys",
+                                rel_type
+                            ),
+                            location: snafu::Location::new(file!(), line!(), column!()),
+                        })
+                    }
+                };
+
+            let instance = RelationshipInstance {
+                id: i,
+                rel_type: rel_type.clone(),
+                source_var: FROM.to_string(),
+                target_var: TO.to_string(),
+                direction: direction.clone(),
+                alias: format!("__sp_rel_{}", i),
+            };
+            let rel_scan =
+                self.build_qualified_relationship_scan(catalog, &instance, &HashMap::new())?;
+            merge_fields(
+                &mut fields,
+                relationship_fields(rel_scan.schema(), &instance.alias),
+            );
+            let source_column =
+                self.qualified_relationship_column(&instance.alias, rel_type, source_field);
+            let target_column =
+                self.qualified_relationship_column(&instance.alias, rel_type, target_field);
+            scans.push((
+                rel_scan,
+                instance.alias,
+                rel_map,
+                source_column,
+                target_column,
+            ));
+        }
+
+        // Relationships of every type share the struct of all their properties
+        let mut branches = Vec::new();
+        for (rel_scan, alias, rel_map, source_column, target_column) in scans {
+            let rel = relationship_struct(rel_scan.schema(), &alias, &fields).alias(REL);
+            for direction in Self::traversal_directions(direction, rel_map) {
+                let (from, to) = match direction {
+                    RelationshipDirection::Incoming => (&target_column, &source_column),
+                    _ => (&source_column, &target_column),
+                };
+                branches.push(
+                    LogicalPlanBuilder::from(rel_scan.clone())
+                        .project(vec![
+                            ident(from.as_str()).alias(FROM),
+                            ident(to.as_str()).alias(TO),
+                            rel.clone(),
+                        ])
+                        .and_then(|builder| builder.build())
+                        .map_err(|e| self.plan_error("Failed to project edge list", e))?,
+                );
+            }
+        }
+        self.union_branches(branches)
+    }
+
+    /// Recursive query producing every simple path (`__sp_start`, `__sp_node`,
+    /// `__sp_length`, `__sp_nodes`, `__sp_rels`) of at most `max_hops` hops from the input's sources
+    fn build_path_search(
+        &self,
+        input_plan: &LogicalPlan,
+        source_key: &str,
+        edges: LogicalPlan,
+        max_hops: u32,
+    ) -> Result<LogicalPlan> {
+        let starts = LogicalPlanBuilder::from(input_plan.clone())
+            .project(vec![ident(source_key).alias(START)])
+            .and_then(|builder| builder.distinct())
+            .and_then(|builder| builder.build())
+            .map_err(|e| self.plan_error("Failed to collect shortest path sources", e))?;
+
+        let first_hop = LogicalPlanBuilder::from(starts)
+            .join(
+                edges.clone(),
+                JoinType::Inner,
+                (vec![START], vec![FROM]),
+                None,
+            )
+            .and_then(|builder| {
+                builder.project(vec![
+                    col(START),
+                    col(TO).alias(NODE),
+                    lit(1i64).alias(LENGTH),
+                    make_array(vec![col(START), col(TO)]).alias(NODES),
+                    make_array(vec![col(REL)]).alias(RELS),
+                ])
+            })
+            .and_then(|builder| builder.build())
+            .map_err(|e| self.plan_error("Failed to plan first shortest path hop", e))?;
+
+        let frontier = CteWorkTable::new(FRONTIER, Arc::new(first_hop.schema().as_arrow().clone()));
+        let next_hop =
+            LogicalPlanBuilder::scan(FRONTIER, provider_as_source(Arc::new(frontier)), None)
+                .and_then(|builder| {
+                    builder.join(edges, JoinType::Inner, (vec![NODE], vec![FROM]), None)
+                })
+                .and_then(|builder| {
+                    builder.filter(
+                        col(LENGTH)
+                            .lt(lit(i64::from(max_hops)))
+                            .and(not(array_has(col(NODES), col(TO)))),
+                    )
+                })
+                .and_then(|builder| {
+                    builder.project(vec![
+                        col(START),
+                        col(TO).alias(NODE),
+                        (col(LENGTH) + lit(1i64)).alias(LENGTH),
+                        array_append(col(NODES), col(TO)).alias(NODES),
+                        array_append(col(RELS), col(REL)).alias(RELS),
+                    ])
+                })
+                .and_then(|builder| builder.build())
+                .map_err(|e| self.plan_error("Failed to plan shortest path expansion", e))?;
+
+        LogicalPlanBuilder::from(first_hop)
+            .to_recursive_query(FRONTIER.to_string(), next_hop, false)
+            .and_then(|builder| builder.build())
+            .map_err(|e| self.plan_error("Failed to build shortest path search", e))
+    }
+
+    /// Keep the minimal-length paths per (source, target) pair
+    fn keep_shortest_paths(
+        &self,
+        paths: LogicalPlan,
+        min_hops: u32,
+        mode: ShortestPathMode,
+    ) -> Result<LogicalPlan> {
+        let mut builder = LogicalPlanBuilder::from(paths);
+        if min_hops > 1 {
+            builder = builder
+                .filter(col(LENGTH).gt_eq(lit(i64::from(min_hops))))
+                .map_err(|e| self.plan_error("Failed to apply minimum path length", e))?;
+        }
+
+        match mode {
+            ShortestPathMode::Single => {
+                let rank = row_number()
+                    .partition_by(vec![col(START), col(NODE)])
+                    .order_by(vec![col(LENGTH).sort(true, false)])
+                    .build()
+                    .map_err(|e| self.plan_error("Failed to rank shortest paths", e))?
+                    .alias(RANK);
+                builder
+                    .window(vec![rank])
+                    .and_then(|builder| builder.filter(col(RANK).eq(lit(1u64))))
+                    .and_then(|builder| builder.build())
+                    .map_err(|e| self.plan_error("Failed to select shortest paths", e))
+            }
+            ShortestPathMode::All => {
+                let paths = builder
+                    .build()
+                    .map_err(|e| self.plan_error("Failed to build shortest paths", e))?;
+                let shortest = LogicalPlanBuilder::from(paths.clone())
+                    .aggregate(
+                        vec![
+                            col(START).alias(SHORTEST_START),
+                            col(NODE).alias(SHORTEST_NODE),
+                        ],
+                        vec![min(col(LENGTH)).alias(SHORTEST)],
+                    )
+                    .and_then(|builder| builder.build())
+                    .map_err(|e| self.plan_error("Failed to compute shortest lengths", e))?;
+                LogicalPlanBuilder::from(paths)
+                    .join(
+                        shortest,
+                        JoinType::Inner,
+                        (
+                            vec![START, NODE, LENGTH],
+                            vec![SHORTEST_START, SHORTEST_NODE, SHORTEST],
+                        ),
+                        None,
+                    )
+                    .and_then(|builder| builder.build())
+                    .map_err(|e| self.plan_error("Failed to select shortest paths", e))
+            }
+        }
+    }
+}
@@ -0,0 +1,119 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Relationship uniqueness within a MATCH clause
+//!
+//! Under [`RelationshipUniqueness::Isomorphism`](crate::config::RelationshipUniqueness)
+//! each MATCH clause is wrapped in a `DistinctRelationships` operator. Its expansions
+//! record the relationships they bind, and the rows binding one relationship under two
+//! aliases are filtered out once the clause is built. Relationships are compared by
+//! type and endpoint keys.
+
+use crate::datafusion_planner::analysis::{BoundRelationship, PlanningContext};
+use crate::datafusion_planner::scan_ops::RELATIONSHIP_TYPE_PROPERTY;
+use crate::datafusion_planner::DataFusionPlanner;
+use crate::error::Result;
+use crate::logical_plan::LogicalOperator;
+use datafusion::common::DFSchema;
+use datafusion::logical_expr::utils::conjunction;
+use datafusion::logical_expr::{
+    binary_expr, ident, not, Expr, LogicalPlan, LogicalPlanBuilder, Operator,
+};
+
+impl DataFusionPlanner {
+    /// Build the patterns of a MATCH clause, keeping the rows in which its relationships
+    /// are pairwise distinct
+    pub(crate) fn build_distinct_relationships(
+        &self,
+        ctx: &mut PlanningContext,
+        input: &LogicalOperator,
+    ) -> Result<LogicalPlan> {
+        ctx.relationship_scopes.push(Vec::new());
+        let plan = self.build_operator(ctx, input);
+        let bound = ctx.relationship_scopes.pop().unwrap_or_default();
+        self.filter_repeated_relationships(plan?, &bound, &[])
+    }
+
+    /// Drop the rows of `plan` in which two of `relationships`, or one of them and one of
+    /// `earlier`, are the same relationship
+    ///
+    /// Pairs whose columns are not all in the plan are left unchecked.
+    pub(crate) fn filter_repeated_relationships(
+        &self,
+        plan: LogicalPlan,
+        relationships: &[BoundRelationship],
+        earlier: &[BoundRelationship],
+    ) -> Result<LogicalPlan> {
+        let schema = plan.schema().clone();
+        let mut distinct = Vec::new();
+        for (i, relationship) in relationships.iter().enumerate() {
+            for other in relationships[..i].iter().chain(earlier) {
+                if let Some(same) = self.same_relationship(&schema, relationship, other) {
+                    distinct.push(not(same));
+                }
+            }
+        }
+        let Some(predicate) = conjunction(distinct) else {
+            return Ok(plan);
+        };
+        LogicalPlanBuilder::from(plan)
+            .filter(predicate)
+            .and_then(|builder| builder.build())
+            .map_err(|e| self.plan_error("Failed to filter repeated relationships", e))
+    }
+
+    /// Predicate holding when `left` and `right` bind the same relationship, or `None`
+    /// when they never can or their keys are not in `schema`
+    fn same_relationship(
+        &self,
+        schema: &DFSchema,
+        left: &BoundRelationship,
+        right: &BoundRelationship,
+    ) -> Option<Expr> {
+        if left.alias == right.alias {
+            return None;
+        }
+        let mut properties = Vec::new();
+        for rel_type in left
+            .rel_types
+            .iter()
+            .filter(|t| right.rel_types.contains(t))
+        {
+            let mapping = self.config.get_relationship_mapping(rel_type)?;
+            for key in mapping
+                .source_id_fields()
+                .into_iter()
+                .chain(mapping.target_id_fields())
+            {
+                let property = mapping.property_for(key);
+                if !properties.contains(&property) {
+                    properties.push(property);
+                }
+            }
+        }
+        if properties.is_empty() {
+            return None;
+        }
+        properties.push(RELATIONSHIP_TYPE_PROPERTY);
+
+        let column = |alias: &str, property: &str| {
+            let name = format!("{}__{}", alias, property);
+            schema
+                .has_column_with_unqualified_name(&name)
+                .then(|| ident(name))
+        };
+        let mut same = Vec::new();
+        for property in properties {
+            match (
+                column(&left.alias, property),
+                column(&right.alias, property),
+            ) {
+                (Some(l), Some(r)) => same.push(binary_expr(l, Operator::IsNotDistinctFrom, r)),
+                // Not every relationship scan projects its type
+                _ if property == RELATIONSHIP_TYPE_PROPERTY => {}
+                _ => return None,
+            }
+        }
+        conjunction(same)
+    }
+}
@@ -5,8 +5,12 @@
 //!
 //! Converts AST expressions to DataFusion expressions
 
+use super::scan_ops;
 use crate::ast::{BooleanExpression, PropertyValue, ValueExpression};
-use datafusion::logical_expr::{col, lit, BinaryExpr, Expr, Operator};
+use datafusion::functions::string::expr_fn as string_fn;
+use datafusion::functions::unicode::expr_fn as unicode_fn;
+use datafusion::logical_expr::{col, ident, lit, BinaryExpr, Expr, ExprFunctionExt, Operator};
+use datafusion_functions_aggregate::array_agg::array_agg;
 use datafusion_functions_aggregate::average::avg;
 use datafusion_functions_aggregate::count::count;
 use datafusion_functions_aggregate::min_max::max;
@@ -38,6 +42,25 @@ pub(crate) fn to_df_boolean_expr(expr: &BooleanExpression) -> Expr {
                 right: Box::new(r),
             })
         }
+        BE::StringMatch {
+            expression,
+            operator,
+            pattern,
+        } => {
+            use crate::ast::StringOperator as SO;
+            let expression = to_df_value_expr(expression);
+            let pattern = to_df_value_expr(pattern);
+            match operator {
+                SO::StartsWith => string_fn::starts_with(expression, pattern),
+                SO::EndsWith => string_fn::ends_with(expression, pattern),
+                SO::Contains => string_fn::contains(expression, pattern),
+                SO::Regex => datafusion::functions::regex::expr_fn::regexp_like(
+                    expression,
+                    anchored_regex(pattern),
+                    None,
+                ),
+            }
+        }
         BE::In { expression, list } => {
             use datafusion::logical_expr::expr::InList as DFInList;
             let expr = to_df_value_expr(expression);
@@ -55,6 +78,10 @@ pub(crate) fn to_df_boolean_expr(expr: &BooleanExpression) -> Expr {
             right: Box::new(to_df_boolean_expr(r)),
         }),
         BE::Not(inner) => Expr::Not(Box::new(to_df_boolean_expr(inner))),
+        BE::FullTextSearch { expression, query } => super::full_text::full_text_search(
+            to_df_value_expr(expression),
+            to_df_value_expr(query),
+        ),
         BE::Exists(prop) => Expr::IsNotNull(Box::new(to_df_value_expr(
             &ValueExpression::Property(prop.clone()),
         ))),
@@ -69,11 +96,10 @@ pub(crate) fn to_df_value_expr(expr: &ValueExpression) -> Expr {
     use crate::ast::{PropertyValue as PV, ValueExpression as VE};
     match expr {
         VE::Property(prop) => {
-            // Create qualified column name: variable__property
-            let qualified_name = format!("{}__{}", prop.variable, prop.property);
-            col(&qualified_name)
+            // Create qualified column name: variable__property (kept case-sensitive)
+            ident(format!("{}__{}", prop.variable, prop.property))
         }
-        VE::Variable(v) => col(v),
+        VE::Variable(v) => ident(v),
         VE::Literal(PV::String(s)) => lit(s.clone()),
         VE::Literal(PV::Integer(i)) => lit(*i),
         VE::Literal(PV::Float(f)) => lit(*f),
@@ -81,14 +107,33 @@ pub(crate) fn to_df_value_expr(expr: &ValueExpression) -> Expr {
         VE::Literal(PV::Null) => {
             datafusion::logical_expr::Expr::Literal(datafusion::scalar::ScalarValue::Null, None)
         }
-        VE::Literal(PV::Parameter(_)) => lit(0),
+        VE::Literal(PV::Parameter(name)) => Expr::Placeholder(
+            datafusion::logical_expr::expr::Placeholder::new(format!("${}", name), None),
+        ),
+        VE::Literal(PV::Variable(v)) => ident(v),
+        VE::Literal(PV::List(items)) => datafusion::functions_nested::expr_fn::make_array(
+            items
+                .iter()
+                .map(|item| to_df_value_expr(&VE::Literal(item.clone())))
+                .collect(),
+        ),
         VE::Literal(PV::Property(prop)) => {
-            // Create qualified column name: variable__property
-            let qualified_name = format!("{}__{}", prop.variable, prop.property);
-            col(&qualified_name)
+            // Create qualified column name: variable__property (kept case-sensitive)
+            ident(format!("{}__{}", prop.variable, prop.property))
         }
-        VE::Function { name, args } => {
+        VE::Function {
+            name,
+            distinct,
+            args,
+        } => {
             // Handle aggregation functions
+            let aggregate = |expr: Expr| {
+                if *distinct {
+                    expr.clone().distinct().build().unwrap_or(expr)
+                } else {
+                    expr
+                }
+            };
             match name.to_lowercase().as_str() {
                 "count" => {
                     if args.len() == 1 {
@@ -108,7 +153,7 @@ pub(crate) fn to_df_value_expr(expr: &ValueExpression) -> Expr {
                         };
 
                         // Use DataFusion's count helper function
-                        count(arg_expr)
+                        aggregate(count(arg_expr))
                     } else {
                         // Invalid argument count - return placeholder
                         lit(0)
@@ -119,7 +164,7 @@ pub(crate) fn to_df_value_expr(expr: &ValueExpression) -> Expr {
                         // Note: SUM(variable) is rejected by semantic validation
                         // So we only handle valid cases here
                         let arg_expr = to_df_value_expr(&args[0]);
-                        sum(arg_expr)
+                        aggregate(sum(arg_expr))
                     } else {
                         // Invalid argument count - return placeholder
                         lit(0)
@@ -128,7 +173,7 @@ pub(crate) fn to_df_value_expr(expr: &ValueExpression) -> Expr {
                 "avg" => {
                     if args.len() == 1 {
                         let arg_expr = to_df_value_expr(&args[0]);
-                        avg(arg_expr)
+                        aggregate(avg(arg_expr))
                     } else {
                         lit(0)
                     }
@@ -136,7 +181,7 @@ pub(crate) fn to_df_value_expr(expr: &ValueExpression) -> Expr {
                 "min" => {
                     if args.len() == 1 {
                         let arg_expr = to_df_value_expr(&args[0]);
-                        min(arg_expr)
+                        aggregate(min(arg_expr))
                     } else {
                         lit(0)
                     }
@@ -144,39 +189,247 @@ pub(crate) fn to_df_value_expr(expr: &ValueExpression) -> Expr {
                 "max" => {
                     if args.len() == 1 {
                         let arg_expr = to_df_value_expr(&args[0]);
-                        max(arg_expr)
+                        aggregate(max(arg_expr))
                     } else {
                         lit(0)
                     }
                 }
+                // collect() gathers the non-null values into a list
+                "collect" => match args.as_slice() {
+                    [arg] => {
+                        let arg_expr = to_df_value_expr(arg);
+                        let collected = array_agg(arg_expr.clone()).filter(arg_expr.is_not_null());
+                        let collected = if *distinct {
+                            collected.distinct()
+                        } else {
+                            collected
+                        };
+                        collected.build().unwrap_or_else(|_| lit(0))
+                    }
+                    _ => lit(0),
+                },
+                // Path functions read the fields of the path struct (see BindPath)
+                "length" | "nodes" | "relationships" => match args.as_slice() {
+                    [VE::Variable(path)] => datafusion::functions::core::expr_fn::get_field(
+                        ident(path),
+                        name.to_lowercase(),
+                    ),
+                    _ => lit(0),
+                },
+                // Entity functions read the pseudo-columns every scan adds (see scan_ops)
+                "type" | "labels" | "id" | "elementid" => match args.as_slice() {
+                    [VE::Variable(variable)] => {
+                        let property = match name.to_lowercase().as_str() {
+                            "type" => scan_ops::RELATIONSHIP_TYPE_PROPERTY,
+                            "labels" => scan_ops::NODE_LABELS_PROPERTY,
+                            "id" => scan_ops::NODE_ID_PROPERTY,
+                            _ => scan_ops::NODE_ELEMENT_ID_PROPERTY,
+                        };
+                        ident(format!("{}__{}", variable, property))
+                    }
+                    _ => lit(0),
+                },
+                "toupper" | "upper" => match args.as_slice() {
+                    [arg] => string_fn::upper(to_df_value_expr(arg)),
+                    _ => lit(0),
+                },
+                "tolower" | "lower" => match args.as_slice() {
+                    [arg] => string_fn::lower(to_df_value_expr(arg)),
+                    _ => lit(0),
+                },
+                "trim" | "ltrim" | "rtrim" => match args.as_slice() {
+                    [arg] => {
+                        let trim = match name.to_lowercase().as_str() {
+                            "ltrim" => string_fn::ltrim,
+                            "rtrim" => string_fn::rtrim,
+                            _ => string_fn::btrim,
+                        };
+                        trim(vec![to_df_value_expr(arg)])
+                    }
+                    _ => lit(0),
+                },
+                "replace" => match args.as_slice() {
+                    [string, search, replacement] => string_fn::replace(
+                        to_df_value_expr(string),
+                        to_df_value_expr(search),
+                        to_df_value_expr(replacement),
+                    ),
+                    _ => lit(0),
+                },
+                // Cypher counts characters from 0, DataFusion from 1
+                "substring" => match args.as_slice() {
+                    [string, start] => {
+                        unicode_fn::substr(to_df_value_expr(string), to_one_based_index(start, 1))
+                    }
+                    [string, start, length] => unicode_fn::substring(
+                        to_df_value_expr(string),
+                        to_one_based_index(start, 1),
+                        to_df_value_expr(length),
+                    ),
+                    _ => lit(0),
+                },
+                "left" => match args.as_slice() {
+                    [string, length] => {
+                        unicode_fn::left(to_df_value_expr(string), to_df_value_expr(length))
+                    }
+                    _ => lit(0),
+                },
+                "right" => match args.as_slice() {
+                    [string, length] => {
+                        unicode_fn::right(to_df_value_expr(string), to_df_value_expr(length))
+                    }
+                    _ => lit(0),
+                },
+                "date" | "localdatetime" | "datetime" | "duration" | "duration.between" => {
+                    super::temporal::temporal_function(&name.to_lowercase(), args)
+                        .unwrap_or_else(|| lit(0))
+                }
+                "coalesce" => datafusion::functions::core::expr_fn::coalesce(
+                    args.iter().map(to_df_value_expr).collect(),
+                ),
+                "nullif" => match args.as_slice() {
+                    [value, other] => datafusion::functions::core::expr_fn::nullif(
+                        to_df_value_expr(value),
+                        to_df_value_expr(other),
+                    ),
+                    _ => lit(0),
+                },
+                "abs" | "sign" | "ceil" | "floor" | "round" | "sqrt" | "exp" | "log" | "log10"
+                | "rand" | "tointeger" | "tofloat" => {
+                    super::numeric::numeric_function(&name.to_lowercase(), args)
+                        .unwrap_or_else(|| lit(0))
+                }
+                "vector.distance" => super::vector::vector_distance(args).unwrap_or_else(|| lit(0)),
+                "split" => match args.as_slice() {
+                    [string, delimiter] => {
+                        datafusion::functions_nested::string::string_to_array_udf()
+                            .call(vec![to_df_value_expr(string), to_df_value_expr(delimiter)])
+                    }
+                    _ => lit(0),
+                },
                 _ => {
                     // Unsupported function - return placeholder for now
                     lit(0)
                 }
             }
         }
-        VE::Arithmetic { .. } => lit(0),
+        VE::Case {
+            branches,
+            else_result,
+        } => {
+            let Some(((first_condition, first_result), rest)) = branches.split_first() else {
+                return lit(0);
+            };
+            let mut case = datafusion::logical_expr::when(
+                to_df_boolean_expr(first_condition),
+                to_df_value_expr(first_result),
+            );
+            for (condition, result) in rest {
+                case.when(to_df_boolean_expr(condition), to_df_value_expr(result));
+            }
+            match else_result {
+                Some(else_result) => case.otherwise(to_df_value_expr(else_result)),
+                None => case.end(),
+            }
+            .unwrap_or_else(|_| lit(0))
+        }
+        VE::List(items) => datafusion::functions_nested::expr_fn::make_array(
+            items.iter().map(to_df_value_expr).collect(),
+        ),
+        VE::Index { list, index } => datafusion::functions_nested::expr_fn::array_element(
+            to_df_value_expr(list),
+            to_one_based_index(index, 1),
+        ),
+        VE::Slice { list, from, to } => {
+            // DataFusion slices include both bounds, Cypher excludes the upper one
+            let from = from
+                .as_ref()
+                .map_or_else(|| lit(1i64), |from| to_one_based_index(from, 1));
+            let to = to
+                .as_ref()
+                .map_or_else(|| lit(-1i64), |to| to_one_based_index(to, 0));
+            datafusion::functions_nested::expr_fn::array_slice(
+                to_df_value_expr(list),
+                from,
+                to,
+                None,
+            )
+        }
+        VE::ListComprehension {
+            variable,
+            list,
+            predicate,
+            projection,
+        } => {
+            let list = to_df_value_expr(list);
+            if predicate.is_none() && projection.is_none() {
+                return list;
+            }
+            super::list_comprehension::list_comprehension(
+                variable,
+                list,
+                predicate.as_deref().map(to_df_boolean_expr),
+                projection.as_deref().map(to_df_value_expr),
+            )
+        }
+        // Replaced by a reference to its count column during logical planning
+        VE::CountSubquery(_) => lit(0),
+        VE::Arithmetic {
+            left,
+            operator,
+            right,
+        } => {
+            use crate::ast::ArithmeticOperator as AO;
+            let op = match operator {
+                AO::Add => Operator::Plus,
+                AO::Subtract => Operator::Minus,
+                AO::Multiply => Operator::Multiply,
+                AO::Divide => Operator::Divide,
+                AO::Modulo => Operator::Modulo,
+            };
+            Expr::BinaryExpr(BinaryExpr {
+                left: Box::new(to_df_value_expr(left)),
+                op,
+                right: Box::new(to_df_value_expr(right)),
+            })
+        }
+        VE::Map(entries) => datafusion::functions::core::expr_fn::named_struct(
+            entries
+                .iter()
+                .flat_map(|(key, value)| [lit(key.as_str()), to_df_value_expr(value)])
+                .collect(),
+        ),
     }
 }
 
-/// Check if a ValueExpression contains an aggregate function
-pub(crate) fn contains_aggregate(expr: &ValueExpression) -> bool {
-    use crate::ast::ValueExpression as VE;
-    match expr {
-        VE::Function { name, args } => {
-            // Check if this is an aggregate function
-            let is_aggregate = matches!(
-                name.to_lowercase().as_str(),
-                "count" | "sum" | "avg" | "min" | "max"
-            );
-            // Also check arguments recursively
-            is_aggregate || args.iter().any(contains_aggregate)
+/// Anchor a regular expression at both ends: `=~` matches whole strings, while
+/// DataFusion's regexp functions look for a match anywhere in them
+fn anchored_regex(pattern: Expr) -> Expr {
+    match pattern {
+        Expr::Literal(datafusion::scalar::ScalarValue::Utf8(Some(pattern)), _) => {
+            lit(format!("^(?:{})$", pattern))
         }
-        VE::Arithmetic { left, right, .. } => contains_aggregate(left) || contains_aggregate(right),
-        _ => false,
+        pattern => string_fn::concat(vec![lit("^(?:"), pattern, lit(")$")]),
     }
 }
 
+/// Convert a zero-based Cypher list position into DataFusion's one-based one, shifting
+/// non-negative positions by `shift`; negative positions count from the end in both
+fn to_one_based_index(index: &ValueExpression, shift: i64) -> Expr {
+    if let ValueExpression::Literal(PropertyValue::Integer(i)) = index {
+        return lit(if *i >= 0 { *i + shift } else { *i - 1 + shift });
+    }
+    let index = to_df_value_expr(index);
+    datafusion::logical_expr::when(index.clone().gt_eq(lit(0i64)), index.clone() + lit(shift))
+        .otherwise(index + lit(shift - 1))
+        .unwrap_or_else(|_| lit(0))
+}
+
+/// Check if a ValueExpression contains an aggregate function
+pub(crate) fn contains_aggregate(expr: &ValueExpression) -> bool {
+    expr.contains_aggregate()
+}
+
 /// Convert a ValueExpression to Cypher dot notation for column naming
 ///
 /// This generates user-friendly column names following Cypher conventions:
@@ -197,15 +450,20 @@ pub(crate) fn to_cypher_column_name(expr: &ValueExpression) -> String {
             // Handle nested property references
             format!("{}.{}", prop.variable, prop.property)
         }
-        VE::Function { name, args } => {
-            // Generate descriptive function name: count(*), count(p.name), etc.
+        VE::Function {
+            name,
+            distinct,
+            args,
+        } => {
+            // Generate descriptive function name: count(*), count(DISTINCT p.name), etc.
             if args.len() == 1 {
                 let arg_repr = match &args[0] {
                     VE::Variable(v) => v.clone(),
                     VE::Property(prop) => format!("{}.{}", prop.variable, prop.property),
                     _ => "expr".to_string(),
                 };
-                format!("{}({})", name.to_lowercase(), arg_repr)
+                let distinct = if *distinct { "DISTINCT " } else { "" };
+                format!("{}({}{})", name.to_lowercase(), distinct, arg_repr)
             } else if args.is_empty() {
                 format!("{}()", name.to_lowercase())
             } else {
@@ -467,14 +725,7 @@ mod tests {
         };
 
         let df_expr = to_df_value_expr(&expr);
-        let s = format!("{:?}", df_expr);
-        // TODO: Arithmetic expressions are not yet implemented (line 124 returns lit(0) placeholder)
-        // Once implemented, this should return a BinaryExpr with Plus operator
-        // For now, verify it returns the placeholder without panicking
-        assert_eq!(
-            s, "Literal(Int32(0), None)",
-            "Arithmetic currently returns placeholder lit(0)"
-        );
+        assert_eq!(df_expr, ident("p__age") + lit(5i64));
     }
 
     #[test]
@@ -505,6 +756,7 @@ mod tests {
     fn test_value_expr_function_count_star() {
         let expr = ValueExpression::Function {
             name: "COUNT".into(),
+            distinct: false,
             args: vec![ValueExpression::Literal(PropertyValue::String("*".into()))],
         };
 
@@ -520,6 +772,7 @@ mod tests {
     fn test_value_expr_function_count_property() {
         let expr = ValueExpression::Function {
             name: "COUNT".into(),
+            distinct: false,
             args: vec![ValueExpression::Property(PropertyRef {
                 variable: "p".into(),
                 property: "id".into(),
@@ -539,6 +792,7 @@ mod tests {
     fn test_value_expr_function_sum() {
         let expr = ValueExpression::Function {
             name: "SUM".into(),
+            distinct: false,
             args: vec![ValueExpression::Property(PropertyRef {
                 variable: "p".into(),
                 property: "amount".into(),
@@ -558,6 +812,7 @@ mod tests {
     fn test_value_expr_function_avg() {
         let expr = ValueExpression::Function {
             name: "AVG".into(),
+            distinct: false,
             args: vec![ValueExpression::Property(PropertyRef {
                 variable: "p".into(),
                 property: "amount".into(),
@@ -577,6 +832,7 @@ mod tests {
     fn test_value_expr_function_min() {
         let expr = ValueExpression::Function {
             name: "MIN".into(),
+            distinct: false,
             args: vec![ValueExpression::Property(PropertyRef {
                 variable: "p".into(),
                 property: "amount".into(),
@@ -596,6 +852,7 @@ mod tests {
     fn test_value_expr_function_max() {
         let expr = ValueExpression::Function {
             name: "MAX".into(),
+            distinct: false,
             args: vec![ValueExpression::Property(PropertyRef {
                 variable: "p".into(),
                 property: "amount".into(),
@@ -619,6 +876,7 @@ mod tests {
     fn test_contains_aggregate_count() {
         let expr = ValueExpression::Function {
             name: "COUNT".into(),
+            distinct: false,
             args: vec![ValueExpression::Literal(PropertyValue::String("*".into()))],
         };
 
@@ -632,6 +890,7 @@ mod tests {
     fn test_contains_aggregate_sum() {
         let expr = ValueExpression::Function {
             name: "SUM".into(),
+            distinct: false,
             args: vec![ValueExpression::Property(PropertyRef {
                 variable: "p".into(),
                 property: "value".into(),
@@ -648,6 +907,7 @@ mod tests {
     fn test_contains_aggregate_min() {
         let expr = ValueExpression::Function {
             name: "MIN".into(),
+            distinct: false,
             args: vec![ValueExpression::Property(PropertyRef {
                 variable: "p".into(),
                 property: "value".into(),
@@ -664,6 +924,7 @@ mod tests {
     fn test_contains_aggregate_max() {
         let expr = ValueExpression::Function {
             name: "MAX".into(),
+            distinct: false,
             args: vec![ValueExpression::Property(PropertyRef {
                 variable: "p".into(),
                 property: "value".into(),
@@ -704,6 +965,7 @@ mod tests {
         let expr = ValueExpression::Arithmetic {
             left: Box::new(ValueExpression::Function {
                 name: "COUNT".into(),
+                distinct: false,
                 args: vec![ValueExpression::Literal(PropertyValue::String("*".into()))],
             }),
             operator: ArithmeticOperator::Multiply,
@@ -738,8 +1000,10 @@ mod tests {
     fn test_contains_aggregate_nested_function() {
         let expr = ValueExpression::Function {
             name: "UPPER".into(),
+            distinct: false,
             args: vec![ValueExpression::Function {
                 name: "COUNT".into(),
+                distinct: false,
                 args: vec![ValueExpression::Literal(PropertyValue::String("*".into()))],
             }],
         };
@@ -769,6 +1033,7 @@ mod tests {
     fn test_cypher_column_name_function_count_star() {
         let expr = ValueExpression::Function {
             name: "COUNT".into(),
+            distinct: false,
             args: vec![ValueExpression::Literal(PropertyValue::String("*".into()))],
         };
 
@@ -785,6 +1050,7 @@ mod tests {
     fn test_cypher_column_name_function_count_property() {
         let expr = ValueExpression::Function {
             name: "COUNT".into(),
+            distinct: false,
             args: vec![ValueExpression::Property(PropertyRef {
                 variable: "p".into(),
                 property: "id".into(),
@@ -799,6 +1065,7 @@ mod tests {
     fn test_cypher_column_name_function_sum() {
         let expr = ValueExpression::Function {
             name: "SUM".into(),
+            distinct: false,
             args: vec![ValueExpression::Property(PropertyRef {
                 variable: "order".into(),
                 property: "amount".into(),
@@ -0,0 +1,410 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Breadth-first expansion of variable-length patterns
+//!
+//! With [`VariableLengthExpansion::Frontier`](crate::config::VariableLengthExpansion),
+//! `(a)-[:KNOWS*1..5]->(b)` is planned as a scan of a [`FrontierTable`] rather than one
+//! join chain per length. Executing it loads the edge list into an adjacency list and
+//! expands from each distinct source one hop at a time, keeping a visited set so every
+//! node is reached once per source. Memory grows with the nodes reached, not with the
+//! number of paths leading to them.
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use arrow::row::{RowConverter, Rows, SortField};
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use async_trait::async_trait;
+use datafusion::catalog::Session;
+use datafusion::datasource::{TableProvider, TableType};
+use datafusion::error::DataFusionError;
+use datafusion::execution::TaskContext;
+use datafusion::logical_expr::{Expr, LogicalPlan};
+use datafusion::physical_expr::EquivalenceProperties;
+use datafusion::physical_plan::execution_plan::{Boundedness, EmissionType};
+use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
+use datafusion::physical_plan::{
+    collect, DisplayAs, DisplayFormatType, ExecutionPlan, Partitioning, PlanProperties,
+    SendableRecordBatchStream,
+};
+
+use crate::cancellation::Interrupt;
+
+/// Source node column of the starts plan and of the expansion's output
+pub(crate) const START: &str = "__vl_start";
+/// Reached node column of the expansion's output
+pub(crate) const NODE: &str = "__vl_node";
+/// Source node column of the edge list
+pub(crate) const FROM: &str = "__vl_from";
+/// Target node column of the edge list
+pub(crate) const TO: &str = "__vl_to";
+
+type DFResult<T> = std::result::Result<T, DataFusionError>;
+
+/// The (`__vl_start`, `__vl_node`) pairs of nodes reachable from each start in
+/// `1..=max_hops` hops over an edge list
+///
+/// `starts` has a single `__vl_start` column and `edges` the `__vl_from` and `__vl_to`
+/// columns, all of one type. Both are planned when the table is scanned.
+pub(crate) struct FrontierTable {
+    starts: LogicalPlan,
+    edges: LogicalPlan,
+    max_hops: u32,
+    schema: SchemaRef,
+}
+
+impl fmt::Debug for FrontierTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FrontierTable")
+            .field("max_hops", &self.max_hops)
+            .finish_non_exhaustive()
+    }
+}
+
+impl FrontierTable {
+    pub fn new(starts: LogicalPlan, edges: LogicalPlan, max_hops: u32, key_type: DataType) -> Self {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new(START, key_type.clone(), false),
+            Field::new(NODE, key_type, false),
+        ]));
+        Self {
+            starts,
+            edges,
+            max_hops,
+            schema,
+        }
+    }
+}
+
+#[async_trait]
+impl TableProvider for FrontierTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::Temporary
+    }
+
+    async fn scan(
+        &self,
+        state: &dyn Session,
+        projection: Option<&Vec<usize>>,
+        _filters: &[Expr],
+        _limit: Option<usize>,
+    ) -> DFResult<Arc<dyn ExecutionPlan>> {
+        let starts = state.create_physical_plan(&self.starts).await?;
+        let edges = state.create_physical_plan(&self.edges).await?;
+        let projection = projection
+            .cloned()
+            .unwrap_or_else(|| (0..self.schema.fields().len()).collect());
+        Ok(Arc::new(FrontierExec::new(
+            starts,
+            edges,
+            self.max_hops,
+            self.schema.clone(),
+            projection,
+        )?))
+    }
+}
+
+/// Expands breadth-first from every start over the collected edge list
+struct FrontierExec {
+    starts: Arc<dyn ExecutionPlan>,
+    edges: Arc<dyn ExecutionPlan>,
+    max_hops: u32,
+    /// Output schema before projection
+    schema: SchemaRef,
+    projection: Vec<usize>,
+    properties: PlanProperties,
+}
+
+impl FrontierExec {
+    fn new(
+        starts: Arc<dyn ExecutionPlan>,
+        edges: Arc<dyn ExecutionPlan>,
+        max_hops: u32,
+        schema: SchemaRef,
+        projection: Vec<usize>,
+    ) -> DFResult<Self> {
+        let projected = Arc::new(schema.project(&projection)?);
+        let properties = PlanProperties::new(
+            EquivalenceProperties::new(projected),
+            Partitioning::UnknownPartitioning(1),
+            EmissionType::Final,
+            Boundedness::Bounded,
+        );
+        Ok(Self {
+            starts,
+            edges,
+            max_hops,
+            schema,
+            projection,
+            properties,
+        })
+    }
+}
+
+impl fmt::Debug for FrontierExec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FrontierExec")
+            .field("max_hops", &self.max_hops)
+            .finish_non_exhaustive()
+    }
+}
+
+impl DisplayAs for FrontierExec {
+    fn fmt_as(&self, _t: DisplayFormatType, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "FrontierExec: max_hops={}", self.max_hops)
+    }
+}
+
+impl ExecutionPlan for FrontierExec {
+    fn name(&self) -> &str {
+        "FrontierExec"
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn properties(&self) -> &PlanProperties {
+        &self.properties
+    }
+
+    fn children(&self) -> Vec<&Arc<dyn ExecutionPlan>> {
+        vec![&self.starts, &self.edges]
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> DFResult<Arc<dyn ExecutionPlan>> {
+        let [starts, edges]: [Arc<dyn ExecutionPlan>; 2] = children
+            .try_into()
+            .map_err(|_| DataFusionError::Internal("FrontierExec has two children".to_string()))?;
+        Ok(Arc::new(FrontierExec::new(
+            starts,
+            edges,
+            self.max_hops,
+            self.schema.clone(),
+            self.projection.clone(),
+        )?))
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> DFResult<SendableRecordBatchStream> {
+        if partition != 0 {
+            return Err(DataFusionError::Internal(format!(
+                "FrontierExec has one partition, asked for {}",
+                partition
+            )));
+        }
+        let starts = self.starts.clone();
+        let edges = self.edges.clone();
+        let max_hops = self.max_hops;
+        let schema = self.schema.clone();
+        let projection = self.projection.clone();
+        let batch = futures::stream::once(async move {
+            let starts = collect(starts, context.clone()).await?;
+            let edges = collect(edges, context.clone()).await?;
+            let pairs = expand(&schema, &starts, &edges, max_hops, &|| {
+                Interrupt::check(&context)
+            })?;
+            Ok::<_, DataFusionError>(pairs.project(&projection)?)
+        });
+        Ok(Box::pin(RecordBatchStreamAdapter::new(
+            self.properties.eq_properties.schema().clone(),
+            batch,
+        )))
+    }
+}
+
+/// Nodes keyed by their encoded id, with their outgoing edges
+struct Adjacency {
+    converter: RowConverter,
+    nodes: Rows,
+    index: HashMap<Box<[u8]>, usize>,
+    neighbors: Vec<Vec<usize>>,
+}
+
+impl Adjacency {
+    fn new(key_type: DataType) -> DFResult<Self> {
+        let converter = RowConverter::new(vec![SortField::new(key_type)])?;
+        let nodes = converter.empty_rows(0, 0);
+        Ok(Self {
+            converter,
+            nodes,
+            index: HashMap::new(),
+            neighbors: Vec::new(),
+        })
+    }
+
+    /// Index of each non-null id in `column`, adding the ids not seen before
+    fn intern(&mut self, column: &ArrayRef) -> DFResult<Vec<Option<usize>>> {
+        let rows = self
+            .converter
+            .convert_columns(std::slice::from_ref(column))?;
+        let mut ids = Vec::with_capacity(rows.num_rows());
+        for (i, row) in rows.iter().enumerate() {
+            if column.is_null(i) {
+                ids.push(None);
+                continue;
+            }
+            let id = match self.index.get(row.as_ref()) {
+                Some(&id) => id,
+                None => {
+                    let id = self.neighbors.len();
+                    self.index.insert(row.as_ref().into(), id);
+                    self.nodes.push(row);
+                    self.neighbors.push(Vec::new());
+                    id
+                }
+            };
+            ids.push(Some(id));
+        }
+        Ok(ids)
+    }
+}
+
+/// Pairs of each start and every node it reaches in `1..=max_hops` hops
+///
+/// `interrupted` is checked before each hop, failing the expansion when the query is
+/// cancelled.
+fn expand(
+    schema: &SchemaRef,
+    starts: &[RecordBatch],
+    edges: &[RecordBatch],
+    max_hops: u32,
+    interrupted: &dyn Fn() -> DFResult<()>,
+) -> DFResult<RecordBatch> {
+    let mut graph = Adjacency::new(schema.field(0).data_type().clone())?;
+    for batch in edges {
+        let from = graph.intern(batch.column_by_name(FROM).ok_or_else(|| missing(FROM))?)?;
+        let to = graph.intern(batch.column_by_name(TO).ok_or_else(|| missing(TO))?)?;
+        for (from, to) in from.into_iter().zip(to) {
+            if let (Some(from), Some(to)) = (from, to) {
+                graph.neighbors[from].push(to);
+            }
+        }
+    }
+    let mut sources = Vec::new();
+    for batch in starts {
+        let column = batch.column_by_name(START).ok_or_else(|| missing(START))?;
+        sources.extend(graph.intern(column)?.into_iter().flatten());
+    }
+
+    // `visited[node]` holds the last start (plus one) that reached the node
+    let mut visited = vec![0usize; graph.neighbors.len()];
+    let mut pairs: Vec<(usize, usize)> = Vec::new();
+    for (i, &start) in sources.iter().enumerate() {
+        let mark = i + 1;
+        let mut frontier = vec![start];
+        for _ in 0..max_hops {
+            interrupted()?;
+            let mut next = Vec::new();
+            for node in frontier {
+                for &neighbor in &graph.neighbors[node] {
+                    if visited[neighbor] != mark {
+                        visited[neighbor] = mark;
+                        next.push(neighbor);
+                        pairs.push((start, neighbor));
+                    }
+                }
+            }
+            if next.is_empty() {
+                break;
+            }
+            frontier = next;
+        }
+    }
+
+    let column = |ids: &mut dyn Iterator<Item = usize>| -> DFResult<ArrayRef> {
+        let mut columns = graph
+            .converter
+            .convert_rows(ids.map(|id| graph.nodes.row(id)))?;
+        Ok(columns.remove(0))
+    };
+    let columns = vec![
+        column(&mut pairs.iter().map(|&(start, _)| start))?,
+        column(&mut pairs.iter().map(|&(_, node)| node))?,
+    ];
+    Ok(RecordBatch::try_new(schema.clone(), columns)?)
+}
+
+fn missing(column: &str) -> DataFusionError {
+    DataFusionError::Internal(format!("Frontier expansion input lacks column {}", column))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::Int64Array;
+
+    fn ids(values: Vec<i64>) -> ArrayRef {
+        Arc::new(Int64Array::from(values))
+    }
+
+    fn pairs(batch: &RecordBatch) -> Vec<(i64, i64)> {
+        let column = |i: usize| {
+            batch
+                .column(i)
+                .as_any()
+                .downcast_ref::<Int64Array>()
+                .unwrap()
+                .values()
+                .to_vec()
+        };
+        let mut pairs: Vec<_> = column(0).into_iter().zip(column(1)).collect();
+        pairs.sort();
+        pairs
+    }
+
+    #[test]
+    fn test_expand_visits_each_node_once_per_start() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new(START, DataType::Int64, false),
+            Field::new(NODE, DataType::Int64, false),
+        ]));
+        let edge_schema = Arc::new(Schema::new(vec![
+            Field::new(FROM, DataType::Int64, false),
+            Field::new(TO, DataType::Int64, false),
+        ]));
+        // A diamond 1 -> {2, 3} -> 4 with a cycle 4 -> 1
+        let edges = RecordBatch::try_new(
+            edge_schema,
+            vec![ids(vec![1, 1, 2, 3, 4]), ids(vec![2, 3, 4, 4, 1])],
+        )
+        .unwrap();
+        let starts = RecordBatch::try_new(
+            Arc::new(Schema::new(vec![Field::new(START, DataType::Int64, false)])),
+            vec![ids(vec![1, 5])],
+        )
+        .unwrap();
+
+        let out = expand(
+            &schema,
+            std::slice::from_ref(&starts),
+            std::slice::from_ref(&edges),
+            2,
+            &|| Ok(()),
+        )
+        .unwrap();
+        assert_eq!(pairs(&out), vec![(1, 2), (1, 3), (1, 4)]);
+
+        // The cycle leads back to the start, which is reached once
+        let out = expand(&schema, &[starts], &[edges], 10, &|| Ok(())).unwrap();
+        assert_eq!(pairs(&out), vec![(1, 1), (1, 2), (1, 3), (1, 4)]);
+    }
+}
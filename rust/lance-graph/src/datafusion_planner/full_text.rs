@@ -0,0 +1,156 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Full-text search
+//!
+//! `fts(n.bio, 'query terms')` is a scalar UDF holding when the text contains any of the
+//! terms, ignoring case, as `n.bio ILIKE '%query%' OR n.bio ILIKE '%terms%'` would. Node
+//! tables backed by Lance datasets answer it with the full-text index of the column when
+//! there is one, matching terms as that index tokenizes them.
+
+use datafusion::arrow::array::{AsArray, BooleanArray};
+use datafusion::arrow::compute::cast;
+use datafusion::arrow::datatypes::DataType;
+use datafusion::common::{exec_err, Column, Result as DFResult};
+use datafusion::logical_expr::{
+    ColumnarValue, Expr, ScalarFunctionArgs, ScalarUDF, ScalarUDFImpl, Signature, Volatility,
+};
+use datafusion::scalar::ScalarValue;
+use std::any::Any;
+use std::sync::Arc;
+
+const FUNCTION_NAME: &str = "fts";
+
+/// `fts(expression, query)`
+pub(crate) fn full_text_search(expression: Expr, query: Expr) -> Expr {
+    let udf = FullTextSearch {
+        signature: Signature::any(2, Volatility::Immutable),
+    };
+    ScalarUDF::new_from_impl(udf).call(vec![expression, query])
+}
+
+/// The column and query of an `fts` call on a column with a constant query
+pub(crate) fn full_text_query(expr: &Expr) -> Option<(&Column, &str)> {
+    let Expr::ScalarFunction(function) = expr else {
+        return None;
+    };
+    if function.name() != FUNCTION_NAME {
+        return None;
+    }
+    match function.args.as_slice() {
+        [Expr::Column(column), Expr::Literal(
+            ScalarValue::Utf8(Some(query))
+            | ScalarValue::LargeUtf8(Some(query))
+            | ScalarValue::Utf8View(Some(query)),
+            _,
+        )] => Some((column, query)),
+        _ => None,
+    }
+}
+
+/// Lowercased terms of a query, split on anything but letters and digits
+fn terms(query: &str) -> Vec<String> {
+    query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|term| !term.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+#[derive(Debug)]
+struct FullTextSearch {
+    signature: Signature,
+}
+
+impl ScalarUDFImpl for FullTextSearch {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        FUNCTION_NAME
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> DFResult<DataType> {
+        Ok(DataType::Boolean)
+    }
+
+    fn invoke_with_args(&self, args: ScalarFunctionArgs) -> DFResult<ColumnarValue> {
+        let terms = match &args.args[1] {
+            ColumnarValue::Scalar(
+                ScalarValue::Utf8(Some(query))
+                | ScalarValue::LargeUtf8(Some(query))
+                | ScalarValue::Utf8View(Some(query)),
+            ) => terms(query),
+            ColumnarValue::Scalar(scalar) if scalar.is_null() => {
+                return Ok(ColumnarValue::Scalar(ScalarValue::Boolean(None)))
+            }
+            _ => return exec_err!("fts takes its query as a string constant"),
+        };
+        let texts = args.args[0].to_array(args.number_rows)?;
+        let texts = cast(&texts, &DataType::Utf8)?;
+        let matches: BooleanArray = texts
+            .as_string::<i32>()
+            .iter()
+            .map(|text| {
+                text.map(|text| {
+                    let text = text.to_lowercase();
+                    terms.iter().any(|term| text.contains(term.as_str()))
+                })
+            })
+            .collect();
+        Ok(ColumnarValue::Array(Arc::new(matches)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::arrow::array::{Array, StringArray};
+    use datafusion::arrow::datatypes::Field;
+    use datafusion::logical_expr::{col, lit};
+
+    #[test]
+    fn test_texts_matching_any_term() {
+        let texts = StringArray::from(vec![
+            Some("Builds Graph engines"),
+            Some("Writes poetry"),
+            None,
+        ]);
+        let udf = FullTextSearch {
+            signature: Signature::any(2, Volatility::Immutable),
+        };
+        let args = ScalarFunctionArgs {
+            arg_fields: vec![
+                Arc::new(Field::new("bio", DataType::Utf8, true)),
+                Arc::new(Field::new("query", DataType::Utf8, true)),
+            ],
+            args: vec![
+                ColumnarValue::Array(Arc::new(texts)),
+                ColumnarValue::Scalar(ScalarValue::from("graph, databases")),
+            ],
+            number_rows: 3,
+            return_field: Arc::new(Field::new("fts", DataType::Boolean, true)),
+        };
+        let ColumnarValue::Array(result) = udf.invoke_with_args(args).unwrap() else {
+            panic!("Expected an array");
+        };
+        let result = result.as_boolean();
+        assert!(result.value(0));
+        assert!(!result.value(1));
+        assert!(result.is_null(2));
+    }
+
+    #[test]
+    fn test_full_text_query_of_a_column() {
+        let expr = full_text_search(col("bio"), lit("graph"));
+        let (column, query) = full_text_query(&expr).unwrap();
+        assert_eq!(column.name, "bio");
+        assert_eq!(query, "graph");
+        assert!(full_text_query(&full_text_search(lit("bio"), lit("graph"))).is_none());
+    }
+}
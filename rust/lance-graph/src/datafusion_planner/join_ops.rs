@@ -13,7 +13,7 @@ use super::DataFusionPlanner;
 use crate::ast::{PropertyValue, RelationshipDirection};
 use crate::config::{NodeMapping, RelationshipMapping};
 use crate::error::Result;
-use crate::source_catalog::GraphSourceCatalog;
+use crate::source_catalog::{GraphSourceCatalog, SourceKind};
 use datafusion::logical_expr::{
     col, BinaryExpr, Expr, JoinType, LogicalPlan, LogicalPlanBuilder, Operator,
 };
@@ -24,7 +24,7 @@ use std::sync::Arc;
 pub(crate) struct SourceJoinParams<'a> {
     pub source_variable: &'a str,
     pub rel_qualifier: &'a str,
-    pub node_id_field: &'a str,
+    pub node_id_fields: Vec<&'a str>,
     pub rel_map: &'a RelationshipMapping,
     pub direction: &'a RelationshipDirection,
 }
@@ -48,22 +48,16 @@ impl DataFusionPlanner {
         params: &SourceJoinParams,
     ) -> Result<LogicalPlanBuilder> {
         // Determine join keys based on direction
-        let right_key = match params.direction {
-            RelationshipDirection::Outgoing => &params.rel_map.source_id_field,
-            RelationshipDirection::Incoming => &params.rel_map.target_id_field,
-            RelationshipDirection::Undirected => &params.rel_map.source_id_field,
-        };
-
-        let qualified_left_key = format!("{}__{}", params.source_variable, params.node_id_field);
-        let qualified_right_key = format!("{}__{}", params.rel_qualifier, right_key);
+        let right_keys = Self::get_source_join_keys(params.direction, params.rel_map);
+        let (left_keys, right_keys) = self.qualified_key_pairs(
+            params.source_variable,
+            &params.node_id_fields,
+            params.rel_qualifier,
+            &right_keys,
+        )?;
 
         LogicalPlanBuilder::from(left_plan)
-            .join(
-                rel_scan,
-                JoinType::Inner,
-                (vec![qualified_left_key], vec![qualified_right_key]),
-                None,
-            )
+            .join(rel_scan, JoinType::Inner, (left_keys, right_keys), None)
             .map_err(|e| self.plan_error("Failed to join source to relationship", e))
     }
 
@@ -72,7 +66,7 @@ impl DataFusionPlanner {
         &self,
         mut builder: LogicalPlanBuilder,
         cat: &Arc<dyn GraphSourceCatalog>,
-        ctx: &PlanningContext,
+        ctx: &mut PlanningContext,
         params: &TargetJoinParams,
     ) -> Result<LogicalPlan> {
         // Get the target label from the analysis (which now has the correct label from Expand)
@@ -87,67 +81,88 @@ impl DataFusionPlanner {
                 .map_err(|e| self.plan_error("Failed to build plan (no target label)", e));
         };
 
-        let Some(target_source) = cat.node_source(&target_label) else {
-            return builder
+        let target_scan = if self.config.has_sub_labels(&target_label) {
+            self.build_label_hierarchy_scan(
+                cat,
+                &target_label,
+                params.target_variable,
+                params.target_properties,
+            )?
+        } else {
+            let Some(target_source) = cat.node_source(&target_label) else {
+                return builder
+                    .build()
+                    .map_err(|e| self.plan_error("Failed to build plan (no target source)", e));
+            };
+
+            // Create target node scan with qualified column aliases and property filters
+            let target_schema = target_source.schema();
+            let target_builder = LogicalPlanBuilder::scan(&target_label, target_source, None)
+                .map_err(|e| {
+                    self.plan_error(&format!("Failed to scan target node '{}'", target_label), e)
+                })?;
+            let mut target_builder = self.filter_mapped_rows(
+                target_builder,
+                SourceKind::Node,
+                &target_label,
+                &target_schema,
+            )?;
+
+            // Apply target property filters (e.g., (b {age: 30}))
+            for (k, v) in params.target_properties.iter() {
+                let lit_expr = super::expression::to_df_value_expr(
+                    &crate::ast::ValueExpression::Literal(v.clone()),
+                );
+                let filter_expr = Expr::BinaryExpr(BinaryExpr {
+                    left: Box::new(self.node_property_expr(&target_label, k, &target_schema)?),
+                    op: Operator::Eq,
+                    right: Box::new(lit_expr),
+                });
+                target_builder = target_builder.filter(filter_expr).map_err(|e| {
+                    self.plan_error(&format!("Failed to apply target filter on '{}'", k), e)
+                })?;
+            }
+
+            let mut target_qualified_exprs: Vec<Expr> = target_schema
+                .fields()
+                .iter()
+                .map(|field| {
+                    let qualified_name = self.qualified_node_column(
+                        params.target_variable,
+                        &target_label,
+                        field.name(),
+                    );
+                    col(field.name()).alias(&qualified_name)
+                })
+                .collect();
+            target_qualified_exprs.extend(self.node_computed_columns(
+                params.target_variable,
+                &target_label,
+                &target_schema,
+            )?);
+            target_qualified_exprs
+                .extend(self.node_identity_columns(params.target_variable, &target_label));
+
+            target_builder
+                .project(target_qualified_exprs)
+                .map_err(|e| self.plan_error("Failed to project target columns", e))?
                 .build()
-                .map_err(|e| self.plan_error("Failed to build plan (no target source)", e));
+                .map_err(|e| self.plan_error("Failed to build target scan", e))?
         };
 
-        // Create target node scan with qualified column aliases and property filters
-        let target_schema = target_source.schema();
-        let mut target_builder = LogicalPlanBuilder::scan(&target_label, target_source, None)
-            .map_err(|e| {
-                self.plan_error(&format!("Failed to scan target node '{}'", target_label), e)
-            })?;
-
-        // Apply target property filters (e.g., (b {age: 30}))
-        for (k, v) in params.target_properties.iter() {
-            let lit_expr = super::expression::to_df_value_expr(
-                &crate::ast::ValueExpression::Literal(v.clone()),
-            );
-            let filter_expr = Expr::BinaryExpr(BinaryExpr {
-                left: Box::new(col(k)),
-                op: Operator::Eq,
-                right: Box::new(lit_expr),
-            });
-            target_builder = target_builder.filter(filter_expr).map_err(|e| {
-                self.plan_error(&format!("Failed to apply target filter on '{}'", k), e)
-            })?;
-        }
-
-        let target_qualified_exprs: Vec<Expr> = target_schema
-            .fields()
-            .iter()
-            .map(|field| {
-                let qualified_name = format!("{}__{}", params.target_variable, field.name());
-                col(field.name()).alias(&qualified_name)
-            })
-            .collect();
-
-        let target_scan = target_builder
-            .project(target_qualified_exprs)
-            .map_err(|e| self.plan_error("Failed to project target columns", e))?
-            .build()
-            .map_err(|e| self.plan_error("Failed to build target scan", e))?;
+        let target_scan = self.finish_scan(ctx, params.target_variable, target_scan)?;
 
         // Determine target join keys
-        let target_key = match params.direction {
-            RelationshipDirection::Outgoing => &params.rel_map.target_id_field,
-            RelationshipDirection::Incoming => &params.rel_map.source_id_field,
-            RelationshipDirection::Undirected => &params.rel_map.target_id_field,
-        };
-
-        let qualified_rel_target_key = format!("{}__{}", params.rel_qualifier, target_key);
-        let qualified_target_key =
-            format!("{}__{}", params.target_variable, &params.node_map.id_field);
+        let target_keys = Self::get_target_join_keys(params.direction, params.rel_map);
+        let (rel_keys, node_keys) = self.qualified_key_pairs(
+            params.rel_qualifier,
+            &target_keys,
+            params.target_variable,
+            &params.node_map.id_fields(),
+        )?;
 
         builder = builder
-            .join(
-                target_scan,
-                JoinType::Inner,
-                (vec![qualified_rel_target_key], vec![qualified_target_key]),
-                None,
-            )
+            .join(target_scan, JoinType::Inner, (rel_keys, node_keys), None)
             .map_err(|e| self.plan_error("Failed to join relationship to target", e))?;
 
         builder
@@ -155,30 +170,130 @@ impl DataFusionPlanner {
             .map_err(|e| self.plan_error("Failed to build final join plan", e))
     }
 
-    /// Get relationship join key based on direction (source side)
-    pub(crate) fn get_source_join_key<'a>(
+    /// Get relationship join keys based on direction (source side)
+    pub(crate) fn get_source_join_keys<'a>(
         direction: &RelationshipDirection,
         rel_map: &'a RelationshipMapping,
-    ) -> &'a str {
+    ) -> Vec<&'a str> {
         match direction {
-            RelationshipDirection::Outgoing => &rel_map.source_id_field,
-            RelationshipDirection::Incoming => &rel_map.target_id_field,
-            RelationshipDirection::Undirected => &rel_map.source_id_field,
+            RelationshipDirection::Outgoing => rel_map.source_id_fields(),
+            RelationshipDirection::Incoming => rel_map.target_id_fields(),
+            RelationshipDirection::Undirected => rel_map.source_id_fields(),
         }
     }
 
-    /// Get relationship join key based on direction (target side)
-    pub(crate) fn get_target_join_key<'a>(
+    /// Get relationship join keys based on direction (target side)
+    pub(crate) fn get_target_join_keys<'a>(
         direction: &RelationshipDirection,
         rel_map: &'a RelationshipMapping,
-    ) -> &'a str {
+    ) -> Vec<&'a str> {
         match direction {
-            RelationshipDirection::Outgoing => &rel_map.target_id_field,
-            RelationshipDirection::Incoming => &rel_map.source_id_field,
-            RelationshipDirection::Undirected => &rel_map.target_id_field,
+            RelationshipDirection::Outgoing => rel_map.target_id_fields(),
+            RelationshipDirection::Incoming => rel_map.source_id_fields(),
+            RelationshipDirection::Undirected => rel_map.target_id_fields(),
         }
     }
 
+    /// Directions to traverse for a pattern direction over a relationship mapping.
+    ///
+    /// Undirected patterns, and any pattern over an undirected mapping, match rows in
+    /// both directions; callers union one join per direction.
+    pub(crate) fn traversal_directions(
+        direction: &RelationshipDirection,
+        rel_map: &RelationshipMapping,
+    ) -> Vec<RelationshipDirection> {
+        if rel_map.undirected || *direction == RelationshipDirection::Undirected {
+            vec![
+                RelationshipDirection::Outgoing,
+                RelationshipDirection::Incoming,
+            ]
+        } else {
+            vec![direction.clone()]
+        }
+    }
+
+    /// Drop self-loops from a qualified relationship scan.
+    ///
+    /// Applied to the reverse branch of a bidirectional traversal so a loop is matched
+    /// once rather than once per direction.
+    pub(crate) fn exclude_self_loops(
+        &self,
+        rel_scan: LogicalPlan,
+        rel_qualifier: &str,
+        rel_map: &RelationshipMapping,
+    ) -> Result<LogicalPlan> {
+        let (source_keys, target_keys) = self.qualified_key_pairs(
+            rel_qualifier,
+            &rel_map.source_id_fields(),
+            rel_qualifier,
+            &rel_map.target_id_fields(),
+        )?;
+        let Some(not_loop) = source_keys
+            .iter()
+            .zip(&target_keys)
+            .map(|(source, target)| col(source.as_str()).not_eq(col(target.as_str())))
+            .reduce(Expr::or)
+        else {
+            return Ok(rel_scan);
+        };
+        LogicalPlanBuilder::from(rel_scan)
+            .filter(not_loop)
+            .and_then(|builder| builder.build())
+            .map_err(|e| self.plan_error("Failed to exclude self-loops", e))
+    }
+
+    /// Union per-direction traversal branches, which share one schema.
+    pub(crate) fn union_branches(&self, branches: Vec<LogicalPlan>) -> Result<LogicalPlan> {
+        let mut branches = branches.into_iter();
+        let first = branches
+            .next()
+            .ok_or_else(|| crate::error::GraphError::PlanError {
+                message: "Traversal produced no branches".to_string(),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            })?;
+        branches.try_fold(first, |union_plan, branch| {
+            LogicalPlanBuilder::from(union_plan)
+                .union(branch)
+                .and_then(|builder| builder.build())
+                .map_err(|e| self.plan_error("Failed to union traversal directions", e))
+        })
+    }
+
+    /// Qualify two equally long key column lists for an equi-join.
+    ///
+    /// Composite node keys join over every column, so the relationship side must
+    /// reference the same number of columns.
+    pub(crate) fn qualified_key_pairs(
+        &self,
+        left_qualifier: &str,
+        left_fields: &[&str],
+        right_qualifier: &str,
+        right_fields: &[&str],
+    ) -> Result<(Vec<String>, Vec<String>)> {
+        if left_fields.len() != right_fields.len() {
+            return Err(crate::error::GraphError::PlanError {
+                message: format!(
+                    "Cannot join '{}' on ({}) with '{}' on ({}): key column counts differ",
+                    left_qualifier,
+                    left_fields.join(", "),
+                    right_qualifier,
+                    right_fields.join(", ")
+                ),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            });
+        }
+        let qualify = |qualifier: &str, fields: &[&str]| -> Vec<String> {
+            fields
+                .iter()
+                .map(|field| format!("{}__{}", qualifier, field))
+                .collect()
+        };
+        Ok((
+            qualify(left_qualifier, left_fields),
+            qualify(right_qualifier, right_fields),
+        ))
+    }
+
     /// Join input plan with relationship scan
     #[allow(clippy::too_many_arguments)]
     pub(crate) fn join_relationship_to_input(
@@ -191,15 +306,18 @@ impl DataFusionPlanner {
         node_map: &NodeMapping,
         direction: &RelationshipDirection,
     ) -> Result<LogicalPlanBuilder> {
-        let source_key = Self::get_source_join_key(direction, rel_map);
-        let qualified_source_key = format!("{}__{}", source_variable, &node_map.id_field);
-        let qualified_rel_source_key = format!("{}__{}", rel_instance.alias, source_key);
+        let (source_keys, rel_source_keys) = self.qualified_key_pairs(
+            source_variable,
+            &node_map.id_fields(),
+            &rel_instance.alias,
+            &Self::get_source_join_keys(direction, rel_map),
+        )?;
 
         LogicalPlanBuilder::from(input_plan)
             .join(
                 rel_scan,
                 JoinType::Inner,
-                (vec![qualified_source_key], vec![qualified_rel_source_key]),
+                (source_keys, rel_source_keys),
                 None,
             )
             .map_err(|e| crate::error::GraphError::PlanError {
@@ -220,15 +338,18 @@ impl DataFusionPlanner {
         node_map: &NodeMapping,
         direction: &RelationshipDirection,
     ) -> Result<LogicalPlanBuilder> {
-        let target_key = Self::get_target_join_key(direction, rel_map);
-        let qualified_rel_target_key = format!("{}__{}", rel_instance.alias, target_key);
-        let qualified_target_key = format!("{}__{}", target_variable, &node_map.id_field);
+        let (rel_target_keys, target_keys) = self.qualified_key_pairs(
+            &rel_instance.alias,
+            &Self::get_target_join_keys(direction, rel_map),
+            target_variable,
+            &node_map.id_fields(),
+        )?;
 
         builder
             .join(
                 target_scan,
                 JoinType::Inner,
-                (vec![qualified_rel_target_key], vec![qualified_target_key]),
+                (rel_target_keys, target_keys),
                 None,
             )
             .map_err(|e| crate::error::GraphError::PlanError {
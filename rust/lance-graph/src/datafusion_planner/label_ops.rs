@@ -0,0 +1,146 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Label Predicates
+//!
+//! A node has the label its variable was scanned by and every label that label
+//! extends; it has any other label whose table holds its key. `n:Banned` therefore
+//! plans as `n__id IN (SELECT id FROM Banned)`, which DataFusion rewrites into a
+//! semi-join, and labels the graph does not declare never hold.
+//!
+//! Null tests of a whole node or relationship, as on the unmatched side of an OPTIONAL
+//! MATCH, need the planning context too: the entity has no column of its own, so they
+//! test the identity column every scan of it adds.
+
+use super::analysis::PlanningContext;
+use super::expression::to_df_boolean_expr;
+use super::scan_ops::{NODE_ID_PROPERTY, RELATIONSHIP_TYPE_PROPERTY};
+use super::DataFusionPlanner;
+use crate::ast::{BooleanExpression, LabelExpression, ValueExpression};
+use crate::error::{GraphError, Result};
+use datafusion::logical_expr::{ident, in_subquery, lit, Expr, LogicalPlanBuilder};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+impl DataFusionPlanner {
+    /// Convert a predicate, resolving its label predicates against the graph's labels
+    pub(crate) fn predicate_expr(
+        &self,
+        ctx: &PlanningContext,
+        predicate: &BooleanExpression,
+    ) -> Result<Expr> {
+        Ok(match predicate {
+            BooleanExpression::HasLabels { variable, labels } => {
+                self.label_expr(ctx, variable, labels)?
+            }
+            BooleanExpression::And(left, right) => self
+                .predicate_expr(ctx, left)?
+                .and(self.predicate_expr(ctx, right)?),
+            BooleanExpression::Or(left, right) => self
+                .predicate_expr(ctx, left)?
+                .or(self.predicate_expr(ctx, right)?),
+            BooleanExpression::Not(inner) => Expr::Not(Box::new(self.predicate_expr(ctx, inner)?)),
+            BooleanExpression::IsNull(ValueExpression::Variable(variable)) => {
+                match self.entity_column(ctx, variable) {
+                    Some(column) => ident(column).is_null(),
+                    None => to_df_boolean_expr(predicate),
+                }
+            }
+            BooleanExpression::IsNotNull(ValueExpression::Variable(variable)) => {
+                match self.entity_column(ctx, variable) {
+                    Some(column) => ident(column).is_not_null(),
+                    None => to_df_boolean_expr(predicate),
+                }
+            }
+            other => to_df_boolean_expr(other),
+        })
+    }
+
+    /// A column of a node or single relationship variable that is null exactly when the
+    /// variable is
+    fn entity_column(&self, ctx: &PlanningContext, variable: &str) -> Option<String> {
+        let property = if ctx.analysis.var_to_label.contains_key(variable) {
+            NODE_ID_PROPERTY
+        } else if ctx
+            .analysis
+            .relationship_instances
+            .iter()
+            .any(|instance| instance.alias == variable)
+        {
+            RELATIONSHIP_TYPE_PROPERTY
+        } else {
+            return None;
+        };
+        Some(format!("{}__{}", variable, property))
+    }
+
+    fn label_expr(
+        &self,
+        ctx: &PlanningContext,
+        variable: &str,
+        labels: &LabelExpression,
+    ) -> Result<Expr> {
+        Ok(match labels {
+            LabelExpression::Label(label) => self.has_label_expr(ctx, variable, label)?,
+            LabelExpression::Not(inner) => {
+                Expr::Not(Box::new(self.label_expr(ctx, variable, inner)?))
+            }
+            LabelExpression::And(left, right) => self
+                .label_expr(ctx, variable, left)?
+                .and(self.label_expr(ctx, variable, right)?),
+            LabelExpression::Or(left, right) => self
+                .label_expr(ctx, variable, left)?
+                .or(self.label_expr(ctx, variable, right)?),
+        })
+    }
+
+    /// Whether the node bound to `variable` has `label`
+    fn has_label_expr(&self, ctx: &PlanningContext, variable: &str, label: &str) -> Result<Expr> {
+        let scanned = ctx
+            .analysis
+            .var_to_label
+            .get(variable)
+            .and_then(|scanned| self.config.resolve_node_label(scanned))
+            .ok_or_else(|| GraphError::PlanError {
+                message: format!(
+                    "Label predicate on '{}', which is not a node with a known label",
+                    variable
+                ),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            })?;
+        let Some(label) = self.config.resolve_node_label(label) else {
+            return Ok(lit(false));
+        };
+        if self.config.label_with_descendants(label).contains(&scanned) {
+            return Ok(lit(true));
+        }
+
+        let key = |label: &str| {
+            let fields = self
+                .config
+                .get_node_mapping(label)
+                .map(|mapping| mapping.id_fields())
+                .unwrap_or_default();
+            match fields.as_slice() {
+                [field] => Ok(field.to_string()),
+                _ => Err(GraphError::UnsupportedFeature {
+                    feature: format!(
+                        "label predicates on '{}' nodes, which have a composite id",
+                        label
+                    ),
+                    location: snafu::Location::new(file!(), line!(), column!()),
+                }),
+            }
+        };
+        let node_key = self.qualified_node_column(variable, scanned, &key(scanned)?);
+        let member = format!("_{}_has_{}", variable, label);
+        let member_key = self.qualified_node_column(&member, label, &key(label)?);
+
+        let members = self.build_scan(ctx, &member, label, &HashMap::new())?;
+        let members = LogicalPlanBuilder::from(members)
+            .project(vec![ident(member_key)])
+            .and_then(|builder| builder.build())
+            .map_err(|e| self.plan_error("Failed to plan label predicate", e))?;
+        Ok(in_subquery(ident(node_key), Arc::new(members)))
+    }
+}
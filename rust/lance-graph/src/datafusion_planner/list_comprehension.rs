@@ -0,0 +1,266 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! List comprehensions
+//!
+//! DataFusion has no lambda functions, so `[x IN list WHERE p(x) | f(x)]` is evaluated by a
+//! scalar UDF. The predicate and projection are compiled against a schema holding the element
+//! variable plus the outer columns they reference, which are passed to the UDF after the list
+//! and repeated once per element.
+
+use datafusion::arrow::array::{Array, ArrayRef, AsArray, ListArray, RecordBatch, UInt32Array};
+use datafusion::arrow::buffer::OffsetBuffer;
+use datafusion::arrow::compute::{cast, filter, filter_record_batch, prep_null_mask_filter, take};
+use datafusion::arrow::datatypes::{DataType, Field, Schema};
+use datafusion::common::tree_node::{Transformed, TransformedResult, TreeNode};
+use datafusion::common::{exec_err, DFSchema, Result as DFResult};
+use datafusion::execution::context::SessionContext;
+use datafusion::functions::core::expr_fn::get_field;
+use datafusion::logical_expr::{
+    ident, ColumnarValue, Expr, ExprSchemable, ScalarFunctionArgs, ScalarUDF, ScalarUDFImpl,
+    Signature, Volatility,
+};
+use datafusion::physical_expr::PhysicalExpr;
+use std::any::Any;
+use std::collections::BTreeSet;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::{Arc, Mutex};
+
+/// Build the expression evaluating a list comprehension over `list`
+pub(crate) fn list_comprehension(
+    variable: &str,
+    list: Expr,
+    predicate: Option<Expr>,
+    projection: Option<Expr>,
+) -> Expr {
+    // `x.prop` reads a field of a struct element, such as a relationship of a path
+    let prefix = format!("{}__", variable);
+    let read_fields = |expr: Expr| {
+        expr.transform(|expr| match &expr {
+            Expr::Column(column) if column.relation.is_none() => {
+                match column.name.strip_prefix(&prefix) {
+                    Some(field) => Ok(Transformed::yes(get_field(ident(variable), field))),
+                    None => Ok(Transformed::no(expr)),
+                }
+            }
+            _ => Ok(Transformed::no(expr)),
+        })
+        .data()
+    };
+    let predicate = predicate.map(|expr| read_fields(expr.clone()).unwrap_or(expr));
+    let projection = projection.map(|expr| read_fields(expr.clone()).unwrap_or(expr));
+
+    let captured: Vec<String> = predicate
+        .iter()
+        .chain(projection.iter())
+        .flat_map(|expr| expr.column_refs())
+        .filter(|column| column.name != variable)
+        .map(|column| column.name.clone())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    let args = std::iter::once(list)
+        .chain(captured.iter().map(ident))
+        .collect();
+    let udf = ListComprehension {
+        variable: variable.to_string(),
+        captured,
+        predicate,
+        projection,
+        signature: Signature::variadic_any(Volatility::Immutable),
+        compiled: Mutex::new(None),
+    };
+    ScalarUDF::new_from_impl(udf).call(args)
+}
+
+/// Predicate and projection compiled for one set of argument types
+type Compiled = (
+    Vec<DataType>,
+    Option<Arc<dyn PhysicalExpr>>,
+    Option<Arc<dyn PhysicalExpr>>,
+);
+
+#[derive(Debug)]
+struct ListComprehension {
+    variable: String,
+    /// Outer columns read by the predicate or projection, passed after the list argument
+    captured: Vec<String>,
+    predicate: Option<Expr>,
+    projection: Option<Expr>,
+    signature: Signature,
+    compiled: Mutex<Option<Compiled>>,
+}
+
+impl ListComprehension {
+    /// Schema the predicate and projection are evaluated against
+    fn element_schema(&self, arg_types: &[DataType]) -> DFResult<DFSchema> {
+        let element_type = match arg_types.first() {
+            Some(
+                DataType::List(field)
+                | DataType::LargeList(field)
+                | DataType::FixedSizeList(field, _),
+            ) => field.data_type().clone(),
+            Some(DataType::Null) => DataType::Null,
+            other => return exec_err!("List comprehension expects a list, got {other:?}"),
+        };
+        let fields = std::iter::once(Field::new(&self.variable, element_type, true))
+            .chain(
+                self.captured
+                    .iter()
+                    .zip(&arg_types[1..])
+                    .map(|(name, data_type)| Field::new(name, data_type.clone(), true)),
+            )
+            .collect::<Vec<_>>();
+        DFSchema::try_from(Schema::new(fields))
+    }
+
+    fn compile(&self, arg_types: &[DataType]) -> DFResult<Compiled> {
+        let mut compiled = self.compiled.lock().unwrap();
+        if let Some(cached) = compiled.as_ref().filter(|(types, ..)| types == arg_types) {
+            return Ok(cached.clone());
+        }
+        let schema = self.element_schema(arg_types)?;
+        // The session applies the type coercion the analyzer would apply to a plan
+        let ctx = SessionContext::new();
+        let predicate = self
+            .predicate
+            .as_ref()
+            .map(|expr| ctx.create_physical_expr(expr.clone(), &schema))
+            .transpose()?;
+        let projection = self
+            .projection
+            .as_ref()
+            .map(|expr| ctx.create_physical_expr(expr.clone(), &schema))
+            .transpose()?;
+        let result = (arg_types.to_vec(), predicate, projection);
+        *compiled = Some(result.clone());
+        Ok(result)
+    }
+}
+
+impl ScalarUDFImpl for ListComprehension {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "list_comprehension"
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, arg_types: &[DataType]) -> DFResult<DataType> {
+        let schema = self.element_schema(arg_types)?;
+        let element_type = match &self.projection {
+            Some(projection) => projection.get_type(&schema)?,
+            None => schema.field(0).data_type().clone(),
+        };
+        Ok(DataType::new_list(element_type, true))
+    }
+
+    fn schema_name(&self, args: &[Expr]) -> DFResult<String> {
+        let mut name = format!("[{} IN {}", self.variable, args[0].schema_name());
+        if let Some(predicate) = &self.predicate {
+            name.push_str(&format!(" WHERE {}", predicate.schema_name()));
+        }
+        if let Some(projection) = &self.projection {
+            name.push_str(&format!(" | {}", projection.schema_name()));
+        }
+        name.push(']');
+        Ok(name)
+    }
+
+    fn invoke_with_args(&self, args: ScalarFunctionArgs) -> DFResult<ColumnarValue> {
+        let arg_types = args
+            .arg_fields
+            .iter()
+            .map(|field| field.data_type().clone())
+            .collect::<Vec<_>>();
+        let arrays = ColumnarValue::values_to_arrays(&args.args)?;
+        let DataType::List(result_field) = args.return_type() else {
+            return exec_err!("List comprehension must return a list");
+        };
+        if arg_types[0] == DataType::Null {
+            return Ok(ColumnarValue::Array(
+                datafusion::arrow::array::new_null_array(args.return_type(), args.number_rows),
+            ));
+        }
+        let schema = self.element_schema(&arg_types)?;
+        let element_type = schema.field(0).data_type().clone();
+        let lists = cast(&arrays[0], &DataType::new_list(element_type, true))?;
+        let lists = lists.as_list::<i32>();
+        let (_, predicate, projection) = self.compile(&arg_types)?;
+
+        // One row per element, remembering the outer row it came from
+        let mut rows = Vec::new();
+        let mut elements = Vec::new();
+        for row in 0..lists.len() {
+            if lists.is_null(row) {
+                continue;
+            }
+            let offsets = lists.value_offsets();
+            for element in offsets[row]..offsets[row + 1] {
+                rows.push(row as u32);
+                elements.push(element as u32);
+            }
+        }
+        let rows = UInt32Array::from(rows);
+        let columns = std::iter::once(take(lists.values(), &UInt32Array::from(elements), None))
+            .chain(arrays[1..].iter().map(|array| take(array, &rows, None)))
+            .collect::<Result<Vec<ArrayRef>, _>>()?;
+        let mut batch = RecordBatch::try_new(Arc::new(schema.as_arrow().clone()), columns)?;
+        let mut rows: ArrayRef = Arc::new(rows);
+
+        if let Some(predicate) = predicate {
+            let keep = predicate.evaluate(&batch)?.into_array(batch.num_rows())?;
+            // Elements whose predicate is NULL are dropped
+            let keep = match keep.nulls() {
+                Some(_) => prep_null_mask_filter(keep.as_boolean()),
+                None => keep.as_boolean().clone(),
+            };
+            rows = filter(&rows, &keep)?;
+            batch = filter_record_batch(&batch, &keep)?;
+        }
+        let values = match projection {
+            Some(projection) => projection.evaluate(&batch)?.into_array(batch.num_rows())?,
+            None => batch.column(0).clone(),
+        };
+        let values = cast(&values, result_field.data_type())?;
+
+        let mut lengths = vec![0; lists.len()];
+        for row in rows
+            .as_primitive::<datafusion::arrow::datatypes::UInt32Type>()
+            .values()
+        {
+            lengths[*row as usize] += 1;
+        }
+        let result = ListArray::try_new(
+            result_field.clone(),
+            OffsetBuffer::from_lengths(lengths),
+            values,
+            lists.nulls().cloned(),
+        )?;
+        Ok(ColumnarValue::Array(Arc::new(result)))
+    }
+
+    fn equals(&self, other: &dyn ScalarUDFImpl) -> bool {
+        other.as_any().downcast_ref::<Self>().is_some_and(|other| {
+            self.variable == other.variable
+                && self.captured == other.captured
+                && self.predicate == other.predicate
+                && self.projection == other.projection
+        })
+    }
+
+    fn hash_value(&self) -> u64 {
+        let hasher = &mut DefaultHasher::new();
+        self.name().hash(hasher);
+        self.variable.hash(hasher);
+        self.captured.hash(hasher);
+        self.predicate.hash(hasher);
+        self.projection.hash(hasher);
+        hasher.finish()
+    }
+}
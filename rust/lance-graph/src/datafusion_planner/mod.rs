@@ -13,13 +13,24 @@
 //! - Nodes -> Table scans, Relationships -> Linking tables, Traversals -> Joins
 //! - Variable-length paths (`*1..3`) use unrolling: generate fixed-length plans + UNION
 //! - All columns qualified as `{variable}__{column}` to avoid ambiguity
+//!
+//! ## Phase 3: Scan Sharing
+//! - Repeated scans of one table run once per execution and replay their batches
 
 pub mod analysis;
 mod builder;
 mod config_helpers;
-mod expression;
+pub(crate) mod expression;
+mod frontier;
+pub(crate) mod full_text;
 mod join_ops;
+mod label_ops;
+mod list_comprehension;
+mod numeric;
 mod scan_ops;
+mod shared_scan;
+pub(crate) mod temporal;
+mod vector;
 
 #[cfg(test)]
 mod test_fixtures;
@@ -30,6 +41,7 @@ pub use analysis::{PlanningContext, QueryAnalysis, RelationshipInstance};
 use crate::config::GraphConfig;
 use crate::error::Result;
 use crate::logical_plan::LogicalOperator;
+use crate::procedures::ProcedureRegistry;
 use crate::source_catalog::GraphSourceCatalog;
 use datafusion::logical_expr::LogicalPlan;
 use std::sync::Arc;
@@ -43,6 +55,7 @@ pub trait GraphPhysicalPlanner {
 pub struct DataFusionPlanner {
     pub(crate) config: GraphConfig,
     pub(crate) catalog: Option<Arc<dyn GraphSourceCatalog>>,
+    pub(crate) procedures: Arc<ProcedureRegistry>,
 }
 
 impl DataFusionPlanner {
@@ -50,6 +63,7 @@ impl DataFusionPlanner {
         Self {
             config,
             catalog: None,
+            procedures: Arc::new(ProcedureRegistry::default()),
         }
     }
 
@@ -57,9 +71,16 @@ impl DataFusionPlanner {
         Self {
             config,
             catalog: Some(catalog),
+            procedures: Arc::new(ProcedureRegistry::default()),
         }
     }
 
+    /// Resolve CALL clauses in `procedures` instead of the built-in procedures
+    pub fn with_procedures(mut self, procedures: Arc<ProcedureRegistry>) -> Self {
+        self.procedures = procedures;
+        self
+    }
+
     pub fn plan_with_context(
         &self,
         logical_plan: &LogicalOperator,
@@ -68,7 +89,8 @@ impl DataFusionPlanner {
         use crate::source_catalog::{InMemoryCatalog, SimpleTableSource};
 
         // Use the analyze() method to extract metadata
-        let analysis = analysis::analyze(logical_plan)?;
+        let analysis =
+            analysis::analyze_with_max_hops(logical_plan, self.config.max_variable_length_hops)?;
 
         // Build an in-memory catalog from provided datasets (nodes and relationships)
         let mut catalog = InMemoryCatalog::new();
@@ -76,26 +98,36 @@ impl DataFusionPlanner {
         // Register node sources from required datasets
         for label in &analysis.required_datasets {
             if self.config.node_mappings.contains_key(label) {
-                if let Some(batch) = datasets.get(label) {
-                    let src = Arc::new(SimpleTableSource::new(batch.schema()));
-                    catalog = catalog.with_node_source(label, src);
+                for concrete in self.config.label_with_descendants(label) {
+                    if let Some(batch) = datasets.get(concrete) {
+                        let src = Arc::new(SimpleTableSource::new(batch.schema()));
+                        catalog = catalog.with_node_source(concrete, src);
+                    }
                 }
             }
         }
 
         // Register relationship sources from required datasets
         for rel_type in &analysis.required_datasets {
-            if self.config.relationship_mappings.contains_key(rel_type) {
-                if let Some(batch) = datasets.get(rel_type) {
+            if let Some(rel_map) = self.config.relationship_mappings.get(rel_type) {
+                if let (Some(_), Some(source_label)) =
+                    (&rel_map.adjacency_column, &rel_map.source_label)
+                {
+                    if let Some(batch) = datasets.get(source_label) {
+                        let src = Arc::new(SimpleTableSource::new(batch.schema()));
+                        catalog = catalog.with_node_source(source_label, src);
+                    }
+                } else if let Some(batch) = datasets.get(rel_map.table_name()) {
                     let src = Arc::new(SimpleTableSource::new(batch.schema()));
-                    catalog = catalog.with_relationship_source(rel_type, src);
+                    catalog = catalog.with_relationship_source(rel_map.table_name(), src);
                 }
             }
         }
 
         // Plan using a planner bound to this catalog so scans get qualified projections
         let planner_with_cat =
-            DataFusionPlanner::with_catalog(self.config.clone(), Arc::new(catalog));
+            DataFusionPlanner::with_catalog(self.config.clone(), Arc::new(catalog))
+                .with_procedures(self.procedures.clone());
         planner_with_cat.plan(logical_plan)
     }
 
@@ -115,11 +147,15 @@ impl DataFusionPlanner {
 impl GraphPhysicalPlanner for DataFusionPlanner {
     fn plan(&self, logical_plan: &LogicalOperator) -> Result<LogicalPlan> {
         // Phase 1: Analyze query structure
-        let analysis = analysis::analyze(logical_plan)?;
+        let analysis =
+            analysis::analyze_with_max_hops(logical_plan, self.config.max_variable_length_hops)?;
 
         // Phase 2: Build execution plan with context
         let mut ctx = PlanningContext::new(&analysis);
-        self.build_operator(&mut ctx, logical_plan)
+        let plan = self.build_operator(&mut ctx, logical_plan)?;
+
+        // Phase 3: Read each table a query scans repeatedly only once
+        shared_scan::share_repeated_scans(plan, &self.config)
     }
 }
 
@@ -274,4 +310,115 @@ mod tests {
             s
         );
     }
+
+    #[test]
+    fn test_single_variable_predicates_pushed_below_joins() {
+        let planner = DataFusionPlanner::with_catalog(person_knows_config(), make_catalog());
+
+        let expand = LogicalOperator::Expand {
+            input: Box::new(person_scan("a")),
+            source_variable: "a".to_string(),
+            target_variable: "b".to_string(),
+            target_label: "Person".to_string(),
+            relationship_types: vec!["KNOWS".to_string()],
+            direction: RelationshipDirection::Outgoing,
+            relationship_variable: Some("r".to_string()),
+            properties: Default::default(),
+            target_properties: Default::default(),
+        };
+        let age = |variable: &str| {
+            ValueExpression::Property(PropertyRef {
+                variable: variable.into(),
+                property: "age".into(),
+            })
+        };
+        // a.age > 30 AND b.age < 40 AND a.age < b.age
+        let filter = LogicalOperator::Filter {
+            input: Box::new(expand),
+            predicate: BooleanExpression::And(
+                Box::new(BooleanExpression::And(
+                    Box::new(BooleanExpression::Comparison {
+                        left: age("a"),
+                        operator: ComparisonOperator::GreaterThan,
+                        right: ValueExpression::Literal(PropertyValue::Integer(30)),
+                    }),
+                    Box::new(BooleanExpression::Comparison {
+                        left: age("b"),
+                        operator: ComparisonOperator::LessThan,
+                        right: ValueExpression::Literal(PropertyValue::Integer(40)),
+                    }),
+                )),
+                Box::new(BooleanExpression::Comparison {
+                    left: age("a"),
+                    operator: ComparisonOperator::LessThan,
+                    right: age("b"),
+                }),
+            ),
+        };
+        let df_plan = planner.plan(&filter).unwrap();
+        let s = df_plan.display_indent().to_string();
+        let lines: Vec<&str> = s.lines().collect();
+        let join = lines.iter().position(|l| l.contains("Join")).unwrap();
+
+        // Only the predicate reading both variables stays above the joins
+        assert!(lines[0].contains("Filter: a__age < b__age"), "{}", s);
+        let pushed = |predicate: &str| {
+            lines
+                .iter()
+                .position(|l| l.contains(&format!("Filter: {}", predicate)))
+                .unwrap_or_else(|| panic!("missing {}: {}", predicate, s))
+        };
+        assert!(pushed("a__age > Int64(30)") > join, "{}", s);
+        assert!(pushed("b__age < Int64(40)") > join, "{}", s);
+    }
+
+    #[test]
+    fn test_scans_project_only_read_columns() {
+        let planner = DataFusionPlanner::with_catalog(person_knows_config(), make_catalog());
+
+        let expand = LogicalOperator::Expand {
+            input: Box::new(person_scan("a")),
+            source_variable: "a".to_string(),
+            target_variable: "b".to_string(),
+            target_label: "Person".to_string(),
+            relationship_types: vec!["KNOWS".to_string()],
+            direction: RelationshipDirection::Outgoing,
+            relationship_variable: None,
+            properties: Default::default(),
+            target_properties: Default::default(),
+        };
+        let project = LogicalOperator::Project {
+            input: Box::new(expand),
+            projections: vec![crate::logical_plan::ProjectionItem {
+                expression: ValueExpression::Property(PropertyRef::new("b", "name")),
+                alias: None,
+            }],
+        };
+        let df_plan = planner.plan(&project).unwrap();
+
+        // Below the final projection only keys and b.name are left
+        let LogicalPlan::Projection(projection) = &df_plan else {
+            panic!("expected a projection: {}", df_plan.display_indent());
+        };
+        let columns: Vec<String> = projection
+            .input
+            .schema()
+            .fields()
+            .iter()
+            .map(|f| f.name().clone())
+            .filter(|name| !name.contains("__@"))
+            .collect();
+        assert_eq!(
+            columns,
+            vec![
+                "a__id",
+                "knows_1__src_person_id",
+                "knows_1__dst_person_id",
+                "b__id",
+                "b__name"
+            ],
+            "{}",
+            df_plan.display_indent()
+        );
+    }
 }
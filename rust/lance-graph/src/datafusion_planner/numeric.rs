@@ -0,0 +1,50 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Numeric functions
+//!
+//! Cypher's rounding functions, roots, logarithms and `exp` return floats whatever the
+//! type of their argument, so integers are cast first; `abs` keeps the argument's type
+//! and `sign` returns an integer. `round` rounds halves up, towards positive infinity,
+//! unlike DataFusion's, which rounds them away from zero. `toInteger` and `toFloat`
+//! return NULL for values they cannot convert instead of failing the query.
+
+use super::expression::to_df_value_expr;
+use crate::ast::ValueExpression;
+use datafusion::arrow::datatypes::DataType;
+use datafusion::functions::core::expr_fn::coalesce;
+use datafusion::functions::math::expr_fn::{
+    abs, ceil, exp, floor, ln, log10, power, random, signum, sqrt,
+};
+use datafusion::logical_expr::{cast, lit, try_cast, Expr};
+
+/// Translate a numeric function call, or `None` for a name or arity it does not have
+pub(crate) fn numeric_function(name: &str, args: &[ValueExpression]) -> Option<Expr> {
+    let float = |arg: &ValueExpression| cast(to_df_value_expr(arg), DataType::Float64);
+    Some(match (name, args) {
+        ("rand", []) => random(),
+        ("abs", [arg]) => abs(to_df_value_expr(arg)),
+        ("sign", [arg]) => cast(signum(to_df_value_expr(arg)), DataType::Int64),
+        ("ceil", [arg]) => ceil(float(arg)),
+        ("floor", [arg]) => floor(float(arg)),
+        ("round", [arg]) => floor(float(arg) + lit(0.5)),
+        ("round", [arg, precision]) => {
+            let scale = power(lit(10.0), float(precision));
+            floor(float(arg) * scale.clone() + lit(0.5)) / scale
+        }
+        ("sqrt", [arg]) => sqrt(float(arg)),
+        ("exp", [arg]) => exp(float(arg)),
+        ("log", [arg]) => ln(float(arg)),
+        ("log10", [arg]) => log10(float(arg)),
+        // Strings such as '3.7' only parse as floats; integers cast from floats truncate
+        ("tointeger", [arg]) => {
+            let value = to_df_value_expr(arg);
+            coalesce(vec![
+                try_cast(value.clone(), DataType::Int64),
+                try_cast(try_cast(value, DataType::Float64), DataType::Int64),
+            ])
+        }
+        ("tofloat", [arg]) => try_cast(to_df_value_expr(arg), DataType::Float64),
+        _ => return None,
+    })
+}
@@ -6,17 +6,484 @@
 //! Helpers for constructing table scans with qualified columns
 
 use super::analysis::{PlanningContext, RelationshipInstance};
+use crate::adjacency_index::NEIGHBORS_COLUMN;
 use crate::ast::PropertyValue;
-use crate::error::Result;
-use crate::source_catalog::GraphSourceCatalog;
-use datafusion::logical_expr::{col, BinaryExpr, Expr, LogicalPlan, LogicalPlanBuilder, Operator};
+use crate::error::{GraphError, Result};
+use crate::source_catalog::{GraphSourceCatalog, SourceKind};
+use arrow_schema::{DataType, Schema, SchemaRef};
+use datafusion::common::DFSchema;
+use datafusion::execution::context::SessionContext;
+use datafusion::functions::core::expr_fn::{get_field, named_struct};
+use datafusion::functions::string::expr_fn::concat;
+use datafusion::functions_nested::expr_fn::make_array;
+use datafusion::logical_expr::utils::conjunction;
+use datafusion::logical_expr::{
+    cast, col, ident, lit, BinaryExpr, Expr, LogicalPlan, LogicalPlanBuilder, Operator, TableSource,
+};
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
 // Forward declare DataFusionPlanner to add methods to it
 use super::DataFusionPlanner;
 
+/// Pseudo-property holding the type of a bound relationship, `{alias}__@type`; no
+/// property can clash with it, as `@` cannot appear in a property name
+pub(crate) const RELATIONSHIP_TYPE_PROPERTY: &str = "@type";
+
+/// Pseudo-properties of a bound node: its labels (`labels(n)`), id (`id(n)`) and element
+/// id (`elementId(n)`)
+pub(crate) const NODE_LABELS_PROPERTY: &str = "@labels";
+pub(crate) const NODE_ID_PROPERTY: &str = "@id";
+pub(crate) const NODE_ELEMENT_ID_PROPERTY: &str = "@elementId";
+
+/// The type column of a relationship scan
+fn relationship_type_column(rel_instance: &RelationshipInstance) -> Expr {
+    lit(rel_instance.rel_type.as_str()).alias(format!(
+        "{}__{}",
+        rel_instance.alias, RELATIONSHIP_TYPE_PROPERTY
+    ))
+}
+
 impl DataFusionPlanner {
+    /// Restrict a scan of the table of a node label or relationship type to the rows
+    /// of that label or type
+    pub(crate) fn filter_mapped_rows(
+        &self,
+        builder: LogicalPlanBuilder,
+        kind: SourceKind,
+        name: &str,
+        schema: &Schema,
+    ) -> Result<LogicalPlanBuilder> {
+        let (filter, owner) = match kind {
+            SourceKind::Node => (self.label_filter(name, schema)?, "label"),
+            SourceKind::Relationship => (
+                self.relationship_type_filter(name, schema)?,
+                "relationship type",
+            ),
+        };
+        match filter {
+            Some(filter) => builder.filter(filter).map_err(|e| {
+                self.plan_error(
+                    &format!("Failed to filter the rows of {} '{}'", owner, name),
+                    e,
+                )
+            }),
+            None => Ok(builder),
+        }
+    }
+
+    /// Predicate selecting a label's rows of its table: its discriminator when it shares
+    /// the table with other labels, and the mapping's filter conditions
+    fn label_filter(&self, label: &str, schema: &Schema) -> Result<Option<Expr>> {
+        let Some(mapping) = self.config.get_node_mapping(label) else {
+            return Ok(None);
+        };
+        let discriminator = mapping
+            .label_discriminator()
+            .map(|(column, value)| col(column).eq(lit(value)));
+        mapping_filter(
+            discriminator,
+            &format!("filter of node label '{}'", label),
+            mapping.filter_conditions.as_deref(),
+            schema,
+        )
+    }
+
+    /// Predicate selecting a relationship type's rows of its table: its discriminator
+    /// when it shares the table with other types, and the mapping's filter conditions
+    fn relationship_type_filter(&self, rel_type: &str, schema: &Schema) -> Result<Option<Expr>> {
+        let Some(mapping) = self.config.get_relationship_mapping(rel_type) else {
+            return Ok(None);
+        };
+        let discriminator = mapping
+            .type_discriminator()
+            .map(|(column, value)| col(column).eq(lit(value)));
+        mapping_filter(
+            discriminator,
+            &format!("filter of relationship type '{}'", rel_type),
+            mapping.filter_conditions.as_deref(),
+            schema,
+        )
+    }
+
+    /// Expression reading a node property from a scan of the label's table
+    ///
+    /// Computed properties plan their SQL expression; other properties read the column
+    /// behind them, after property aliases.
+    pub(crate) fn node_property_expr(
+        &self,
+        label: &str,
+        property: &str,
+        schema: &Schema,
+    ) -> Result<Expr> {
+        match self.config.get_node_mapping(label) {
+            Some(mapping) => match mapping.computed_properties.get(property) {
+                Some(sql) => parse_computed_property(property, sql, schema),
+                None => Ok(col(mapping.column_for(property))),
+            },
+            None => Ok(col(property)),
+        }
+    }
+
+    /// Computed properties of a label, projected as `variable__property`
+    pub(crate) fn node_computed_columns(
+        &self,
+        variable: &str,
+        label: &str,
+        schema: &Schema,
+    ) -> Result<Vec<Expr>> {
+        match self.config.get_node_mapping(label) {
+            Some(mapping) => computed_columns(&mapping.computed_properties, variable, schema),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Identity columns of a scan of `label`'s table, computed from its key columns
+    ///
+    /// A node's labels are the label of its table and the labels that label extends. Its
+    /// id is its key, a struct of the key columns for a composite key, and its element
+    /// id the label and key joined by colons (`Person:42`), which stays unique across
+    /// labels and stable across queries.
+    pub(crate) fn node_identity_columns(&self, variable: &str, label: &str) -> Vec<Expr> {
+        let keys = self
+            .config
+            .get_node_mapping(label)
+            .map(|mapping| mapping.id_fields())
+            .unwrap_or_default();
+        let column = |property: &str| format!("{}__{}", variable, property);
+
+        let labels = make_array(
+            self.config
+                .label_with_ancestors(label)
+                .into_iter()
+                .map(lit)
+                .collect(),
+        );
+        let id = match keys.as_slice() {
+            [key] => ident(*key),
+            keys => named_struct(
+                keys.iter()
+                    .flat_map(|key| [lit(*key), ident(*key)])
+                    .collect(),
+            ),
+        };
+        let element_id = concat(
+            std::iter::once(lit(label))
+                .chain(
+                    keys.iter()
+                        .flat_map(|key| [lit(":"), cast(ident(*key), DataType::Utf8)]),
+                )
+                .collect(),
+        );
+        vec![
+            labels.alias(column(NODE_LABELS_PROPERTY)),
+            id.alias(column(NODE_ID_PROPERTY)),
+            element_id.alias(column(NODE_ELEMENT_ID_PROPERTY)),
+        ]
+    }
+
+    /// Qualified output name (`variable__property`) of a node column
+    pub(crate) fn qualified_node_column(
+        &self,
+        variable: &str,
+        label: &str,
+        column: &str,
+    ) -> String {
+        let property = self
+            .config
+            .get_node_mapping(label)
+            .map_or(column, |mapping| mapping.property_for(column));
+        format!("{}__{}", variable, property)
+    }
+
+    /// Expression reading a relationship property from a scan of the type's table
+    pub(crate) fn relationship_property_expr(
+        &self,
+        rel_type: &str,
+        property: &str,
+        schema: &Schema,
+    ) -> Result<Expr> {
+        match self.config.get_relationship_mapping(rel_type) {
+            Some(mapping) => match mapping.computed_properties.get(property) {
+                Some(sql) => parse_computed_property(property, sql, schema),
+                None => Ok(col(mapping.column_for(property))),
+            },
+            None => Ok(col(property)),
+        }
+    }
+
+    /// Computed properties of a relationship type, projected as `alias__property`
+    pub(crate) fn relationship_computed_columns(
+        &self,
+        alias: &str,
+        rel_type: &str,
+        schema: &Schema,
+    ) -> Result<Vec<Expr>> {
+        match self.config.get_relationship_mapping(rel_type) {
+            Some(mapping) => computed_columns(&mapping.computed_properties, alias, schema),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Qualified output name (`alias__property`) of a relationship column
+    pub(crate) fn qualified_relationship_column(
+        &self,
+        alias: &str,
+        rel_type: &str,
+        column: &str,
+    ) -> String {
+        let property = self
+            .config
+            .get_relationship_mapping(rel_type)
+            .map_or(column, |mapping| mapping.property_for(column));
+        format!("{}__{}", alias, property)
+    }
+
+    /// Table source backing a relationship type: its edge table, or the source label's
+    /// node table when edges live in an adjacency-list column
+    ///
+    /// Edge tables with a current materialized adjacency index are read from the index.
+    pub(crate) fn relationship_table_source(
+        &self,
+        catalog: &dyn GraphSourceCatalog,
+        rel_type: &str,
+    ) -> Option<Arc<dyn TableSource>> {
+        let Some(rel_map) = self.config.get_relationship_mapping(rel_type) else {
+            return catalog.relationship_source(rel_type);
+        };
+        match (&rel_map.adjacency_column, &rel_map.source_label) {
+            (Some(_), Some(source_label)) => catalog.node_source(source_label),
+            _ => catalog
+                .adjacency_source(rel_type)
+                .or_else(|| catalog.relationship_source(rel_map.table_name())),
+        }
+    }
+
+    /// List column holding the edges of a relationship type's source rows, if they are
+    /// stored as adjacency lists
+    fn adjacency_list_column(&self, rel_type: &str, schema: &Schema) -> Option<String> {
+        let rel_map = self.config.get_relationship_mapping(rel_type)?;
+        if let Some(column) = &rel_map.adjacency_column {
+            return Some(column.clone());
+        }
+        let materialized = self
+            .catalog
+            .as_ref()
+            .is_some_and(|catalog| catalog.adjacency_source(rel_type).is_some());
+        (materialized && schema.field_with_name(NEIGHBORS_COLUMN).is_ok())
+            .then(|| NEIGHBORS_COLUMN.to_string())
+    }
+
+    /// Scan a relationship type's rows, one per edge, along with their schema
+    ///
+    /// Adjacency-list layouts and materialized adjacency indexes unnest the list column
+    /// and flatten its struct fields next to the node id columns, so the rows look like
+    /// those of an edge table.
+    fn scan_relationship_rows(
+        &self,
+        rel_type: &str,
+        rel_source: Arc<dyn TableSource>,
+    ) -> Result<(LogicalPlanBuilder, SchemaRef)> {
+        let schema = rel_source.schema();
+        let builder = LogicalPlanBuilder::scan(rel_type, rel_source, None).map_err(|e| {
+            self.plan_error(&format!("Failed to scan relationship '{}'", rel_type), e)
+        })?;
+        let Some(rel_map) = self.config.get_relationship_mapping(rel_type) else {
+            return Ok((builder, schema));
+        };
+        let Some(list_column) = self.adjacency_list_column(rel_type, &schema) else {
+            return Ok((builder, schema));
+        };
+        let list_column = &list_column;
+
+        let edge_fields = match schema.field_with_name(list_column).map(|f| f.data_type()) {
+            Ok(DataType::List(item) | DataType::LargeList(item)) => match item.data_type() {
+                DataType::Struct(fields) => fields.clone(),
+                _ => return Err(adjacency_error(rel_type, list_column)),
+            },
+            _ => return Err(adjacency_error(rel_type, list_column)),
+        };
+        let node_keys = rel_map.source_id_fields();
+        if let Some(clash) = edge_fields
+            .iter()
+            .find(|field| node_keys.contains(&field.name().as_str()))
+        {
+            return Err(GraphError::ConfigError {
+                message: format!(
+                    "Adjacency column '{}' of relationship '{}' has field '{}', which clashes with a source id column",
+                    list_column,
+                    rel_type,
+                    clash.name()
+                ),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            });
+        }
+
+        let keep: Vec<Expr> = node_keys
+            .iter()
+            .map(|key| col(*key))
+            .chain(std::iter::once(col(list_column.as_str())))
+            .collect();
+        let flatten: Vec<Expr> = node_keys
+            .iter()
+            .map(|key| col(*key))
+            .chain(edge_fields.iter().map(|field| {
+                get_field(col(list_column.as_str()), field.name().as_str()).alias(field.name())
+            }))
+            .collect();
+        let builder = builder
+            .project(keep)
+            .and_then(|b| b.unnest_column(list_column.as_str()))
+            .and_then(|b| b.project(flatten))
+            .map_err(|e| self.plan_error("Failed to unnest adjacency column", e))?;
+        let schema = Arc::new(builder.schema().as_arrow().clone());
+        Ok((builder, schema))
+    }
+
+    /// Whether the catalog holds a table for the edges of `rel_type`
+    pub(crate) fn has_relationship_table(&self, rel_type: &str) -> bool {
+        self.catalog
+            .as_ref()
+            .and_then(|catalog| self.relationship_table_source(catalog.as_ref(), rel_type))
+            .is_some()
+    }
+
+    /// Plan of every edge of a relationship type, one row per edge with the columns of
+    /// an edge table, read from the planner's catalog
+    pub(crate) fn relationship_edges(&self, rel_type: &str) -> Result<LogicalPlan> {
+        let source = self
+            .catalog
+            .as_ref()
+            .and_then(|catalog| self.relationship_table_source(catalog.as_ref(), rel_type))
+            .ok_or_else(|| GraphError::PlanError {
+                message: format!("No table source found for relationship: {}", rel_type),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            })?;
+        let (builder, schema) = self.scan_relationship_rows(rel_type, source)?;
+        self.filter_mapped_rows(builder, SourceKind::Relationship, rel_type, &schema)?
+            .build()
+            .map_err(|e| self.plan_error("Failed to scan relationship edges", e))
+    }
+
+    /// Finish a qualified scan of `variable`: filter it by the predicates pushed into it,
+    /// then drop the columns the query never reads
+    pub(crate) fn finish_scan(
+        &self,
+        ctx: &mut PlanningContext,
+        variable: &str,
+        scan: LogicalPlan,
+    ) -> Result<LogicalPlan> {
+        let scan = self.apply_pushed_predicates(ctx, variable, scan)?;
+        self.prune_scan_columns(ctx, variable, scan)
+    }
+
+    /// Filter a qualified scan of `variable` by the pushed predicates on it whose columns
+    /// it has, recording which ones it applied
+    fn apply_pushed_predicates(
+        &self,
+        ctx: &mut PlanningContext,
+        variable: &str,
+        scan: LogicalPlan,
+    ) -> Result<LogicalPlan> {
+        let mut exprs = Vec::new();
+        for pushed in ctx
+            .pushed_predicates
+            .iter_mut()
+            .filter(|pushed| pushed.variable == variable)
+        {
+            let scanned = pushed
+                .expr
+                .column_refs()
+                .iter()
+                .all(|column| scan.schema().has_column_with_unqualified_name(&column.name));
+            if scanned {
+                pushed.applied = true;
+                exprs.push(pushed.expr.clone());
+            } else {
+                pushed.missed = true;
+            }
+        }
+        let Some(predicate) = conjunction(exprs) else {
+            return Ok(scan);
+        };
+        LogicalPlanBuilder::from(scan)
+            .filter(predicate)
+            .and_then(|builder| builder.build())
+            .map_err(|e| {
+                self.plan_error(
+                    &format!("Failed to push predicates into the scan of '{}'", variable),
+                    e,
+                )
+            })
+    }
+
+    /// Project only the columns of a qualified scan of `variable` that hold properties
+    /// the query reads, keys or identity columns, so the table scan reads no others
+    fn prune_scan_columns(
+        &self,
+        ctx: &PlanningContext,
+        variable: &str,
+        scan: LogicalPlan,
+    ) -> Result<LogicalPlan> {
+        let Some(used) = ctx.analysis.used_properties.get(variable) else {
+            return Ok(scan);
+        };
+
+        // Keys are read by joins both as named by the table and after property aliases
+        let mut keys: HashSet<&str> = HashSet::new();
+        if let Some(mapping) = ctx
+            .analysis
+            .var_to_label
+            .get(variable)
+            .and_then(|label| self.config.get_node_mapping(label))
+        {
+            for key in mapping.id_fields() {
+                keys.extend([key, mapping.property_for(key)]);
+            }
+        }
+        for instance in ctx
+            .analysis
+            .relationship_instances
+            .iter()
+            .filter(|instance| instance.alias == variable)
+        {
+            if let Some(mapping) = self.config.get_relationship_mapping(&instance.rel_type) {
+                for key in mapping
+                    .source_id_fields()
+                    .into_iter()
+                    .chain(mapping.target_id_fields())
+                {
+                    keys.extend([key, mapping.property_for(key)]);
+                }
+            }
+        }
+
+        let prefix = format!("{}__", variable);
+        let fields = scan.schema().fields();
+        let kept: Vec<Expr> = fields
+            .iter()
+            .filter(|field| match field.name().strip_prefix(&prefix) {
+                Some(property) => {
+                    property.starts_with('@') || used.contains(property) || keys.contains(property)
+                }
+                None => true,
+            })
+            .map(|field| ident(field.name()))
+            .collect();
+        if kept.len() == fields.len() {
+            return Ok(scan);
+        }
+        LogicalPlanBuilder::from(scan)
+            .project(kept)
+            .and_then(|builder| builder.build())
+            .map_err(|e| {
+                self.plan_error(
+                    &format!("Failed to prune the columns of the scan of '{}'", variable),
+                    e,
+                )
+            })
+    }
+
     /// Build a qualified node scan with property filters and column aliasing
     pub(crate) fn build_scan(
         &self,
@@ -27,13 +494,18 @@ impl DataFusionPlanner {
     ) -> Result<LogicalPlan> {
         // Try to use catalog if available
         if let Some(cat) = &self.catalog {
+            if self.config.has_sub_labels(label) {
+                return self.build_label_hierarchy_scan(cat, label, variable, properties);
+            }
             // Catalog exists - check if label is registered
             if let Some(source) = cat.node_source(label) {
                 // Get schema before moving source
                 let schema = source.schema();
-                let mut builder = LogicalPlanBuilder::scan(label, source, None).map_err(|e| {
+                let builder = LogicalPlanBuilder::scan(label, source, None).map_err(|e| {
                     self.plan_error(&format!("Failed to scan node source '{}'", label), e)
                 })?;
+                let mut builder =
+                    self.filter_mapped_rows(builder, SourceKind::Node, label, &schema)?;
 
                 // Combine property filters into single predicate for efficiency
                 if !properties.is_empty() {
@@ -43,13 +515,13 @@ impl DataFusionPlanner {
                             let lit_expr = super::expression::to_df_value_expr(
                                 &crate::ast::ValueExpression::Literal(v.clone()),
                             );
-                            Expr::BinaryExpr(BinaryExpr {
-                                left: Box::new(col(k)),
+                            Ok(Expr::BinaryExpr(BinaryExpr {
+                                left: Box::new(self.node_property_expr(label, k, &schema)?),
                                 op: Operator::Eq,
                                 right: Box::new(lit_expr),
-                            })
+                            }))
                         })
-                        .collect();
+                        .collect::<Result<_>>()?;
 
                     // Combine with AND if multiple filters
                     let combined_filter = filter_exprs
@@ -69,19 +541,17 @@ impl DataFusionPlanner {
                 }
 
                 // Create qualified column aliases: variable__property
-                // Optimization: Pre-allocate string capacity to reduce allocations
-                let qualified_exprs: Vec<Expr> = schema
+                let mut qualified_exprs: Vec<Expr> = schema
                     .fields()
                     .iter()
                     .map(|field| {
-                        let mut qualified_name =
-                            String::with_capacity(variable.len() + field.name().len() + 2);
-                        qualified_name.push_str(variable);
-                        qualified_name.push_str("__");
-                        qualified_name.push_str(field.name());
+                        let qualified_name =
+                            self.qualified_node_column(variable, label, field.name());
                         col(field.name()).alias(&qualified_name)
                     })
                     .collect();
+                qualified_exprs.extend(self.node_computed_columns(variable, label, &schema)?);
+                qualified_exprs.extend(self.node_identity_columns(variable, label));
 
                 // Add projection with qualified aliases
                 builder = builder
@@ -118,28 +588,20 @@ impl DataFusionPlanner {
     }
 
     /// Build a qualified relationship scan with property filters
-    pub(crate) fn build_relationship_scan(
+    /// Keep the relationship rows whose properties equal the pattern's property map
+    fn filter_relationship_properties(
         &self,
-        rel_instance: &RelationshipInstance,
-        rel_source: Arc<dyn datafusion::logical_expr::TableSource>,
+        mut rel_builder: LogicalPlanBuilder,
+        rel_type: &str,
         relationship_properties: &HashMap<String, PropertyValue>,
-    ) -> Result<LogicalPlan> {
-        let rel_schema = rel_source.schema();
-        let mut rel_builder = LogicalPlanBuilder::scan(&rel_instance.rel_type, rel_source, None)
-            .map_err(|e| {
-                self.plan_error(
-                    &format!("Failed to scan relationship '{}'", rel_instance.rel_type),
-                    e,
-                )
-            })?;
-
-        // Apply relationship property filters (e.g., -[r {since: 2020}]->)
+        rel_schema: &SchemaRef,
+    ) -> Result<LogicalPlanBuilder> {
         for (k, v) in relationship_properties.iter() {
             let lit_expr = super::expression::to_df_value_expr(
                 &crate::ast::ValueExpression::Literal(v.clone()),
             );
             let filter_expr = Expr::BinaryExpr(BinaryExpr {
-                left: Box::new(col(k)),
+                left: Box::new(self.relationship_property_expr(rel_type, k, rel_schema)?),
                 op: Operator::Eq,
                 right: Box::new(lit_expr),
             });
@@ -150,16 +612,51 @@ impl DataFusionPlanner {
                 )
             })?;
         }
+        Ok(rel_builder)
+    }
+
+    pub(crate) fn build_relationship_scan(
+        &self,
+        rel_instance: &RelationshipInstance,
+        rel_source: Arc<dyn datafusion::logical_expr::TableSource>,
+        relationship_properties: &HashMap<String, PropertyValue>,
+    ) -> Result<LogicalPlan> {
+        let (rel_builder, rel_schema) =
+            self.scan_relationship_rows(&rel_instance.rel_type, rel_source)?;
+        let mut rel_builder = self.filter_mapped_rows(
+            rel_builder,
+            SourceKind::Relationship,
+            &rel_instance.rel_type,
+            &rel_schema,
+        )?;
+
+        // Apply relationship property filters (e.g., -[r {since: 2020}]->)
+        rel_builder = self.filter_relationship_properties(
+            rel_builder,
+            &rel_instance.rel_type,
+            relationship_properties,
+            &rel_schema,
+        )?;
 
         // Use unique alias from rel_instance to avoid column conflicts
-        let rel_qualified_exprs: Vec<Expr> = rel_schema
+        let mut rel_qualified_exprs: Vec<Expr> = rel_schema
             .fields()
             .iter()
             .map(|field| {
-                let qualified_name = format!("{}__{}", rel_instance.alias, field.name());
+                let qualified_name = self.qualified_relationship_column(
+                    &rel_instance.alias,
+                    &rel_instance.rel_type,
+                    field.name(),
+                );
                 col(field.name()).alias(&qualified_name)
             })
             .collect();
+        rel_qualified_exprs.extend(self.relationship_computed_columns(
+            &rel_instance.alias,
+            &rel_instance.rel_type,
+            &rel_schema,
+        )?);
+        rel_qualified_exprs.push(relationship_type_column(rel_instance));
 
         rel_builder
             .project(rel_qualified_exprs)
@@ -190,9 +687,16 @@ impl DataFusionPlanner {
             if let Some(source) = cat.node_source(source_label) {
                 let source_schema = source.schema();
                 for field in source_schema.fields() {
-                    let qualified_name = format!("{}__{}", source_variable, field.name());
+                    let qualified_name =
+                        self.qualified_node_column(source_variable, source_label, field.name());
                     expected.insert(qualified_name);
                 }
+                if let Some(mapping) = self.config.get_node_mapping(source_label) {
+                    for property in mapping.computed_properties.keys() {
+                        expected.insert(format!("{}__{}", source_variable, property));
+                    }
+                }
+                expected.extend(identity_column_names(source_variable));
             }
         }
 
@@ -201,9 +705,16 @@ impl DataFusionPlanner {
             if let Some(target) = cat.node_source(target_label) {
                 let target_schema = target.schema();
                 for field in target_schema.fields() {
-                    let qualified_name = format!("{}__{}", target_variable, field.name());
+                    let qualified_name =
+                        self.qualified_node_column(target_variable, target_label, field.name());
                     expected.insert(qualified_name);
                 }
+                if let Some(mapping) = self.config.get_node_mapping(target_label) {
+                    for property in mapping.computed_properties.keys() {
+                        expected.insert(format!("{}__{}", target_variable, property));
+                    }
+                }
+                expected.extend(identity_column_names(target_variable));
             }
         }
 
@@ -215,9 +726,10 @@ impl DataFusionPlanner {
         &self,
         catalog: &Arc<dyn GraphSourceCatalog>,
         rel_instance: &RelationshipInstance,
+        relationship_properties: &HashMap<String, PropertyValue>,
     ) -> Result<LogicalPlan> {
-        let rel_source = catalog
-            .relationship_source(&rel_instance.rel_type)
+        let rel_source = self
+            .relationship_table_source(catalog.as_ref(), &rel_instance.rel_type)
             .ok_or_else(|| crate::error::GraphError::ConfigError {
                 message: format!(
                     "No table source found for relationship: {}",
@@ -226,21 +738,39 @@ impl DataFusionPlanner {
                 location: snafu::Location::new(file!(), line!(), column!()),
             })?;
 
-        let rel_schema = rel_source.schema();
-        let rel_builder = LogicalPlanBuilder::scan(&rel_instance.rel_type, rel_source, None)
-            .map_err(|e| crate::error::GraphError::PlanError {
-                message: format!("Failed to scan relationship: {}", e),
-                location: snafu::Location::new(file!(), line!(), column!()),
-            })?;
+        let (rel_builder, rel_schema) =
+            self.scan_relationship_rows(&rel_instance.rel_type, rel_source)?;
+        let mut rel_builder = self.filter_mapped_rows(
+            rel_builder,
+            SourceKind::Relationship,
+            &rel_instance.rel_type,
+            &rel_schema,
+        )?;
+        rel_builder = self.filter_relationship_properties(
+            rel_builder,
+            &rel_instance.rel_type,
+            relationship_properties,
+            &rel_schema,
+        )?;
 
-        let rel_qualified_exprs: Vec<Expr> = rel_schema
+        let mut rel_qualified_exprs: Vec<Expr> = rel_schema
             .fields()
             .iter()
             .map(|field| {
-                let qualified_name = format!("{}__{}", rel_instance.alias, field.name());
+                let qualified_name = self.qualified_relationship_column(
+                    &rel_instance.alias,
+                    &rel_instance.rel_type,
+                    field.name(),
+                );
                 col(field.name()).alias(&qualified_name)
             })
             .collect();
+        rel_qualified_exprs.extend(self.relationship_computed_columns(
+            &rel_instance.alias,
+            &rel_instance.rel_type,
+            &rel_schema,
+        )?);
+        rel_qualified_exprs.push(relationship_type_column(rel_instance));
 
         rel_builder
             .project(rel_qualified_exprs)
@@ -262,6 +792,101 @@ impl DataFusionPlanner {
         target_label: &str,
         target_variable: &str,
         target_properties: &HashMap<String, PropertyValue>,
+    ) -> Result<LogicalPlan> {
+        if self.config.has_sub_labels(target_label) {
+            return self.build_label_hierarchy_scan(
+                catalog,
+                target_label,
+                target_variable,
+                target_properties,
+            );
+        }
+        self.build_single_label_scan(catalog, target_label, target_variable, target_properties)
+    }
+
+    /// Qualified scan of a label and every label extending it, unioned by column name
+    ///
+    /// Sub-label id columns are renamed to the queried label's id names so joins see one
+    /// key; columns missing from a sub-label's table come back as nulls.
+    pub(crate) fn build_label_hierarchy_scan(
+        &self,
+        catalog: &Arc<dyn GraphSourceCatalog>,
+        label: &str,
+        variable: &str,
+        properties: &HashMap<String, PropertyValue>,
+    ) -> Result<LogicalPlan> {
+        let parent_ids = self
+            .config
+            .get_node_mapping(label)
+            .map(|mapping| mapping.id_fields())
+            .unwrap_or_default();
+
+        let mut branches = Vec::new();
+        for concrete in self.config.label_with_descendants(label) {
+            if catalog.node_source(concrete).is_none() {
+                continue;
+            }
+            let scan = self.build_single_label_scan(catalog, concrete, variable, properties)?;
+            let concrete_ids = self
+                .config
+                .get_node_mapping(concrete)
+                .map(|mapping| mapping.id_fields())
+                .unwrap_or_default();
+            if concrete_ids == parent_ids {
+                branches.push(scan);
+                continue;
+            }
+
+            let renames: HashMap<String, String> = concrete_ids
+                .iter()
+                .zip(&parent_ids)
+                .map(|(from, to)| {
+                    (
+                        format!("{}__{}", variable, from),
+                        format!("{}__{}", variable, to),
+                    )
+                })
+                .collect();
+            let exprs: Vec<Expr> = scan
+                .schema()
+                .fields()
+                .iter()
+                .map(|field| match renames.get(field.name()) {
+                    Some(to) => ident(field.name()).alias(to),
+                    None => ident(field.name()),
+                })
+                .collect();
+            branches.push(
+                LogicalPlanBuilder::from(scan)
+                    .project(exprs)
+                    .and_then(|builder| builder.build())
+                    .map_err(|e| self.plan_error("Failed to align sub-label id columns", e))?,
+            );
+        }
+
+        let mut branches = branches.into_iter();
+        let first = branches.next().ok_or_else(|| GraphError::ConfigError {
+            message: format!(
+                "No table source found for node label '{}' or any label extending it",
+                label
+            ),
+            location: snafu::Location::new(file!(), line!(), column!()),
+        })?;
+        branches.try_fold(first, |union_plan, branch| {
+            LogicalPlanBuilder::from(union_plan)
+                .union_by_name(branch)
+                .and_then(|builder| builder.build())
+                .map_err(|e| self.plan_error("Failed to union sub-label scans", e))
+        })
+    }
+
+    /// Qualified scan of exactly one label's table with property filters
+    fn build_single_label_scan(
+        &self,
+        catalog: &Arc<dyn GraphSourceCatalog>,
+        target_label: &str,
+        target_variable: &str,
+        target_properties: &HashMap<String, PropertyValue>,
     ) -> Result<LogicalPlan> {
         let target_source = catalog.node_source(target_label).ok_or_else(|| {
             crate::error::GraphError::ConfigError {
@@ -271,11 +896,19 @@ impl DataFusionPlanner {
         })?;
 
         let target_schema = target_source.schema();
-        let mut target_builder = LogicalPlanBuilder::scan(target_label, target_source, None)
-            .map_err(|e| crate::error::GraphError::PlanError {
-                message: format!("Failed to scan target node: {}", e),
-                location: snafu::Location::new(file!(), line!(), column!()),
+        let target_builder =
+            LogicalPlanBuilder::scan(target_label, target_source, None).map_err(|e| {
+                crate::error::GraphError::PlanError {
+                    message: format!("Failed to scan target node: {}", e),
+                    location: snafu::Location::new(file!(), line!(), column!()),
+                }
             })?;
+        let mut target_builder = self.filter_mapped_rows(
+            target_builder,
+            SourceKind::Node,
+            target_label,
+            &target_schema,
+        )?;
 
         // Apply target property filters
         for (k, v) in target_properties.iter() {
@@ -283,7 +916,7 @@ impl DataFusionPlanner {
                 &crate::ast::ValueExpression::Literal(v.clone()),
             );
             let filter_expr = Expr::BinaryExpr(BinaryExpr {
-                left: Box::new(col(k)),
+                left: Box::new(self.node_property_expr(target_label, k, &target_schema)?),
                 op: Operator::Eq,
                 right: Box::new(lit_expr),
             });
@@ -295,14 +928,21 @@ impl DataFusionPlanner {
             })?;
         }
 
-        let target_qualified_exprs: Vec<Expr> = target_schema
+        let mut target_qualified_exprs: Vec<Expr> = target_schema
             .fields()
             .iter()
             .map(|field| {
-                let qualified_name = format!("{}__{}", target_variable, field.name());
+                let qualified_name =
+                    self.qualified_node_column(target_variable, target_label, field.name());
                 col(field.name()).alias(&qualified_name)
             })
             .collect();
+        target_qualified_exprs.extend(self.node_computed_columns(
+            target_variable,
+            target_label,
+            &target_schema,
+        )?);
+        target_qualified_exprs.extend(self.node_identity_columns(target_variable, target_label));
 
         target_builder
             .project(target_qualified_exprs)
@@ -318,6 +958,80 @@ impl DataFusionPlanner {
     }
 }
 
+/// Names of the identity columns of a node variable
+fn identity_column_names(variable: &str) -> impl Iterator<Item = String> + '_ {
+    [
+        NODE_LABELS_PROPERTY,
+        NODE_ID_PROPERTY,
+        NODE_ELEMENT_ID_PROPERTY,
+    ]
+    .into_iter()
+    .map(move |property| format!("{}__{}", variable, property))
+}
+
+fn adjacency_error(rel_type: &str, column: &str) -> GraphError {
+    GraphError::ConfigError {
+        message: format!(
+            "Adjacency column '{}' of relationship '{}' must be a list of structs",
+            column, rel_type
+        ),
+        location: snafu::Location::new(file!(), line!(), column!()),
+    }
+}
+
+/// Plan a computed property's SQL expression against the columns of its table
+fn parse_computed_property(property: &str, sql: &str, schema: &Schema) -> Result<Expr> {
+    parse_mapping_sql(&format!("computed property '{}' =", property), sql, schema)
+}
+
+/// Plan a SQL expression of a mapping against the columns of its table; `what` names
+/// the expression in errors
+fn parse_mapping_sql(what: &str, sql: &str, schema: &Schema) -> Result<Expr> {
+    let invalid = |e: datafusion::error::DataFusionError| GraphError::ConfigError {
+        message: format!("Invalid {} `{}`: {}", what, sql, e),
+        location: snafu::Location::new(file!(), line!(), column!()),
+    };
+    let df_schema = DFSchema::try_from(schema.clone()).map_err(invalid)?;
+    SessionContext::new()
+        .parse_sql_expr(sql, &df_schema)
+        .map_err(invalid)
+}
+
+/// A discriminator predicate and-ed with the planned filter conditions of a mapping
+fn mapping_filter(
+    discriminator: Option<Expr>,
+    what: &str,
+    conditions: Option<&str>,
+    schema: &Schema,
+) -> Result<Option<Expr>> {
+    let conditions = conditions
+        .map(|sql| parse_mapping_sql(what, sql, schema))
+        .transpose()?;
+    Ok(match (discriminator, conditions) {
+        (Some(discriminator), Some(conditions)) => Some(discriminator.and(conditions)),
+        (discriminator, conditions) => discriminator.or(conditions),
+    })
+}
+
+/// Project every computed property as `qualifier__property`, in name order
+fn computed_columns(
+    computed: &HashMap<String, String>,
+    qualifier: &str,
+    schema: &Schema,
+) -> Result<Vec<Expr>> {
+    let mut properties: Vec<&String> = computed.keys().collect();
+    properties.sort();
+    properties
+        .into_iter()
+        .map(|property| {
+            Ok(
+                parse_computed_property(property, &computed[property], schema)?
+                    .alias(format!("{}__{}", qualifier, property)),
+            )
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use crate::ast::{PropertyRef, PropertyValue, ValueExpression};
@@ -410,7 +1124,9 @@ mod tests {
             min_length: Some(2),
             max_length: Some(2),
             relationship_variable: None,
+            properties: std::collections::HashMap::new(),
             target_properties: Default::default(),
+            path_variable: None,
         };
 
         let result = planner.plan(&var_expand);
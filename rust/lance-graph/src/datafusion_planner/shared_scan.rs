@@ -0,0 +1,292 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Shared scans for tables a query reads more than once
+//!
+//! Friend-of-friend patterns, symmetric patterns and UNION branches lower to several
+//! scans of the same label or relationship table. [`share_repeated_scans`] points all of
+//! them at one [`SharedScanTable`], which runs each distinct scan (projection, filters
+//! and limit) once per execution and replays its batches to every consumer.
+//!
+//! Shared scans are buffered in memory for the length of one execution; tables read
+//! once are left untouched and stream as before.
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex, Weak};
+
+use arrow_array::RecordBatch;
+use arrow_schema::SchemaRef;
+use async_trait::async_trait;
+use datafusion::catalog::Session;
+use datafusion::common::tree_node::{Transformed, TreeNodeRecursion};
+use datafusion::common::TableReference;
+use datafusion::datasource::{provider_as_source, source_as_provider, TableProvider, TableType};
+use datafusion::error::DataFusionError;
+use datafusion::execution::TaskContext;
+use datafusion::logical_expr::{Expr, LogicalPlan, TableProviderFilterPushDown};
+use datafusion::physical_expr::EquivalenceProperties;
+use datafusion::physical_plan::execution_plan::{Boundedness, EmissionType};
+use datafusion::physical_plan::metrics::MetricsSet;
+use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
+use datafusion::physical_plan::{
+    collect, DisplayAs, DisplayFormatType, ExecutionPlan, Partitioning, PlanProperties,
+    SendableRecordBatchStream,
+};
+use futures::{StreamExt, TryStreamExt};
+use tokio::sync::OnceCell;
+
+use crate::config::GraphConfig;
+use crate::error::{GraphError, Result};
+
+/// Point repeated scans of one label or relationship table at a single shared table
+///
+/// Only tables the graph configuration maps are shared; scans the planner builds
+/// itself, such as procedure results, are left alone.
+pub(crate) fn share_repeated_scans(plan: LogicalPlan, config: &GraphConfig) -> Result<LogicalPlan> {
+    // Scans are named as the planner names them, which normalizes unquoted names
+    let scanned = |name: &str| TableReference::from(name).table().to_string();
+    let mut tables: HashMap<String, usize> = HashMap::new();
+    for label in config.node_mappings.keys() {
+        tables.insert(scanned(label), 0);
+    }
+    for (rel_type, mapping) in &config.relationship_mappings {
+        tables.insert(scanned(rel_type), 0);
+        tables.insert(scanned(mapping.table_name()), 0);
+    }
+
+    plan.apply_with_subqueries(|node| {
+        if let LogicalPlan::TableScan(scan) = node {
+            if let Some(count) = tables.get_mut(scan.table_name.table()) {
+                *count += 1;
+            }
+        }
+        Ok(TreeNodeRecursion::Continue)
+    })
+    .map_err(plan_error)?;
+    tables.retain(|_, count| *count > 1);
+    if tables.is_empty() {
+        return Ok(plan);
+    }
+
+    let mut shared: HashMap<String, Arc<SharedScanTable>> = HashMap::new();
+    plan.transform_up_with_subqueries(|node| {
+        let LogicalPlan::TableScan(scan) = &node else {
+            return Ok(Transformed::no(node));
+        };
+        let name = scan.table_name.table();
+        if !tables.contains_key(name) {
+            return Ok(Transformed::no(node));
+        }
+        // Views and sources without a provider are expanded or planned elsewhere
+        let table = match shared.get(name) {
+            Some(table) => table.clone(),
+            None => match source_as_provider(&scan.source) {
+                Ok(inner) => {
+                    let table = Arc::new(SharedScanTable::new(inner));
+                    shared.insert(name.to_string(), table.clone());
+                    table
+                }
+                Err(_) => return Ok(Transformed::no(node)),
+            },
+        };
+        let LogicalPlan::TableScan(mut scan) = node else {
+            unreachable!()
+        };
+        scan.source = provider_as_source(table);
+        Ok(Transformed::yes(LogicalPlan::TableScan(scan)))
+    })
+    .map(|transformed| transformed.data)
+    .map_err(plan_error)
+}
+
+fn plan_error(e: DataFusionError) -> GraphError {
+    GraphError::PlanError {
+        message: format!("Failed to share repeated scans: {}", e),
+        location: snafu::Location::new(file!(), line!(), column!()),
+    }
+}
+
+/// Projection, filters and limit of one scan of a shared table
+type ScanKey = (Option<Vec<usize>>, Vec<Expr>, Option<usize>);
+
+/// One scan of the inner table and, once some consumer runs it, its batches
+struct SharedScan {
+    plan: Arc<dyn ExecutionPlan>,
+    batches: OnceCell<Vec<RecordBatch>>,
+}
+
+/// A table whose identical scans within one physical plan run only once
+///
+/// Scans are remembered only while a physical plan using them is alive, so every
+/// execution reads the inner table afresh.
+pub(crate) struct SharedScanTable {
+    inner: Arc<dyn TableProvider>,
+    scans: Mutex<HashMap<ScanKey, Weak<SharedScan>>>,
+}
+
+impl fmt::Debug for SharedScanTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedScanTable").finish_non_exhaustive()
+    }
+}
+
+impl SharedScanTable {
+    pub fn new(inner: Arc<dyn TableProvider>) -> Self {
+        Self {
+            inner,
+            scans: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<ScanKey, Weak<SharedScan>>> {
+        self.scans.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[async_trait]
+impl TableProvider for SharedScanTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.inner.schema()
+    }
+
+    fn table_type(&self) -> TableType {
+        self.inner.table_type()
+    }
+
+    fn supports_filters_pushdown(
+        &self,
+        filters: &[&Expr],
+    ) -> std::result::Result<Vec<TableProviderFilterPushDown>, DataFusionError> {
+        self.inner.supports_filters_pushdown(filters)
+    }
+
+    async fn scan(
+        &self,
+        state: &dyn Session,
+        projection: Option<&Vec<usize>>,
+        filters: &[Expr],
+        limit: Option<usize>,
+    ) -> std::result::Result<Arc<dyn ExecutionPlan>, DataFusionError> {
+        let key = (projection.cloned(), filters.to_vec(), limit);
+        let live = self.lock().get(&key).and_then(Weak::upgrade);
+        let scan = match live {
+            Some(scan) => scan,
+            None => {
+                // Planned without holding the lock; a scan planned concurrently is replaced
+                let plan = self.inner.scan(state, projection, filters, limit).await?;
+                let scan = Arc::new(SharedScan {
+                    plan,
+                    batches: OnceCell::new(),
+                });
+                let mut scans = self.lock();
+                scans.retain(|_, scan| scan.strong_count() > 0);
+                scans.insert(key, Arc::downgrade(&scan));
+                scan
+            }
+        };
+        Ok(Arc::new(SharedScanExec::new(scan)))
+    }
+}
+
+/// Replays the batches of a shared scan, running it for the first consumer
+struct SharedScanExec {
+    scan: Arc<SharedScan>,
+    properties: PlanProperties,
+}
+
+impl SharedScanExec {
+    fn new(scan: Arc<SharedScan>) -> Self {
+        let properties = PlanProperties::new(
+            EquivalenceProperties::new(scan.plan.schema()),
+            Partitioning::UnknownPartitioning(1),
+            EmissionType::Final,
+            Boundedness::Bounded,
+        );
+        Self { scan, properties }
+    }
+}
+
+impl fmt::Debug for SharedScanExec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedScanExec").finish_non_exhaustive()
+    }
+}
+
+impl DisplayAs for SharedScanExec {
+    fn fmt_as(&self, t: DisplayFormatType, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "SharedScanExec: consumers={}, scan=",
+            Arc::strong_count(&self.scan)
+        )?;
+        self.scan.plan.fmt_as(t, f)
+    }
+}
+
+impl ExecutionPlan for SharedScanExec {
+    fn name(&self) -> &str {
+        "SharedScanExec"
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn properties(&self) -> &PlanProperties {
+        &self.properties
+    }
+
+    fn children(&self) -> Vec<&Arc<dyn ExecutionPlan>> {
+        vec![]
+    }
+
+    /// The metrics of the shared scan, such as the fragments it pruned
+    fn metrics(&self) -> Option<MetricsSet> {
+        self.scan.plan.metrics()
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> std::result::Result<Arc<dyn ExecutionPlan>, DataFusionError> {
+        if children.is_empty() {
+            Ok(self)
+        } else {
+            Err(DataFusionError::Internal(
+                "SharedScanExec has no children".to_string(),
+            ))
+        }
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> std::result::Result<SendableRecordBatchStream, DataFusionError> {
+        if partition != 0 {
+            return Err(DataFusionError::Internal(format!(
+                "SharedScanExec has one partition, asked for {}",
+                partition
+            )));
+        }
+        let scan = self.scan.clone();
+        let batches = futures::stream::once(async move {
+            let batches = scan
+                .batches
+                .get_or_try_init(|| collect(scan.plan.clone(), context))
+                .await?;
+            Ok::<_, DataFusionError>(futures::stream::iter(batches.clone()).map(Ok))
+        })
+        .try_flatten();
+        Ok(Box::pin(RecordBatchStreamAdapter::new(
+            self.scan.plan.schema(),
+            batches,
+        )))
+    }
+}
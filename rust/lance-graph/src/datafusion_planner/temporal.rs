@@ -0,0 +1,260 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Temporal functions
+//!
+//! Cypher's temporal values map onto Arrow types: a `date` is a `Date32`, a
+//! `localdatetime` a nanosecond timestamp without time zone, a `datetime` a nanosecond
+//! timestamp in UTC (the type of DataFusion's `now()`) and a `duration` an
+//! `Interval(MonthDayNano)`. Strings are converted by Arrow's casts, except durations,
+//! whose ISO 8601 form (`P1Y2M3DT4H`) is read here. Maps of components are assembled from
+//! DataFusion's date functions.
+
+use super::expression::to_df_value_expr;
+use crate::ast::{PropertyValue, ValueExpression};
+use datafusion::arrow::datatypes::{DataType, IntervalMonthDayNano, IntervalUnit, TimeUnit};
+use datafusion::functions::datetime::expr_fn::{current_date, make_date, now, to_timestamp_nanos};
+use datafusion::logical_expr::{cast, lit, Expr};
+use datafusion::scalar::ScalarValue;
+
+/// Keys of the maps accepted by `date()`
+pub(crate) const DATE_COMPONENTS: &[&str] = &["year", "month", "day"];
+
+/// Keys of the maps accepted by `localdatetime()` and `datetime()`
+pub(crate) const DATETIME_COMPONENTS: &[&str] = &[
+    "year",
+    "month",
+    "day",
+    "hour",
+    "minute",
+    "second",
+    "millisecond",
+    "microsecond",
+    "nanosecond",
+];
+
+/// Keys of the maps accepted by `duration()`
+pub(crate) const DURATION_COMPONENTS: &[&str] = &[
+    "years",
+    "months",
+    "weeks",
+    "days",
+    "hours",
+    "minutes",
+    "seconds",
+    "milliseconds",
+    "microseconds",
+    "nanoseconds",
+];
+
+const NANOS_PER_SECOND: i64 = 1_000_000_000;
+const NANOS_PER_DAY: i64 = 86_400 * NANOS_PER_SECOND;
+
+/// Translate a call to the temporal function `name` (lowercased), or `None` when it is
+/// not one or its arguments do not fit it
+pub(super) fn temporal_function(name: &str, args: &[ValueExpression]) -> Option<Expr> {
+    match (name, args) {
+        ("date", []) => Some(current_date()),
+        ("date", [ValueExpression::Map(entries)]) => date_from_components(entries),
+        ("date", [value]) => Some(cast(to_df_value_expr(value), DataType::Date32)),
+        ("localdatetime", []) => Some(cast(now(), local_timestamp())),
+        ("localdatetime", [ValueExpression::Map(entries)]) => timestamp_from_components(entries),
+        ("localdatetime", [value]) => Some(cast(to_df_value_expr(value), local_timestamp())),
+        ("datetime", []) => Some(now()),
+        ("datetime", [ValueExpression::Map(entries)]) => {
+            timestamp_from_components(entries).map(|local| cast(local, utc_timestamp()))
+        }
+        ("datetime", [value]) => Some(cast(to_df_value_expr(value), utc_timestamp())),
+        ("duration", [ValueExpression::Literal(PropertyValue::String(text))]) => {
+            Some(interval(parse_duration(text)))
+        }
+        ("duration", [ValueExpression::Map(entries)]) => {
+            duration_from_components(entries).map(|duration| interval(Some(duration)))
+        }
+        ("duration.between", [from, to]) => Some(cast(
+            to_df_value_expr(to) - to_df_value_expr(from),
+            DataType::Interval(IntervalUnit::MonthDayNano),
+        )),
+        _ => None,
+    }
+}
+
+fn local_timestamp() -> DataType {
+    DataType::Timestamp(TimeUnit::Nanosecond, None)
+}
+
+fn utc_timestamp() -> DataType {
+    DataType::Timestamp(TimeUnit::Nanosecond, Some("+00:00".into()))
+}
+
+/// Value of the component `key` of a temporal map, as an Int64
+fn component(entries: &[(String, ValueExpression)], key: &str) -> Option<Expr> {
+    entries
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case(key))
+        .map(|(_, value)| cast(to_df_value_expr(value), DataType::Int64))
+}
+
+/// `date({year, month, day})`, with month and day defaulting to 1
+fn date_from_components(entries: &[(String, ValueExpression)]) -> Option<Expr> {
+    Some(make_date(
+        component(entries, "year")?,
+        component(entries, "month").unwrap_or_else(|| lit(1i64)),
+        component(entries, "day").unwrap_or_else(|| lit(1i64)),
+    ))
+}
+
+/// `localdatetime({year, ..., nanosecond})`, counted in nanoseconds from the epoch
+fn timestamp_from_components(entries: &[(String, ValueExpression)]) -> Option<Expr> {
+    let days = cast(
+        cast(date_from_components(entries)?, DataType::Int32),
+        DataType::Int64,
+    );
+    let nanos = [
+        ("hour", 3_600 * NANOS_PER_SECOND),
+        ("minute", 60 * NANOS_PER_SECOND),
+        ("second", NANOS_PER_SECOND),
+        ("millisecond", 1_000_000),
+        ("microsecond", 1_000),
+        ("nanosecond", 1),
+    ]
+    .into_iter()
+    .filter_map(|(key, scale)| component(entries, key).map(|value| value * lit(scale)))
+    .fold(days * lit(NANOS_PER_DAY), |total, part| total + part);
+    Some(to_timestamp_nanos(vec![nanos]))
+}
+
+/// `duration({years, ..., nanoseconds})`; the components have to be integer literals
+fn duration_from_components(entries: &[(String, ValueExpression)]) -> Option<IntervalMonthDayNano> {
+    let mut duration = IntervalMonthDayNano::new(0, 0, 0);
+    for (key, value) in entries {
+        let ValueExpression::Literal(PropertyValue::Integer(amount)) = value else {
+            return None;
+        };
+        add_component(&mut duration, &key.to_lowercase(), *amount)?;
+    }
+    Some(duration)
+}
+
+fn add_component(duration: &mut IntervalMonthDayNano, unit: &str, amount: i64) -> Option<()> {
+    let (months, days, nanos) = match unit {
+        "years" => (amount.checked_mul(12)?, 0, 0),
+        "months" => (amount, 0, 0),
+        "weeks" => (0, amount.checked_mul(7)?, 0),
+        "days" => (0, amount, 0),
+        "hours" => (0, 0, amount.checked_mul(3_600 * NANOS_PER_SECOND)?),
+        "minutes" => (0, 0, amount.checked_mul(60 * NANOS_PER_SECOND)?),
+        "seconds" => (0, 0, amount.checked_mul(NANOS_PER_SECOND)?),
+        "milliseconds" => (0, 0, amount.checked_mul(1_000_000)?),
+        "microseconds" => (0, 0, amount.checked_mul(1_000)?),
+        "nanoseconds" => (0, 0, amount),
+        _ => return None,
+    };
+    duration.months = duration.months.checked_add(i32::try_from(months).ok()?)?;
+    duration.days = duration.days.checked_add(i32::try_from(days).ok()?)?;
+    duration.nanoseconds = duration.nanoseconds.checked_add(nanos)?;
+    Some(())
+}
+
+/// Interval literal of a duration, NULL when it could not be read
+fn interval(duration: Option<IntervalMonthDayNano>) -> Expr {
+    lit(ScalarValue::IntervalMonthDayNano(duration))
+}
+
+/// Read an ISO 8601 duration such as `P1Y2M10DT2H30M1.5S` or `P2W`
+pub(crate) fn parse_duration(text: &str) -> Option<IntervalMonthDayNano> {
+    let rest = text.strip_prefix(['P', 'p'])?;
+    let (date_part, time_part) = match rest.split_once(['T', 't']) {
+        Some((date_part, time_part)) if !time_part.is_empty() => (date_part, Some(time_part)),
+        Some(_) => return None,
+        None => (rest, None),
+    };
+    if date_part.is_empty() && time_part.is_none() {
+        return None;
+    }
+
+    let mut duration = IntervalMonthDayNano::new(0, 0, 0);
+    for (amount, designator) in duration_fields(date_part)? {
+        let unit = match designator {
+            'Y' => "years",
+            'M' => "months",
+            'W' => "weeks",
+            'D' => "days",
+            _ => return None,
+        };
+        add_component(&mut duration, unit, amount.parse().ok()?)?;
+    }
+    for (amount, designator) in time_part.map_or(Some(Vec::new()), duration_fields)? {
+        match designator {
+            'H' => add_component(&mut duration, "hours", amount.parse().ok()?)?,
+            'M' => add_component(&mut duration, "minutes", amount.parse().ok()?)?,
+            'S' => {
+                let (seconds, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+                add_component(&mut duration, "seconds", seconds.parse().ok()?)?;
+                if fraction.len() > 9 || !fraction.chars().all(|c| c.is_ascii_digit()) {
+                    return None;
+                }
+                if !fraction.is_empty() {
+                    let nanos: i64 = format!("{:0<9}", fraction).parse().ok()?;
+                    let nanos = if seconds.starts_with('-') {
+                        -nanos
+                    } else {
+                        nanos
+                    };
+                    add_component(&mut duration, "nanoseconds", nanos)?;
+                }
+            }
+            _ => return None,
+        }
+    }
+    Some(duration)
+}
+
+/// Split `1Y2M` into its amounts and their (uppercased) designators
+fn duration_fields(text: &str) -> Option<Vec<(&str, char)>> {
+    let mut fields = Vec::new();
+    let mut start = 0;
+    for (i, c) in text.char_indices() {
+        if c.is_ascii_alphabetic() {
+            let amount = &text[start..i];
+            if amount.is_empty() {
+                return None;
+            }
+            fields.push((amount, c.to_ascii_uppercase()));
+            start = i + 1;
+        }
+    }
+    (start == text.len()).then_some(fields)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(
+            parse_duration("P1Y2M10DT2H30M1.5S"),
+            Some(IntervalMonthDayNano::new(
+                14,
+                10,
+                (2 * 3_600 + 30 * 60 + 1) * NANOS_PER_SECOND + 500_000_000
+            ))
+        );
+        assert_eq!(
+            parse_duration("P2W"),
+            Some(IntervalMonthDayNano::new(0, 14, 0))
+        );
+        assert_eq!(
+            parse_duration("PT36H"),
+            Some(IntervalMonthDayNano::new(
+                0,
+                0,
+                36 * 3_600 * NANOS_PER_SECOND
+            ))
+        );
+        for invalid in ["", "P", "1D", "PT", "P1H", "PT1D", "P1.5D", "PXD"] {
+            assert_eq!(parse_duration(invalid), None, "{}", invalid);
+        }
+    }
+}
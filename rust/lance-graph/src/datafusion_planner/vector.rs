@@ -0,0 +1,176 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Vector distances
+//!
+//! `vector.distance(a, b [, metric])` is evaluated by a scalar UDF over whole batches, so
+//! `ORDER BY vector.distance(m.embedding, $q) LIMIT 10` ranks the rows of a traversal
+//! inside the plan's top-k sort. Vectors are lists or fixed-size lists of numbers; the
+//! distance is a float, NULL when either vector is NULL, computed as by `vector.knn`.
+
+use super::expression::to_df_value_expr;
+use crate::ast::ValueExpression;
+use crate::vector_search::{distance, parse_metric};
+use datafusion::arrow::array::{Array, AsArray, Float32Array, Float32Builder, ListArray};
+use datafusion::arrow::compute::cast;
+use datafusion::arrow::datatypes::{DataType, Float32Type};
+use datafusion::common::{exec_err, Result as DFResult};
+use datafusion::logical_expr::{
+    ColumnarValue, Expr, ScalarFunctionArgs, ScalarUDF, ScalarUDFImpl, Signature, Volatility,
+};
+use datafusion::scalar::ScalarValue;
+use lance_linalg::distance::DistanceType;
+use std::any::Any;
+use std::sync::Arc;
+
+/// Translate `vector.distance`, or `None` for an arity it does not have
+pub(crate) fn vector_distance(args: &[ValueExpression]) -> Option<Expr> {
+    if !matches!(args.len(), 2 | 3) {
+        return None;
+    }
+    let udf = VectorDistance {
+        signature: Signature::variadic_any(Volatility::Immutable),
+    };
+    Some(ScalarUDF::new_from_impl(udf).call(args.iter().map(to_df_value_expr).collect()))
+}
+
+#[derive(Debug)]
+struct VectorDistance {
+    signature: Signature,
+}
+
+/// One vector argument: a vector per row, or one shared by every row
+struct Vectors {
+    lists: ListArray,
+    values: Float32Array,
+    shared: bool,
+}
+
+impl Vectors {
+    fn try_new(value: &ColumnarValue) -> DFResult<Self> {
+        let (array, shared) = match value {
+            ColumnarValue::Array(array) => (array.clone(), false),
+            ColumnarValue::Scalar(scalar) => (scalar.to_array_of_size(1)?, true),
+        };
+        let lists = cast(&array, &DataType::new_list(DataType::Float32, true))?
+            .as_list::<i32>()
+            .clone();
+        let values = lists.values().as_primitive::<Float32Type>().clone();
+        Ok(Self {
+            lists,
+            values,
+            shared,
+        })
+    }
+
+    fn get(&self, row: usize) -> Option<&[f32]> {
+        let row = if self.shared { 0 } else { row };
+        if self.lists.is_null(row) {
+            return None;
+        }
+        let offsets = self.lists.value_offsets();
+        Some(&self.values.values()[offsets[row] as usize..offsets[row + 1] as usize])
+    }
+}
+
+impl ScalarUDFImpl for VectorDistance {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "vector_distance"
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> DFResult<DataType> {
+        Ok(DataType::Float32)
+    }
+
+    fn invoke_with_args(&self, args: ScalarFunctionArgs) -> DFResult<ColumnarValue> {
+        let metric = match args.args.get(2) {
+            None => DistanceType::L2,
+            Some(ColumnarValue::Scalar(
+                ScalarValue::Utf8(Some(name))
+                | ScalarValue::LargeUtf8(Some(name))
+                | ScalarValue::Utf8View(Some(name)),
+            )) => match parse_metric(name) {
+                Some(metric) => metric,
+                None => {
+                    return exec_err!(
+                        "vector.distance: unknown metric '{name}', expected 'l2', 'cosine' or 'dot'"
+                    )
+                }
+            },
+            Some(_) => return exec_err!("vector.distance takes its metric as a string constant"),
+        };
+        let left = Vectors::try_new(&args.args[0])?;
+        let right = Vectors::try_new(&args.args[1])?;
+
+        let mut distances = Float32Builder::with_capacity(args.number_rows);
+        for row in 0..args.number_rows {
+            match (left.get(row), right.get(row)) {
+                (Some(a), Some(b)) if a.len() != b.len() => {
+                    return exec_err!(
+                        "vector.distance of vectors of {} and {} dimensions",
+                        a.len(),
+                        b.len()
+                    )
+                }
+                (Some(a), Some(b)) => distances.append_value(distance(metric, a, b)),
+                _ => distances.append_null(),
+            }
+        }
+        Ok(ColumnarValue::Array(Arc::new(distances.finish())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::arrow::array::FixedSizeListArray;
+    use datafusion::arrow::datatypes::Field;
+
+    #[test]
+    fn test_distances_against_a_shared_query() {
+        let vectors = FixedSizeListArray::from_iter_primitive::<Float32Type, _, _>(
+            vec![
+                Some(vec![Some(1.0), Some(0.0)]),
+                None,
+                Some(vec![Some(0.0), Some(3.0)]),
+            ],
+            2,
+        );
+        let query = ScalarValue::List(Arc::new(
+            ListArray::from_iter_primitive::<Float32Type, _, _>(vec![Some(vec![
+                Some(0.0),
+                Some(1.0),
+            ])]),
+        ));
+        let udf = VectorDistance {
+            signature: Signature::variadic_any(Volatility::Immutable),
+        };
+        let args = ScalarFunctionArgs {
+            arg_fields: vec![
+                Arc::new(Field::new("v", vectors.data_type().clone(), true)),
+                Arc::new(Field::new("q", query.data_type(), true)),
+            ],
+            args: vec![
+                ColumnarValue::Array(Arc::new(vectors)),
+                ColumnarValue::Scalar(query),
+            ],
+            number_rows: 3,
+            return_field: Arc::new(Field::new("d", DataType::Float32, true)),
+        };
+        let ColumnarValue::Array(result) = udf.invoke_with_args(args).unwrap() else {
+            panic!("Expected an array");
+        };
+        let result = result.as_primitive::<Float32Type>();
+        assert_eq!(result.value(0), 2.0);
+        assert!(result.is_null(1));
+        assert_eq!(result.value(2), 4.0);
+    }
+}
@@ -0,0 +1,142 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Typed results.
+//!
+//! [`CypherQuery::query_as`] runs a query and deserializes each row into a type
+//! implementing `serde::Deserialize`, with one field per returned column:
+//!
+//! ```ignore
+//! #[derive(serde::Deserialize)]
+//! struct Friend {
+//!     name: String,
+//!     age: Option<i64>,
+//! }
+//!
+//! let friends: Vec<Friend> = CypherQuery::new(
+//!     "MATCH (:Person {name: 'Alice'})-[:KNOWS]->(f:Person) RETURN f.name AS name, f.age AS age",
+//! )?
+//! .with_config(config)
+//! .query_as(Arc::new(catalog))
+//! .await?;
+//! ```
+//!
+//! Columns are matched to fields by name, so unaliased columns such as `f.name` need
+//! `#[serde(rename = "f.name")]`. Rows are decoded through their JSON form: numbers and
+//! booleans map to Rust numbers and `bool`, lists to `Vec`, structs (such as paths)
+//! to nested structs, and dates and timestamps to strings. Null values map to `None`.
+
+use arrow_array::RecordBatch;
+use serde::de::DeserializeOwned;
+
+use crate::error::{GraphError, Result};
+use crate::query::CypherQuery;
+
+/// Deserialize every row of `batch` into a `T`
+pub fn from_record_batch<T: DeserializeOwned>(batch: &RecordBatch) -> Result<Vec<T>> {
+    if batch.num_rows() == 0 {
+        return Ok(Vec::new());
+    }
+    let conversion_error = |e: arrow::error::ArrowError| GraphError::ExecutionError {
+        message: format!("Failed to convert result rows: {}", e),
+        location: snafu::Location::new(file!(), line!(), column!()),
+    };
+    let mut writer = arrow::json::ArrayWriter::new(Vec::new());
+    writer.write(batch).map_err(conversion_error)?;
+    writer.finish().map_err(conversion_error)?;
+    let rows: Vec<serde_json::Value> =
+        serde_json::from_slice(&writer.into_inner()).map_err(|e| GraphError::ExecutionError {
+            message: format!("Failed to convert result rows: {}", e),
+            location: snafu::Location::new(file!(), line!(), column!()),
+        })?;
+
+    rows.into_iter()
+        .enumerate()
+        .map(|(i, row)| {
+            serde_path_to_error::deserialize(row).map_err(|e| GraphError::ExecutionError {
+                message: format!("Failed to deserialize row {}: {}", i, e),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            })
+        })
+        .collect()
+}
+
+impl CypherQuery {
+    /// Execute the query against a catalog of scannable table providers and
+    /// deserialize each returned row into a `T`
+    ///
+    /// See [`crate::deserialize`] for how columns map to fields.
+    pub async fn query_as<T: DeserializeOwned>(
+        &self,
+        catalog: std::sync::Arc<dyn crate::source_catalog::GraphSourceCatalog>,
+    ) -> Result<Vec<T>> {
+        let batch = self.execute_with_catalog(catalog).await?;
+        from_record_batch(&batch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::{Int64Array, StringArray};
+    use arrow_schema::{DataType, Field, Schema};
+    use serde::Deserialize;
+    use std::sync::Arc;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Person {
+        #[serde(rename = "p.name")]
+        name: String,
+        age: Option<i64>,
+    }
+
+    fn batch() -> RecordBatch {
+        RecordBatch::try_new(
+            Arc::new(Schema::new(vec![
+                Field::new("p.name", DataType::Utf8, false),
+                Field::new("age", DataType::Int64, true),
+            ])),
+            vec![
+                Arc::new(StringArray::from(vec!["Alice", "Bob"])),
+                Arc::new(Int64Array::from(vec![Some(28), None])),
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_rows_deserialize_into_structs() {
+        let people: Vec<Person> = from_record_batch(&batch()).unwrap();
+        assert_eq!(
+            people,
+            vec![
+                Person {
+                    name: "Alice".to_string(),
+                    age: Some(28)
+                },
+                Person {
+                    name: "Bob".to_string(),
+                    age: None
+                },
+            ]
+        );
+
+        let empty: Vec<Person> = from_record_batch(&batch().slice(0, 0)).unwrap();
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn test_errors_name_the_row_and_field() {
+        #[derive(Debug, Deserialize)]
+        #[allow(dead_code)]
+        struct Misnamed {
+            #[serde(rename = "p.name")]
+            name: i64,
+        }
+        let err = from_record_batch::<Misnamed>(&batch())
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("row 0"), "{}", err);
+        assert!(err.contains("p.name"), "{}", err);
+    }
+}
@@ -0,0 +1,332 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Automatic catalog discovery from a directory of Lance datasets.
+//!
+//! [`DirectoryCatalog`] lists a local or object-store directory and registers every
+//! `<name>.lance` dataset it finds. Each dataset is classified as a node label or a
+//! relationship type and a matching [`NodeMapping`] /
+//! [`RelationshipMapping`](crate::config::RelationshipMapping) is
+//! derived from its schema.
+//!
+//! Classification is driven by an optional sidecar file ([`DirectoryCatalog::SIDECAR_FILE`])
+//! holding a serialized [`GraphConfig`]. Datasets named in the sidecar use its
+//! mappings; every other dataset falls back to the naming convention:
+//!
+//! - upper snake case names (`KNOWS`, `WORKS_FOR`) are relationship types whose
+//!   endpoints are inferred by [`infer_relationship_mapping`];
+//! - anything else (`Person`, `Company`) is a node label keyed by the config's
+//!   `default_node_id_field`, or by [`infer_node_mapping`] when the dataset has no
+//!   such column.
+
+use std::sync::Arc;
+
+use arrow_schema::SchemaRef;
+use datafusion::logical_expr::TableSource;
+use lance::io::ObjectStore;
+
+use crate::adjacency_index::AdjacencyIndex;
+use crate::config::{GraphConfig, NodeMapping};
+use crate::error::{GraphError, Result};
+use crate::lance_catalog::LanceCatalog;
+use crate::mapping_inference::{infer_node_mapping, infer_relationship_mapping};
+use crate::source_catalog::{GraphSourceCatalog, SourceDescription, SourceStatistics};
+
+pub use crate::mapping_inference::RELATIONSHIP_ENDPOINT_FIELDS;
+
+const LANCE_EXTENSION: &str = ".lance";
+
+/// A Lance catalog and graph configuration discovered from a directory.
+#[derive(Debug, Clone)]
+pub struct DirectoryCatalog {
+    catalog: LanceCatalog,
+    config: GraphConfig,
+}
+
+impl DirectoryCatalog {
+    /// Name of the optional sidecar config file inside the scanned directory.
+    pub const SIDECAR_FILE: &'static str = "graph.json";
+
+    /// Scan `uri` and build a catalog from the Lance datasets it contains.
+    pub async fn open(uri: &str) -> Result<Self> {
+        let (store, base) = ObjectStore::from_uri(uri).await?;
+
+        let sidecar_path = base.child(Self::SIDECAR_FILE);
+        let sidecar = if store.exists(&sidecar_path).await? {
+            let bytes = store.read_one_all(&sidecar_path).await?;
+            let config: GraphConfig =
+                serde_json::from_slice(&bytes).map_err(|e| GraphError::ConfigError {
+                    message: format!("Invalid {} in '{}': {}", Self::SIDECAR_FILE, uri, e),
+                    location: snafu::Location::new(file!(), line!(), column!()),
+                })?;
+            Some(config)
+        } else {
+            None
+        };
+
+        let mut entries = store.read_dir(base).await?;
+        entries.sort();
+
+        let mut catalog = LanceCatalog::new();
+        let mut builder = GraphConfig::builder();
+        if let Some(sidecar) = &sidecar {
+            builder = builder
+                .with_default_node_id_field(sidecar.default_node_id_field.clone())
+                .with_default_relationship_type_field(
+                    sidecar.default_relationship_type_field.clone(),
+                );
+        }
+        let default_id_field = sidecar
+            .as_ref()
+            .map(|c| c.default_node_id_field.clone())
+            .unwrap_or_else(|| GraphConfig::default().default_node_id_field);
+
+        for entry in entries {
+            let Some(name) = entry.strip_suffix(LANCE_EXTENSION) else {
+                continue;
+            };
+            let dataset_uri = format!("{}/{}", uri.trim_end_matches('/'), entry);
+
+            if let Some(mapping) = sidecar.as_ref().and_then(|c| c.node_mappings.get(name)) {
+                catalog = catalog.with_node_uri(name, &dataset_uri).await?;
+                builder = builder.with_node_mapping(mapping.clone());
+            } else if let Some(mapping) = sidecar
+                .as_ref()
+                .and_then(|c| c.relationship_mappings.get(name))
+            {
+                catalog = catalog.with_relationship_uri(name, &dataset_uri).await?;
+                builder = builder.with_relationship_mapping(mapping.clone());
+            } else if is_relationship_name(name) {
+                catalog = catalog.with_relationship_uri(name, &dataset_uri).await?;
+                let schema = catalog.relationship_schema(name).unwrap();
+                builder =
+                    builder.with_relationship_mapping(infer_relationship_mapping(name, &schema)?);
+            } else {
+                catalog = catalog.with_node_uri(name, &dataset_uri).await?;
+                let schema = catalog.node_schema(name).unwrap();
+                builder =
+                    builder.with_node_mapping(node_mapping(name, &default_id_field, &schema)?);
+            }
+        }
+
+        Ok(Self {
+            catalog,
+            config: builder.build()?,
+        })
+    }
+
+    /// Graph configuration derived from the discovered datasets.
+    pub fn config(&self) -> &GraphConfig {
+        &self.config
+    }
+
+    /// Underlying Lance catalog.
+    pub fn catalog(&self) -> &LanceCatalog {
+        &self.catalog
+    }
+
+    /// Register the materialized adjacency lists of a relationship type, replacing any
+    /// registered before.
+    pub fn with_adjacency_index(mut self, index: AdjacencyIndex) -> Self {
+        self.catalog = self.catalog.with_adjacency_index(index);
+        self
+    }
+
+    /// Split into the Lance catalog and graph configuration.
+    pub fn into_parts(self) -> (LanceCatalog, GraphConfig) {
+        (self.catalog, self.config)
+    }
+}
+
+impl GraphSourceCatalog for DirectoryCatalog {
+    fn node_source(&self, label: &str) -> Option<Arc<dyn TableSource>> {
+        self.catalog.node_source(label)
+    }
+
+    fn relationship_source(&self, rel_type: &str) -> Option<Arc<dyn TableSource>> {
+        self.catalog.relationship_source(rel_type)
+    }
+
+    fn list_node_labels(&self) -> Vec<String> {
+        self.catalog.list_node_labels()
+    }
+
+    fn list_relationship_types(&self) -> Vec<String> {
+        self.catalog.list_relationship_types()
+    }
+
+    fn describe(&self, name: &str) -> Option<SourceDescription> {
+        self.catalog
+            .describe(name)
+            .map(|d| d.with_keys_from(&self.config))
+    }
+
+    fn statistics(&self, name: &str) -> Option<SourceStatistics> {
+        self.catalog.statistics(name)
+    }
+
+    fn adjacency_source(&self, rel_type: &str) -> Option<Arc<dyn TableSource>> {
+        self.catalog.adjacency_source(rel_type)
+    }
+}
+
+/// Relationship types follow the Cypher convention of upper snake case.
+fn is_relationship_name(name: &str) -> bool {
+    name.chars().any(|c| c.is_ascii_uppercase())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+}
+
+fn node_mapping(label: &str, id_field: &str, schema: &SchemaRef) -> Result<NodeMapping> {
+    if schema.field_with_name(id_field).is_err() {
+        return infer_node_mapping(label, schema);
+    }
+    let properties = schema
+        .fields()
+        .iter()
+        .map(|f| f.name().clone())
+        .filter(|name| name != id_field)
+        .collect();
+    Ok(NodeMapping::new(label, id_field).with_properties(properties))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::CypherQuery;
+    use arrow_array::{Int64Array, RecordBatch, RecordBatchIterator, StringArray};
+    use arrow_schema::{DataType, Field, Schema};
+    use datafusion::execution::context::SessionContext;
+    use lance::Dataset;
+
+    async fn write_dataset(uri: &str, batch: RecordBatch) {
+        let reader = RecordBatchIterator::new(vec![Ok(batch.clone())], batch.schema());
+        Dataset::write(reader, uri, None).await.unwrap();
+    }
+
+    fn person_batch(id_column: &str) -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new(id_column, DataType::Int64, false),
+            Field::new("name", DataType::Utf8, false),
+        ]));
+        RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(Int64Array::from(vec![1, 2, 3])),
+                Arc::new(StringArray::from(vec!["Alice", "Bob", "Carol"])),
+            ],
+        )
+        .unwrap()
+    }
+
+    fn edge_batch(src: &str, dst: &str) -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new(src, DataType::Int64, false),
+            Field::new(dst, DataType::Int64, false),
+            Field::new("since", DataType::Int64, false),
+        ]));
+        RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(Int64Array::from(vec![1, 2])),
+                Arc::new(Int64Array::from(vec![2, 3])),
+                Arc::new(Int64Array::from(vec![2020, 2021])),
+            ],
+        )
+        .unwrap()
+    }
+
+    fn uri_in(dir: &tempfile::TempDir, name: &str) -> String {
+        dir.path().join(name).to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_relationship_naming_convention() {
+        assert!(is_relationship_name("KNOWS"));
+        assert!(is_relationship_name("WORKS_FOR"));
+        assert!(is_relationship_name("RATED_5"));
+        assert!(!is_relationship_name("Person"));
+        assert!(!is_relationship_name("person"));
+        assert!(!is_relationship_name("_1"));
+    }
+
+    #[tokio::test]
+    async fn test_directory_catalog_naming_convention() {
+        let dir = tempfile::tempdir().unwrap();
+        write_dataset(&uri_in(&dir, "Person.lance"), person_batch("id")).await;
+        write_dataset(&uri_in(&dir, "KNOWS.lance"), edge_batch("src_id", "dst_id")).await;
+        std::fs::write(dir.path().join("notes.txt"), "ignored").unwrap();
+
+        let discovered = DirectoryCatalog::open(dir.path().to_str().unwrap())
+            .await
+            .unwrap();
+        let config = discovered.config();
+
+        let person = config.get_node_mapping("Person").unwrap();
+        assert_eq!(person.id_field, "id");
+        assert_eq!(person.property_fields, vec!["name"]);
+
+        let knows = config.get_relationship_mapping("KNOWS").unwrap();
+        assert_eq!(knows.source_id_field, "src_id");
+        assert_eq!(knows.target_id_field, "dst_id");
+        assert_eq!(knows.property_fields, vec!["since"]);
+        assert_eq!(discovered.list_relationship_types(), vec!["KNOWS"]);
+        assert_eq!(
+            discovered.describe("KNOWS").unwrap().key_columns,
+            vec!["src_id", "dst_id"]
+        );
+
+        let query = CypherQuery::new("MATCH (a:Person)-[:KNOWS]->(b:Person) RETURN b.name")
+            .unwrap()
+            .with_config(config.clone());
+        let result = query
+            .execute_with_catalog_and_context(Arc::new(discovered), SessionContext::new())
+            .await
+            .unwrap();
+        assert_eq!(result.num_rows(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_directory_catalog_sidecar_overrides_convention() {
+        let dir = tempfile::tempdir().unwrap();
+        write_dataset(&uri_in(&dir, "people.lance"), person_batch("person_id")).await;
+        write_dataset(&uri_in(&dir, "friendships.lance"), edge_batch("a", "b")).await;
+
+        let sidecar = GraphConfig::builder()
+            .with_node_label("people", "person_id")
+            .with_relationship("friendships", "a", "b")
+            .build()
+            .unwrap();
+        std::fs::write(
+            dir.path().join(DirectoryCatalog::SIDECAR_FILE),
+            serde_json::to_string(&sidecar).unwrap(),
+        )
+        .unwrap();
+
+        let discovered = DirectoryCatalog::open(dir.path().to_str().unwrap())
+            .await
+            .unwrap();
+        assert!(discovered.node_source("people").is_some());
+        assert!(discovered.relationship_source("friendships").is_some());
+        assert_eq!(
+            discovered
+                .config()
+                .get_node_mapping("people")
+                .unwrap()
+                .id_field,
+            "person_id"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_directory_catalog_reports_missing_endpoints() {
+        let dir = tempfile::tempdir().unwrap();
+        write_dataset(&uri_in(&dir, "LIKES.lance"), edge_batch("a", "b")).await;
+
+        let err = DirectoryCatalog::open(dir.path().to_str().unwrap())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, GraphError::ConfigError { .. }));
+    }
+}
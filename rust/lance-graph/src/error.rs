@@ -39,6 +39,10 @@ pub enum GraphError {
     #[snafu(display("Invalid graph pattern: {message}"))]
     InvalidPattern { message: String, location: Location },
 
+    /// Query stopped by its cancellation token or timeout
+    #[snafu(display("Query cancelled: {message}"))]
+    Cancelled { message: String, location: Location },
+
     /// DataFusion integration error
     #[snafu(display("DataFusion error: {source}"))]
     DataFusion {
@@ -0,0 +1,250 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Running DataFusion plans under the limits of one query.
+//!
+//! The [`ExecutionConfig`] of the graph overrides the partitioning and batch size of
+//! the session the query runs in.
+//!
+//! A query with a memory limit runs in a runtime of its own, sharing the session's
+//! disk manager and caches but drawing from a [`FairSpillPool`] of that size. Sorts,
+//! aggregations and sort-merge joins spill to the disk manager's files (the OS
+//! temporary directory by default) once their share of the pool is used up, and joins
+//! are planned as sort-merge joins since hash joins cannot spill. A query whose
+//! operators cannot fit even after spilling fails with
+//! [`GraphError::ExecutionError`] rather than exhausting the host's memory.
+
+use std::num::NonZeroUsize;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Instant;
+
+use arrow::compute::concat_batches;
+use arrow_array::RecordBatch;
+use arrow_schema::SchemaRef;
+use datafusion::error::DataFusionError;
+use datafusion::execution::context::SessionContext;
+use datafusion::execution::memory_pool::{FairSpillPool, TrackConsumersPool};
+use datafusion::execution::session_state::SessionStateBuilder;
+use datafusion::execution::TaskContext;
+use datafusion::logical_expr::LogicalPlan;
+use datafusion::physical_plan::{
+    execute_stream, EmptyRecordBatchStream, ExecutionPlan, RecordBatchStream,
+    SendableRecordBatchStream,
+};
+use futures::future::BoxFuture;
+use futures::{FutureExt, Stream, StreamExt, TryStreamExt};
+
+use crate::cancellation::Interrupt;
+use crate::config::{ExecutionConfig, Repartitioning};
+use crate::error::{GraphError, Result};
+use crate::metrics::QueryMetrics;
+
+/// Number of memory consumers named when a query exceeds its memory limit
+const REPORTED_CONSUMERS: usize = 5;
+
+/// Limits and settings of one execution of a query
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ExecutionLimits {
+    pub interrupt: Interrupt,
+    /// Bytes the operators of the query may hold in memory before spilling
+    pub memory_limit: Option<usize>,
+    /// Parallelism and batching overriding the session's
+    pub settings: ExecutionConfig,
+}
+
+/// A physical plan ready to run under the limits of one execution
+pub(crate) struct Execution {
+    pub plan: Arc<dyn ExecutionPlan>,
+    task_ctx: Arc<TaskContext>,
+    interrupt: Interrupt,
+}
+
+impl Execution {
+    /// Plan `plan` for execution in `ctx` under `limits`
+    pub(crate) async fn try_new(
+        plan: LogicalPlan,
+        ctx: &SessionContext,
+        limits: ExecutionLimits,
+    ) -> Result<Self> {
+        let ExecutionLimits {
+            interrupt,
+            memory_limit,
+            settings,
+        } = limits;
+        let mut state = ctx.state();
+        if interrupt.is_set() || memory_limit.is_some() || settings != ExecutionConfig::default() {
+            let mut config = state
+                .config()
+                .clone()
+                .with_extension(Arc::new(interrupt.clone()));
+            if let Some(partitions) = settings.target_partitions {
+                config = config.with_target_partitions(partitions);
+            }
+            if let Some(rows) = settings.batch_size {
+                config = config.with_batch_size(rows);
+            }
+            let repartition = match settings.repartitioning {
+                Repartitioning::Session => None,
+                Repartitioning::Enabled => Some(true),
+                Repartitioning::Disabled => Some(false),
+            };
+            if let Some(repartition) = repartition {
+                config = config
+                    .with_repartition_joins(repartition)
+                    .with_repartition_aggregations(repartition)
+                    .with_repartition_windows(repartition)
+                    .with_repartition_sorts(repartition)
+                    .with_repartition_file_scans(repartition);
+            }
+            let mut runtime = state.runtime_env().as_ref().clone();
+            if let Some(limit) = memory_limit {
+                // Hash joins hold their build side in memory; sort-merge joins can spill it
+                config.options_mut().optimizer.prefer_hash_join = false;
+                runtime.memory_pool = Arc::new(TrackConsumersPool::new(
+                    FairSpillPool::new(limit),
+                    NonZeroUsize::new(REPORTED_CONSUMERS).unwrap(),
+                ));
+            }
+            state = SessionStateBuilder::new_from_existing(state)
+                .with_config(config)
+                .with_runtime_env(Arc::new(runtime))
+                .build();
+        }
+        let physical_plan = state
+            .create_physical_plan(&plan)
+            .await
+            .map_err(execution_error)?;
+        if interrupt.is_interrupted() {
+            return Err(interrupt.error());
+        }
+        Ok(Self {
+            plan: physical_plan,
+            task_ctx: Arc::new(TaskContext::from(&state)),
+            interrupt,
+        })
+    }
+
+    /// Start running the plan
+    pub(crate) fn stream(&self) -> Result<SendableRecordBatchStream> {
+        let inner =
+            execute_stream(self.plan.clone(), self.task_ctx.clone()).map_err(execution_error)?;
+        if !self.interrupt.is_set() {
+            return Ok(inner);
+        }
+        Ok(Box::pin(InterruptibleStream {
+            schema: inner.schema(),
+            inner,
+            stop: self.interrupt.clone().wait().boxed(),
+            interrupt: self.interrupt.clone(),
+            stopped: false,
+        }))
+    }
+
+    /// Run the plan to completion
+    pub(crate) async fn collect(&self) -> Result<(SchemaRef, Vec<RecordBatch>)> {
+        let stream = self.stream()?;
+        let schema = stream.schema();
+        let batches = stream.try_collect().await.map_err(|e| {
+            if self.interrupt.is_interrupted() {
+                return self.interrupt.error();
+            }
+            let message = match e.find_root() {
+                DataFusionError::ResourcesExhausted(reason) => {
+                    format!("Query exceeded its memory limit: {}", reason)
+                }
+                _ => format!("Failed to collect query results: {}", e),
+            };
+            GraphError::ExecutionError {
+                message,
+                location: snafu::Location::new(file!(), line!(), column!()),
+            }
+        })?;
+        Ok((schema, batches))
+    }
+}
+
+/// Execute `plan` in `ctx` under `limits`
+pub(crate) async fn stream(
+    plan: LogicalPlan,
+    ctx: &SessionContext,
+    limits: ExecutionLimits,
+) -> Result<SendableRecordBatchStream> {
+    Execution::try_new(plan, ctx, limits).await?.stream()
+}
+
+/// Execute `plan` in `ctx` under `limits` and concatenate its batches
+pub(crate) async fn collect(
+    plan: LogicalPlan,
+    ctx: &SessionContext,
+    limits: ExecutionLimits,
+) -> Result<RecordBatch> {
+    let (schema, batches) = Execution::try_new(plan, ctx, limits)
+        .await?
+        .collect()
+        .await?;
+    concat(&schema, &batches)
+}
+
+/// Execute `plan` in `ctx` under `limits`, returning its batches concatenated along with
+/// the metrics of its operators
+pub(crate) async fn collect_with_metrics(
+    plan: LogicalPlan,
+    ctx: &SessionContext,
+    limits: ExecutionLimits,
+) -> Result<(RecordBatch, QueryMetrics)> {
+    let execution = Execution::try_new(plan, ctx, limits).await?;
+    let start = Instant::now();
+    let (schema, batches) = execution.collect().await?;
+    let metrics = QueryMetrics::from_plan(execution.plan.as_ref(), &batches, start.elapsed());
+    Ok((concat(&schema, &batches)?, metrics))
+}
+
+fn concat(schema: &SchemaRef, batches: &[RecordBatch]) -> Result<RecordBatch> {
+    concat_batches(schema, batches).map_err(|e| GraphError::ExecutionError {
+        message: format!("Failed to concatenate result batches: {}", e),
+        location: snafu::Location::new(file!(), line!(), column!()),
+    })
+}
+
+fn execution_error(e: DataFusionError) -> GraphError {
+    GraphError::ExecutionError {
+        message: format!("Failed to execute DataFusion plan: {}", e),
+        location: snafu::Location::new(file!(), line!(), column!()),
+    }
+}
+
+/// Batches of a running plan, until its interrupt fires
+///
+/// The plan is dropped when interrupted, cancelling the tasks it spawned.
+struct InterruptibleStream {
+    schema: SchemaRef,
+    inner: SendableRecordBatchStream,
+    stop: BoxFuture<'static, ()>,
+    interrupt: Interrupt,
+    stopped: bool,
+}
+
+impl Stream for InterruptibleStream {
+    type Item = std::result::Result<RecordBatch, DataFusionError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.stopped {
+            return Poll::Ready(None);
+        }
+        if self.stop.poll_unpin(cx).is_ready() || self.interrupt.is_interrupted() {
+            self.stopped = true;
+            self.inner = Box::pin(EmptyRecordBatchStream::new(self.schema.clone()));
+            let error = self.interrupt.error();
+            return Poll::Ready(Some(Err(DataFusionError::External(Box::new(error)))));
+        }
+        self.inner.poll_next_unpin(cx)
+    }
+}
+
+impl RecordBatchStream for InterruptibleStream {
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
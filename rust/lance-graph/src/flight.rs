@@ -0,0 +1,175 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Arrow Flight SQL server for Cypher queries.
+//!
+//! [`GraphFlightService`] answers Flight SQL statement queries whose text is Cypher,
+//! so any Flight SQL client (ADBC drivers, the JDBC driver, `pyarrow.flight`) can query
+//! a graph:
+//!
+//! ```ignore
+//! GraphFlightService::new(config, Arc::new(catalog))
+//!     .serve("0.0.0.0:50051".parse()?)
+//!     .await?;
+//! ```
+//!
+//! `GetFlightInfo` on a `CommandStatementQuery` parses and plans the query, returning
+//! the schema of its rows and a single endpoint whose ticket holds the query text.
+//! `DoGet` on that ticket executes the query and streams its batches as they are
+//! computed. Parse and planning errors are returned as `INVALID_ARGUMENT`, cancelled
+//! queries as `CANCELLED` and other failures as `INTERNAL`. Prepared statements,
+//! catalog metadata commands and updates are not supported.
+//!
+//! This module is available with the `flight` feature.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use arrow_flight::encode::FlightDataEncoderBuilder;
+use arrow_flight::error::FlightError;
+use arrow_flight::flight_service_server::{FlightService, FlightServiceServer};
+use arrow_flight::sql::server::FlightSqlService;
+use arrow_flight::sql::{CommandStatementQuery, ProstMessageExt, SqlInfo, TicketStatementQuery};
+use arrow_flight::{FlightDescriptor, FlightEndpoint, FlightInfo, Ticket};
+use datafusion::execution::context::SessionContext;
+use futures::TryStreamExt;
+use prost::Message;
+use tonic::{Request, Response, Status};
+
+use crate::config::GraphConfig;
+use crate::error::{GraphError, Result};
+use crate::query::CypherQuery;
+use crate::source_catalog::GraphSourceCatalog;
+
+/// Flight SQL service executing Cypher statements against one graph
+#[derive(Clone)]
+pub struct GraphFlightService {
+    config: GraphConfig,
+    catalog: Arc<dyn GraphSourceCatalog>,
+    context: SessionContext,
+}
+
+impl GraphFlightService {
+    /// Serve queries on the graph described by `config` over the tables of `catalog`
+    pub fn new(config: GraphConfig, catalog: Arc<dyn GraphSourceCatalog>) -> Self {
+        Self {
+            config,
+            catalog,
+            context: SessionContext::new(),
+        }
+    }
+
+    /// Execute queries in `context`, with its settings and user-defined functions
+    pub fn with_context(mut self, context: SessionContext) -> Self {
+        self.context = context;
+        self
+    }
+
+    /// The tonic service, to add to a server alongside others
+    pub fn into_server(self) -> FlightServiceServer<Self> {
+        FlightServiceServer::new(self)
+    }
+
+    /// Serve queries on `addr` until the server fails
+    pub async fn serve(self, addr: SocketAddr) -> Result<()> {
+        tonic::transport::Server::builder()
+            .add_service(self.into_server())
+            .serve(addr)
+            .await
+            .map_err(|e| GraphError::ExecutionError {
+                message: format!("Flight server failed: {}", e),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            })
+    }
+
+    fn query(&self, text: &str) -> Result<CypherQuery> {
+        Ok(CypherQuery::new(text)?.with_config(self.config.clone()))
+    }
+}
+
+#[tonic::async_trait]
+impl FlightSqlService for GraphFlightService {
+    type FlightService = Self;
+
+    async fn get_flight_info_statement(
+        &self,
+        query: CommandStatementQuery,
+        request: Request<FlightDescriptor>,
+    ) -> std::result::Result<Response<FlightInfo>, Status> {
+        let schema = self
+            .query(&query.query)
+            .and_then(|query| query.output_schema(self.catalog.clone()))
+            .map_err(status)?;
+        let ticket = TicketStatementQuery {
+            statement_handle: query.query.into_bytes().into(),
+        };
+        let endpoint =
+            FlightEndpoint::new().with_ticket(Ticket::new(ticket.as_any().encode_to_vec()));
+        let info = FlightInfo::new()
+            .try_with_schema(&schema)
+            .map_err(|e| Status::internal(format!("Failed to encode schema: {}", e)))?
+            .with_endpoint(endpoint)
+            .with_descriptor(request.into_inner());
+        Ok(Response::new(info))
+    }
+
+    async fn do_get_statement(
+        &self,
+        ticket: TicketStatementQuery,
+        _request: Request<Ticket>,
+    ) -> std::result::Result<Response<<Self as FlightService>::DoGetStream>, Status> {
+        let text = std::str::from_utf8(&ticket.statement_handle)
+            .map_err(|e| Status::invalid_argument(format!("Ticket is not a query: {}", e)))?;
+        let stream = self
+            .query(text)
+            .map_err(status)?
+            .execute_stream_with_catalog_and_context(self.catalog.clone(), self.context.clone())
+            .await
+            .map_err(status)?;
+        let schema = stream.schema();
+        let batches = stream.map_err(|e| FlightError::ExternalError(Box::new(e)));
+        let data = FlightDataEncoderBuilder::new()
+            .with_schema(schema)
+            .build(batches)
+            .map_err(Status::from);
+        Ok(Response::new(Box::pin(data)))
+    }
+
+    async fn register_sql_info(&self, _id: i32, _result: &SqlInfo) {}
+}
+
+/// The gRPC status of a failed query
+fn status(error: GraphError) -> Status {
+    match error {
+        GraphError::ParseError { .. }
+        | GraphError::PlanError { .. }
+        | GraphError::ConfigError { .. }
+        | GraphError::InvalidPattern { .. }
+        | GraphError::UnsupportedFeature { .. } => Status::invalid_argument(error.to_string()),
+        GraphError::Cancelled { .. } => Status::cancelled(error.to_string()),
+        _ => Status::internal(error.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_errors_map_to_status_codes() {
+        let err = CypherQuery::new("MATCH (n RETURN n").unwrap_err();
+        assert_eq!(status(err).code(), tonic::Code::InvalidArgument);
+
+        let err = GraphError::Cancelled {
+            message: "timed out".to_string(),
+            location: snafu::Location::new(file!(), line!(), column!()),
+        };
+        assert_eq!(status(err).code(), tonic::Code::Cancelled);
+
+        let err = GraphError::ExecutionError {
+            message: "disk full".to_string(),
+            location: snafu::Location::new(file!(), line!(), column!()),
+        };
+        assert_eq!(status(err).code(), tonic::Code::Internal);
+    }
+}
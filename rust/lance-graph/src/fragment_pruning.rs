@@ -0,0 +1,603 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Fragment pruning of Lance scans
+//!
+//! Scans of a [`crate::lance_catalog::LanceCatalog`] dataset filtered by comparisons,
+//! ranges, `IN` lists or null checks on a column skip the fragments whose minimum,
+//! maximum and null count for the column show they hold no matching row. An anchored
+//! traversal such as `MATCH (a:Person {id: 1})-[:KNOWS]->(b)` thus reads only the
+//! fragments of the edge table that can hold edges of node 1.
+//!
+//! The statistics of a column are computed by reading that column alone, so they are
+//! never computed on the path of a query: the first scan filtering on a column starts
+//! computing them in the background and reads every fragment, as do the scans after it
+//! until they are ready. [`LanceCatalog::build_fragment_statistics`] computes them up
+//! front instead. They are shared by the clones of the catalog until the dataset
+//! changes version. Pruned scans appear in plans as a [`FragmentPruningExec`], whose
+//! `fragments_pruned` metric `PROFILE` reports.
+//!
+//! Computing the statistics of a column also estimates its number of distinct values
+//! across the dataset, which with its null count the catalog reports to the planner.
+//!
+//! [`LanceCatalog::build_fragment_statistics`]: crate::lance_catalog::LanceCatalog::build_fragment_statistics
+
+use std::any::Any;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::{Arc, Mutex};
+
+use arrow::array::Array;
+use arrow::row::{RowConverter, SortField};
+
+use datafusion::common::ScalarValue;
+use datafusion::error::DataFusionError;
+use datafusion::execution::TaskContext;
+use datafusion::functions_aggregate::min_max::{MaxAccumulator, MinAccumulator};
+use datafusion::logical_expr::{Accumulator, BinaryExpr, Expr, Operator};
+use datafusion::physical_plan::metrics::{ExecutionPlanMetricsSet, MetricBuilder, MetricsSet};
+use datafusion::physical_plan::{
+    DisplayAs, DisplayFormatType, ExecutionPlan, PlanProperties, SendableRecordBatchStream,
+};
+use futures::TryStreamExt;
+use lance::table::format::Fragment;
+use lance::Dataset;
+
+type DFResult<T> = std::result::Result<T, DataFusionError>;
+
+/// Hashes kept to estimate the number of distinct values of a column
+const DISTINCT_SKETCH_SIZE: usize = 4096;
+
+/// Minimum, maximum and null count of a column in one fragment
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ColumnStatistics {
+    /// Smallest non-null value, null when the fragment has none
+    pub(crate) min: ScalarValue,
+    /// Largest non-null value, null when the fragment has none
+    pub(crate) max: ScalarValue,
+    pub(crate) null_count: usize,
+    pub(crate) row_count: usize,
+}
+
+/// Statistics of a column in one version of a dataset
+#[derive(Debug)]
+struct DatasetColumnStatistics {
+    /// By fragment id
+    fragments: HashMap<u64, ColumnStatistics>,
+    /// Estimated number of distinct non-null values in all fragments
+    distinct_count: usize,
+}
+
+/// Dataset URI, version and column the statistics are of
+type ColumnKey = (String, u64, String);
+
+/// Statistics of a column, once computed
+#[derive(Debug, Clone)]
+enum ColumnState {
+    /// Being computed in the background
+    Pending,
+    /// `None` if the values of the column cannot be ordered
+    Ready(Option<Arc<DatasetColumnStatistics>>),
+}
+
+/// Per-fragment statistics of dataset columns, by dataset URI, version and column
+#[derive(Debug, Default)]
+pub(crate) struct FragmentStatistics {
+    columns: Mutex<HashMap<ColumnKey, ColumnState>>,
+}
+
+impl FragmentStatistics {
+    /// Compute the statistics of `column` in each fragment of `dataset`, unless they
+    /// are already
+    pub(crate) async fn build(&self, dataset: &Dataset, column: &str) -> DFResult<()> {
+        let key = column_key(dataset, column);
+        if let Some(ColumnState::Ready(_)) = self.lock().get(&key) {
+            return Ok(());
+        }
+        let statistics = compute_column_statistics(dataset, column).await?;
+        self.lock()
+            .insert(key, ColumnState::Ready(statistics.map(Arc::new)));
+        Ok(())
+    }
+
+    /// The null fraction and estimated number of distinct values of each column of
+    /// `dataset` whose statistics are computed, without computing any others
+    pub(crate) fn column_summaries(&self, dataset: &Dataset) -> Vec<(String, f64, usize)> {
+        let (uri, version) = (dataset.uri(), dataset.version().version);
+        self.lock()
+            .iter()
+            .filter(|((u, v, _), _)| u == uri && *v == version)
+            .filter_map(|((_, _, column), state)| match state {
+                ColumnState::Ready(Some(statistics)) => Some((column, statistics)),
+                _ => None,
+            })
+            .filter_map(|(column, statistics)| {
+                let fragments = statistics.fragments.values();
+                let (nulls, rows) = fragments.fold((0, 0), |(nulls, rows), s| {
+                    (nulls + s.null_count, rows + s.row_count)
+                });
+                (rows > 0).then(|| {
+                    let null_fraction = nulls as f64 / rows as f64;
+                    (column.clone(), null_fraction, statistics.distinct_count)
+                })
+            })
+            .collect()
+    }
+
+    /// Statistics of `column` in `dataset`, or `None` if they are not computed yet or
+    /// its values cannot be ordered
+    ///
+    /// The first request for a column starts computing its statistics in the
+    /// background, when there is a Tokio runtime to run them on.
+    fn column(
+        self: &Arc<Self>,
+        dataset: &Dataset,
+        column: &str,
+    ) -> Option<Arc<DatasetColumnStatistics>> {
+        let key = column_key(dataset, column);
+        let mut columns = self.lock();
+        if let Some(state) = columns.get(&key) {
+            return match state {
+                ColumnState::Pending => None,
+                ColumnState::Ready(statistics) => statistics.clone(),
+            };
+        }
+        let runtime = tokio::runtime::Handle::try_current().ok()?;
+        columns.insert(key.clone(), ColumnState::Pending);
+        let (statistics, dataset, column) = (self.clone(), dataset.clone(), column.to_string());
+        runtime.spawn(async move {
+            match compute_column_statistics(&dataset, &column).await {
+                Ok(computed) => {
+                    statistics
+                        .lock()
+                        .insert(key, ColumnState::Ready(computed.map(Arc::new)));
+                }
+                // Left to be computed again by a later scan
+                Err(_) => {
+                    statistics.lock().remove(&key);
+                }
+            }
+        });
+        None
+    }
+
+    /// The fragments of `dataset` that may hold rows matching all of `filters`, or
+    /// `None` when none can be ruled out
+    pub(crate) fn matching_fragments(
+        self: &Arc<Self>,
+        dataset: &Dataset,
+        filters: &[Expr],
+    ) -> Option<Vec<Fragment>> {
+        let mut columns = Vec::new();
+        for filter in filters {
+            prunable_columns(filter, &mut columns);
+        }
+        columns.sort();
+        columns.dedup();
+        let schema = dataset.schema();
+        let mut statistics = HashMap::new();
+        for column in columns {
+            if schema.field(&column).is_none() {
+                continue;
+            }
+            if let Some(column_statistics) = self.column(dataset, &column) {
+                statistics.insert(column, column_statistics);
+            }
+        }
+        if statistics.is_empty() {
+            return None;
+        }
+
+        let fragments = dataset.fragments();
+        let matching: Vec<Fragment> = fragments
+            .iter()
+            .filter(|fragment| {
+                let fragment_statistics: HashMap<&str, &ColumnStatistics> = statistics
+                    .iter()
+                    .filter_map(|(column, column_statistics)| {
+                        let by_fragment = &column_statistics.fragments;
+                        Some((column.as_str(), by_fragment.get(&fragment.id)?))
+                    })
+                    .collect();
+                filters
+                    .iter()
+                    .all(|filter| may_match(filter, &fragment_statistics))
+            })
+            .cloned()
+            .collect();
+        (matching.len() < fragments.len()).then_some(matching)
+    }
+
+    // A panic while holding the lock cannot leave the map half-updated
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<ColumnKey, ColumnState>> {
+        self.columns.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn column_key(dataset: &Dataset, column: &str) -> ColumnKey {
+    (
+        dataset.uri().to_string(),
+        dataset.version().version,
+        column.to_string(),
+    )
+}
+
+/// Read `column` of every fragment of `dataset`, or `None` if its values cannot be
+/// ordered
+async fn compute_column_statistics(
+    dataset: &Dataset,
+    column: &str,
+) -> DFResult<Option<DatasetColumnStatistics>> {
+    let Some(field) = dataset.schema().field(column) else {
+        return Ok(None);
+    };
+    let data_type = field.data_type();
+    let Ok(converter) = RowConverter::new(vec![SortField::new(data_type.clone())]) else {
+        return Ok(None);
+    };
+    let mut distinct = DistinctSketch::default();
+    let mut statistics = HashMap::new();
+    for fragment in dataset.get_fragments() {
+        let (Ok(mut min), Ok(mut max)) = (
+            MinAccumulator::try_new(&data_type),
+            MaxAccumulator::try_new(&data_type),
+        ) else {
+            return Ok(None);
+        };
+        let mut scanner = fragment.scan();
+        scanner.project(&[column])?;
+        let mut batches = scanner.try_into_stream().await?;
+        let (mut null_count, mut row_count) = (0, 0);
+        while let Some(batch) = batches.try_next().await? {
+            let values = batch.column(0).clone();
+            null_count += values.null_count();
+            row_count += values.len();
+            distinct.update(&converter, &values)?;
+            min.update_batch(std::slice::from_ref(&values))?;
+            max.update_batch(&[values])?;
+        }
+        statistics.insert(
+            fragment.id() as u64,
+            ColumnStatistics {
+                min: min.evaluate()?,
+                max: max.evaluate()?,
+                null_count,
+                row_count,
+            },
+        );
+    }
+    Ok(Some(DatasetColumnStatistics {
+        fragments: statistics,
+        distinct_count: distinct.estimate(),
+    }))
+}
+
+/// The smallest hashes of the non-null values of a column, from which the number of
+/// distinct values is estimated: exactly while they number fewer than
+/// [`DISTINCT_SKETCH_SIZE`], otherwise from how small the largest kept hash is
+#[derive(Debug, Default)]
+struct DistinctSketch {
+    hashes: BTreeSet<u64>,
+}
+
+impl DistinctSketch {
+    fn update(&mut self, converter: &RowConverter, values: &Arc<dyn Array>) -> DFResult<()> {
+        let rows = converter.convert_columns(std::slice::from_ref(values))?;
+        for (index, row) in rows.iter().enumerate() {
+            if values.is_null(index) {
+                continue;
+            }
+            let mut hasher = DefaultHasher::new();
+            row.as_ref().hash(&mut hasher);
+            let hash = hasher.finish();
+            if self.hashes.len() < DISTINCT_SKETCH_SIZE {
+                self.hashes.insert(hash);
+            } else if self.hashes.last().is_some_and(|largest| hash < *largest)
+                && self.hashes.insert(hash)
+            {
+                self.hashes.pop_last();
+            }
+        }
+        Ok(())
+    }
+
+    fn estimate(&self) -> usize {
+        match self.hashes.last() {
+            Some(&largest) if self.hashes.len() == DISTINCT_SKETCH_SIZE => {
+                let fraction = largest as f64 / u64::MAX as f64;
+                ((DISTINCT_SKETCH_SIZE - 1) as f64 / fraction) as usize
+            }
+            _ => self.hashes.len(),
+        }
+    }
+}
+
+/// Columns `filter` compares in a way fragment statistics can rule out
+fn prunable_columns(filter: &Expr, columns: &mut Vec<String>) {
+    match filter {
+        Expr::BinaryExpr(BinaryExpr {
+            left,
+            op: Operator::And | Operator::Or,
+            right,
+        }) => {
+            prunable_columns(left, columns);
+            prunable_columns(right, columns);
+        }
+        _ => {
+            if let Some(column) = compared_column(filter) {
+                columns.push(column.to_string());
+            }
+        }
+    }
+}
+
+/// The column a comparison, `IN` list or null check tests against literals
+fn compared_column(filter: &Expr) -> Option<&str> {
+    match filter {
+        Expr::BinaryExpr(BinaryExpr { left, right, .. }) => match (&**left, &**right) {
+            (Expr::Column(column), Expr::Literal(..))
+            | (Expr::Literal(..), Expr::Column(column)) => Some(column.name.as_str()),
+            _ => None,
+        },
+        Expr::InList(in_list) if !in_list.negated => match &*in_list.expr {
+            Expr::Column(column) => Some(column.name.as_str()),
+            _ => None,
+        },
+        Expr::IsNull(expr) | Expr::IsNotNull(expr) => match &**expr {
+            Expr::Column(column) => Some(column.name.as_str()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Whether a fragment with `statistics` may hold rows matching `filter`
+///
+/// Anything other than a comparison of a column with statistics to a literal may match.
+fn may_match(filter: &Expr, statistics: &HashMap<&str, &ColumnStatistics>) -> bool {
+    let column_statistics = |name: &str| statistics.get(name).copied();
+    match filter {
+        Expr::BinaryExpr(BinaryExpr {
+            left,
+            op: Operator::And,
+            right,
+        }) => may_match(left, statistics) && may_match(right, statistics),
+        Expr::BinaryExpr(BinaryExpr {
+            left,
+            op: Operator::Or,
+            right,
+        }) => may_match(left, statistics) || may_match(right, statistics),
+        Expr::BinaryExpr(BinaryExpr { left, op, right }) => {
+            let (column, op, value) = match (&**left, &**right) {
+                (Expr::Column(column), Expr::Literal(value, _)) => (column, *op, value),
+                (Expr::Literal(value, _), Expr::Column(column)) => match op.swap() {
+                    Some(op) => (column, op, value),
+                    None => return true,
+                },
+                _ => return true,
+            };
+            column_statistics(&column.name).is_none_or(|s| may_compare(s, op, value))
+        }
+        Expr::InList(in_list) if !in_list.negated => match &*in_list.expr {
+            Expr::Column(column) => column_statistics(&column.name).is_none_or(|s| {
+                in_list.list.iter().any(|value| match value {
+                    Expr::Literal(value, _) => may_compare(s, Operator::Eq, value),
+                    _ => true,
+                })
+            }),
+            _ => true,
+        },
+        Expr::IsNull(expr) => match &**expr {
+            Expr::Column(column) => {
+                column_statistics(&column.name).is_none_or(|s| s.null_count > 0)
+            }
+            _ => true,
+        },
+        Expr::IsNotNull(expr) => match &**expr {
+            Expr::Column(column) => {
+                column_statistics(&column.name).is_none_or(|s| s.null_count < s.row_count)
+            }
+            _ => true,
+        },
+        _ => true,
+    }
+}
+
+/// Whether a fragment with `statistics` may hold a value `v` with `v op value`
+fn may_compare(statistics: &ColumnStatistics, op: Operator, value: &ScalarValue) -> bool {
+    if value.is_null() {
+        return true;
+    }
+    // Comparisons never hold for nulls
+    if statistics.min.is_null() || statistics.max.is_null() {
+        return false;
+    }
+    let (Some(from_min), Some(from_max)) = (
+        statistics.min.partial_cmp(value),
+        statistics.max.partial_cmp(value),
+    ) else {
+        return true;
+    };
+    match op {
+        Operator::Eq => from_min.is_le() && from_max.is_ge(),
+        Operator::NotEq => !(from_min.is_eq() && from_max.is_eq()),
+        Operator::Lt => from_min.is_lt(),
+        Operator::LtEq => from_min.is_le(),
+        Operator::Gt => from_max.is_gt(),
+        Operator::GtEq => from_max.is_ge(),
+        _ => true,
+    }
+}
+
+/// A Lance scan restricted to the fragments that may hold matching rows
+pub(crate) struct FragmentPruningExec {
+    input: Arc<dyn ExecutionPlan>,
+    scanned: usize,
+    total: usize,
+    metrics: ExecutionPlanMetricsSet,
+}
+
+impl FragmentPruningExec {
+    pub(crate) fn new(input: Arc<dyn ExecutionPlan>, scanned: usize, total: usize) -> Self {
+        let metrics = ExecutionPlanMetricsSet::new();
+        MetricBuilder::new(&metrics)
+            .global_counter("fragments_scanned")
+            .add(scanned);
+        MetricBuilder::new(&metrics)
+            .global_counter("fragments_pruned")
+            .add(total - scanned);
+        Self {
+            input,
+            scanned,
+            total,
+            metrics,
+        }
+    }
+}
+
+impl fmt::Debug for FragmentPruningExec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FragmentPruningExec")
+            .field("scanned", &self.scanned)
+            .field("total", &self.total)
+            .finish_non_exhaustive()
+    }
+}
+
+impl DisplayAs for FragmentPruningExec {
+    fn fmt_as(&self, _t: DisplayFormatType, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "FragmentPruningExec: fragments={}/{}",
+            self.scanned, self.total
+        )
+    }
+}
+
+impl ExecutionPlan for FragmentPruningExec {
+    fn name(&self) -> &str {
+        "FragmentPruningExec"
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn properties(&self) -> &PlanProperties {
+        self.input.properties()
+    }
+
+    fn children(&self) -> Vec<&Arc<dyn ExecutionPlan>> {
+        vec![&self.input]
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> DFResult<Arc<dyn ExecutionPlan>> {
+        let [input]: [Arc<dyn ExecutionPlan>; 1] = children.try_into().map_err(|_| {
+            DataFusionError::Internal("FragmentPruningExec has one child".to_string())
+        })?;
+        Ok(Arc::new(FragmentPruningExec::new(
+            input,
+            self.scanned,
+            self.total,
+        )))
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> DFResult<SendableRecordBatchStream> {
+        self.input.execute(partition, context)
+    }
+
+    fn metrics(&self) -> Option<MetricsSet> {
+        Some(self.metrics.clone_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::Int64Array;
+    use arrow_schema::DataType;
+    use datafusion::logical_expr::{col, lit};
+
+    fn statistics(min: i64, max: i64, null_count: usize) -> ColumnStatistics {
+        ColumnStatistics {
+            min: ScalarValue::Int64(Some(min)),
+            max: ScalarValue::Int64(Some(max)),
+            null_count,
+            row_count: 10,
+        }
+    }
+
+    #[test]
+    fn test_may_match_ranges() {
+        let id = statistics(10, 20, 0);
+        let fragment = HashMap::from([("id", &id)]);
+        let matches = |filter: Expr| may_match(&filter, &fragment);
+
+        assert!(matches(col("id").eq(lit(15i64))));
+        assert!(!matches(col("id").eq(lit(21i64))));
+        assert!(!matches(lit(5i64).eq(col("id"))));
+        assert!(matches(col("id").lt(lit(11i64))));
+        assert!(!matches(col("id").lt(lit(10i64))));
+        assert!(!matches(lit(20i64).lt(col("id"))));
+        assert!(matches(col("id").gt_eq(lit(20i64))));
+        assert!(!matches(
+            col("id").in_list(vec![lit(1i64), lit(30i64)], false)
+        ));
+        assert!(matches(
+            col("id").in_list(vec![lit(1i64), lit(12i64)], false)
+        ));
+        assert!(!matches(col("id").is_null()));
+        assert!(matches(
+            col("id").eq(lit(1i64)).or(col("id").eq(lit(12i64)))
+        ));
+        assert!(!matches(
+            col("id").eq(lit(12i64)).and(col("id").gt(lit(30i64)))
+        ));
+        // Columns without statistics and other expressions may match
+        assert!(matches(col("name").eq(lit("Alice"))));
+        assert!(matches(col("id").eq(col("other"))));
+    }
+
+    #[test]
+    fn test_distinct_sketch() {
+        let converter = RowConverter::new(vec![SortField::new(DataType::Int64)]).unwrap();
+        let sketch = |values: Vec<Option<i64>>| {
+            let mut sketch = DistinctSketch::default();
+            let values: Arc<dyn Array> = Arc::new(Int64Array::from(values));
+            sketch.update(&converter, &values).unwrap();
+            sketch.estimate()
+        };
+        // Exact while every hash is kept, not counting nulls
+        assert_eq!(sketch(vec![]), 0);
+        assert_eq!(sketch(vec![Some(1), Some(2), Some(1), None]), 2);
+
+        let estimate = sketch((0..100_000).map(|v| Some(v % 50_000)).collect());
+        assert!(
+            (47_500..=52_500).contains(&estimate),
+            "estimated {} distinct values of 50000",
+            estimate
+        );
+    }
+
+    #[test]
+    fn test_all_null_fragment_matches_no_comparison() {
+        let id = ColumnStatistics {
+            min: ScalarValue::Int64(None),
+            max: ScalarValue::Int64(None),
+            null_count: 10,
+            row_count: 10,
+        };
+        let fragment = HashMap::from([("id", &id)]);
+        assert!(!may_match(&col("id").eq(lit(1i64)), &fragment));
+        assert!(!may_match(&col("id").is_not_null(), &fragment));
+        assert!(may_match(&col("id").is_null(), &fragment));
+    }
+}
@@ -0,0 +1,650 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Lance dataset-backed source catalog.
+//!
+//! [`LanceCatalog`] resolves node labels and relationship types to Lance datasets,
+//! either opened from a URI or supplied as an existing [`Dataset`] handle. Each
+//! dataset is exposed to the planner as a DataFusion table source, so the resulting
+//! plans can be executed directly by a `SessionContext`.
+//!
+//! Queries read the versions of the datasets the catalog holds. Pinning a label or
+//! relationship type to an older version with [`LanceCatalog::checkout_node_version`],
+//! or the whole graph to a point in time with [`LanceCatalog::as_of`], makes queries
+//! see the graph as it was then; [`LanceCatalog::node_version`] records the versions a
+//! result was computed from. [`LanceCatalog::create_tag`] names the versions of all the
+//! datasets with one Lance tag, and [`LanceCatalog::at_tag`] reads them back as one
+//! snapshot.
+//!
+//! Scans skip the fragments whose column statistics rule out the predicates pushed into
+//! them; the statistics are computed once per dataset version, in the background or up
+//! front with [`LanceCatalog::build_fragment_statistics`], and shared by clones of the
+//! catalog. The null fractions and distinct counts of the columns whose statistics are
+//! computed are also the [`SourceStatistics`] the planner estimates selectivity with,
+//! next to row counts read from the fragment metadata.
+//!
+//! [`LanceCatalog::with_adjacency_index`] registers an [`AdjacencyIndex`] that queries
+//! read instead of the edge dataset of its relationship type while the index is current.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use arrow_schema::{Schema, SchemaRef};
+use datafusion::datasource::DefaultTableSource;
+use datafusion::logical_expr::TableSource;
+use lance::Dataset;
+
+use crate::adjacency_index::AdjacencyIndex;
+use crate::error::{GraphError, Result};
+use crate::fragment_pruning::FragmentStatistics;
+use crate::lance_table::LanceTable;
+use crate::source_catalog::{GraphSourceCatalog, SourceDescription, SourceKind, SourceStatistics};
+
+/// A catalog that maps graph labels and relationship types to Lance datasets.
+#[derive(Debug, Clone, Default)]
+pub struct LanceCatalog {
+    node_datasets: HashMap<String, Arc<Dataset>>,
+    rel_datasets: HashMap<String, Arc<Dataset>>,
+    adjacency_indexes: HashMap<String, AdjacencyIndex>,
+    fragment_statistics: Arc<FragmentStatistics>,
+}
+
+impl LanceCatalog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register an already opened dataset as the source for a node label.
+    pub fn with_node_dataset(mut self, label: impl Into<String>, dataset: Arc<Dataset>) -> Self {
+        self.node_datasets.insert(label.into(), dataset);
+        self
+    }
+
+    /// Register an already opened dataset as the source for a relationship type.
+    pub fn with_relationship_dataset(
+        mut self,
+        rel_type: impl Into<String>,
+        dataset: Arc<Dataset>,
+    ) -> Self {
+        self.rel_datasets.insert(rel_type.into(), dataset);
+        self
+    }
+
+    /// Register the materialized adjacency lists of a relationship type, replacing any
+    /// registered before.
+    pub fn with_adjacency_index(mut self, index: AdjacencyIndex) -> Self {
+        self.adjacency_indexes
+            .insert(index.relationship_type().to_string(), index);
+        self
+    }
+
+    /// The adjacency index registered for a relationship type, current or not.
+    pub fn adjacency_index(&self, rel_type: &str) -> Option<&AdjacencyIndex> {
+        self.adjacency_indexes.get(rel_type)
+    }
+
+    /// Open the dataset at `uri` and register it as the source for a node label.
+    pub async fn with_node_uri(self, label: impl Into<String>, uri: &str) -> Result<Self> {
+        let dataset = Dataset::open(uri).await?;
+        Ok(self.with_node_dataset(label, Arc::new(dataset)))
+    }
+
+    /// Open the dataset at `uri` and register it as the source for a relationship type.
+    pub async fn with_relationship_uri(
+        self,
+        rel_type: impl Into<String>,
+        uri: &str,
+    ) -> Result<Self> {
+        let dataset = Dataset::open(uri).await?;
+        Ok(self.with_relationship_dataset(rel_type, Arc::new(dataset)))
+    }
+
+    /// The dataset registered for a node label, if any.
+    pub fn node_dataset(&self, label: &str) -> Option<Arc<Dataset>> {
+        self.node_datasets.get(label).cloned()
+    }
+
+    /// The dataset registered for a relationship type, if any.
+    pub fn relationship_dataset(&self, rel_type: &str) -> Option<Arc<Dataset>> {
+        self.rel_datasets.get(rel_type).cloned()
+    }
+
+    /// The dataset registered for a node label or relationship type, if any.
+    pub(crate) fn dataset(&self, kind: SourceKind, name: &str) -> Option<Arc<Dataset>> {
+        match kind {
+            SourceKind::Node => self.node_dataset(name),
+            SourceKind::Relationship => self.relationship_dataset(name),
+        }
+    }
+
+    /// Point a node label or relationship type at another version of its dataset.
+    pub(crate) fn replace_dataset(&mut self, kind: SourceKind, name: &str, dataset: Arc<Dataset>) {
+        let datasets = match kind {
+            SourceKind::Node => &mut self.node_datasets,
+            SourceKind::Relationship => &mut self.rel_datasets,
+        };
+        datasets.insert(name.to_string(), dataset);
+    }
+
+    /// Arrow schema of a node dataset, read from its manifest.
+    pub fn node_schema(&self, label: &str) -> Option<SchemaRef> {
+        self.node_datasets.get(label).map(|ds| dataset_schema(ds))
+    }
+
+    /// Arrow schema of a relationship dataset, read from its manifest.
+    pub fn relationship_schema(&self, rel_type: &str) -> Option<SchemaRef> {
+        self.rel_datasets.get(rel_type).map(|ds| dataset_schema(ds))
+    }
+
+    /// Version of the dataset behind a node label, if any.
+    pub fn node_version(&self, label: &str) -> Option<u64> {
+        self.node_datasets.get(label).map(|ds| ds.version().version)
+    }
+
+    /// Version of the dataset behind a relationship type, if any.
+    pub fn relationship_version(&self, rel_type: &str) -> Option<u64> {
+        self.rel_datasets
+            .get(rel_type)
+            .map(|ds| ds.version().version)
+    }
+
+    /// Pin a node label to a version of its dataset.
+    pub async fn checkout_node_version(self, label: &str, version: u64) -> Result<Self> {
+        self.checkout_version(SourceKind::Node, label, version)
+            .await
+    }
+
+    /// Pin a relationship type to a version of its dataset.
+    pub async fn checkout_relationship_version(self, rel_type: &str, version: u64) -> Result<Self> {
+        self.checkout_version(SourceKind::Relationship, rel_type, version)
+            .await
+    }
+
+    async fn checkout_version(
+        mut self,
+        kind: SourceKind,
+        name: &str,
+        version: u64,
+    ) -> Result<Self> {
+        let dataset = self
+            .dataset(kind, name)
+            .ok_or_else(|| GraphError::ConfigError {
+                message: format!("No dataset registered for '{}'", name),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            })?;
+        let pinned = dataset.checkout_version(version).await?;
+        self.replace_dataset(kind, name, Arc::new(pinned));
+        Ok(self)
+    }
+
+    /// The catalog as it was at `timestamp`: every dataset checked out at its latest
+    /// version committed at or before then.
+    ///
+    /// Fails if a dataset has no version that old.
+    pub async fn as_of(&self, timestamp: SystemTime) -> Result<Self> {
+        let mut catalog = self.clone();
+        for (kind, name, dataset) in self.datasets() {
+            let version = dataset
+                .versions()
+                .await?
+                .into_iter()
+                .filter(|v| SystemTime::from(v.timestamp) <= timestamp)
+                .map(|v| v.version)
+                .max()
+                .ok_or_else(|| GraphError::ConfigError {
+                    message: format!(
+                        "Dataset of '{}' has no version committed at or before {:?}",
+                        name, timestamp
+                    ),
+                    location: snafu::Location::new(file!(), line!(), column!()),
+                })?;
+            if version != dataset.version().version {
+                let pinned = dataset.checkout_version(version).await?;
+                catalog.replace_dataset(kind, name, Arc::new(pinned));
+            }
+        }
+        Ok(catalog)
+    }
+
+    /// The catalog as of the Lance tag `tag`: every dataset checked out at the version
+    /// the tag names in it, so queries read one consistent snapshot of the graph.
+    ///
+    /// Fails, naming them, if some datasets lack the tag, rather than mixing snapshots.
+    pub async fn at_tag(&self, tag: &str) -> Result<Self> {
+        let mut catalog = self.clone();
+        let mut missing = Vec::new();
+        for (kind, name, dataset) in self.datasets() {
+            let version = match dataset.tags.get_version(tag).await {
+                Ok(version) => version,
+                Err(lance::Error::RefNotFound { .. }) => {
+                    missing.push(name.as_str());
+                    continue;
+                }
+                Err(e) => return Err(e.into()),
+            };
+            if version != dataset.version().version {
+                let pinned = dataset.checkout_version(version).await?;
+                catalog.replace_dataset(kind, name, Arc::new(pinned));
+            }
+        }
+        if !missing.is_empty() {
+            missing.sort();
+            return Err(GraphError::ConfigError {
+                message: format!(
+                    "Tag '{}' is missing from the datasets of {}",
+                    tag,
+                    missing.join(", ")
+                ),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            });
+        }
+        Ok(catalog)
+    }
+
+    /// Tag the dataset versions the catalog reads as `tag`, so [`LanceCatalog::at_tag`]
+    /// finds this snapshot of the graph later.
+    ///
+    /// Fails without tagging anything if a dataset already has the tag.
+    pub async fn create_tag(&self, tag: &str) -> Result<()> {
+        for (_, name, dataset) in self.datasets() {
+            if dataset.tags.get_version(tag).await.is_ok() {
+                return Err(GraphError::ConfigError {
+                    message: format!("Dataset of '{}' already has tag '{}'", name, tag),
+                    location: snafu::Location::new(file!(), line!(), column!()),
+                });
+            }
+        }
+        // A dataset registered under several names is tagged once
+        let mut tagged = HashSet::new();
+        for (_, _, dataset) in self.datasets() {
+            if tagged.insert(dataset.uri().to_string()) {
+                let mut tags = dataset.tags.clone();
+                tags.create(tag, dataset.version().version).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Compute the per-fragment statistics of `columns` in the dataset of a node label
+    /// or relationship type, so scans filtering on them skip fragments from the first
+    /// query on rather than once the statistics are computed in the background, and
+    /// the catalog's statistics include their null fractions and distinct counts.
+    pub async fn build_fragment_statistics(&self, name: &str, columns: &[&str]) -> Result<()> {
+        let dataset = self
+            .node_datasets
+            .get(name)
+            .or_else(|| self.rel_datasets.get(name))
+            .ok_or_else(|| GraphError::ConfigError {
+                message: format!("No dataset registered for '{}'", name),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            })?;
+        for column in columns {
+            if dataset.schema().field(column).is_none() {
+                return Err(GraphError::ConfigError {
+                    message: format!("Dataset of '{}' has no column '{}'", name, column),
+                    location: snafu::Location::new(file!(), line!(), column!()),
+                });
+            }
+            self.fragment_statistics.build(dataset, column).await?;
+        }
+        Ok(())
+    }
+
+    /// Every dataset of the catalog, with the kind and name it is registered under.
+    fn datasets(&self) -> impl Iterator<Item = (SourceKind, &String, &Arc<Dataset>)> {
+        self.node_datasets
+            .iter()
+            .map(|(name, ds)| (SourceKind::Node, name, ds))
+            .chain(
+                self.rel_datasets
+                    .iter()
+                    .map(|(name, ds)| (SourceKind::Relationship, name, ds)),
+            )
+    }
+
+    fn to_table_source(&self, dataset: &Arc<Dataset>) -> Arc<dyn TableSource> {
+        let provider = LanceTable::new(dataset.clone())
+            .with_fragment_pruning(self.fragment_statistics.clone());
+        Arc::new(DefaultTableSource::new(Arc::new(provider)))
+    }
+}
+
+impl GraphSourceCatalog for LanceCatalog {
+    fn node_source(&self, label: &str) -> Option<Arc<dyn TableSource>> {
+        self.node_datasets
+            .get(label)
+            .map(|ds| self.to_table_source(ds))
+    }
+
+    fn relationship_source(&self, rel_type: &str) -> Option<Arc<dyn TableSource>> {
+        self.rel_datasets
+            .get(rel_type)
+            .map(|ds| self.to_table_source(ds))
+    }
+
+    fn adjacency_source(&self, rel_type: &str) -> Option<Arc<dyn TableSource>> {
+        let index = self.adjacency_indexes.get(rel_type)?;
+        index
+            .is_current(self)
+            .then(|| self.to_table_source(&index.dataset()))
+    }
+
+    fn list_node_labels(&self) -> Vec<String> {
+        sorted_keys(&self.node_datasets)
+    }
+
+    fn list_relationship_types(&self) -> Vec<String> {
+        sorted_keys(&self.rel_datasets)
+    }
+
+    fn describe(&self, name: &str) -> Option<SourceDescription> {
+        if let Some(schema) = self.node_schema(name) {
+            return Some(SourceDescription::new(name, SourceKind::Node, schema));
+        }
+        self.relationship_schema(name)
+            .map(|schema| SourceDescription::new(name, SourceKind::Relationship, schema))
+    }
+
+    fn statistics(&self, name: &str) -> Option<SourceStatistics> {
+        let dataset = self
+            .node_datasets
+            .get(name)
+            .or_else(|| self.rel_datasets.get(name))?;
+        let mut statistics = SourceStatistics {
+            row_count: fragment_row_count(dataset),
+            ..Default::default()
+        };
+        for (column, null_fraction, distinct_count) in
+            self.fragment_statistics.column_summaries(dataset)
+        {
+            statistics
+                .null_fractions
+                .insert(column.clone(), null_fraction);
+            statistics.distinct_counts.insert(column, distinct_count);
+        }
+        Some(statistics)
+    }
+}
+
+/// Live row count from fragment metadata, or `None` if any fragment lacks the counts.
+fn fragment_row_count(dataset: &Dataset) -> Option<usize> {
+    dataset
+        .fragments()
+        .iter()
+        .try_fold(0usize, |total, fragment| {
+            let physical = fragment.physical_rows?;
+            let deleted = match &fragment.deletion_file {
+                Some(deletion) => deletion.num_deleted_rows?,
+                None => 0,
+            };
+            Some(total + physical.saturating_sub(deleted))
+        })
+}
+
+fn sorted_keys(datasets: &HashMap<String, Arc<Dataset>>) -> Vec<String> {
+    let mut keys: Vec<String> = datasets.keys().cloned().collect();
+    keys.sort();
+    keys
+}
+
+fn dataset_schema(dataset: &Dataset) -> SchemaRef {
+    Arc::new(Schema::from(dataset.schema()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::GraphConfig;
+    use crate::query::CypherQuery;
+    use arrow_array::{Int64Array, RecordBatch, RecordBatchIterator, StringArray};
+    use arrow_schema::{DataType, Field};
+    use datafusion::execution::context::SessionContext;
+
+    async fn write_dataset(uri: &str, batch: RecordBatch) -> Arc<Dataset> {
+        let reader = RecordBatchIterator::new(vec![Ok(batch.clone())], batch.schema());
+        Arc::new(Dataset::write(reader, uri, None).await.unwrap())
+    }
+
+    fn person_batch() -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("name", DataType::Utf8, false),
+            Field::new("age", DataType::Int64, false),
+        ]));
+        RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(Int64Array::from(vec![1, 2, 3])),
+                Arc::new(StringArray::from(vec!["Alice", "Bob", "Carol"])),
+                Arc::new(Int64Array::from(vec![28, 34, 41])),
+            ],
+        )
+        .unwrap()
+    }
+
+    fn knows_batch() -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("src_id", DataType::Int64, false),
+            Field::new("dst_id", DataType::Int64, false),
+        ]));
+        RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(Int64Array::from(vec![1, 2])),
+                Arc::new(Int64Array::from(vec![2, 3])),
+            ],
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_lance_catalog_exposes_manifest_schema() {
+        let dir = tempfile::tempdir().unwrap();
+        let uri = dir.path().join("person.lance");
+        write_dataset(uri.to_str().unwrap(), person_batch()).await;
+
+        let catalog = LanceCatalog::new()
+            .with_node_uri("Person", uri.to_str().unwrap())
+            .await
+            .unwrap();
+
+        let schema = catalog.node_schema("Person").unwrap();
+        assert_eq!(schema.fields().len(), 3);
+        assert_eq!(schema.field(1).name(), "name");
+        assert!(catalog.node_source("Person").is_some());
+        assert!(catalog.node_source("Company").is_none());
+        assert!(catalog.relationship_schema("KNOWS").is_none());
+        assert_eq!(catalog.list_node_labels(), vec!["Person"]);
+        assert_eq!(catalog.describe("Person").unwrap().schema, schema);
+        assert_eq!(catalog.statistics("Person").unwrap().row_count, Some(3));
+    }
+
+    #[tokio::test]
+    async fn test_lance_catalog_column_statistics() {
+        let dir = tempfile::tempdir().unwrap();
+        let uri = dir.path().join("person.lance");
+        let batch = RecordBatch::try_new(
+            Arc::new(Schema::new(vec![
+                Field::new("id", DataType::Int64, false),
+                Field::new("city", DataType::Utf8, true),
+            ])),
+            vec![
+                Arc::new(Int64Array::from(vec![1, 2, 3, 4])),
+                Arc::new(StringArray::from(vec![
+                    Some("Oslo"),
+                    None,
+                    Some("Oslo"),
+                    Some("Rome"),
+                ])),
+            ],
+        )
+        .unwrap();
+        let catalog = LanceCatalog::new()
+            .with_node_dataset("Person", write_dataset(uri.to_str().unwrap(), batch).await);
+
+        // Only the columns whose statistics are computed
+        let statistics = catalog.statistics("Person").unwrap();
+        assert!(statistics.distinct_counts.is_empty());
+        assert!(statistics.null_fractions.is_empty());
+
+        catalog
+            .build_fragment_statistics("Person", &["id", "city"])
+            .await
+            .unwrap();
+        let statistics = catalog.statistics("Person").unwrap();
+        assert_eq!(statistics.row_count, Some(4));
+        assert_eq!(statistics.distinct_counts["id"], 4);
+        assert_eq!(statistics.distinct_counts["city"], 2);
+        assert_eq!(statistics.null_fractions["id"], 0.0);
+        assert_eq!(statistics.null_fractions["city"], 0.25);
+    }
+
+    #[tokio::test]
+    async fn test_lance_catalog_executes_query() {
+        let dir = tempfile::tempdir().unwrap();
+        let person_uri = dir.path().join("person.lance");
+        let knows_uri = dir.path().join("knows.lance");
+        let person = write_dataset(person_uri.to_str().unwrap(), person_batch()).await;
+        write_dataset(knows_uri.to_str().unwrap(), knows_batch()).await;
+
+        let catalog = LanceCatalog::new()
+            .with_node_dataset("Person", person)
+            .with_relationship_uri("KNOWS", knows_uri.to_str().unwrap())
+            .await
+            .unwrap();
+
+        let config = GraphConfig::builder()
+            .with_node_label("Person", "id")
+            .with_relationship("KNOWS", "src_id", "dst_id")
+            .build()
+            .unwrap();
+        let query = CypherQuery::new(
+            "MATCH (a:Person)-[:KNOWS]->(b:Person) WHERE a.age > 30 RETURN b.name",
+        )
+        .unwrap()
+        .with_config(config);
+
+        let result = query
+            .execute_with_catalog_and_context(Arc::new(catalog), SessionContext::new())
+            .await
+            .unwrap();
+
+        assert_eq!(result.num_rows(), 1);
+        let names = result
+            .column(0)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        assert_eq!(names.value(0), "Carol");
+    }
+
+    #[tokio::test]
+    async fn test_lance_catalog_pins_versions() {
+        let dir = tempfile::tempdir().unwrap();
+        let uri = dir.path().join("person.lance");
+        write_dataset(uri.to_str().unwrap(), person_batch()).await;
+        let before_append = std::time::SystemTime::now();
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        let batch = person_batch();
+        let reader = RecordBatchIterator::new(vec![Ok(batch.clone())], batch.schema());
+        let params = lance::dataset::WriteParams {
+            mode: lance::dataset::WriteMode::Append,
+            ..Default::default()
+        };
+        Dataset::write(reader, uri.to_str().unwrap(), Some(params))
+            .await
+            .unwrap();
+
+        let config = GraphConfig::builder()
+            .with_node_label("Person", "id")
+            .build()
+            .unwrap();
+        let count = |catalog: LanceCatalog| {
+            let config = config.clone();
+            async move {
+                CypherQuery::new("MATCH (p:Person) RETURN count(*)")
+                    .unwrap()
+                    .with_config(config)
+                    .execute_with_catalog(Arc::new(catalog))
+                    .await
+                    .unwrap()
+                    .column(0)
+                    .as_any()
+                    .downcast_ref::<Int64Array>()
+                    .unwrap()
+                    .value(0)
+            }
+        };
+
+        let latest = LanceCatalog::new()
+            .with_node_uri("Person", uri.to_str().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(latest.node_version("Person"), Some(2));
+        assert_eq!(count(latest.clone()).await, 6);
+
+        let pinned = latest
+            .clone()
+            .checkout_node_version("Person", 1)
+            .await
+            .unwrap();
+        assert_eq!(pinned.node_version("Person"), Some(1));
+        assert_eq!(count(pinned).await, 3);
+
+        let yesterday = latest.as_of(before_append).await.unwrap();
+        assert_eq!(yesterday.node_version("Person"), Some(1));
+        assert_eq!(count(yesterday).await, 3);
+
+        let too_early = before_append - std::time::Duration::from_secs(3600);
+        assert!(latest.as_of(too_early).await.is_err());
+        assert!(latest
+            .checkout_relationship_version("KNOWS", 1)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_lance_catalog_snapshots_by_tag() {
+        let dir = tempfile::tempdir().unwrap();
+        let person_uri = dir.path().join("person.lance");
+        let knows_uri = dir.path().join("knows.lance");
+        let person = write_dataset(person_uri.to_str().unwrap(), person_batch()).await;
+        let knows = write_dataset(knows_uri.to_str().unwrap(), knows_batch()).await;
+        let catalog = LanceCatalog::new()
+            .with_node_dataset("Person", person)
+            .with_relationship_dataset("KNOWS", knows);
+        catalog.create_tag("snapshot-1").await.unwrap();
+        assert!(catalog.create_tag("snapshot-1").await.is_err());
+
+        let batch = knows_batch();
+        let reader = RecordBatchIterator::new(vec![Ok(batch.clone())], batch.schema());
+        let params = lance::dataset::WriteParams {
+            mode: lance::dataset::WriteMode::Append,
+            ..Default::default()
+        };
+        Dataset::write(reader, knows_uri.to_str().unwrap(), Some(params))
+            .await
+            .unwrap();
+        let latest = catalog
+            .clone()
+            .with_relationship_uri("KNOWS", knows_uri.to_str().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(latest.relationship_version("KNOWS"), Some(2));
+
+        let snapshot = latest.at_tag("snapshot-1").await.unwrap();
+        assert_eq!(snapshot.relationship_version("KNOWS"), Some(1));
+        assert_eq!(snapshot.node_version("Person"), Some(1));
+        assert_eq!(snapshot.statistics("KNOWS").unwrap().row_count, Some(2));
+
+        let error = latest.at_tag("snapshot-2").await.unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("missing from the datasets of KNOWS, Person"),
+            "{}",
+            error
+        );
+    }
+}
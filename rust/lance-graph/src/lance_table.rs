@@ -0,0 +1,195 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Table provider of the Lance datasets of a [`crate::lance_catalog::LanceCatalog`]
+//!
+//! Scans are those of Lance's own provider, except that an `fts(column, 'terms')`
+//! predicate pushed into the scan of a column with a full-text index is answered by a
+//! Lance full-text search, with the other predicates applied before it. Other pushed
+//! predicates are given to the Lance scanner, which answers comparisons and ranges on
+//! columns with a BTree or Bitmap index from the index instead of reading every row;
+//! [`index_usage`] lists the index lookups of a plan for `EXPLAIN`. Tables of a catalog
+//! also skip the fragments that cannot match, see [`crate::fragment_pruning`].
+
+use std::any::Any;
+use std::sync::Arc;
+
+use arrow_schema::SchemaRef;
+use async_trait::async_trait;
+use datafusion::catalog::Session;
+use datafusion::common::DataFusionError;
+use datafusion::datasource::TableProvider;
+use datafusion::logical_expr::utils::conjunction;
+use datafusion::logical_expr::{Expr, TableProviderFilterPushDown, TableType};
+use datafusion::physical_plan::{displayable, ExecutionPlan};
+use lance::datafusion::LanceTableProvider;
+use lance::Dataset;
+use lance_index::scalar::FullTextSearchQuery;
+use lance_index::{DatasetIndexExt, ScalarIndexCriteria};
+
+use crate::datafusion_planner::full_text::full_text_query;
+use crate::fragment_pruning::{FragmentPruningExec, FragmentStatistics};
+
+/// Lance operators reading an index: scalar index queries, full-text searches and
+/// vector index searches
+const INDEX_OPERATORS: &[&str] = &[
+    "ScalarIndexExec",
+    "MaterializeIndexExec",
+    "MatchQueryExec",
+    "PhraseQueryExec",
+    "BooleanQueryExec",
+    "BoostQueryExec",
+    "ANNIVFPartitionExec",
+    "ANNSubIndexExec",
+];
+
+/// A Lance dataset read as a DataFusion table
+#[derive(Debug)]
+pub(crate) struct LanceTable {
+    provider: LanceTableProvider,
+    fragment_statistics: Option<Arc<FragmentStatistics>>,
+}
+
+impl LanceTable {
+    pub(crate) fn new(dataset: Arc<Dataset>) -> Self {
+        Self {
+            provider: LanceTableProvider::new(dataset, false, false),
+            fragment_statistics: None,
+        }
+    }
+
+    /// Skip the fragments that statistics show cannot match the pushed predicates
+    pub(crate) fn with_fragment_pruning(mut self, statistics: Arc<FragmentStatistics>) -> Self {
+        self.fragment_statistics = Some(statistics);
+        self
+    }
+
+    pub(crate) fn dataset(&self) -> Arc<Dataset> {
+        self.provider.dataset()
+    }
+
+    /// The first `fts` predicate among `filters` on a column with a full-text index
+    async fn indexed_search(
+        &self,
+        filters: &[Expr],
+    ) -> datafusion::common::Result<Option<(usize, FullTextSearchQuery)>> {
+        let dataset = self.dataset();
+        for (i, filter) in filters.iter().enumerate() {
+            let Some((column, query)) = full_text_query(filter) else {
+                continue;
+            };
+            let criteria = ScalarIndexCriteria::default()
+                .for_column(&column.name)
+                .supports_fts();
+            if dataset.load_scalar_index(criteria).await?.is_some() {
+                let search =
+                    FullTextSearchQuery::new(query.to_string()).with_column(column.name.clone())?;
+                return Ok(Some((i, search)));
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[async_trait]
+impl TableProvider for LanceTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.provider.schema()
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::Base
+    }
+
+    async fn scan(
+        &self,
+        state: &dyn Session,
+        projection: Option<&Vec<usize>>,
+        filters: &[Expr],
+        limit: Option<usize>,
+    ) -> datafusion::common::Result<Arc<dyn ExecutionPlan>> {
+        // Scans reading no columns only count rows, which the search cannot return
+        let search = match projection {
+            Some(projection) if projection.is_empty() => None,
+            _ => self.indexed_search(filters).await?,
+        };
+        let dataset = self.dataset();
+        let fragments = match &self.fragment_statistics {
+            Some(statistics) => statistics.matching_fragments(&dataset, filters),
+            None => None,
+        };
+        if search.is_none() && fragments.is_none() {
+            return self.provider.scan(state, projection, filters, limit).await;
+        }
+
+        let schema = self.schema();
+        let mut scanner = dataset.scan();
+        match projection {
+            Some(projection) if projection.is_empty() => {
+                scanner.empty_project()?;
+            }
+            Some(projection) => {
+                let columns: Vec<&str> = projection
+                    .iter()
+                    .map(|i| schema.field(*i).name().as_str())
+                    .collect();
+                scanner.project(&columns)?;
+            }
+            None => {}
+        }
+        let total = dataset.fragments().len();
+        let scanned = fragments.as_ref().map(Vec::len);
+        if let Some(fragments) = fragments {
+            scanner.with_fragments(fragments);
+        }
+        match search {
+            Some((search_filter, search)) => {
+                scanner.full_text_search(search)?;
+                let others = filters
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| *i != search_filter)
+                    .map(|(_, filter)| filter.clone());
+                if let Some(filter) = conjunction(others) {
+                    scanner.filter_expr(filter).prefilter(true);
+                }
+                scanner.disable_scoring_autoprojection();
+            }
+            None => {
+                if let Some(filter) = conjunction(filters.iter().cloned()) {
+                    scanner.filter_expr(filter);
+                }
+            }
+        }
+        scanner.limit(limit.map(|l| l as i64), None)?;
+        let plan = scanner.create_plan().await.map_err(DataFusionError::from)?;
+        Ok(match scanned {
+            Some(scanned) => Arc::new(FragmentPruningExec::new(plan, scanned, total)),
+            None => plan,
+        })
+    }
+
+    fn supports_filters_pushdown(
+        &self,
+        filters: &[&Expr],
+    ) -> datafusion::common::Result<Vec<TableProviderFilterPushDown>> {
+        self.provider.supports_filters_pushdown(filters)
+    }
+}
+
+/// One line per Lance index lookup in a physical plan, such as
+/// `ScalarIndexQuery: query=[id = 2]@id_idx`
+pub(crate) fn index_usage(plan: &dyn ExecutionPlan) -> Vec<String> {
+    let mut lookups = Vec::new();
+    if INDEX_OPERATORS.contains(&plan.name()) {
+        lookups.push(displayable(plan).one_line().to_string().trim().to_string());
+    }
+    for child in plan.children() {
+        lookups.extend(index_usage(child.as_ref()));
+    }
+    lookups
+}
@@ -35,23 +35,61 @@
 //! # }
 //! ```
 
+pub mod adjacency_index;
+pub mod algorithms;
 pub mod ast;
+pub mod cached_catalog;
+pub mod cancellation;
+pub mod catalog_manifest;
+pub mod catalog_validation;
 pub mod config;
+pub mod datafusion_catalog;
 pub mod datafusion_planner;
+pub mod deserialize;
+pub mod directory_catalog;
 pub mod error;
+mod execution;
+#[cfg(feature = "flight")]
+pub mod flight;
+mod fragment_pruning;
+pub mod lance_catalog;
 pub mod lance_native_planner;
+mod lance_table;
 pub mod logical_plan;
+pub mod mapping_inference;
+pub mod metrics;
+mod parameters;
 pub mod parser;
+pub mod plan_diagram;
+pub mod prepared;
+pub mod procedures;
 pub mod query;
 pub mod query_processor;
+#[cfg(feature = "remote")]
+pub mod remote_catalog;
 pub mod semantic;
 pub mod simple_executor;
 pub mod source_catalog;
 pub mod sql_converter;
+#[cfg(feature = "substrait")]
+pub mod substrait;
+mod vector_search;
+pub mod write;
 
-/// Maximum allowed hops for variable-length relationship expansion (e.g., *1..N)
+/// Default maximum hops for variable-length relationship expansion (e.g., *1..N);
+/// override per graph with `GraphConfigBuilder::with_max_variable_length_hops`
 pub const MAX_VARIABLE_LENGTH_HOPS: u32 = 20;
 
-pub use config::{GraphConfig, NodeMapping, RelationshipMapping};
+pub use algorithms::projection::{GraphProjection, GraphProjectionBuilder};
+pub use algorithms::subgraph::{Subgraph, SubgraphQuery};
+pub use cancellation::CancellationToken;
+pub use config::{
+    ExecutionConfig, GraphConfig, LabelResolution, NodeMapping, RelationshipMapping,
+    RelationshipUniqueness, Repartitioning, VariableLengthExpansion,
+};
+pub use directory_catalog::DirectoryCatalog;
 pub use error::{GraphError, Result};
+pub use lance_catalog::LanceCatalog;
+pub use metrics::{QueryMetrics, QueryResult};
+pub use prepared::{PlanCache, PreparedQuery};
 pub use query::{CypherQuery, ExecutionStrategy};
@@ -9,9 +9,12 @@
 //! Logical plans describe WHAT operations to perform, not HOW to perform them.
 
 use crate::ast::*;
+use crate::config::RelationshipUniqueness;
 use crate::error::{GraphError, Result};
+use crate::source_catalog::{GraphSourceCatalog, SourceStatistics};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::Arc;
 
 /// A logical plan operator - describes what operation to perform
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -71,20 +74,112 @@ pub enum LogicalOperator {
         direction: RelationshipDirection,
         /// Optional variable name for the relationship pattern
         relationship_variable: Option<String>,
+        /// Property filters to apply on every relationship of the path
+        properties: HashMap<String, PropertyValue>,
         /// Minimum number of hops (defaults to 1 if None)
         min_length: Option<u32>,
         /// Maximum number of hops (defaults to system max if None)
         max_length: Option<u32>,
         /// Property filters to apply on target nodes
         target_properties: HashMap<String, PropertyValue>,
+        /// Column receiving the hops taken, when the expansion is part of a named path
+        path_variable: Option<String>,
+    },
+
+    /// Shortest paths between the source and target nodes
+    /// (`shortestPath((a)-[:KNOWS*]->(b))` or `allShortestPaths(...)`)
+    ///
+    /// Expands breadth-first from every source node, keeping for each reachable target
+    /// one (or, with [`ShortestPathMode::All`], every) path of minimal length.
+    ShortestPath {
+        /// The input operator binding the source node (and possibly the target node)
+        input: Box<LogicalOperator>,
+        /// Variable name for the source node
+        source_variable: String,
+        /// Variable name for the target node
+        target_variable: String,
+        /// Label of the target node
+        target_label: String,
+        /// Types of relationships to traverse in each hop
+        relationship_types: Vec<String>,
+        /// Direction of traversal for each hop
+        direction: RelationshipDirection,
+        /// Optional variable bound to the path (e.g., "p" in p = shortestPath(...))
+        path_variable: Option<String>,
+        /// Minimum path length (defaults to 1 if None)
+        min_length: Option<u32>,
+        /// Maximum path length (defaults to the configured hop limit if None)
+        max_length: Option<u32>,
+        /// Property filters to apply on the target node
+        target_properties: HashMap<String, PropertyValue>,
+        /// Whether one or all minimal paths are kept per pair of endpoints
+        mode: ShortestPathMode,
     },
 
+    /// Bind a path variable to the path matched by the input
+    /// (`p = (a)-[:KNOWS]->(b)-[:KNOWS*1..3]->(c)`)
+    ///
+    /// The path is a struct of its node ids (`nodes`), the properties of its
+    /// relationships (`relationships`) and its number of hops (`length`), assembled from
+    /// the steps in order.
+    BindPath {
+        input: Box<LogicalOperator>,
+        variable: String,
+        /// Variable of the node the path starts from
+        start_variable: String,
+        steps: Vec<PathStep>,
+    },
+
+    /// Drop the rows in which the patterns of one MATCH clause bind a relationship more
+    /// than once
+    ///
+    /// Planned around each MATCH clause under [`RelationshipUniqueness::Isomorphism`];
+    /// relationships bound by earlier clauses in the input may repeat.
+    DistinctRelationships { input: Box<LogicalOperator> },
+
     /// Project specific columns (RETURN clause)
     Project {
         input: Box<LogicalOperator>,
         projections: Vec<ProjectionItem>,
     },
 
+    /// Expand a list into one row per element (UNWIND clause)
+    Unwind {
+        /// Rows to expand; `None` unwinds over a single empty row
+        input: Option<Box<LogicalOperator>>,
+        expression: ValueExpression,
+        alias: String,
+    },
+
+    /// Call a procedure (CALL clause), once, cross joining its rows with the input
+    ProcedureCall {
+        /// Rows the procedure's output is joined to; `None` for a CALL starting a query
+        input: Option<Box<LogicalOperator>>,
+        procedure: String,
+        /// Constant arguments, in parameter order
+        arguments: Vec<ValueExpression>,
+        /// Output columns and the variables they are bound to; `None` binds every column
+        /// under its own name
+        yields: Option<Vec<(String, String)>>,
+    },
+
+    /// Carry bindings into the next query part (WITH clause)
+    ///
+    /// A bare node or relationship variable keeps all its columns, renamed when aliased;
+    /// any other item becomes a single column named by its alias.
+    With {
+        input: Box<LogicalOperator>,
+        items: Vec<ProjectionItem>,
+    },
+
+    /// Combine the rows of two queries returning the same columns (UNION clause)
+    Union {
+        left: Box<LogicalOperator>,
+        right: Box<LogicalOperator>,
+        /// UNION ALL keeps duplicate rows; plain UNION removes them
+        all: bool,
+    },
+
     /// Join multiple disconnected patterns
     Join {
         left: Box<LogicalOperator>,
@@ -92,6 +187,33 @@ pub enum LogicalOperator {
         join_type: JoinType,
     },
 
+    /// Keep the input rows for which a pattern has a match, or has none when negated
+    /// (`WHERE [NOT] EXISTS { MATCH ... }`)
+    ///
+    /// The pattern is joined on the variables it shares with the input as a semi-join
+    /// (an anti-join when negated). The predicate is evaluated on the joined rows, so it
+    /// can read the variables of both sides.
+    SemiJoin {
+        input: Box<LogicalOperator>,
+        pattern: Box<LogicalOperator>,
+        predicate: Option<BooleanExpression>,
+        negated: bool,
+    },
+
+    /// Count the matches of a pattern for each input row into a new column
+    /// (`COUNT { MATCH ... }`)
+    ///
+    /// The pattern is left-joined on the variables it shares with the input and the
+    /// joined rows are counted per input row, so rows without a match count zero. As in
+    /// a semi-join, the predicate is evaluated on the joined rows.
+    PatternCount {
+        input: Box<LogicalOperator>,
+        pattern: Box<LogicalOperator>,
+        predicate: Option<BooleanExpression>,
+        /// Name of the column holding the count
+        alias: String,
+    },
+
     /// Apply DISTINCT
     Distinct { input: Box<LogicalOperator> },
 
@@ -114,6 +236,18 @@ pub enum LogicalOperator {
     },
 }
 
+/// One step of a named path
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum PathStep {
+    /// A single hop whose relationship and target node are bound to these variables
+    Hop {
+        relationship_variable: String,
+        target_variable: String,
+    },
+    /// A variable-length hop whose partial path is held in `column`
+    Segment { column: String },
+}
+
 /// Projection item for SELECT/RETURN clauses
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ProjectionItem {
@@ -142,33 +276,203 @@ pub struct SortItem {
 pub struct LogicalPlanner {
     /// Track variables in scope
     variables: HashMap<String, String>, // variable -> label
+    /// Catalog consulted for table statistics when ordering pattern joins
+    statistics: Option<Arc<dyn GraphSourceCatalog>>,
+    /// Conjuncts of the WHERE following the MATCH clauses being planned, used to
+    /// estimate how many rows each pattern node matches
+    selection_hints: Vec<BooleanExpression>,
+    /// Number of `COUNT { }` subqueries planned so far, numbering their count columns
+    count_columns: usize,
+    /// Whether the relationships of a MATCH clause must be distinct
+    relationship_uniqueness: RelationshipUniqueness,
 }
 
 impl LogicalPlanner {
     pub fn new() -> Self {
         Self {
             variables: HashMap::new(),
+            statistics: None,
+            selection_hints: Vec::new(),
+            count_columns: 0,
+            relationship_uniqueness: RelationshipUniqueness::Homomorphism,
         }
     }
 
+    /// Plan each MATCH clause so that it binds every relationship at most once
+    pub fn with_relationship_uniqueness(mut self, uniqueness: RelationshipUniqueness) -> Self {
+        self.relationship_uniqueness = uniqueness;
+        self
+    }
+
+    /// Use catalog statistics to choose where path patterns start.
+    ///
+    /// Each node of a fixed-length path is estimated from its label's row count, narrowed
+    /// by its inline properties and the WHERE conjuncts on its variable. The path is
+    /// planned from the node with the smallest estimate and expanded outward from there,
+    /// so the joins grow from the most selective table.
+    pub fn with_statistics(mut self, catalog: Arc<dyn GraphSourceCatalog>) -> Self {
+        self.statistics = Some(catalog);
+        self
+    }
+
     /// Convert a Cypher AST to a logical plan
     pub fn plan(&mut self, query: &CypherQuery) -> Result<LogicalOperator> {
-        // Start with the MATCH clause(s)
-        let mut plan = self.plan_match_clauses(&query.match_clauses)?;
+        let mut plan = self.plan_single_query(query)?;
+
+        // UNION parts are combined left to right, each binding its own variables
+        for union in &query.unions {
+            self.variables.clear();
+            plan = LogicalOperator::Union {
+                left: Box::new(plan),
+                right: Box::new(self.plan(&union.query)?),
+                all: union.all,
+            };
+        }
+
+        Ok(plan)
+    }
+
+    /// Plan one query part ending in RETURN, ignoring its UNION parts
+    fn plan_single_query(&mut self, query: &CypherQuery) -> Result<LogicalOperator> {
+        if !query.write_clauses.is_empty() {
+            return Err(crate::write::write_clauses_unsupported());
+        }
+        let standalone_call = is_standalone_call(query);
+        if !standalone_call {
+            let calls = query
+                .call_clauses
+                .iter()
+                .chain(query.with_clauses.iter().flat_map(|w| &w.call_clauses));
+            if let Some(call) = calls.into_iter().find(|c| c.yield_items.is_none()) {
+                return Err(GraphError::PlanError {
+                    message: format!(
+                        "CALL {} inside a larger query must YIELD the columns it binds",
+                        call.procedure
+                    ),
+                    location: snafu::Location::new(file!(), line!(), column!()),
+                });
+            }
+        }
+
+        // Start with the MATCH, UNWIND and CALL clause(s)
+        self.selection_hints = where_conjuncts(query.where_clause.as_ref());
+        let mut plan = self
+            .plan_reading_clauses(
+                None,
+                &query.match_clauses,
+                &query.unwind_clauses,
+                &query.call_clauses,
+            )?
+            .ok_or_else(|| GraphError::PlanError {
+                message: "Query must have at least one MATCH clause".to_string(),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            })?;
 
         // Apply WHERE clause if present
         if let Some(where_clause) = &query.where_clause {
-            plan = LogicalOperator::Filter {
+            plan = self.plan_where(plan, &where_clause.expression)?;
+        }
+
+        // Each WITH starts a new query part on top of the previous one
+        for with_clause in &query.with_clauses {
+            plan = self.plan_with_clause(plan, with_clause)?;
+        }
+
+        // Apply RETURN clause; a CALL standing alone returns what it yields
+        validate_projection_ordering(
+            "RETURN",
+            &query.return_clause.items,
+            query.return_clause.distinct,
+            query.order_by.as_ref(),
+        )?;
+        if !(standalone_call && query.return_clause.items.is_empty()) {
+            plan = self.plan_return_clause(&query.return_clause, plan)?;
+        }
+
+        // Apply ORDER BY, SKIP/OFFSET and LIMIT if present
+        let order_by =
+            order_by_projected_subqueries(&query.return_clause.items, query.order_by.as_ref())?;
+        Ok(Self::plan_order_and_pagination(
+            plan,
+            order_by.as_ref(),
+            query.skip,
+            query.limit,
+        ))
+    }
+
+    /// Plan a WITH clause followed by the MATCH clauses of its query part
+    fn plan_with_clause(
+        &mut self,
+        input: LogicalOperator,
+        with_clause: &WithClause,
+    ) -> Result<LogicalOperator> {
+        validate_projection_ordering(
+            "WITH",
+            &with_clause.items,
+            with_clause.distinct,
+            with_clause.order_by.as_ref(),
+        )?;
+
+        // Only the carried node variables stay bound, under their new names
+        let mut scope = HashMap::new();
+        for item in &with_clause.items {
+            if let ValueExpression::Variable(variable) = &item.expression {
+                if let Some(label) = self.variables.get(variable) {
+                    let name = item.alias.as_ref().unwrap_or(variable);
+                    scope.insert(name.clone(), label.clone());
+                }
+            }
+        }
+        self.variables = scope;
+
+        let (input, items) = self.plan_projected_subqueries(input, &with_clause.items)?;
+        let mut plan = LogicalOperator::With {
+            input: Box::new(input),
+            items,
+        };
+        if with_clause.distinct {
+            plan = LogicalOperator::Distinct {
                 input: Box::new(plan),
-                predicate: where_clause.expression.clone(),
             };
         }
+        let order_by =
+            order_by_projected_subqueries(&with_clause.items, with_clause.order_by.as_ref())?;
+        plan = Self::plan_order_and_pagination(
+            plan,
+            order_by.as_ref(),
+            with_clause.skip,
+            with_clause.limit,
+        );
+        if let Some(where_clause) = &with_clause.where_clause {
+            plan = self.plan_where(plan, &where_clause.expression)?;
+        }
+
+        self.selection_hints = where_conjuncts(with_clause.match_where_clause.as_ref());
+        let plan = self.plan_reading_clauses(
+            Some(plan),
+            &with_clause.match_clauses,
+            &with_clause.unwind_clauses,
+            &with_clause.call_clauses,
+        )?;
+        let mut plan = plan.ok_or_else(|| GraphError::PlanError {
+            message: "Failed to plan WITH clause".to_string(),
+            location: snafu::Location::new(file!(), line!(), column!()),
+        })?;
+        if let Some(where_clause) = &with_clause.match_where_clause {
+            plan = self.plan_where(plan, &where_clause.expression)?;
+        }
 
-        // Apply RETURN clause
-        plan = self.plan_return_clause(&query.return_clause, plan)?;
+        Ok(plan)
+    }
 
-        // Apply ORDER BY if present
-        if let Some(order_by) = &query.order_by {
+    /// Apply ORDER BY, SKIP/OFFSET and LIMIT, in that order
+    fn plan_order_and_pagination(
+        mut plan: LogicalOperator,
+        order_by: Option<&OrderByClause>,
+        skip: Option<u64>,
+        limit: Option<u64>,
+    ) -> LogicalOperator {
+        if let Some(order_by) = order_by {
             plan = LogicalOperator::Sort {
                 input: Box::new(plan),
                 sort_items: order_by
@@ -182,42 +486,100 @@ impl LogicalPlanner {
             };
         }
 
-        // Apply SKIP/OFFSET if present
-        if let Some(skip) = query.skip {
+        if let Some(skip) = skip {
             plan = LogicalOperator::Offset {
                 input: Box::new(plan),
                 offset: skip,
             };
         }
 
-        // Apply LIMIT if present
-        if let Some(limit) = query.limit {
+        if let Some(limit) = limit {
             plan = LogicalOperator::Limit {
                 input: Box::new(plan),
                 count: limit,
             };
         }
 
+        plan
+    }
+
+    /// Plan the MATCH, UNWIND and CALL clauses of a query part in query order
+    ///
+    /// A CALL and an UNWIND following the same MATCH are planned CALL first, so the
+    /// UNWIND can expand a yielded list.
+    fn plan_reading_clauses(
+        &mut self,
+        base: Option<LogicalOperator>,
+        match_clauses: &[MatchClause],
+        unwind_clauses: &[UnwindClause],
+        call_clauses: &[CallClause],
+    ) -> Result<Option<LogicalOperator>> {
+        let mut plan = base;
+        for i in 0..=match_clauses.len() {
+            for call in call_clauses.iter().filter(|c| c.preceding_matches == i) {
+                plan = Some(self.plan_call_clause(plan, call)?);
+            }
+            for unwind in unwind_clauses.iter().filter(|u| u.preceding_matches == i) {
+                if !unwind.expression.subqueries().is_empty() {
+                    return Err(GraphError::UnsupportedFeature {
+                        feature: "pattern subqueries in UNWIND".to_string(),
+                        location: snafu::Location::new(file!(), line!(), column!()),
+                    });
+                }
+                plan = Some(LogicalOperator::Unwind {
+                    input: plan.map(Box::new),
+                    expression: unwind.expression.clone(),
+                    alias: unwind.alias.clone(),
+                });
+            }
+            if let Some(clause) = match_clauses.get(i) {
+                plan = Some(if clause.optional {
+                    self.plan_optional_match(plan, clause)?
+                } else {
+                    self.plan_match_clause_with_base(plan, clause)?
+                });
+            }
+        }
         Ok(plan)
     }
 
-    /// Plan MATCH clauses - the core graph pattern matching
-    fn plan_match_clauses(&mut self, match_clauses: &[MatchClause]) -> Result<LogicalOperator> {
-        if match_clauses.is_empty() {
-            return Err(GraphError::PlanError {
-                message: "Query must have at least one MATCH clause".to_string(),
+    /// Plan a CALL clause and the WHERE filtering what it yields
+    fn plan_call_clause(
+        &mut self,
+        input: Option<LogicalOperator>,
+        call: &CallClause,
+    ) -> Result<LogicalOperator> {
+        let mut referenced = Vec::new();
+        for argument in &call.arguments {
+            collect_referenced_variables(argument, &mut referenced);
+        }
+        if let Some(variable) = referenced.first() {
+            return Err(GraphError::UnsupportedFeature {
+                feature: format!(
+                    "CALL {} arguments referencing variables ('{}'); arguments must be \
+                     constants or parameters",
+                    call.procedure, variable
+                ),
                 location: snafu::Location::new(file!(), line!(), column!()),
             });
         }
 
-        let plan = match_clauses.iter().try_fold(None, |plan, clause| {
-            self.plan_match_clause_with_base(plan, clause).map(Some)
-        })?;
-
-        plan.ok_or_else(|| GraphError::PlanError {
-            message: "Failed to plan MATCH clauses".to_string(),
-            location: snafu::Location::new(file!(), line!(), column!()),
-        })
+        let yields = call.yield_items.as_ref().map(|items| {
+            items
+                .iter()
+                .map(|item| (item.column.clone(), item.variable().to_string()))
+                .collect()
+        });
+        let plan = LogicalOperator::ProcedureCall {
+            input: input.map(Box::new),
+            procedure: call.procedure.clone(),
+            arguments: call.arguments.clone(),
+            yields,
+        };
+        match &call.where_clause {
+            Some(where_clause) => self.plan_where(plan, &where_clause.expression),
+            None => Ok(plan),
+        }
     }
 
     /// Plan a single MATCH clause, optionally starting from an existing base plan
@@ -233,9 +595,16 @@ impl LogicalPlanner {
             });
         }
 
+        // OPTIONAL MATCH and pattern subqueries carry their own WHERE
+        let hints = match &match_clause.where_clause {
+            Some(where_clause) => where_conjuncts(Some(where_clause)),
+            None => self.selection_hints.clone(),
+        };
+
         let mut plan = base;
         for pattern in &match_clause.patterns {
-            match pattern {
+            let (pattern, residual) = split_property_references(pattern, &self.variables)?;
+            match &pattern {
                 GraphPattern::Node(node) => {
                     let already_bound = node
                         .variable
@@ -243,8 +612,8 @@ impl LogicalPlanner {
                         .is_some_and(|v| self.variables.contains_key(v));
 
                     match (already_bound, plan.as_ref()) {
-                        (true, _) => { /* no-op */ }
-                        (false, None) => plan = Some(self.plan_node_scan(node)?),
+                        (true, Some(_)) => { /* no-op */ }
+                        (_, None) => plan = Some(self.plan_node_scan(node)?),
                         (false, Some(_)) => {
                             let right = self.plan_node_scan(node)?;
                             plan = Some(LogicalOperator::Join {
@@ -255,16 +624,223 @@ impl LogicalPlanner {
                         }
                     }
                 }
-                GraphPattern::Path(path) => plan = Some(self.plan_path(plan, path)?),
+                GraphPattern::Path(path) if path.shortest.is_some() => {
+                    plan = Some(self.plan_shortest_path(plan, path)?)
+                }
+                GraphPattern::Path(path) => plan = Some(self.plan_path(plan, path, &hints)?),
+            }
+            if let Some(predicate) = residual {
+                plan = plan.map(|input| LogicalOperator::Filter {
+                    input: Box::new(input),
+                    predicate,
+                });
             }
         }
 
-        plan.ok_or_else(|| GraphError::PlanError {
+        let plan = plan.ok_or_else(|| GraphError::PlanError {
             message: "Failed to plan MATCH clause".to_string(),
             location: snafu::Location::new(file!(), line!(), column!()),
+        })?;
+        Ok(match self.relationship_uniqueness {
+            RelationshipUniqueness::Homomorphism => plan,
+            RelationshipUniqueness::Isomorphism => LogicalOperator::DistinctRelationships {
+                input: Box::new(plan),
+            },
         })
     }
 
+    /// Plan an OPTIONAL MATCH clause as a left outer join onto the preceding clauses
+    ///
+    /// The optional patterns are planned on their own, re-scanning any variables they
+    /// share with the base plan, and joined on those shared variables. Base rows without
+    /// a match are kept with NULLs for the variables the clause introduces.
+    fn plan_optional_match(
+        &mut self,
+        base: Option<LogicalOperator>,
+        match_clause: &MatchClause,
+    ) -> Result<LogicalOperator> {
+        let Some(base) = base else {
+            return Err(GraphError::PlanError {
+                message: "OPTIONAL MATCH must follow a MATCH clause".to_string(),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            });
+        };
+
+        let mut optional = self.plan_match_clause_with_base(None, match_clause)?;
+        if let Some(where_clause) = &match_clause.where_clause {
+            optional = self.plan_where(optional, &where_clause.expression)?;
+        }
+
+        Ok(LogicalOperator::Join {
+            left: Box::new(base),
+            right: Box::new(optional),
+            join_type: JoinType::Left,
+        })
+    }
+
+    /// Apply a WHERE predicate, planning its `EXISTS { }` conditions and pattern predicates
+    /// as semi-joins
+    fn plan_where(
+        &mut self,
+        input: LogicalOperator,
+        predicate: &BooleanExpression,
+    ) -> Result<LogicalOperator> {
+        reject_value_label_predicates(predicate_values(predicate))?;
+        let (plan, residual) = self.plan_subqueries(input, predicate)?;
+        Ok(match residual {
+            Some(predicate) => LogicalOperator::Filter {
+                input: Box::new(plan),
+                predicate,
+            },
+            None => plan,
+        })
+    }
+
+    /// Plan the `EXISTS { }` conjuncts of a predicate as semi-joins onto `input` and its
+    /// `COUNT { }` subqueries as pattern counts, returning the remaining conjuncts
+    fn plan_subqueries(
+        &mut self,
+        input: LogicalOperator,
+        predicate: &BooleanExpression,
+    ) -> Result<(LogicalOperator, Option<BooleanExpression>)> {
+        let mut predicate = predicate.clone();
+        let mut plan = self.plan_counts(input, predicate.count_subqueries_mut())?;
+        let mut conjuncts = Vec::new();
+        split_conjunction(&predicate, &mut conjuncts);
+
+        let mut subqueries = Vec::new();
+        let mut residual = Vec::new();
+        for conjunct in conjuncts {
+            match conjunct {
+                BooleanExpression::ExistsSubquery(clause) => subqueries.push((clause, false)),
+                BooleanExpression::Not(inner) => match inner.as_ref() {
+                    BooleanExpression::ExistsSubquery(clause) => subqueries.push((clause, true)),
+                    _ => residual.push(conjunct.clone()),
+                },
+                _ => residual.push(conjunct.clone()),
+            }
+        }
+        if !residual
+            .iter()
+            .all(|conjunct| conjunct.subqueries().is_empty())
+        {
+            return Err(GraphError::UnsupportedFeature {
+                feature: "EXISTS subqueries and pattern predicates inside OR; use them as WHERE \
+                          conditions combined with AND, optionally negated with NOT"
+                    .to_string(),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            });
+        }
+
+        for (clause, negated) in subqueries {
+            plan = self.plan_exists(plan, clause, negated)?;
+        }
+        let residual = residual
+            .into_iter()
+            .reduce(|left, right| BooleanExpression::And(Box::new(left), Box::new(right)));
+        Ok((plan, residual))
+    }
+
+    /// Plan `[NOT] EXISTS { MATCH pattern WHERE predicate }` as a semi-join onto `input`
+    fn plan_exists(
+        &mut self,
+        input: LogicalOperator,
+        clause: &MatchClause,
+        negated: bool,
+    ) -> Result<LogicalOperator> {
+        let (pattern, predicate) = self.plan_pattern_subquery(clause)?;
+        Ok(LogicalOperator::SemiJoin {
+            input: Box::new(input),
+            pattern: Box::new(pattern),
+            predicate,
+            negated,
+        })
+    }
+
+    /// Plan each `COUNT { }` subquery expression as a pattern count onto `input`,
+    /// replacing the expression with a reference to its count column
+    fn plan_counts(
+        &mut self,
+        input: LogicalOperator,
+        subqueries: Vec<&mut ValueExpression>,
+    ) -> Result<LogicalOperator> {
+        let mut plan = input;
+        for subquery in subqueries {
+            let ValueExpression::CountSubquery(clause) = &*subquery else {
+                continue;
+            };
+            let (pattern, predicate) = self.plan_pattern_subquery(clause)?;
+            let alias = format!("_count_{}", self.count_columns);
+            self.count_columns += 1;
+            plan = LogicalOperator::PatternCount {
+                input: Box::new(plan),
+                pattern: Box::new(pattern),
+                predicate,
+                alias: alias.clone(),
+            };
+            *subquery = ValueExpression::Variable(alias);
+        }
+        Ok(plan)
+    }
+
+    /// Plan the `COUNT { }` subqueries of RETURN or WITH items onto `input`, returning
+    /// the items reading their count columns instead
+    ///
+    /// Unaliased items computing a count are named like other computed expressions.
+    fn plan_projected_subqueries(
+        &mut self,
+        input: LogicalOperator,
+        items: &[ReturnItem],
+    ) -> Result<(LogicalOperator, Vec<ProjectionItem>)> {
+        let mut plan = input;
+        let mut projections = Vec::with_capacity(items.len());
+        reject_value_label_predicates(items.iter().map(|item| &item.expression))?;
+        for item in items {
+            let mut expression = item.expression.clone();
+            let mut alias = item.alias.clone();
+            if !expression.subqueries().is_empty() {
+                alias.get_or_insert_with(|| SUBQUERY_ITEM_NAME.to_string());
+                plan = self.plan_counts(plan, expression.count_subqueries_mut())?;
+                if !expression.subqueries().is_empty() {
+                    return Err(GraphError::UnsupportedFeature {
+                        feature: "EXISTS subqueries outside WHERE".to_string(),
+                        location: snafu::Location::new(file!(), line!(), column!()),
+                    });
+                }
+            }
+            projections.push(ProjectionItem { expression, alias });
+        }
+        Ok((plan, projections))
+    }
+
+    /// Plan the pattern of an `EXISTS { }` or `COUNT { }` subquery, returning it with
+    /// the part of its WHERE evaluated when it is joined onto the outer rows
+    ///
+    /// Like an optional match, the pattern is planned on its own, re-scanning the
+    /// variables it shares with the outer rows.
+    fn plan_pattern_subquery(
+        &mut self,
+        clause: &MatchClause,
+    ) -> Result<(LogicalOperator, Option<BooleanExpression>)> {
+        let outer = self.variables.clone();
+        let pattern_clause = MatchClause {
+            patterns: clause.patterns.clone(),
+            optional: false,
+            where_clause: None,
+        };
+        let planned = self
+            .plan_match_clause_with_base(None, &pattern_clause)
+            .and_then(|pattern| match &clause.where_clause {
+                Some(where_clause) => self.plan_subqueries(pattern, &where_clause.expression),
+                None => Ok((pattern, None)),
+            });
+        // Variables bound inside the subquery are not visible outside it; generated
+        // names stay registered so later anonymous nodes get fresh ones
+        self.variables
+            .retain(|name, _| outer.contains_key(name) || name.starts_with("_node_"));
+        planned
+    }
+
     /// Plan a node scan (ScanByLabel)
     fn plan_node_scan(&mut self, node: &NodePattern) -> Result<LogicalOperator> {
         let variable = node
@@ -272,35 +848,89 @@ impl LogicalPlanner {
             .clone()
             .unwrap_or_else(|| format!("_node_{}", self.variables.len()));
 
+        // Nodes are scanned from the table of a label they are known to have
+        if node.labels.is_empty()
+            && node.label_expression.is_some()
+            && !self.variables.contains_key(&variable)
+        {
+            return Err(GraphError::UnsupportedFeature {
+                feature: format!(
+                    "label expressions without a label every match has, as for '{}'; \
+                     add one with '&'",
+                    variable
+                ),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            });
+        }
+
+        // A variable bound by an earlier clause keeps its label when re-scanned
         let label = node
             .labels
             .first()
+            .or_else(|| self.variables.get(&variable))
             .cloned()
             .unwrap_or_else(|| "Node".to_string());
 
         // Register variable
         self.variables.insert(variable.clone(), label.clone());
 
-        Ok(LogicalOperator::ScanByLabel {
-            variable,
+        let scan = LogicalOperator::ScanByLabel {
+            variable: variable.clone(),
             label,
             properties: node.properties.clone(),
-        })
+        };
+        Ok(filter_node_labels(scan, &variable, node))
     }
 
     // (removed) plan_path_segment is superseded by plan_path
 
-    /// Plan a full path pattern, respecting the starting variable if provided
+    /// Plan a full path pattern, anchoring a fresh one at its most selective node
     fn plan_path(
         &mut self,
         base: Option<LogicalOperator>,
         path: &PathPattern,
+        hints: &[BooleanExpression],
+    ) -> Result<LogicalOperator> {
+        // A named path keeps its written order, so it is never re-anchored
+        let anchor = match base {
+            None if path.variable.is_none() => self.anchor_index(path, hints),
+            _ => 0,
+        };
+        if anchor == 0 {
+            return self.plan_path_from(base, path);
+        }
+        if anchor == path.segments.len() {
+            return self.plan_path_from(None, &reverse_path(path));
+        }
+
+        // Expand forward from the anchor, then backward to the path's start
+        let (suffix, prefix) =
+            split_path(path, anchor, || format!("_node_{}", self.variables.len()));
+        let plan = self.plan_path_from(None, &suffix)?;
+        self.plan_path_from(Some(plan), &reverse_path(&prefix))
+    }
+
+    /// Plan a path pattern in its written order, respecting the starting variable if
+    /// provided
+    fn plan_path_from(
+        &mut self,
+        base: Option<LogicalOperator>,
+        path: &PathPattern,
     ) -> Result<LogicalOperator> {
-        // Establish a base plan
-        let mut plan = if let Some(p) = base {
-            p
-        } else {
-            self.plan_node_scan(&path.start_node)?
+        // Establish a base plan; a start node not bound by it is scanned and joined in
+        let start_unbound = path
+            .start_node
+            .variable
+            .as_deref()
+            .is_some_and(|v| !self.variables.contains_key(v));
+        let mut plan = match base {
+            Some(p) if start_unbound => LogicalOperator::Join {
+                left: Box::new(p),
+                right: Box::new(self.plan_node_scan(&path.start_node)?),
+                join_type: JoinType::Cross,
+            },
+            Some(p) => p,
+            None => self.plan_node_scan(&path.start_node)?,
         };
 
         // Determine the current source variable for the first hop
@@ -309,8 +939,11 @@ impl LogicalPlanner {
             None => self.extract_variable_from_plan(&plan)?,
         };
 
+        let start_variable = current_src.clone();
+        let mut steps = Vec::new();
+
         // For each segment, add an expand
-        for segment in &path.segments {
+        for (i, segment) in path.segments.iter().enumerate() {
             // Determine / register target variable
             let target_variable = segment
                 .end_node
@@ -322,12 +955,49 @@ impl LogicalPlanner {
                 .end_node
                 .labels
                 .first()
+                .or_else(|| self.variables.get(&target_variable))
                 .cloned()
                 .unwrap_or_else(|| "Node".to_string());
             self.variables
                 .insert(target_variable.clone(), target_label.clone());
 
+            // A named path keeps each hop's relationship bound, naming anonymous ones
+            let relationship_variable = match &path.variable {
+                Some(path_variable) => Some(
+                    segment
+                        .relationship
+                        .variable
+                        .clone()
+                        .unwrap_or_else(|| format!("_{}_rel_{}", path_variable, i)),
+                ),
+                None => segment.relationship.variable.clone(),
+            };
+            let segment_column = path
+                .variable
+                .as_ref()
+                .map(|path_variable| format!("_{}_segment_{}", path_variable, i));
+
             // Optimize fixed-length var-length expansions (*1 or *1..1)
+            let fixed_length = segment
+                .relationship
+                .length
+                .as_ref()
+                .is_none_or(|l| l.min == Some(1) && l.max == Some(1));
+            if let (Some(relationship_variable), Some(column)) =
+                (&relationship_variable, &segment_column)
+            {
+                steps.push(if fixed_length {
+                    PathStep::Hop {
+                        relationship_variable: relationship_variable.clone(),
+                        target_variable: target_variable.clone(),
+                    }
+                } else {
+                    PathStep::Segment {
+                        column: column.clone(),
+                    }
+                });
+            }
+
             let next_plan = match segment.relationship.length.as_ref() {
                 Some(length_range)
                     if length_range.min == Some(1) && length_range.max == Some(1) =>
@@ -339,7 +1009,7 @@ impl LogicalPlanner {
                         target_label: target_label.clone(),
                         relationship_types: segment.relationship.types.clone(),
                         direction: segment.relationship.direction.clone(),
-                        relationship_variable: segment.relationship.variable.clone(),
+                        relationship_variable: relationship_variable.clone(),
                         properties: segment.relationship.properties.clone(),
                         target_properties: segment.end_node.properties.clone(),
                     }
@@ -351,9 +1021,11 @@ impl LogicalPlanner {
                     relationship_types: segment.relationship.types.clone(),
                     direction: segment.relationship.direction.clone(),
                     relationship_variable: segment.relationship.variable.clone(),
+                    properties: segment.relationship.properties.clone(),
                     min_length: length_range.min,
                     max_length: length_range.max,
                     target_properties: segment.end_node.properties.clone(),
+                    path_variable: segment_column,
                 },
                 None => LogicalOperator::Expand {
                     input: Box::new(plan),
@@ -362,19 +1034,146 @@ impl LogicalPlanner {
                     target_label: target_label.clone(),
                     relationship_types: segment.relationship.types.clone(),
                     direction: segment.relationship.direction.clone(),
-                    relationship_variable: segment.relationship.variable.clone(),
+                    relationship_variable,
                     properties: segment.relationship.properties.clone(),
                     target_properties: segment.end_node.properties.clone(),
                 },
             };
 
-            plan = next_plan;
+            plan = filter_node_labels(next_plan, &target_variable, &segment.end_node);
             current_src = target_variable;
         }
 
+        if let Some(variable) = &path.variable {
+            plan = LogicalOperator::BindPath {
+                input: Box::new(plan),
+                variable: variable.clone(),
+                start_variable,
+                steps,
+            };
+        }
         Ok(plan)
     }
 
+    /// Plan a `shortestPath(...)` / `allShortestPaths(...)` pattern
+    fn plan_shortest_path(
+        &mut self,
+        base: Option<LogicalOperator>,
+        path: &PathPattern,
+    ) -> Result<LogicalOperator> {
+        let invalid = |message: &str| GraphError::InvalidPattern {
+            message: message.to_string(),
+            location: snafu::Location::new(file!(), line!(), column!()),
+        };
+        let [segment] = path.segments.as_slice() else {
+            return Err(invalid(
+                "shortestPath requires a single relationship pattern between two nodes",
+            ));
+        };
+        if segment.relationship.variable.is_some() {
+            return Err(invalid(
+                "Relationship variables are not supported inside shortestPath",
+            ));
+        }
+        if !segment.relationship.properties.is_empty() {
+            return Err(GraphError::UnsupportedFeature {
+                feature: "Relationship property maps inside shortestPath".to_string(),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            });
+        }
+
+        // Bind the source node: reuse it from the base plan, or scan it
+        let source_bound = path
+            .start_node
+            .variable
+            .as_deref()
+            .is_some_and(|v| self.variables.contains_key(v));
+        let input = match base {
+            Some(base) if source_bound => base,
+            Some(base) => LogicalOperator::Join {
+                left: Box::new(base),
+                right: Box::new(self.plan_node_scan(&path.start_node)?),
+                join_type: JoinType::Cross,
+            },
+            None => self.plan_node_scan(&path.start_node)?,
+        };
+        let source_variable = match &path.start_node.variable {
+            Some(var) => var.clone(),
+            None => self.extract_variable_from_plan(&input)?,
+        };
+
+        let target_variable = segment
+            .end_node
+            .variable
+            .clone()
+            .unwrap_or_else(|| format!("_node_{}", self.variables.len()));
+        let target_label = segment
+            .end_node
+            .labels
+            .first()
+            .or_else(|| self.variables.get(&target_variable))
+            .cloned()
+            .unwrap_or_else(|| "Node".to_string());
+        self.variables
+            .insert(target_variable.clone(), target_label.clone());
+
+        let length = segment.relationship.length.as_ref();
+        let shortest = LogicalOperator::ShortestPath {
+            input: Box::new(input),
+            source_variable,
+            target_variable: target_variable.clone(),
+            target_label,
+            relationship_types: segment.relationship.types.clone(),
+            direction: segment.relationship.direction.clone(),
+            path_variable: path.variable.clone(),
+            min_length: length.and_then(|l| l.min),
+            max_length: length.and_then(|l| l.max),
+            target_properties: segment.end_node.properties.clone(),
+            mode: path.shortest.unwrap_or(ShortestPathMode::Single),
+        };
+        Ok(filter_node_labels(
+            shortest,
+            &target_variable,
+            &segment.end_node,
+        ))
+    }
+
+    /// Index of the path node to plan from: the one with the fewest estimated rows
+    ///
+    /// Returns 0, the written start, without statistics, for variable-length paths, and
+    /// when the start node cannot be estimated.
+    fn anchor_index(&self, path: &PathPattern, hints: &[BooleanExpression]) -> usize {
+        let Some(catalog) = &self.statistics else {
+            return 0;
+        };
+        // Variable-length segments infer their endpoints from the traversal order
+        let fixed_length = path.segments.iter().all(|s| {
+            s.relationship
+                .length
+                .as_ref()
+                .is_none_or(|l| l.min == Some(1) && l.max == Some(1))
+        });
+        if !fixed_length {
+            return 0;
+        }
+
+        let mut nodes = vec![&path.start_node];
+        nodes.extend(path.segments.iter().map(|s| &s.end_node));
+        let Some(mut best) = estimate_node_rows(catalog.as_ref(), nodes[0], hints) else {
+            return 0;
+        };
+        let mut anchor = 0;
+        for (i, node) in nodes.iter().enumerate().skip(1) {
+            if let Some(rows) = estimate_node_rows(catalog.as_ref(), node, hints) {
+                if rows < best {
+                    best = rows;
+                    anchor = i;
+                }
+            }
+        }
+        anchor
+    }
+
     /// Extract the main variable from a logical plan (for chaining)
     #[allow(clippy::only_used_in_recursion)]
     fn extract_variable_from_plan(&self, plan: &LogicalOperator) -> Result<String> {
@@ -386,8 +1185,45 @@ impl LogicalPlanner {
             LogicalOperator::VariableLengthExpand {
                 target_variable, ..
             } => Ok(target_variable.clone()),
+            LogicalOperator::ShortestPath {
+                target_variable, ..
+            } => Ok(target_variable.clone()),
             LogicalOperator::Filter { input, .. } => self.extract_variable_from_plan(input),
+            LogicalOperator::BindPath { input, .. } => self.extract_variable_from_plan(input),
+            LogicalOperator::DistinctRelationships { input } => {
+                self.extract_variable_from_plan(input)
+            }
             LogicalOperator::Project { input, .. } => self.extract_variable_from_plan(input),
+            LogicalOperator::Unwind {
+                input: Some(input), ..
+            } => self.extract_variable_from_plan(input),
+            LogicalOperator::Unwind { input: None, .. } => Err(GraphError::PlanError {
+                message: "UNWIND binds no node variable".to_string(),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            }),
+            LogicalOperator::ProcedureCall {
+                input: Some(input), ..
+            } => self.extract_variable_from_plan(input),
+            LogicalOperator::ProcedureCall { input: None, .. } => Err(GraphError::PlanError {
+                message: "CALL binds no node variable".to_string(),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            }),
+            LogicalOperator::With { input, items } => items
+                .iter()
+                .find_map(|item| match &item.expression {
+                    ValueExpression::Variable(v) => {
+                        let name = item.alias.as_ref().unwrap_or(v);
+                        self.variables.contains_key(name).then(|| name.clone())
+                    }
+                    _ => None,
+                })
+                .map_or_else(|| self.extract_variable_from_plan(input), Ok),
+            LogicalOperator::Union { .. } => Err(GraphError::PlanError {
+                message: "UNION binds no node variable".to_string(),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            }),
+            LogicalOperator::SemiJoin { input, .. }
+            | LogicalOperator::PatternCount { input, .. } => self.extract_variable_from_plan(input),
             LogicalOperator::Distinct { input } => self.extract_variable_from_plan(input),
             LogicalOperator::Sort { input, .. } => self.extract_variable_from_plan(input),
             LogicalOperator::Offset { input, .. } => self.extract_variable_from_plan(input),
@@ -402,18 +1238,11 @@ impl LogicalPlanner {
 
     /// Plan RETURN clause (Project)
     fn plan_return_clause(
-        &self,
+        &mut self,
         return_clause: &ReturnClause,
         input: LogicalOperator,
     ) -> Result<LogicalOperator> {
-        let projections = return_clause
-            .items
-            .iter()
-            .map(|item| ProjectionItem {
-                expression: item.expression.clone(),
-                alias: item.alias.clone(),
-            })
-            .collect();
+        let (input, projections) = self.plan_projected_subqueries(input, &return_clause.items)?;
 
         let mut plan = LogicalOperator::Project {
             input: Box::new(input),
@@ -431,6 +1260,505 @@ impl LogicalPlanner {
     }
 }
 
+/// After DISTINCT or an aggregation only the projected items exist, so ORDER BY may
+/// repeat an item's expression or refer to the names it projects, but nothing else
+fn validate_projection_ordering(
+    clause: &str,
+    items: &[ReturnItem],
+    distinct: bool,
+    order_by: Option<&OrderByClause>,
+) -> Result<()> {
+    let Some(order_by) = order_by else {
+        return Ok(());
+    };
+    let aggregating = items
+        .iter()
+        .any(|item| item.expression.contains_aggregate());
+    if !distinct && !aggregating {
+        return Ok(());
+    }
+
+    let projected: Vec<&str> = items
+        .iter()
+        .filter_map(|item| match (&item.alias, &item.expression) {
+            (Some(alias), _) => Some(alias.as_str()),
+            (None, ValueExpression::Variable(v)) => Some(v.as_str()),
+            _ => None,
+        })
+        .collect();
+    for sort_item in &order_by.items {
+        if items
+            .iter()
+            .any(|item| item.expression == sort_item.expression)
+        {
+            continue;
+        }
+        let mut referenced = Vec::new();
+        collect_referenced_variables(&sort_item.expression, &mut referenced);
+        if let Some(missing) = referenced.iter().find(|v| !projected.contains(v)) {
+            return Err(GraphError::PlanError {
+                message: format!(
+                    "ORDER BY after {} {} can only refer to projected items, but '{}' is not \
+                     projected",
+                    clause,
+                    if distinct {
+                        "DISTINCT"
+                    } else {
+                        "with aggregation"
+                    },
+                    missing
+                ),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Output name of an unaliased RETURN or WITH item containing a subquery
+const SUBQUERY_ITEM_NAME: &str = "expr";
+
+/// ORDER BY items computing a `COUNT { }` subquery sort on the output column of the
+/// RETURN or WITH item with the same expression, as the subquery cannot be planned
+/// after the projection
+fn order_by_projected_subqueries(
+    items: &[ReturnItem],
+    order_by: Option<&OrderByClause>,
+) -> Result<Option<OrderByClause>> {
+    let Some(order_by) = order_by else {
+        return Ok(None);
+    };
+    let mut order_by = order_by.clone();
+    for sort_item in &mut order_by.items {
+        if sort_item.expression.subqueries().is_empty() {
+            continue;
+        }
+        let item = items
+            .iter()
+            .find(|item| item.expression == sort_item.expression)
+            .ok_or_else(|| GraphError::UnsupportedFeature {
+                feature: "ORDER BY on a pattern subquery that is not also projected".to_string(),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            })?;
+        let name = item.alias.as_deref().unwrap_or(SUBQUERY_ITEM_NAME);
+        sort_item.expression = ValueExpression::Variable(name.to_string());
+    }
+    Ok(Some(order_by))
+}
+
+/// Flatten the AND-ed conditions of a predicate
+/// Keep the rows whose node `variable` satisfies the label condition of its pattern
+/// beyond the label it was scanned by
+fn filter_node_labels(
+    plan: LogicalOperator,
+    variable: &str,
+    node: &NodePattern,
+) -> LogicalOperator {
+    match node.label_condition() {
+        Some(labels) => LogicalOperator::Filter {
+            input: Box::new(plan),
+            predicate: BooleanExpression::HasLabels {
+                variable: variable.to_string(),
+                labels,
+            },
+        },
+        None => plan,
+    }
+}
+
+/// Label predicates are planned where they filter rows: directly in WHERE, or combined
+/// there with AND, OR and NOT, but not inside values such as CASE conditions
+fn reject_value_label_predicates<'a>(
+    values: impl IntoIterator<Item = &'a ValueExpression>,
+) -> Result<()> {
+    if values
+        .into_iter()
+        .any(ValueExpression::contains_label_predicate)
+    {
+        return Err(GraphError::UnsupportedFeature {
+            feature: "label predicates inside expressions; test labels in WHERE".to_string(),
+            location: snafu::Location::new(file!(), line!(), column!()),
+        });
+    }
+    Ok(())
+}
+
+/// The values a predicate compares, through its AND, OR and NOT
+fn predicate_values(predicate: &BooleanExpression) -> Vec<&ValueExpression> {
+    let (values, predicates) = predicate.operands();
+    values
+        .into_iter()
+        .chain(predicates.into_iter().flat_map(predicate_values))
+        .collect()
+}
+
+fn split_conjunction<'a>(expr: &'a BooleanExpression, out: &mut Vec<&'a BooleanExpression>) {
+    match expr {
+        BooleanExpression::And(left, right) => {
+            split_conjunction(left, out);
+            split_conjunction(right, out);
+        }
+        _ => out.push(expr),
+    }
+}
+
+/// Whether a query part is a lone CALL, which may leave out YIELD and RETURN
+fn is_standalone_call(query: &CypherQuery) -> bool {
+    query.call_clauses.len() == 1
+        && query.match_clauses.is_empty()
+        && query.unwind_clauses.is_empty()
+        && query.where_clause.is_none()
+        && query.with_clauses.is_empty()
+}
+
+/// Variables an expression reads; `count(*)` reads the pseudo-variable `*`
+pub(crate) fn collect_referenced_variables<'a>(expr: &'a ValueExpression, out: &mut Vec<&'a str>) {
+    match expr {
+        ValueExpression::Variable(v) | ValueExpression::Literal(PropertyValue::Variable(v)) => {
+            out.push(v)
+        }
+        ValueExpression::Property(prop)
+        | ValueExpression::Literal(PropertyValue::Property(prop)) => out.push(&prop.variable),
+        ValueExpression::Literal(_) => {}
+        ValueExpression::Function { args, .. } => {
+            for arg in args {
+                collect_referenced_variables(arg, out);
+            }
+        }
+        ValueExpression::Case {
+            branches,
+            else_result,
+        } => {
+            for (condition, result) in branches {
+                collect_predicate_variables(condition, out);
+                collect_referenced_variables(result, out);
+            }
+            if let Some(else_result) = else_result {
+                collect_referenced_variables(else_result, out);
+            }
+        }
+        ValueExpression::List(items) => {
+            for item in items {
+                collect_referenced_variables(item, out);
+            }
+        }
+        ValueExpression::Index { list, index } => {
+            collect_referenced_variables(list, out);
+            collect_referenced_variables(index, out);
+        }
+        ValueExpression::Slice { list, from, to } => {
+            collect_referenced_variables(list, out);
+            for bound in [from, to].into_iter().flatten() {
+                collect_referenced_variables(bound, out);
+            }
+        }
+        ValueExpression::ListComprehension {
+            variable,
+            list,
+            predicate,
+            projection,
+        } => {
+            collect_referenced_variables(list, out);
+            let mut inner = Vec::new();
+            if let Some(predicate) = predicate {
+                collect_predicate_variables(predicate, &mut inner);
+            }
+            if let Some(projection) = projection {
+                collect_referenced_variables(projection, &mut inner);
+            }
+            out.extend(inner.into_iter().filter(|v| *v != variable));
+        }
+        ValueExpression::Arithmetic { left, right, .. } => {
+            collect_referenced_variables(left, out);
+            collect_referenced_variables(right, out);
+        }
+        ValueExpression::Map(entries) => {
+            for (_, value) in entries {
+                collect_referenced_variables(value, out);
+            }
+        }
+        // The variables a subquery shares with the outer rows are only join keys
+        ValueExpression::CountSubquery(_) => {}
+    }
+}
+
+/// Variables a predicate reads
+fn collect_predicate_variables<'a>(expr: &'a BooleanExpression, out: &mut Vec<&'a str>) {
+    match expr {
+        BooleanExpression::Comparison { left, right, .. } => {
+            collect_referenced_variables(left, out);
+            collect_referenced_variables(right, out);
+        }
+        BooleanExpression::StringMatch {
+            expression,
+            pattern,
+            ..
+        } => {
+            collect_referenced_variables(expression, out);
+            collect_referenced_variables(pattern, out);
+        }
+        BooleanExpression::And(left, right) | BooleanExpression::Or(left, right) => {
+            collect_predicate_variables(left, out);
+            collect_predicate_variables(right, out);
+        }
+        BooleanExpression::Not(inner) => collect_predicate_variables(inner, out),
+        BooleanExpression::FullTextSearch { expression, query } => {
+            collect_referenced_variables(expression, out);
+            collect_referenced_variables(query, out);
+        }
+        BooleanExpression::In { expression, list } => {
+            collect_referenced_variables(expression, out);
+            for item in list {
+                collect_referenced_variables(item, out);
+            }
+        }
+        BooleanExpression::Like { expression, .. }
+        | BooleanExpression::IsNull(expression)
+        | BooleanExpression::IsNotNull(expression) => collect_referenced_variables(expression, out),
+        BooleanExpression::Exists(prop) => out.push(&prop.variable),
+        BooleanExpression::HasLabels { variable, .. } => out.push(variable),
+        // Pattern subqueries are only planned directly in WHERE, outside projections
+        BooleanExpression::ExistsSubquery(_) => {}
+    }
+}
+
+/// Move pattern properties that cannot be applied while scanning out of the pattern,
+/// into a predicate applied once the pattern is joined with the rows they depend on:
+/// those compared with values bound elsewhere in the query (such as `{id: id}` or
+/// `{name: a.name}`), and every property of a node variable bound by an earlier pattern,
+/// which is not scanned again
+fn split_property_references(
+    pattern: &GraphPattern,
+    bound: &HashMap<String, String>,
+) -> Result<(GraphPattern, Option<BooleanExpression>)> {
+    let is_reference = |value: &PropertyValue| {
+        matches!(
+            value,
+            PropertyValue::Variable(_) | PropertyValue::Property(_)
+        )
+    };
+    let mut pattern = pattern.clone();
+    let mut predicates = Vec::new();
+    let mut split = |variable: &str, properties: &mut HashMap<String, PropertyValue>, all: bool| {
+        let mut keys: Vec<String> = properties
+            .iter()
+            .filter(|(_, value)| all || is_reference(value))
+            .map(|(key, _)| key.clone())
+            .collect();
+        keys.sort();
+        for key in keys {
+            let value = properties.remove(&key).unwrap();
+            predicates.push(BooleanExpression::Comparison {
+                left: ValueExpression::Property(PropertyRef {
+                    variable: variable.to_string(),
+                    property: key,
+                }),
+                operator: ComparisonOperator::Equal,
+                right: ValueExpression::Literal(value),
+            });
+        }
+    };
+    let mut split_node = |node: &mut NodePattern| {
+        if let Some(variable) = &node.variable {
+            let all = bound.contains_key(variable);
+            split(variable, &mut node.properties, all);
+        }
+    };
+    match &mut pattern {
+        GraphPattern::Node(node) => split_node(node),
+        GraphPattern::Path(path) => {
+            split_node(&mut path.start_node);
+            for segment in &mut path.segments {
+                split_node(&mut segment.end_node);
+            }
+            for segment in &mut path.segments {
+                let relationship = &mut segment.relationship;
+                if !relationship.properties.values().any(is_reference) {
+                    continue;
+                }
+                match &relationship.variable {
+                    Some(variable) if relationship.length.is_none() => {
+                        split(variable, &mut relationship.properties, false)
+                    }
+                    _ => {
+                        return Err(GraphError::UnsupportedFeature {
+                            feature: "Relationship property maps referencing other variables \
+                                      on unnamed or variable-length relationships"
+                                .to_string(),
+                            location: snafu::Location::new(file!(), line!(), column!()),
+                        })
+                    }
+                }
+            }
+        }
+    }
+
+    let predicate = predicates
+        .into_iter()
+        .reduce(|acc, p| BooleanExpression::And(Box::new(acc), Box::new(p)));
+    Ok((pattern, predicate))
+}
+
+/// Fraction of rows assumed to pass an equality without a known distinct count
+const DEFAULT_EQUALITY_SELECTIVITY: f64 = 0.1;
+/// Fraction of rows assumed to pass a range comparison
+const RANGE_SELECTIVITY: f64 = 1.0 / 3.0;
+
+/// The conjuncts of an optional WHERE clause
+fn where_conjuncts(where_clause: Option<&WhereClause>) -> Vec<BooleanExpression> {
+    let mut conjuncts = Vec::new();
+    if let Some(where_clause) = where_clause {
+        split_conjunction(&where_clause.expression, &mut conjuncts);
+    }
+    conjuncts.into_iter().cloned().collect()
+}
+
+/// Estimated rows matching a pattern node, if its label's row count is known
+fn estimate_node_rows(
+    catalog: &dyn GraphSourceCatalog,
+    node: &NodePattern,
+    hints: &[BooleanExpression],
+) -> Option<f64> {
+    let stats = catalog.statistics(node.labels.first()?)?;
+    let mut rows = stats.row_count? as f64;
+    for property in node.properties.keys() {
+        rows *= equality_selectivity(&stats, property);
+    }
+    if let Some(variable) = &node.variable {
+        for hint in hints {
+            rows *= predicate_selectivity(&stats, variable, hint);
+        }
+    }
+    Some(rows)
+}
+
+/// Fraction of rows with one given value of a property
+fn equality_selectivity(stats: &SourceStatistics, property: &str) -> f64 {
+    stats
+        .distinct_counts
+        .get(property)
+        .map(|&count| 1.0 / count.max(1) as f64)
+        .unwrap_or(DEFAULT_EQUALITY_SELECTIVITY)
+}
+
+/// Fraction of a variable's rows passing a WHERE conjunct; 1 for conjuncts that do not
+/// restrict that variable's properties to constants
+fn predicate_selectivity(
+    stats: &SourceStatistics,
+    variable: &str,
+    predicate: &BooleanExpression,
+) -> f64 {
+    // The property of `variable` an expression reads, if it reads nothing else
+    let property_of = |expression: &ValueExpression| match expression {
+        ValueExpression::Property(property) if property.variable == variable => {
+            Some(property.property.clone())
+        }
+        _ => None,
+    };
+    let constant = |expression: &ValueExpression| {
+        let mut referenced = Vec::new();
+        collect_referenced_variables(expression, &mut referenced);
+        referenced.is_empty()
+    };
+
+    match predicate {
+        BooleanExpression::Comparison {
+            left,
+            operator,
+            right,
+        } => {
+            let property = match (property_of(left), property_of(right)) {
+                (Some(property), None) if constant(right) => property,
+                (None, Some(property)) if constant(left) => property,
+                _ => return 1.0,
+            };
+            match operator {
+                ComparisonOperator::Equal => equality_selectivity(stats, &property),
+                ComparisonOperator::NotEqual => 1.0 - equality_selectivity(stats, &property),
+                _ => RANGE_SELECTIVITY,
+            }
+        }
+        BooleanExpression::In { expression, list } if list.iter().all(constant) => {
+            match property_of(expression) {
+                Some(property) => {
+                    (list.len() as f64 * equality_selectivity(stats, &property)).min(1.0)
+                }
+                None => 1.0,
+            }
+        }
+        BooleanExpression::IsNull(expression) => match property_of(expression) {
+            Some(property) => stats
+                .null_fractions
+                .get(&property)
+                .copied()
+                .unwrap_or(DEFAULT_EQUALITY_SELECTIVITY),
+            None => 1.0,
+        },
+        _ => 1.0,
+    }
+}
+
+/// Split a path at node `index` into the part from that node onward and the part up
+/// to it, naming the shared node with `fresh_variable` if it is anonymous
+fn split_path(
+    path: &PathPattern,
+    index: usize,
+    fresh_variable: impl FnOnce() -> String,
+) -> (PathPattern, PathPattern) {
+    let mut anchor = path.segments[index - 1].end_node.clone();
+    if anchor.variable.is_none() {
+        anchor.variable = Some(fresh_variable());
+    }
+
+    let mut head = path.segments[..index].to_vec();
+    head[index - 1].end_node = anchor.clone();
+    let prefix = PathPattern {
+        start_node: path.start_node.clone(),
+        segments: head,
+        variable: None,
+        shortest: None,
+    };
+    let suffix = PathPattern {
+        start_node: anchor,
+        segments: path.segments[index..].to_vec(),
+        variable: None,
+        shortest: None,
+    };
+    (suffix, prefix)
+}
+
+/// The same path traversed from its last node back to its first
+fn reverse_path(path: &PathPattern) -> PathPattern {
+    let mut nodes: Vec<&NodePattern> = vec![&path.start_node];
+    nodes.extend(path.segments.iter().map(|s| &s.end_node));
+
+    let segments = path
+        .segments
+        .iter()
+        .enumerate()
+        .rev()
+        .map(|(i, segment)| {
+            let mut relationship = segment.relationship.clone();
+            relationship.direction = match relationship.direction {
+                RelationshipDirection::Outgoing => RelationshipDirection::Incoming,
+                RelationshipDirection::Incoming => RelationshipDirection::Outgoing,
+                RelationshipDirection::Undirected => RelationshipDirection::Undirected,
+            };
+            PathSegment {
+                relationship,
+                end_node: nodes[i].clone(),
+            }
+        })
+        .collect();
+
+    PathPattern {
+        start_node: nodes[nodes.len() - 1].clone(),
+        segments,
+        variable: None,
+        shortest: None,
+    }
+}
+
 impl Default for LogicalPlanner {
     fn default() -> Self {
         Self::new()
@@ -917,6 +2245,27 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_relationship_uniqueness_wraps_each_match_clause() {
+        let q = "MATCH (a:Person)-[:KNOWS]->(b:Person) MATCH (b)-[:KNOWS]->(c:Person) RETURN c";
+        let ast = parse_cypher_query(q).unwrap();
+        let mut planner =
+            LogicalPlanner::new().with_relationship_uniqueness(RelationshipUniqueness::Isomorphism);
+        let LogicalOperator::Project { input, .. } = planner.plan(&ast).unwrap() else {
+            panic!("Expected Project at top level");
+        };
+        let LogicalOperator::DistinctRelationships { input } = *input else {
+            panic!("Expected the second clause wrapped");
+        };
+        let LogicalOperator::Expand { input, .. } = *input else {
+            panic!("Expected the second clause's Expand");
+        };
+        assert!(matches!(
+            *input,
+            LogicalOperator::DistinctRelationships { .. }
+        ));
+    }
+
     #[test]
     fn test_multiple_match_clauses_cross_join() {
         let q = "MATCH (a:Person) MATCH (b:Company) RETURN a.name, b.name";
@@ -958,6 +2307,410 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_optional_match_left_join() {
+        let q = "MATCH (a:Person) OPTIONAL MATCH (a)-[:KNOWS]->(b:Person) WHERE b.age > 30 \
+                 RETURN a.name, b.name";
+        let ast = parse_cypher_query(q).unwrap();
+        let mut planner = LogicalPlanner::new();
+        let logical = planner.plan(&ast).unwrap();
+        let LogicalOperator::Project { input, .. } = logical else {
+            panic!("Expected Project at top level");
+        };
+        let LogicalOperator::Join {
+            left,
+            right,
+            join_type,
+        } = *input
+        else {
+            panic!("Expected Join under Project");
+        };
+        assert_eq!(join_type, JoinType::Left);
+        assert!(
+            matches!(*left, LogicalOperator::ScanByLabel { ref variable, .. } if variable == "a")
+        );
+
+        // The optional side is filtered by its own WHERE and re-scans `a` with its label
+        let LogicalOperator::Filter { input, .. } = *right else {
+            panic!("Expected the optional WHERE under the join");
+        };
+        let LogicalOperator::Expand { input, .. } = *input else {
+            panic!("Expected Expand on the optional side");
+        };
+        assert!(matches!(
+            *input,
+            LogicalOperator::ScanByLabel { ref variable, ref label, .. }
+                if variable == "a" && label == "Person"
+        ));
+
+        let ast = parse_cypher_query("OPTIONAL MATCH (a:Person) RETURN a.name").unwrap();
+        assert!(LogicalPlanner::new().plan(&ast).is_err());
+    }
+
+    #[test]
+    fn test_exists_subquery_semi_join() {
+        let q = "MATCH (a:Person) WHERE a.age > 20 \
+                 AND NOT EXISTS { MATCH (a)-[:KNOWS]->(b:Person) WHERE b.age > a.age } \
+                 RETURN a.name";
+        let ast = parse_cypher_query(q).unwrap();
+        let mut planner = LogicalPlanner::new();
+        let logical = planner.plan(&ast).unwrap();
+        let LogicalOperator::Project { input, .. } = logical else {
+            panic!("Expected Project at top level");
+        };
+        // The conditions outside the subquery filter the semi-join's output
+        let LogicalOperator::Filter { input, .. } = *input else {
+            panic!("Expected Filter under Project");
+        };
+        let LogicalOperator::SemiJoin {
+            input,
+            pattern,
+            predicate,
+            negated,
+        } = *input
+        else {
+            panic!("Expected SemiJoin under Filter");
+        };
+        assert!(negated);
+        // The subquery's WHERE is evaluated with the join
+        assert!(matches!(
+            predicate,
+            Some(BooleanExpression::Comparison { .. })
+        ));
+        assert!(matches!(*input, LogicalOperator::ScanByLabel { .. }));
+        let LogicalOperator::Expand { input, .. } = *pattern else {
+            panic!("Expected Expand in the subquery");
+        };
+        assert!(matches!(
+            *input,
+            LogicalOperator::ScanByLabel { ref variable, ref label, .. }
+                if variable == "a" && label == "Person"
+        ));
+        // Subquery variables stay inside it
+        assert!(!planner.variables.contains_key("b"));
+
+        let ast = parse_cypher_query(
+            "MATCH (a:Person) WHERE a.age > 30 OR EXISTS { (a)-[:KNOWS]->(:Person) } \
+             RETURN a.name",
+        )
+        .unwrap();
+        assert!(matches!(
+            LogicalPlanner::new().plan(&ast),
+            Err(GraphError::UnsupportedFeature { .. })
+        ));
+    }
+
+    #[test]
+    fn test_count_subquery_pattern_count() {
+        let q = "MATCH (a:Person) WHERE COUNT { (a)-[:KNOWS]->(:Person) } > 1 \
+                 RETURN a.name, COUNT { MATCH (a)<-[:KNOWS]-(b:Person) WHERE b.age > a.age } AS c \
+                 ORDER BY COUNT { MATCH (a)<-[:KNOWS]-(b:Person) WHERE b.age > a.age }";
+        let ast = parse_cypher_query(q).unwrap();
+        let mut planner = LogicalPlanner::new();
+        let logical = planner.plan(&ast).unwrap();
+
+        // Sorting repeats the projected count, so it reads the projected column
+        let LogicalOperator::Sort { input, sort_items } = logical else {
+            panic!("Expected Sort at top level");
+        };
+        assert_eq!(
+            sort_items[0].expression,
+            ValueExpression::Variable("c".to_string())
+        );
+        let LogicalOperator::Project { input, projections } = *input else {
+            panic!("Expected Project under Sort");
+        };
+        assert_eq!(
+            projections[1].expression,
+            ValueExpression::Variable("_count_1".to_string())
+        );
+        let LogicalOperator::PatternCount {
+            input,
+            predicate,
+            alias,
+            ..
+        } = *input
+        else {
+            panic!("Expected PatternCount under Project");
+        };
+        assert_eq!(alias, "_count_1");
+        assert!(predicate.is_some());
+
+        // The WHERE compares the count column computed below it
+        let LogicalOperator::Filter { input, predicate } = *input else {
+            panic!("Expected Filter under PatternCount");
+        };
+        assert!(matches!(
+            predicate,
+            BooleanExpression::Comparison {
+                left: ValueExpression::Variable(ref v),
+                ..
+            } if v == "_count_0"
+        ));
+        assert!(matches!(
+            *input,
+            LogicalOperator::PatternCount { ref alias, predicate: None, .. } if alias == "_count_0"
+        ));
+        assert!(!planner.variables.contains_key("b"));
+
+        let ast = parse_cypher_query(
+            "MATCH (a:Person) RETURN a.name ORDER BY COUNT { (a)-[:KNOWS]->(:Person) }",
+        )
+        .unwrap();
+        assert!(matches!(
+            LogicalPlanner::new().plan(&ast),
+            Err(GraphError::UnsupportedFeature { .. })
+        ));
+    }
+
+    #[test]
+    fn test_with_clause_plan() {
+        let q =
+            "MATCH (a:Person)-[:KNOWS]->(b:Person) WITH b AS friend ORDER BY friend.age LIMIT 2 \
+                 MATCH (friend)-[:KNOWS]->(c:Person) RETURN c.name";
+        let ast = parse_cypher_query(q).unwrap();
+        let logical = LogicalPlanner::new().plan(&ast).unwrap();
+        let LogicalOperator::Project { input, .. } = logical else {
+            panic!("Expected Project at top level");
+        };
+        // The next part expands from the renamed binding
+        let LogicalOperator::Expand {
+            input,
+            source_variable,
+            ..
+        } = *input
+        else {
+            panic!("Expected Expand under Project");
+        };
+        assert_eq!(source_variable, "friend");
+        let LogicalOperator::Limit { input, count } = *input else {
+            panic!("Expected the WITH's LIMIT under Expand");
+        };
+        assert_eq!(count, 2);
+        let LogicalOperator::Sort { input, .. } = *input else {
+            panic!("Expected the WITH's ORDER BY under LIMIT");
+        };
+        let LogicalOperator::With { items, .. } = *input else {
+            panic!("Expected With under Sort");
+        };
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].alias.as_deref(), Some("friend"));
+    }
+
+    #[test]
+    fn test_order_by_after_distinct_uses_projected_items() {
+        let plan = |q: &str| LogicalPlanner::new().plan(&parse_cypher_query(q).unwrap());
+
+        // Repeated expressions, aliases and properties of carried variables are allowed
+        for q in [
+            "MATCH (p:Person) RETURN DISTINCT p.name ORDER BY p.name",
+            "MATCH (p:Person) RETURN DISTINCT p.name AS n ORDER BY n DESC",
+            "MATCH (p:Person) WITH DISTINCT p ORDER BY p.age RETURN p.name",
+            "MATCH (p:Person) RETURN p.city, count(*) ORDER BY count(*), p.city",
+        ] {
+            assert!(plan(q).is_ok(), "{} should plan", q);
+        }
+
+        for q in [
+            "MATCH (p:Person) RETURN DISTINCT p.name ORDER BY p.age",
+            "MATCH (p:Person) WITH DISTINCT p.name AS n ORDER BY p.age RETURN n",
+            "MATCH (p:Person) RETURN p.city, count(*) AS c ORDER BY p.age",
+        ] {
+            let err = plan(q).unwrap_err().to_string();
+            assert!(
+                err.contains("'p' is not projected"),
+                "unexpected error for {}: {}",
+                q,
+                err
+            );
+        }
+    }
+
+    #[test]
+    fn test_unwind_with_property_reference() {
+        let q = "UNWIND [1, 2] AS id MATCH (n:Person {id: id, name: 'Bob'}) RETURN n.name";
+        let ast = parse_cypher_query(q).unwrap();
+        let logical = LogicalPlanner::new().plan(&ast).unwrap();
+        let LogicalOperator::Project { input, .. } = logical else {
+            panic!("Expected Project at top level");
+        };
+        // The reference to `id` is checked after joining the scan with the UNWIND rows
+        let LogicalOperator::Filter { input, predicate } = *input else {
+            panic!("Expected Filter under Project");
+        };
+        assert!(matches!(
+            predicate,
+            BooleanExpression::Comparison {
+                right: ValueExpression::Literal(PropertyValue::Variable(ref v)),
+                ..
+            } if v == "id"
+        ));
+        let LogicalOperator::Join {
+            left,
+            right,
+            join_type,
+        } = *input
+        else {
+            panic!("Expected Join under Filter");
+        };
+        assert_eq!(join_type, JoinType::Cross);
+        assert!(matches!(*left, LogicalOperator::Unwind { input: None, .. }));
+        // Literal properties stay on the scan
+        let LogicalOperator::ScanByLabel { properties, .. } = *right else {
+            panic!("Expected ScanByLabel on the right");
+        };
+        assert_eq!(properties.len(), 1);
+        assert!(properties.contains_key("name"));
+    }
+
+    #[test]
+    fn test_union_plans_each_part_separately() {
+        let q = "MATCH (p:Person) RETURN p.name AS name \
+                 UNION ALL MATCH (p:Company) RETURN p.name AS name \
+                 UNION ALL MATCH (c:City) RETURN c.name AS name";
+        let ast = parse_cypher_query(q).unwrap();
+        let logical = LogicalPlanner::new().plan(&ast).unwrap();
+
+        // Parts are combined left to right
+        let LogicalOperator::Union { left, right, all } = logical else {
+            panic!("Expected Union at top level");
+        };
+        assert!(all);
+        assert!(matches!(*right, LogicalOperator::Project { .. }));
+        let LogicalOperator::Union { left, right, all } = *left else {
+            panic!("Expected Union on the left");
+        };
+        assert!(all);
+        assert!(matches!(*left, LogicalOperator::Project { .. }));
+
+        // `p` is rebound with the label of the second part
+        let LogicalOperator::Project { input, .. } = *right else {
+            panic!("Expected Project for the second part");
+        };
+        assert!(matches!(
+            *input,
+            LogicalOperator::ScanByLabel { ref variable, ref label, .. }
+                if variable == "p" && label == "Company"
+        ));
+
+        // Mixing UNION and UNION ALL is rejected before planning
+        let q = "MATCH (p:Person) RETURN p.name AS name \
+                 UNION ALL MATCH (p:Company) RETURN p.name AS name \
+                 UNION MATCH (c:City) RETURN c.name AS name";
+        assert!(parse_cypher_query(q).is_err());
+    }
+
+    #[test]
+    fn test_shortest_path_operator() {
+        let q = "MATCH p = shortestPath((a:Person)-[:KNOWS*..4]->(b:Person)) RETURN length(p)";
+        let ast = parse_cypher_query(q).unwrap();
+        let logical = LogicalPlanner::new().plan(&ast).unwrap();
+        let LogicalOperator::Project { input, .. } = logical else {
+            panic!("Expected Project at top level");
+        };
+        let LogicalOperator::ShortestPath {
+            input,
+            target_variable,
+            path_variable,
+            max_length,
+            mode,
+            ..
+        } = *input
+        else {
+            panic!("Expected ShortestPath under Project");
+        };
+        assert_eq!(target_variable, "b");
+        assert_eq!(path_variable.as_deref(), Some("p"));
+        assert_eq!(max_length, Some(4));
+        assert_eq!(mode, ShortestPathMode::Single);
+        assert!(
+            matches!(*input, LogicalOperator::ScanByLabel { ref variable, .. } if variable == "a")
+        );
+
+        // Paths must be a single relationship segment
+        let q = "MATCH p = shortestPath((a:Person)-[:KNOWS*]->(b)-[:KNOWS]->(c)) RETURN p";
+        let ast = parse_cypher_query(q).unwrap();
+        assert!(LogicalPlanner::new().plan(&ast).is_err());
+    }
+
+    #[test]
+    fn test_property_maps_on_bound_variables_filter_after_join() {
+        let q = "MATCH (a:Person) MATCH (a {name: 'Eve'})-[:KNOWS*1..2 {since_year: 2020}]->(b) \
+                 RETURN b.name";
+        let ast = parse_cypher_query(q).unwrap();
+        let logical = LogicalPlanner::new().plan(&ast).unwrap();
+        let LogicalOperator::Project { input, .. } = logical else {
+            panic!("Expected Project at top level");
+        };
+        let LogicalOperator::Filter { input, predicate } = *input else {
+            panic!("Expected the bound node's properties in a Filter");
+        };
+        assert!(matches!(
+            predicate,
+            BooleanExpression::Comparison { left: ValueExpression::Property(ref p), .. }
+                if p.variable == "a" && p.property == "name"
+        ));
+        let LogicalOperator::VariableLengthExpand { properties, .. } = *input else {
+            panic!("Expected VariableLengthExpand under the Filter");
+        };
+        assert_eq!(
+            properties.get("since_year"),
+            Some(&PropertyValue::Integer(2020))
+        );
+
+        // References on relationships planned without a variable cannot be filtered later
+        let q = "MATCH (a:Person) MATCH (a)-[:KNOWS {since_year: a.age}]->(b) RETURN b";
+        let ast = parse_cypher_query(q).unwrap();
+        assert!(LogicalPlanner::new().plan(&ast).is_err());
+    }
+
+    #[test]
+    fn test_named_path_binds_steps() {
+        let q = "MATCH p = (a:Person)-[:KNOWS]->(b:Person)-[:KNOWS*1..2]->(c:Person) \
+                 RETURN nodes(p)";
+        let ast = parse_cypher_query(q).unwrap();
+        let logical = LogicalPlanner::new().plan(&ast).unwrap();
+        let LogicalOperator::Project { input, .. } = logical else {
+            panic!("Expected Project at top level");
+        };
+        let LogicalOperator::BindPath {
+            input,
+            variable,
+            start_variable,
+            steps,
+        } = *input
+        else {
+            panic!("Expected BindPath under Project");
+        };
+        assert_eq!(variable, "p");
+        assert_eq!(start_variable, "a");
+        assert_eq!(
+            steps,
+            vec![
+                PathStep::Hop {
+                    relationship_variable: "_p_rel_0".to_string(),
+                    target_variable: "b".to_string(),
+                },
+                PathStep::Segment {
+                    column: "_p_segment_1".to_string(),
+                },
+            ]
+        );
+        let LogicalOperator::VariableLengthExpand {
+            input,
+            path_variable,
+            ..
+        } = *input
+        else {
+            panic!("Expected VariableLengthExpand under BindPath");
+        };
+        assert_eq!(path_variable.as_deref(), Some("_p_segment_1"));
+        assert!(matches!(
+            *input,
+            LogicalOperator::Expand { relationship_variable: Some(ref r), .. } if r == "_p_rel_0"
+        ));
+    }
+
     #[test]
     fn test_variable_only_node_default_label() {
         let q = "MATCH (x) RETURN x";
@@ -980,16 +2733,29 @@ mod tests {
 
     #[test]
     fn test_multi_label_node_uses_first_label() {
+        // The other labels are tested on the scanned rows
         let q = "MATCH (n:Person:Employee) RETURN n";
         let ast = parse_cypher_query(q).unwrap();
         let mut planner = LogicalPlanner::new();
         let logical = planner.plan(&ast).unwrap();
         match logical {
             LogicalOperator::Project { input, .. } => match *input {
-                LogicalOperator::ScanByLabel { label, .. } => {
-                    assert_eq!(label, "Person");
+                LogicalOperator::Filter { input, predicate } => {
+                    assert_eq!(
+                        predicate,
+                        BooleanExpression::HasLabels {
+                            variable: "n".to_string(),
+                            labels: LabelExpression::Label("Employee".to_string()),
+                        }
+                    );
+                    match *input {
+                        LogicalOperator::ScanByLabel { label, .. } => {
+                            assert_eq!(label, "Person");
+                        }
+                        _ => panic!("Expected ScanByLabel under Filter"),
+                    }
                 }
-                _ => panic!("Expected ScanByLabel under Project"),
+                _ => panic!("Expected Filter under Project"),
             },
             _ => panic!("Expected Project at top level"),
         }
@@ -1057,4 +2823,195 @@ mod tests {
             _ => panic!("Expected Project at top level"),
         }
     }
+
+    #[test]
+    fn test_statistics_anchor_path_at_smaller_end() {
+        use crate::source_catalog::{InMemoryCatalog, SourceStatistics};
+
+        let query = "MATCH (p:Person)-[:WORKS_FOR]->(c:Company) RETURN p.name";
+        let ast = parse_cypher_query(query).unwrap();
+        let root_expand = |plan: &LogicalOperator| match plan {
+            LogicalOperator::Project { input, .. } => match input.as_ref() {
+                LogicalOperator::Expand {
+                    input,
+                    source_variable,
+                    direction,
+                    ..
+                } => match input.as_ref() {
+                    LogicalOperator::ScanByLabel { variable, .. } => {
+                        assert_eq!(variable, source_variable);
+                        (source_variable.clone(), direction.clone())
+                    }
+                    other => panic!("Expected ScanByLabel, got {:?}", other),
+                },
+                other => panic!("Expected Expand, got {:?}", other),
+            },
+            other => panic!("Expected Project, got {:?}", other),
+        };
+
+        // Without statistics the path is planned as written
+        let plan = LogicalPlanner::new().plan(&ast).unwrap();
+        assert_eq!(
+            root_expand(&plan),
+            ("p".to_string(), RelationshipDirection::Outgoing)
+        );
+
+        // Few companies, many people: start from the companies and walk edges backwards
+        let catalog = InMemoryCatalog::new()
+            .with_statistics("Person", SourceStatistics::with_row_count(1_000_000))
+            .with_statistics("Company", SourceStatistics::with_row_count(10));
+        let plan = LogicalPlanner::new()
+            .with_statistics(Arc::new(catalog))
+            .plan(&ast)
+            .unwrap();
+        assert_eq!(
+            root_expand(&plan),
+            ("c".to_string(), RelationshipDirection::Incoming)
+        );
+
+        // Variable-length paths keep their written order
+        let varlength =
+            parse_cypher_query("MATCH (p:Person)-[:KNOWS*1..2]->(c:Company) RETURN p").unwrap();
+        let catalog = InMemoryCatalog::new()
+            .with_statistics("Person", SourceStatistics::with_row_count(100))
+            .with_statistics("Company", SourceStatistics::with_row_count(1));
+        let plan = LogicalPlanner::new()
+            .with_statistics(Arc::new(catalog))
+            .plan(&varlength)
+            .unwrap();
+        match plan {
+            LogicalOperator::Project { input, .. } => match *input {
+                LogicalOperator::VariableLengthExpand {
+                    source_variable, ..
+                } => assert_eq!(source_variable, "p"),
+                other => panic!("Expected VariableLengthExpand, got {:?}", other),
+            },
+            other => panic!("Expected Project, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_statistics_anchor_path_at_most_selective_node() {
+        use crate::source_catalog::{InMemoryCatalog, SourceStatistics};
+
+        // Expands down to the scan, as (source, target, direction) from the top
+        fn expands(
+            plan: &LogicalOperator,
+        ) -> (Vec<(String, String, RelationshipDirection)>, String) {
+            let mut hops = Vec::new();
+            let mut current = plan;
+            loop {
+                match current {
+                    LogicalOperator::Project { input, .. }
+                    | LogicalOperator::Filter { input, .. } => current = input,
+                    LogicalOperator::Expand {
+                        input,
+                        source_variable,
+                        target_variable,
+                        direction,
+                        ..
+                    } => {
+                        hops.push((
+                            source_variable.clone(),
+                            target_variable.clone(),
+                            direction.clone(),
+                        ));
+                        current = input;
+                    }
+                    LogicalOperator::ScanByLabel { variable, .. } => {
+                        return (hops, variable.clone())
+                    }
+                    other => panic!("Unexpected operator {:?}", other),
+                }
+            }
+        }
+
+        // Few companies: start in the middle and expand outward both ways
+        let ast = parse_cypher_query(
+            "MATCH (p:Person)-[:WORKS_FOR]->(c:Company)-[:LOCATED_IN]->(t:City) RETURN p.name",
+        )
+        .unwrap();
+        let catalog = InMemoryCatalog::new()
+            .with_statistics("Person", SourceStatistics::with_row_count(1_000_000))
+            .with_statistics("Company", SourceStatistics::with_row_count(10))
+            .with_statistics("City", SourceStatistics::with_row_count(1_000));
+        let plan = LogicalPlanner::new()
+            .with_statistics(Arc::new(catalog))
+            .plan(&ast)
+            .unwrap();
+        let (hops, scan) = expands(&plan);
+        assert_eq!(scan, "c");
+        assert_eq!(
+            hops,
+            vec![
+                (
+                    "c".to_string(),
+                    "p".to_string(),
+                    RelationshipDirection::Incoming
+                ),
+                (
+                    "c".to_string(),
+                    "t".to_string(),
+                    RelationshipDirection::Outgoing
+                ),
+            ]
+        );
+
+        // A selective WHERE equality makes the larger label the better anchor
+        let ast = parse_cypher_query(
+            "MATCH (p:Person)-[:WORKS_FOR]->(c:Company) WHERE p.email = 'a@b.c' RETURN c.name",
+        )
+        .unwrap();
+        let catalog = || {
+            InMemoryCatalog::new()
+                .with_statistics(
+                    "Person",
+                    SourceStatistics::with_row_count(1_000_000)
+                        .with_distinct_count("email", 1_000_000),
+                )
+                .with_statistics("Company", SourceStatistics::with_row_count(10))
+        };
+        let plan = LogicalPlanner::new()
+            .with_statistics(Arc::new(catalog()))
+            .plan(&ast)
+            .unwrap();
+        assert_eq!(expands(&plan).1, "p");
+
+        // So does an inline property, and an anonymous anchor gets a generated name
+        let ast = parse_cypher_query(
+            "MATCH (c:Company)<-[:WORKS_FOR]-(:Person {email: 'a@b.c'})-[:LIVES_IN]->(t:City) \
+             RETURN c.name",
+        )
+        .unwrap();
+        let catalog = catalog().with_statistics("City", SourceStatistics::with_row_count(1_000));
+        let plan = LogicalPlanner::new()
+            .with_statistics(Arc::new(catalog))
+            .plan(&ast)
+            .unwrap();
+        let (hops, scan) = expands(&plan);
+        assert!(scan.starts_with("_node_"), "anchored at {}", scan);
+        assert!(hops.iter().all(|(source, _, _)| source == &scan));
+    }
+
+    #[test]
+    fn test_reverse_path_flips_directions() {
+        let ast = parse_cypher_query("MATCH (a:A)-[:R]->(b:B)<-[:S]-(c:C) RETURN a").unwrap();
+        let GraphPattern::Path(path) = &ast.match_clauses[0].patterns[0] else {
+            panic!("Expected path pattern");
+        };
+        let reversed = reverse_path(path);
+        assert_eq!(reversed.start_node.variable.as_deref(), Some("c"));
+        assert_eq!(reversed.segments[0].relationship.types, vec!["S"]);
+        assert_eq!(
+            reversed.segments[0].relationship.direction,
+            RelationshipDirection::Outgoing
+        );
+        assert_eq!(reversed.segments[0].end_node.variable.as_deref(), Some("b"));
+        assert_eq!(
+            reversed.segments[1].relationship.direction,
+            RelationshipDirection::Incoming
+        );
+        assert_eq!(reversed.segments[1].end_node.variable.as_deref(), Some("a"));
+        assert_eq!(&reverse_path(&reversed), path);
+    }
 }
@@ -0,0 +1,202 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Heuristic mapping inference from table schemas.
+//!
+//! Bootstrapping a graph over existing tables means writing a mapping per table.
+//! [`infer_node_mapping`] and [`infer_relationship_mapping`] guess a plausible mapping
+//! from an Arrow schema alone: key columns are picked by naming convention and every
+//! other column becomes a property. The result is a starting point to adjust with the
+//! usual `with_*` builders before registering it.
+
+use arrow_schema::Schema;
+
+use crate::config::{NodeMapping, RelationshipMapping};
+use crate::error::{GraphError, Result};
+
+/// Source/target column pairs recognised on relationship tables, in priority order.
+pub const RELATIONSHIP_ENDPOINT_FIELDS: &[(&str, &str)] = &[
+    ("src_id", "dst_id"),
+    ("source_id", "target_id"),
+    ("src", "dst"),
+    ("from_id", "to_id"),
+    ("source", "target"),
+    ("from", "to"),
+    ("start_id", "end_id"),
+];
+
+/// Prefixes marking the source and target side of a prefixed endpoint pair such as
+/// `src_person_id` / `dst_person_id`.
+const ENDPOINT_PREFIXES: &[(&str, &str)] = &[
+    ("src_", "dst_"),
+    ("source_", "target_"),
+    ("from_", "to_"),
+    ("start_", "end_"),
+];
+
+/// Infer a node mapping for `label` from the schema of its table.
+///
+/// The id column is the first of `id`, `<label>_id`, `<label>id`, `uuid` and `key`
+/// present (compared case-insensitively, with the label in snake case), falling back
+/// to the only column ending in `_id`. All other columns become properties.
+pub fn infer_node_mapping(label: &str, schema: &Schema) -> Result<NodeMapping> {
+    let id_field = infer_node_id(label, schema).ok_or_else(|| GraphError::ConfigError {
+        message: format!(
+            "Cannot infer an id column for node label '{}' from columns [{}]",
+            label,
+            column_names(schema).join(", ")
+        ),
+        location: snafu::Location::new(file!(), line!(), column!()),
+    })?;
+    let properties = column_names(schema)
+        .into_iter()
+        .filter(|name| *name != id_field)
+        .collect();
+    Ok(NodeMapping::new(label.to_string(), id_field).with_properties(properties))
+}
+
+/// Infer a relationship mapping for `rel_type` from the schema of its table.
+///
+/// Endpoints are the first of [`RELATIONSHIP_ENDPOINT_FIELDS`] present, then a pair of
+/// columns sharing a suffix after source/target prefixes (`src_person_id` /
+/// `dst_person_id`), then exactly two columns ending in `_id`, in schema order. All
+/// other columns become properties.
+pub fn infer_relationship_mapping(rel_type: &str, schema: &Schema) -> Result<RelationshipMapping> {
+    let (source, target) = infer_endpoints(schema).ok_or_else(|| GraphError::ConfigError {
+        message: format!(
+            "Cannot infer source/target id columns for relationship type '{}' from columns [{}]",
+            rel_type,
+            column_names(schema).join(", ")
+        ),
+        location: snafu::Location::new(file!(), line!(), column!()),
+    })?;
+    let properties = column_names(schema)
+        .into_iter()
+        .filter(|name| *name != source && *name != target)
+        .collect();
+    Ok(RelationshipMapping::new(rel_type.to_string(), source, target).with_properties(properties))
+}
+
+fn infer_node_id(label: &str, schema: &Schema) -> Option<String> {
+    let names = column_names(schema);
+    let snake_label = crate::config::LabelResolution::SnakeCase.normalize(label);
+    let candidates = [
+        "id".to_string(),
+        format!("{}_id", snake_label),
+        format!("{}id", snake_label.replace('_', "")),
+        "uuid".to_string(),
+        "key".to_string(),
+    ];
+    candidates
+        .iter()
+        .find_map(|candidate| {
+            names
+                .iter()
+                .find(|name| name.eq_ignore_ascii_case(candidate))
+        })
+        .or_else(|| only(names.iter().filter(|name| is_id_like(name))))
+        .cloned()
+}
+
+fn infer_endpoints(schema: &Schema) -> Option<(String, String)> {
+    let names = column_names(schema);
+    let has = |name: &str| names.iter().any(|n| n == name);
+
+    if let Some((source, target)) = RELATIONSHIP_ENDPOINT_FIELDS
+        .iter()
+        .find(|(source, target)| has(source) && has(target))
+    {
+        return Some((source.to_string(), target.to_string()));
+    }
+
+    for (source_prefix, target_prefix) in ENDPOINT_PREFIXES {
+        for name in &names {
+            if let Some(suffix) = name.strip_prefix(source_prefix) {
+                let target = format!("{}{}", target_prefix, suffix);
+                if has(&target) {
+                    return Some((name.clone(), target));
+                }
+            }
+        }
+    }
+
+    let mut ids = names.iter().filter(|name| is_id_like(name));
+    match (ids.next(), ids.next(), ids.next()) {
+        (Some(source), Some(target), None) => Some((source.clone(), target.clone())),
+        _ => None,
+    }
+}
+
+fn is_id_like(name: &str) -> bool {
+    name.to_ascii_lowercase().ends_with("_id")
+}
+
+fn only<T>(mut iter: impl Iterator<Item = T>) -> Option<T> {
+    let first = iter.next()?;
+    iter.next().is_none().then_some(first)
+}
+
+fn column_names(schema: &Schema) -> Vec<String> {
+    schema.fields().iter().map(|f| f.name().clone()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_schema::{DataType, Field};
+
+    fn schema(columns: &[&str]) -> Schema {
+        Schema::new(
+            columns
+                .iter()
+                .map(|name| Field::new(*name, DataType::Int64, false))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    #[test]
+    fn test_infer_node_id_column() {
+        let cases = [
+            ("Person", vec!["name", "id"], "id"),
+            ("Person", vec!["name", "person_id"], "person_id"),
+            ("BlogPost", vec!["title", "blog_post_id"], "blog_post_id"),
+            ("BlogPost", vec!["title", "BlogPostId"], "BlogPostId"),
+            ("Account", vec!["uuid", "owner"], "uuid"),
+            ("Company", vec!["name", "org_id"], "org_id"),
+        ];
+        for (label, columns, expected) in cases {
+            let mapping = infer_node_mapping(label, &schema(&columns)).unwrap();
+            assert_eq!(mapping.id_field, expected, "{} {:?}", label, columns);
+            assert!(!mapping.property_fields.contains(&mapping.id_field));
+            assert_eq!(mapping.property_fields.len(), columns.len() - 1);
+        }
+
+        let err = infer_node_mapping("Person", &schema(&["a_id", "b_id"])).unwrap_err();
+        assert!(err.to_string().contains("a_id, b_id"), "{}", err);
+    }
+
+    #[test]
+    fn test_infer_relationship_endpoints() {
+        let cases = [
+            (vec!["src_id", "dst_id", "since"], ("src_id", "dst_id")),
+            (vec!["from", "to", "weight"], ("from", "to")),
+            (
+                vec!["since", "src_person_id", "dst_person_id"],
+                ("src_person_id", "dst_person_id"),
+            ),
+            (
+                vec!["person_id", "company_id", "role"],
+                ("person_id", "company_id"),
+            ),
+        ];
+        for (columns, (source, target)) in cases {
+            let mapping = infer_relationship_mapping("REL", &schema(&columns)).unwrap();
+            assert_eq!(mapping.source_id_field, source, "{:?}", columns);
+            assert_eq!(mapping.target_id_field, target, "{:?}", columns);
+            assert_eq!(mapping.property_fields.len(), columns.len() - 2);
+        }
+
+        assert!(infer_relationship_mapping("REL", &schema(&["a", "b"])).is_err());
+        assert!(infer_relationship_mapping("REL", &schema(&["a_id", "b_id", "c_id"])).is_err());
+    }
+}
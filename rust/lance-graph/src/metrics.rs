@@ -0,0 +1,191 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Metrics of executed queries.
+//!
+//! [`CypherQuery::execute_with_metrics`] returns a [`QueryResult`] whose
+//! [`QueryMetrics`] hold, for every operator of the physical plan, the rows it produced,
+//! the time it spent computing and the data it spilled, nested like the plan. Scans,
+//! joins and expansions appear under their DataFusion names (`DataSourceExec`,
+//! `HashJoinExec`, `FrontierExec`, ...). The `profile_json` row of a `PROFILE` statement
+//! is the JSON form of the same metrics.
+//!
+//! [`CypherQuery::execute_with_metrics`]: crate::query::CypherQuery::execute_with_metrics
+
+use std::time::Duration;
+
+use arrow_array::RecordBatch;
+use datafusion::physical_plan::ExecutionPlan;
+
+/// Rows and metrics of one execution of a query
+#[derive(Debug, Clone)]
+pub struct QueryResult {
+    pub batch: RecordBatch,
+    pub metrics: QueryMetrics,
+}
+
+/// Metrics of one execution of a query
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryMetrics {
+    /// Rows returned
+    pub output_rows: usize,
+    /// Batches the rows were returned in
+    pub output_batches: usize,
+    /// Time from starting the plan to its last batch
+    pub elapsed: Duration,
+    /// Metrics of the root operator, holding those of its inputs
+    pub plan: OperatorMetrics,
+}
+
+/// Metrics of one operator of an executed plan
+///
+/// A metric is `None` when the operator does not record it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OperatorMetrics {
+    /// DataFusion name of the operator, such as `HashJoinExec`
+    pub operator: String,
+    /// Rows produced, summed over partitions
+    pub output_rows: Option<usize>,
+    /// CPU time spent producing the rows, summed over partitions
+    pub elapsed_compute: Option<Duration>,
+    /// Bytes written to spill files
+    pub spilled_bytes: Option<usize>,
+    /// Spill files written
+    pub spill_count: Option<usize>,
+    pub children: Vec<OperatorMetrics>,
+}
+
+impl QueryMetrics {
+    /// Metrics of `plan` once run to completion, having returned `batches` after
+    /// `elapsed`
+    pub(crate) fn from_plan(
+        plan: &dyn ExecutionPlan,
+        batches: &[RecordBatch],
+        elapsed: Duration,
+    ) -> Self {
+        Self {
+            output_rows: batches.iter().map(|batch| batch.num_rows()).sum(),
+            output_batches: batches.len(),
+            elapsed,
+            plan: OperatorMetrics::from_plan(plan),
+        }
+    }
+
+    /// Every operator of the plan, each before its inputs
+    pub fn operators(&self) -> Vec<&OperatorMetrics> {
+        fn visit<'a>(operator: &'a OperatorMetrics, out: &mut Vec<&'a OperatorMetrics>) {
+            out.push(operator);
+            for child in &operator.children {
+                visit(child, out);
+            }
+        }
+        let mut out = Vec::new();
+        visit(&self.plan, &mut out);
+        out
+    }
+
+    /// The operator that spent the most compute time, excluding its inputs' time
+    pub fn slowest_operator(&self) -> Option<&OperatorMetrics> {
+        self.operators()
+            .into_iter()
+            .filter(|operator| operator.elapsed_compute.is_some())
+            .max_by_key(|operator| operator.elapsed_compute)
+    }
+
+    /// The metrics as the `profile_json` of a `PROFILE` statement
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "rows": self.output_rows,
+            "batches": self.output_batches,
+            "elapsed_ns": self.elapsed.as_nanos() as u64,
+            "plan": self.plan.to_json(),
+        })
+    }
+}
+
+impl OperatorMetrics {
+    fn from_plan(plan: &dyn ExecutionPlan) -> Self {
+        let metrics = plan.metrics().map(|m| m.aggregate_by_name());
+        Self {
+            operator: plan.name().to_string(),
+            output_rows: metrics.as_ref().and_then(|m| m.output_rows()),
+            elapsed_compute: metrics
+                .as_ref()
+                .and_then(|m| m.elapsed_compute())
+                .map(|ns| Duration::from_nanos(ns as u64)),
+            spilled_bytes: metrics.as_ref().and_then(|m| m.spilled_bytes()),
+            spill_count: metrics.as_ref().and_then(|m| m.spill_count()),
+            children: plan
+                .children()
+                .into_iter()
+                .map(|child| Self::from_plan(child.as_ref()))
+                .collect(),
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "operator": self.operator,
+            "output_rows": self.output_rows,
+            "elapsed_compute_ns": self.elapsed_compute.map(|d| d.as_nanos() as u64),
+            "spilled_bytes": self.spilled_bytes,
+            "spill_count": self.spill_count,
+            "children": self
+                .children
+                .iter()
+                .map(OperatorMetrics::to_json)
+                .collect::<Vec<_>>(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn operator(name: &str, ms: Option<u64>, children: Vec<OperatorMetrics>) -> OperatorMetrics {
+        OperatorMetrics {
+            operator: name.to_string(),
+            output_rows: Some(1),
+            elapsed_compute: ms.map(Duration::from_millis),
+            spilled_bytes: None,
+            spill_count: None,
+            children,
+        }
+    }
+
+    #[test]
+    fn test_operators_and_slowest() {
+        let metrics = QueryMetrics {
+            output_rows: 1,
+            output_batches: 1,
+            elapsed: Duration::from_millis(20),
+            plan: operator(
+                "ProjectionExec",
+                Some(1),
+                vec![
+                    operator("HashJoinExec", Some(12), vec![]),
+                    operator("DataSourceExec", None, vec![]),
+                ],
+            ),
+        };
+        let names: Vec<_> = metrics
+            .operators()
+            .iter()
+            .map(|op| op.operator.as_str())
+            .collect();
+        assert_eq!(
+            names,
+            vec!["ProjectionExec", "HashJoinExec", "DataSourceExec"]
+        );
+        assert_eq!(metrics.slowest_operator().unwrap().operator, "HashJoinExec");
+
+        let json = metrics.to_json();
+        assert_eq!(json["elapsed_ns"], 20_000_000);
+        assert_eq!(
+            json["plan"]["children"][0]["elapsed_compute_ns"],
+            12_000_000
+        );
+        assert!(json["plan"]["children"][1]["elapsed_compute_ns"].is_null());
+    }
+}
@@ -0,0 +1,770 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Query parameters
+//!
+//! `$name` placeholders are bound to values supplied as JSON or as typed DataFusion
+//! scalars. Before binding, each parameter is checked against how the query uses it:
+//! a parameter compared with a property must have a type comparable with the
+//! property's column, and one unwound must be a list.
+
+use crate::ast::{
+    BooleanExpression, CypherQuery, GraphPattern, PropertyValue, ValueExpression, WriteClause,
+};
+use crate::config::GraphConfig;
+use crate::error::{GraphError, Result};
+use crate::source_catalog::GraphSourceCatalog;
+use arrow_schema::DataType;
+use datafusion::scalar::ScalarValue;
+use std::collections::HashMap;
+
+/// Convert the JSON and typed parameter values into the literals they bind
+pub(crate) fn resolve_parameters(
+    json: &HashMap<String, serde_json::Value>,
+    scalars: &HashMap<String, ScalarValue>,
+) -> Result<HashMap<String, PropertyValue>> {
+    let unsupported = |name: &str, value: String| GraphError::PlanError {
+        message: format!("Unsupported value for parameter '${}': {}", name, value),
+        location: snafu::Location::new(file!(), line!(), column!()),
+    };
+
+    let mut resolved = HashMap::with_capacity(json.len() + scalars.len());
+    for (name, value) in json {
+        let literal =
+            json_to_property_value(value).ok_or_else(|| unsupported(name, value.to_string()))?;
+        resolved.insert(name.clone(), literal);
+    }
+    for (name, value) in scalars {
+        let literal = scalar_to_property_value(value)
+            .ok_or_else(|| unsupported(name, format!("{} of type {}", value, value.data_type())))?;
+        resolved.insert(name.clone(), literal);
+    }
+    Ok(resolved)
+}
+
+/// Replace every `$name` placeholder with the value bound to `name`
+pub(crate) fn bind_parameters(
+    ast: &mut CypherQuery,
+    parameters: &HashMap<String, PropertyValue>,
+) -> Result<()> {
+    use crate::ast::{BooleanExpression as BE, PropertyValue as PV, ValueExpression as VE};
+
+    fn bind_value(value: &mut PV, parameters: &HashMap<String, PropertyValue>) -> Result<()> {
+        match value {
+            PV::Parameter(name) => {
+                let bound = parameters
+                    .get(name.as_str())
+                    .ok_or_else(|| GraphError::PlanError {
+                        message: format!("Missing value for parameter '${}'", name),
+                        location: snafu::Location::new(file!(), line!(), column!()),
+                    })?;
+                *value = bound.clone();
+            }
+            PV::List(items) => {
+                for item in items {
+                    bind_value(item, parameters)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn bind_expression(expr: &mut VE, parameters: &HashMap<String, PropertyValue>) -> Result<()> {
+        match expr {
+            VE::Literal(value) => bind_value(value, parameters),
+            VE::Function { args, .. } => args
+                .iter_mut()
+                .try_for_each(|arg| bind_expression(arg, parameters)),
+            VE::Case {
+                branches,
+                else_result,
+            } => {
+                for (condition, result) in branches {
+                    bind_predicate(condition, parameters)?;
+                    bind_expression(result, parameters)?;
+                }
+                match else_result {
+                    Some(else_result) => bind_expression(else_result, parameters),
+                    None => Ok(()),
+                }
+            }
+            VE::List(items) => items
+                .iter_mut()
+                .try_for_each(|item| bind_expression(item, parameters)),
+            VE::Index { list, index } => {
+                bind_expression(list, parameters)?;
+                bind_expression(index, parameters)
+            }
+            VE::Slice { list, from, to } => {
+                bind_expression(list, parameters)?;
+                [from, to]
+                    .into_iter()
+                    .flatten()
+                    .try_for_each(|bound| bind_expression(bound, parameters))
+            }
+            VE::ListComprehension {
+                list,
+                predicate,
+                projection,
+                ..
+            } => {
+                bind_expression(list, parameters)?;
+                if let Some(predicate) = predicate {
+                    bind_predicate(predicate, parameters)?;
+                }
+                match projection {
+                    Some(projection) => bind_expression(projection, parameters),
+                    None => Ok(()),
+                }
+            }
+            VE::CountSubquery(clause) => bind_match(clause, parameters),
+            VE::Arithmetic { left, right, .. } => {
+                bind_expression(left, parameters)?;
+                bind_expression(right, parameters)
+            }
+            VE::Map(entries) => entries
+                .iter_mut()
+                .try_for_each(|(_, value)| bind_expression(value, parameters)),
+            VE::Variable(_) | VE::Property(_) => Ok(()),
+        }
+    }
+
+    fn bind_predicate(expr: &mut BE, parameters: &HashMap<String, PropertyValue>) -> Result<()> {
+        match expr {
+            BE::Comparison { left, right, .. } => {
+                bind_expression(left, parameters)?;
+                bind_expression(right, parameters)
+            }
+            BE::StringMatch {
+                expression,
+                pattern,
+                ..
+            } => {
+                bind_expression(expression, parameters)?;
+                bind_expression(pattern, parameters)
+            }
+            BE::And(l, r) | BE::Or(l, r) => {
+                bind_predicate(l, parameters)?;
+                bind_predicate(r, parameters)
+            }
+            BE::Not(inner) => bind_predicate(inner, parameters),
+            BE::FullTextSearch { expression, query } => {
+                bind_expression(expression, parameters)?;
+                bind_expression(query, parameters)
+            }
+            BE::In { expression, list } => {
+                bind_expression(expression, parameters)?;
+                list.iter_mut()
+                    .try_for_each(|item| bind_expression(item, parameters))?;
+                // `x IN $list` supplies the whole list, so splice its items in
+                if let [VE::Literal(PV::List(items))] = list.as_mut_slice() {
+                    let items = std::mem::take(items);
+                    *list = items.into_iter().map(VE::Literal).collect();
+                }
+                Ok(())
+            }
+            BE::Like { expression, .. } | BE::IsNull(expression) | BE::IsNotNull(expression) => {
+                bind_expression(expression, parameters)
+            }
+            BE::Exists(_) | BE::HasLabels { .. } => Ok(()),
+            BE::ExistsSubquery(clause) => bind_match(clause, parameters),
+        }
+    }
+
+    fn bind_match(
+        clause: &mut crate::ast::MatchClause,
+        parameters: &HashMap<String, PropertyValue>,
+    ) -> Result<()> {
+        bind_patterns(&mut clause.patterns, parameters)?;
+        if let Some(where_clause) = &mut clause.where_clause {
+            bind_predicate(&mut where_clause.expression, parameters)?;
+        }
+        Ok(())
+    }
+
+    fn bind_patterns(
+        patterns: &mut [GraphPattern],
+        parameters: &HashMap<String, PropertyValue>,
+    ) -> Result<()> {
+        for pattern in patterns {
+            let (nodes, relationships) = match pattern {
+                GraphPattern::Node(node) => (vec![node], Vec::new()),
+                GraphPattern::Path(path) => {
+                    let mut nodes = vec![&mut path.start_node];
+                    let mut relationships = Vec::new();
+                    for segment in &mut path.segments {
+                        nodes.push(&mut segment.end_node);
+                        relationships.push(&mut segment.relationship);
+                    }
+                    (nodes, relationships)
+                }
+            };
+            let values = nodes
+                .into_iter()
+                .flat_map(|node| node.properties.values_mut())
+                .chain(
+                    relationships
+                        .into_iter()
+                        .flat_map(|rel| rel.properties.values_mut()),
+                );
+            for value in values {
+                bind_value(value, parameters)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn bind_call(
+        call: &mut crate::ast::CallClause,
+        parameters: &HashMap<String, PropertyValue>,
+    ) -> Result<()> {
+        for argument in &mut call.arguments {
+            bind_expression(argument, parameters)?;
+        }
+        if let Some(where_clause) = &mut call.where_clause {
+            bind_predicate(&mut where_clause.expression, parameters)?;
+        }
+        Ok(())
+    }
+
+    for clause in &mut ast.match_clauses {
+        bind_match(clause, parameters)?;
+    }
+    for unwind in &mut ast.unwind_clauses {
+        bind_expression(&mut unwind.expression, parameters)?;
+    }
+    for call in &mut ast.call_clauses {
+        bind_call(call, parameters)?;
+    }
+    if let Some(where_clause) = &mut ast.where_clause {
+        bind_predicate(&mut where_clause.expression, parameters)?;
+    }
+    for with_clause in &mut ast.with_clauses {
+        for item in &mut with_clause.items {
+            bind_expression(&mut item.expression, parameters)?;
+        }
+        for where_clause in with_clause
+            .where_clause
+            .iter_mut()
+            .chain(with_clause.match_where_clause.iter_mut())
+        {
+            bind_predicate(&mut where_clause.expression, parameters)?;
+        }
+        for clause in &mut with_clause.match_clauses {
+            bind_match(clause, parameters)?;
+        }
+        for unwind in &mut with_clause.unwind_clauses {
+            bind_expression(&mut unwind.expression, parameters)?;
+        }
+        for call in &mut with_clause.call_clauses {
+            bind_call(call, parameters)?;
+        }
+    }
+    for item in &mut ast.return_clause.items {
+        bind_expression(&mut item.expression, parameters)?;
+    }
+    for write_clause in &mut ast.write_clauses {
+        match write_clause {
+            WriteClause::Create(create) => bind_patterns(&mut create.patterns, parameters)?,
+            WriteClause::Merge(merge) => {
+                bind_patterns(std::slice::from_mut(&mut merge.pattern), parameters)?;
+                for item in merge.on_create.iter_mut().chain(merge.on_match.iter_mut()) {
+                    bind_expression(&mut item.value, parameters)?;
+                }
+            }
+            WriteClause::Set(set) => {
+                for item in &mut set.items {
+                    bind_expression(&mut item.value, parameters)?;
+                }
+            }
+            WriteClause::Remove(_) | WriteClause::Delete(_) => {}
+        }
+    }
+    let order_by = ast
+        .with_clauses
+        .iter_mut()
+        .filter_map(|w| w.order_by.as_mut())
+        .chain(ast.order_by.as_mut());
+    for item in order_by.flat_map(|o| o.items.iter_mut()) {
+        bind_expression(&mut item.expression, parameters)?;
+    }
+    for union in &mut ast.unions {
+        bind_parameters(&mut union.query, parameters)?;
+    }
+    Ok(())
+}
+
+/// Convert a JSON parameter value into a literal; objects have no literal form
+fn json_to_property_value(value: &serde_json::Value) -> Option<PropertyValue> {
+    use serde_json::Value;
+
+    match value {
+        Value::Null => Some(PropertyValue::Null),
+        Value::Bool(b) => Some(PropertyValue::Boolean(*b)),
+        Value::Number(n) => n
+            .as_i64()
+            .map(PropertyValue::Integer)
+            .or_else(|| n.as_f64().map(PropertyValue::Float)),
+        Value::String(s) => Some(PropertyValue::String(s.clone())),
+        Value::Array(items) => items
+            .iter()
+            .map(json_to_property_value)
+            .collect::<Option<Vec<_>>>()
+            .map(PropertyValue::List),
+        Value::Object(_) => None,
+    }
+}
+
+/// Convert a typed parameter value into a literal
+///
+/// Booleans, integers, floats, strings and lists of those are supported; NULL of any
+/// type binds NULL.
+fn scalar_to_property_value(value: &ScalarValue) -> Option<PropertyValue> {
+    if value.is_null() {
+        return Some(PropertyValue::Null);
+    }
+    Some(match value {
+        ScalarValue::Boolean(Some(b)) => PropertyValue::Boolean(*b),
+        ScalarValue::Int8(Some(i)) => PropertyValue::Integer((*i).into()),
+        ScalarValue::Int16(Some(i)) => PropertyValue::Integer((*i).into()),
+        ScalarValue::Int32(Some(i)) => PropertyValue::Integer((*i).into()),
+        ScalarValue::Int64(Some(i)) => PropertyValue::Integer(*i),
+        ScalarValue::UInt8(Some(i)) => PropertyValue::Integer((*i).into()),
+        ScalarValue::UInt16(Some(i)) => PropertyValue::Integer((*i).into()),
+        ScalarValue::UInt32(Some(i)) => PropertyValue::Integer((*i).into()),
+        ScalarValue::UInt64(Some(i)) => PropertyValue::Integer(i64::try_from(*i).ok()?),
+        ScalarValue::Float16(Some(f)) => PropertyValue::Float(f64::from(*f)),
+        ScalarValue::Float32(Some(f)) => PropertyValue::Float((*f).into()),
+        ScalarValue::Float64(Some(f)) => PropertyValue::Float(*f),
+        ScalarValue::Utf8(Some(s))
+        | ScalarValue::LargeUtf8(Some(s))
+        | ScalarValue::Utf8View(Some(s)) => PropertyValue::String(s.clone()),
+        ScalarValue::List(list) => list_items(list.value(0))?,
+        ScalarValue::LargeList(list) => list_items(list.value(0))?,
+        ScalarValue::FixedSizeList(list) => list_items(list.value(0))?,
+        _ => return None,
+    })
+}
+
+fn list_items(values: arrow_array::ArrayRef) -> Option<PropertyValue> {
+    (0..values.len())
+        .map(|i| {
+            ScalarValue::try_from_array(&values, i)
+                .ok()
+                .and_then(|item| scalar_to_property_value(&item))
+        })
+        .collect::<Option<Vec<_>>>()
+        .map(PropertyValue::List)
+}
+
+/// Check the bound parameters against how the query uses them
+///
+/// Parameters compared with a property, matched against one in a pattern or listed in
+/// an `IN` list on one must be comparable with the property's column type, and
+/// parameters expanded by UNWIND must be lists. Properties whose type cannot be found
+/// in the catalog are not checked.
+pub(crate) fn validate_parameter_types(
+    query: &CypherQuery,
+    parameters: &HashMap<String, PropertyValue>,
+    config: &GraphConfig,
+    catalog: &dyn GraphSourceCatalog,
+) -> Result<()> {
+    let validator = ParameterValidator {
+        parameters,
+        config,
+        catalog,
+        bindings: variable_bindings(query),
+    };
+    validator.validate_query(query)?;
+    for union in &query.unions {
+        validate_parameter_types(&union.query, parameters, config, catalog)?;
+    }
+    Ok(())
+}
+
+/// What a pattern variable is bound to
+enum Binding {
+    Node(String),
+    Relationship(String),
+}
+
+/// Labels and relationship types of the pattern variables of one query part
+fn variable_bindings(query: &CypherQuery) -> HashMap<String, Binding> {
+    let mut bindings = HashMap::new();
+    for clause in query.part_match_clauses() {
+        for pattern in &clause.patterns {
+            let (start, segments) = match pattern {
+                GraphPattern::Node(node) => (node, &[][..]),
+                GraphPattern::Path(path) => (&path.start_node, &path.segments[..]),
+            };
+            let nodes = std::iter::once(start).chain(segments.iter().map(|s| &s.end_node));
+            for node in nodes {
+                if let (Some(variable), Some(label)) = (&node.variable, node.labels.first()) {
+                    bindings
+                        .entry(variable.clone())
+                        .or_insert_with(|| Binding::Node(label.clone()));
+                }
+            }
+            for relationship in segments.iter().map(|s| &s.relationship) {
+                if let (Some(variable), [rel_type]) =
+                    (&relationship.variable, relationship.types.as_slice())
+                {
+                    bindings
+                        .entry(variable.clone())
+                        .or_insert_with(|| Binding::Relationship(rel_type.clone()));
+                }
+            }
+        }
+    }
+    bindings
+}
+
+struct ParameterValidator<'a> {
+    parameters: &'a HashMap<String, PropertyValue>,
+    config: &'a GraphConfig,
+    catalog: &'a dyn GraphSourceCatalog,
+    bindings: HashMap<String, Binding>,
+}
+
+impl ParameterValidator<'_> {
+    fn validate_query(&self, query: &CypherQuery) -> Result<()> {
+        let mut predicates: Vec<&BooleanExpression> = Vec::new();
+        let mut expressions: Vec<&ValueExpression> = Vec::new();
+        let mut unwinds: Vec<&ValueExpression> = Vec::new();
+
+        for clause in query.part_match_clauses() {
+            for pattern in &clause.patterns {
+                self.validate_pattern(pattern)?;
+            }
+            predicates.extend(clause.where_clause.iter().map(|w| &w.expression));
+        }
+        predicates.extend(query.where_clause.iter().map(|w| &w.expression));
+        unwinds.extend(query.unwind_clauses.iter().map(|u| &u.expression));
+        for with_clause in &query.with_clauses {
+            predicates.extend(
+                with_clause
+                    .where_clause
+                    .iter()
+                    .chain(&with_clause.match_where_clause)
+                    .map(|w| &w.expression),
+            );
+            expressions.extend(with_clause.items.iter().map(|i| &i.expression));
+            unwinds.extend(with_clause.unwind_clauses.iter().map(|u| &u.expression));
+        }
+        expressions.extend(query.return_clause.items.iter().map(|i| &i.expression));
+        let calls = query
+            .call_clauses
+            .iter()
+            .chain(query.with_clauses.iter().flat_map(|w| &w.call_clauses));
+        for call in calls {
+            expressions.extend(&call.arguments);
+            predicates.extend(call.where_clause.iter().map(|w| &w.expression));
+        }
+
+        for unwind in unwinds {
+            if let ValueExpression::Literal(PropertyValue::Parameter(name)) = unwind {
+                match self.parameters.get(name) {
+                    Some(PropertyValue::List(_) | PropertyValue::Null) | None => {}
+                    Some(value) => {
+                        return Err(GraphError::PlanError {
+                            message: format!(
+                                "Parameter '${}' is expanded by UNWIND, so it must be a list, \
+                                 but it is bound to {}",
+                                name,
+                                describe(value)
+                            ),
+                            location: snafu::Location::new(file!(), line!(), column!()),
+                        })
+                    }
+                }
+            }
+        }
+        for predicate in predicates {
+            self.validate_predicate(predicate)?;
+        }
+        for expression in expressions {
+            self.validate_expression(expression)?;
+        }
+        Ok(())
+    }
+
+    fn validate_pattern(&self, pattern: &GraphPattern) -> Result<()> {
+        let (start, segments) = match pattern {
+            GraphPattern::Node(node) => (node, &[][..]),
+            GraphPattern::Path(path) => (&path.start_node, &path.segments[..]),
+        };
+        for node in std::iter::once(start).chain(segments.iter().map(|s| &s.end_node)) {
+            if let Some(label) = node.labels.first() {
+                for (property, value) in &node.properties {
+                    let binding = Binding::Node(label.clone());
+                    self.validate_comparison(&binding, label, property, value, false)?;
+                }
+            }
+        }
+        for relationship in segments.iter().map(|s| &s.relationship) {
+            if let [rel_type] = relationship.types.as_slice() {
+                for (property, value) in &relationship.properties {
+                    let binding = Binding::Relationship(rel_type.clone());
+                    self.validate_comparison(&binding, rel_type, property, value, false)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_predicate(&self, predicate: &BooleanExpression) -> Result<()> {
+        match predicate {
+            BooleanExpression::Comparison { left, right, .. } => {
+                for (property, value) in [(left, right), (right, left)] {
+                    if let (
+                        ValueExpression::Property(property),
+                        ValueExpression::Literal(value @ PropertyValue::Parameter(_)),
+                    ) = (property, value)
+                    {
+                        self.validate_property(property, value, false)?;
+                    }
+                }
+            }
+            BooleanExpression::In {
+                expression: ValueExpression::Property(property),
+                list,
+            } => {
+                for item in list {
+                    if let ValueExpression::Literal(value @ PropertyValue::Parameter(_)) = item {
+                        self.validate_property(property, value, true)?;
+                    }
+                }
+            }
+            _ => {}
+        }
+        let (values, predicates) = predicate.operands();
+        for value in values {
+            self.validate_expression(value)?;
+        }
+        for predicate in predicates {
+            self.validate_predicate(predicate)?;
+        }
+        Ok(())
+    }
+
+    fn validate_expression(&self, expression: &ValueExpression) -> Result<()> {
+        let (values, predicates) = expression.operands();
+        for value in values {
+            self.validate_expression(value)?;
+        }
+        for predicate in predicates {
+            self.validate_predicate(predicate)?;
+        }
+        Ok(())
+    }
+
+    fn validate_property(
+        &self,
+        property: &crate::ast::PropertyRef,
+        value: &PropertyValue,
+        in_list: bool,
+    ) -> Result<()> {
+        match self.bindings.get(&property.variable) {
+            Some(binding) => self.validate_comparison(
+                binding,
+                &property.variable,
+                &property.property,
+                value,
+                in_list,
+            ),
+            None => Ok(()),
+        }
+    }
+
+    /// Check a parameter compared with `property` of a node label or relationship type;
+    /// the items of a list parameter are compared when it supplies an `IN` list
+    fn validate_comparison(
+        &self,
+        binding: &Binding,
+        owner: &str,
+        property: &str,
+        value: &PropertyValue,
+        in_list: bool,
+    ) -> Result<()> {
+        let PropertyValue::Parameter(name) = value else {
+            return Ok(());
+        };
+        let Some(bound) = self.parameters.get(name) else {
+            // Reported as missing when the parameter is bound
+            return Ok(());
+        };
+        let Some(data_type) = self.property_type(binding, property) else {
+            return Ok(());
+        };
+        let compatible = match bound {
+            PropertyValue::List(items) if in_list => {
+                items.iter().all(|item| is_comparable(item, &data_type))
+            }
+            bound => is_comparable(bound, &data_type),
+        };
+        if compatible {
+            return Ok(());
+        }
+        Err(GraphError::PlanError {
+            message: format!(
+                "Parameter '${}' is bound to {}, which cannot be compared with {}.{} of type {}",
+                name,
+                describe(bound),
+                owner,
+                property,
+                data_type
+            ),
+            location: snafu::Location::new(file!(), line!(), column!()),
+        })
+    }
+
+    /// Type of the column behind a property, if the catalog knows it
+    fn property_type(&self, binding: &Binding, property: &str) -> Option<DataType> {
+        let (source, column) = match binding {
+            Binding::Node(label) => {
+                let mapping = self.config.get_node_mapping(label);
+                if mapping.is_some_and(|m| m.computed_properties.contains_key(property)) {
+                    return None;
+                }
+                (
+                    self.catalog.node_source(label)?,
+                    mapping.map_or(property, |m| m.column_for(property)),
+                )
+            }
+            Binding::Relationship(rel_type) => {
+                let mapping = self.config.get_relationship_mapping(rel_type);
+                if mapping.is_some_and(|m| m.computed_properties.contains_key(property)) {
+                    return None;
+                }
+                (
+                    self.catalog.relationship_source(rel_type)?,
+                    mapping.map_or(property, |m| m.column_for(property)),
+                )
+            }
+        };
+        let schema = source.schema();
+        let field = schema.field_with_name(column).ok()?;
+        Some(field.data_type().clone())
+    }
+}
+
+/// Whether a literal can be compared with values of `data_type`
+fn is_comparable(value: &PropertyValue, data_type: &DataType) -> bool {
+    match (value, data_type) {
+        (_, DataType::Dictionary(_, values)) => is_comparable(value, values),
+        (PropertyValue::Null, _) => true,
+        (PropertyValue::Integer(_) | PropertyValue::Float(_), data_type) => data_type.is_numeric(),
+        // Strings are also accepted for temporal columns, which parse them
+        (PropertyValue::String(_), data_type) => {
+            matches!(
+                data_type,
+                DataType::Utf8 | DataType::LargeUtf8 | DataType::Utf8View
+            ) || data_type.is_temporal()
+        }
+        (PropertyValue::Boolean(_), data_type) => *data_type == DataType::Boolean,
+        (
+            PropertyValue::List(items),
+            DataType::List(field) | DataType::LargeList(field) | DataType::FixedSizeList(field, _),
+        ) => items
+            .iter()
+            .all(|item| is_comparable(item, field.data_type())),
+        (PropertyValue::List(_), _) => false,
+        _ => true,
+    }
+}
+
+fn describe(value: &PropertyValue) -> &'static str {
+    match value {
+        PropertyValue::String(_) => "a string",
+        PropertyValue::Integer(_) => "an integer",
+        PropertyValue::Float(_) => "a float",
+        PropertyValue::Boolean(_) => "a boolean",
+        PropertyValue::Null => "NULL",
+        PropertyValue::List(_) => "a list",
+        _ => "a value",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::{ArrayRef, Int32Array};
+    use datafusion::common::utils::SingleRowListArrayBuilder;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_scalar_parameters_convert_to_literals() {
+        let cases = [
+            (ScalarValue::Int32(Some(7)), PropertyValue::Integer(7)),
+            (ScalarValue::UInt8(Some(3)), PropertyValue::Integer(3)),
+            (ScalarValue::Float32(Some(1.5)), PropertyValue::Float(1.5)),
+            (
+                ScalarValue::Utf8View(Some("a".to_string())),
+                PropertyValue::String("a".to_string()),
+            ),
+            (
+                ScalarValue::Boolean(Some(true)),
+                PropertyValue::Boolean(true),
+            ),
+            (ScalarValue::Int64(None), PropertyValue::Null),
+        ];
+        for (scalar, expected) in cases {
+            assert_eq!(scalar_to_property_value(&scalar), Some(expected));
+        }
+
+        let values: ArrayRef = Arc::new(Int32Array::from(vec![Some(1), None]));
+        let list = SingleRowListArrayBuilder::new(values).build_list_scalar();
+        assert_eq!(
+            scalar_to_property_value(&list),
+            Some(PropertyValue::List(vec![
+                PropertyValue::Integer(1),
+                PropertyValue::Null
+            ]))
+        );
+
+        assert_eq!(
+            scalar_to_property_value(&ScalarValue::UInt64(Some(u64::MAX))),
+            None
+        );
+        assert!(resolve_parameters(
+            &HashMap::new(),
+            &HashMap::from([("d".to_string(), ScalarValue::Date32(Some(1)))]),
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_comparable_types() {
+        let list_type = DataType::List(Arc::new(arrow_schema::Field::new(
+            "item",
+            DataType::Utf8,
+            true,
+        )));
+        assert!(is_comparable(
+            &PropertyValue::Integer(1),
+            &DataType::Float64
+        ));
+        assert!(is_comparable(&PropertyValue::Null, &DataType::Boolean));
+        assert!(is_comparable(
+            &PropertyValue::String("2024-01-01".to_string()),
+            &DataType::Date32
+        ));
+        assert!(is_comparable(
+            &PropertyValue::List(vec![PropertyValue::String("a".to_string())]),
+            &list_type
+        ));
+        assert!(!is_comparable(
+            &PropertyValue::String("a".to_string()),
+            &DataType::Int64
+        ));
+        assert!(!is_comparable(
+            &PropertyValue::Integer(1),
+            &DataType::Boolean
+        ));
+        assert!(!is_comparable(
+            &PropertyValue::List(vec![PropertyValue::Integer(1)]),
+            &DataType::Int64
+        ));
+    }
+}
@@ -35,45 +35,346 @@ pub fn parse_cypher_query(input: &str) -> Result<CypherQuery> {
         });
     }
 
+    if let Some(first) = query.unions.first() {
+        if query.unions.iter().any(|union| union.all != first.all) {
+            return Err(GraphError::ParseError {
+                message: "Invalid combination of UNION and UNION ALL".to_string(),
+                position: 0,
+                location: snafu::Location::new(file!(), line!(), column!()),
+            });
+        }
+    }
+
     Ok(query)
 }
 
 // Top-level parser for a complete Cypher query
 fn cypher_query(input: &str) -> IResult<&str, CypherQuery> {
     let (input, _) = multispace0(input)?;
-    let (input, match_clauses) = many0(match_clause)(input)?;
+    let (input, explain) = opt(explain_prefix)(input)?;
+    let (input, graph) = opt(use_clause)(input)?;
+    let (input, mut query) = single_query(input)?;
+    let (input, unions) = many0(union_clause)(input)?;
+    let (input, _) = multispace0(input)?;
+
+    query.explain = explain;
+    query.graph = graph.map(|g| g.to_string());
+    query.unions = unions;
+    Ok((input, query))
+}
+
+// Parse EXPLAIN or PROFILE before a query
+fn explain_prefix(input: &str) -> IResult<&str, ExplainMode> {
+    let (input, mode) = alt((
+        map(tag_no_case("EXPLAIN"), |_| ExplainMode::Explain),
+        map(tag_no_case("PROFILE"), |_| ExplainMode::Profile),
+    ))(input)?;
+    let (input, _) = multispace1(input)?;
+    Ok((input, mode))
+}
+
+// Parse one query ending in RETURN, the unit combined by UNION
+fn single_query(input: &str) -> IResult<&str, CypherQuery> {
+    let (input, (match_clauses, unwind_clauses, call_clauses)) = reading_clauses(input)?;
     let (input, where_clause) = opt(where_clause)(input)?;
-    let (input, return_clause) = return_clause(input)?;
+    let (input, with_clauses) = many0(with_clause)(input)?;
+    let (input, write_clauses) = many0(write_clause)(input)?;
+    // A CALL standing alone returns the columns it yields
+    let standalone_call = match_clauses.is_empty()
+        && unwind_clauses.is_empty()
+        && call_clauses.len() == 1
+        && where_clause.is_none()
+        && with_clauses.is_empty();
+    // Queries ending in write clauses need not return anything
+    let (input, return_clause) = if standalone_call || !write_clauses.is_empty() {
+        let (input, return_clause) = opt(return_clause)(input)?;
+        let return_clause = return_clause.unwrap_or(ReturnClause {
+            distinct: false,
+            items: Vec::new(),
+        });
+        (input, return_clause)
+    } else {
+        return_clause(input)?
+    };
     let (input, order_by) = opt(order_by_clause)(input)?;
     let (input, (skip, limit)) = pagination_clauses(input)?;
-    let (input, _) = multispace0(input)?;
 
     Ok((
         input,
         CypherQuery {
+            graph: None,
+            explain: None,
             match_clauses,
+            unwind_clauses,
+            call_clauses,
             where_clause,
+            with_clauses,
+            write_clauses,
             return_clause,
             limit,
             order_by,
             skip,
+            unions: Vec::new(),
+        },
+    ))
+}
+
+// Parse UNION [ALL] followed by the query it appends
+fn union_clause(input: &str) -> IResult<&str, UnionClause> {
+    let (input, _) = multispace0(input)?;
+    let (input, _) = tag_no_case("UNION")(input)?;
+    let (input, _) = multispace1(input)?;
+    let (input, all) = opt(tuple((tag_no_case("ALL"), multispace1)))(input)?;
+    let (input, query) = single_query(input)?;
+    Ok((
+        input,
+        UnionClause {
+            all: all.is_some(),
+            query,
         },
     ))
 }
 
-// Parse a MATCH clause
+// Parse a USE clause selecting the graph to query
+fn use_clause(input: &str) -> IResult<&str, &str> {
+    let (input, _) = tag_no_case("USE")(input)?;
+    let (input, _) = multispace1(input)?;
+    let (input, graph) = identifier(input)?;
+    let (input, _) = multispace0(input)?;
+    Ok((input, graph))
+}
+
+// Parse a run of MATCH, UNWIND and CALL clauses, recording where each UNWIND and CALL
+// falls
+#[allow(clippy::type_complexity)]
+fn reading_clauses(
+    input: &str,
+) -> IResult<&str, (Vec<MatchClause>, Vec<UnwindClause>, Vec<CallClause>)> {
+    let mut input = input;
+    let mut match_clauses = Vec::new();
+    let mut unwind_clauses = Vec::new();
+    let mut call_clauses = Vec::new();
+    loop {
+        if let Ok((rest, (expression, alias))) = unwind_clause(input) {
+            unwind_clauses.push(UnwindClause {
+                expression,
+                alias: alias.to_string(),
+                preceding_matches: match_clauses.len(),
+            });
+            input = rest;
+        } else if let Ok((rest, mut clause)) = call_clause(input) {
+            clause.preceding_matches = match_clauses.len();
+            call_clauses.push(clause);
+            input = rest;
+        } else if let Ok((rest, clause)) = match_clause(input) {
+            match_clauses.push(clause);
+            input = rest;
+        } else {
+            return Ok((input, (match_clauses, unwind_clauses, call_clauses)));
+        }
+    }
+}
+
+// Parse a CALL clause: CALL <procedure>(<args>) [YIELD * | <column> [AS <variable>], ...
+// [WHERE <condition>]]
+fn call_clause(input: &str) -> IResult<&str, CallClause> {
+    let (input, _) = multispace0(input)?;
+    let (input, _) = tag_no_case("CALL")(input)?;
+    let (input, _) = multispace1(input)?;
+    let (input, procedure) = recognize(separated_list1(char('.'), identifier))(input)?;
+    let (input, _) = multispace0(input)?;
+    let (input, _) = char('(')(input)?;
+    let (input, _) = multispace0(input)?;
+    let (input, arguments) = separated_list0(comma_ws, value_expression)(input)?;
+    let (input, _) = multispace0(input)?;
+    let (input, _) = char(')')(input)?;
+    let (input, yields) = opt(preceded(
+        tuple((multispace0, tag_no_case("YIELD"), multispace1)),
+        alt((
+            map(char('*'), |_| None),
+            map(separated_list1(comma_ws, yield_item), Some),
+        )),
+    ))(input)?;
+    // WHERE filters the yielded rows, so it needs a YIELD
+    let (input, where_clause) = match yields {
+        Some(_) => opt(where_clause)(input)?,
+        None => (input, None),
+    };
+
+    Ok((
+        input,
+        CallClause {
+            procedure: procedure.to_string(),
+            arguments,
+            yield_items: yields.flatten(),
+            where_clause,
+            preceding_matches: 0,
+        },
+    ))
+}
+
+// Parse a yielded column: <column> [AS <variable>]
+fn yield_item(input: &str) -> IResult<&str, YieldItem> {
+    let (input, column) = identifier(input)?;
+    let (input, alias) = opt(preceded(
+        tuple((multispace1, tag_no_case("AS"), multispace1)),
+        identifier,
+    ))(input)?;
+    Ok((
+        input,
+        YieldItem {
+            column: column.to_string(),
+            alias: alias.map(str::to_string),
+        },
+    ))
+}
+
+// Parse an UNWIND clause: UNWIND <list> AS <variable>
+fn unwind_clause(input: &str) -> IResult<&str, (ValueExpression, &str)> {
+    let (input, _) = multispace0(input)?;
+    let (input, _) = tag_no_case("UNWIND")(input)?;
+    let (input, _) = multispace1(input)?;
+    let (input, expression) = value_expression(input)?;
+    let (input, _) = multispace1(input)?;
+    let (input, _) = tag_no_case("AS")(input)?;
+    let (input, _) = multispace1(input)?;
+    let (input, alias) = identifier(input)?;
+    Ok((input, (expression, alias)))
+}
+
+// Parse a MATCH or OPTIONAL MATCH clause
 fn match_clause(input: &str) -> IResult<&str, MatchClause> {
     let (input, _) = multispace0(input)?;
+    let (input, optional) = opt(tuple((tag_no_case("OPTIONAL"), multispace1)))(input)?;
     let (input, _) = tag_no_case("MATCH")(input)?;
     let (input, _) = multispace1(input)?;
     let (input, patterns) = separated_list0(comma_ws, graph_pattern)(input)?;
 
-    Ok((input, MatchClause { patterns }))
+    // A WHERE directly after OPTIONAL MATCH restricts the optional patterns only
+    let (input, where_clause) = if optional.is_some() {
+        opt(where_clause)(input)?
+    } else {
+        (input, None)
+    };
+
+    Ok((
+        input,
+        MatchClause {
+            patterns,
+            optional: optional.is_some(),
+            where_clause,
+        },
+    ))
+}
+
+// Parse a clause writing to the graph
+fn write_clause(input: &str) -> IResult<&str, WriteClause> {
+    alt((
+        map(create_clause, WriteClause::Create),
+        map(merge_clause, WriteClause::Merge),
+        map(set_clause, WriteClause::Set),
+        map(remove_clause, WriteClause::Remove),
+        map(delete_clause, WriteClause::Delete),
+    ))(input)
+}
+
+// Parse a CREATE clause: CREATE <pattern>, ...
+fn create_clause(input: &str) -> IResult<&str, CreateClause> {
+    let (input, _) = multispace0(input)?;
+    let (input, _) = tag_no_case("CREATE")(input)?;
+    let (input, _) = multispace1(input)?;
+    let (input, patterns) = separated_list1(comma_ws, graph_pattern)(input)?;
+    Ok((input, CreateClause { patterns }))
+}
+
+// Parse a MERGE clause: MERGE <pattern> [ON CREATE SET <items>] [ON MATCH SET <items>]
+fn merge_clause(input: &str) -> IResult<&str, MergeClause> {
+    let (input, _) = multispace0(input)?;
+    let (input, _) = tag_no_case("MERGE")(input)?;
+    let (input, _) = multispace1(input)?;
+    let (mut input, pattern) = graph_pattern(input)?;
+    let mut clause = MergeClause {
+        pattern,
+        on_create: Vec::new(),
+        on_match: Vec::new(),
+    };
+    loop {
+        let action = tuple((
+            multispace1,
+            tag_no_case("ON"),
+            multispace1,
+            alt((
+                map(tag_no_case("CREATE"), |_| true),
+                map(tag_no_case("MATCH"), |_| false),
+            )),
+            multispace1,
+            set_items,
+        ));
+        match opt(action)(input)? {
+            (rest, Some((_, _, _, on_create, _, items))) => {
+                if on_create {
+                    clause.on_create.extend(items);
+                } else {
+                    clause.on_match.extend(items);
+                }
+                input = rest;
+            }
+            (rest, None) => return Ok((rest, clause)),
+        }
+    }
+}
+
+// Parse a SET clause
+fn set_clause(input: &str) -> IResult<&str, SetClause> {
+    let (input, _) = multispace0(input)?;
+    let (input, items) = set_items(input)?;
+    Ok((input, SetClause { items }))
+}
+
+// Parse a REMOVE clause: REMOVE <property>, ...
+fn remove_clause(input: &str) -> IResult<&str, RemoveClause> {
+    let (input, _) = multispace0(input)?;
+    let (input, _) = tag_no_case("REMOVE")(input)?;
+    let (input, _) = multispace1(input)?;
+    let (input, properties) = separated_list1(comma_ws, property_reference)(input)?;
+    Ok((input, RemoveClause { properties }))
+}
+
+// Parse a DELETE clause: [DETACH] DELETE <variable>, ...
+fn delete_clause(input: &str) -> IResult<&str, DeleteClause> {
+    let (input, _) = multispace0(input)?;
+    let (input, detach) = opt(tuple((tag_no_case("DETACH"), multispace1)))(input)?;
+    let (input, _) = tag_no_case("DELETE")(input)?;
+    let (input, _) = multispace1(input)?;
+    let (input, variables) = separated_list1(comma_ws, identifier)(input)?;
+    Ok((
+        input,
+        DeleteClause {
+            detach: detach.is_some(),
+            variables: variables.into_iter().map(str::to_string).collect(),
+        },
+    ))
+}
+
+// Parse SET <property> = <value>, ...
+fn set_items(input: &str) -> IResult<&str, Vec<SetItem>> {
+    let (input, _) = tag_no_case("SET")(input)?;
+    let (input, _) = multispace1(input)?;
+    separated_list1(comma_ws, set_item)(input)
+}
+
+fn set_item(input: &str) -> IResult<&str, SetItem> {
+    let (input, property) = property_reference(input)?;
+    let (input, _) = tuple((multispace0, char('='), multispace0))(input)?;
+    let (input, value) = value_expression(input)?;
+    Ok((input, SetItem { property, value }))
 }
 
 // Parse a graph pattern (node or path)
 fn graph_pattern(input: &str) -> IResult<&str, GraphPattern> {
     alt((
+        map(shortest_path_pattern, GraphPattern::Path),
+        map(named_path_pattern, GraphPattern::Path),
         map(path_pattern, GraphPattern::Path),
         map(node_pattern, GraphPattern::Node),
     ))(input)
@@ -97,10 +398,37 @@ fn path_pattern(input: &str) -> IResult<&str, PathPattern> {
         PathPattern {
             start_node,
             segments,
+            variable: None,
+            shortest: None,
         },
     ))
 }
 
+// Parse `p = <path>`
+fn named_path_pattern(input: &str) -> IResult<&str, PathPattern> {
+    let (input, (variable, ..)) = tuple((identifier, multispace0, char('='), multispace0))(input)?;
+    let (input, mut path) = path_pattern(input)?;
+    path.variable = Some(variable.to_string());
+    Ok((input, path))
+}
+
+// Parse `[p =] shortestPath(<path>)` or `[p =] allShortestPaths(<path>)`
+fn shortest_path_pattern(input: &str) -> IResult<&str, PathPattern> {
+    let (input, _) = multispace0(input)?;
+    let (input, variable) = opt(tuple((identifier, multispace0, char('='), multispace0)))(input)?;
+    let (input, mode) = alt((
+        map(tag_no_case("allShortestPaths"), |_| ShortestPathMode::All),
+        map(tag_no_case("shortestPath"), |_| ShortestPathMode::Single),
+    ))(input)?;
+    let (input, _) = multispace0(input)?;
+    let (input, mut path) =
+        delimited(char('('), path_pattern, preceded(multispace0, char(')')))(input)?;
+
+    path.variable = variable.map(|(name, ..)| name.to_string());
+    path.shortest = Some(mode);
+    Ok((input, path))
+}
+
 // Parse a path segment (relationship + node)
 fn path_segment(input: &str) -> IResult<&str, PathSegment> {
     let (input, relationship) = relationship_pattern(input)?;
@@ -121,7 +449,8 @@ fn node_pattern(input: &str) -> IResult<&str, NodePattern> {
     let (input, _) = char('(')(input)?;
     let (input, _) = multispace0(input)?;
     let (input, variable) = opt(identifier)(input)?;
-    let (input, labels) = many0(preceded(char(':'), identifier))(input)?;
+    let (input, labels) = opt(node_labels)(input)?;
+    let (labels, label_expression) = labels.map(split_required_labels).unwrap_or_default();
     let (input, _) = multispace0(input)?;
     let (input, properties) = opt(property_map)(input)?;
     let (input, _) = multispace0(input)?;
@@ -131,12 +460,92 @@ fn node_pattern(input: &str) -> IResult<&str, NodePattern> {
         input,
         NodePattern {
             variable: variable.map(|s| s.to_string()),
-            labels: labels.into_iter().map(|s| s.to_string()).collect(),
+            labels,
             properties: properties.unwrap_or_default(),
+            label_expression,
         },
     ))
 }
 
+// Parse the labels of a node: `:A:B` or a label expression such as `:A&!(B|C)`
+fn node_labels(input: &str) -> IResult<&str, LabelExpression> {
+    let label = || preceded(pair(char(':'), multispace0), label_or_expression);
+    let (input, first) = label()(input)?;
+    let (input, rest) = many0(label())(input)?;
+    let expression = rest.into_iter().fold(first, |acc, item| {
+        LabelExpression::And(Box::new(acc), Box::new(item))
+    });
+    Ok((input, expression))
+}
+
+fn label_or_expression(input: &str) -> IResult<&str, LabelExpression> {
+    let (input, first) = label_and_expression(input)?;
+    let (input, rest) = many0(preceded(
+        tuple((multispace0, char('|'), multispace0)),
+        label_and_expression,
+    ))(input)?;
+    let expression = rest.into_iter().fold(first, |acc, item| {
+        LabelExpression::Or(Box::new(acc), Box::new(item))
+    });
+    Ok((input, expression))
+}
+
+fn label_and_expression(input: &str) -> IResult<&str, LabelExpression> {
+    let (input, first) = label_not_expression(input)?;
+    let (input, rest) = many0(preceded(
+        tuple((multispace0, char('&'), multispace0)),
+        label_not_expression,
+    ))(input)?;
+    let expression = rest.into_iter().fold(first, |acc, item| {
+        LabelExpression::And(Box::new(acc), Box::new(item))
+    });
+    Ok((input, expression))
+}
+
+fn label_not_expression(input: &str) -> IResult<&str, LabelExpression> {
+    alt((
+        map(
+            preceded(pair(char('!'), multispace0), label_not_expression),
+            |expression| LabelExpression::Not(Box::new(expression)),
+        ),
+        delimited(
+            pair(char('('), multispace0),
+            label_or_expression,
+            pair(multispace0, char(')')),
+        ),
+        map(identifier, |label| {
+            LabelExpression::Label(label.to_string())
+        }),
+    ))(input)
+}
+
+/// Split the labels every match must have off a node's label expression, returning
+/// them with the rest of the condition
+fn split_required_labels(expression: LabelExpression) -> (Vec<String>, Option<LabelExpression>) {
+    fn split(
+        expression: LabelExpression,
+        labels: &mut Vec<String>,
+        rest: &mut Vec<LabelExpression>,
+    ) {
+        match expression {
+            LabelExpression::Label(label) => labels.push(label),
+            LabelExpression::And(left, right) => {
+                split(*left, labels, rest);
+                split(*right, labels, rest);
+            }
+            other => rest.push(other),
+        }
+    }
+
+    let mut labels = Vec::new();
+    let mut rest = Vec::new();
+    split(expression, &mut labels, &mut rest);
+    let rest = rest
+        .into_iter()
+        .reduce(|left, right| LabelExpression::And(Box::new(left), Box::new(right)));
+    (labels, rest)
+}
+
 // Parse a relationship pattern: -[variable:TYPE {prop: value}]->
 fn relationship_pattern(input: &str) -> IResult<&str, RelationshipPattern> {
     let (input, _) = multispace0(input)?;
@@ -198,14 +607,25 @@ type RelationshipContentResult<'a> = (
 fn relationship_content(input: &str) -> IResult<&str, RelationshipContentResult<'_>> {
     let (input, _) = multispace0(input)?;
     let (input, variable) = opt(identifier)(input)?;
-    let (input, types) = many0(preceded(char(':'), identifier))(input)?;
+    let (input, types) = opt(preceded(char(':'), relationship_type_alternatives))(input)?;
     let (input, _) = multispace0(input)?;
     let (input, length) = opt(length_range)(input)?;
     let (input, _) = multispace0(input)?;
     let (input, properties) = opt(property_map)(input)?;
     let (input, _) = multispace0(input)?;
 
-    Ok((input, (variable, types, properties, length)))
+    Ok((
+        input,
+        (variable, types.unwrap_or_default(), properties, length),
+    ))
+}
+
+// Parse the types a relationship may have: TYPE1|TYPE2, also written TYPE1|:TYPE2
+fn relationship_type_alternatives(input: &str) -> IResult<&str, Vec<&str>> {
+    separated_list1(
+        tuple((multispace0, char('|'), opt(char(':')), multispace0)),
+        identifier,
+    )(input)
 }
 
 // Parse a property map: {key: value, key2: value2}
@@ -227,7 +647,12 @@ fn property_pair(input: &str) -> IResult<&str, (String, PropertyValue)> {
     let (input, _) = multispace0(input)?;
     let (input, _) = char(':')(input)?;
     let (input, _) = multispace0(input)?;
-    let (input, value) = property_value(input)?;
+    // Pattern properties may also compare against values bound earlier in the query
+    let (input, value) = alt((
+        property_value,
+        map(property_reference, PropertyValue::Property),
+        map(identifier, |id| PropertyValue::Variable(id.to_string())),
+    ))(input)?;
 
     Ok((input, (key.to_string(), value)))
 }
@@ -236,14 +661,24 @@ fn property_pair(input: &str) -> IResult<&str, (String, PropertyValue)> {
 fn property_value(input: &str) -> IResult<&str, PropertyValue> {
     alt((
         map(string_literal, PropertyValue::String),
-        map(integer_literal, PropertyValue::Integer),
         map(float_literal, PropertyValue::Float),
+        map(integer_literal, PropertyValue::Integer),
         map(boolean_literal, PropertyValue::Boolean),
         map(tag("null"), |_| PropertyValue::Null),
         map(parameter, PropertyValue::Parameter),
+        map(list_literal, PropertyValue::List),
     ))(input)
 }
 
+// Parse a list literal: [value, ...]
+fn list_literal(input: &str) -> IResult<&str, Vec<PropertyValue>> {
+    delimited(
+        tuple((char('['), multispace0)),
+        separated_list0(comma_ws, property_value),
+        tuple((multispace0, char(']'))),
+    )(input)
+}
+
 // Parse a WHERE clause
 fn where_clause(input: &str) -> IResult<&str, WhereClause> {
     let (input, _) = multispace0(input)?;
@@ -300,6 +735,7 @@ fn boolean_not_expression(input: &str) -> IResult<&str, BooleanExpression> {
 fn boolean_primary_expression(input: &str) -> IResult<&str, BooleanExpression> {
     let (input, _) = multispace0(input)?;
     alt((
+        pattern_predicate,
         map(
             delimited(
                 tuple((char('('), multispace0)),
@@ -308,18 +744,98 @@ fn boolean_primary_expression(input: &str) -> IResult<&str, BooleanExpression> {
             ),
             |expr| expr,
         ),
+        exists_subquery,
+        full_text_search,
+        label_predicate,
         comparison_expression,
     ))(input)
 }
 
+// Parse a full-text search predicate: fts(n.bio, 'query terms')
+fn full_text_search(input: &str) -> IResult<&str, BooleanExpression> {
+    let (input, _) = tuple((tag_no_case("fts"), multispace0, char('('), multispace0))(input)?;
+    let (input, expression) = value_expression(input)?;
+    let (input, _) = comma_ws(input)?;
+    let (input, query) = value_expression(input)?;
+    let (input, _) = tuple((multispace0, char(')')))(input)?;
+    Ok((
+        input,
+        BooleanExpression::FullTextSearch { expression, query },
+    ))
+}
+
+// Parse a label predicate: n:Person, n:Person&!Banned
+fn label_predicate(input: &str) -> IResult<&str, BooleanExpression> {
+    let (input, variable) = identifier(input)?;
+    let (input, labels) = node_labels(input)?;
+    Ok((
+        input,
+        BooleanExpression::HasLabels {
+            variable: variable.to_string(),
+            labels,
+        },
+    ))
+}
+
+// Parse a pattern existence subquery: EXISTS { [MATCH] pattern [WHERE predicate] }
+fn exists_subquery(input: &str) -> IResult<&str, BooleanExpression> {
+    let (input, _) = tag_no_case("EXISTS")(input)?;
+    let (input, clause) = subquery_body(input)?;
+    Ok((input, BooleanExpression::ExistsSubquery(Box::new(clause))))
+}
+
+// Parse a pattern predicate: (a)-[:KNOWS]->(b), a shorthand for EXISTS { (a)-[:KNOWS]->(b) }
+fn pattern_predicate(input: &str) -> IResult<&str, BooleanExpression> {
+    let (input, path) = path_pattern(input)?;
+    Ok((
+        input,
+        BooleanExpression::ExistsSubquery(Box::new(MatchClause {
+            patterns: vec![GraphPattern::Path(path)],
+            optional: false,
+            where_clause: None,
+        })),
+    ))
+}
+
+// Parse a pattern count subquery: COUNT { [MATCH] pattern [WHERE predicate] }
+fn count_subquery(input: &str) -> IResult<&str, ValueExpression> {
+    let (input, _) = tag_no_case("COUNT")(input)?;
+    let (input, clause) = subquery_body(input)?;
+    Ok((input, ValueExpression::CountSubquery(Box::new(clause))))
+}
+
+// Parse the braced pattern and optional WHERE of a subquery
+fn subquery_body(input: &str) -> IResult<&str, MatchClause> {
+    let (input, _) = tuple((multispace0, char('{'), multispace0))(input)?;
+    let (input, _) = opt(tuple((tag_no_case("MATCH"), multispace1)))(input)?;
+    let (input, patterns) = separated_list1(comma_ws, graph_pattern)(input)?;
+    let (input, where_clause) = opt(where_clause)(input)?;
+    let (input, _) = tuple((multispace0, char('}')))(input)?;
+    Ok((
+        input,
+        MatchClause {
+            patterns,
+            optional: false,
+            where_clause,
+        },
+    ))
+}
+
 fn comparison_expression(input: &str) -> IResult<&str, BooleanExpression> {
     let (input, _) = multispace0(input)?;
     let (input, left) = value_expression(input)?;
     let (input, _) = multispace0(input)?;
     let left_clone = left.clone();
 
-    if let Ok((input_after_in, (_, _, list))) =
-        tuple((tag_no_case("IN"), multispace0, value_expression_list))(input)
+    // `IN $list` binds the whole list; its items are spliced in when it is bound
+    let parameter_list = map(parameter, |name| {
+        vec![ValueExpression::Literal(PropertyValue::Parameter(name))]
+    });
+    if let Ok((input_after_in, (_, _, list))) = tuple((
+        tag_no_case("IN"),
+        multispace0,
+        alt((value_expression_list, parameter_list)),
+    ))(input)
     {
         return Ok((
             input_after_in,
@@ -329,6 +845,18 @@ fn comparison_expression(input: &str) -> IResult<&str, BooleanExpression> {
             },
         ));
     }
+    if let Ok((input_after_op, (operator, _, pattern))) =
+        tuple((string_operator, multispace0, value_expression))(input)
+    {
+        return Ok((
+            input_after_op,
+            BooleanExpression::StringMatch {
+                expression: left,
+                operator,
+                pattern,
+            },
+        ));
+    }
     // Match is null
     if let Ok((rest, ())) = is_null_comparison(input) {
         return Ok((rest, BooleanExpression::IsNull(left_clone)));
@@ -352,6 +880,22 @@ fn comparison_expression(input: &str) -> IResult<&str, BooleanExpression> {
     ))
 }
 
+// Parse a string matching operator
+fn string_operator(input: &str) -> IResult<&str, StringOperator> {
+    alt((
+        map(
+            tuple((tag_no_case("STARTS"), multispace1, tag_no_case("WITH"))),
+            |_| StringOperator::StartsWith,
+        ),
+        map(
+            tuple((tag_no_case("ENDS"), multispace1, tag_no_case("WITH"))),
+            |_| StringOperator::EndsWith,
+        ),
+        map(tag_no_case("CONTAINS"), |_| StringOperator::Contains),
+        map(tag("=~"), |_| StringOperator::Regex),
+    ))(input)
+}
+
 // Parse a comparison operator
 fn comparison_operator(input: &str) -> IResult<&str, ComparisonOperator> {
     alt((
@@ -365,19 +909,247 @@ fn comparison_operator(input: &str) -> IResult<&str, ComparisonOperator> {
     ))(input)
 }
 
-// Parse a value expression
+// Parse a value expression; `*`, `/` and `%` bind tighter than `+` and `-`
 fn value_expression(input: &str) -> IResult<&str, ValueExpression> {
-    alt((
+    let additive_operator = alt((
+        map(char('+'), |_| ArithmeticOperator::Add),
+        map(char('-'), |_| ArithmeticOperator::Subtract),
+    ));
+    arithmetic_chain(multiplicative_expression, additive_operator)(input)
+}
+
+fn multiplicative_expression(input: &str) -> IResult<&str, ValueExpression> {
+    let multiplicative_operator = alt((
+        map(char('*'), |_| ArithmeticOperator::Multiply),
+        map(char('/'), |_| ArithmeticOperator::Divide),
+        map(char('%'), |_| ArithmeticOperator::Modulo),
+    ));
+    arithmetic_chain(primary_value_expression, multiplicative_operator)(input)
+}
+
+// Parse operands separated by operators of the same precedence, associating to the left
+fn arithmetic_chain<'a>(
+    operand: impl FnMut(&'a str) -> IResult<&'a str, ValueExpression> + Copy,
+    operator: impl FnMut(&'a str) -> IResult<&'a str, ArithmeticOperator>,
+) -> impl FnMut(&'a str) -> IResult<&'a str, ValueExpression> {
+    let mut rest = many0(tuple((
+        preceded(multispace0, operator),
+        preceded(multispace0, operand),
+    )));
+    move |input| {
+        let (input, first) = { operand }(input)?;
+        let (input, rest) = rest(input)?;
+        let expression = rest.into_iter().fold(first, |left, (operator, right)| {
+            ValueExpression::Arithmetic {
+                left: Box::new(left),
+                operator,
+                right: Box::new(right),
+            }
+        });
+        Ok((input, expression))
+    }
+}
+
+// Parse a value expression without arithmetic, followed by any subscripts
+fn primary_value_expression(input: &str) -> IResult<&str, ValueExpression> {
+    let (input, expression) = alt((
+        case_expression,
+        count_subquery,
         function_call,
+        map_projection,
+        // Before property references, which would read `2.5` as property `5` of `2`
+        map(float_literal, |f| {
+            ValueExpression::Literal(PropertyValue::Float(f))
+        }),
         map(property_reference, ValueExpression::Property),
         map(property_value, ValueExpression::Literal),
+        list_comprehension,
+        list_expression,
+        map_expression,
+        delimited(
+            tuple((char('('), multispace0)),
+            value_expression,
+            tuple((multispace0, char(')'))),
+        ),
         map(identifier, |id| ValueExpression::Variable(id.to_string())),
-    ))(input)
+    ))(input)?;
+    let (input, subscripts) = many0(subscript)(input)?;
+    let expression = subscripts
+        .into_iter()
+        .fold(expression, |list, subscript| match subscript {
+            Subscript::Index(index) => ValueExpression::Index {
+                list: Box::new(list),
+                index: Box::new(index),
+            },
+            Subscript::Slice(from, to) => ValueExpression::Slice {
+                list: Box::new(list),
+                from: from.map(Box::new),
+                to: to.map(Box::new),
+            },
+        });
+    Ok((input, expression))
+}
+
+enum Subscript {
+    Index(ValueExpression),
+    Slice(Option<ValueExpression>, Option<ValueExpression>),
+}
+
+// Parse an index `[i]` or a slice `[from..to]` directly following a value
+fn subscript(input: &str) -> IResult<&str, Subscript> {
+    let slice = map(
+        tuple((
+            opt(value_expression),
+            multispace0,
+            tag(".."),
+            multispace0,
+            opt(value_expression),
+        )),
+        |(from, _, _, _, to)| Subscript::Slice(from, to),
+    );
+    delimited(
+        tuple((char('['), multispace0)),
+        alt((slice, map(value_expression, Subscript::Index))),
+        tuple((multispace0, char(']'))),
+    )(input)
+}
+
+// Parse a list of expressions: [p.name, p.age]
+fn list_expression(input: &str) -> IResult<&str, ValueExpression> {
+    map(
+        delimited(
+            tuple((char('['), multispace0)),
+            separated_list0(comma_ws, value_expression),
+            tuple((multispace0, char(']'))),
+        ),
+        ValueExpression::List,
+    )(input)
+}
+
+// Parse a map of expressions: {year: 2020, month: m.month}
+fn map_expression(input: &str) -> IResult<&str, ValueExpression> {
+    let entry = map(
+        tuple((
+            identifier,
+            multispace0,
+            char(':'),
+            multispace0,
+            value_expression,
+        )),
+        |(key, _, _, _, value)| (key.to_string(), value),
+    );
+    map(
+        delimited(
+            tuple((char('{'), multispace0)),
+            separated_list0(comma_ws, entry),
+            tuple((multispace0, char('}'))),
+        ),
+        ValueExpression::Map,
+    )(input)
+}
+
+// Parse a map projection, `p {.name, employer: c.name, since}`: `.name` reads the
+// property `name` of the variable and `since` is short for `since: since`
+fn map_projection(input: &str) -> IResult<&str, ValueExpression> {
+    let (input, variable) = identifier(input)?;
+    let (input, _) = tuple((multispace0, char('{'), multispace0))(input)?;
+    let property = map(preceded(char('.'), identifier), |property: &str| {
+        (
+            property.to_string(),
+            ValueExpression::Property(PropertyRef::new(variable, property)),
+        )
+    });
+    let entry = map(
+        tuple((
+            identifier,
+            multispace0,
+            char(':'),
+            multispace0,
+            value_expression,
+        )),
+        |(key, _, _, _, value)| (key.to_string(), value),
+    );
+    let shorthand = map(identifier, |name: &str| {
+        (
+            name.to_string(),
+            ValueExpression::Variable(name.to_string()),
+        )
+    });
+    let (input, entries) = separated_list0(comma_ws, alt((property, entry, shorthand)))(input)?;
+    let (input, _) = tuple((multispace0, char('}')))(input)?;
+    Ok((input, ValueExpression::Map(entries)))
+}
+
+// Parse a list comprehension: [x IN list WHERE predicate | projection]
+fn list_comprehension(input: &str) -> IResult<&str, ValueExpression> {
+    let (input, _) = tuple((char('['), multispace0))(input)?;
+    let (input, variable) = identifier(input)?;
+    let (input, _) = tuple((multispace1, tag_no_case("IN"), multispace1))(input)?;
+    let (input, list) = value_expression(input)?;
+    let (input, predicate) = opt(preceded(
+        tuple((multispace1, tag_no_case("WHERE"), multispace1)),
+        boolean_expression,
+    ))(input)?;
+    let (input, projection) = opt(preceded(
+        tuple((multispace0, char('|'), multispace0)),
+        value_expression,
+    ))(input)?;
+    let (input, _) = tuple((multispace0, char(']')))(input)?;
+    Ok((
+        input,
+        ValueExpression::ListComprehension {
+            variable: variable.to_string(),
+            list: Box::new(list),
+            predicate: predicate.map(Box::new),
+            projection: projection.map(Box::new),
+        },
+    ))
+}
+
+// Parse a searched CASE (CASE WHEN <predicate> THEN ...) or a simple CASE
+// (CASE <value> WHEN <value> THEN ...), each ending with an optional ELSE and END
+fn case_expression(input: &str) -> IResult<&str, ValueExpression> {
+    let (input, _) = tag_no_case("CASE")(input)?;
+    let (input, _) = multispace1(input)?;
+    let (input, operand) = opt(preceded(
+        nom::combinator::not(tuple((tag_no_case("WHEN"), multispace1))),
+        value_expression,
+    ))(input)?;
+
+    let case_branch = |input| {
+        let (input, _) = tuple((multispace0, tag_no_case("WHEN"), multispace1))(input)?;
+        let (input, condition) = match &operand {
+            Some(operand) => map(value_expression, |value| BooleanExpression::Comparison {
+                left: operand.clone(),
+                operator: ComparisonOperator::Equal,
+                right: value,
+            })(input)?,
+            None => boolean_expression(input)?,
+        };
+        let (input, _) = tuple((multispace0, tag_no_case("THEN"), multispace1))(input)?;
+        let (input, result) = value_expression(input)?;
+        Ok((input, (condition, result)))
+    };
+    let (input, branches) = nom::multi::many1(case_branch)(input)?;
+    let (input, else_result) = opt(preceded(
+        tuple((multispace0, tag_no_case("ELSE"), multispace1)),
+        value_expression,
+    ))(input)?;
+    let (input, _) = tuple((multispace0, tag_no_case("END")))(input)?;
+
+    Ok((
+        input,
+        ValueExpression::Case {
+            branches,
+            else_result: else_result.map(Box::new),
+        },
+    ))
 }
 
-// Parse a function call: function_name(args)
+// Parse a function call: function_name(args), where the name may be namespaced
+// (`duration.between`)
 fn function_call(input: &str) -> IResult<&str, ValueExpression> {
-    let (input, name) = identifier(input)?;
+    let (input, name) = recognize(separated_list1(char('.'), identifier))(input)?;
     let (input, _) = multispace0(input)?;
     let (input, _) = char('(')(input)?;
     let (input, _) = multispace0(input)?;
@@ -392,6 +1164,7 @@ fn function_call(input: &str) -> IResult<&str, ValueExpression> {
                 input,
                 ValueExpression::Function {
                     name: name.to_string(),
+                    distinct: false,
                     args: vec![ValueExpression::Variable("*".to_string())],
                 },
             ));
@@ -401,7 +1174,8 @@ fn function_call(input: &str) -> IResult<&str, ValueExpression> {
         }
     }
 
-    // Parse regular function arguments
+    // Parse regular function arguments, optionally preceded by DISTINCT
+    let (input, distinct) = opt(tuple((tag_no_case("DISTINCT"), multispace1)))(input)?;
     let (input, args) = separated_list0(
         tuple((multispace0, char(','), multispace0)),
         value_expression,
@@ -413,6 +1187,7 @@ fn function_call(input: &str) -> IResult<&str, ValueExpression> {
         input,
         ValueExpression::Function {
             name: name.to_string(),
+            distinct: distinct.is_some(),
             args,
         },
     ))
@@ -466,6 +1241,36 @@ fn return_clause(input: &str) -> IResult<&str, ReturnClause> {
     ))
 }
 
+// Parse a WITH clause together with the MATCH clauses of the query part it starts
+fn with_clause(input: &str) -> IResult<&str, WithClause> {
+    let (input, _) = multispace0(input)?;
+    let (input, _) = tag_no_case("WITH")(input)?;
+    let (input, _) = multispace1(input)?;
+    let (input, distinct) = opt(tuple((tag_no_case("DISTINCT"), multispace1)))(input)?;
+    let (input, items) = separated_list1(comma_ws, return_item)(input)?;
+    let (input, order_by) = opt(order_by_clause)(input)?;
+    let (input, (skip, limit)) = pagination_clauses(input)?;
+    let (input, with_where_clause) = opt(where_clause)(input)?;
+    let (input, (match_clauses, unwind_clauses, call_clauses)) = reading_clauses(input)?;
+    let (input, match_where_clause) = opt(where_clause)(input)?;
+
+    Ok((
+        input,
+        WithClause {
+            distinct: distinct.is_some(),
+            items,
+            order_by,
+            skip,
+            limit,
+            where_clause: with_where_clause,
+            match_clauses,
+            unwind_clauses,
+            call_clauses,
+            match_where_clause,
+        },
+    ))
+}
+
 // Parse a return item
 fn return_item(input: &str) -> IResult<&str, ReturnItem> {
     let (input, expression) = value_expression(input)?;
@@ -525,7 +1330,9 @@ fn order_by_item(input: &str) -> IResult<&str, OrderByItem> {
     let (input, expression) = value_expression(input)?;
     let (input, _) = multispace0(input)?;
     let (input, direction) = opt(alt((
+        map(tag_no_case("ASCENDING"), |_| SortDirection::Ascending),
         map(tag_no_case("ASC"), |_| SortDirection::Ascending),
+        map(tag_no_case("DESCENDING"), |_| SortDirection::Descending),
         map(tag_no_case("DESC"), |_| SortDirection::Descending),
     )))(input)?;
 
@@ -723,6 +1530,206 @@ mod tests {
         assert_eq!(result.return_clause.items.len(), 1);
     }
 
+    #[test]
+    fn test_parse_use_clause() {
+        let result = parse_cypher_query("USE social MATCH (n:Person) RETURN n.name").unwrap();
+        assert_eq!(result.graph.as_deref(), Some("social"));
+        assert_eq!(result.match_clauses.len(), 1);
+
+        let result = parse_cypher_query("MATCH (n:Person) RETURN n.name").unwrap();
+        assert!(result.graph.is_none());
+    }
+
+    #[test]
+    fn test_parse_named_path() {
+        let query = "MATCH p = (a:Person)-[:KNOWS*1..2]->(b:Person) RETURN nodes(p)";
+        let result = parse_cypher_query(query).unwrap();
+        let GraphPattern::Path(path) = &result.match_clauses[0].patterns[0] else {
+            panic!("Expected a path pattern");
+        };
+        assert_eq!(path.variable.as_deref(), Some("p"));
+        assert!(path.shortest.is_none());
+        assert_eq!(path.start_node.variable.as_deref(), Some("a"));
+        assert_eq!(path.segments.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_shortest_path() {
+        let query = "MATCH (a:Person {name: 'Alice'}), (b:Person {name: 'Eve'}), \
+                     p = shortestPath((a)-[:KNOWS*..5]->(b)) RETURN length(p)";
+        let result = parse_cypher_query(query).unwrap();
+        let GraphPattern::Path(path) = &result.match_clauses[0].patterns[2] else {
+            panic!("Expected a path pattern");
+        };
+        assert_eq!(path.variable.as_deref(), Some("p"));
+        assert_eq!(path.shortest, Some(ShortestPathMode::Single));
+        assert_eq!(path.segments[0].relationship.types, vec!["KNOWS"]);
+
+        let query = "MATCH allShortestPaths((a:Person)-[:KNOWS*]-(b:Person)) RETURN a.name";
+        let result = parse_cypher_query(query).unwrap();
+        let GraphPattern::Path(path) = &result.match_clauses[0].patterns[0] else {
+            panic!("Expected a path pattern");
+        };
+        assert!(path.variable.is_none());
+        assert_eq!(path.shortest, Some(ShortestPathMode::All));
+
+        // Plain paths are not shortest-path patterns
+        let result = parse_cypher_query("MATCH (a)-[:KNOWS]->(b) RETURN a").unwrap();
+        let GraphPattern::Path(path) = &result.match_clauses[0].patterns[0] else {
+            panic!("Expected a path pattern");
+        };
+        assert!(path.shortest.is_none());
+    }
+
+    #[test]
+    fn test_parse_optional_match() {
+        let query = "MATCH (a:Person) OPTIONAL MATCH (a)-[:KNOWS]->(b:Person) WHERE b.age > 30 \
+                     RETURN a.name, b.name";
+        let result = parse_cypher_query(query).unwrap();
+        assert_eq!(result.match_clauses.len(), 2);
+        assert!(!result.match_clauses[0].optional);
+        assert!(result.match_clauses[1].optional);
+        // The WHERE belongs to the OPTIONAL MATCH, not the whole query
+        assert!(result.match_clauses[1].where_clause.is_some());
+        assert!(result.where_clause.is_none());
+
+        let result = parse_cypher_query("MATCH (a:Person) WHERE a.age > 30 RETURN a.name").unwrap();
+        assert!(result.where_clause.is_some());
+        assert!(result.match_clauses[0].where_clause.is_none());
+    }
+
+    #[test]
+    fn test_parse_with_clause() {
+        let query = "MATCH (a:Person)-[:KNOWS]->(b:Person) \
+                     WITH DISTINCT a, count(b) AS friends ORDER BY friends DESC LIMIT 3 \
+                     WHERE friends > 1 \
+                     MATCH (a)-[:KNOWS]->(c:Person) WHERE c.age > 30 \
+                     WITH a.name AS name, c \
+                     RETURN name, c.name";
+        let result = parse_cypher_query(query).unwrap();
+        assert_eq!(result.with_clauses.len(), 2);
+
+        let first = &result.with_clauses[0];
+        assert!(first.distinct);
+        assert_eq!(first.items.len(), 2);
+        assert_eq!(first.items[1].alias.as_deref(), Some("friends"));
+        assert_eq!(first.order_by.as_ref().unwrap().items.len(), 1);
+        assert_eq!(first.limit, Some(3));
+        assert!(first.where_clause.is_some());
+        assert_eq!(first.match_clauses.len(), 1);
+        assert!(first.match_where_clause.is_some());
+
+        let second = &result.with_clauses[1];
+        assert!(!second.distinct);
+        assert!(second.match_clauses.is_empty());
+        assert_eq!(result.return_clause.items.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_unwind_clause() {
+        let query = "UNWIND $ids AS id MATCH (n:Person {id: id}) UNWIND [1, 'a'] AS x \
+                     RETURN n.name, x";
+        let result = parse_cypher_query(query).unwrap();
+        assert_eq!(result.match_clauses.len(), 1);
+        assert_eq!(result.unwind_clauses.len(), 2);
+
+        let first = &result.unwind_clauses[0];
+        assert_eq!(first.alias, "id");
+        assert_eq!(first.preceding_matches, 0);
+        assert_eq!(
+            first.expression,
+            ValueExpression::Literal(PropertyValue::Parameter("ids".to_string()))
+        );
+
+        let second = &result.unwind_clauses[1];
+        assert_eq!(second.preceding_matches, 1);
+        assert_eq!(
+            second.expression,
+            ValueExpression::Literal(PropertyValue::List(vec![
+                PropertyValue::Integer(1),
+                PropertyValue::String("a".to_string()),
+            ]))
+        );
+
+        let GraphPattern::Node(node) = &result.match_clauses[0].patterns[0] else {
+            panic!("Expected node pattern");
+        };
+        assert_eq!(
+            node.properties.get("id"),
+            Some(&PropertyValue::Variable("id".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_call_clause() {
+        let query = "MATCH (p:Person) CALL algo.rank(p.id, 10) YIELD node, score AS s \
+                     WHERE s > 1 RETURN node, s";
+        let result = parse_cypher_query(query).unwrap();
+        assert_eq!(result.call_clauses.len(), 1);
+        let call = &result.call_clauses[0];
+        assert_eq!(call.procedure, "algo.rank");
+        assert_eq!(call.preceding_matches, 1);
+        assert_eq!(call.arguments.len(), 2);
+        assert_eq!(
+            call.yield_items,
+            Some(vec![
+                YieldItem {
+                    column: "node".to_string(),
+                    alias: None,
+                },
+                YieldItem {
+                    column: "score".to_string(),
+                    alias: Some("s".to_string()),
+                },
+            ])
+        );
+        assert!(call.where_clause.is_some());
+        assert!(result.where_clause.is_none());
+
+        // A CALL standing alone may leave out YIELD and RETURN
+        for query in ["CALL db.labels()", "call db.labels() YIELD *"] {
+            let result = parse_cypher_query(query).unwrap();
+            assert_eq!(result.call_clauses[0].yield_items, None);
+            assert!(result.return_clause.items.is_empty());
+        }
+        assert!(parse_cypher_query("MATCH (p:Person) CALL db.labels() YIELD label").is_err());
+    }
+
+    #[test]
+    fn test_parse_union() {
+        let query = "MATCH (p:Person) RETURN p.name AS name LIMIT 2 \
+                     UNION MATCH (c:Company) RETURN c.name AS name \
+                     union MATCH (p:Person) RETURN p.city AS name";
+        let result = parse_cypher_query(query).unwrap();
+        assert_eq!(result.limit, Some(2));
+        assert_eq!(result.unions.len(), 2);
+        assert!(!result.unions[0].all);
+        assert!(!result.unions[1].all);
+
+        let second = &result.unions[0].query;
+        assert_eq!(second.get_node_labels(), vec!["Company"]);
+        assert_eq!(second.limit, None);
+        assert_eq!(
+            result.get_node_labels(),
+            vec!["Person".to_string(), "Company".to_string()]
+        );
+
+        let query = "MATCH (p:Person) RETURN p.name AS name \
+                     UNION ALL MATCH (c:Company) RETURN c.name AS name";
+        assert!(parse_cypher_query(query).unwrap().unions[0].all);
+
+        // UNION and UNION ALL cannot be mixed in one query
+        let query = "MATCH (p:Person) RETURN p.name AS name \
+                     UNION MATCH (c:Company) RETURN c.name AS name \
+                     union all MATCH (p:Person) RETURN p.city AS name";
+        let error = parse_cypher_query(query).unwrap_err();
+        assert!(
+            error.to_string().contains("UNION and UNION ALL"),
+            "{}",
+            error
+        );
+    }
+
     #[test]
     fn test_parse_node_with_properties() {
         let query = r#"MATCH (n:Person {name: "John", age: 30}) RETURN n"#;
@@ -752,6 +1759,60 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_relationship_type_alternatives() {
+        for query in [
+            "MATCH (a)-[r:KNOWS|LIKES]->(b) RETURN a",
+            "MATCH (a)-[r:KNOWS|:LIKES]->(b) RETURN a",
+            "MATCH (a)-[r:KNOWS | LIKES]->(b) RETURN a",
+        ] {
+            let result = parse_cypher_query(query).unwrap();
+            if let GraphPattern::Path(path) = &result.match_clauses[0].patterns[0] {
+                assert_eq!(path.segments[0].relationship.types, vec!["KNOWS", "LIKES"]);
+            } else {
+                panic!("Expected path pattern");
+            }
+        }
+
+        let result = parse_cypher_query("MATCH (a)-[:KNOWS|LIKES*1..2]->(b) RETURN a").unwrap();
+        if let GraphPattern::Path(path) = &result.match_clauses[0].patterns[0] {
+            let relationship = &path.segments[0].relationship;
+            assert_eq!(relationship.types, vec!["KNOWS", "LIKES"]);
+            assert_eq!(relationship.length.as_ref().unwrap().max, Some(2));
+        } else {
+            panic!("Expected path pattern");
+        }
+    }
+
+    #[test]
+    fn test_parse_label_expressions() {
+        let label = |name: &str| LabelExpression::Label(name.to_string());
+        let result = parse_cypher_query("MATCH (n:Person&!Banned:Adult) RETURN n").unwrap();
+        let GraphPattern::Node(node) = &result.match_clauses[0].patterns[0] else {
+            panic!("Expected node pattern");
+        };
+        assert_eq!(node.labels, vec!["Person", "Adult"]);
+        assert_eq!(
+            node.label_expression,
+            Some(LabelExpression::Not(Box::new(label("Banned"))))
+        );
+
+        let result = parse_cypher_query("MATCH (n) WHERE n:A|(B&C) RETURN n").unwrap();
+        assert_eq!(
+            result.where_clause.unwrap().expression,
+            BooleanExpression::HasLabels {
+                variable: "n".to_string(),
+                labels: LabelExpression::Or(
+                    Box::new(label("A")),
+                    Box::new(LabelExpression::And(
+                        Box::new(label("B")),
+                        Box::new(label("C"))
+                    )),
+                ),
+            }
+        );
+    }
+
     #[test]
     fn test_parse_variable_length_path() {
         let query = "MATCH (a:Person)-[:FRIEND_OF*1..2]-(b:Person) RETURN a.name, b.name";
@@ -827,6 +1888,293 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_in_parameter() {
+        let query = "MATCH (p:Person) WHERE p.name IN $names RETURN p.name";
+        let result = parse_cypher_query(query).unwrap();
+
+        match result
+            .where_clause
+            .expect("Expected WHERE clause")
+            .expression
+        {
+            BooleanExpression::In { list, .. } => {
+                assert_eq!(
+                    list,
+                    vec![ValueExpression::Literal(PropertyValue::Parameter(
+                        "names".to_string()
+                    ))]
+                );
+            }
+            other => panic!("Expected IN expression, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_arithmetic_precedence() {
+        let query = "MATCH (p:Person) RETURN p.age - 1 - 2 * (p.id + 3) AS x";
+        let result = parse_cypher_query(query).unwrap();
+        let arithmetic = |left, operator, right| ValueExpression::Arithmetic {
+            left: Box::new(left),
+            operator,
+            right: Box::new(right),
+        };
+        let age = ValueExpression::Property(PropertyRef::new("p", "age"));
+        let id = ValueExpression::Property(PropertyRef::new("p", "id"));
+        let int = |i| ValueExpression::Literal(PropertyValue::Integer(i));
+        assert_eq!(
+            result.return_clause.items[0].expression,
+            arithmetic(
+                arithmetic(age, ArithmeticOperator::Subtract, int(1)),
+                ArithmeticOperator::Subtract,
+                arithmetic(
+                    int(2),
+                    ArithmeticOperator::Multiply,
+                    arithmetic(id, ArithmeticOperator::Add, int(3))
+                )
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_float_literals() {
+        let query = "MATCH (p:Person {score: 2.5}) RETURN round(-2.5) AS r, 0.5 * p.age AS half";
+        let result = parse_cypher_query(query).unwrap();
+        let float = |f| ValueExpression::Literal(PropertyValue::Float(f));
+        let MatchClause { patterns, .. } = &result.match_clauses[0];
+        let GraphPattern::Node(node) = &patterns[0] else {
+            panic!("expected a node pattern");
+        };
+        assert_eq!(node.properties["score"], PropertyValue::Float(2.5));
+        assert_eq!(
+            result.return_clause.items[0].expression,
+            ValueExpression::Function {
+                name: "round".to_string(),
+                distinct: false,
+                args: vec![float(-2.5)],
+            }
+        );
+        assert_eq!(
+            result.return_clause.items[1].expression,
+            ValueExpression::Arithmetic {
+                left: Box::new(float(0.5)),
+                operator: ArithmeticOperator::Multiply,
+                right: Box::new(ValueExpression::Property(PropertyRef::new("p", "age"))),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_create_clause() {
+        let query = "MATCH (a:Person {name: 'Alice'}) \
+                     CREATE (b:Person {name: 'Zoe'}), (a)-[:KNOWS {since: 2024}]->(b)";
+        let result = parse_cypher_query(query).unwrap();
+        assert_eq!(result.match_clauses.len(), 1);
+        assert!(result.return_clause.items.is_empty());
+        let [WriteClause::Create(create)] = result.write_clauses.as_slice() else {
+            panic!("expected a single CREATE clause");
+        };
+        assert_eq!(create.patterns.len(), 2);
+        let GraphPattern::Path(path) = &create.patterns[1] else {
+            panic!("expected a path pattern");
+        };
+        assert_eq!(path.segments[0].relationship.types, vec!["KNOWS"]);
+
+        // Standalone CREATE, and CREATE followed by RETURN
+        assert!(parse_cypher_query("CREATE (n:Person {name: 'Zoe'})").is_ok());
+        let result = parse_cypher_query("CREATE (n:Person) RETURN n.name").unwrap();
+        assert_eq!(result.return_clause.items.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_merge_clause() {
+        let query = "UNWIND [1, 2] AS i MERGE (n:Person {id: i}) \
+                     ON CREATE SET n.visits = 1, n.name = 'new' \
+                     ON MATCH SET n.visits = n.visits + 1 \
+                     MERGE (m:Person {id: 0})";
+        let result = parse_cypher_query(query).unwrap();
+        let [WriteClause::Merge(merge), WriteClause::Merge(plain)] =
+            result.write_clauses.as_slice()
+        else {
+            panic!("expected two MERGE clauses");
+        };
+        let GraphPattern::Node(node) = &merge.pattern else {
+            panic!("expected a node pattern");
+        };
+        assert_eq!(node.labels, vec!["Person"]);
+        assert_eq!(merge.on_create.len(), 2);
+        assert_eq!(merge.on_create[1].property, PropertyRef::new("n", "name"));
+        assert_eq!(
+            merge.on_match,
+            vec![SetItem {
+                property: PropertyRef::new("n", "visits"),
+                value: ValueExpression::Arithmetic {
+                    left: Box::new(ValueExpression::Property(PropertyRef::new("n", "visits"))),
+                    operator: ArithmeticOperator::Add,
+                    right: Box::new(ValueExpression::Literal(PropertyValue::Integer(1))),
+                },
+            }]
+        );
+        assert!(plain.on_create.is_empty() && plain.on_match.is_empty());
+
+        assert!(parse_cypher_query("MERGE (n:Person {id: 1}) ON CREATE n.x = 1").is_err());
+    }
+
+    #[test]
+    fn test_parse_set_and_remove_clauses() {
+        let query = "MATCH (n:Person) SET n.age = n.age + 1, n.city = $city REMOVE n.nickname";
+        let result = parse_cypher_query(query).unwrap();
+        let [WriteClause::Set(set), WriteClause::Remove(remove)] = result.write_clauses.as_slice()
+        else {
+            panic!("expected SET followed by REMOVE");
+        };
+        assert_eq!(set.items.len(), 2);
+        assert_eq!(set.items[1].property, PropertyRef::new("n", "city"));
+        assert_eq!(
+            set.items[1].value,
+            ValueExpression::Literal(PropertyValue::Parameter("city".to_string()))
+        );
+        assert_eq!(remove.properties, vec![PropertyRef::new("n", "nickname")]);
+
+        // Whole maps and labels are not supported
+        assert!(parse_cypher_query("MATCH (n:Person) SET n = {age: 1}").is_err());
+        assert!(parse_cypher_query("MATCH (n:Person) REMOVE n:Person").is_err());
+    }
+
+    #[test]
+    fn test_parse_explain_and_profile() {
+        let result = parse_cypher_query("EXPLAIN MATCH (n:Person) RETURN n.name").unwrap();
+        assert_eq!(result.explain, Some(ExplainMode::Explain));
+        assert_eq!(result.match_clauses.len(), 1);
+
+        let result = parse_cypher_query("profile USE social MATCH (n) RETURN n").unwrap();
+        assert_eq!(result.explain, Some(ExplainMode::Profile));
+        assert_eq!(result.graph.as_deref(), Some("social"));
+
+        let result = parse_cypher_query("MATCH (n:Person) RETURN n.name").unwrap();
+        assert_eq!(result.explain, None);
+        assert!(parse_cypher_query("EXPLAIN").is_err());
+    }
+
+    #[test]
+    fn test_parse_delete_clauses() {
+        let query = "MATCH (a:Person)-[r:KNOWS]->(b:Person) DELETE r DETACH DELETE a, b";
+        let result = parse_cypher_query(query).unwrap();
+        assert_eq!(
+            result.write_clauses,
+            vec![
+                WriteClause::Delete(DeleteClause {
+                    detach: false,
+                    variables: vec!["r".to_string()],
+                }),
+                WriteClause::Delete(DeleteClause {
+                    detach: true,
+                    variables: vec!["a".to_string(), "b".to_string()],
+                }),
+            ]
+        );
+
+        assert!(parse_cypher_query("MATCH (n:Person) DETACH n").is_err());
+        assert!(parse_cypher_query("MATCH (n:Person) DELETE n.name").is_err());
+    }
+
+    #[test]
+    fn test_parse_map_and_namespaced_function() {
+        let query = "MATCH (p:Person) \
+                     RETURN duration.between(date({year: 2020, month: p.month}), date()) AS d";
+        let result = parse_cypher_query(query).unwrap();
+        let function = |name: &str, args| ValueExpression::Function {
+            name: name.to_string(),
+            distinct: false,
+            args,
+        };
+        assert_eq!(
+            result.return_clause.items[0].expression,
+            function(
+                "duration.between",
+                vec![
+                    function(
+                        "date",
+                        vec![ValueExpression::Map(vec![
+                            (
+                                "year".to_string(),
+                                ValueExpression::Literal(PropertyValue::Integer(2020))
+                            ),
+                            (
+                                "month".to_string(),
+                                ValueExpression::Property(PropertyRef::new("p", "month"))
+                            ),
+                        ])]
+                    ),
+                    function("date", vec![]),
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_map_projection() {
+        let query = "MATCH (p:Person)-[:WORKS_AT]->(c:Company) \
+                     RETURN p { .name, .age, employer: c.name, c } AS person";
+        let result = parse_cypher_query(query).unwrap();
+        let property =
+            |variable, property| ValueExpression::Property(PropertyRef::new(variable, property));
+        assert_eq!(
+            result.return_clause.items[0].expression,
+            ValueExpression::Map(vec![
+                ("name".to_string(), property("p", "name")),
+                ("age".to_string(), property("p", "age")),
+                ("employer".to_string(), property("c", "name")),
+                ("c".to_string(), ValueExpression::Variable("c".to_string())),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_string_operators() {
+        for (operator, expected) in [
+            ("STARTS WITH", StringOperator::StartsWith),
+            ("ends  with", StringOperator::EndsWith),
+            ("CONTAINS", StringOperator::Contains),
+            ("=~", StringOperator::Regex),
+        ] {
+            let query = format!(
+                "MATCH (p:Person) WHERE p.name {} 'li' RETURN p.name",
+                operator
+            );
+            let result = parse_cypher_query(&query).unwrap();
+            assert_eq!(
+                result
+                    .where_clause
+                    .expect("Expected WHERE clause")
+                    .expression,
+                BooleanExpression::StringMatch {
+                    expression: ValueExpression::Property(PropertyRef::new("p", "name")),
+                    operator: expected,
+                    pattern: ValueExpression::Literal(PropertyValue::String("li".to_string())),
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_full_text_search() {
+        let query = "MATCH (p:Person) WHERE NOT FTS(p.bio, 'graph databases') RETURN p.name";
+        let result = parse_cypher_query(query).unwrap();
+        assert_eq!(
+            result
+                .where_clause
+                .expect("Expected WHERE clause")
+                .expression,
+            BooleanExpression::Not(Box::new(BooleanExpression::FullTextSearch {
+                expression: ValueExpression::Property(PropertyRef::new("p", "bio")),
+                query: ValueExpression::Literal(PropertyValue::String(
+                    "graph databases".to_string()
+                )),
+            }))
+        );
+    }
+
     #[test]
     fn test_parse_query_with_in_clause() {
         let query = "MATCH (src:Entity)-[rel:RELATIONSHIP]->(dst:Entity) WHERE rel.relationship_type IN ['WORKS_FOR', 'PART_OF'] RETURN src.name";
@@ -943,6 +2291,247 @@ mod tests {
         assert!(result.order_by.is_some());
     }
 
+    #[test]
+    fn test_parse_order_by_long_direction_keywords() {
+        let query = "MATCH (n:Person) RETURN n.name ORDER BY n.age DESCENDING, n.name ASCENDING";
+        let result = parse_cypher_query(query).unwrap();
+
+        let items = &result.order_by.unwrap().items;
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].direction, SortDirection::Descending);
+        assert_eq!(items[1].direction, SortDirection::Ascending);
+    }
+
+    #[test]
+    fn test_parse_distinct_aggregate() {
+        let query = "MATCH (n:Person) RETURN count(DISTINCT n.city), count(distinctCity)";
+        let result = parse_cypher_query(query).unwrap();
+
+        let items = &result.return_clause.items;
+        assert!(matches!(
+            &items[0].expression,
+            ValueExpression::Function { distinct: true, args, .. } if args.len() == 1
+        ));
+        // An argument that merely starts with "distinct" is not the keyword
+        assert!(matches!(
+            &items[1].expression,
+            ValueExpression::Function { distinct: false, args, .. }
+                if args == &vec![ValueExpression::Variable("distinctCity".to_string())]
+        ));
+    }
+
+    #[test]
+    fn test_parse_case_expressions() {
+        let query = "MATCH (n:Person) RETURN \
+                     CASE WHEN n.age > 30 THEN 'old' ELSE 'young' END AS band, \
+                     CASE n.city WHEN 'Chicago' THEN 1 WHEN 'Seattle' THEN 2 END AS code";
+        let result = parse_cypher_query(query).unwrap();
+        let items = &result.return_clause.items;
+        assert_eq!(items[0].alias.as_deref(), Some("band"));
+        assert_eq!(items[1].alias.as_deref(), Some("code"));
+
+        let ValueExpression::Case {
+            branches,
+            else_result,
+        } = &items[0].expression
+        else {
+            panic!("Expected searched CASE");
+        };
+        assert_eq!(branches.len(), 1);
+        assert!(matches!(
+            branches[0].0,
+            BooleanExpression::Comparison {
+                operator: ComparisonOperator::GreaterThan,
+                ..
+            }
+        ));
+        assert_eq!(
+            else_result.as_deref(),
+            Some(&ValueExpression::Literal(PropertyValue::String(
+                "young".to_string()
+            )))
+        );
+
+        // A simple CASE compares its operand with each WHEN value
+        let ValueExpression::Case {
+            branches,
+            else_result,
+        } = &items[1].expression
+        else {
+            panic!("Expected simple CASE");
+        };
+        assert!(else_result.is_none());
+        assert_eq!(
+            branches[1].0,
+            BooleanExpression::Comparison {
+                left: ValueExpression::Property(PropertyRef::new("n", "city")),
+                operator: ComparisonOperator::Equal,
+                right: ValueExpression::Literal(PropertyValue::String("Seattle".to_string())),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_list_expressions() {
+        let query = "MATCH (n:Person) RETURN [n.name, 1] AS pair, n.scores[-1] AS last, \
+                     n.scores[1..3] AS middle, n.scores[..2] AS head, \
+                     [x IN n.scores WHERE x > 1 | n.name] AS names";
+        let result = parse_cypher_query(query).unwrap();
+        let items = &result.return_clause.items;
+        let scores = || Box::new(ValueExpression::Property(PropertyRef::new("n", "scores")));
+        let int = |i| Box::new(ValueExpression::Literal(PropertyValue::Integer(i)));
+
+        assert_eq!(
+            items[0].expression,
+            ValueExpression::List(vec![
+                ValueExpression::Property(PropertyRef::new("n", "name")),
+                ValueExpression::Literal(PropertyValue::Integer(1)),
+            ])
+        );
+        assert_eq!(
+            items[1].expression,
+            ValueExpression::Index {
+                list: scores(),
+                index: int(-1),
+            }
+        );
+        assert_eq!(
+            items[2].expression,
+            ValueExpression::Slice {
+                list: scores(),
+                from: Some(int(1)),
+                to: Some(int(3)),
+            }
+        );
+        assert_eq!(
+            items[3].expression,
+            ValueExpression::Slice {
+                list: scores(),
+                from: None,
+                to: Some(int(2)),
+            }
+        );
+        let ValueExpression::ListComprehension {
+            variable,
+            list,
+            predicate,
+            projection,
+        } = &items[4].expression
+        else {
+            panic!("Expected list comprehension");
+        };
+        assert_eq!(variable, "x");
+        assert_eq!(list, &scores());
+        assert!(matches!(
+            predicate.as_deref(),
+            Some(BooleanExpression::Comparison {
+                operator: ComparisonOperator::GreaterThan,
+                ..
+            })
+        ));
+        assert_eq!(
+            projection.as_deref(),
+            Some(&ValueExpression::Property(PropertyRef::new("n", "name")))
+        );
+
+        // Lists of literals stay literal values
+        let result = parse_cypher_query("MATCH (n:Person) RETURN [1, 2][0] AS first").unwrap();
+        assert_eq!(
+            result.return_clause.items[0].expression,
+            ValueExpression::Index {
+                list: Box::new(ValueExpression::Literal(PropertyValue::List(vec![
+                    PropertyValue::Integer(1),
+                    PropertyValue::Integer(2),
+                ]))),
+                index: int(0),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_exists_subquery() {
+        let query =
+            "MATCH (n:Person) WHERE EXISTS { MATCH (n)-[:OWNS]->(c:Car) WHERE c.year > 2000 } \
+                     AND NOT exists { (n)-[:KNOWS]->(:Person) } RETURN n.name";
+        let result = parse_cypher_query(query).unwrap();
+        let where_clause = result.where_clause.unwrap();
+        let BooleanExpression::And(left, right) = &where_clause.expression else {
+            panic!("Expected AND of two subqueries");
+        };
+        let BooleanExpression::ExistsSubquery(owns) = left.as_ref() else {
+            panic!("Expected EXISTS subquery");
+        };
+        assert_eq!(owns.patterns.len(), 1);
+        assert!(owns.where_clause.is_some());
+
+        let BooleanExpression::Not(inner) = right.as_ref() else {
+            panic!("Expected NOT EXISTS");
+        };
+        let BooleanExpression::ExistsSubquery(knows) = inner.as_ref() else {
+            panic!("Expected EXISTS subquery without MATCH");
+        };
+        assert!(knows.where_clause.is_none());
+        assert!(parse_cypher_query("MATCH (n) WHERE EXISTS { } RETURN n").is_err());
+    }
+
+    #[test]
+    fn test_parse_pattern_predicate() {
+        let query = "MATCH (a:Person), (b:Person) \
+                     WHERE (a)-[:KNOWS]->(:Person)-[:KNOWS]->(b) AND NOT (b)-[:KNOWS]->(a) \
+                     AND a.age > 20 RETURN a.name";
+        let result = parse_cypher_query(query).unwrap();
+        let where_clause = result.where_clause.unwrap();
+        let BooleanExpression::And(left, age) = &where_clause.expression else {
+            panic!("Expected AND of three conditions");
+        };
+        assert!(matches!(age.as_ref(), BooleanExpression::Comparison { .. }));
+        let BooleanExpression::And(path, negated) = left.as_ref() else {
+            panic!("Expected AND of two pattern predicates");
+        };
+        let BooleanExpression::ExistsSubquery(path) = path.as_ref() else {
+            panic!("Expected pattern predicate");
+        };
+        let GraphPattern::Path(path) = &path.patterns[0] else {
+            panic!("Expected path pattern");
+        };
+        assert_eq!(path.segments.len(), 2);
+        let BooleanExpression::Not(inner) = negated.as_ref() else {
+            panic!("Expected NOT pattern predicate");
+        };
+        assert!(matches!(
+            inner.as_ref(),
+            BooleanExpression::ExistsSubquery(_)
+        ));
+    }
+
+    #[test]
+    fn test_parse_count_subquery() {
+        let query = "MATCH (n:Person) WHERE count { (n)-[:FOLLOWS]->(:Person) } > 1 \
+                     RETURN n.name, COUNT { MATCH (n)<-[:FOLLOWS]-(f:Person) WHERE f.age > 30 } AS followers, \
+                     count(n) AS total";
+        let result = parse_cypher_query(query).unwrap();
+        let items = &result.return_clause.items;
+        let ValueExpression::CountSubquery(followers) = &items[1].expression else {
+            panic!("Expected COUNT subquery");
+        };
+        assert_eq!(followers.patterns.len(), 1);
+        assert!(followers.where_clause.is_some());
+        // count(...) is still the aggregate function
+        assert!(matches!(
+            items[2].expression,
+            ValueExpression::Function { .. }
+        ));
+
+        let Some(BooleanExpression::Comparison { left, .. }) =
+            result.where_clause.as_ref().map(|w| &w.expression)
+        else {
+            panic!("Expected comparison");
+        };
+        assert!(
+            matches!(left, ValueExpression::CountSubquery(clause) if clause.where_clause.is_none())
+        );
+    }
+
     #[test]
     fn test_parse_count_star() {
         let query = "MATCH (n:Person) RETURN count(*) AS total";
@@ -953,7 +2542,7 @@ mod tests {
         assert_eq!(item.alias, Some("total".to_string()));
 
         match &item.expression {
-            ValueExpression::Function { name, args } => {
+            ValueExpression::Function { name, args, .. } => {
                 assert_eq!(name, "count");
                 assert_eq!(args.len(), 1);
                 match &args[0] {
@@ -974,7 +2563,7 @@ mod tests {
         let item = &result.return_clause.items[0];
 
         match &item.expression {
-            ValueExpression::Function { name, args } => {
+            ValueExpression::Function { name, args, .. } => {
                 assert_eq!(name, "count");
                 assert_eq!(args.len(), 1);
                 match &args[0] {
@@ -1011,7 +2600,7 @@ mod tests {
         // Verify the AST structure
         let ast = result.unwrap();
         match &ast.return_clause.items[0].expression {
-            ValueExpression::Function { name, args } => {
+            ValueExpression::Function { name, args, .. } => {
                 assert_eq!(name, "count");
                 assert_eq!(args.len(), 2);
             }
@@ -0,0 +1,493 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Diagrams of graph logical plans.
+//!
+//! [`render`] draws a [`LogicalOperator`] tree as a Graphviz DOT or Mermaid flowchart,
+//! one box per operator labelled with the pattern it matches (`(a:Person)`,
+//! `(a)-[r:KNOWS]->(b:Person)`, `(b)-[:KNOWS*1..3]->(c)`) and an arrow from each input
+//! to the operator consuming it. The DataFusion plan of a five-hop pattern spreads
+//! over dozens of joins and projections; the diagram shows which operator binds each
+//! variable. [`CypherQuery::plan_diagram`] plans a query and draws it in one step.
+
+use crate::ast::{PropertyValue, RelationshipDirection, ShortestPathMode, ValueExpression};
+use crate::datafusion_planner::expression::{to_cypher_column_name, to_df_boolean_expr};
+use crate::error::Result;
+use crate::logical_plan::{JoinType, LogicalOperator, PathStep, ProjectionItem};
+use crate::query::CypherQuery;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// Output syntax of a plan diagram
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagramFormat {
+    /// Graphviz `digraph`, rendered with `dot -Tsvg`
+    Dot,
+    /// Mermaid `flowchart`, rendered by Markdown viewers supporting Mermaid
+    Mermaid,
+}
+
+/// Draw `plan` in `format`, inputs above the operators consuming them
+pub fn render(plan: &LogicalOperator, format: DiagramFormat) -> String {
+    let mut nodes = Vec::new();
+    let mut edges = Vec::new();
+    collect(plan, &mut nodes, &mut edges);
+
+    let mut out = String::new();
+    match format {
+        DiagramFormat::Dot => {
+            out.push_str("digraph plan {\n    rankdir=BT;\n    node [shape=box];\n");
+            for (id, label) in nodes.iter().enumerate() {
+                let _ = writeln!(out, "    n{} [label=\"{}\"];", id, escape_dot(label));
+            }
+            for (from, to, label) in &edges {
+                match label {
+                    Some(label) => {
+                        let _ = writeln!(out, "    n{} -> n{} [label=\"{}\"];", from, to, label);
+                    }
+                    None => {
+                        let _ = writeln!(out, "    n{} -> n{};", from, to);
+                    }
+                }
+            }
+            out.push_str("}\n");
+        }
+        DiagramFormat::Mermaid => {
+            out.push_str("flowchart BT\n");
+            for (id, label) in nodes.iter().enumerate() {
+                let _ = writeln!(out, "    n{}[\"{}\"]", id, escape_mermaid(label));
+            }
+            for (from, to, label) in &edges {
+                match label {
+                    Some(label) => {
+                        let _ = writeln!(out, "    n{} -->|{}| n{}", from, label, to);
+                    }
+                    None => {
+                        let _ = writeln!(out, "    n{} --> n{}", from, to);
+                    }
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Number `op` and its inputs, returning the number of `op`
+///
+/// Edges run from input to consumer; the inputs of a join and the pattern of a
+/// subquery are labelled with their role.
+fn collect(
+    op: &LogicalOperator,
+    nodes: &mut Vec<String>,
+    edges: &mut Vec<(usize, usize, Option<&'static str>)>,
+) -> usize {
+    let id = nodes.len();
+    nodes.push(label(op));
+    let inputs: Vec<(&LogicalOperator, Option<&'static str>)> = match op {
+        LogicalOperator::ScanByLabel { .. } => vec![],
+        LogicalOperator::Unwind { input, .. } | LogicalOperator::ProcedureCall { input, .. } => {
+            input.iter().map(|input| (input.as_ref(), None)).collect()
+        }
+        LogicalOperator::Union { left, right, .. } | LogicalOperator::Join { left, right, .. } => {
+            vec![
+                (left.as_ref(), Some("left")),
+                (right.as_ref(), Some("right")),
+            ]
+        }
+        LogicalOperator::SemiJoin { input, pattern, .. }
+        | LogicalOperator::PatternCount { input, pattern, .. } => {
+            vec![(input.as_ref(), None), (pattern.as_ref(), Some("pattern"))]
+        }
+        LogicalOperator::Filter { input, .. }
+        | LogicalOperator::Expand { input, .. }
+        | LogicalOperator::VariableLengthExpand { input, .. }
+        | LogicalOperator::ShortestPath { input, .. }
+        | LogicalOperator::BindPath { input, .. }
+        | LogicalOperator::DistinctRelationships { input }
+        | LogicalOperator::Project { input, .. }
+        | LogicalOperator::With { input, .. }
+        | LogicalOperator::Distinct { input }
+        | LogicalOperator::Sort { input, .. }
+        | LogicalOperator::Offset { input, .. }
+        | LogicalOperator::Limit { input, .. } => vec![(input.as_ref(), None)],
+    };
+    for (input, role) in inputs {
+        let input_id = collect(input, nodes, edges);
+        edges.push((input_id, id, role));
+    }
+    id
+}
+
+/// Operator name over the pattern or clause it plans
+fn label(op: &LogicalOperator) -> String {
+    match op {
+        LogicalOperator::ScanByLabel {
+            variable,
+            label,
+            properties,
+        } => format!(
+            "ScanByLabel\n({}:{}{})",
+            variable,
+            label,
+            property_keys(properties)
+        ),
+        LogicalOperator::Filter { predicate, .. } => {
+            format!("Filter\n{}", to_df_boolean_expr(predicate))
+        }
+        LogicalOperator::Expand {
+            source_variable,
+            target_variable,
+            target_label,
+            relationship_types,
+            direction,
+            relationship_variable,
+            properties,
+            target_properties,
+            ..
+        } => {
+            let relationship = format!(
+                "{}{}{}",
+                relationship_variable.as_deref().unwrap_or(""),
+                types(relationship_types),
+                property_keys(properties)
+            );
+            format!(
+                "Expand\n{}({}:{}{})",
+                arrow(source_variable, direction, &relationship),
+                target_variable,
+                target_label,
+                property_keys(target_properties)
+            )
+        }
+        LogicalOperator::VariableLengthExpand {
+            source_variable,
+            target_variable,
+            relationship_types,
+            direction,
+            relationship_variable,
+            properties,
+            min_length,
+            max_length,
+            target_properties,
+            path_variable,
+            ..
+        } => {
+            let relationship = format!(
+                "{}{}{}{}",
+                relationship_variable.as_deref().unwrap_or(""),
+                types(relationship_types),
+                hops(min_length, max_length),
+                property_keys(properties)
+            );
+            let path = path_variable
+                .as_ref()
+                .map(|path| format!("\nhops into {}", path))
+                .unwrap_or_default();
+            format!(
+                "VariableLengthExpand\n{}({}{}){}",
+                arrow(source_variable, direction, &relationship),
+                target_variable,
+                property_keys(target_properties),
+                path
+            )
+        }
+        LogicalOperator::ShortestPath {
+            source_variable,
+            target_variable,
+            target_label,
+            relationship_types,
+            direction,
+            path_variable,
+            min_length,
+            max_length,
+            target_properties,
+            mode,
+            ..
+        } => {
+            let function = match mode {
+                ShortestPathMode::Single => "shortestPath",
+                ShortestPathMode::All => "allShortestPaths",
+            };
+            let relationship = format!(
+                "{}{}",
+                types(relationship_types),
+                hops(min_length, max_length)
+            );
+            let path = path_variable
+                .as_ref()
+                .map(|path| format!("{} = ", path))
+                .unwrap_or_default();
+            format!(
+                "ShortestPath\n{}{}({}({}:{}{}))",
+                path,
+                function,
+                arrow(source_variable, direction, &relationship),
+                target_variable,
+                target_label,
+                property_keys(target_properties)
+            )
+        }
+        LogicalOperator::BindPath {
+            variable,
+            start_variable,
+            steps,
+            ..
+        } => {
+            let mut path = format!("({})", start_variable);
+            for step in steps {
+                match step {
+                    PathStep::Hop {
+                        relationship_variable,
+                        target_variable,
+                    } => {
+                        let _ = write!(path, "-[{}]-({})", relationship_variable, target_variable);
+                    }
+                    PathStep::Segment { column } => {
+                        let _ = write!(path, "-[{}]-", column);
+                    }
+                }
+            }
+            format!("BindPath\n{} = {}", variable, path)
+        }
+        LogicalOperator::DistinctRelationships { .. } => "DistinctRelationships".to_string(),
+        LogicalOperator::Project { projections, .. } => {
+            format!("Project\n{}", items(projections))
+        }
+        LogicalOperator::With { items: with, .. } => format!("With\n{}", items(with)),
+        LogicalOperator::Unwind {
+            expression, alias, ..
+        } => format!("Unwind\n{} AS {}", expression_name(expression), alias),
+        LogicalOperator::ProcedureCall {
+            procedure, yields, ..
+        } => match yields {
+            Some(yields) => format!(
+                "ProcedureCall\n{} YIELD {}",
+                procedure,
+                yields
+                    .iter()
+                    .map(|(column, variable)| {
+                        if column == variable {
+                            column.clone()
+                        } else {
+                            format!("{} AS {}", column, variable)
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            None => format!("ProcedureCall\n{}", procedure),
+        },
+        LogicalOperator::Union { all, .. } => {
+            if *all {
+                "Union\nALL".to_string()
+            } else {
+                "Union".to_string()
+            }
+        }
+        LogicalOperator::Join { join_type, .. } => {
+            let join_type = match join_type {
+                JoinType::Inner => "inner",
+                JoinType::Left => "left",
+                JoinType::Right => "right",
+                JoinType::Full => "full",
+                JoinType::Cross => "cross",
+            };
+            format!("Join\n{}", join_type)
+        }
+        LogicalOperator::SemiJoin {
+            predicate, negated, ..
+        } => {
+            let name = if *negated { "AntiJoin" } else { "SemiJoin" };
+            match predicate {
+                Some(predicate) => format!("{}\n{}", name, to_df_boolean_expr(predicate)),
+                None => name.to_string(),
+            }
+        }
+        LogicalOperator::PatternCount { alias, .. } => format!("PatternCount\ninto {}", alias),
+        LogicalOperator::Distinct { .. } => "Distinct".to_string(),
+        LogicalOperator::Sort { sort_items, .. } => format!(
+            "Sort\n{}",
+            sort_items
+                .iter()
+                .map(|item| expression_name(&item.expression))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        LogicalOperator::Offset { offset, .. } => format!("Offset\n{}", offset),
+        LogicalOperator::Limit { count, .. } => format!("Limit\n{}", count),
+    }
+}
+
+/// `(a)-[rel]->`, `(a)<-[rel]-` or `(a)-[rel]-`, ready for the target node
+fn arrow(source: &str, direction: &RelationshipDirection, relationship: &str) -> String {
+    match direction {
+        RelationshipDirection::Outgoing => format!("({})-[{}]->", source, relationship),
+        RelationshipDirection::Incoming => format!("({})<-[{}]-", source, relationship),
+        RelationshipDirection::Undirected => format!("({})-[{}]-", source, relationship),
+    }
+}
+
+fn types(relationship_types: &[String]) -> String {
+    if relationship_types.is_empty() {
+        String::new()
+    } else {
+        format!(":{}", relationship_types.join("|"))
+    }
+}
+
+fn hops(min_length: &Option<u32>, max_length: &Option<u32>) -> String {
+    match (min_length, max_length) {
+        (None, None) => "*".to_string(),
+        (Some(min), Some(max)) if min == max => format!("*{}", min),
+        (min, max) => format!(
+            "*{}..{}",
+            min.map(|min| min.to_string()).unwrap_or_default(),
+            max.map(|max| max.to_string()).unwrap_or_default()
+        ),
+    }
+}
+
+/// ` {age, name}` for inline property filters, sorted so diagrams are stable
+fn property_keys(properties: &HashMap<String, PropertyValue>) -> String {
+    if properties.is_empty() {
+        return String::new();
+    }
+    let mut keys: Vec<&str> = properties.keys().map(String::as_str).collect();
+    keys.sort_unstable();
+    format!(" {{{}}}", keys.join(", "))
+}
+
+fn items(items: &[ProjectionItem]) -> String {
+    items
+        .iter()
+        .map(|item| {
+            let name = expression_name(&item.expression);
+            match &item.alias {
+                Some(alias) if *alias != name => format!("{} AS {}", name, alias),
+                _ => name,
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn expression_name(expression: &ValueExpression) -> String {
+    to_cypher_column_name(expression)
+}
+
+fn escape_dot(label: &str) -> String {
+    label
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Mermaid labels take HTML entities for quotes and `<br/>` for line breaks
+fn escape_mermaid(label: &str) -> String {
+    label
+        .replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('\n', "<br/>")
+}
+
+impl CypherQuery {
+    /// Plan the query against `catalog` and draw its graph logical plan
+    ///
+    /// See [`render`] for the diagram drawn.
+    ///
+    /// # Example
+    /// ```ignore
+    /// use lance_graph::plan_diagram::DiagramFormat;
+    ///
+    /// let dot = query.plan_diagram(Arc::new(catalog), DiagramFormat::Dot)?;
+    /// std::fs::write("plan.dot", dot)?;
+    /// ```
+    pub fn plan_diagram(
+        &self,
+        catalog: std::sync::Arc<dyn crate::source_catalog::GraphSourceCatalog>,
+        format: DiagramFormat,
+    ) -> Result<String> {
+        let (plan, _) = self.plan_ast(self.ast(), self.scoped_catalog(catalog)?)?;
+        Ok(render(&plan, format))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn two_hop_plan() -> LogicalOperator {
+        let expand =
+            |input, source: &str, target: &str, rel: Option<&str>| LogicalOperator::Expand {
+                input: Box::new(input),
+                source_variable: source.to_string(),
+                target_variable: target.to_string(),
+                target_label: "Person".to_string(),
+                relationship_types: vec!["KNOWS".to_string()],
+                direction: RelationshipDirection::Outgoing,
+                relationship_variable: rel.map(str::to_string),
+                properties: HashMap::new(),
+                target_properties: HashMap::new(),
+            };
+        let scan = LogicalOperator::ScanByLabel {
+            variable: "a".to_string(),
+            label: "Person".to_string(),
+            properties: HashMap::from([(
+                "name".to_string(),
+                PropertyValue::String("Alice".to_string()),
+            )]),
+        };
+        LogicalOperator::Limit {
+            input: Box::new(expand(expand(scan, "a", "b", Some("r")), "b", "c", None)),
+            count: 10,
+        }
+    }
+
+    #[test]
+    fn test_dot_labels_operators_with_their_patterns() {
+        let dot = render(&two_hop_plan(), DiagramFormat::Dot);
+        assert!(dot.starts_with("digraph plan {"));
+        assert!(dot.contains("n3 [label=\"ScanByLabel\\n(a:Person {name})\"];"));
+        assert!(dot.contains("n2 [label=\"Expand\\n(a)-[r:KNOWS]->(b:Person)\"];"));
+        assert!(dot.contains("n1 [label=\"Expand\\n(b)-[:KNOWS]->(c:Person)\"];"));
+        assert!(dot.contains("n3 -> n2;"));
+        assert!(dot.contains("n1 -> n0;"));
+    }
+
+    #[test]
+    fn test_mermaid_escapes_labels() {
+        let mermaid = render(&two_hop_plan(), DiagramFormat::Mermaid);
+        assert!(mermaid.starts_with("flowchart BT\n"));
+        assert!(mermaid.contains("n2[\"Expand<br/>(a)-[r:KNOWS]-&gt;(b:Person)\"]"));
+        assert!(mermaid.contains("n0[\"Limit<br/>10\"]"));
+        assert!(mermaid.contains("n3 --> n2"));
+    }
+
+    #[test]
+    fn test_join_inputs_are_labelled() {
+        let scan = |variable: &str| LogicalOperator::ScanByLabel {
+            variable: variable.to_string(),
+            label: "Person".to_string(),
+            properties: HashMap::new(),
+        };
+        let join = LogicalOperator::Join {
+            left: Box::new(scan("a")),
+            right: Box::new(scan("b")),
+            join_type: JoinType::Cross,
+        };
+        let dot = render(&join, DiagramFormat::Dot);
+        assert!(dot.contains("n0 [label=\"Join\\ncross\"];"));
+        assert!(dot.contains("n1 -> n0 [label=\"left\"];"));
+        assert!(dot.contains("n2 -> n0 [label=\"right\"];"));
+    }
+
+    #[test]
+    fn test_variable_length_hops() {
+        assert_eq!(hops(&Some(1), &Some(3)), "*1..3");
+        assert_eq!(hops(&Some(2), &Some(2)), "*2");
+        assert_eq!(hops(&None, &Some(5)), "*..5");
+        assert_eq!(hops(&None, &None), "*");
+    }
+}
@@ -0,0 +1,375 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Prepared queries and a plan cache.
+//!
+//! [`PreparedQuery`] parses and plans a query once, leaving its `$parameters` as
+//! DataFusion placeholders, and executes that plan with new parameter values each time.
+//! Queries whose plan depends on the values themselves, such as those given a list
+//! parameter, are planned again for the values of each execution, so every query that
+//! runs with [`CypherQuery::execute_with_catalog`] can be prepared.
+//!
+//! [`PlanCache`] keeps the prepared queries of one graph keyed by their text, with
+//! whitespace outside string literals normalized, and drops the least recently used one
+//! beyond its capacity.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use arrow_array::RecordBatch;
+use datafusion::common::ParamValues;
+use datafusion::execution::context::SessionContext;
+use datafusion::logical_expr::LogicalPlan;
+use datafusion::physical_plan::SendableRecordBatchStream;
+use datafusion::scalar::ScalarValue;
+
+use crate::ast::PropertyValue;
+use crate::config::GraphConfig;
+use crate::error::{GraphError, Result};
+use crate::execution;
+use crate::parameters::{bind_parameters, resolve_parameters, validate_parameter_types};
+use crate::query::CypherQuery;
+use crate::source_catalog::GraphSourceCatalog;
+
+/// A query planned once against a catalog, to execute with different parameter values
+pub struct PreparedQuery {
+    query: CypherQuery,
+    /// The catalog scoped to the query's graph
+    catalog: Arc<dyn GraphSourceCatalog>,
+    /// The DataFusion plan with a placeholder for each parameter, unless the plan
+    /// depends on their values
+    plan: Option<LogicalPlan>,
+}
+
+impl std::fmt::Debug for PreparedQuery {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PreparedQuery")
+            .field("query", &self.query.query_text())
+            .field("planned", &self.plan.is_some())
+            .finish()
+    }
+}
+
+impl PreparedQuery {
+    /// Plan `query`, which needs a graph configuration, against `catalog`
+    ///
+    /// Parameters bound on `query` are defaults that the values given to each execution
+    /// override.
+    pub fn try_new(query: CypherQuery, catalog: Arc<dyn GraphSourceCatalog>) -> Result<Self> {
+        if !query.ast().write_clauses.is_empty() {
+            return Err(crate::write::write_clauses_unsupported());
+        }
+        let catalog = query.scoped_catalog(catalog)?;
+        let plan = if query.ast().explain.is_some() {
+            None
+        } else {
+            match query.plan_ast(query.ast(), catalog.clone()) {
+                Ok((_, plan)) => Some(plan),
+                // Planning may need the values, as UNWIND does; errors not caused by
+                // them surface when executing
+                Err(_) if has_parameters(&query) => None,
+                Err(e) => return Err(e),
+            }
+        };
+        Ok(Self {
+            query,
+            catalog,
+            plan,
+        })
+    }
+
+    /// The text of the prepared query
+    pub fn query_text(&self) -> &str {
+        self.query.query_text()
+    }
+
+    /// Whether executions reuse one plan; otherwise each is planned for its values
+    pub fn is_planned(&self) -> bool {
+        self.plan.is_some()
+    }
+
+    /// Execute with parameters given as JSON values
+    pub async fn execute(
+        &self,
+        parameters: HashMap<String, serde_json::Value>,
+    ) -> Result<RecordBatch> {
+        self.execute_with_context(parameters, HashMap::new(), SessionContext::new())
+            .await
+    }
+
+    /// Execute with parameters given as typed scalars
+    pub async fn execute_with_scalar_parameters(
+        &self,
+        parameters: HashMap<String, ScalarValue>,
+    ) -> Result<RecordBatch> {
+        self.execute_with_context(HashMap::new(), parameters, SessionContext::new())
+            .await
+    }
+
+    /// Execute in `ctx` with parameters given as JSON values and as typed scalars
+    pub async fn execute_with_context(
+        &self,
+        parameters: HashMap<String, serde_json::Value>,
+        scalar_parameters: HashMap<String, ScalarValue>,
+        ctx: SessionContext,
+    ) -> Result<RecordBatch> {
+        let query = self
+            .query
+            .clone()
+            .with_parameters(parameters)
+            .with_scalar_parameters(scalar_parameters);
+        match self.bound_plan(&query)? {
+            Some(plan) => execution::collect(plan, &ctx, query.execution_limits()).await,
+            None => {
+                query
+                    .execute_with_catalog_and_context(self.catalog.clone(), ctx)
+                    .await
+            }
+        }
+    }
+
+    /// Execute with parameters given as JSON values, streaming the result batches
+    pub async fn execute_stream(
+        &self,
+        parameters: HashMap<String, serde_json::Value>,
+    ) -> Result<SendableRecordBatchStream> {
+        self.execute_stream_with_context(parameters, HashMap::new(), SessionContext::new())
+            .await
+    }
+
+    /// Execute in `ctx` with parameters given as JSON values and as typed scalars,
+    /// streaming the result batches
+    ///
+    /// See [`CypherQuery::execute_stream_with_catalog_and_context`].
+    pub async fn execute_stream_with_context(
+        &self,
+        parameters: HashMap<String, serde_json::Value>,
+        scalar_parameters: HashMap<String, ScalarValue>,
+        ctx: SessionContext,
+    ) -> Result<SendableRecordBatchStream> {
+        let query = self
+            .query
+            .clone()
+            .with_parameters(parameters)
+            .with_scalar_parameters(scalar_parameters);
+        match self.bound_plan(&query)? {
+            Some(plan) => execution::stream(plan, &ctx, query.execution_limits()).await,
+            None => {
+                query
+                    .execute_stream_with_catalog_and_context(self.catalog.clone(), ctx)
+                    .await
+            }
+        }
+    }
+
+    /// Encode the plan, with parameters given as JSON values substituted, as Substrait
+    ///
+    /// See [`crate::substrait`] for what the encoded plan reads.
+    #[cfg(feature = "substrait")]
+    pub fn to_substrait(&self, parameters: HashMap<String, serde_json::Value>) -> Result<Vec<u8>> {
+        let query = self.query.clone().with_parameters(parameters);
+        match self.bound_plan(&query)? {
+            Some(plan) => crate::substrait::to_substrait(&plan, &SessionContext::new()),
+            None => query.to_substrait(self.catalog.clone()),
+        }
+    }
+
+    /// The prepared plan with the parameter values of `query` bound, or `None` when the
+    /// query is planned for each execution's values
+    fn bound_plan(&self, query: &CypherQuery) -> Result<Option<LogicalPlan>> {
+        let resolved = resolve_parameters(query.parameters(), query.scalar_parameters())?;
+        let values = resolved
+            .iter()
+            .map(|(name, value)| Some((name.clone(), scalar(value)?)))
+            .collect::<Option<HashMap<_, _>>>();
+        let (Some(plan), Some(values)) = (&self.plan, values) else {
+            return Ok(None);
+        };
+
+        let config = query.config().ok_or_else(|| GraphError::ConfigError {
+            message: "Graph configuration is required for query execution".to_string(),
+            location: snafu::Location::new(file!(), line!(), column!()),
+        })?;
+        validate_parameter_types(query.ast(), &resolved, config, self.catalog.as_ref())?;
+        let placeholders = plan
+            .get_parameter_types()
+            .map_err(|e| GraphError::PlanError {
+                message: format!("Failed to find the query parameters: {}", e),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            })?;
+        for placeholder in placeholders.keys() {
+            let name = placeholder.trim_start_matches('$');
+            if !values.contains_key(name) {
+                return Err(GraphError::PlanError {
+                    message: format!("Missing value for parameter '${}'", name),
+                    location: snafu::Location::new(file!(), line!(), column!()),
+                });
+            }
+        }
+        plan.clone()
+            .with_param_values(ParamValues::Map(values))
+            .map(Some)
+            .map_err(|e| GraphError::PlanError {
+                message: format!("Failed to bind the query parameters: {}", e),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            })
+    }
+}
+
+/// Run a DataFusion plan, concatenating its results
+/// Whether the query has any `$parameter`, which binding none of them reports
+fn has_parameters(query: &CypherQuery) -> bool {
+    bind_parameters(&mut query.ast().clone(), &HashMap::new()).is_err()
+}
+
+/// The scalar replacing a placeholder; lists are planned with their values instead
+fn scalar(value: &PropertyValue) -> Option<ScalarValue> {
+    match value {
+        PropertyValue::String(s) => Some(ScalarValue::from(s.as_str())),
+        PropertyValue::Integer(i) => Some(ScalarValue::from(*i)),
+        PropertyValue::Float(f) => Some(ScalarValue::from(*f)),
+        PropertyValue::Boolean(b) => Some(ScalarValue::from(*b)),
+        PropertyValue::Null => Some(ScalarValue::Null),
+        _ => None,
+    }
+}
+
+/// Query text with runs of whitespace outside string literals collapsed to one space,
+/// the key of [`PlanCache`] entries
+pub fn normalize_query_text(text: &str) -> String {
+    let mut normalized = String::with_capacity(text.len());
+    let mut quote = None;
+    let mut escaped = false;
+    let mut space = false;
+    for c in text.trim().chars() {
+        match quote {
+            Some(q) => {
+                if escaped {
+                    escaped = false;
+                } else if c == '\\' {
+                    escaped = true;
+                } else if c == q {
+                    quote = None;
+                }
+                normalized.push(c);
+            }
+            None if c.is_whitespace() => space = true,
+            None => {
+                if space {
+                    normalized.push(' ');
+                    space = false;
+                }
+                if c == '\'' || c == '"' {
+                    quote = Some(c);
+                }
+                normalized.push(c);
+            }
+        }
+    }
+    normalized
+}
+
+struct CacheEntry {
+    query: Arc<PreparedQuery>,
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct CacheState {
+    entries: HashMap<String, CacheEntry>,
+    /// Incremented on every lookup, ordering entries by use
+    clock: u64,
+    hits: u64,
+    misses: u64,
+}
+
+/// A least-recently-used cache of the prepared queries of one graph
+pub struct PlanCache {
+    config: GraphConfig,
+    catalog: Arc<dyn GraphSourceCatalog>,
+    capacity: usize,
+    state: Mutex<CacheState>,
+}
+
+impl PlanCache {
+    /// Cache up to `capacity` queries planned with `config` against `catalog`
+    pub fn new(config: GraphConfig, catalog: Arc<dyn GraphSourceCatalog>, capacity: usize) -> Self {
+        Self {
+            config,
+            catalog,
+            capacity,
+            state: Mutex::new(CacheState::default()),
+        }
+    }
+
+    /// The prepared query for `query`, parsing and planning it unless it is cached
+    pub fn prepare(&self, query: &str) -> Result<Arc<PreparedQuery>> {
+        let key = normalize_query_text(query);
+        {
+            let mut state = self.lock();
+            state.clock += 1;
+            let clock = state.clock;
+            if let Some(entry) = state.entries.get_mut(&key) {
+                entry.last_used = clock;
+                let query = entry.query.clone();
+                state.hits += 1;
+                return Ok(query);
+            }
+            state.misses += 1;
+        }
+
+        // Planned without holding the lock; a query prepared concurrently is replaced
+        let parsed = CypherQuery::new(&key)?.with_config(self.config.clone());
+        let prepared = Arc::new(PreparedQuery::try_new(parsed, self.catalog.clone())?);
+        let mut state = self.lock();
+        let last_used = state.clock;
+        state.entries.insert(
+            key,
+            CacheEntry {
+                query: prepared.clone(),
+                last_used,
+            },
+        );
+        while state.entries.len() > self.capacity {
+            let oldest = state
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone());
+            match oldest {
+                Some(key) => state.entries.remove(&key),
+                None => break,
+            };
+        }
+        Ok(prepared)
+    }
+
+    /// Number of cached queries
+    pub fn len(&self) -> usize {
+        self.lock().entries.len()
+    }
+
+    /// Whether no query is cached
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Lookups answered from the cache
+    pub fn hits(&self) -> u64 {
+        self.lock().hits
+    }
+
+    /// Lookups that planned a query
+    pub fn misses(&self) -> u64 {
+        self.lock().misses
+    }
+
+    /// Drop every cached query, as after the catalog's schemas change
+    pub fn clear(&self) {
+        self.lock().entries.clear();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, CacheState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
@@ -0,0 +1,584 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Procedures invoked with `CALL`
+//!
+//! `CALL db.labels() YIELD label` runs a [`Procedure`] looked up by name in a
+//! [`ProcedureRegistry`] and binds the columns of the rows it returns. Procedures are
+//! called during execution, once per query, with their arguments evaluated to constants;
+//! their output is cross joined with the rows of the clauses before the CALL.
+//!
+//! The default registry holds the built-in procedures:
+//!
+//! - `db.labels()` yields the node labels of the graph, one `label` per row.
+//! - `db.relationshipTypes()` yields its relationship types as `relationshipType`.
+//! - `vector.knn(label, property, query, k [, metric])` yields the `id` and `distance`
+//!   of the `k` nodes of `label` whose vector `property` is nearest to `query`, using
+//!   `l2` (the default), `cosine` or `dot` distance. Lance datasets are searched with
+//!   their vector index when the property has one; other tables are searched
+//!   exhaustively. Yielded ids seed the patterns that follow:
+//!   `CALL vector.knn('Person', 'embedding', $q, 5) YIELD id AS seed, distance
+//!   MATCH (p:Person)-[:KNOWS]->(f) WHERE p.id = seed RETURN f.name, distance`.
+//! - `graph.bfs(startNodes, relTypes, maxDepth)` yields the `node` and `depth` of every
+//!   node reachable from the start node keys in at most `maxDepth` hops over the listed
+//!   relationship types, once each at its shortest depth and level by level;
+//!   `graph.dfs` yields them in depth-first preorder, at the depth first visited.
+//! - `graph.shortestPath.dijkstra(source, target, weightProperty [, relTypes])` yields
+//!   the `nodes`, `relationships` and total `cost` of a path from `source` to `target`
+//!   of least total weight, summing the `weightProperty` of its relationships; no row
+//!   when there is none. Without `relTypes`, every relationship type with the property
+//!   is traversed.
+//! - `graph.pageRank([config])` yields the `nodeId` and `score` of every node ending an
+//!   edge, from highest score to lowest, computed over an in-memory projection of the
+//!   edges. `config` is a map of `relationshipTypes` (default: all), `dampingFactor`
+//!   (0.85), `maxIterations` (20) and `tolerance` (1e-7).
+//! - `graph.wcc([config])` and `graph.scc([config])` yield the `nodeId` and
+//!   `componentId` of every node ending an edge, by weakly or strongly connected
+//!   component. Besides `relationshipTypes`, `config` may name a `writeLabel` and
+//!   `writeProperty` to store the components in the label's Lance dataset, or a
+//!   `writeDataset` URI to write the rows to.
+//! - `graph.louvain([config])` and `graph.labelPropagation([config])` yield the
+//!   `nodeId`, `communityId` and partition `modularity` of every node ending an edge,
+//!   with relationships taken as undirected. `config` is a map of `relationshipTypes`
+//!   and `maxIterations` (10), and for Louvain `maxLevels` (10) and `tolerance` (1e-7).
+//! - `graph.triangleCount([config])` yields the `triangleCount` of the graph taken as
+//!   simple and undirected, and `graph.localClusteringCoefficient([config])` the
+//!   `nodeId`, `triangleCount` and clustering `coefficient` of every node ending an
+//!   edge. `config` may list `relationshipTypes`.
+//! - `graph.degree([config])` yields the `nodeId` and `degree` of every node ending an
+//!   edge, counting its outgoing relationships, or with `direction: 'in'` or
+//!   `'total'` its incoming or all ones; `weightProperty` sums a property instead.
+//!   `graph.betweenness([config])` yields the betweenness `score` of every node,
+//!   exactly or, with `samplingSize` (and `samplingSeed`), estimated from a sample of
+//!   source nodes. Both yield nodes from highest score to lowest.
+//! - `graph.nodeSimilarity([config])` yields `node1`, `node2` and the `similarity` of
+//!   the nodes their relationships lead to, for the `topK` (10) most similar nodes of
+//!   each node. `config` also takes the `similarityMetric` (`'jaccard'` or
+//!   `'overlap'`), a `similarityCutoff` and the `degreeCutoff` below which nodes are
+//!   not compared.
+//! - `graph.randomWalk(startNodes, steps [, p, q, config])` yields the `startNode` and
+//!   `walk`, a list of node keys, of node2vec random walks of up to `steps` steps from
+//!   each start node, or from every node when the list is empty. `p` (1.0) weighs
+//!   against stepping back and `q` (1.0) against moving away; `config` takes
+//!   `relationshipTypes`, `walksPerNode` (1) and the `seed` (0) of the walks.
+//! - `graph.subgraph(seeds, maxHops [, config])` yields one row: the `nodes` within
+//!   `maxHops` hops of the seed node keys and the `relationships` between them, the
+//!   subgraph [`SubgraphQuery`](crate::algorithms::subgraph::SubgraphQuery) extracts. `config`
+//!   takes `relationshipTypes` and the `direction` to expand in: `'both'` (the
+//!   default), `'out'` or `'in'`.
+//! - `graph.topologicalSort([config])` yields the `nodeId` and `position` of every
+//!   node ending an edge, each before the nodes its relationships lead to and the
+//!   node of smallest key first among those free to go next; it fails naming a cycle
+//!   when the graph has one. `graph.findCycle([config])` yields the `nodeId` and
+//!   `position` of the nodes of such a cycle, in the order of its relationships, and
+//!   no row for an acyclic graph. `config` may list `relationshipTypes`.
+//!
+//! The algorithms from `graph.pageRank` to `graph.randomWalk`, `graph.topologicalSort`
+//! and `graph.findCycle` read their relationship tables into memory on every call,
+//! unless their `config` names a `projection`: a [`GraphProjection`] registered with
+//! the query, read once and shared by every call given its name.
+//!
+//! Others are added with
+//! [`ProcedureRegistry::with_procedure`] or
+//! [`CypherQuery::with_procedure`](crate::query::CypherQuery::with_procedure).
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use arrow::array::{ArrayRef, RecordBatch, RecordBatchOptions, StringArray};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use async_trait::async_trait;
+use datafusion::catalog::Session;
+use datafusion::common::DFSchema;
+use datafusion::datasource::memory::MemorySourceConfig;
+use datafusion::datasource::{TableProvider, TableType};
+use datafusion::execution::context::SessionContext;
+use datafusion::execution::session_state::SessionState;
+use datafusion::logical_expr::{ColumnarValue, Expr};
+use datafusion::physical_plan::ExecutionPlan;
+use datafusion::scalar::ScalarValue;
+use datafusion_common::DataFusionError;
+
+use crate::algorithms::projection::GraphProjection;
+use crate::algorithms::subgraph::ExtractSubgraph;
+use crate::algorithms::{
+    BetweennessCentrality, CommunityAlgorithm, CommunityDetection, ConnectedComponents,
+    Connectivity, DegreeCentrality, DijkstraShortestPath, GraphTraversal, NodeSimilarity,
+    OrderOutput, PageRank, RandomWalk, TopologicalOrder, TraversalOrder, TriangleOutput, Triangles,
+};
+use crate::config::GraphConfig;
+use crate::error::{GraphError, Result};
+use crate::source_catalog::GraphSourceCatalog;
+use crate::vector_search::VectorKnn;
+
+/// A parameter of a procedure
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProcedureParameter {
+    /// Parameter name, used in error messages
+    pub name: String,
+    /// Type the argument is cast to
+    pub data_type: DataType,
+    /// Value used when the argument is left out; only trailing parameters may have one
+    pub default: Option<ScalarValue>,
+}
+
+impl ProcedureParameter {
+    pub fn new(name: impl Into<String>, data_type: DataType) -> Self {
+        Self {
+            name: name.into(),
+            data_type,
+            default: None,
+        }
+    }
+
+    /// Make the parameter optional, taking `default` when left out
+    pub fn with_default(mut self, default: ScalarValue) -> Self {
+        self.default = Some(default);
+        self
+    }
+}
+
+/// What a procedure can see of the graph it is called on
+#[derive(Clone)]
+pub struct ProcedureContext {
+    config: GraphConfig,
+    catalog: Option<Arc<dyn GraphSourceCatalog>>,
+    session: Option<SessionContext>,
+    projections: HashMap<String, Arc<GraphProjection>>,
+}
+
+impl ProcedureContext {
+    pub fn new(config: GraphConfig, catalog: Option<Arc<dyn GraphSourceCatalog>>) -> Self {
+        Self {
+            config,
+            catalog,
+            session: None,
+            projections: HashMap::new(),
+        }
+    }
+
+    /// Attach the session the query runs in
+    pub fn with_session(mut self, session: SessionContext) -> Self {
+        self.session = Some(session);
+        self
+    }
+
+    /// Make a projection available to the procedure under `name`
+    pub fn with_projection(
+        mut self,
+        name: impl Into<String>,
+        projection: Arc<GraphProjection>,
+    ) -> Self {
+        self.projections.insert(name.into(), projection);
+        self
+    }
+
+    pub(crate) fn with_projections(
+        mut self,
+        projections: &HashMap<String, Arc<GraphProjection>>,
+    ) -> Self {
+        self.projections.extend(projections.clone());
+        self
+    }
+
+    /// Graph configuration of the query
+    pub fn config(&self) -> &GraphConfig {
+        &self.config
+    }
+
+    /// Catalog the query resolves labels and relationship types in, if any
+    pub fn catalog(&self) -> Option<&Arc<dyn GraphSourceCatalog>> {
+        self.catalog.as_ref()
+    }
+
+    /// Session executing the query; only set when the procedure is called, not while
+    /// its output schema is planned
+    pub fn session(&self) -> Option<&SessionContext> {
+        self.session.as_ref()
+    }
+
+    /// The projection registered under `name`, if any
+    pub fn projection(&self, name: &str) -> Option<&Arc<GraphProjection>> {
+        self.projections.get(name)
+    }
+}
+
+impl fmt::Debug for ProcedureContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProcedureContext")
+            .field("config", &self.config)
+            .field("has_catalog", &self.catalog.is_some())
+            .field("has_session", &self.session.is_some())
+            .field("projections", &self.projections.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+/// A procedure callable with `CALL name(args) YIELD columns`
+#[async_trait]
+pub trait Procedure: fmt::Debug + Send + Sync {
+    /// Name the procedure is called by, namespace included (`db.labels`); matched
+    /// case-insensitively
+    fn name(&self) -> &str;
+
+    /// Parameters, in argument order
+    fn parameters(&self) -> Vec<ProcedureParameter> {
+        Vec::new()
+    }
+
+    /// Schema of the rows the procedure returns
+    fn output_schema(&self, ctx: &ProcedureContext) -> Result<SchemaRef>;
+
+    /// Schema of the rows returned for `args`, for procedures whose columns depend on
+    /// their arguments
+    ///
+    /// `args` holds one entry per parameter: the argument cast to its type when it is
+    /// written as a literal or left to its default, `None` when it is only known once the
+    /// procedure is called.
+    fn output_schema_for(
+        &self,
+        ctx: &ProcedureContext,
+        _args: &[Option<ScalarValue>],
+    ) -> Result<SchemaRef> {
+        self.output_schema(ctx)
+    }
+
+    /// Run the procedure; `args` holds one value per parameter, cast to its type
+    async fn call(&self, ctx: &ProcedureContext, args: Vec<ScalarValue>) -> Result<RecordBatch>;
+}
+
+/// Procedures available to `CALL`, by lowercased name, and the graph projections they
+/// may run on, by name
+#[derive(Debug, Clone)]
+pub struct ProcedureRegistry {
+    procedures: HashMap<String, Arc<dyn Procedure>>,
+    projections: HashMap<String, Arc<GraphProjection>>,
+}
+
+impl ProcedureRegistry {
+    /// A registry without any procedure
+    pub fn empty() -> Self {
+        Self {
+            procedures: HashMap::new(),
+            projections: HashMap::new(),
+        }
+    }
+
+    /// A registry with the built-in procedures
+    pub fn new() -> Self {
+        Self::empty()
+            .with_procedure(Arc::new(Labels))
+            .with_procedure(Arc::new(RelationshipTypes))
+            .with_procedure(Arc::new(VectorKnn))
+            .with_procedure(Arc::new(GraphTraversal::new(TraversalOrder::BreadthFirst)))
+            .with_procedure(Arc::new(GraphTraversal::new(TraversalOrder::DepthFirst)))
+            .with_procedure(Arc::new(DijkstraShortestPath))
+            .with_procedure(Arc::new(PageRank))
+            .with_procedure(Arc::new(ConnectedComponents::new(Connectivity::Weak)))
+            .with_procedure(Arc::new(ConnectedComponents::new(Connectivity::Strong)))
+            .with_procedure(Arc::new(CommunityDetection::new(
+                CommunityAlgorithm::Louvain,
+            )))
+            .with_procedure(Arc::new(CommunityDetection::new(
+                CommunityAlgorithm::LabelPropagation,
+            )))
+            .with_procedure(Arc::new(Triangles::new(TriangleOutput::Global)))
+            .with_procedure(Arc::new(Triangles::new(TriangleOutput::Local)))
+            .with_procedure(Arc::new(DegreeCentrality))
+            .with_procedure(Arc::new(BetweennessCentrality))
+            .with_procedure(Arc::new(NodeSimilarity))
+            .with_procedure(Arc::new(RandomWalk))
+            .with_procedure(Arc::new(ExtractSubgraph))
+            .with_procedure(Arc::new(TopologicalOrder::new(OrderOutput::Order)))
+            .with_procedure(Arc::new(TopologicalOrder::new(OrderOutput::Cycle)))
+    }
+
+    /// Add a procedure, replacing any registered under the same name
+    pub fn with_procedure(mut self, procedure: Arc<dyn Procedure>) -> Self {
+        self.register(procedure);
+        self
+    }
+
+    /// Add a procedure, replacing any registered under the same name
+    pub fn register(&mut self, procedure: Arc<dyn Procedure>) {
+        self.procedures
+            .insert(procedure.name().to_lowercase(), procedure);
+    }
+
+    /// Look up a procedure by name, ignoring case
+    pub fn get(&self, name: &str) -> Option<&Arc<dyn Procedure>> {
+        self.procedures.get(&name.to_lowercase())
+    }
+
+    /// Make a projection available to procedures under `name`, replacing any of the same
+    /// name
+    pub fn with_projection(
+        mut self,
+        name: impl Into<String>,
+        projection: Arc<GraphProjection>,
+    ) -> Self {
+        self.register_projection(name, projection);
+        self
+    }
+
+    /// Make a projection available to procedures under `name`, replacing any of the same
+    /// name
+    pub fn register_projection(
+        &mut self,
+        name: impl Into<String>,
+        projection: Arc<GraphProjection>,
+    ) {
+        self.projections.insert(name.into(), projection);
+    }
+
+    /// The projections registered, by name
+    pub fn projections(&self) -> &HashMap<String, Arc<GraphProjection>> {
+        &self.projections
+    }
+
+    /// Names of the registered procedures, sorted
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.procedures.values().map(|p| p.name()).collect();
+        names.sort_unstable();
+        names
+    }
+}
+
+impl Default for ProcedureRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// `db.labels()`: the node labels of the graph, one `label` per row
+#[derive(Debug)]
+struct Labels;
+
+#[async_trait]
+impl Procedure for Labels {
+    fn name(&self) -> &str {
+        "db.labels"
+    }
+
+    fn output_schema(&self, _ctx: &ProcedureContext) -> Result<SchemaRef> {
+        Ok(single_string_schema("label"))
+    }
+
+    async fn call(&self, ctx: &ProcedureContext, _args: Vec<ScalarValue>) -> Result<RecordBatch> {
+        let labels = sorted_names(ctx.config().node_mappings.keys());
+        single_string_batch(self.output_schema(ctx)?, labels)
+    }
+}
+
+/// `db.relationshipTypes()`: the relationship types of the graph, one
+/// `relationshipType` per row
+#[derive(Debug)]
+struct RelationshipTypes;
+
+#[async_trait]
+impl Procedure for RelationshipTypes {
+    fn name(&self) -> &str {
+        "db.relationshipTypes"
+    }
+
+    fn output_schema(&self, _ctx: &ProcedureContext) -> Result<SchemaRef> {
+        Ok(single_string_schema("relationshipType"))
+    }
+
+    async fn call(&self, ctx: &ProcedureContext, _args: Vec<ScalarValue>) -> Result<RecordBatch> {
+        let types = sorted_names(ctx.config().relationship_mappings.keys());
+        single_string_batch(self.output_schema(ctx)?, types)
+    }
+}
+
+fn sorted_names<'a>(names: impl Iterator<Item = &'a String>) -> Vec<&'a str> {
+    let mut names: Vec<&str> = names.map(String::as_str).collect();
+    names.sort_unstable();
+    names
+}
+
+fn single_string_schema(column: &str) -> SchemaRef {
+    Arc::new(Schema::new(vec![Field::new(column, DataType::Utf8, false)]))
+}
+
+fn single_string_batch(schema: SchemaRef, values: Vec<&str>) -> Result<RecordBatch> {
+    let column: ArrayRef = Arc::new(StringArray::from(values));
+    RecordBatch::try_new(schema, vec![column]).map_err(|e| GraphError::ExecutionError {
+        message: format!("Failed to build procedure output: {}", e),
+        location: snafu::Location::new(file!(), line!(), column!()),
+    })
+}
+
+/// The rows of one CALL, produced when the plan is executed
+///
+/// Planning only needs the output schema, so EXPLAIN does not run the procedure.
+pub(crate) struct ProcedureTable {
+    procedure: Arc<dyn Procedure>,
+    context: ProcedureContext,
+    /// One constant expression per parameter, cast to its type
+    arguments: Vec<Expr>,
+    schema: SchemaRef,
+}
+
+impl ProcedureTable {
+    /// `known` holds the arguments known while planning, as passed to
+    /// [`Procedure::output_schema_for`]
+    pub(crate) fn try_new(
+        procedure: Arc<dyn Procedure>,
+        context: ProcedureContext,
+        arguments: Vec<Expr>,
+        known: &[Option<ScalarValue>],
+    ) -> Result<Self> {
+        let schema = procedure.output_schema_for(&context, known)?;
+        Ok(Self {
+            procedure,
+            context,
+            arguments,
+            schema,
+        })
+    }
+
+    /// Evaluate the argument expressions against a single empty row
+    fn evaluate_arguments(
+        &self,
+        state: &dyn Session,
+    ) -> std::result::Result<Vec<ScalarValue>, DataFusionError> {
+        let row = RecordBatch::try_new_with_options(
+            Arc::new(Schema::empty()),
+            vec![],
+            &RecordBatchOptions::new().with_row_count(Some(1)),
+        )?;
+        self.arguments
+            .iter()
+            .map(|argument| {
+                let expr = state.create_physical_expr(argument.clone(), &DFSchema::empty())?;
+                match expr.evaluate(&row)? {
+                    ColumnarValue::Scalar(value) => Ok(value),
+                    ColumnarValue::Array(array) => ScalarValue::try_from_array(&array, 0),
+                }
+            })
+            .collect()
+    }
+}
+
+impl fmt::Debug for ProcedureTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProcedureTable")
+            .field("procedure", &self.procedure.name())
+            .field("arguments", &self.arguments)
+            .finish()
+    }
+}
+
+#[async_trait]
+impl TableProvider for ProcedureTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::Temporary
+    }
+
+    async fn scan(
+        &self,
+        state: &dyn Session,
+        projection: Option<&Vec<usize>>,
+        _filters: &[Expr],
+        _limit: Option<usize>,
+    ) -> std::result::Result<Arc<dyn ExecutionPlan>, DataFusionError> {
+        let args = self.evaluate_arguments(state)?;
+        let mut context = self.context.clone();
+        if let Some(state) = state.as_any().downcast_ref::<SessionState>() {
+            context = context.with_session(SessionContext::new_with_state(state.clone()));
+        }
+        let batch = self
+            .procedure
+            .call(&context, args)
+            .await
+            .map_err(|e| DataFusionError::External(Box::new(e)))?;
+        if batch.schema().fields() != self.schema.fields() {
+            return Err(DataFusionError::Execution(format!(
+                "Procedure '{}' returned rows of schema {:?}, declared {:?}",
+                self.procedure.name(),
+                batch.schema(),
+                self.schema
+            )));
+        }
+        let batch = batch.with_schema(self.schema.clone())?;
+        let exec = MemorySourceConfig::try_new_exec(
+            &[vec![batch]],
+            self.schema.clone(),
+            projection.cloned(),
+        )?;
+        Ok(exec)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug)]
+    struct Echo;
+
+    #[async_trait]
+    impl Procedure for Echo {
+        fn name(&self) -> &str {
+            "test.Echo"
+        }
+
+        fn output_schema(&self, _ctx: &ProcedureContext) -> Result<SchemaRef> {
+            Ok(single_string_schema("value"))
+        }
+
+        async fn call(
+            &self,
+            ctx: &ProcedureContext,
+            _args: Vec<ScalarValue>,
+        ) -> Result<RecordBatch> {
+            single_string_batch(self.output_schema(ctx)?, vec!["echo"])
+        }
+    }
+
+    #[test]
+    fn test_registry_lookup_ignores_case() {
+        let registry = ProcedureRegistry::new().with_procedure(Arc::new(Echo));
+        assert_eq!(
+            registry.names(),
+            vec![
+                "db.labels",
+                "db.relationshipTypes",
+                "graph.betweenness",
+                "graph.bfs",
+                "graph.degree",
+                "graph.dfs",
+                "graph.findCycle",
+                "graph.labelPropagation",
+                "graph.localClusteringCoefficient",
+                "graph.louvain",
+                "graph.nodeSimilarity",
+                "graph.pageRank",
+                "graph.randomWalk",
+                "graph.scc",
+                "graph.shortestPath.dijkstra",
+                "graph.subgraph",
+                "graph.topologicalSort",
+                "graph.triangleCount",
+                "graph.wcc",
+                "test.Echo",
+                "vector.knn"
+            ]
+        );
+        assert_eq!(registry.get("DB.LABELS").unwrap().name(), "db.labels");
+        assert_eq!(registry.get("test.echo").unwrap().name(), "test.Echo");
+        assert!(registry.get("db.missing").is_none());
+        assert!(ProcedureRegistry::empty().names().is_empty());
+    }
+}
@@ -4,13 +4,23 @@
 //! High-level Cypher query interface for Lance datasets
 
 use crate::ast::CypherQuery as CypherAST;
+use crate::cancellation::{CancellationToken, Interrupt};
 use crate::config::GraphConfig;
 use crate::error::{GraphError, Result};
+use crate::execution::ExecutionLimits;
 use crate::logical_plan::LogicalPlanner;
+use crate::metrics::{QueryMetrics, QueryResult};
+use crate::parameters::{bind_parameters, resolve_parameters, validate_parameter_types};
 use crate::parser::parse_cypher_query;
+use crate::procedures::{Procedure, ProcedureRegistry};
 use crate::simple_executor::{
-    to_df_boolean_expr_simple, to_df_order_by_expr_simple, to_df_value_expr_simple, PathExecutor,
+    to_df_boolean_expr_simple, to_df_order_by_expr_simple, to_df_value_expr_simple,
+    unsupported_predicate, PathExecutor,
 };
+use arrow_array::ArrayRef;
+use datafusion::common::utils::SingleRowListArrayBuilder;
+use datafusion::optimizer::OptimizerRule;
+use datafusion::scalar::ScalarValue;
 use std::collections::HashMap;
 
 /// Execution strategy for Cypher queries
@@ -34,8 +44,22 @@ pub struct CypherQuery {
     ast: CypherAST,
     /// Graph configuration for mapping
     config: Option<GraphConfig>,
+    /// Graph configurations of named graphs, used instead of `config` under `USE`
+    graph_configs: HashMap<String, GraphConfig>,
     /// Query parameters
     parameters: HashMap<String, serde_json::Value>,
+    /// Query parameters given as typed scalars
+    scalar_parameters: HashMap<String, ScalarValue>,
+    /// Procedures available to CALL
+    procedures: std::sync::Arc<ProcedureRegistry>,
+    /// Rules rewriting the DataFusion plan lowered from the graph plan, in order
+    rewrite_rules: Vec<std::sync::Arc<dyn OptimizerRule + Send + Sync>>,
+    /// Token stopping executions of the query when cancelled
+    cancellation_token: Option<CancellationToken>,
+    /// Time limit of each execution
+    timeout: Option<std::time::Duration>,
+    /// Bytes each execution may hold in memory before spilling to disk
+    memory_limit: Option<usize>,
 }
 impl CypherQuery {
     /// Create a new Cypher query from a query string
@@ -46,29 +70,155 @@ impl CypherQuery {
             query_text: query.to_string(),
             ast,
             config: None,
+            graph_configs: HashMap::new(),
             parameters: HashMap::new(),
+            scalar_parameters: HashMap::new(),
+            procedures: std::sync::Arc::new(ProcedureRegistry::default()),
+            rewrite_rules: Vec::new(),
+            cancellation_token: None,
+            timeout: None,
+            memory_limit: None,
         })
     }
 
     /// Set the graph configuration for this query
+    ///
+    /// Labels and relationship types in the query are rewritten to the names declared
+    /// in `config` according to its [`LabelResolution`](crate::config::LabelResolution).
     pub fn with_config(mut self, config: GraphConfig) -> Self {
+        if self.graph_config().is_none() {
+            resolve_names(&mut self.ast, &config);
+        }
         self.config = Some(config);
         self
     }
 
+    /// Set the graph configuration of one named graph of a multi-graph catalog
+    ///
+    /// Queries selecting `graph` with `USE` map their labels and relationship types
+    /// with `config` rather than the one given to [`Self::with_config`], so graphs can
+    /// map the same label to different tables and keys.
+    pub fn with_graph_config(mut self, graph: impl Into<String>, config: GraphConfig) -> Self {
+        let graph = graph.into();
+        if self.ast.graph.as_deref() == Some(graph.as_str()) {
+            resolve_names(&mut self.ast, &config);
+        }
+        self.graph_configs.insert(graph, config);
+        self
+    }
+
+    /// Configuration of the graph selected by `USE`, if one was set for it
+    fn graph_config(&self) -> Option<&GraphConfig> {
+        self.graph_configs.get(self.ast.graph.as_deref()?)
+    }
+
     /// Add a parameter to the query
     pub fn with_parameter<K, V>(mut self, key: K, value: V) -> Self
     where
         K: Into<String>,
         V: Into<serde_json::Value>,
     {
-        self.parameters.insert(key.into(), value.into());
+        let key = key.into();
+        self.scalar_parameters.remove(&key);
+        self.parameters.insert(key, value.into());
         self
     }
 
     /// Add multiple parameters to the query
     pub fn with_parameters(mut self, params: HashMap<String, serde_json::Value>) -> Self {
-        self.parameters.extend(params);
+        for (key, value) in params {
+            self = self.with_parameter(key, value);
+        }
+        self
+    }
+
+    /// Add a typed parameter to the query
+    ///
+    /// Booleans, integers, floats, strings and lists of those can be bound; the value
+    /// is checked against the type of any property it is compared with.
+    pub fn with_scalar_parameter<K: Into<String>>(mut self, key: K, value: ScalarValue) -> Self {
+        let key = key.into();
+        self.parameters.remove(&key);
+        self.scalar_parameters.insert(key, value);
+        self
+    }
+
+    /// Add multiple typed parameters to the query
+    pub fn with_scalar_parameters(mut self, params: HashMap<String, ScalarValue>) -> Self {
+        for (key, value) in params {
+            self = self.with_scalar_parameter(key, value);
+        }
+        self
+    }
+
+    /// Add a list parameter holding the values of an Arrow array
+    pub fn with_list_parameter<K: Into<String>>(self, key: K, values: ArrayRef) -> Self {
+        let list = SingleRowListArrayBuilder::new(values).build_list_scalar();
+        self.with_scalar_parameter(key, list)
+    }
+
+    /// Make a procedure callable with CALL, replacing any of the same name
+    pub fn with_procedure(mut self, procedure: std::sync::Arc<dyn Procedure>) -> Self {
+        std::sync::Arc::make_mut(&mut self.procedures).register(procedure);
+        self
+    }
+
+    /// Make an in-memory graph projection available to the algorithm procedures under
+    /// `name`, for them to run on when given `{projection: name}` instead of reading
+    /// their relationship tables
+    pub fn with_projection(
+        mut self,
+        name: impl Into<String>,
+        projection: std::sync::Arc<crate::algorithms::projection::GraphProjection>,
+    ) -> Self {
+        std::sync::Arc::make_mut(&mut self.procedures).register_projection(name, projection);
+        self
+    }
+
+    /// Resolve CALL clauses in `procedures` instead of the built-in procedures
+    pub fn with_procedures(mut self, procedures: ProcedureRegistry) -> Self {
+        self.procedures = std::sync::Arc::new(procedures);
+        self
+    }
+
+    /// Rewrite the DataFusion plan with `rule` once the graph plan is lowered to it
+    ///
+    /// Rules run in the order they are added, before the session's own optimizer, and
+    /// the optimizer's usual passes apply: a rule returning `Transformed::no` leaves the
+    /// plan as is, and a rule that fails fails the query.
+    pub fn with_rewrite_rule(
+        mut self,
+        rule: std::sync::Arc<dyn OptimizerRule + Send + Sync>,
+    ) -> Self {
+        self.rewrite_rules.push(rule);
+        self
+    }
+
+    /// Stop executions of the query once `token` is cancelled
+    ///
+    /// See [`crate::cancellation`] for how a running query stops.
+    pub fn with_cancellation_token(mut self, token: CancellationToken) -> Self {
+        self.cancellation_token = Some(token);
+        self
+    }
+
+    /// Stop each execution of the query that runs longer than `timeout`
+    ///
+    /// The limit covers running the plan, not parsing or planning the query.
+    pub fn with_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Run each execution of the query in a memory pool of `bytes`, spilling to disk
+    /// beyond it
+    ///
+    /// Sorts, aggregations and joins spill to the session's disk manager (the OS
+    /// temporary directory by default) once their share of the pool is used up; joins are
+    /// planned as sort-merge joins, which can spill, rather than hash joins. An execution
+    /// that does not fit even when spilling fails instead of exhausting the host's memory.
+    pub fn with_memory_limit(mut self, bytes: usize) -> Self {
+        self.memory_limit = Some(bytes);
         self
     }
 
@@ -82,9 +232,9 @@ impl CypherQuery {
         &self.ast
     }
 
-    /// Get the graph configuration
+    /// Get the graph configuration, that of the graph selected by `USE` if one was set
     pub fn config(&self) -> Option<&GraphConfig> {
-        self.config.as_ref()
+        self.graph_config().or(self.config.as_ref())
     }
 
     /// Get query parameters
@@ -92,9 +242,44 @@ impl CypherQuery {
         &self.parameters
     }
 
+    /// Get the typed query parameters
+    pub fn scalar_parameters(&self) -> &HashMap<String, ScalarValue> {
+        &self.scalar_parameters
+    }
+
+    /// Get the procedures available to CALL
+    pub fn procedures(&self) -> &ProcedureRegistry {
+        &self.procedures
+    }
+
+    /// Get the rules rewriting the lowered DataFusion plan
+    pub fn rewrite_rules(&self) -> &[std::sync::Arc<dyn OptimizerRule + Send + Sync>] {
+        &self.rewrite_rules
+    }
+
+    /// The same query with another AST, such as the reading part of a write query
+    pub(crate) fn with_ast(&self, ast: CypherAST) -> Self {
+        Self {
+            ast,
+            ..self.clone()
+        }
+    }
+
+    /// Limits of an execution starting now
+    pub(crate) fn execution_limits(&self) -> ExecutionLimits {
+        ExecutionLimits {
+            interrupt: Interrupt::new(self.cancellation_token.clone(), self.timeout),
+            memory_limit: self.memory_limit,
+            settings: self
+                .config()
+                .map(|config| config.execution.clone())
+                .unwrap_or_default(),
+        }
+    }
+
     /// Get the required config, returning an error if not set
     fn require_config(&self) -> Result<&GraphConfig> {
-        self.config.as_ref().ok_or_else(|| GraphError::ConfigError {
+        self.config().ok_or_else(|| GraphError::ConfigError {
             message: "Graph configuration is required for query execution".to_string(),
             location: snafu::Location::new(file!(), line!(), column!()),
         })
@@ -140,7 +325,11 @@ impl CypherQuery {
         datasets: HashMap<String, arrow::record_batch::RecordBatch>,
         strategy: Option<ExecutionStrategy>,
     ) -> Result<arrow::record_batch::RecordBatch> {
-        let strategy = strategy.unwrap_or_default();
+        // Only DataFusion plans can be explained
+        let strategy = match self.ast.explain {
+            Some(_) => ExecutionStrategy::DataFusion,
+            None => strategy.unwrap_or_default(),
+        };
         match strategy {
             ExecutionStrategy::DataFusion => self.execute_datafusion(datasets).await,
             ExecutionStrategy::Simple => self.execute_simple(datasets).await,
@@ -305,60 +494,56 @@ impl CypherQuery {
     ///
     /// # Note
     /// The catalog is built by querying the SessionContext for schemas of tables
-    /// mentioned in the GraphConfig. Table names must match between GraphConfig
-    /// (node labels/relationship types) and SessionContext (registered table names).
+    /// mentioned in the GraphConfig. Node labels and the table names of relationship
+    /// types in the GraphConfig must match the table names registered in SessionContext.
     pub async fn execute_with_context(
         &self,
         ctx: datafusion::execution::context::SessionContext,
     ) -> Result<arrow::record_batch::RecordBatch> {
-        use crate::source_catalog::InMemoryCatalog;
-        use datafusion::datasource::DefaultTableSource;
         use std::sync::Arc;
 
         let config = self.require_config()?;
 
         // Build catalog by querying SessionContext for table providers
-        let mut catalog = InMemoryCatalog::new();
-
-        // Register node sources
-        for label in config.node_mappings.keys() {
-            let table_provider =
-                ctx.table_provider(label)
-                    .await
-                    .map_err(|e| GraphError::ConfigError {
-                        message: format!(
-                            "Node label '{}' not found in SessionContext: {}",
-                            label, e
-                        ),
-                        location: snafu::Location::new(file!(), line!(), column!()),
-                    })?;
-
-            let table_source = Arc::new(DefaultTableSource::new(table_provider));
-            catalog = catalog.with_node_source(label, table_source);
-        }
-
-        // Register relationship sources
-        for rel_type in config.relationship_mappings.keys() {
-            let table_provider =
-                ctx.table_provider(rel_type)
-                    .await
-                    .map_err(|e| GraphError::ConfigError {
-                        message: format!(
-                            "Relationship type '{}' not found in SessionContext: {}",
-                            rel_type, e
-                        ),
-                        location: snafu::Location::new(file!(), line!(), column!()),
-                    })?;
-
-            let table_source = Arc::new(DefaultTableSource::new(table_provider));
-            catalog = catalog.with_relationship_source(rel_type, table_source);
-        }
+        let catalog = crate::datafusion_catalog::catalog_from_session_context(&ctx, config).await?;
 
         // Execute using the built catalog
         self.execute_with_catalog_and_context(Arc::new(catalog), ctx)
             .await
     }
 
+    /// Execute query against a catalog whose sources are scannable table providers
+    ///
+    /// Use this with catalogs such as
+    /// [`ProviderCatalog`](crate::source_catalog::ProviderCatalog) or
+    /// [`LanceCatalog`](crate::lance_catalog::LanceCatalog), whose sources carry their
+    /// own `TableProvider`s, so no tables need to be registered in a `SessionContext`.
+    /// Schema-only sources such as `SimpleTableSource` can be planned but not executed.
+    ///
+    /// # Example
+    /// ```ignore
+    /// use std::sync::Arc;
+    /// use lance_graph::source_catalog::ProviderCatalog;
+    ///
+    /// let catalog = ProviderCatalog::new()
+    ///     .with_node_table("Person", Arc::new(person_mem_table))
+    ///     .with_relationship_table("KNOWS", Arc::new(knows_mem_table));
+    ///
+    /// let query = CypherQuery::new("MATCH (p:Person)-[:KNOWS]->(f:Person) RETURN f.name")?
+    ///     .with_config(config);
+    /// let result = query.execute_with_catalog(Arc::new(catalog)).await?;
+    /// ```
+    pub async fn execute_with_catalog(
+        &self,
+        catalog: std::sync::Arc<dyn crate::source_catalog::GraphSourceCatalog>,
+    ) -> Result<arrow::record_batch::RecordBatch> {
+        self.execute_with_catalog_and_context(
+            catalog,
+            datafusion::execution::context::SessionContext::new(),
+        )
+        .await
+    }
+
     /// Execute query with an explicit catalog and session context
     ///
     /// This is the most flexible API for advanced users who want to provide their own
@@ -395,46 +580,201 @@ impl CypherQuery {
     ///     .with_config(config);
     /// let result = query.execute_with_catalog_and_context(Arc::new(catalog), ctx).await?;
     /// ```
+    ///
+    /// A query starting with `EXPLAIN` or `PROFILE` returns its plans instead, see
+    /// [`ExplainMode`](crate::ast::ExplainMode).
     pub async fn execute_with_catalog_and_context(
         &self,
         catalog: std::sync::Arc<dyn crate::source_catalog::GraphSourceCatalog>,
         ctx: datafusion::execution::context::SessionContext,
     ) -> Result<arrow::record_batch::RecordBatch> {
-        use arrow::compute::concat_batches;
+        if let Some(mode) = self.ast.explain {
+            return self.explain_statement(mode, catalog, ctx).await;
+        }
 
         // Create logical plans (phases 1-3)
         let (_logical_plan, df_logical_plan) = self.create_logical_plans(catalog)?;
 
         // Execute the DataFusion plan (phase 4)
-        let df = ctx
-            .execute_logical_plan(df_logical_plan)
-            .await
-            .map_err(|e| GraphError::ExecutionError {
-                message: format!("Failed to execute DataFusion plan: {}", e),
+        crate::execution::collect(df_logical_plan, &ctx, self.execution_limits()).await
+    }
+
+    /// Execute query with an explicit catalog and session context, returning the rows
+    /// along with the metrics of every operator that produced them
+    ///
+    /// # Example
+    /// ```ignore
+    /// let result = query.execute_with_metrics(Arc::new(catalog), ctx).await?;
+    /// for operator in result.metrics.operators() {
+    ///     println!("{}: {:?} rows in {:?}", operator.operator, operator.output_rows,
+    ///              operator.elapsed_compute);
+    /// }
+    /// ```
+    pub async fn execute_with_metrics(
+        &self,
+        catalog: std::sync::Arc<dyn crate::source_catalog::GraphSourceCatalog>,
+        ctx: datafusion::execution::context::SessionContext,
+    ) -> Result<QueryResult> {
+        if self.ast.explain.is_some() {
+            return Err(GraphError::UnsupportedFeature {
+                feature: "metrics of EXPLAIN and PROFILE statements".to_string(),
                 location: snafu::Location::new(file!(), line!(), column!()),
-            })?;
+            });
+        }
+        let (_logical_plan, df_logical_plan) = self.create_logical_plans(catalog)?;
+        let (batch, metrics) =
+            crate::execution::collect_with_metrics(df_logical_plan, &ctx, self.execution_limits())
+                .await?;
+        Ok(QueryResult { batch, metrics })
+    }
 
-        // Get schema before collecting (in case result is empty)
-        let result_schema = df.schema().inner().clone();
+    /// Execute query against a catalog of scannable table providers, streaming the result
+    ///
+    /// See [`Self::execute_stream_with_catalog_and_context`].
+    pub async fn execute_stream_with_catalog(
+        &self,
+        catalog: std::sync::Arc<dyn crate::source_catalog::GraphSourceCatalog>,
+    ) -> Result<datafusion::physical_plan::SendableRecordBatchStream> {
+        self.execute_stream_with_catalog_and_context(
+            catalog,
+            datafusion::execution::context::SessionContext::new(),
+        )
+        .await
+    }
 
-        // Collect results
-        let batches = df.collect().await.map_err(|e| GraphError::ExecutionError {
-            message: format!("Failed to collect query results: {}", e),
-            location: snafu::Location::new(file!(), line!(), column!()),
-        })?;
+    /// Execute query with an explicit catalog and session context, returning the result
+    /// as a stream of batches instead of collecting it
+    ///
+    /// Batches are computed as the stream is polled, so a consumer reading slowly holds
+    /// back the scans and joins feeding it rather than letting the result pile up in
+    /// memory. `EXPLAIN` and `PROFILE` statements stream their single batch.
+    ///
+    /// # Example
+    /// ```ignore
+    /// use futures::TryStreamExt;
+    ///
+    /// let mut stream = query
+    ///     .execute_stream_with_catalog_and_context(Arc::new(catalog), ctx)
+    ///     .await?;
+    /// while let Some(batch) = stream.try_next().await? {
+    ///     println!("{} rows", batch.num_rows());
+    /// }
+    /// ```
+    pub async fn execute_stream_with_catalog_and_context(
+        &self,
+        catalog: std::sync::Arc<dyn crate::source_catalog::GraphSourceCatalog>,
+        ctx: datafusion::execution::context::SessionContext,
+    ) -> Result<datafusion::physical_plan::SendableRecordBatchStream> {
+        use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
+
+        if let Some(mode) = self.ast.explain {
+            let batch = self.explain_statement(mode, catalog, ctx).await?;
+            return Ok(Box::pin(RecordBatchStreamAdapter::new(
+                batch.schema(),
+                futures::stream::iter(vec![Ok(batch)]),
+            )));
+        }
 
-        if batches.is_empty() {
-            // Return empty batch with the schema from the DataFrame
-            // This preserves column structure even when there are no rows
-            return Ok(arrow::record_batch::RecordBatch::new_empty(result_schema));
+        let (_logical_plan, df_logical_plan) = self.create_logical_plans(catalog)?;
+        crate::execution::stream(df_logical_plan, &ctx, self.execution_limits()).await
+    }
+
+    /// The Arrow schema of the rows the query returns, planned against `catalog` without
+    /// executing it
+    ///
+    /// Parameters bound on the query are substituted as when executing. A query with no
+    /// bound parameters is planned with placeholders, so a column returning a parameter
+    /// as-is has no type until executed (`DataType::Null`).
+    ///
+    /// # Example
+    /// ```ignore
+    /// let query = CypherQuery::new("MATCH (p:Person) RETURN p.name, p.age")?
+    ///     .with_config(config);
+    /// let schema = query.output_schema(Arc::new(catalog))?;
+    /// assert_eq!(schema.field(0).name(), "p.name");
+    /// ```
+    pub fn output_schema(
+        &self,
+        catalog: std::sync::Arc<dyn crate::source_catalog::GraphSourceCatalog>,
+    ) -> Result<arrow_schema::SchemaRef> {
+        if self.ast.explain.is_some() {
+            return Ok(explain_schema());
         }
+        let (_, plan) = if self.parameters.is_empty() && self.scalar_parameters.is_empty() {
+            self.plan_ast(&self.ast, self.scoped_catalog(catalog)?)?
+        } else {
+            self.create_logical_plans(catalog)?
+        };
+        Ok(plan.schema().inner().clone())
+    }
 
-        // Combine all batches
-        let schema = batches[0].schema();
-        concat_batches(&schema, &batches).map_err(|e| GraphError::ExecutionError {
-            message: format!("Failed to concatenate result batches: {}", e),
-            location: snafu::Location::new(file!(), line!(), column!()),
-        })
+    /// Plan the query once against `catalog`, to execute repeatedly with different
+    /// parameter values
+    ///
+    /// # Example
+    /// ```ignore
+    /// let prepared = CypherQuery::new("MATCH (p:Person {id: $id}) RETURN p.name")?
+    ///     .with_config(config)
+    ///     .prepare(Arc::new(catalog))?;
+    /// for id in [1, 2, 3] {
+    ///     let mut parameters = HashMap::new();
+    ///     parameters.insert("id".to_string(), serde_json::json!(id));
+    ///     let result = prepared.execute(parameters).await?;
+    /// }
+    /// ```
+    pub fn prepare(
+        self,
+        catalog: std::sync::Arc<dyn crate::source_catalog::GraphSourceCatalog>,
+    ) -> Result<crate::prepared::PreparedQuery> {
+        crate::prepared::PreparedQuery::try_new(self, catalog)
+    }
+
+    /// Execute a query ending in write clauses against the Lance datasets of a catalog
+    ///
+    /// The reading clauses run first, and the write clauses apply once for every row they
+    /// produce; a query without reading clauses applies them once. The datasets written
+    /// to are committed together, and `catalog` is pointed at their new versions.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let query = CypherQuery::new(
+    ///     "MATCH (a:Person {name: 'Alice'}) CREATE (a)-[:KNOWS]->(:Person {name: 'Zoe'})",
+    /// )?
+    /// .with_config(config);
+    /// let summary = query.execute_write(&mut catalog).await?;
+    /// assert_eq!(summary.nodes_created, 1);
+    /// ```
+    pub async fn execute_write(
+        &self,
+        catalog: &mut crate::lance_catalog::LanceCatalog,
+    ) -> Result<crate::write::WriteSummary> {
+        crate::write::execute_write(self, catalog).await
+    }
+
+    /// Execute the query against a catalog and write the rows it returns to the Lance
+    /// dataset at `uri`, returning the dataset written
+    ///
+    /// Batches are written as they are computed. `WriteMode::Create` fails if the dataset
+    /// exists, `Append` adds the rows to it and `Overwrite` commits them as its new
+    /// version. Dots in column names, as in an unaliased `p.name`, become underscores.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let query = CypherQuery::new(
+    ///     "MATCH (p:Person)-[:KNOWS]->(f) RETURN p.id AS person, count(f) AS friends",
+    /// )?
+    /// .with_config(config);
+    /// query
+    ///     .write_to(Arc::new(catalog), "s3://bucket/friends.lance", WriteMode::Create)
+    ///     .await?;
+    /// ```
+    pub async fn write_to(
+        &self,
+        catalog: std::sync::Arc<dyn crate::source_catalog::GraphSourceCatalog>,
+        uri: &str,
+        mode: lance::dataset::WriteMode,
+    ) -> Result<lance::Dataset> {
+        crate::write::write_results(self, catalog, uri, mode).await
     }
 
     /// Execute using the DataFusion planner with in-memory datasets
@@ -536,16 +876,150 @@ impl CypherQuery {
         self.format_explain_output(&logical_plan, &df_logical_plan, physical_plan.as_ref())
     }
 
+    /// Run an `EXPLAIN` or `PROFILE` statement, returning one row per plan
+    ///
+    /// Like DataFusion's own EXPLAIN, the batch has a `plan_type` and a `plan` column.
+    /// Both statements return the graph logical plan and the DataFusion logical plan as
+    /// text and as JSON, and the Lance index lookups of the physical plan (`none` when it
+    /// reads no index). EXPLAIN adds the physical plan; PROFILE runs it and adds it
+    /// annotated with the metrics of every operator, along with a JSON profile giving the
+    /// rows and compute time of each operator and the total rows and elapsed time.
+    async fn explain_statement(
+        &self,
+        mode: crate::ast::ExplainMode,
+        catalog: std::sync::Arc<dyn crate::source_catalog::GraphSourceCatalog>,
+        ctx: datafusion::execution::context::SessionContext,
+    ) -> Result<arrow::record_batch::RecordBatch> {
+        use arrow_array::StringArray;
+        use datafusion::physical_plan::display::DisplayableExecutionPlan;
+        use std::sync::Arc;
+
+        let (logical_plan, df_logical_plan, physical_plan) =
+            self.create_plans(catalog, &ctx).await?;
+        let graph_plan_json =
+            serde_json::to_string_pretty(&logical_plan).map_err(|e| GraphError::PlanError {
+                message: format!("Failed to serialize the graph logical plan: {}", e),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            })?;
+        let mut rows = vec![
+            ("graph_logical_plan", format!("{:#?}", logical_plan)),
+            ("graph_logical_plan_json", graph_plan_json),
+            ("logical_plan", df_logical_plan.display_indent().to_string()),
+            (
+                "logical_plan_json",
+                df_logical_plan.display_pg_json().to_string(),
+            ),
+        ];
+        // Lance index lookups, so point and range lookups can be checked to use one
+        let lookups = crate::lance_table::index_usage(physical_plan.as_ref());
+        rows.push((
+            "index_usage",
+            if lookups.is_empty() {
+                "none".to_string()
+            } else {
+                lookups.join("\n")
+            },
+        ));
+        match mode {
+            crate::ast::ExplainMode::Explain => rows.push((
+                "physical_plan",
+                DisplayableExecutionPlan::new(physical_plan.as_ref())
+                    .indent(true)
+                    .to_string(),
+            )),
+            crate::ast::ExplainMode::Profile => {
+                let start = std::time::Instant::now();
+                let batches =
+                    datafusion::physical_plan::collect(physical_plan.clone(), ctx.task_ctx())
+                        .await
+                        .map_err(|e| GraphError::ExecutionError {
+                            message: format!("Failed to collect query results: {}", e),
+                            location: snafu::Location::new(file!(), line!(), column!()),
+                        })?;
+                let profile =
+                    QueryMetrics::from_plan(physical_plan.as_ref(), &batches, start.elapsed())
+                        .to_json();
+                rows.push((
+                    "physical_plan_with_metrics",
+                    DisplayableExecutionPlan::with_metrics(physical_plan.as_ref())
+                        .indent(true)
+                        .to_string(),
+                ));
+                rows.push((
+                    "profile_json",
+                    serde_json::to_string_pretty(&profile).unwrap_or_default(),
+                ));
+            }
+        }
+
+        let schema = explain_schema();
+        let (plan_types, plans): (Vec<_>, Vec<_>) = rows.into_iter().unzip();
+        arrow::record_batch::RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(StringArray::from(plan_types)),
+                Arc::new(StringArray::from(plans)),
+            ],
+        )
+        .map_err(|e| GraphError::ExecutionError {
+            message: format!("Failed to build the explain output: {}", e),
+            location: snafu::Location::new(file!(), line!(), column!()),
+        })
+    }
+
     /// Helper to create logical plans (graph logical, DataFusion logical)
     ///
     /// This performs phases 1-3 of query execution (semantic analysis, graph logical planning,
     /// DataFusion logical planning) without creating the physical plan.
-    fn create_logical_plans(
+    pub(crate) fn create_logical_plans(
         &self,
         catalog: std::sync::Arc<dyn crate::source_catalog::GraphSourceCatalog>,
     ) -> Result<(
         crate::logical_plan::LogicalOperator,
         datafusion::logical_expr::LogicalPlan,
+    )> {
+        let config = self.require_config()?;
+        let catalog = self.scoped_catalog(catalog)?;
+
+        // Substitute $parameters with their bound values, once their types are checked
+        let parameters = resolve_parameters(&self.parameters, &self.scalar_parameters)?;
+        validate_parameter_types(&self.ast, &parameters, config, catalog.as_ref())?;
+        let mut ast = self.ast.clone();
+        bind_parameters(&mut ast, &parameters)?;
+
+        self.plan_ast(&ast, catalog)
+    }
+
+    /// The catalog names resolve in, narrowed to one graph by `USE <graph>`
+    pub(crate) fn scoped_catalog(
+        &self,
+        catalog: std::sync::Arc<dyn crate::source_catalog::GraphSourceCatalog>,
+    ) -> Result<std::sync::Arc<dyn crate::source_catalog::GraphSourceCatalog>> {
+        Ok(match &self.ast.graph {
+            Some(graph) => {
+                if !catalog.has_graph(graph) {
+                    return Err(GraphError::ConfigError {
+                        message: format!("Graph '{}' is not registered in the catalog", graph),
+                        location: snafu::Location::new(file!(), line!(), column!()),
+                    });
+                }
+                std::sync::Arc::new(crate::source_catalog::GraphScopedCatalog::new(
+                    catalog, graph,
+                ))
+            }
+            None => catalog,
+        })
+    }
+
+    /// Plan an AST in `catalog`, which is already scoped to the query's graph; parameters
+    /// left unbound become DataFusion placeholders
+    pub(crate) fn plan_ast(
+        &self,
+        ast: &CypherAST,
+        catalog: std::sync::Arc<dyn crate::source_catalog::GraphSourceCatalog>,
+    ) -> Result<(
+        crate::logical_plan::LogicalOperator,
+        datafusion::logical_expr::LogicalPlan,
     )> {
         use crate::datafusion_planner::{DataFusionPlanner, GraphPhysicalPlanner};
         use crate::semantic::SemanticAnalyzer;
@@ -554,15 +1028,32 @@ impl CypherQuery {
 
         // Phase 1: Semantic Analysis
         let mut analyzer = SemanticAnalyzer::new(config.clone());
-        analyzer.analyze(&self.ast)?;
+        analyzer.analyze(ast)?;
 
         // Phase 2: Graph Logical Plan
-        let mut logical_planner = LogicalPlanner::new();
-        let logical_plan = logical_planner.plan(&self.ast)?;
+        let mut logical_planner = LogicalPlanner::new()
+            .with_statistics(catalog.clone())
+            .with_relationship_uniqueness(config.relationship_uniqueness);
+        let logical_plan = logical_planner.plan(ast)?;
 
         // Phase 3: DataFusion Logical Plan
-        let df_planner = DataFusionPlanner::with_catalog(config.clone(), catalog);
-        let df_logical_plan = df_planner.plan(&logical_plan)?;
+        let df_planner = DataFusionPlanner::with_catalog(config.clone(), catalog)
+            .with_procedures(self.procedures.clone());
+        let mut df_logical_plan = df_planner.plan(&logical_plan)?;
+
+        // User rewrite rules see the plan as lowered, before DataFusion optimizes it
+        if !self.rewrite_rules.is_empty() {
+            let optimizer =
+                datafusion::optimizer::Optimizer::with_rules(self.rewrite_rules.clone());
+            let context =
+                datafusion::optimizer::OptimizerContext::new().with_skip_failing_rules(false);
+            df_logical_plan = optimizer
+                .optimize(df_logical_plan, &context, |_, _| {})
+                .map_err(|e| GraphError::PlanError {
+                    message: format!("Rewrite rule failed: {}", e),
+                    location: snafu::Location::new(file!(), line!(), column!()),
+                })?;
+        }
 
         Ok((logical_plan, df_logical_plan))
     }
@@ -719,6 +1210,34 @@ impl CypherQuery {
         // Require a config for now, even if we don't fully exploit it yet
         let _config = self.require_config()?;
 
+        if !self.ast.with_clauses.is_empty() {
+            return Err(GraphError::UnsupportedFeature {
+                feature: "WITH clauses require the DataFusion execution strategy".to_string(),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            });
+        }
+        if !self.ast.unwind_clauses.is_empty() {
+            return Err(GraphError::UnsupportedFeature {
+                feature: "UNWIND clauses require the DataFusion execution strategy".to_string(),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            });
+        }
+        if !self.ast.call_clauses.is_empty() {
+            return Err(GraphError::UnsupportedFeature {
+                feature: "CALL clauses require the DataFusion execution strategy".to_string(),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            });
+        }
+        if !self.ast.unions.is_empty() {
+            return Err(GraphError::UnsupportedFeature {
+                feature: "UNION requires the DataFusion execution strategy".to_string(),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            });
+        }
+        if !self.ast.write_clauses.is_empty() {
+            return Err(crate::write::write_clauses_unsupported());
+        }
+
         if datasets.is_empty() {
             return Err(GraphError::PlanError {
                 message: "No input datasets provided".to_string(),
@@ -777,12 +1296,12 @@ impl CypherQuery {
 
         // Apply WHERE if present (limited support: simple comparisons on a single column)
         if let Some(where_clause) = &self.ast.where_clause {
-            if let Some(filter_expr) = to_df_boolean_expr_simple(&where_clause.expression) {
-                df = df.filter(filter_expr).map_err(|e| GraphError::PlanError {
-                    message: format!("Failed to apply filter: {}", e),
-                    location: snafu::Location::new(file!(), line!(), column!()),
-                })?;
-            }
+            let filter_expr = to_df_boolean_expr_simple(&where_clause.expression)
+                .ok_or_else(unsupported_predicate)?;
+            df = df.filter(filter_expr).map_err(|e| GraphError::PlanError {
+                message: format!("Failed to apply filter: {}", e),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            })?;
         }
 
         // Build projection from RETURN clause
@@ -850,7 +1369,7 @@ impl CypherQuery {
     pub fn referenced_node_labels(&self) -> Vec<String> {
         let mut labels = Vec::new();
 
-        for match_clause in &self.ast.match_clauses {
+        for match_clause in self.ast.all_match_clauses() {
             for pattern in &match_clause.patterns {
                 self.collect_node_labels_from_pattern(pattern, &mut labels);
             }
@@ -865,7 +1384,7 @@ impl CypherQuery {
     pub fn referenced_relationship_types(&self) -> Vec<String> {
         let mut types = Vec::new();
 
-        for match_clause in &self.ast.match_clauses {
+        for match_clause in self.ast.all_match_clauses() {
             for pattern in &match_clause.patterns {
                 self.collect_relationship_types_from_pattern(pattern, &mut types);
             }
@@ -880,7 +1399,7 @@ impl CypherQuery {
     pub fn variables(&self) -> Vec<String> {
         let mut variables = Vec::new();
 
-        for match_clause in &self.ast.match_clauses {
+        for match_clause in self.ast.all_match_clauses() {
             for pattern in &match_clause.patterns {
                 self.collect_variables_from_pattern(pattern, &mut variables);
             }
@@ -935,6 +1454,9 @@ impl CypherQuery {
                 }
             }
             crate::ast::GraphPattern::Path(path) => {
+                if let Some(var) = &path.variable {
+                    variables.push(var.clone());
+                }
                 if let Some(var) = &path.start_node.variable {
                     variables.push(var.clone());
                 }
@@ -963,6 +1485,12 @@ impl CypherQuery {
         };
         let match_clause = mc;
         let path = match match_clause.patterns.as_slice() {
+            [GraphPattern::Path(p)] if p.shortest.is_some() => {
+                return Err(GraphError::UnsupportedFeature {
+                    feature: "shortestPath requires the DataFusion execution strategy".to_string(),
+                    location: snafu::Location::new(file!(), line!(), column!()),
+                })
+            }
             [GraphPattern::Path(p)] if !p.segments.is_empty() => p,
             _ => return Ok(None),
         };
@@ -971,7 +1499,7 @@ impl CypherQuery {
         // Handle single-segment variable-length paths by unrolling ranges (*1..N, capped)
         if path.segments.len() == 1 {
             if let Some(length_range) = &path.segments[0].relationship.length {
-                let cap: u32 = crate::MAX_VARIABLE_LENGTH_HOPS;
+                let cap: u32 = cfg.max_variable_length_hops;
                 let min_len = length_range.min.unwrap_or(1).max(1);
                 let max_len = length_range.max.unwrap_or(cap);
 
@@ -1003,6 +1531,8 @@ impl CypherQuery {
                     let mut synthetic = crate::ast::PathPattern {
                         start_node: path.start_node.clone(),
                         segments: Vec::with_capacity(hops as usize),
+                        variable: None,
+                        shortest: None,
                     };
 
                     for i in 0..hops {
@@ -1069,10 +1599,13 @@ impl CypherQueryBuilder {
             variable: Some(variable.to_string()),
             labels: vec![label.to_string()],
             properties: HashMap::new(),
+            label_expression: None,
         };
 
         let match_clause = crate::ast::MatchClause {
             patterns: vec![crate::ast::GraphPattern::Node(node)],
+            optional: false,
+            where_clause: None,
         };
 
         self.match_clauses.push(match_clause);
@@ -1132,10 +1665,17 @@ impl CypherQueryBuilder {
         }
 
         let ast = crate::ast::CypherQuery {
+            graph: None,
+            explain: None,
             match_clauses: self.match_clauses,
             where_clause: self
                 .where_expression
                 .map(|expr| crate::ast::WhereClause { expression: expr }),
+            with_clauses: Vec::new(),
+            write_clauses: Vec::new(),
+            unions: Vec::new(),
+            unwind_clauses: Vec::new(),
+            call_clauses: Vec::new(),
             return_clause: crate::ast::ReturnClause {
                 distinct: self.distinct,
                 items: self.return_items,
@@ -1154,17 +1694,217 @@ impl CypherQueryBuilder {
         // Generate query text from AST (simplified)
         let query_text = "MATCH ... RETURN ...".to_string(); // TODO: Implement AST->text conversion
 
+        let mut ast = ast;
+        if let Some(config) = &self.config {
+            resolve_names(&mut ast, config);
+        }
+
         let query = CypherQuery {
             query_text,
             ast,
             config: self.config,
+            graph_configs: HashMap::new(),
             parameters: self.parameters,
+            scalar_parameters: HashMap::new(),
+            procedures: std::sync::Arc::new(ProcedureRegistry::default()),
+            rewrite_rules: Vec::new(),
+            cancellation_token: None,
+            timeout: None,
+            memory_limit: None,
         };
 
         Ok(query)
     }
 }
 
+/// Rewrite labels and relationship types to their declared names; names that do not
+/// resolve are left untouched so semantic analysis can report them.
+fn resolve_names(ast: &mut CypherAST, config: &GraphConfig) {
+    let resolve_label = |label: &mut String| {
+        if let Some(declared) = config.resolve_node_label(label) {
+            *label = declared.to_string();
+        }
+    };
+    let resolve_predicate_labels = |predicate: &mut crate::ast::BooleanExpression| {
+        for expression in predicate.label_expressions_mut() {
+            expression.labels_mut().into_iter().for_each(resolve_label);
+        }
+    };
+    let resolve_node = |node: &mut crate::ast::NodePattern| {
+        node.labels.iter_mut().for_each(resolve_label);
+        if let Some(expression) = &mut node.label_expression {
+            expression.labels_mut().into_iter().for_each(resolve_label);
+        }
+    };
+
+    fn resolve_clause(
+        match_clause: &mut crate::ast::MatchClause,
+        resolve_node: &impl Fn(&mut crate::ast::NodePattern),
+        resolve_predicate_labels: &impl Fn(&mut crate::ast::BooleanExpression),
+        config: &GraphConfig,
+    ) {
+        for pattern in match_clause.patterns.iter_mut() {
+            match pattern {
+                crate::ast::GraphPattern::Node(node) => resolve_node(node),
+                crate::ast::GraphPattern::Path(path) => {
+                    resolve_node(&mut path.start_node);
+                    for segment in path.segments.iter_mut() {
+                        resolve_node(&mut segment.end_node);
+                        for rel_type in segment.relationship.types.iter_mut() {
+                            if let Some(declared) = config.resolve_relationship_type(rel_type) {
+                                *rel_type = declared.to_string();
+                            }
+                        }
+                    }
+                    infer_endpoint_labels(path, config);
+                }
+            }
+        }
+        if let Some(where_clause) = &mut match_clause.where_clause {
+            resolve_predicate_labels(&mut where_clause.expression);
+            for subquery in where_clause.expression.subqueries_mut() {
+                resolve_clause(subquery, resolve_node, resolve_predicate_labels, config);
+            }
+        }
+    }
+
+    // Subqueries can appear in any WHERE, projected item or ORDER BY item
+    let mut predicates: Vec<&mut crate::ast::BooleanExpression> = Vec::new();
+    let mut expressions: Vec<&mut crate::ast::ValueExpression> = Vec::new();
+    predicates.extend(ast.where_clause.iter_mut().map(|w| &mut w.expression));
+    for with_clause in ast.with_clauses.iter_mut() {
+        predicates.extend(
+            with_clause
+                .where_clause
+                .iter_mut()
+                .chain(&mut with_clause.match_where_clause)
+                .map(|w| &mut w.expression),
+        );
+        expressions.extend(with_clause.items.iter_mut().map(|i| &mut i.expression));
+        expressions.extend(
+            with_clause
+                .order_by
+                .iter_mut()
+                .flat_map(|o| o.items.iter_mut().map(|i| &mut i.expression)),
+        );
+    }
+    expressions.extend(
+        ast.return_clause
+            .items
+            .iter_mut()
+            .map(|i| &mut i.expression),
+    );
+    expressions.extend(
+        ast.order_by
+            .iter_mut()
+            .flat_map(|o| o.items.iter_mut().map(|i| &mut i.expression)),
+    );
+    for predicate in predicates.iter_mut() {
+        resolve_predicate_labels(predicate);
+    }
+    let subqueries: Vec<_> = predicates
+        .into_iter()
+        .flat_map(crate::ast::BooleanExpression::subqueries_mut)
+        .chain(
+            expressions
+                .into_iter()
+                .flat_map(crate::ast::ValueExpression::subqueries_mut),
+        )
+        .collect();
+    for subquery in subqueries {
+        resolve_clause(subquery, &resolve_node, &resolve_predicate_labels, config);
+    }
+    let match_clauses = ast.match_clauses.iter_mut().chain(
+        ast.with_clauses
+            .iter_mut()
+            .flat_map(|with_clause| with_clause.match_clauses.iter_mut()),
+    );
+    for match_clause in match_clauses {
+        resolve_clause(
+            match_clause,
+            &resolve_node,
+            &resolve_predicate_labels,
+            config,
+        );
+    }
+    let write_patterns = ast
+        .write_clauses
+        .iter_mut()
+        .flat_map(|write_clause| match write_clause {
+            crate::ast::WriteClause::Create(create) => create.patterns.iter_mut(),
+            crate::ast::WriteClause::Merge(merge) => {
+                std::slice::from_mut(&mut merge.pattern).iter_mut()
+            }
+            crate::ast::WriteClause::Set(_)
+            | crate::ast::WriteClause::Remove(_)
+            | crate::ast::WriteClause::Delete(_) => Default::default(),
+        });
+    for pattern in write_patterns {
+        match pattern {
+            crate::ast::GraphPattern::Node(node) => resolve_node(node),
+            crate::ast::GraphPattern::Path(path) => {
+                resolve_node(&mut path.start_node);
+                for segment in path.segments.iter_mut() {
+                    resolve_node(&mut segment.end_node);
+                    for rel_type in segment.relationship.types.iter_mut() {
+                        if let Some(declared) = config.resolve_relationship_type(rel_type) {
+                            *rel_type = declared.to_string();
+                        }
+                    }
+                }
+            }
+        }
+    }
+    for union in ast.unions.iter_mut() {
+        resolve_names(&mut union.query, config);
+    }
+}
+
+/// Label unlabeled path endpoints from the endpoint labels of the relationship
+/// mapping between them, so `()-[r:RATED]->()` can be planned.
+fn infer_endpoint_labels(path: &mut crate::ast::PathPattern, config: &GraphConfig) {
+    use crate::ast::RelationshipDirection;
+
+    for i in 0..path.segments.len() {
+        let relationship = &path.segments[i].relationship;
+        let [rel_type] = relationship.types.as_slice() else {
+            continue;
+        };
+        let Some(mapping) = config.get_relationship_mapping(rel_type) else {
+            continue;
+        };
+        let (left_label, right_label) = match relationship.direction {
+            RelationshipDirection::Outgoing => (&mapping.source_label, &mapping.target_label),
+            RelationshipDirection::Incoming => (&mapping.target_label, &mapping.source_label),
+            RelationshipDirection::Undirected => continue,
+        };
+        let (left_label, right_label) = (left_label.clone(), right_label.clone());
+
+        let left = if i == 0 {
+            &mut path.start_node
+        } else {
+            &mut path.segments[i - 1].end_node
+        };
+        if let Some(label) = left_label.filter(|_| left.labels.is_empty()) {
+            left.labels.push(label);
+        }
+        let right = &mut path.segments[i].end_node;
+        if let Some(label) = right_label.filter(|_| right.labels.is_empty()) {
+            right.labels.push(label);
+        }
+    }
+}
+
+/// Columns of the rows returned by `EXPLAIN` and `PROFILE` statements
+fn explain_schema() -> arrow_schema::SchemaRef {
+    use arrow_schema::{DataType, Field, Schema};
+
+    std::sync::Arc::new(Schema::new(vec![
+        Field::new("plan_type", DataType::Utf8, false),
+        Field::new("plan", DataType::Utf8, false),
+    ]))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1219,6 +1959,35 @@ mod tests {
         assert_eq!(query.variables(), vec!["a", "b", "r"]);
     }
 
+    #[test]
+    fn test_with_config_resolves_label_case() {
+        use crate::config::LabelResolution;
+
+        let cfg = GraphConfig::builder()
+            .with_node_label("Person", "id")
+            .with_relationship("WORKS_FOR", "person_id", "company_id")
+            .with_label_resolution(LabelResolution::SnakeCase)
+            .build()
+            .unwrap();
+
+        let query = CypherQuery::new("MATCH (a:person)-[:worksFor]->(b:Company) RETURN a.name")
+            .unwrap()
+            .with_config(cfg);
+        // Undeclared labels are left for semantic analysis to report
+        assert_eq!(query.referenced_node_labels(), vec!["Company", "Person"]);
+        assert_eq!(query.referenced_relationship_types(), vec!["WORKS_FOR"]);
+
+        let exact = CypherQuery::new("MATCH (a:person) RETURN a.name")
+            .unwrap()
+            .with_config(
+                GraphConfig::builder()
+                    .with_node_label("Person", "id")
+                    .build()
+                    .unwrap(),
+            );
+        assert_eq!(exact.referenced_node_labels(), vec!["person"]);
+    }
+
     #[tokio::test]
     async fn test_execute_basic_projection_and_filter() {
         use arrow_array::{Int64Array, RecordBatch, StringArray};
@@ -1374,6 +2143,74 @@ mod tests {
         assert_eq!(collected, vec![28, 29, 34, 42]);
     }
 
+    #[tokio::test]
+    async fn test_execute_simple_null_semantics() {
+        use arrow_array::{Int64Array, RecordBatch, StringArray};
+        use arrow_schema::{DataType, Field, Schema};
+        use std::sync::Arc;
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("name", DataType::Utf8, true),
+            Field::new("age", DataType::Int64, true),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(StringArray::from(vec!["Bob", "Alice", "David"])),
+                Arc::new(Int64Array::from(vec![Some(34), None, Some(42)])),
+            ],
+        )
+        .unwrap();
+        let cfg = GraphConfig::builder()
+            .with_node_label("Person", "id")
+            .build()
+            .unwrap();
+        let run = |cypher: &str| {
+            let q = CypherQuery::new(cypher).unwrap().with_config(cfg.clone());
+            let data = HashMap::from([("people".to_string(), batch.clone())]);
+            async move { q.execute_simple(data).await }
+        };
+        let names = |out: &RecordBatch| -> Vec<String> {
+            let names = out
+                .column(0)
+                .as_any()
+                .downcast_ref::<StringArray>()
+                .unwrap();
+            names.iter().map(|n| n.unwrap().to_string()).collect()
+        };
+
+        let out = run("MATCH (p:Person) WHERE p.age IS NULL RETURN p.name")
+            .await
+            .unwrap();
+        assert_eq!(names(&out), vec!["Alice"]);
+
+        // A NULL in the list makes IN unknown rather than false
+        let out = run("MATCH (p:Person) WHERE NOT p.age IN [34, null] RETURN p.name")
+            .await
+            .unwrap();
+        assert_eq!(out.num_rows(), 0);
+
+        // NULL sorts last ascending and first descending
+        let out = run("MATCH (p:Person) RETURN p.name ORDER BY p.age")
+            .await
+            .unwrap();
+        assert_eq!(names(&out), vec!["Bob", "David", "Alice"]);
+        let out = run("MATCH (p:Person) RETURN p.name ORDER BY p.age DESC")
+            .await
+            .unwrap();
+        assert_eq!(names(&out), vec!["Alice", "David", "Bob"]);
+
+        // Predicates the simple translator cannot express are rejected, not dropped
+        let err = run("MATCH (p:Person) WHERE p.name STARTS WITH 'A' RETURN p.name")
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, GraphError::UnsupportedFeature { .. }),
+            "{}",
+            err
+        );
+    }
+
     #[tokio::test]
     async fn test_execute_order_by_desc_with_skip_limit() {
         use arrow_array::{Int64Array, RecordBatch, StringArray};
@@ -64,7 +64,8 @@ impl QueryProcessor {
         }
 
         // Phase 3: Logical Planning - Convert AST to logical operators
-        let mut logical_planner = LogicalPlanner::new();
+        let mut logical_planner =
+            LogicalPlanner::new().with_relationship_uniqueness(self.config.relationship_uniqueness);
         let logical_plan = logical_planner.plan(&ast)?;
 
         // Phase 4: Physical Planning with datasets registered in a DF context
@@ -98,7 +99,8 @@ impl QueryProcessor {
         }
 
         // Phase 3: Logical Planning - Convert AST to logical operators
-        let mut logical_planner = LogicalPlanner::new();
+        let mut logical_planner =
+            LogicalPlanner::new().with_relationship_uniqueness(self.config.relationship_uniqueness);
         let logical_plan = logical_planner.plan(&ast)?;
 
         // Phase 4: Physical Planning - Convert logical plan to DataFusion plan
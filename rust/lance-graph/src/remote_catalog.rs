@@ -0,0 +1,403 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Graph catalog served by a central metadata service.
+//!
+//! A fleet of query nodes can share one graph definition instead of baking it into
+//! each binary: [`RemoteCatalog`] fetches a [`RemoteGraphDefinition`] — the
+//! [`GraphConfig`] plus the schema (and optionally the Lance dataset URI) behind every
+//! label and relationship type — through a [`MetadataClient`].
+//!
+//! [`HttpMetadataClient`] speaks JSON over HTTP(S). Other transports such as gRPC plug
+//! in by implementing [`MetadataClient`]; credentials plug in through [`AuthProvider`].
+//!
+//! Planning is synchronous, so the definition is fetched when the catalog is connected
+//! and served from memory until [`RemoteCatalog::refresh`] is called.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+use arrow_schema::Schema;
+use async_trait::async_trait;
+use datafusion::logical_expr::TableSource;
+use serde::{Deserialize, Serialize};
+
+use crate::config::GraphConfig;
+use crate::error::{GraphError, Result};
+use crate::lance_catalog::LanceCatalog;
+use crate::source_catalog::{
+    ChainedCatalog, GraphSourceCatalog, InMemoryCatalog, SimpleTableSource, SourceDescription,
+    SourceStatistics,
+};
+
+/// Graph definition as served by a metadata service.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteGraphDefinition {
+    /// Node and relationship mappings
+    pub config: GraphConfig,
+    /// Source for each node label
+    #[serde(default)]
+    pub nodes: BTreeMap<String, RemoteSource>,
+    /// Source for each relationship type
+    #[serde(default)]
+    pub relationships: BTreeMap<String, RemoteSource>,
+}
+
+/// Schema and location of one label or relationship type.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteSource {
+    /// Arrow schema of the table
+    pub schema: Schema,
+    /// Lance dataset URI; sources without one can be planned but not scanned
+    #[serde(default)]
+    pub uri: Option<String>,
+}
+
+/// Supplies credentials for metadata requests as header name/value pairs.
+pub trait AuthProvider: Send + Sync {
+    fn headers(&self) -> Result<Vec<(String, String)>>;
+}
+
+/// Static bearer token sent as an `Authorization` header.
+#[derive(Clone)]
+pub struct BearerToken(String);
+
+impl BearerToken {
+    pub fn new(token: impl Into<String>) -> Self {
+        Self(token.into())
+    }
+}
+
+impl fmt::Debug for BearerToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("BearerToken(***)")
+    }
+}
+
+impl AuthProvider for BearerToken {
+    fn headers(&self) -> Result<Vec<(String, String)>> {
+        Ok(vec![(
+            "Authorization".to_string(),
+            format!("Bearer {}", self.0),
+        )])
+    }
+}
+
+/// Transport that fetches graph definitions from a metadata service.
+#[async_trait]
+pub trait MetadataClient: Send + Sync {
+    async fn fetch_graph(&self, graph: &str) -> Result<RemoteGraphDefinition>;
+}
+
+/// Fetches graph definitions as JSON from `{base_url}/graphs/{graph}`.
+pub struct HttpMetadataClient {
+    base_url: String,
+    client: reqwest::Client,
+    auth: Option<Arc<dyn AuthProvider>>,
+}
+
+impl HttpMetadataClient {
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            client: reqwest::Client::new(),
+            auth: None,
+        }
+    }
+
+    /// Attach credentials to every request.
+    pub fn with_auth(mut self, auth: Arc<dyn AuthProvider>) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+
+    /// Use a preconfigured HTTP client (timeouts, proxies, TLS roots).
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// `{base_url}/graphs/{graph}`, with the graph name percent-encoded as one segment
+    fn graph_url(&self, graph: &str) -> Result<reqwest::Url> {
+        let invalid = |message: String| GraphError::ConfigError {
+            message: format!(
+                "Invalid metadata service URL '{}': {}",
+                self.base_url, message
+            ),
+            location: snafu::Location::new(file!(), line!(), column!()),
+        };
+        // A dot segment would resolve to another path rather than be encoded
+        if graph == "." || graph == ".." {
+            return Err(GraphError::ConfigError {
+                message: format!("'{}' is not a graph name", graph),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            });
+        }
+        let mut url = reqwest::Url::parse(&self.base_url).map_err(|e| invalid(e.to_string()))?;
+        url.path_segments_mut()
+            .map_err(|_| invalid("it cannot have a path".to_string()))?
+            .pop_if_empty()
+            .extend(["graphs", graph]);
+        Ok(url)
+    }
+}
+
+#[async_trait]
+impl MetadataClient for HttpMetadataClient {
+    async fn fetch_graph(&self, graph: &str) -> Result<RemoteGraphDefinition> {
+        let url = self.graph_url(graph)?;
+        let mut request = self.client.get(url.clone());
+        if let Some(auth) = &self.auth {
+            for (name, value) in auth.headers()? {
+                request = request.header(name, value);
+            }
+        }
+
+        let remote_error = |e: reqwest::Error| GraphError::ConfigError {
+            message: format!("Failed to fetch graph definition from {}: {}", url, e),
+            location: snafu::Location::new(file!(), line!(), column!()),
+        };
+        request
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(remote_error)?
+            .json::<RemoteGraphDefinition>()
+            .await
+            .map_err(remote_error)
+    }
+}
+
+struct Snapshot {
+    config: GraphConfig,
+    sources: ChainedCatalog,
+}
+
+/// A catalog backed by a graph definition from a metadata service.
+pub struct RemoteCatalog {
+    client: Arc<dyn MetadataClient>,
+    graph: String,
+    snapshot: RwLock<Arc<Snapshot>>,
+}
+
+impl RemoteCatalog {
+    /// Fetch the definition of `graph` and open the datasets it references.
+    pub async fn connect(
+        client: Arc<dyn MetadataClient>,
+        graph: impl Into<String>,
+    ) -> Result<Self> {
+        let graph = graph.into();
+        let snapshot = load_snapshot(client.as_ref(), &graph).await?;
+        Ok(Self {
+            client,
+            graph,
+            snapshot: RwLock::new(Arc::new(snapshot)),
+        })
+    }
+
+    /// Re-fetch the definition. The previous one keeps serving lookups until the new
+    /// one has loaded, and is kept if loading fails.
+    pub async fn refresh(&self) -> Result<()> {
+        let snapshot = load_snapshot(self.client.as_ref(), &self.graph).await?;
+        *self.snapshot.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(snapshot);
+        Ok(())
+    }
+
+    /// Name of the graph this catalog serves.
+    pub fn graph(&self) -> &str {
+        &self.graph
+    }
+
+    /// Graph mappings from the most recent definition.
+    pub fn config(&self) -> GraphConfig {
+        self.current().config.clone()
+    }
+
+    fn current(&self) -> Arc<Snapshot> {
+        self.snapshot
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+}
+
+async fn load_snapshot(client: &dyn MetadataClient, graph: &str) -> Result<Snapshot> {
+    let definition = client.fetch_graph(graph).await?;
+    definition.config.validate()?;
+
+    let mut datasets = LanceCatalog::new();
+    let mut schemas = InMemoryCatalog::new();
+    for (label, source) in definition.nodes {
+        match &source.uri {
+            Some(uri) => datasets = datasets.with_node_uri(&label, uri).await?,
+            None => {
+                schemas = schemas.with_node_source(
+                    label,
+                    Arc::new(SimpleTableSource::new(Arc::new(source.schema))),
+                )
+            }
+        }
+    }
+    for (rel_type, source) in definition.relationships {
+        match &source.uri {
+            Some(uri) => datasets = datasets.with_relationship_uri(&rel_type, uri).await?,
+            None => {
+                schemas = schemas.with_relationship_source(
+                    rel_type,
+                    Arc::new(SimpleTableSource::new(Arc::new(source.schema))),
+                )
+            }
+        }
+    }
+
+    Ok(Snapshot {
+        config: definition.config,
+        sources: ChainedCatalog::new(vec![Arc::new(datasets), Arc::new(schemas)]),
+    })
+}
+
+impl GraphSourceCatalog for RemoteCatalog {
+    fn node_source(&self, label: &str) -> Option<Arc<dyn TableSource>> {
+        self.current().sources.node_source(label)
+    }
+
+    fn relationship_source(&self, rel_type: &str) -> Option<Arc<dyn TableSource>> {
+        self.current().sources.relationship_source(rel_type)
+    }
+
+    fn list_node_labels(&self) -> Vec<String> {
+        self.current().sources.list_node_labels()
+    }
+
+    fn list_relationship_types(&self) -> Vec<String> {
+        self.current().sources.list_relationship_types()
+    }
+
+    fn describe(&self, name: &str) -> Option<SourceDescription> {
+        let snapshot = self.current();
+        snapshot
+            .sources
+            .describe(name)
+            .map(|description| description.with_keys_from(&snapshot.config))
+    }
+
+    fn statistics(&self, name: &str) -> Option<SourceStatistics> {
+        self.current().sources.statistics(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_schema::{DataType, Field};
+    use std::io::{Read, Write};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn definition(property: &str) -> RemoteGraphDefinition {
+        let schema = Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new(property, DataType::Utf8, true),
+        ]);
+        RemoteGraphDefinition {
+            config: GraphConfig::builder()
+                .with_node_label("Person", "id")
+                .build()
+                .unwrap(),
+            nodes: BTreeMap::from([("Person".to_string(), RemoteSource { schema, uri: None })]),
+            relationships: BTreeMap::new(),
+        }
+    }
+
+    /// Serves a different definition on every fetch.
+    struct VersionedClient {
+        fetches: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl MetadataClient for VersionedClient {
+        async fn fetch_graph(&self, graph: &str) -> Result<RemoteGraphDefinition> {
+            assert_eq!(graph, "social");
+            let version = self.fetches.fetch_add(1, Ordering::SeqCst);
+            Ok(definition(&format!("name_v{}", version)))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_remote_catalog_serves_and_refreshes_definition() {
+        let client = Arc::new(VersionedClient {
+            fetches: AtomicUsize::new(0),
+        });
+        let catalog = RemoteCatalog::connect(client, "social").await.unwrap();
+        assert_eq!(catalog.list_node_labels(), vec!["Person"]);
+        assert!(catalog.config().get_node_mapping("Person").is_some());
+
+        let description = catalog.describe("Person").unwrap();
+        assert_eq!(description.key_columns, vec!["id"]);
+        assert_eq!(description.schema.field(1).name(), "name_v0");
+
+        catalog.refresh().await.unwrap();
+        let source = catalog.node_source("Person").unwrap();
+        assert_eq!(source.schema().field(1).name(), "name_v1");
+    }
+
+    #[tokio::test]
+    async fn test_http_client_sends_auth_and_parses_definition() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let body = serde_json::to_string(&definition("name")).unwrap();
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 1024];
+            while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                let n = stream.read(&mut buf).unwrap();
+                request.extend_from_slice(&buf[..n]);
+            }
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                body.len(),
+                body
+            )
+            .unwrap();
+            String::from_utf8(request).unwrap()
+        });
+
+        let client = HttpMetadataClient::new(format!("http://{}/", addr))
+            .with_auth(Arc::new(BearerToken::new("secret")));
+        let fetched = client.fetch_graph("social").await.unwrap();
+        assert!(fetched.nodes.contains_key("Person"));
+
+        let request = server.join().unwrap().to_ascii_lowercase();
+        assert!(request.starts_with("get /graphs/social "), "{}", request);
+        assert!(
+            request.contains("authorization: bearer secret"),
+            "{}",
+            request
+        );
+    }
+
+    #[test]
+    fn test_http_client_encodes_graph_name() {
+        let url = |base: &str, graph: &str| {
+            HttpMetadataClient::new(base)
+                .graph_url(graph)
+                .unwrap()
+                .to_string()
+        };
+        assert_eq!(
+            url("http://metadata/", "social"),
+            "http://metadata/graphs/social"
+        );
+        assert_eq!(
+            url("http://metadata/api", "team a/b?c#d%"),
+            "http://metadata/api/graphs/team%20a%2Fb%3Fc%23d%25"
+        );
+        let client = HttpMetadataClient::new("http://metadata");
+        assert!(client.graph_url("..").is_err());
+        assert!(HttpMetadataClient::new("metadata")
+            .graph_url("social")
+            .is_err());
+    }
+}
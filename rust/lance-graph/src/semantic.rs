@@ -17,6 +17,8 @@ use std::collections::{HashMap, HashSet};
 pub struct SemanticAnalyzer {
     config: GraphConfig,
     variables: HashMap<String, VariableInfo>,
+    /// Variables that went out of scope at a WITH, still subject to schema validation
+    retired: Vec<VariableInfo>,
     current_scope: ScopeType,
 }
 
@@ -44,6 +46,9 @@ pub enum VariableType {
 pub enum ScopeType {
     Match,
     Where,
+    Unwind,
+    Call,
+    With,
     Return,
     OrderBy,
 }
@@ -62,6 +67,7 @@ impl SemanticAnalyzer {
         Self {
             config,
             variables: HashMap::new(),
+            retired: Vec::new(),
             current_scope: ScopeType::Match,
         }
     }
@@ -71,29 +77,39 @@ impl SemanticAnalyzer {
         let mut errors = Vec::new();
         let mut warnings = Vec::new();
 
-        // Phase 1: Variable discovery in MATCH clauses
-        self.current_scope = ScopeType::Match;
-        for match_clause in &query.match_clauses {
-            if let Err(e) = self.analyze_match_clause(match_clause) {
-                errors.push(format!("MATCH clause error: {}", e));
-            }
-        }
+        // Phase 1: Variable discovery in MATCH, UNWIND and CALL clauses
+        self.analyze_reading_clauses(
+            &query.match_clauses,
+            &query.unwind_clauses,
+            &query.call_clauses,
+            &mut errors,
+        );
 
-        // Phase 2: Validate WHERE clause
-        if let Some(where_clause) = &query.where_clause {
+        // Phase 2: Validate WHERE clauses, including those attached to OPTIONAL MATCH
+        let where_clauses = query
+            .match_clauses
+            .iter()
+            .filter_map(|clause| clause.where_clause.as_ref())
+            .chain(&query.where_clause);
+        for where_clause in where_clauses {
             self.current_scope = ScopeType::Where;
             if let Err(e) = self.analyze_where_clause(where_clause) {
                 errors.push(format!("WHERE clause error: {}", e));
             }
         }
 
-        // Phase 3: Validate RETURN clause
+        // Phase 3: Each WITH replaces the scope with its projected bindings
+        for with_clause in &query.with_clauses {
+            self.analyze_with_clause(with_clause, &mut errors);
+        }
+
+        // Phase 4: Validate RETURN clause
         self.current_scope = ScopeType::Return;
         if let Err(e) = self.analyze_return_clause(&query.return_clause) {
             errors.push(format!("RETURN clause error: {}", e));
         }
 
-        // Phase 4: Validate ORDER BY clause
+        // Phase 5: Validate ORDER BY clause
         if let Some(order_by) = &query.order_by {
             self.current_scope = ScopeType::OrderBy;
             if let Err(e) = self.analyze_order_by_clause(order_by) {
@@ -101,12 +117,20 @@ impl SemanticAnalyzer {
             }
         }
 
-        // Phase 5: Schema validation
+        // Phase 6: Schema validation
         self.validate_schema(&mut warnings);
 
-        // Phase 6: Type checking
+        // Phase 7: Type checking
         self.validate_types(&mut errors);
 
+        // Phase 8: Each UNION part is analyzed in a scope of its own
+        for (idx, union) in query.unions.iter().enumerate() {
+            let part = SemanticAnalyzer::new(self.config.clone()).analyze(&union.query)?;
+            let prefix = format!("UNION part {}", idx + 2);
+            errors.extend(part.errors.iter().map(|e| format!("{}: {}", prefix, e)));
+            warnings.extend(part.warnings.iter().map(|w| format!("{}: {}", prefix, w)));
+        }
+
         Ok(SemanticResult {
             query: query.clone(),
             variables: self.variables.clone(),
@@ -145,8 +169,200 @@ impl SemanticAnalyzer {
                     // Register end node
                     self.register_node_variable(&segment.end_node)?;
                 }
+
+                if let Some(path_var) = &path.variable {
+                    self.register_path_variable(path_var)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Analyze the MATCH, UNWIND and CALL clauses of a query part in query order
+    fn analyze_reading_clauses(
+        &mut self,
+        match_clauses: &[MatchClause],
+        unwind_clauses: &[UnwindClause],
+        call_clauses: &[CallClause],
+        errors: &mut Vec<String>,
+    ) {
+        for i in 0..=match_clauses.len() {
+            for call in call_clauses.iter().filter(|c| c.preceding_matches == i) {
+                if let Err(e) = self.analyze_call_clause(call) {
+                    errors.push(format!("CALL clause error: {}", e));
+                }
+            }
+            for unwind in unwind_clauses.iter().filter(|u| u.preceding_matches == i) {
+                if let Err(e) = self.analyze_unwind_clause(unwind) {
+                    errors.push(format!("UNWIND clause error: {}", e));
+                }
+            }
+            if let Some(match_clause) = match_clauses.get(i) {
+                self.current_scope = ScopeType::Match;
+                if let Err(e) = self.analyze_match_clause(match_clause) {
+                    errors.push(format!("MATCH clause error: {}", e));
+                }
+            }
+        }
+    }
+
+    /// Analyze an UNWIND clause and bind its element variable
+    fn analyze_unwind_clause(&mut self, unwind: &UnwindClause) -> Result<()> {
+        self.current_scope = ScopeType::Unwind;
+        self.analyze_value_expression(&unwind.expression)?;
+        if self.variables.contains_key(&unwind.alias) {
+            return Err(GraphError::PlanError {
+                message: format!("Variable '{}' already defined", unwind.alias),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            });
+        }
+        self.variables.insert(
+            unwind.alias.clone(),
+            VariableInfo {
+                name: unwind.alias.clone(),
+                variable_type: VariableType::Property,
+                labels: Vec::new(),
+                properties: HashSet::new(),
+                defined_in: ScopeType::Unwind,
+            },
+        );
+        Ok(())
+    }
+
+    /// Analyze a CALL clause and bind the variables it yields
+    fn analyze_call_clause(&mut self, call: &CallClause) -> Result<()> {
+        self.current_scope = ScopeType::Call;
+        for argument in &call.arguments {
+            self.analyze_value_expression(argument)?;
+        }
+        for item in call.yield_items.iter().flatten() {
+            let variable = item.variable();
+            if self.variables.contains_key(variable) {
+                return Err(GraphError::PlanError {
+                    message: format!("Variable '{}' already defined", variable),
+                    location: snafu::Location::new(file!(), line!(), column!()),
+                });
+            }
+            self.variables.insert(
+                variable.to_string(),
+                VariableInfo {
+                    name: variable.to_string(),
+                    variable_type: VariableType::Property,
+                    labels: Vec::new(),
+                    properties: HashSet::new(),
+                    defined_in: ScopeType::Call,
+                },
+            );
+        }
+        if let Some(where_clause) = &call.where_clause {
+            self.current_scope = ScopeType::Where;
+            self.analyze_where_clause(where_clause)?;
+        }
+        Ok(())
+    }
+
+    /// Analyze a WITH clause and the query part it starts
+    fn analyze_with_clause(&mut self, with_clause: &WithClause, errors: &mut Vec<String>) {
+        self.current_scope = ScopeType::With;
+        let mut scope = HashMap::new();
+        for item in &with_clause.items {
+            if let Err(e) = self.analyze_value_expression(&item.expression) {
+                errors.push(format!("WITH clause error: {}", e));
+                continue;
+            }
+            let carried = match &item.expression {
+                ValueExpression::Variable(v) => self.variables.get(v).cloned(),
+                _ => None,
+            };
+            let Some(name) = item
+                .alias
+                .clone()
+                .or_else(|| carried.as_ref().map(|v| v.name.clone()))
+            else {
+                errors.push(
+                    "WITH clause error: expression in WITH must be aliased (use AS)".to_string(),
+                );
+                continue;
+            };
+            let info = match carried {
+                Some(info) => VariableInfo {
+                    name: name.clone(),
+                    ..info
+                },
+                None => VariableInfo {
+                    name: name.clone(),
+                    variable_type: VariableType::Property,
+                    labels: Vec::new(),
+                    properties: HashSet::new(),
+                    defined_in: ScopeType::With,
+                },
+            };
+            if scope.insert(name.clone(), info).is_some() {
+                errors.push(format!("WITH clause error: duplicate name '{}'", name));
+            }
+        }
+        let carried: HashSet<&String> = with_clause
+            .items
+            .iter()
+            .filter_map(|item| match &item.expression {
+                ValueExpression::Variable(v) => Some(v),
+                _ => None,
+            })
+            .collect();
+        for (name, info) in std::mem::replace(&mut self.variables, scope) {
+            if !carried.contains(&name) {
+                self.retired.push(info);
+            }
+        }
+
+        if let Some(order_by) = &with_clause.order_by {
+            if let Err(e) = self.analyze_order_by_clause(order_by) {
+                errors.push(format!("WITH ORDER BY error: {}", e));
+            }
+        }
+        if let Some(where_clause) = &with_clause.where_clause {
+            if let Err(e) = self.analyze_where_clause(where_clause) {
+                errors.push(format!("WITH WHERE error: {}", e));
+            }
+        }
+
+        self.analyze_reading_clauses(
+            &with_clause.match_clauses,
+            &with_clause.unwind_clauses,
+            &with_clause.call_clauses,
+            errors,
+        );
+        let where_clauses = with_clause
+            .match_clauses
+            .iter()
+            .filter_map(|clause| clause.where_clause.as_ref())
+            .chain(&with_clause.match_where_clause);
+        for where_clause in where_clauses {
+            self.current_scope = ScopeType::Where;
+            if let Err(e) = self.analyze_where_clause(where_clause) {
+                errors.push(format!("WHERE clause error: {}", e));
             }
         }
+    }
+
+    /// Register a path variable (e.g., 'p' in p = shortestPath(...))
+    fn register_path_variable(&mut self, var_name: &str) -> Result<()> {
+        if self.variables.contains_key(var_name) {
+            return Err(GraphError::PlanError {
+                message: format!("Variable '{}' redefined as a path", var_name),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            });
+        }
+        self.variables.insert(
+            var_name.to_string(),
+            VariableInfo {
+                name: var_name.to_string(),
+                variable_type: VariableType::Path,
+                labels: Vec::new(),
+                properties: HashSet::new(),
+                defined_in: self.current_scope.clone(),
+            },
+        );
         Ok(())
     }
 
@@ -218,9 +434,35 @@ impl SemanticAnalyzer {
 
     /// Analyze WHERE clause
     fn analyze_where_clause(&mut self, where_clause: &WhereClause) -> Result<()> {
+        if where_clause.expression.contains_aggregate() {
+            return Err(GraphError::PlanError {
+                message: "Aggregate functions are not allowed in WHERE; aggregate in a WITH \
+                          clause and filter its result instead"
+                    .to_string(),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            });
+        }
         self.analyze_boolean_expression(&where_clause.expression)
     }
 
+    /// Analyze the pattern and WHERE of an `EXISTS { }` or `COUNT { }` subquery
+    fn analyze_subquery(&mut self, clause: &MatchClause) -> Result<()> {
+        let outer = self.variables.clone();
+        let result = self.analyze_match_clause(clause).and_then(|_| {
+            clause
+                .where_clause
+                .as_ref()
+                .map_or(Ok(()), |w| self.analyze_where_clause(w))
+        });
+        // Variables the subquery introduces are not visible outside it
+        for (name, info) in std::mem::replace(&mut self.variables, outer.clone()) {
+            if !outer.contains_key(&name) {
+                self.retired.push(info);
+            }
+        }
+        result
+    }
+
     /// Analyze boolean expression and check variable references
     fn analyze_boolean_expression(&mut self, expr: &BooleanExpression) -> Result<()> {
         match expr {
@@ -228,6 +470,14 @@ impl SemanticAnalyzer {
                 self.analyze_value_expression(left)?;
                 self.analyze_value_expression(right)?;
             }
+            BooleanExpression::StringMatch {
+                expression,
+                pattern,
+                ..
+            } => {
+                self.analyze_value_expression(expression)?;
+                self.analyze_value_expression(pattern)?;
+            }
             BooleanExpression::And(left, right) | BooleanExpression::Or(left, right) => {
                 self.analyze_boolean_expression(left)?;
                 self.analyze_boolean_expression(right)?;
@@ -235,9 +485,27 @@ impl SemanticAnalyzer {
             BooleanExpression::Not(inner) => {
                 self.analyze_boolean_expression(inner)?;
             }
+            BooleanExpression::FullTextSearch { expression, query } => {
+                if let ValueExpression::Literal(value) = query {
+                    if !matches!(
+                        value,
+                        PropertyValue::String(_) | PropertyValue::Parameter(_)
+                    ) {
+                        return Err(GraphError::PlanError {
+                            message: format!("FTS query must be a string, got {:?}", value),
+                            location: snafu::Location::new(file!(), line!(), column!()),
+                        });
+                    }
+                }
+                self.analyze_value_expression(expression)?;
+                self.analyze_value_expression(query)?;
+            }
             BooleanExpression::Exists(prop_ref) => {
                 self.validate_property_reference(prop_ref)?;
             }
+            BooleanExpression::ExistsSubquery(clause) => {
+                self.analyze_subquery(clause)?;
+            }
             BooleanExpression::In { expression, list } => {
                 self.analyze_value_expression(expression)?;
                 for item in list {
@@ -253,6 +521,9 @@ impl SemanticAnalyzer {
             BooleanExpression::IsNotNull(expression) => {
                 self.analyze_value_expression(expression)?;
             }
+            BooleanExpression::HasLabels { variable, .. } => {
+                self.analyze_value_expression(&ValueExpression::Variable(variable.clone()))?;
+            }
         }
         Ok(())
     }
@@ -274,7 +545,31 @@ impl SemanticAnalyzer {
                     });
                 }
             }
-            ValueExpression::Function { name, args } => {
+            ValueExpression::Function {
+                name,
+                distinct,
+                args,
+            } => {
+                let is_aggregate = ValueExpression::is_aggregate_function(name);
+                if *distinct && !is_aggregate {
+                    return Err(GraphError::PlanError {
+                        message: format!(
+                            "DISTINCT is only allowed in aggregate functions, not {}",
+                            name.to_uppercase()
+                        ),
+                        location: snafu::Location::new(file!(), line!(), column!()),
+                    });
+                }
+                if is_aggregate && args.iter().any(ValueExpression::contains_aggregate) {
+                    return Err(GraphError::PlanError {
+                        message: format!(
+                            "Aggregate functions cannot be nested inside {}",
+                            name.to_uppercase()
+                        ),
+                        location: snafu::Location::new(file!(), line!(), column!()),
+                    });
+                }
+
                 // Validate function-specific arity and signature rules
                 match name.to_lowercase().as_str() {
                     "count" | "sum" | "avg" | "min" | "max" => {
@@ -303,6 +598,129 @@ impl SemanticAnalyzer {
                             }
                         }
                     }
+                    "collect" if args.len() != 1 => {
+                        return Err(GraphError::PlanError {
+                            message: format!(
+                                "COLLECT requires exactly 1 argument, got {}",
+                                args.len()
+                            ),
+                            location: snafu::Location::new(file!(), line!(), column!()),
+                        });
+                    }
+                    "length" | "nodes" | "relationships" => {
+                        let is_path = match args.as_slice() {
+                            [ValueExpression::Variable(v)] => self
+                                .variables
+                                .get(v)
+                                .is_some_and(|info| info.variable_type == VariableType::Path),
+                            _ => false,
+                        };
+                        if !is_path {
+                            return Err(GraphError::PlanError {
+                                message: format!(
+                                    "{} requires a single path variable argument",
+                                    name.to_uppercase()
+                                ),
+                                location: snafu::Location::new(file!(), line!(), column!()),
+                            });
+                        }
+                    }
+                    "toupper" | "upper" | "tolower" | "lower" | "trim" | "ltrim" | "rtrim"
+                    | "abs" | "sign" | "ceil" | "floor" | "sqrt" | "exp" | "log" | "log10"
+                    | "tointeger" | "tofloat"
+                        if args.len() != 1 =>
+                    {
+                        return Err(GraphError::PlanError {
+                            message: format!(
+                                "{} requires exactly 1 argument, got {}",
+                                name.to_uppercase(),
+                                args.len()
+                            ),
+                            location: snafu::Location::new(file!(), line!(), column!()),
+                        });
+                    }
+                    "split" | "left" | "right" | "nullif" if args.len() != 2 => {
+                        return Err(GraphError::PlanError {
+                            message: format!(
+                                "{} requires exactly 2 arguments, got {}",
+                                name.to_uppercase(),
+                                args.len()
+                            ),
+                            location: snafu::Location::new(file!(), line!(), column!()),
+                        });
+                    }
+                    "round" if !matches!(args.len(), 1 | 2) => {
+                        return Err(GraphError::PlanError {
+                            message: format!("ROUND requires 1 or 2 arguments, got {}", args.len()),
+                            location: snafu::Location::new(file!(), line!(), column!()),
+                        });
+                    }
+                    "rand" if !args.is_empty() => {
+                        return Err(GraphError::PlanError {
+                            message: format!("RAND takes no arguments, got {}", args.len()),
+                            location: snafu::Location::new(file!(), line!(), column!()),
+                        });
+                    }
+                    "coalesce" if args.is_empty() => {
+                        return Err(GraphError::PlanError {
+                            message: "COALESCE requires at least 1 argument".to_string(),
+                            location: snafu::Location::new(file!(), line!(), column!()),
+                        });
+                    }
+                    "replace" if args.len() != 3 => {
+                        return Err(GraphError::PlanError {
+                            message: format!(
+                                "REPLACE requires exactly 3 arguments, got {}",
+                                args.len()
+                            ),
+                            location: snafu::Location::new(file!(), line!(), column!()),
+                        });
+                    }
+                    "date" | "localdatetime" | "datetime" | "duration" => {
+                        self.validate_temporal_function(name, args)?;
+                    }
+                    "duration.between" if args.len() != 2 => {
+                        return Err(GraphError::PlanError {
+                            message: format!(
+                                "DURATION.BETWEEN requires exactly 2 arguments, got {}",
+                                args.len()
+                            ),
+                            location: snafu::Location::new(file!(), line!(), column!()),
+                        });
+                    }
+                    "vector.distance" if !matches!(args.len(), 2 | 3) => {
+                        return Err(GraphError::PlanError {
+                            message: format!(
+                                "VECTOR.DISTANCE requires 2 or 3 arguments, got {}",
+                                args.len()
+                            ),
+                            location: snafu::Location::new(file!(), line!(), column!()),
+                        });
+                    }
+                    "vector.distance" => {
+                        if let Some(ValueExpression::Literal(PropertyValue::String(metric))) =
+                            args.get(2)
+                        {
+                            if crate::vector_search::parse_metric(metric).is_none() {
+                                return Err(GraphError::PlanError {
+                                    message: format!(
+                                        "VECTOR.DISTANCE metric must be 'l2', 'cosine' or 'dot', got '{}'",
+                                        metric
+                                    ),
+                                    location: snafu::Location::new(file!(), line!(), column!()),
+                                });
+                            }
+                        }
+                    }
+                    "substring" if !matches!(args.len(), 2 | 3) => {
+                        return Err(GraphError::PlanError {
+                            message: format!(
+                                "SUBSTRING requires 2 or 3 arguments, got {}",
+                                args.len()
+                            ),
+                            location: snafu::Location::new(file!(), line!(), column!()),
+                        });
+                    }
                     _ => {
                         // Other functions - no validation yet
                     }
@@ -313,6 +731,73 @@ impl SemanticAnalyzer {
                     self.analyze_value_expression(arg)?;
                 }
             }
+            ValueExpression::Case {
+                branches,
+                else_result,
+            } => {
+                for (condition, result) in branches {
+                    self.analyze_boolean_expression(condition)?;
+                    self.analyze_value_expression(result)?;
+                }
+                if let Some(else_result) = else_result {
+                    self.analyze_value_expression(else_result)?;
+                }
+            }
+            ValueExpression::List(items) => {
+                for item in items {
+                    self.analyze_value_expression(item)?;
+                }
+            }
+            ValueExpression::Map(entries) => {
+                for (_, value) in entries {
+                    self.analyze_value_expression(value)?;
+                }
+            }
+            ValueExpression::Index { list, index } => {
+                self.analyze_value_expression(list)?;
+                self.analyze_value_expression(index)?;
+            }
+            ValueExpression::Slice { list, from, to } => {
+                self.analyze_value_expression(list)?;
+                for bound in [from, to].into_iter().flatten() {
+                    self.analyze_value_expression(bound)?;
+                }
+            }
+            ValueExpression::ListComprehension {
+                variable,
+                list,
+                predicate,
+                projection,
+            } => {
+                self.analyze_value_expression(list)?;
+                // The element variable shadows any outer binding inside the comprehension
+                let outer = self.variables.insert(
+                    variable.clone(),
+                    VariableInfo {
+                        name: variable.clone(),
+                        variable_type: VariableType::Property,
+                        labels: Vec::new(),
+                        properties: HashSet::new(),
+                        defined_in: ScopeType::Return,
+                    },
+                );
+                let result = predicate
+                    .iter()
+                    .try_for_each(|p| self.analyze_boolean_expression(p))
+                    .and_then(|_| {
+                        projection
+                            .iter()
+                            .try_for_each(|e| self.analyze_value_expression(e))
+                    });
+                match outer {
+                    Some(outer) => self.variables.insert(variable.clone(), outer),
+                    None => self.variables.remove(variable),
+                };
+                result?;
+            }
+            ValueExpression::CountSubquery(clause) => {
+                self.analyze_subquery(clause)?;
+            }
             ValueExpression::Arithmetic { left, right, .. } => {
                 // Validate arithmetic operands recursively
                 self.analyze_value_expression(left)?;
@@ -338,6 +823,78 @@ impl SemanticAnalyzer {
         Ok(())
     }
 
+    /// Validate the arguments of `date()`, `localdatetime()`, `datetime()` and
+    /// `duration()`: at most one value, whose map form only names known components
+    fn validate_temporal_function(&self, name: &str, args: &[ValueExpression]) -> Result<()> {
+        use crate::datafusion_planner::temporal;
+        let is_duration = name.eq_ignore_ascii_case("duration");
+        let arity_ok = if is_duration {
+            args.len() == 1
+        } else {
+            args.len() <= 1
+        };
+        if !arity_ok {
+            return Err(GraphError::PlanError {
+                message: format!(
+                    "{} requires {} argument, got {}",
+                    name.to_uppercase(),
+                    if is_duration {
+                        "exactly 1"
+                    } else {
+                        "at most 1"
+                    },
+                    args.len()
+                ),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            });
+        }
+        let invalid = |message: String| {
+            Err(GraphError::PlanError {
+                message,
+                location: snafu::Location::new(file!(), line!(), column!()),
+            })
+        };
+        match args.first() {
+            Some(ValueExpression::Map(entries)) => {
+                let components = match name.to_lowercase().as_str() {
+                    "date" => temporal::DATE_COMPONENTS,
+                    "duration" => temporal::DURATION_COMPONENTS,
+                    _ => temporal::DATETIME_COMPONENTS,
+                };
+                for (key, value) in entries {
+                    if !components.contains(&key.to_lowercase().as_str()) {
+                        return invalid(format!(
+                            "{} does not accept the component '{}'",
+                            name.to_uppercase(),
+                            key
+                        ));
+                    }
+                    if is_duration
+                        && !matches!(value, ValueExpression::Literal(PropertyValue::Integer(_)))
+                    {
+                        return invalid(format!(
+                            "DURATION component '{}' must be an integer literal",
+                            key
+                        ));
+                    }
+                }
+                if !is_duration && !entries.iter().any(|(k, _)| k.eq_ignore_ascii_case("year")) {
+                    return invalid(format!(
+                        "{} requires a 'year' component",
+                        name.to_uppercase()
+                    ));
+                }
+            }
+            Some(ValueExpression::Literal(PropertyValue::String(text)))
+                if is_duration && temporal::parse_duration(text).is_none() =>
+            {
+                return invalid(format!("Invalid ISO 8601 duration '{}'", text));
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
     /// Validate property reference
     fn validate_property_reference(&self, prop_ref: &PropertyRef) -> Result<()> {
         if !self.variables.contains_key(&prop_ref.variable) {
@@ -367,7 +924,7 @@ impl SemanticAnalyzer {
 
     /// Validate schema references against configuration
     fn validate_schema(&self, warnings: &mut Vec<String>) {
-        for var_info in self.variables.values() {
+        for var_info in self.variables.values().chain(&self.retired) {
             match var_info.variable_type {
                 VariableType::Node => {
                     for label in &var_info.labels {
@@ -399,24 +956,24 @@ impl SemanticAnalyzer {
         // - Check that arithmetic operations are valid
 
         // Check that properties referenced in patterns exist in schema when property fields are defined
-        for var_info in self.variables.values() {
+        for var_info in self.variables.values().chain(&self.retired) {
             match var_info.variable_type {
                 VariableType::Node => {
                     // Collect property_fields from all known label mappings that specify properties
-                    let mut label_property_sets: Vec<&[String]> = Vec::new();
+                    let mut label_mappings = Vec::new();
                     for label in &var_info.labels {
                         if let Some(mapping) = self.config.get_node_mapping(label) {
                             if !mapping.property_fields.is_empty() {
-                                label_property_sets.push(&mapping.property_fields);
+                                label_mappings.push(mapping);
                             }
                         }
                     }
 
-                    if !label_property_sets.is_empty() {
+                    if !label_mappings.is_empty() {
                         'prop: for prop in &var_info.properties {
-                            // Property is valid if present in at least one label's property_fields
-                            for fields in &label_property_sets {
-                                if fields.iter().any(|f| f == prop) {
+                            // Property is valid if declared by at least one label's mapping
+                            for mapping in &label_mappings {
+                                if mapping.has_property(prop) {
                                     continue 'prop;
                                 }
                             }
@@ -429,19 +986,19 @@ impl SemanticAnalyzer {
                 }
                 VariableType::Relationship => {
                     // Collect property_fields from all known relationship mappings that specify properties
-                    let mut rel_property_sets: Vec<&[String]> = Vec::new();
+                    let mut rel_mappings = Vec::new();
                     for rel_type in &var_info.labels {
                         if let Some(mapping) = self.config.get_relationship_mapping(rel_type) {
                             if !mapping.property_fields.is_empty() {
-                                rel_property_sets.push(&mapping.property_fields);
+                                rel_mappings.push(mapping);
                             }
                         }
                     }
 
-                    if !rel_property_sets.is_empty() {
+                    if !rel_mappings.is_empty() {
                         'prop_rel: for prop in &var_info.properties {
-                            for fields in &rel_property_sets {
-                                if fields.iter().any(|f| f == prop) {
+                            for mapping in &rel_mappings {
+                                if mapping.has_property(prop) {
                                     continue 'prop_rel;
                                 }
                             }
@@ -497,8 +1054,15 @@ mod tests {
     // Helper: analyze a query that only has a single RETURN expression
     fn analyze_return_expr(expr: ValueExpression) -> Result<SemanticResult> {
         let query = CypherQuery {
+            graph: None,
+            explain: None,
             match_clauses: vec![],
             where_clause: None,
+            with_clauses: vec![],
+            write_clauses: vec![],
+            unions: vec![],
+            unwind_clauses: vec![],
+            call_clauses: vec![],
             return_clause: ReturnClause {
                 distinct: false,
                 items: vec![ReturnItem {
@@ -522,10 +1086,19 @@ mod tests {
     ) -> Result<SemanticResult> {
         let node = NodePattern::new(Some(var.to_string())).with_label(label);
         let query = CypherQuery {
+            graph: None,
+            explain: None,
             match_clauses: vec![MatchClause {
                 patterns: vec![GraphPattern::Node(node)],
+                optional: false,
+                where_clause: None,
             }],
             where_clause: None,
+            with_clauses: vec![],
+            write_clauses: vec![],
+            unions: vec![],
+            unwind_clauses: vec![],
+            call_clauses: vec![],
             return_clause: ReturnClause {
                 distinct: false,
                 items: vec![ReturnItem {
@@ -552,10 +1125,19 @@ mod tests {
             .with_property("dept", PropertyValue::String("X".to_string()));
 
         let query = CypherQuery {
+            graph: None,
+            explain: None,
             match_clauses: vec![MatchClause {
                 patterns: vec![GraphPattern::Node(node1), GraphPattern::Node(node2)],
+                optional: false,
+                where_clause: None,
             }],
             where_clause: None,
+            with_clauses: vec![],
+            write_clauses: vec![],
+            unions: vec![],
+            unwind_clauses: vec![],
+            call_clauses: vec![],
             return_clause: ReturnClause {
                 distinct: false,
                 items: vec![],
@@ -577,6 +1159,41 @@ mod tests {
         assert!(n.properties.contains("dept"));
     }
 
+    #[test]
+    fn test_with_clause_replaces_scope() {
+        let analyze = |q: &str| {
+            let query = crate::parser::parse_cypher_query(q).unwrap();
+            SemanticAnalyzer::new(test_config())
+                .analyze(&query)
+                .unwrap()
+        };
+
+        let result = analyze(
+            "MATCH (a:Person)-[:KNOWS]->(b:Person) WITH b AS friend, count(a) AS n \
+             RETURN friend.name, n",
+        );
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+        assert_eq!(
+            result.variables.get("friend").unwrap().variable_type,
+            VariableType::Node
+        );
+        assert_eq!(
+            result.variables.get("n").unwrap().variable_type,
+            VariableType::Property
+        );
+
+        // Bindings not carried by the WITH are out of scope
+        let result = analyze("MATCH (a:Person)-[:KNOWS]->(b:Person) WITH b RETURN a.name");
+        assert!(result
+            .errors
+            .iter()
+            .any(|e| e.contains("Undefined variable: 'a'")));
+
+        // Expressions other than bare variables need an alias
+        let result = analyze("MATCH (a:Person) WITH a.name RETURN a");
+        assert!(result.errors.iter().any(|e| e.contains("must be aliased")));
+    }
+
     #[test]
     fn test_invalid_length_range_collects_error() {
         let start = NodePattern::new(Some("a".to_string())).with_label("Person");
@@ -595,13 +1212,24 @@ mod tests {
                 relationship: rel,
                 end_node: end,
             }],
+            variable: None,
+            shortest: None,
         };
 
         let query = CypherQuery {
+            graph: None,
+            explain: None,
             match_clauses: vec![MatchClause {
                 patterns: vec![GraphPattern::Path(path)],
+                optional: false,
+                where_clause: None,
             }],
             where_clause: None,
+            with_clauses: vec![],
+            write_clauses: vec![],
+            unions: vec![],
+            unwind_clauses: vec![],
+            call_clauses: vec![],
             return_clause: ReturnClause {
                 distinct: false,
                 items: vec![],
@@ -627,10 +1255,19 @@ mod tests {
             expression: BooleanExpression::Exists(PropertyRef::new("m", "name")),
         };
         let query = CypherQuery {
+            graph: None,
+            explain: None,
             match_clauses: vec![MatchClause {
                 patterns: vec![GraphPattern::Node(node)],
+                optional: false,
+                where_clause: None,
             }],
             where_clause: Some(where_clause),
+            with_clauses: vec![],
+            write_clauses: vec![],
+            unions: vec![],
+            unwind_clauses: vec![],
+            call_clauses: vec![],
             return_clause: ReturnClause {
                 distinct: false,
                 items: vec![],
@@ -663,13 +1300,24 @@ mod tests {
                 relationship: rel,
                 end_node: end,
             }],
+            variable: None,
+            shortest: None,
         };
 
         let query = CypherQuery {
+            graph: None,
+            explain: None,
             match_clauses: vec![MatchClause {
                 patterns: vec![GraphPattern::Path(path)],
+                optional: false,
+                where_clause: None,
             }],
             where_clause: None,
+            with_clauses: vec![],
+            write_clauses: vec![],
+            unions: vec![],
+            unwind_clauses: vec![],
+            call_clauses: vec![],
             return_clause: ReturnClause {
                 distinct: false,
                 items: vec![],
@@ -692,10 +1340,19 @@ mod tests {
         // MATCH (x:Unknown)
         let node = NodePattern::new(Some("x".to_string())).with_label("Unknown");
         let query = CypherQuery {
+            graph: None,
+            explain: None,
             match_clauses: vec![MatchClause {
                 patterns: vec![GraphPattern::Node(node)],
+                optional: false,
+                where_clause: None,
             }],
             where_clause: None,
+            with_clauses: vec![],
+            write_clauses: vec![],
+            unions: vec![],
+            unwind_clauses: vec![],
+            call_clauses: vec![],
             return_clause: ReturnClause {
                 distinct: false,
                 items: vec![],
@@ -729,10 +1386,19 @@ mod tests {
             .with_label("Person")
             .with_property("age", PropertyValue::Integer(30));
         let query = CypherQuery {
+            graph: None,
+            explain: None,
             match_clauses: vec![MatchClause {
                 patterns: vec![GraphPattern::Node(node)],
+                optional: false,
+                where_clause: None,
             }],
             where_clause: None,
+            with_clauses: vec![],
+            write_clauses: vec![],
+            unions: vec![],
+            unwind_clauses: vec![],
+            call_clauses: vec![],
             return_clause: ReturnClause {
                 distinct: false,
                 items: vec![],
@@ -768,13 +1434,24 @@ mod tests {
                 relationship: rel,
                 end_node: end,
             }],
+            variable: None,
+            shortest: None,
         };
 
         let query = CypherQuery {
+            graph: None,
+            explain: None,
             match_clauses: vec![MatchClause {
                 patterns: vec![GraphPattern::Path(path)],
+                optional: false,
+                where_clause: None,
             }],
             where_clause: None,
+            with_clauses: vec![],
+            write_clauses: vec![],
+            unions: vec![],
+            unwind_clauses: vec![],
+            call_clauses: vec![],
             return_clause: ReturnClause {
                 distinct: false,
                 items: vec![],
@@ -824,6 +1501,8 @@ mod tests {
                     end_node: end,
                 },
             ],
+            variable: None,
+            shortest: None,
         };
 
         // Custom config that knows both relationship types to avoid warnings muddying the assertion
@@ -835,10 +1514,19 @@ mod tests {
             .unwrap();
 
         let query = CypherQuery {
+            graph: None,
+            explain: None,
             match_clauses: vec![MatchClause {
                 patterns: vec![GraphPattern::Path(path)],
+                optional: false,
+                where_clause: None,
             }],
             where_clause: None,
+            with_clauses: vec![],
+            write_clauses: vec![],
+            unions: vec![],
+            unwind_clauses: vec![],
+            call_clauses: vec![],
             return_clause: ReturnClause {
                 distinct: false,
                 items: vec![],
@@ -864,6 +1552,7 @@ mod tests {
         // RETURN toUpper(m.name)
         let expr = ValueExpression::Function {
             name: "toUpper".to_string(),
+            distinct: false,
             args: vec![ValueExpression::Property(PropertyRef::new("m", "name"))],
         };
         let result = analyze_return_expr(expr).unwrap();
@@ -878,6 +1567,7 @@ mod tests {
         // MATCH (n:Person) RETURN toUpper(n.name)
         let expr = ValueExpression::Function {
             name: "toUpper".to_string(),
+            distinct: false,
             args: vec![ValueExpression::Property(PropertyRef::new("n", "name"))],
         };
         let result = analyze_return_with_match("n", "Person", expr).unwrap();
@@ -914,11 +1604,71 @@ mod tests {
             .all(|e| !e.contains("Undefined variable: 'n'")));
     }
 
+    #[test]
+    fn test_aggregate_placement_rules() {
+        let errors = |q: &str| {
+            let query = crate::parser::parse_cypher_query(q).unwrap();
+            SemanticAnalyzer::new(test_config())
+                .analyze(&query)
+                .unwrap()
+                .errors
+        };
+
+        let result = errors("MATCH (n:Person) RETURN n.name, count(DISTINCT n.age)");
+        assert!(result.is_empty(), "{:?}", result);
+
+        let result = errors("MATCH (n:Person) RETURN toUpper(DISTINCT n.name)");
+        assert!(
+            result
+                .iter()
+                .any(|e| e.contains("DISTINCT is only allowed")),
+            "{:?}",
+            result
+        );
+
+        let result = errors("MATCH (n:Person) RETURN sum(count(n.age))");
+        assert!(
+            result.iter().any(|e| e.contains("cannot be nested")),
+            "{:?}",
+            result
+        );
+
+        let result = errors("MATCH (n:Person) WHERE count(n.age) > 1 RETURN n.name");
+        assert!(
+            result.iter().any(|e| e.contains("not allowed in WHERE")),
+            "{:?}",
+            result
+        );
+    }
+
+    #[test]
+    fn test_list_comprehension_variable_scope() {
+        let errors = |q: &str| {
+            let query = crate::parser::parse_cypher_query(q).unwrap();
+            SemanticAnalyzer::new(test_config())
+                .analyze(&query)
+                .unwrap()
+                .errors
+        };
+
+        let result = errors("MATCH (n:Person) RETURN [x IN [1, 2] WHERE x > n.age | x] AS l");
+        assert!(result.is_empty(), "{:?}", result);
+
+        // The element variable is only bound inside the comprehension
+        let result = errors("MATCH (n:Person) RETURN [x IN [1, 2] | x] AS l, x");
+        assert!(
+            result.iter().any(|e| e.contains("Undefined variable: 'x'")),
+            "{:?}",
+            result
+        );
+    }
+
     #[test]
     fn test_count_with_multiple_args_fails_validation() {
         // COUNT(n.age, n.name) should fail semantic validation
         let expr = ValueExpression::Function {
             name: "count".to_string(),
+            distinct: false,
             args: vec![
                 ValueExpression::Property(PropertyRef::new("n", "age")),
                 ValueExpression::Property(PropertyRef::new("n", "name")),
@@ -940,6 +1690,7 @@ mod tests {
         // COUNT() with no arguments should fail
         let expr = ValueExpression::Function {
             name: "count".to_string(),
+            distinct: false,
             args: vec![],
         };
         let result = analyze_return_with_match("n", "Person", expr).unwrap();
@@ -958,6 +1709,7 @@ mod tests {
         // COUNT(n.age) should pass validation
         let expr = ValueExpression::Function {
             name: "count".to_string(),
+            distinct: false,
             args: vec![ValueExpression::Property(PropertyRef::new("n", "age"))],
         };
         let result = analyze_return_with_match("n", "Person", expr).unwrap();
@@ -971,10 +1723,62 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_string_function_arity_validation() {
+        let expr = ValueExpression::Function {
+            name: "replace".to_string(),
+            distinct: false,
+            args: vec![
+                ValueExpression::Property(PropertyRef::new("n", "name")),
+                ValueExpression::Literal(PropertyValue::String("a".to_string())),
+            ],
+        };
+        let result = analyze_return_with_match("n", "Person", expr).unwrap();
+        assert!(
+            result
+                .errors
+                .iter()
+                .any(|e| e.contains("REPLACE requires exactly 3 arguments, got 2")),
+            "Expected error about REPLACE arity, got: {:?}",
+            result.errors
+        );
+
+        let expr = ValueExpression::Function {
+            name: "substring".to_string(),
+            distinct: false,
+            args: vec![
+                ValueExpression::Property(PropertyRef::new("n", "name")),
+                ValueExpression::Literal(PropertyValue::Integer(1)),
+            ],
+        };
+        let result = analyze_return_with_match("n", "Person", expr).unwrap();
+        assert!(
+            result.errors.iter().all(|e| !e.contains("SUBSTRING")),
+            "SUBSTRING with 2 args should not produce arity error, got: {:?}",
+            result.errors
+        );
+
+        let expr = ValueExpression::Function {
+            name: "coalesce".to_string(),
+            distinct: false,
+            args: vec![],
+        };
+        let result = analyze_return_with_match("n", "Person", expr).unwrap();
+        assert!(
+            result
+                .errors
+                .iter()
+                .any(|e| e.contains("COALESCE requires at least 1 argument")),
+            "Expected error about COALESCE arity, got: {:?}",
+            result.errors
+        );
+    }
+
     #[test]
     fn test_sum_with_variable_fails_validation() {
         let expr = ValueExpression::Function {
             name: "sum".to_string(),
+            distinct: false,
             args: vec![ValueExpression::Variable("n".to_string())],
         };
         let result = analyze_return_with_match("n", "Person", expr).unwrap();
@@ -997,6 +1801,7 @@ mod tests {
     fn test_avg_with_variable_fails_validation() {
         let expr = ValueExpression::Function {
             name: "avg".to_string(),
+            distinct: false,
             args: vec![ValueExpression::Variable("n".to_string())],
         };
         let result = analyze_return_with_match("n", "Person", expr).unwrap();
@@ -1019,6 +1824,7 @@ mod tests {
     fn test_sum_with_property_passes_validation() {
         let expr = ValueExpression::Function {
             name: "sum".to_string(),
+            distinct: false,
             args: vec![ValueExpression::Property(PropertyRef::new("n", "age"))],
         };
         let result = analyze_return_with_match("n", "Person", expr).unwrap();
@@ -1033,6 +1839,7 @@ mod tests {
     fn test_min_with_variable_fails_validation() {
         let expr = ValueExpression::Function {
             name: "min".to_string(),
+            distinct: false,
             args: vec![ValueExpression::Variable("n".to_string())],
         };
         let result = analyze_return_with_match("n", "Person", expr).unwrap();
@@ -1055,6 +1862,7 @@ mod tests {
     fn test_max_with_variable_fails_validation() {
         let expr = ValueExpression::Function {
             name: "max".to_string(),
+            distinct: false,
             args: vec![ValueExpression::Variable("n".to_string())],
         };
         let result = analyze_return_with_match("n", "Person", expr).unwrap();
@@ -1077,6 +1885,7 @@ mod tests {
     fn test_min_with_property_passes_validation() {
         let expr = ValueExpression::Function {
             name: "min".to_string(),
+            distinct: false,
             args: vec![ValueExpression::Property(PropertyRef::new("n", "age"))],
         };
         let result = analyze_return_with_match("n", "Person", expr).unwrap();
@@ -1091,6 +1900,7 @@ mod tests {
     fn test_max_with_property_passes_validation() {
         let expr = ValueExpression::Function {
             name: "max".to_string(),
+            distinct: false,
             args: vec![ValueExpression::Property(PropertyRef::new("n", "age"))],
         };
         let result = analyze_return_with_match("n", "Person", expr).unwrap();
@@ -8,17 +8,15 @@ pub(super) fn apply_where_with_qualifier(
     ast: &crate::ast::CypherQuery,
     qualify: &dyn Fn(&str, &str) -> String,
 ) -> Result<datafusion::dataframe::DataFrame> {
-    use super::expr::to_df_boolean_expr_with_vars;
+    use super::expr::{to_df_boolean_expr_with_vars, unsupported_predicate};
     use crate::error::GraphError;
     if let Some(where_clause) = &ast.where_clause {
-        if let Some(expr) =
-            to_df_boolean_expr_with_vars(&where_clause.expression, &|v, p| qualify(v, p))
-        {
-            df = df.filter(expr).map_err(|e| GraphError::PlanError {
-                message: format!("Failed to apply WHERE: {}", e),
-                location: snafu::Location::new(file!(), line!(), column!()),
-            })?;
-        }
+        let expr = to_df_boolean_expr_with_vars(&where_clause.expression, &|v, p| qualify(v, p))
+            .ok_or_else(unsupported_predicate)?;
+        df = df.filter(expr).map_err(|e| GraphError::PlanError {
+            message: format!("Failed to apply WHERE: {}", e),
+            location: snafu::Location::new(file!(), line!(), column!()),
+        })?;
     }
     Ok(df)
 }
@@ -65,7 +63,7 @@ pub(super) fn apply_return_with_qualifier(
                 sorts.push(SortExpr {
                     expr: col,
                     asc,
-                    nulls_first: false,
+                    nulls_first: !asc,
                 });
             }
         }
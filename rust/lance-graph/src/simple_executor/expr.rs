@@ -59,6 +59,24 @@ where
         BE::Not(inner) => Some(datafusion::logical_expr::Expr::Not(Box::new(
             to_df_boolean_expr_with_vars(inner, qualify)?,
         ))),
+        BE::Exists(p) => Some(col(qualify(&p.variable, &p.property)).is_not_null()),
+        BE::IsNull(VE::Property(p)) => Some(col(qualify(&p.variable, &p.property)).is_null()),
+        BE::IsNotNull(VE::Property(p)) => {
+            Some(col(qualify(&p.variable, &p.property)).is_not_null())
+        }
+        BE::In {
+            expression: VE::Property(p),
+            list,
+        } => {
+            let list = list
+                .iter()
+                .map(|item| match item {
+                    VE::Literal(val) => Some(to_df_literal(val)),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>()?;
+            Some(col(qualify(&p.variable, &p.property)).in_list(list, false))
+        }
         _ => None,
     }
 }
@@ -75,6 +93,12 @@ pub(super) fn to_df_literal(val: &crate::ast::PropertyValue) -> datafusion::logi
         }
         crate::ast::PropertyValue::Parameter(_) => lit(0),
         crate::ast::PropertyValue::Property(prop) => datafusion::logical_expr::col(&prop.property),
+        crate::ast::PropertyValue::Variable(v) => datafusion::logical_expr::col(v),
+        crate::ast::PropertyValue::List(items) => {
+            datafusion::functions_nested::expr_fn::make_array(
+                items.iter().map(to_df_literal).collect(),
+            )
+        }
     }
 }
 
@@ -82,60 +106,17 @@ pub(super) fn to_df_literal(val: &crate::ast::PropertyValue) -> datafusion::logi
 pub(crate) fn to_df_boolean_expr_simple(
     expr: &crate::ast::BooleanExpression,
 ) -> Option<datafusion::logical_expr::Expr> {
-    use crate::ast::{BooleanExpression as BE, ComparisonOperator as CO, ValueExpression as VE};
-    use datafusion::logical_expr::{col, Expr, Operator};
-    match expr {
-        BE::Comparison {
-            left,
-            operator,
-            right,
-        } => {
-            let (col_name, lit_expr) = match (left, right) {
-                (VE::Property(prop), VE::Literal(val)) => {
-                    (prop.property.clone(), to_df_literal(val))
-                }
-                (VE::Literal(val), VE::Property(prop)) => {
-                    (prop.property.clone(), to_df_literal(val))
-                }
-                _ => return None,
-            };
-            let op = match operator {
-                CO::Equal => Operator::Eq,
-                CO::NotEqual => Operator::NotEq,
-                CO::LessThan => Operator::Lt,
-                CO::LessThanOrEqual => Operator::LtEq,
-                CO::GreaterThan => Operator::Gt,
-                CO::GreaterThanOrEqual => Operator::GtEq,
-            };
-            Some(Expr::BinaryExpr(datafusion::logical_expr::BinaryExpr {
-                left: Box::new(col(col_name)),
-                op,
-                right: Box::new(lit_expr),
-            }))
-        }
-        BE::And(l, r) => Some(datafusion::logical_expr::Expr::BinaryExpr(
-            datafusion::logical_expr::BinaryExpr {
-                left: Box::new(to_df_boolean_expr_simple(l)?),
-                op: Operator::And,
-                right: Box::new(to_df_boolean_expr_simple(r)?),
-            },
-        )),
-        BE::Or(l, r) => Some(datafusion::logical_expr::Expr::BinaryExpr(
-            datafusion::logical_expr::BinaryExpr {
-                left: Box::new(to_df_boolean_expr_simple(l)?),
-                op: Operator::Or,
-                right: Box::new(to_df_boolean_expr_simple(r)?),
-            },
-        )),
-        BE::Not(inner) => Some(datafusion::logical_expr::Expr::Not(Box::new(
-            to_df_boolean_expr_simple(inner)?,
-        ))),
-        BE::Exists(prop) => Some(datafusion::logical_expr::Expr::IsNotNull(Box::new(
-            datafusion::logical_expr::Expr::Column(datafusion::common::Column::from_name(
-                prop.property.clone(),
-            )),
-        ))),
-        _ => None,
+    to_df_boolean_expr_with_vars(expr, &|_, property| property.to_string())
+}
+
+/// Error for a WHERE predicate the simple translators cannot express; dropping it would
+/// return rows the query excludes
+pub(crate) fn unsupported_predicate() -> crate::error::GraphError {
+    crate::error::GraphError::UnsupportedFeature {
+        feature: "WHERE predicates other than comparisons, IN and null tests of properties \
+                  without the DataFusion execution strategy"
+            .to_string(),
+        location: snafu::Location::new(file!(), line!(), column!()),
     }
 }
 
@@ -149,10 +130,11 @@ pub(crate) fn to_df_order_by_expr_simple(
         .map(|item| {
             let expr = to_df_value_expr_simple(&item.expression);
             let asc = matches!(item.direction, crate::ast::SortDirection::Ascending);
+            // Nulls sort last ascending and first descending, as in Cypher
             SortExpr {
                 expr,
                 asc,
-                nulls_first: false,
+                nulls_first: !asc,
             }
         })
         .collect()
@@ -168,6 +150,14 @@ pub(crate) fn to_df_value_expr_simple(
         VE::Property(prop) => col(&prop.property),
         VE::Variable(v) => col(v),
         VE::Literal(v) => to_df_literal(v),
-        VE::Function { .. } | VE::Arithmetic { .. } => lit(0),
+        VE::Function { .. }
+        | VE::Case { .. }
+        | VE::List(_)
+        | VE::Index { .. }
+        | VE::Slice { .. }
+        | VE::ListComprehension { .. }
+        | VE::CountSubquery(_)
+        | VE::Arithmetic { .. }
+        | VE::Map(_) => lit(0),
     }
 }
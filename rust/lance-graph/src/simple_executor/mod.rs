@@ -16,5 +16,6 @@ mod path_executor;
 
 pub(crate) use expr::{
     to_df_boolean_expr_simple, to_df_order_by_expr_simple, to_df_value_expr_simple,
+    unsupported_predicate,
 };
 pub(crate) use path_executor::PathExecutor;
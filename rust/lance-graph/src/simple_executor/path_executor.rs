@@ -208,23 +208,19 @@ impl<'a> PathExecutor<'a> {
             let rel_df = self.open_aliased(s.rel_type, &s.rel_alias).await?;
             let node_map = self.node_maps.get(current_node_alias).unwrap();
             let rel_map = self.rel_maps.get(&s.rel_alias).unwrap();
-            let (left_key, right_key) = match s.dir {
+            let rel_source_fields = match s.dir {
                 crate::ast::RelationshipDirection::Outgoing
-                | crate::ast::RelationshipDirection::Undirected => (
-                    format!("{}__{}", current_node_alias, node_map.id_field),
-                    format!("{}__{}", s.rel_alias, rel_map.source_id_field),
-                ),
-                crate::ast::RelationshipDirection::Incoming => (
-                    format!("{}__{}", current_node_alias, node_map.id_field),
-                    format!("{}__{}", s.rel_alias, rel_map.target_id_field),
-                ),
+                | crate::ast::RelationshipDirection::Undirected => rel_map.source_id_fields(),
+                crate::ast::RelationshipDirection::Incoming => rel_map.target_id_fields(),
             };
+            let left_keys = qualified_keys(current_node_alias, &node_map.id_fields());
+            let right_keys = qualified_keys(&s.rel_alias, &rel_source_fields);
             df = df
                 .join(
                     rel_df,
                     JoinType::Inner,
-                    &[left_key.as_str()],
-                    &[right_key.as_str()],
+                    &as_strs(&left_keys),
+                    &as_strs(&right_keys),
                     None,
                 )
                 .map_err(|e| GraphError::PlanError {
@@ -233,31 +229,20 @@ impl<'a> PathExecutor<'a> {
                 })?;
 
             let end_df = self.open_aliased(s.end_label, &s.end_alias).await?;
-            let (left_key2, right_key2) = match s.dir {
+            let rel_target_fields = match s.dir {
                 crate::ast::RelationshipDirection::Outgoing
-                | crate::ast::RelationshipDirection::Undirected => (
-                    format!("{}__{}", s.rel_alias, rel_map.target_id_field),
-                    format!(
-                        "{}__{}",
-                        s.end_alias,
-                        self.node_maps.get(&s.end_alias).unwrap().id_field
-                    ),
-                ),
-                crate::ast::RelationshipDirection::Incoming => (
-                    format!("{}__{}", s.rel_alias, rel_map.source_id_field),
-                    format!(
-                        "{}__{}",
-                        s.end_alias,
-                        self.node_maps.get(&s.end_alias).unwrap().id_field
-                    ),
-                ),
+                | crate::ast::RelationshipDirection::Undirected => rel_map.target_id_fields(),
+                crate::ast::RelationshipDirection::Incoming => rel_map.source_id_fields(),
             };
+            let end_map = self.node_maps.get(&s.end_alias).unwrap();
+            let left_keys2 = qualified_keys(&s.rel_alias, &rel_target_fields);
+            let right_keys2 = qualified_keys(&s.end_alias, &end_map.id_fields());
             df = df
                 .join(
                     end_df,
                     JoinType::Inner,
-                    &[left_key2.as_str()],
-                    &[right_key2.as_str()],
+                    &as_strs(&left_keys2),
+                    &as_strs(&right_keys2),
                     None,
                 )
                 .map_err(|e| GraphError::PlanError {
@@ -307,3 +292,14 @@ impl<'a> PathExecutor<'a> {
         })
     }
 }
+
+fn qualified_keys(alias: &str, fields: &[&str]) -> Vec<String> {
+    fields
+        .iter()
+        .map(|field| format!("{}__{}", alias, field))
+        .collect()
+}
+
+fn as_strs(keys: &[String]) -> Vec<&str> {
+    keys.iter().map(String::as_str).collect()
+}
@@ -5,21 +5,197 @@
 
 use std::any::Any;
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 
 use arrow_schema::{Schema, SchemaRef};
-use datafusion::logical_expr::TableSource;
+use datafusion::datasource::{provider_as_source, TableProvider};
+use datafusion::logical_expr::{
+    col, Expr, LogicalPlan, LogicalPlanBuilder, TableSource, TableType,
+};
+
+use crate::config::GraphConfig;
+use crate::error::{GraphError, Result};
 
 /// A minimal catalog to resolve node labels and relationship types to logical table sources.
 pub trait GraphSourceCatalog: Send + Sync {
     fn node_source(&self, label: &str) -> Option<Arc<dyn TableSource>>;
     fn relationship_source(&self, rel_type: &str) -> Option<Arc<dyn TableSource>>;
+
+    /// Whether this catalog hosts the named graph. Single-graph catalogs host none.
+    fn has_graph(&self, _graph: &str) -> bool {
+        false
+    }
+
+    /// Resolve a node label within a named graph.
+    fn node_source_in(&self, _graph: &str, _label: &str) -> Option<Arc<dyn TableSource>> {
+        None
+    }
+
+    /// Resolve a relationship type within a named graph.
+    fn relationship_source_in(
+        &self,
+        _graph: &str,
+        _rel_type: &str,
+    ) -> Option<Arc<dyn TableSource>> {
+        None
+    }
+
+    /// Node labels this catalog can resolve, sorted. Empty if the catalog cannot enumerate them.
+    fn list_node_labels(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Relationship types this catalog can resolve, sorted. Empty if the catalog cannot
+    /// enumerate them.
+    fn list_relationship_types(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Describe the source behind a node label or relationship type.
+    ///
+    /// Node labels take precedence when a name is both. Implementations that know the
+    /// graph mappings report key columns; otherwise `key_columns` is empty and can be
+    /// filled in with [`SourceDescription::with_keys_from`].
+    fn describe(&self, name: &str) -> Option<SourceDescription> {
+        describe_sources(self, name)
+    }
+
+    /// Table statistics for a node label or relationship type, if the catalog has any.
+    ///
+    /// The planner uses these to pick the cheaper end of a pattern to start from.
+    fn statistics(&self, _name: &str) -> Option<SourceStatistics> {
+        None
+    }
+
+    /// Materialized adjacency lists of a relationship type, if the catalog has current
+    /// ones: a source node table with its outgoing edges in a
+    /// [`crate::adjacency_index::NEIGHBORS_COLUMN`] list column.
+    ///
+    /// The planner reads these instead of the edge table when present.
+    fn adjacency_source(&self, _rel_type: &str) -> Option<Arc<dyn TableSource>> {
+        None
+    }
+
+    /// The catalog of a named graph, if this catalog hosts it as a catalog of its own.
+    ///
+    /// Scoping a query to the graph uses it for listings, descriptions, statistics and
+    /// adjacency lists; sources are still resolved with [`Self::node_source_in`] and
+    /// [`Self::relationship_source_in`].
+    fn graph_catalog(&self, _graph: &str) -> Option<Arc<dyn GraphSourceCatalog>> {
+        None
+    }
+}
+
+/// Describe `name` from the node or relationship source `catalog` resolves it to.
+fn describe_sources<C: GraphSourceCatalog + ?Sized>(
+    catalog: &C,
+    name: &str,
+) -> Option<SourceDescription> {
+    if let Some(source) = catalog.node_source(name) {
+        return Some(SourceDescription::new(
+            name,
+            SourceKind::Node,
+            source.schema(),
+        ));
+    }
+    catalog
+        .relationship_source(name)
+        .map(|source| SourceDescription::new(name, SourceKind::Relationship, source.schema()))
+}
+
+/// Planner statistics for the table behind a label or relationship type.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SourceStatistics {
+    /// Number of live rows, if known
+    pub row_count: Option<usize>,
+    /// Distinct value counts per column, for the columns where they are known
+    pub distinct_counts: HashMap<String, usize>,
+    /// Fraction of null values per column, for the columns where they are known
+    pub null_fractions: HashMap<String, f64>,
+}
+
+impl SourceStatistics {
+    pub fn with_row_count(row_count: usize) -> Self {
+        Self {
+            row_count: Some(row_count),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_distinct_count(mut self, column: impl Into<String>, count: usize) -> Self {
+        self.distinct_counts.insert(column.into(), count);
+        self
+    }
+
+    pub fn with_null_fraction(mut self, column: impl Into<String>, fraction: f64) -> Self {
+        self.null_fractions.insert(column.into(), fraction);
+        self
+    }
+}
+
+/// Whether a catalog entry backs a node label or a relationship type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SourceKind {
+    Node,
+    Relationship,
+}
+
+/// Introspection result for a single catalog entry.
+#[derive(Debug, Clone)]
+pub struct SourceDescription {
+    /// Node label or relationship type
+    pub name: String,
+    pub kind: SourceKind,
+    /// Schema of the backing table
+    pub schema: SchemaRef,
+    /// Identifier columns: the node id, or the source and target id of a relationship
+    pub key_columns: Vec<String>,
+}
+
+impl SourceDescription {
+    pub fn new(name: impl Into<String>, kind: SourceKind, schema: SchemaRef) -> Self {
+        Self {
+            name: name.into(),
+            kind,
+            schema,
+            key_columns: Vec::new(),
+        }
+    }
+
+    /// Fill in key columns from the matching mapping in `config`, if any.
+    pub fn with_keys_from(mut self, config: &GraphConfig) -> Self {
+        match self.kind {
+            SourceKind::Node => {
+                if let Some(mapping) = config.get_node_mapping(&self.name) {
+                    self.key_columns = mapping.id_fields().into_iter().map(String::from).collect();
+                }
+            }
+            SourceKind::Relationship => {
+                if let Some(mapping) = config.get_relationship_mapping(&self.name) {
+                    self.key_columns = mapping
+                        .source_id_fields()
+                        .into_iter()
+                        .chain(mapping.target_id_fields())
+                        .map(String::from)
+                        .collect();
+                }
+            }
+        }
+        self
+    }
+}
+
+fn sorted_keys<V>(map: &HashMap<String, V>) -> Vec<String> {
+    let mut keys: Vec<String> = map.keys().cloned().collect();
+    keys.sort();
+    keys
 }
 
 /// A simple in-memory catalog useful for tests and bootstrap wiring.
 pub struct InMemoryCatalog {
     node_sources: HashMap<String, Arc<dyn TableSource>>,
     rel_sources: HashMap<String, Arc<dyn TableSource>>,
+    statistics: HashMap<String, SourceStatistics>,
 }
 
 impl InMemoryCatalog {
@@ -27,9 +203,20 @@ impl InMemoryCatalog {
         Self {
             node_sources: HashMap::new(),
             rel_sources: HashMap::new(),
+            statistics: HashMap::new(),
         }
     }
 
+    /// Attach planner statistics to a node label or relationship type.
+    pub fn with_statistics(
+        mut self,
+        name: impl Into<String>,
+        statistics: SourceStatistics,
+    ) -> Self {
+        self.statistics.insert(name.into(), statistics);
+        self
+    }
+
     pub fn with_node_source(
         mut self,
         label: impl Into<String>,
@@ -63,6 +250,397 @@ impl GraphSourceCatalog for InMemoryCatalog {
     fn relationship_source(&self, rel_type: &str) -> Option<Arc<dyn TableSource>> {
         self.rel_sources.get(rel_type).cloned()
     }
+
+    fn list_node_labels(&self) -> Vec<String> {
+        sorted_keys(&self.node_sources)
+    }
+
+    fn list_relationship_types(&self) -> Vec<String> {
+        sorted_keys(&self.rel_sources)
+    }
+
+    fn statistics(&self, name: &str) -> Option<SourceStatistics> {
+        self.statistics.get(name).cloned()
+    }
+}
+
+/// A catalog of DataFusion table providers, so planned queries can also be executed.
+///
+/// Unlike schema-only sources, every entry is scannable (`MemTable`, Lance scans,
+/// listing tables, ...), which lets [`CypherQuery::execute_with_catalog`] run a query
+/// without a separately populated `SessionContext`.
+///
+/// [`CypherQuery::execute_with_catalog`]: crate::query::CypherQuery::execute_with_catalog
+#[derive(Default)]
+pub struct ProviderCatalog {
+    node_tables: HashMap<String, Arc<dyn TableProvider>>,
+    rel_tables: HashMap<String, Arc<dyn TableProvider>>,
+}
+
+impl ProviderCatalog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_node_table(
+        mut self,
+        label: impl Into<String>,
+        table: Arc<dyn TableProvider>,
+    ) -> Self {
+        self.node_tables.insert(label.into(), table);
+        self
+    }
+
+    pub fn with_relationship_table(
+        mut self,
+        rel_type: impl Into<String>,
+        table: Arc<dyn TableProvider>,
+    ) -> Self {
+        self.rel_tables.insert(rel_type.into(), table);
+        self
+    }
+
+    /// The table provider behind a node label.
+    pub fn node_table(&self, label: &str) -> Option<Arc<dyn TableProvider>> {
+        self.node_tables.get(label).cloned()
+    }
+
+    /// The table provider behind a relationship type.
+    pub fn relationship_table(&self, rel_type: &str) -> Option<Arc<dyn TableProvider>> {
+        self.rel_tables.get(rel_type).cloned()
+    }
+}
+
+impl GraphSourceCatalog for ProviderCatalog {
+    fn node_source(&self, label: &str) -> Option<Arc<dyn TableSource>> {
+        self.node_table(label).map(provider_as_source)
+    }
+
+    fn relationship_source(&self, rel_type: &str) -> Option<Arc<dyn TableSource>> {
+        self.relationship_table(rel_type).map(provider_as_source)
+    }
+
+    fn list_node_labels(&self) -> Vec<String> {
+        sorted_keys(&self.node_tables)
+    }
+
+    fn list_relationship_types(&self) -> Vec<String> {
+        sorted_keys(&self.rel_tables)
+    }
+}
+
+/// A catalog whose sources can be registered and removed after construction.
+///
+/// Sources are kept behind a read-write lock, so registration is safe while other
+/// threads are planning queries against the same catalog. A query that has already
+/// resolved a source keeps using it even if it is deregistered afterwards.
+#[derive(Default)]
+pub struct MutableCatalog {
+    node_sources: RwLock<SourceMap>,
+    rel_sources: RwLock<SourceMap>,
+}
+
+impl MutableCatalog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a node source, returning the source it replaced, if any.
+    pub fn register_node_source(
+        &self,
+        label: impl Into<String>,
+        source: Arc<dyn TableSource>,
+    ) -> Option<Arc<dyn TableSource>> {
+        write_sources(&self.node_sources).insert(label.into(), source)
+    }
+
+    /// Register a relationship source, returning the source it replaced, if any.
+    pub fn register_relationship_source(
+        &self,
+        rel_type: impl Into<String>,
+        source: Arc<dyn TableSource>,
+    ) -> Option<Arc<dyn TableSource>> {
+        write_sources(&self.rel_sources).insert(rel_type.into(), source)
+    }
+
+    /// Remove a node source, returning it if it was registered.
+    pub fn deregister_node_source(&self, label: &str) -> Option<Arc<dyn TableSource>> {
+        write_sources(&self.node_sources).remove(label)
+    }
+
+    /// Remove a relationship source, returning it if it was registered.
+    pub fn deregister_relationship_source(&self, rel_type: &str) -> Option<Arc<dyn TableSource>> {
+        write_sources(&self.rel_sources).remove(rel_type)
+    }
+}
+
+impl GraphSourceCatalog for MutableCatalog {
+    fn node_source(&self, label: &str) -> Option<Arc<dyn TableSource>> {
+        read_sources(&self.node_sources).get(label).cloned()
+    }
+
+    fn relationship_source(&self, rel_type: &str) -> Option<Arc<dyn TableSource>> {
+        read_sources(&self.rel_sources).get(rel_type).cloned()
+    }
+
+    fn list_node_labels(&self) -> Vec<String> {
+        sorted_keys(&read_sources(&self.node_sources))
+    }
+
+    fn list_relationship_types(&self) -> Vec<String> {
+        sorted_keys(&read_sources(&self.rel_sources))
+    }
+}
+
+/// A catalog that resolves sources by falling through an ordered list of catalogs.
+///
+/// The first catalog that knows a label or relationship type wins, so placing a small
+/// in-memory catalog in front of a Lance-backed one overlays it.
+#[derive(Default)]
+pub struct ChainedCatalog {
+    catalogs: Vec<Arc<dyn GraphSourceCatalog>>,
+}
+
+impl ChainedCatalog {
+    pub fn new(catalogs: Vec<Arc<dyn GraphSourceCatalog>>) -> Self {
+        Self { catalogs }
+    }
+
+    /// Append a catalog with lower priority than the ones already in the chain.
+    pub fn with_catalog(mut self, catalog: Arc<dyn GraphSourceCatalog>) -> Self {
+        self.catalogs.push(catalog);
+        self
+    }
+
+    /// Catalogs in resolution order.
+    pub fn catalogs(&self) -> &[Arc<dyn GraphSourceCatalog>] {
+        &self.catalogs
+    }
+}
+
+impl GraphSourceCatalog for ChainedCatalog {
+    fn node_source(&self, label: &str) -> Option<Arc<dyn TableSource>> {
+        self.catalogs.iter().find_map(|c| c.node_source(label))
+    }
+
+    fn relationship_source(&self, rel_type: &str) -> Option<Arc<dyn TableSource>> {
+        self.catalogs
+            .iter()
+            .find_map(|c| c.relationship_source(rel_type))
+    }
+
+    fn list_node_labels(&self) -> Vec<String> {
+        let mut labels: Vec<String> = self
+            .catalogs
+            .iter()
+            .flat_map(|c| c.list_node_labels())
+            .collect();
+        labels.sort();
+        labels.dedup();
+        labels
+    }
+
+    fn list_relationship_types(&self) -> Vec<String> {
+        let mut types: Vec<String> = self
+            .catalogs
+            .iter()
+            .flat_map(|c| c.list_relationship_types())
+            .collect();
+        types.sort();
+        types.dedup();
+        types
+    }
+
+    fn describe(&self, name: &str) -> Option<SourceDescription> {
+        self.catalogs.iter().find_map(|c| c.describe(name))
+    }
+
+    fn statistics(&self, name: &str) -> Option<SourceStatistics> {
+        self.catalogs.iter().find_map(|c| c.statistics(name))
+    }
+
+    fn adjacency_source(&self, rel_type: &str) -> Option<Arc<dyn TableSource>> {
+        self.catalogs
+            .iter()
+            .find_map(|c| c.adjacency_source(rel_type))
+    }
+
+    fn has_graph(&self, graph: &str) -> bool {
+        self.catalogs.iter().any(|c| c.has_graph(graph))
+    }
+
+    fn node_source_in(&self, graph: &str, label: &str) -> Option<Arc<dyn TableSource>> {
+        self.catalogs
+            .iter()
+            .find_map(|c| c.node_source_in(graph, label))
+    }
+
+    fn relationship_source_in(&self, graph: &str, rel_type: &str) -> Option<Arc<dyn TableSource>> {
+        self.catalogs
+            .iter()
+            .find_map(|c| c.relationship_source_in(graph, rel_type))
+    }
+
+    fn graph_catalog(&self, graph: &str) -> Option<Arc<dyn GraphSourceCatalog>> {
+        self.catalogs.iter().find_map(|c| c.graph_catalog(graph))
+    }
+}
+
+/// A catalog hosting several independent graphs, each backed by its own catalog.
+///
+/// Labels are resolved per graph, so two graphs may both define `Person` without
+/// colliding. Unqualified lookups go to the default graph, if one is set.
+#[derive(Default)]
+pub struct NamespacedCatalog {
+    graphs: HashMap<String, Arc<dyn GraphSourceCatalog>>,
+    default_graph: Option<String>,
+}
+
+impl NamespacedCatalog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_graph(
+        mut self,
+        graph: impl Into<String>,
+        catalog: Arc<dyn GraphSourceCatalog>,
+    ) -> Self {
+        self.graphs.insert(graph.into(), catalog);
+        self
+    }
+
+    /// Set the graph used by queries without a `USE` clause.
+    pub fn with_default_graph(mut self, graph: impl Into<String>) -> Self {
+        self.default_graph = Some(graph.into());
+        self
+    }
+
+    fn default_catalog(&self) -> Option<&Arc<dyn GraphSourceCatalog>> {
+        self.default_graph.as_ref().and_then(|g| self.graphs.get(g))
+    }
+}
+
+impl GraphSourceCatalog for NamespacedCatalog {
+    fn node_source(&self, label: &str) -> Option<Arc<dyn TableSource>> {
+        self.default_catalog()?.node_source(label)
+    }
+
+    fn relationship_source(&self, rel_type: &str) -> Option<Arc<dyn TableSource>> {
+        self.default_catalog()?.relationship_source(rel_type)
+    }
+
+    fn list_node_labels(&self) -> Vec<String> {
+        self.default_catalog()
+            .map(|c| c.list_node_labels())
+            .unwrap_or_default()
+    }
+
+    fn list_relationship_types(&self) -> Vec<String> {
+        self.default_catalog()
+            .map(|c| c.list_relationship_types())
+            .unwrap_or_default()
+    }
+
+    fn describe(&self, name: &str) -> Option<SourceDescription> {
+        self.default_catalog()?.describe(name)
+    }
+
+    fn statistics(&self, name: &str) -> Option<SourceStatistics> {
+        self.default_catalog()?.statistics(name)
+    }
+
+    fn adjacency_source(&self, rel_type: &str) -> Option<Arc<dyn TableSource>> {
+        self.default_catalog()?.adjacency_source(rel_type)
+    }
+
+    fn has_graph(&self, graph: &str) -> bool {
+        self.graphs.contains_key(graph)
+    }
+
+    fn node_source_in(&self, graph: &str, label: &str) -> Option<Arc<dyn TableSource>> {
+        self.graphs.get(graph)?.node_source(label)
+    }
+
+    fn relationship_source_in(&self, graph: &str, rel_type: &str) -> Option<Arc<dyn TableSource>> {
+        self.graphs.get(graph)?.relationship_source(rel_type)
+    }
+
+    fn graph_catalog(&self, graph: &str) -> Option<Arc<dyn GraphSourceCatalog>> {
+        self.graphs.get(graph).cloned()
+    }
+}
+
+/// A view of one graph of a multi-graph catalog, as selected by `USE <graph>`.
+///
+/// Sources resolve through the multi-graph catalog, so a caching wrapper around it
+/// still applies; everything else comes from the graph's own catalog.
+pub(crate) struct GraphScopedCatalog {
+    inner: Arc<dyn GraphSourceCatalog>,
+    graph: String,
+    catalog: Option<Arc<dyn GraphSourceCatalog>>,
+}
+
+impl GraphScopedCatalog {
+    pub(crate) fn new(inner: Arc<dyn GraphSourceCatalog>, graph: impl Into<String>) -> Self {
+        let graph = graph.into();
+        let catalog = inner.graph_catalog(&graph);
+        Self {
+            inner,
+            graph,
+            catalog,
+        }
+    }
+}
+
+impl GraphSourceCatalog for GraphScopedCatalog {
+    fn node_source(&self, label: &str) -> Option<Arc<dyn TableSource>> {
+        self.inner.node_source_in(&self.graph, label)
+    }
+
+    fn relationship_source(&self, rel_type: &str) -> Option<Arc<dyn TableSource>> {
+        self.inner.relationship_source_in(&self.graph, rel_type)
+    }
+
+    fn list_node_labels(&self) -> Vec<String> {
+        self.catalog
+            .as_ref()
+            .map(|c| c.list_node_labels())
+            .unwrap_or_default()
+    }
+
+    fn list_relationship_types(&self) -> Vec<String> {
+        self.catalog
+            .as_ref()
+            .map(|c| c.list_relationship_types())
+            .unwrap_or_default()
+    }
+
+    fn describe(&self, name: &str) -> Option<SourceDescription> {
+        match &self.catalog {
+            Some(catalog) => catalog.describe(name),
+            None => describe_sources(self, name),
+        }
+    }
+
+    fn statistics(&self, name: &str) -> Option<SourceStatistics> {
+        self.catalog.as_ref()?.statistics(name)
+    }
+
+    fn adjacency_source(&self, rel_type: &str) -> Option<Arc<dyn TableSource>> {
+        self.catalog.as_ref()?.adjacency_source(rel_type)
+    }
+}
+
+type SourceMap = HashMap<String, Arc<dyn TableSource>>;
+
+// A panic while holding the lock cannot leave a map half-updated, so recover from poisoning.
+fn read_sources(lock: &RwLock<SourceMap>) -> std::sync::RwLockReadGuard<'_, SourceMap> {
+    lock.read().unwrap_or_else(|e| e.into_inner())
+}
+
+fn write_sources(lock: &RwLock<SourceMap>) -> std::sync::RwLockWriteGuard<'_, SourceMap> {
+    lock.write().unwrap_or_else(|e| e.into_inner())
 }
 
 /// A trivial logical table source with a fixed schema.
@@ -89,3 +667,311 @@ impl TableSource for SimpleTableSource {
         self.schema.clone()
     }
 }
+
+/// A view over another source that applies a fixed filter and column projection.
+///
+/// Useful when one wide table stores several entity kinds, e.g. mapping `Person` to
+/// `users WHERE type = 'person'`. The view is expanded into the query plan by
+/// DataFusion's analyzer, so the filter and projection are applied transparently.
+/// When the filter can be written in SQL, `NodeMapping::with_filter` keeps it in the
+/// graph config instead; use a view to hide columns or filter with a built `Expr`.
+pub struct FilteredTableSource {
+    plan: LogicalPlan,
+    schema: SchemaRef,
+}
+
+impl FilteredTableSource {
+    /// Build a view over `inner`, registered under `table_name` in the expanded plan.
+    ///
+    /// The filter is evaluated against the columns of `inner`; `projection` selects the
+    /// columns the view exposes and defaults to all of them.
+    pub fn try_new(
+        table_name: &str,
+        inner: Arc<dyn TableSource>,
+        filter: Option<Expr>,
+        projection: Option<Vec<String>>,
+    ) -> Result<Self> {
+        let plan_error = |e: datafusion_common::DataFusionError| GraphError::PlanError {
+            message: format!("Invalid view over '{}': {}", table_name, e),
+            location: snafu::Location::new(file!(), line!(), column!()),
+        };
+
+        let mut builder = LogicalPlanBuilder::scan(table_name, inner, None).map_err(plan_error)?;
+        if let Some(filter) = filter {
+            builder = builder.filter(filter).map_err(plan_error)?;
+        }
+        if let Some(columns) = projection {
+            builder = builder
+                .project(columns.iter().map(col).collect::<Vec<_>>())
+                .map_err(plan_error)?;
+        }
+        let plan = builder.build().map_err(plan_error)?;
+        let schema = plan.schema().inner().clone();
+        Ok(Self { plan, schema })
+    }
+}
+
+impl TableSource for FilteredTableSource {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::View
+    }
+
+    fn get_logical_plan(&self) -> Option<std::borrow::Cow<'_, LogicalPlan>> {
+        Some(std::borrow::Cow::Borrowed(&self.plan))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_schema::{DataType, Field};
+
+    fn source_with_column(name: &str) -> Arc<dyn TableSource> {
+        Arc::new(SimpleTableSource::new(Arc::new(Schema::new(vec![
+            Field::new(name, DataType::Int64, false),
+        ]))))
+    }
+
+    #[test]
+    fn test_mutable_catalog_register_and_deregister() {
+        let catalog = MutableCatalog::new();
+        assert!(catalog.node_source("Person").is_none());
+
+        assert!(catalog
+            .register_node_source("Person", source_with_column("id"))
+            .is_none());
+        assert!(catalog
+            .register_relationship_source("KNOWS", source_with_column("src_id"))
+            .is_none());
+        assert!(catalog.node_source("Person").is_some());
+        assert!(catalog.relationship_source("KNOWS").is_some());
+
+        let replaced = catalog.register_node_source("Person", source_with_column("person_id"));
+        assert_eq!(replaced.unwrap().schema().field(0).name(), "id");
+        assert_eq!(
+            catalog
+                .node_source("Person")
+                .unwrap()
+                .schema()
+                .field(0)
+                .name(),
+            "person_id"
+        );
+
+        assert!(catalog.deregister_node_source("Person").is_some());
+        assert!(catalog.deregister_node_source("Person").is_none());
+        assert!(catalog.node_source("Person").is_none());
+        assert!(catalog.deregister_relationship_source("KNOWS").is_some());
+        assert!(catalog.relationship_source("KNOWS").is_none());
+    }
+
+    #[test]
+    fn test_mutable_catalog_concurrent_access() {
+        let catalog = Arc::new(MutableCatalog::new());
+        let handles: Vec<_> = (0..8)
+            .map(|i| {
+                let catalog = catalog.clone();
+                std::thread::spawn(move || {
+                    let label = format!("Label{}", i);
+                    catalog.register_node_source(label.clone(), source_with_column("id"));
+                    assert!(catalog.node_source(&label).is_some());
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        for i in 0..8 {
+            assert!(catalog.node_source(&format!("Label{}", i)).is_some());
+        }
+    }
+
+    #[test]
+    fn test_chained_catalog_falls_through_in_order() {
+        let overlay =
+            InMemoryCatalog::new().with_node_source("Person", source_with_column("test_id"));
+        let base = InMemoryCatalog::new()
+            .with_node_source("Person", source_with_column("id"))
+            .with_node_source("Company", source_with_column("company_id"))
+            .with_relationship_source("WORKS_FOR", source_with_column("src_id"));
+
+        let chained = ChainedCatalog::new(vec![Arc::new(overlay)]).with_catalog(Arc::new(base));
+        assert_eq!(chained.catalogs().len(), 2);
+
+        let person = chained.node_source("Person").unwrap();
+        assert_eq!(person.schema().field(0).name(), "test_id");
+        let company = chained.node_source("Company").unwrap();
+        assert_eq!(company.schema().field(0).name(), "company_id");
+        assert!(chained.relationship_source("WORKS_FOR").is_some());
+        assert!(chained.node_source("Missing").is_none());
+        assert!(ChainedCatalog::default().node_source("Person").is_none());
+    }
+
+    #[test]
+    fn test_namespaced_catalog_isolates_graphs() {
+        let social =
+            InMemoryCatalog::new().with_node_source("Person", source_with_column("user_id"));
+        let hr = InMemoryCatalog::new()
+            .with_node_source("Person", source_with_column("employee_id"))
+            .with_relationship_source("REPORTS_TO", source_with_column("src_id"));
+        let catalog = NamespacedCatalog::new()
+            .with_graph("social", Arc::new(social))
+            .with_graph("hr", Arc::new(hr))
+            .with_default_graph("social");
+
+        assert!(catalog.has_graph("hr"));
+        assert!(!catalog.has_graph("finance"));
+        let social_person = catalog.node_source_in("social", "Person").unwrap();
+        assert_eq!(social_person.schema().field(0).name(), "user_id");
+        let hr_person = catalog.node_source_in("hr", "Person").unwrap();
+        assert_eq!(hr_person.schema().field(0).name(), "employee_id");
+        assert!(catalog
+            .relationship_source_in("social", "REPORTS_TO")
+            .is_none());
+        assert!(catalog.node_source_in("finance", "Person").is_none());
+
+        // Unqualified lookups use the default graph
+        let default_person = catalog.node_source("Person").unwrap();
+        assert_eq!(default_person.schema().field(0).name(), "user_id");
+
+        let scoped = GraphScopedCatalog::new(Arc::new(catalog), "hr");
+        assert!(scoped.relationship_source("REPORTS_TO").is_some());
+    }
+
+    #[test]
+    fn test_chained_catalog_resolves_named_graphs() {
+        let hr = InMemoryCatalog::new()
+            .with_node_source("Person", source_with_column("employee_id"))
+            .with_relationship_source("REPORTS_TO", source_with_column("src_id"));
+        let overlay =
+            InMemoryCatalog::new().with_node_source("Person", source_with_column("test_id"));
+        let namespaced = NamespacedCatalog::new().with_graph("hr", Arc::new(hr));
+        let chained = ChainedCatalog::new(vec![Arc::new(overlay), Arc::new(namespaced)]);
+
+        assert!(chained.has_graph("hr"));
+        assert!(!chained.has_graph("finance"));
+        let person = chained.node_source_in("hr", "Person").unwrap();
+        assert_eq!(person.schema().field(0).name(), "employee_id");
+        assert!(chained.relationship_source_in("hr", "REPORTS_TO").is_some());
+        assert!(chained.node_source_in("finance", "Person").is_none());
+    }
+
+    #[test]
+    fn test_graph_scoped_catalog_forwards_to_the_graph() {
+        let social = InMemoryCatalog::new()
+            .with_node_source("Person", source_with_column("user_id"))
+            .with_relationship_source("KNOWS", source_with_column("src_id"))
+            .with_statistics("Person", SourceStatistics::with_row_count(100));
+        let hr = InMemoryCatalog::new()
+            .with_node_source("Person", source_with_column("employee_id"))
+            .with_relationship_source("REPORTS_TO", source_with_column("src_id"))
+            .with_statistics("Person", SourceStatistics::with_row_count(3));
+        let namespaced = NamespacedCatalog::new()
+            .with_graph("social", Arc::new(social))
+            .with_graph("hr", Arc::new(hr))
+            .with_default_graph("social");
+        let chained = ChainedCatalog::new(vec![Arc::new(namespaced)]);
+
+        let scoped = GraphScopedCatalog::new(Arc::new(chained), "hr");
+        assert_eq!(scoped.list_node_labels(), vec!["Person"]);
+        assert_eq!(scoped.list_relationship_types(), vec!["REPORTS_TO"]);
+        let person = scoped.describe("Person").unwrap();
+        assert_eq!(person.schema.field(0).name(), "employee_id");
+        assert!(scoped.describe("KNOWS").is_none());
+        assert_eq!(scoped.statistics("Person").unwrap().row_count, Some(3));
+
+        // Without a catalog of the graph, descriptions come from its sources
+        struct Sources(NamespacedCatalog);
+        impl GraphSourceCatalog for Sources {
+            fn node_source(&self, _label: &str) -> Option<Arc<dyn TableSource>> {
+                None
+            }
+            fn relationship_source(&self, _rel_type: &str) -> Option<Arc<dyn TableSource>> {
+                None
+            }
+            fn node_source_in(&self, graph: &str, label: &str) -> Option<Arc<dyn TableSource>> {
+                self.0.node_source_in(graph, label)
+            }
+        }
+        let hr =
+            InMemoryCatalog::new().with_node_source("Person", source_with_column("employee_id"));
+        let sources = Sources(NamespacedCatalog::new().with_graph("hr", Arc::new(hr)));
+        let scoped = GraphScopedCatalog::new(Arc::new(sources), "hr");
+        let person = scoped.describe("Person").unwrap();
+        assert_eq!(person.schema.field(0).name(), "employee_id");
+        assert!(scoped.list_node_labels().is_empty());
+    }
+
+    #[test]
+    fn test_catalog_listing_and_describe() {
+        let base = InMemoryCatalog::new()
+            .with_node_source("Person", source_with_column("id"))
+            .with_relationship_source("KNOWS", source_with_column("src_id"));
+        let overlay = MutableCatalog::new();
+        overlay.register_node_source("Company", source_with_column("company_id"));
+        overlay.register_node_source("Person", source_with_column("person_id"));
+        let chained = ChainedCatalog::new(vec![Arc::new(overlay), Arc::new(base)]);
+
+        assert_eq!(chained.list_node_labels(), vec!["Company", "Person"]);
+        assert_eq!(chained.list_relationship_types(), vec!["KNOWS"]);
+
+        let person = chained.describe("Person").unwrap();
+        assert_eq!(person.kind, SourceKind::Node);
+        assert_eq!(person.schema.field(0).name(), "person_id");
+        assert!(person.key_columns.is_empty());
+
+        let config = GraphConfig::builder()
+            .with_node_label("Person", "person_id")
+            .with_relationship("KNOWS", "src_id", "dst_id")
+            .build()
+            .unwrap();
+        let knows = chained.describe("KNOWS").unwrap().with_keys_from(&config);
+        assert_eq!(knows.kind, SourceKind::Relationship);
+        assert_eq!(knows.key_columns, vec!["src_id", "dst_id"]);
+        assert!(chained.describe("Missing").is_none());
+    }
+
+    #[test]
+    fn test_filtered_table_source_projects_schema() {
+        use datafusion::logical_expr::lit;
+
+        let users: Arc<dyn TableSource> =
+            Arc::new(SimpleTableSource::new(Arc::new(Schema::new(vec![
+                Field::new("id", DataType::Int64, false),
+                Field::new("name", DataType::Utf8, false),
+                Field::new("type", DataType::Utf8, false),
+            ]))));
+
+        let view = FilteredTableSource::try_new(
+            "users",
+            users.clone(),
+            Some(col("type").eq(lit("person"))),
+            Some(vec!["id".to_string(), "name".to_string()]),
+        )
+        .unwrap();
+        assert_eq!(view.table_type(), TableType::View);
+        let names: Vec<_> = view
+            .schema()
+            .fields()
+            .iter()
+            .map(|f| f.name().clone())
+            .collect();
+        assert_eq!(names, vec!["id", "name"]);
+        assert!(view.get_logical_plan().is_some());
+
+        let unfiltered = FilteredTableSource::try_new("users", users.clone(), None, None).unwrap();
+        assert_eq!(unfiltered.schema().fields().len(), 3);
+
+        let bad =
+            FilteredTableSource::try_new("users", users, None, Some(vec!["missing".to_string()]));
+        assert!(matches!(bad, Err(GraphError::PlanError { .. })));
+    }
+}
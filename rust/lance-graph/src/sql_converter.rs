@@ -9,7 +9,7 @@
 
 use crate::ast::{
     BooleanExpression, ComparisonOperator, PropertyRef, PropertyValue, RelationshipDirection,
-    ValueExpression,
+    StringOperator, ValueExpression,
 };
 use crate::config::GraphConfig;
 use crate::error::{GraphError, Result};
@@ -68,6 +68,11 @@ impl<'a> LogicalPlanToSqlConverter<'a> {
 
             LogicalOperator::Distinct { input } => self.convert_distinct(input),
 
+            LogicalOperator::DistinctRelationships { .. } => Err(GraphError::PlanError {
+                message: "Relationship uniqueness not supported in SQL conversion".to_string(),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            }),
+
             LogicalOperator::Limit { input, count } => self.convert_limit(input, *count as i64),
 
             LogicalOperator::Offset { input, offset } => self.convert_offset(input, *offset as i64),
@@ -79,6 +84,39 @@ impl<'a> LogicalPlanToSqlConverter<'a> {
                 location: snafu::Location::new(file!(), line!(), column!()),
             }),
 
+            LogicalOperator::Unwind { .. } => Err(GraphError::PlanError {
+                message: "UNWIND clauses not supported in SQL conversion".to_string(),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            }),
+            LogicalOperator::ProcedureCall { .. } => Err(GraphError::PlanError {
+                message: "CALL clauses not supported in SQL conversion".to_string(),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            }),
+            LogicalOperator::With { .. } => Err(GraphError::PlanError {
+                message: "WITH clauses not supported in SQL conversion".to_string(),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            }),
+            LogicalOperator::Union { .. } => Err(GraphError::PlanError {
+                message: "UNION not supported in SQL conversion".to_string(),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            }),
+            LogicalOperator::SemiJoin { .. } => Err(GraphError::PlanError {
+                message: "EXISTS subqueries not supported in SQL conversion".to_string(),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            }),
+            LogicalOperator::PatternCount { .. } => Err(GraphError::PlanError {
+                message: "COUNT subqueries not supported in SQL conversion".to_string(),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            }),
+            LogicalOperator::BindPath { .. } => Err(GraphError::PlanError {
+                message: "Named paths not supported in SQL conversion".to_string(),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            }),
+            LogicalOperator::ShortestPath { .. } => Err(GraphError::PlanError {
+                message: "Shortest paths not supported in SQL conversion".to_string(),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            }),
+
             LogicalOperator::Join { .. } => Err(GraphError::PlanError {
                 message: "Complex joins not supported in SQL conversion".to_string(),
                 location: snafu::Location::new(file!(), line!(), column!()),
@@ -262,6 +300,28 @@ impl<'a> LogicalPlanToSqlConverter<'a> {
                 Ok(format!("{} {} {}", left_sql, op_sql, right_sql))
             }
 
+            BooleanExpression::StringMatch {
+                expression,
+                operator,
+                pattern,
+            } => {
+                let expr_sql = self.value_expr_to_sql(expression)?;
+                let pattern_sql = self.value_expr_to_sql(pattern)?;
+                Ok(match operator {
+                    StringOperator::StartsWith => {
+                        format!("starts_with({}, {})", expr_sql, pattern_sql)
+                    }
+                    StringOperator::EndsWith => format!("ends_with({}, {})", expr_sql, pattern_sql),
+                    StringOperator::Contains => {
+                        format!("strpos({}, {}) > 0", expr_sql, pattern_sql)
+                    }
+                    StringOperator::Regex => format!(
+                        "regexp_like({}, concat('^(?:', {}, ')$'))",
+                        expr_sql, pattern_sql
+                    ),
+                })
+            }
+
             BooleanExpression::In { expression, list } => {
                 let expr_sql = self.value_expr_to_sql(expression)?;
                 let list_sql = list
@@ -331,6 +391,15 @@ impl<'a> LogicalPlanToSqlConverter<'a> {
             PropertyValue::Null => Ok("NULL".to_string()),
             PropertyValue::Parameter(p) => Ok(format!("${}", p)), // Parameter placeholder
             PropertyValue::Property(prop) => self.property_ref_to_sql(prop),
+            PropertyValue::Variable(v) => Ok(v.clone()),
+            PropertyValue::List(items) => Ok(format!(
+                "[{}]",
+                items
+                    .iter()
+                    .map(|item| self.property_value_to_sql(item))
+                    .collect::<Result<Vec<_>>>()?
+                    .join(", ")
+            )),
         }
     }
 }
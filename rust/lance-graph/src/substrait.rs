@@ -0,0 +1,119 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Substrait serialization of planned queries.
+//!
+//! [`to_substrait`] encodes the DataFusion plan of a query as a Substrait `Plan`
+//! message, to run on another DataFusion (or other Substrait-capable) engine or to keep
+//! as a build artifact; [`from_substrait`] decodes one back into a DataFusion plan.
+//! [`CypherQuery::to_substrait`] and [`PreparedQuery::to_substrait`] plan a query and
+//! encode it in one step.
+//!
+//! A Substrait plan names the tables it reads: each label and relationship type of the
+//! query, or the table a relationship mapping names. The context decoding the plan must
+//! have tables registered under those names, as for
+//! [`CypherQuery::execute_with_context`], along with any user-defined functions the
+//! query calls. Plans reading tables computed while planning, such as procedure results
+//! or breadth-first expansions, cannot be encoded.
+//!
+//! This module is available with the `substrait` feature.
+//!
+//! [`PreparedQuery::to_substrait`]: crate::prepared::PreparedQuery::to_substrait
+
+use datafusion::common::tree_node::TreeNodeRecursion;
+use datafusion::datasource::TableType;
+use datafusion::execution::context::SessionContext;
+use datafusion::logical_expr::LogicalPlan;
+use datafusion_substrait::logical_plan::{consumer, producer};
+use datafusion_substrait::substrait::proto::Plan;
+use prost::Message;
+
+use crate::error::{GraphError, Result};
+use crate::query::CypherQuery;
+
+/// Encode a DataFusion plan as a Substrait `Plan` message
+///
+/// The plan is optimized in `ctx` first, which also inlines the views of filtered
+/// mappings so the encoded plan reads only the tables underneath.
+pub fn to_substrait(plan: &LogicalPlan, ctx: &SessionContext) -> Result<Vec<u8>> {
+    let state = ctx.state();
+    let optimized = state.optimize(plan).map_err(|e| GraphError::PlanError {
+        message: format!("Failed to optimize plan: {}", e),
+        location: snafu::Location::new(file!(), line!(), column!()),
+    })?;
+
+    let mut computed = None;
+    optimized
+        .apply_with_subqueries(|node| {
+            if let LogicalPlan::TableScan(scan) = node {
+                if scan.source.table_type() == TableType::Temporary {
+                    computed = Some(scan.table_name.to_string());
+                    return Ok(TreeNodeRecursion::Stop);
+                }
+            }
+            Ok(TreeNodeRecursion::Continue)
+        })
+        .map_err(|e| GraphError::PlanError {
+            message: format!("Failed to inspect plan: {}", e),
+            location: snafu::Location::new(file!(), line!(), column!()),
+        })?;
+    if let Some(table) = computed {
+        return Err(GraphError::UnsupportedFeature {
+            feature: format!(
+                "Substrait plans reading '{}', a table computed while planning",
+                table
+            ),
+            location: snafu::Location::new(file!(), line!(), column!()),
+        });
+    }
+
+    let plan =
+        producer::to_substrait_plan(&optimized, &state).map_err(|e| GraphError::PlanError {
+            message: format!("Failed to encode plan as Substrait: {}", e),
+            location: snafu::Location::new(file!(), line!(), column!()),
+        })?;
+    Ok(plan.encode_to_vec())
+}
+
+/// Decode a Substrait `Plan` message into a DataFusion plan over the tables of `ctx`
+///
+/// # Example
+/// ```ignore
+/// let bytes = query.to_substrait(Arc::new(catalog))?;
+///
+/// // Elsewhere, with the same tables registered
+/// let plan = lance_graph::substrait::from_substrait(&bytes, &ctx).await?;
+/// let batches = ctx.execute_logical_plan(plan).await?.collect().await?;
+/// ```
+pub async fn from_substrait(bytes: &[u8], ctx: &SessionContext) -> Result<LogicalPlan> {
+    let plan = Plan::decode(bytes).map_err(|e| GraphError::PlanError {
+        message: format!("Invalid Substrait plan: {}", e),
+        location: snafu::Location::new(file!(), line!(), column!()),
+    })?;
+    consumer::from_substrait_plan(&ctx.state(), &plan)
+        .await
+        .map_err(|e| GraphError::PlanError {
+            message: format!("Failed to decode Substrait plan: {}", e),
+            location: snafu::Location::new(file!(), line!(), column!()),
+        })
+}
+
+impl CypherQuery {
+    /// Plan the query against `catalog` and encode its DataFusion plan as Substrait
+    ///
+    /// Parameters bound on the query are substituted into the plan. See
+    /// [`to_substrait`] for what the encoded plan reads.
+    pub fn to_substrait(
+        &self,
+        catalog: std::sync::Arc<dyn crate::source_catalog::GraphSourceCatalog>,
+    ) -> Result<Vec<u8>> {
+        if self.ast().explain.is_some() {
+            return Err(GraphError::UnsupportedFeature {
+                feature: "Substrait plans of EXPLAIN and PROFILE statements".to_string(),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            });
+        }
+        let (_, plan) = self.create_logical_plans(catalog)?;
+        to_substrait(&plan, &SessionContext::new())
+    }
+}
@@ -0,0 +1,315 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Nearest neighbour search over node vectors, the `vector.knn` procedure
+//!
+//! Nodes backed by a Lance dataset are searched by the Lance scanner, which uses the
+//! vector index of the property when there is one; other tables are read in full and
+//! searched exhaustively.
+
+use std::sync::Arc;
+
+use arrow::array::{new_empty_array, Array, ArrayRef, AsArray, Float32Array, RecordBatch};
+use arrow::compute::{cast, interleave};
+use arrow::datatypes::Float32Type;
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use async_trait::async_trait;
+use datafusion::datasource::{source_as_provider, TableProvider};
+use datafusion::execution::context::SessionContext;
+use datafusion::scalar::ScalarValue;
+use lance::Dataset;
+use lance_linalg::distance::DistanceType;
+
+use crate::error::{GraphError, Result};
+use crate::lance_table::LanceTable;
+use crate::procedures::{Procedure, ProcedureContext, ProcedureParameter};
+
+/// Column of the distance of each neighbour to the query vector
+const DISTANCE_COLUMN: &str = "distance";
+/// Column of the distance in Lance search results
+const LANCE_DISTANCE_COLUMN: &str = "_distance";
+
+/// `vector.knn(label, property, query, k [, metric])`: the `id` and `distance` of the
+/// `k` nodes nearest to `query`
+#[derive(Debug)]
+pub(crate) struct VectorKnn;
+
+/// Arguments of one call
+struct KnnArguments<'a> {
+    label: &'a str,
+    property: &'a str,
+    query: Float32Array,
+    k: usize,
+    metric: DistanceType,
+}
+
+#[async_trait]
+impl Procedure for VectorKnn {
+    fn name(&self) -> &str {
+        "vector.knn"
+    }
+
+    fn parameters(&self) -> Vec<ProcedureParameter> {
+        vec![
+            ProcedureParameter::new("label", DataType::Utf8),
+            ProcedureParameter::new("property", DataType::Utf8),
+            ProcedureParameter::new("query", DataType::new_list(DataType::Float32, true)),
+            ProcedureParameter::new("k", DataType::Int64),
+            ProcedureParameter::new("metric", DataType::Utf8).with_default(ScalarValue::from("l2")),
+        ]
+    }
+
+    fn output_schema(&self, _ctx: &ProcedureContext) -> Result<SchemaRef> {
+        Err(GraphError::PlanError {
+            message: "vector.knn needs its label to plan its output".to_string(),
+            location: snafu::Location::new(file!(), line!(), column!()),
+        })
+    }
+
+    fn output_schema_for(
+        &self,
+        ctx: &ProcedureContext,
+        args: &[Option<ScalarValue>],
+    ) -> Result<SchemaRef> {
+        let label = match args.first() {
+            Some(Some(ScalarValue::Utf8(Some(label)))) => label,
+            _ => {
+                return Err(GraphError::PlanError {
+                    message: "vector.knn takes its label as a string literal".to_string(),
+                    location: snafu::Location::new(file!(), line!(), column!()),
+                })
+            }
+        };
+        let (key, provider) = node_table(ctx, label)?;
+        let schema = provider.schema();
+        let key_field = schema
+            .field_with_name(&key)
+            .map_err(|_| GraphError::PlanError {
+                message: format!("Node table of '{}' has no id column '{}'", label, key),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            })?;
+        Ok(Arc::new(Schema::new(vec![
+            Field::new("id", key_field.data_type().clone(), key_field.is_nullable()),
+            Field::new(DISTANCE_COLUMN, DataType::Float32, false),
+        ])))
+    }
+
+    async fn call(&self, ctx: &ProcedureContext, args: Vec<ScalarValue>) -> Result<RecordBatch> {
+        let known: Vec<Option<ScalarValue>> = args.iter().cloned().map(Some).collect();
+        let schema = self.output_schema_for(ctx, &known)?;
+        let args = KnnArguments::try_from_scalars(&args)?;
+        let (key, provider) = node_table(ctx, args.label)?;
+
+        let (ids, distances) = match provider.as_any().downcast_ref::<LanceTable>() {
+            Some(lance) => lance_knn(lance.dataset(), &key, &args).await?,
+            None => {
+                let session = ctx.session().cloned().unwrap_or_default();
+                exact_knn(&session, provider, &key, &args).await?
+            }
+        };
+        Ok(RecordBatch::try_new(schema, vec![ids, distances])?)
+    }
+}
+
+impl<'a> KnnArguments<'a> {
+    fn try_from_scalars(args: &'a [ScalarValue]) -> Result<Self> {
+        let invalid = |message: String| GraphError::ExecutionError {
+            message: format!("vector.knn: {}", message),
+            location: snafu::Location::new(file!(), line!(), column!()),
+        };
+        let string = |i: usize, name: &str| match args.get(i) {
+            Some(ScalarValue::Utf8(Some(value))) => Ok(value.as_str()),
+            _ => Err(invalid(format!("{} must be a string", name))),
+        };
+        let label = string(0, "label")?;
+        let property = string(1, "property")?;
+        let metric = string(4, "metric")?;
+        let query = match args.get(2) {
+            Some(ScalarValue::List(list)) if !list.is_null(0) => {
+                list.value(0).as_primitive::<Float32Type>().clone()
+            }
+            _ => {
+                return Err(invalid(
+                    "query vector must be a list of numbers".to_string(),
+                ))
+            }
+        };
+        if query.is_empty() || query.null_count() > 0 {
+            return Err(invalid(
+                "query vector must be a non-empty list of numbers".to_string(),
+            ));
+        }
+        let k = match args.get(3) {
+            Some(ScalarValue::Int64(Some(k))) if *k > 0 => *k as usize,
+            other => return Err(invalid(format!("k must be positive, got {:?}", other))),
+        };
+        let metric = parse_metric(metric).ok_or_else(|| {
+            invalid(format!(
+                "unknown metric '{}', expected 'l2', 'cosine' or 'dot'",
+                metric
+            ))
+        })?;
+        Ok(Self {
+            label,
+            property,
+            query,
+            k,
+            metric,
+        })
+    }
+}
+
+/// The id column and table of the nodes of `label`
+fn node_table(ctx: &ProcedureContext, label: &str) -> Result<(String, Arc<dyn TableProvider>)> {
+    let mapping = ctx
+        .config()
+        .get_node_mapping(label)
+        .ok_or_else(|| GraphError::PlanError {
+            message: format!("vector.knn: unknown node label '{}'", label),
+            location: snafu::Location::new(file!(), line!(), column!()),
+        })?;
+    let [key] = mapping.id_fields()[..] else {
+        return Err(GraphError::PlanError {
+            message: format!("vector.knn: node label '{}' has a composite key", label),
+            location: snafu::Location::new(file!(), line!(), column!()),
+        });
+    };
+    let source = ctx
+        .catalog()
+        .and_then(|catalog| catalog.node_source(label))
+        .ok_or_else(|| GraphError::PlanError {
+            message: format!("vector.knn: no table for node label '{}'", label),
+            location: snafu::Location::new(file!(), line!(), column!()),
+        })?;
+    Ok((key.to_string(), source_as_provider(&source)?))
+}
+
+/// Search a Lance dataset with its scanner, through the vector index of the property
+/// if it has one
+async fn lance_knn(
+    dataset: Arc<Dataset>,
+    key: &str,
+    args: &KnnArguments<'_>,
+) -> Result<(ArrayRef, ArrayRef)> {
+    let mut scanner = dataset.scan();
+    scanner
+        .project(&[key])?
+        .nearest(args.property, &args.query, args.k)?
+        .distance_metric(args.metric);
+    let batch = scanner.try_into_batch().await?;
+    let column = |name: &str| {
+        batch
+            .column_by_name(name)
+            .cloned()
+            .ok_or_else(|| GraphError::ExecutionError {
+                message: format!("vector.knn: Lance search returned no '{}' column", name),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            })
+    };
+    let distances = cast(&column(LANCE_DISTANCE_COLUMN)?, &DataType::Float32)?;
+    Ok((column(key)?, distances))
+}
+
+/// Compare the query to the vector of every node of a table
+async fn exact_knn(
+    session: &SessionContext,
+    provider: Arc<dyn TableProvider>,
+    key: &str,
+    args: &KnnArguments<'_>,
+) -> Result<(ArrayRef, ArrayRef)> {
+    let key_type = provider.schema().field_with_name(key)?.data_type().clone();
+    let batches = session
+        .read_table(provider)?
+        .select_columns(&[key, args.property])?
+        .collect()
+        .await?;
+
+    // (distance, batch, row) of every node with a vector
+    let mut candidates: Vec<(f32, usize, usize)> = Vec::new();
+    for (b, batch) in batches.iter().enumerate() {
+        let vectors = cast(
+            batch.column(1),
+            &DataType::new_list(DataType::Float32, true),
+        )
+        .map_err(|e| GraphError::ExecutionError {
+            message: format!(
+                "vector.knn: property '{}' is not a vector: {}",
+                args.property, e
+            ),
+            location: snafu::Location::new(file!(), line!(), column!()),
+        })?;
+        let vectors = vectors.as_list::<i32>();
+        for row in 0..vectors.len() {
+            if vectors.is_null(row) {
+                continue;
+            }
+            let vector = vectors.value(row);
+            let vector = vector.as_primitive::<Float32Type>();
+            if vector.len() != args.query.len() {
+                return Err(GraphError::ExecutionError {
+                    message: format!(
+                        "vector.knn: query has {} dimensions, property '{}' has {}",
+                        args.query.len(),
+                        args.property,
+                        vector.len()
+                    ),
+                    location: snafu::Location::new(file!(), line!(), column!()),
+                });
+            }
+            let distance = distance(args.metric, args.query.values(), vector.values());
+            candidates.push((distance, b, row));
+        }
+    }
+    candidates.sort_by(|a, b| a.0.total_cmp(&b.0));
+    candidates.truncate(args.k);
+
+    let keys: Vec<&dyn Array> = batches
+        .iter()
+        .map(|batch| batch.column(0).as_ref())
+        .collect();
+    let indices: Vec<(usize, usize)> = candidates.iter().map(|&(_, b, row)| (b, row)).collect();
+    let ids = if indices.is_empty() {
+        new_empty_array(&key_type)
+    } else {
+        interleave(&keys, &indices)?
+    };
+    let distances = Float32Array::from_iter_values(candidates.iter().map(|&(d, _, _)| d));
+    Ok((ids, Arc::new(distances)))
+}
+
+/// The metric named `name`, ignoring case: `l2`, `cosine` or `dot`
+pub(crate) fn parse_metric(name: &str) -> Option<DistanceType> {
+    match DistanceType::try_from(name.to_lowercase().as_str()) {
+        Ok(metric @ (DistanceType::L2 | DistanceType::Cosine | DistanceType::Dot)) => Some(metric),
+        _ => None,
+    }
+}
+
+/// Distance between two vectors as Lance computes it: squared euclidean distance,
+/// one minus the cosine similarity, or one minus the dot product
+pub(crate) fn distance(metric: DistanceType, a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    match metric {
+        DistanceType::Cosine => {
+            let norm = |v: &[f32]| v.iter().map(|x| x * x).sum::<f32>().sqrt();
+            1.0 - dot / (norm(a) * norm(b))
+        }
+        DistanceType::Dot => 1.0 - dot,
+        _ => a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distances() {
+        let a = [1.0, 0.0];
+        let b = [0.0, 2.0];
+        assert_eq!(distance(DistanceType::L2, &a, &b), 5.0);
+        assert_eq!(distance(DistanceType::Cosine, &a, &b), 1.0);
+        assert_eq!(distance(DistanceType::Cosine, &a, &[3.0, 0.0]), 0.0);
+        assert_eq!(distance(DistanceType::Dot, &a, &[3.0, 0.0]), -2.0);
+    }
+}
@@ -0,0 +1,547 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! CREATE
+//!
+//! Every node a CREATE pattern labels becomes a row of its label's table and every
+//! relationship a row of its type's table, once per row of the reading clauses.
+//! Property values and the keys of already bound nodes, through `id()`, are returned by
+//! the reading clauses; a created node's key is taken from its properties or, for a
+//! single integer key left out, numbered on from the largest key in its table.
+//! Relationships take the keys of the nodes they connect, so nodes are added first.
+//! Nodes of earlier MERGE clauses are connected by the keys given in their patterns.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use arrow::compute::cast;
+use arrow_array::cast::AsArray;
+use arrow_array::types::Int64Type;
+use arrow_array::{new_null_array, Array, ArrayRef, Int64Array, RecordBatch, StringArray};
+use arrow_schema::{DataType, Schema};
+use datafusion::execution::context::SessionContext;
+use datafusion::functions_aggregate::expr_fn::max;
+use datafusion::logical_expr::ident;
+use lance::datafusion::LanceTableProvider;
+use lance::Dataset;
+
+use crate::ast::{
+    GraphPattern, NodePattern, PathSegment, PropertyValue, RelationshipDirection, ValueExpression,
+};
+use crate::config::GraphConfig;
+use crate::error::{GraphError, Result};
+use crate::lance_catalog::LanceCatalog;
+use crate::source_catalog::SourceKind;
+use crate::write::transaction::WriteTransaction;
+use crate::write::{registered, Endpoint, Projection, WriteSummary};
+
+/// A node added for every input row
+#[derive(Debug)]
+struct NodeCreation {
+    variable: String,
+    label: String,
+    /// Each property and the returned column holding its values
+    properties: Vec<(String, usize)>,
+}
+
+/// A relationship added for every input row
+#[derive(Debug)]
+struct RelationshipCreation {
+    rel_type: String,
+    source: Endpoint,
+    target: Endpoint,
+    properties: Vec<(String, usize)>,
+}
+
+/// What the CREATE clauses of a query add, and the values they need from each row
+#[derive(Debug)]
+pub(crate) struct CreatePlan {
+    nodes: Vec<NodeCreation>,
+    relationships: Vec<RelationshipCreation>,
+}
+
+impl CreatePlan {
+    /// Plan the patterns of the query's CREATE clauses; `bound` holds the variables the
+    /// reading clauses bind and `merged` the key columns of nodes of MERGE clauses
+    pub(super) fn try_new<'a>(
+        patterns: impl IntoIterator<Item = &'a GraphPattern>,
+        bound: &[String],
+        merged: &HashMap<String, Vec<usize>>,
+        projection: &mut Projection,
+    ) -> Result<Self> {
+        let mut plan = Self {
+            nodes: Vec::new(),
+            relationships: Vec::new(),
+        };
+        for pattern in patterns {
+            match pattern {
+                GraphPattern::Node(node) => {
+                    plan.node(node, bound, merged, projection)?;
+                }
+                GraphPattern::Path(path) => {
+                    if path.variable.is_some() || path.shortest.is_some() {
+                        return Err(GraphError::UnsupportedFeature {
+                            feature: "Named or shortest paths in CREATE".to_string(),
+                            location: snafu::Location::new(file!(), line!(), column!()),
+                        });
+                    }
+                    let mut left = plan.node(&path.start_node, bound, merged, projection)?;
+                    for segment in &path.segments {
+                        let right = plan.node(&segment.end_node, bound, merged, projection)?;
+                        plan.relationship(segment, left, right.clone(), bound, projection)?;
+                        left = right;
+                    }
+                }
+            }
+        }
+        Ok(plan)
+    }
+
+    fn property_columns(
+        properties: &HashMap<String, PropertyValue>,
+        projection: &mut Projection,
+    ) -> Vec<(String, usize)> {
+        let mut keys: Vec<&String> = properties.keys().collect();
+        keys.sort();
+        keys.into_iter()
+            .map(|key| {
+                let value = ValueExpression::Literal(properties[key].clone());
+                (key.clone(), projection.column(value))
+            })
+            .collect()
+    }
+
+    /// Plan a node pattern: a node to create when it has a label, otherwise a reference
+    /// to a node created earlier, merged or bound by the reading clauses
+    fn node(
+        &mut self,
+        node: &NodePattern,
+        bound: &[String],
+        merged: &HashMap<String, Vec<usize>>,
+        projection: &mut Projection,
+    ) -> Result<Endpoint> {
+        let invalid = |message: String| GraphError::InvalidPattern {
+            message,
+            location: snafu::Location::new(file!(), line!(), column!()),
+        };
+        let variable = node
+            .variable
+            .clone()
+            .unwrap_or_else(|| format!("_create_node_{}", self.nodes.len()));
+        let created = self.nodes.iter().any(|n| n.variable == variable);
+
+        if node.labels.is_empty() && node.properties.is_empty() {
+            if created {
+                return Ok(Endpoint::Created(variable));
+            }
+            if let Some(keys) = merged.get(&variable) {
+                return Ok(Endpoint::Merged(keys.clone()));
+            }
+            if node.variable.is_none() || !bound.contains(&variable) {
+                return Err(invalid(format!(
+                    "CREATE needs a label for the new node '{}'",
+                    variable
+                )));
+            }
+            return Ok(Endpoint::Bound(projection.id_column(&variable)));
+        }
+
+        if created || bound.contains(&variable) || merged.contains_key(&variable) {
+            return Err(invalid(format!(
+                "Variable '{}' is already bound and cannot be created again",
+                variable
+            )));
+        }
+        let label = match node.labels.as_slice() {
+            [label] => label,
+            [] => {
+                return Err(invalid(format!(
+                    "CREATE needs a label for the new node '{}'",
+                    variable
+                )))
+            }
+            _ => {
+                return Err(GraphError::UnsupportedFeature {
+                    feature: "Creating nodes with several labels".to_string(),
+                    location: snafu::Location::new(file!(), line!(), column!()),
+                })
+            }
+        };
+        if node.label_expression.is_some() {
+            return Err(invalid(format!(
+                "CREATE cannot use a label expression on '{}'",
+                variable
+            )));
+        }
+        let properties = Self::property_columns(&node.properties, projection);
+        self.nodes.push(NodeCreation {
+            variable: variable.clone(),
+            label: label.clone(),
+            properties,
+        });
+        Ok(Endpoint::Created(variable))
+    }
+
+    fn relationship(
+        &mut self,
+        segment: &PathSegment,
+        left: Endpoint,
+        right: Endpoint,
+        bound: &[String],
+        projection: &mut Projection,
+    ) -> Result<()> {
+        let invalid = |message: &str| GraphError::InvalidPattern {
+            message: message.to_string(),
+            location: snafu::Location::new(file!(), line!(), column!()),
+        };
+        let relationship = &segment.relationship;
+        let [rel_type] = relationship.types.as_slice() else {
+            return Err(invalid("CREATE needs exactly one type per relationship"));
+        };
+        if relationship.length.is_some() {
+            return Err(invalid(
+                "CREATE cannot create variable-length relationships",
+            ));
+        }
+        if relationship
+            .variable
+            .as_ref()
+            .is_some_and(|v| bound.contains(v))
+        {
+            return Err(invalid(
+                "CREATE cannot create an already bound relationship",
+            ));
+        }
+        let (source, target) = match relationship.direction {
+            RelationshipDirection::Outgoing => (left, right),
+            RelationshipDirection::Incoming => (right, left),
+            RelationshipDirection::Undirected => {
+                return Err(invalid("CREATE needs a direction for every relationship"))
+            }
+        };
+        let properties = Self::property_columns(&relationship.properties, projection);
+        self.relationships.push(RelationshipCreation {
+            rel_type: rel_type.clone(),
+            source,
+            target,
+            properties,
+        });
+        Ok(())
+    }
+
+    /// Queue the rows to add for the rows returned by the reading clauses
+    pub(super) async fn apply(
+        &self,
+        rows: &RecordBatch,
+        config: &GraphConfig,
+        catalog: &LanceCatalog,
+        transaction: &mut WriteTransaction,
+        summary: &mut WriteSummary,
+    ) -> Result<()> {
+        let num_rows = rows.num_rows();
+        if num_rows == 0 {
+            return Ok(());
+        }
+        let mut next_keys: HashMap<&str, i64> = HashMap::new();
+        let mut created_keys: HashMap<&str, Vec<ArrayRef>> = HashMap::new();
+
+        for node in &self.nodes {
+            let mapping =
+                config
+                    .get_node_mapping(&node.label)
+                    .ok_or_else(|| GraphError::ConfigError {
+                        message: format!("No node mapping found for label '{}'", node.label),
+                        location: snafu::Location::new(file!(), line!(), column!()),
+                    })?;
+            let dataset = registered(catalog, SourceKind::Node, &node.label)?;
+            let schema = Schema::from(dataset.schema());
+
+            let mut columns = property_arrays(
+                rows,
+                &node.properties,
+                |p| mapping.column_for(p).to_string(),
+                |p| mapping.computed_properties.contains_key(p),
+                &node.label,
+            )?;
+            if let Some((column, value)) = mapping.label_discriminator() {
+                columns.insert(column.to_string(), repeated(value, num_rows));
+            }
+            let mut keys = Vec::new();
+            for field in mapping.id_fields() {
+                if !columns.contains_key(field) {
+                    let numbered = mapping.id_fields().len() == 1
+                        && schema
+                            .field_with_name(field)
+                            .is_ok_and(|f| f.data_type().is_integer());
+                    if !numbered {
+                        return Err(GraphError::InvalidPattern {
+                            message: format!(
+                                "CREATE of a '{}' node must set its key property '{}'",
+                                node.label,
+                                mapping.property_for(field)
+                            ),
+                            location: snafu::Location::new(file!(), line!(), column!()),
+                        });
+                    }
+                    let next = match next_keys.get(node.label.as_str()) {
+                        Some(next) => *next,
+                        None => max_key(&dataset, field).await?.map_or(0, |max| max + 1),
+                    };
+                    let end = next + num_rows as i64;
+                    next_keys.insert(&node.label, end);
+                    columns.insert(
+                        field.to_string(),
+                        Arc::new(Int64Array::from_iter_values(next..end)),
+                    );
+                }
+                keys.push(columns[field].clone());
+            }
+            created_keys.insert(&node.variable, keys);
+
+            let batch = table_batch(&schema, columns, num_rows, &node.label)?;
+            transaction.append(SourceKind::Node, &node.label, batch);
+            summary.nodes_created += num_rows;
+        }
+
+        for relationship in &self.relationships {
+            let rel_type = &relationship.rel_type;
+            let mapping = config.get_relationship_mapping(rel_type).ok_or_else(|| {
+                GraphError::ConfigError {
+                    message: format!("No relationship mapping found for type '{}'", rel_type),
+                    location: snafu::Location::new(file!(), line!(), column!()),
+                }
+            })?;
+            if mapping.adjacency_column.is_some() {
+                return Err(GraphError::UnsupportedFeature {
+                    feature: format!(
+                        "CREATE of '{}' relationships, stored in an adjacency column",
+                        rel_type
+                    ),
+                    location: snafu::Location::new(file!(), line!(), column!()),
+                });
+            }
+            let dataset = registered(catalog, SourceKind::Relationship, mapping.table_name())?;
+            let schema = Schema::from(dataset.schema());
+
+            let mut columns = property_arrays(
+                rows,
+                &relationship.properties,
+                |p| mapping.column_for(p).to_string(),
+                |p| mapping.computed_properties.contains_key(p),
+                rel_type,
+            )?;
+            if let Some((column, value)) = mapping.type_discriminator() {
+                columns.insert(column.to_string(), repeated(value, num_rows));
+            }
+            let endpoints = [
+                (&relationship.source, mapping.source_id_fields()),
+                (&relationship.target, mapping.target_id_fields()),
+            ];
+            for (endpoint, fields) in endpoints {
+                let keys = endpoint.keys(rows, &created_keys)?;
+                if keys.len() != fields.len() {
+                    return Err(GraphError::InvalidPattern {
+                        message: format!(
+                            "'{}' relationships connect nodes with {} key field(s), got {}",
+                            rel_type,
+                            fields.len(),
+                            keys.len()
+                        ),
+                        location: snafu::Location::new(file!(), line!(), column!()),
+                    });
+                }
+                for (field, key) in fields.into_iter().zip(keys) {
+                    columns.insert(field.to_string(), key);
+                }
+            }
+
+            let batch = table_batch(&schema, columns, num_rows, rel_type)?;
+            transaction.append(SourceKind::Relationship, mapping.table_name(), batch);
+            summary.relationships_created += num_rows;
+        }
+        Ok(())
+    }
+}
+
+/// The returned values of each property, by the column storing it
+fn property_arrays(
+    rows: &RecordBatch,
+    properties: &[(String, usize)],
+    column_for: impl Fn(&str) -> String,
+    is_computed: impl Fn(&str) -> bool,
+    owner: &str,
+) -> Result<HashMap<String, ArrayRef>> {
+    properties
+        .iter()
+        .map(|(property, index)| {
+            if is_computed(property) {
+                return Err(GraphError::InvalidPattern {
+                    message: format!(
+                        "Property '{}' of '{}' is computed and cannot be set",
+                        property, owner
+                    ),
+                    location: snafu::Location::new(file!(), line!(), column!()),
+                });
+            }
+            Ok((column_for(property), rows.column(*index).clone()))
+        })
+        .collect()
+}
+
+fn repeated(value: &str, num_rows: usize) -> ArrayRef {
+    Arc::new(StringArray::from(vec![value; num_rows]))
+}
+
+/// The largest key in a table, or `None` when it is empty
+async fn max_key(dataset: &Arc<Dataset>, column: &str) -> Result<Option<i64>> {
+    let provider = LanceTableProvider::new(dataset.clone(), false, false);
+    let batches = SessionContext::new()
+        .read_table(Arc::new(provider))?
+        .aggregate(vec![], vec![max(ident(column))])?
+        .collect()
+        .await?;
+    let Some(batch) = batches.first().filter(|b| b.num_rows() > 0) else {
+        return Ok(None);
+    };
+    let values = cast(batch.column(0), &DataType::Int64)?;
+    let values = values.as_primitive::<Int64Type>();
+    Ok((!values.is_null(0)).then(|| values.value(0)))
+}
+
+/// A batch in the table's schema: given columns cast to their field types and the
+/// others NULL
+fn table_batch(
+    schema: &Schema,
+    mut columns: HashMap<String, ArrayRef>,
+    num_rows: usize,
+    owner: &str,
+) -> Result<RecordBatch> {
+    let arrays = schema
+        .fields()
+        .iter()
+        .map(|field| match columns.remove(field.name()) {
+            Some(values) => Ok(cast(&values, field.data_type())?),
+            None if field.is_nullable() => Ok(new_null_array(field.data_type(), num_rows)),
+            None => Err(GraphError::InvalidPattern {
+                message: format!("CREATE of '{}' must set '{}'", owner, field.name()),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            }),
+        })
+        .collect::<Result<Vec<_>>>()?;
+    if let Some(column) = columns.keys().next() {
+        return Err(GraphError::InvalidPattern {
+            message: format!("The table of '{}' has no column '{}'", owner, column),
+            location: snafu::Location::new(file!(), line!(), column!()),
+        });
+    }
+    Ok(RecordBatch::try_new(Arc::new(schema.clone()), arrays)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::types::Int32Type;
+    use arrow_array::{Int32Array, RecordBatchIterator};
+    use arrow_schema::Field;
+
+    fn person_schema() -> Schema {
+        Schema::new(vec![
+            Field::new("id", DataType::Int32, false),
+            Field::new("name", DataType::Utf8, true),
+        ])
+    }
+
+    #[test]
+    fn test_table_batch_casts_and_fills_columns() {
+        let ids: ArrayRef = Arc::new(Int64Array::from(vec![1, 2]));
+        let batch = table_batch(
+            &person_schema(),
+            HashMap::from([("id".to_string(), ids)]),
+            2,
+            "Person",
+        )
+        .unwrap();
+        assert_eq!(batch.schema().as_ref(), &person_schema());
+        assert_eq!(
+            batch.column(0).as_primitive::<Int32Type>(),
+            &Int32Array::from(vec![1, 2])
+        );
+        assert_eq!(batch.column(1).null_count(), 2);
+    }
+
+    #[test]
+    fn test_table_batch_errors() {
+        let names = repeated("Alice", 1);
+        let error = table_batch(
+            &person_schema(),
+            HashMap::from([("name".to_string(), names.clone())]),
+            1,
+            "Person",
+        )
+        .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("CREATE of 'Person' must set 'id'"));
+
+        let ids: ArrayRef = Arc::new(Int64Array::from(vec![1]));
+        let error = table_batch(
+            &person_schema(),
+            HashMap::from([("id".to_string(), ids), ("nickname".to_string(), names)]),
+            1,
+            "Person",
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("has no column 'nickname'"));
+    }
+
+    #[test]
+    fn test_property_arrays() {
+        let rows = RecordBatch::try_from_iter(vec![
+            (
+                "_write_row",
+                Arc::new(Int64Array::from(vec![0])) as ArrayRef,
+            ),
+            ("name", repeated("Alice", 1)),
+        ])
+        .unwrap();
+        let properties = vec![("name".to_string(), 1)];
+        let arrays = property_arrays(
+            &rows,
+            &properties,
+            |property| format!("{}_col", property),
+            |_| false,
+            "Person",
+        )
+        .unwrap();
+        assert_eq!(arrays.keys().collect::<Vec<_>>(), vec!["name_col"]);
+
+        let error =
+            property_arrays(&rows, &properties, str::to_string, |_| true, "Person").unwrap_err();
+        assert!(error.to_string().contains("is computed and cannot be set"));
+    }
+
+    #[tokio::test]
+    async fn test_max_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let uri = dir.path().join("person.lance");
+        let batch = RecordBatch::try_new(
+            Arc::new(person_schema()),
+            vec![
+                Arc::new(Int32Array::from(vec![3, 7, 5])),
+                repeated("Alice", 3),
+            ],
+        )
+        .unwrap();
+        let reader = RecordBatchIterator::new(vec![Ok(batch.clone())], batch.schema());
+        let mut dataset = Dataset::write(reader, uri.to_str().unwrap(), None)
+            .await
+            .unwrap();
+        assert_eq!(
+            max_key(&Arc::new(dataset.clone()), "id").await.unwrap(),
+            Some(7)
+        );
+
+        dataset.delete("true").await.unwrap();
+        assert_eq!(max_key(&Arc::new(dataset), "id").await.unwrap(), None);
+    }
+}
@@ -0,0 +1,367 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! DELETE and DETACH DELETE
+//!
+//! The reading clauses return the keys of every deleted node and relationship, and each
+//! dataset losing rows becomes Lance deletes of the rows with those keys. A node's
+//! relationships are the rows of every edge table mapped to its label whose source or
+//! target keys are the node's. An edge table whose endpoints name no label holds them
+//! only when no unrelated label is keyed by as many columns; otherwise the delete fails
+//! rather than guess. DETACH DELETE deletes the relationships with the node, and DELETE
+//! fails unless the query deletes them too. As with SET, relationships are told apart by
+//! the nodes they connect, so deleting one deletes all relationships of its type between
+//! the same two nodes.
+
+use std::collections::HashSet;
+
+use arrow_array::cast::AsArray;
+use arrow_array::types::UInt64Type;
+use arrow_array::RecordBatch;
+use arrow_schema::Schema;
+use lance::Dataset;
+use lance_core::ROW_ID;
+
+use crate::ast::{CypherQuery, DeleteClause};
+use crate::config::{GraphConfig, RelationshipMapping};
+use crate::error::{GraphError, Result};
+use crate::lance_catalog::LanceCatalog;
+use crate::source_catalog::SourceKind;
+use crate::write::set::{discriminated, key_filters, Bound};
+use crate::write::transaction::WriteTransaction;
+use crate::write::{registered, Projection, WriteSummary};
+
+/// What the DELETE clauses of a query remove
+#[derive(Debug)]
+pub(crate) struct DeletePlan {
+    /// Each deleted variable, and whether its relationships are deleted with it
+    targets: Vec<(Bound, bool)>,
+}
+
+impl DeletePlan {
+    pub(super) fn try_new<'a>(
+        clauses: impl IntoIterator<Item = &'a DeleteClause>,
+        ast: &CypherQuery,
+        config: &GraphConfig,
+        projection: &mut Projection,
+    ) -> Result<Self> {
+        let mut plan = Self {
+            targets: Vec::new(),
+        };
+        for clause in clauses {
+            for variable in &clause.variables {
+                match plan
+                    .targets
+                    .iter_mut()
+                    .find(|(bound, _)| bound.variable == *variable)
+                {
+                    Some((_, detach)) => *detach |= clause.detach,
+                    None => {
+                        let bound = Bound::try_new(variable, "DELETE", ast, config, projection)?;
+                        plan.targets.push((bound, clause.detach));
+                    }
+                }
+            }
+        }
+        Ok(plan)
+    }
+
+    /// Queue the deletes for the rows returned by the reading clauses
+    pub(super) async fn apply(
+        &self,
+        rows: &RecordBatch,
+        config: &GraphConfig,
+        catalog: &LanceCatalog,
+        transaction: &mut WriteTransaction,
+        summary: &mut WriteSummary,
+    ) -> Result<()> {
+        // SQL filters of the rows deleted from each dataset
+        let mut deleted: Vec<(SourceKind, String, Vec<String>)> = Vec::new();
+        let mut delete = |kind: SourceKind, name: &str, filters: Vec<String>| match deleted
+            .iter_mut()
+            .find(|(k, n, _)| *k == kind && n == name)
+        {
+            Some((_, _, deleted)) => deleted.extend(filters),
+            None => deleted.push((kind, name.to_string(), filters)),
+        };
+        // Relationships of nodes deleted without DETACH, which must be deleted too
+        let mut attached = Vec::new();
+
+        for (bound, detach) in &self.targets {
+            let dataset = registered(catalog, bound.kind, &bound.name)?;
+            let batch = bound.rows(rows, &Schema::from(dataset.schema()), &[])?;
+            if batch.num_rows() == 0 {
+                continue;
+            }
+            delete(bound.kind, &bound.name, bound.filters(&batch)?);
+            if bound.kind == SourceKind::Relationship {
+                continue;
+            }
+            for (table, filters) in incident(bound, &batch, config, catalog)? {
+                if *detach {
+                    delete(SourceKind::Relationship, &table, filters);
+                } else {
+                    attached.push((bound, table, filters));
+                }
+            }
+        }
+
+        // The rows each dataset loses, by row id, as the filters of one may overlap
+        let mut deleted_rows = Vec::with_capacity(deleted.len());
+        for (kind, name, filters) in &deleted {
+            let dataset = registered(catalog, *kind, name)?;
+            deleted_rows.push(matching_rows(&dataset, filters).await?);
+        }
+
+        for (bound, table, filters) in attached {
+            let dataset = registered(catalog, SourceKind::Relationship, &table)?;
+            let mut remaining = matching_rows(&dataset, &filters).await?;
+            if let Some(index) = deleted
+                .iter()
+                .position(|(k, n, _)| *k == SourceKind::Relationship && *n == table)
+            {
+                remaining.retain(|row| !deleted_rows[index].contains(row));
+            }
+            if !remaining.is_empty() {
+                return Err(GraphError::InvalidPattern {
+                    message: format!(
+                        "Cannot delete '{}' nodes that still have relationships; DETACH DELETE deletes them too",
+                        bound.owner
+                    ),
+                    location: snafu::Location::new(file!(), line!(), column!()),
+                });
+            }
+        }
+
+        for ((kind, name, filters), rows) in deleted.into_iter().zip(deleted_rows) {
+            match kind {
+                SourceKind::Node => summary.nodes_deleted += rows.len(),
+                SourceKind::Relationship => summary.relationships_deleted += rows.len(),
+            }
+            for filter in filters {
+                transaction.delete(kind, &name, filter);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// SQL filters of the relationships of the deleted nodes of `bound`, whose keys `batch`
+/// holds, by edge table
+fn incident(
+    bound: &Bound,
+    batch: &RecordBatch,
+    config: &GraphConfig,
+    catalog: &LanceCatalog,
+) -> Result<Vec<(String, Vec<String>)>> {
+    let keys = &batch.columns()[..bound.keys.len()];
+    let mut filters = Vec::new();
+    for mapping in config.relationship_mappings.values() {
+        let table = mapping.table_name();
+        let mut ends = Vec::new();
+        for (end, label, fields) in [
+            ("source", &mapping.source_label, mapping.source_id_fields()),
+            ("target", &mapping.target_label, mapping.target_id_fields()),
+        ] {
+            if holds(bound, config, mapping, end, label.as_deref(), fields.len())? {
+                ends.push(fields);
+            }
+        }
+        if ends.is_empty() {
+            continue;
+        }
+        // Lists of edges on the rows of other nodes cannot lose one
+        if mapping.adjacency_column.is_some() {
+            return Err(GraphError::UnsupportedFeature {
+                feature: format!(
+                    "DELETE of '{}' nodes, which '{}' relationships in an adjacency column connect",
+                    bound.owner, mapping.relationship_type
+                ),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            });
+        }
+        if catalog.dataset(SourceKind::Relationship, table).is_none() {
+            continue;
+        }
+        let discriminator = mapping
+            .type_discriminator()
+            .map(|(c, v)| (c.to_string(), v.to_string()));
+        let mut table_filters = Vec::new();
+        for fields in &ends {
+            for filter in key_filters(fields, keys)? {
+                table_filters.push(discriminated(filter, discriminator.as_ref())?);
+            }
+        }
+        filters.push((table.to_string(), table_filters));
+    }
+    Ok(filters)
+}
+
+/// Whether the `end` nodes of `mapping`, of `label` and keyed by `arity` columns, can be
+/// deleted nodes of `bound`
+///
+/// An end without a label holds nodes of any label keyed by as many columns, so it is
+/// only taken to hold the deleted nodes when every such label is related to theirs.
+fn holds(
+    bound: &Bound,
+    config: &GraphConfig,
+    mapping: &RelationshipMapping,
+    end: &str,
+    label: Option<&str>,
+    arity: usize,
+) -> Result<bool> {
+    if arity != bound.keys.len() {
+        return Ok(false);
+    }
+    let ancestors = config.label_with_ancestors(&bound.owner);
+    if let Some(label) = label {
+        return Ok(ancestors.contains(&label));
+    }
+    let descendants = config.label_with_descendants(&bound.owner);
+    let mut others: Vec<&str> = config
+        .node_mappings
+        .iter()
+        .filter(|(_, node)| node.id_fields().len() == arity)
+        .map(|(label, _)| label.as_str())
+        .filter(|label| !ancestors.contains(label) && !descendants.contains(label))
+        .collect();
+    if others.is_empty() {
+        return Ok(true);
+    }
+    others.sort_unstable();
+    Err(GraphError::ConfigError {
+        message: format!(
+            "Cannot tell whether the {} nodes of '{}' relationships are '{}' nodes or '{}' nodes; \
+             declare them with RelationshipMapping::with_endpoint_labels",
+            end,
+            mapping.relationship_type,
+            bound.owner,
+            others.join("', '")
+        ),
+        location: snafu::Location::new(file!(), line!(), column!()),
+    })
+}
+
+/// The row ids of the rows of `dataset` any of `filters` match
+async fn matching_rows(dataset: &Dataset, filters: &[String]) -> Result<HashSet<u64>> {
+    let mut rows = HashSet::new();
+    for filter in filters {
+        let mut scanner = dataset.scan();
+        scanner.project::<&str>(&[])?.with_row_id().filter(filter)?;
+        let batch = scanner.try_into_batch().await?;
+        if let Some(ids) = batch.column_by_name(ROW_ID) {
+            rows.extend(ids.as_primitive::<UInt64Type>().values().iter().copied());
+        }
+    }
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::NodeMapping;
+    use crate::write::set::KEYS_PER_FILTER;
+    use arrow_array::{ArrayRef, Int64Array, RecordBatchIterator, StringArray};
+    use std::sync::Arc;
+
+    fn bound(label: &str) -> Bound {
+        Bound {
+            variable: "n".to_string(),
+            kind: SourceKind::Node,
+            name: label.to_string(),
+            owner: label.to_string(),
+            keys: vec![("id".to_string(), 0)],
+            discriminator: None,
+        }
+    }
+
+    fn people(with_company: bool) -> GraphConfig {
+        let mut builder = GraphConfig::builder()
+            .with_node_label("Person", "id")
+            .with_node_mapping(NodeMapping::new("Employee", "id").with_extends("Person"))
+            .with_node_mapping(
+                NodeMapping::new("Tenant", "tenant_id")
+                    .with_composite_id(vec!["tenant_id".to_string(), "id".to_string()]),
+            )
+            .with_relationship("KNOWS", "src_id", "dst_id");
+        if with_company {
+            builder = builder.with_node_label("Company", "id");
+        }
+        builder.build().unwrap()
+    }
+
+    #[test]
+    fn test_holds() {
+        let config = people(true);
+        let knows = config.get_relationship_mapping("KNOWS").unwrap();
+        let employee = bound("Employee");
+        let source = |label, arity| holds(&employee, &config, knows, "source", label, arity);
+
+        // The label of the end, or one of its ancestors
+        assert!(source(Some("Employee"), 1).unwrap());
+        assert!(source(Some("Person"), 1).unwrap());
+        assert!(!source(Some("Company"), 1).unwrap());
+        assert!(!source(Some("Person"), 2).unwrap());
+
+        // Without a label, only when no unrelated label has as many key columns
+        let error = source(None, 1).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("'Employee' nodes or 'Company' nodes"));
+        let without_company = people(false);
+        assert!(holds(&employee, &without_company, knows, "source", None, 1).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_incident_relationships_by_row() {
+        let dir = tempfile::tempdir().unwrap();
+        let ids: Vec<i64> = (0..=KEYS_PER_FILTER as i64).collect();
+        let knows = RecordBatch::try_from_iter(vec![
+            (
+                "src_id",
+                Arc::new(Int64Array::from(ids.clone())) as ArrayRef,
+            ),
+            (
+                "dst_id",
+                Arc::new(Int64Array::from(ids.clone())) as ArrayRef,
+            ),
+            (
+                "kind",
+                Arc::new(StringArray::from(vec!["KNOWS"; ids.len()])) as ArrayRef,
+            ),
+        ])
+        .unwrap();
+        let uri = dir.path().join("knows.lance");
+        let reader = RecordBatchIterator::new(vec![Ok(knows.clone())], knows.schema());
+        let dataset = Dataset::write(reader, uri.to_str().unwrap(), None)
+            .await
+            .unwrap();
+        let catalog = LanceCatalog::new().with_relationship_dataset("KNOWS", Arc::new(dataset));
+        let config = GraphConfig::builder()
+            .with_node_label("Person", "id")
+            .with_relationship_mapping(
+                RelationshipMapping::new("KNOWS", "src_id", "dst_id").with_type_field("kind"),
+            )
+            .build()
+            .unwrap();
+
+        // Every node, so each edge matches a filter of both of its ends
+        let batch = RecordBatch::try_from_iter(vec![(
+            "id",
+            Arc::new(Int64Array::from(ids.clone())) as ArrayRef,
+        )])
+        .unwrap();
+        let incident = incident(&bound("Person"), &batch, &config, &catalog).unwrap();
+        let [(table, filters)] = &incident[..] else {
+            panic!("one edge table, not {:?}", incident);
+        };
+        assert_eq!(table, "KNOWS");
+        assert_eq!(filters.len(), 4);
+        assert!(filters.iter().all(|f| f.ends_with("AND `kind` = 'KNOWS'")));
+
+        let dataset = catalog.dataset(SourceKind::Relationship, "KNOWS").unwrap();
+        let rows = matching_rows(&dataset, filters).await.unwrap();
+        assert_eq!(rows.len(), ids.len());
+        assert!(matching_rows(&dataset, &[]).await.unwrap().is_empty());
+    }
+}
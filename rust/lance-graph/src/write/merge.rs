@@ -0,0 +1,628 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! MERGE
+//!
+//! A MERGE clause looks its pattern up for every row of the reading clauses and creates
+//! it when it is missing. A node is identified by the key properties of its label, which
+//! the pattern must give, and a relationship by the keys of the two nodes it connects,
+//! which must be bound or merged before; other properties of the pattern are only set
+//! on creation.
+//!
+//! The reading clauses return the keys and every value that does not depend on the
+//! merged node or relationship. The rows of its table with those keys are then joined
+//! in, providing the values `ON MATCH SET n.seen = n.seen + 1` reads, and the resulting
+//! rows go to Lance as one merge-insert: rows with a key the table holds update it, the
+//! others are inserted. Of several rows with the same key, the first one is written.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use arrow::compute::{cast, concat_batches};
+use arrow_array::cast::AsArray;
+use arrow_array::{Array, ArrayRef, RecordBatch, StringArray, UInt64Array};
+use arrow_schema::{DataType, Field, Schema};
+use datafusion::common::{JoinType, ScalarValue};
+use datafusion::execution::context::SessionContext;
+use datafusion::logical_expr::{cast as cast_expr, ident, lit, when, Expr};
+use lance::datafusion::LanceTableProvider;
+use lance::Dataset;
+
+use crate::ast::{
+    GraphPattern, MergeClause, NodePattern, RelationshipDirection, SetItem, ValueExpression,
+};
+use crate::config::GraphConfig;
+use crate::datafusion_planner::expression::to_df_value_expr;
+use crate::error::{GraphError, Result};
+use crate::lance_catalog::LanceCatalog;
+use crate::logical_plan::collect_referenced_variables;
+use crate::source_catalog::SourceKind;
+use crate::write::transaction::{first_rows, WriteTransaction};
+use crate::write::{registered, settable, Endpoint, Projection, WriteSummary};
+
+/// Column telling joined rows that exist from created ones
+const MATCHED: &str = "_merge_matched";
+/// Column keeping the order of the input rows
+const ROW: &str = "_merge_row";
+
+/// A value MERGE writes to a property
+#[derive(Debug)]
+enum Value {
+    /// Returned by the reading clauses, in this column
+    Returned(usize),
+    /// Computed from the properties the merged node or relationship has
+    Existing(ValueExpression),
+}
+
+/// What a MERGE clause finds or creates
+#[derive(Debug)]
+enum Target {
+    /// A node of `label`, with the returned columns of its key properties
+    Node { label: String, keys: Vec<usize> },
+    /// A relationship of `rel_type` between two nodes
+    Relationship {
+        rel_type: String,
+        source: Endpoint,
+        target: Endpoint,
+    },
+}
+
+/// One MERGE clause and the values it needs from each row
+#[derive(Debug)]
+pub(crate) struct MergePlan {
+    variable: String,
+    target: Target,
+    /// Property values of a created node or relationship, later ones winning
+    on_create: Vec<(String, Value)>,
+    /// Property values of an existing one
+    on_match: Vec<(String, Value)>,
+    /// Number of ON CREATE and ON MATCH SET items
+    set_items: (usize, usize),
+}
+
+/// The table a MERGE writes to and the keys it looks up
+struct Table {
+    kind: SourceKind,
+    /// Name of the dataset
+    name: String,
+    /// Label or relationship type, for messages
+    owner: String,
+    dataset: Arc<Dataset>,
+    /// Key columns, including a shared table's label or type column, and their values
+    keys: Vec<(String, ArrayRef)>,
+    /// Column storing each written property
+    columns: HashMap<String, String>,
+    /// Property each column of the table stores
+    properties: HashMap<String, String>,
+}
+
+impl MergePlan {
+    /// Plan a MERGE clause; `bound` holds the variables the reading clauses bind and
+    /// `merged` the key columns of nodes of earlier MERGE clauses, which a merged node
+    /// is added to
+    pub(super) fn try_new(
+        clause: &MergeClause,
+        config: &GraphConfig,
+        bound: &[String],
+        projection: &mut Projection,
+        merged: &mut HashMap<String, Vec<usize>>,
+    ) -> Result<Self> {
+        let invalid = |message: String| GraphError::InvalidPattern {
+            message,
+            location: snafu::Location::new(file!(), line!(), column!()),
+        };
+        let unsupported = |feature: &str| GraphError::UnsupportedFeature {
+            feature: feature.to_string(),
+            location: snafu::Location::new(file!(), line!(), column!()),
+        };
+        let already_bound = |variable: &str| {
+            invalid(format!(
+                "Variable '{}' is already bound and cannot be merged",
+                variable
+            ))
+        };
+
+        let (variable, target, properties) = match &clause.pattern {
+            GraphPattern::Node(node) => {
+                let variable = node
+                    .variable
+                    .clone()
+                    .unwrap_or_else(|| format!("_merge_node_{}", merged.len()));
+                if bound.contains(&variable) || merged.contains_key(&variable) {
+                    return Err(already_bound(&variable));
+                }
+                let label = match node.labels.as_slice() {
+                    [label] if node.label_expression.is_none() => label,
+                    [] => {
+                        return Err(invalid(format!(
+                            "MERGE needs a label for the node '{}'",
+                            variable
+                        )))
+                    }
+                    _ => {
+                        return Err(unsupported(
+                            "Merging nodes with several labels or a label expression",
+                        ))
+                    }
+                };
+                let mapping =
+                    config
+                        .get_node_mapping(label)
+                        .ok_or_else(|| GraphError::ConfigError {
+                            message: format!("No node mapping found for label '{}'", label),
+                            location: snafu::Location::new(file!(), line!(), column!()),
+                        })?;
+                let key_properties: Vec<&str> = mapping
+                    .id_fields()
+                    .into_iter()
+                    .map(|field| mapping.property_for(field))
+                    .collect();
+                let keys = key_properties
+                    .iter()
+                    .map(|property| match node.properties.get(*property) {
+                        Some(value) => {
+                            Ok(projection.column(ValueExpression::Literal(value.clone())))
+                        }
+                        None => Err(invalid(format!(
+                            "MERGE of a '{}' node needs its key property '{}'",
+                            label, property
+                        ))),
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                merged.insert(variable.clone(), keys.clone());
+                let properties: Vec<_> = node
+                    .properties
+                    .iter()
+                    .filter(|(property, _)| !key_properties.contains(&property.as_str()))
+                    .collect();
+                let target = Target::Node {
+                    label: label.clone(),
+                    keys,
+                };
+                (variable, target, properties)
+            }
+            GraphPattern::Path(path) => {
+                if path.variable.is_some() || path.shortest.is_some() {
+                    return Err(unsupported("Named or shortest paths in MERGE"));
+                }
+                let [segment] = path.segments.as_slice() else {
+                    return Err(unsupported("MERGE of several relationships at once"));
+                };
+                let relationship = &segment.relationship;
+                let [rel_type] = relationship.types.as_slice() else {
+                    return Err(invalid(
+                        "MERGE needs exactly one type per relationship".to_string(),
+                    ));
+                };
+                if relationship.length.is_some() {
+                    return Err(invalid(
+                        "MERGE cannot merge variable-length relationships".to_string(),
+                    ));
+                }
+                let variable = relationship
+                    .variable
+                    .clone()
+                    .unwrap_or_else(|| "_merge_relationship".to_string());
+                if bound.contains(&variable) {
+                    return Err(already_bound(&variable));
+                }
+                let left = endpoint(&path.start_node, bound, merged, projection)?;
+                let right = endpoint(&segment.end_node, bound, merged, projection)?;
+                let (source, target) = match relationship.direction {
+                    RelationshipDirection::Outgoing => (left, right),
+                    RelationshipDirection::Incoming => (right, left),
+                    RelationshipDirection::Undirected => {
+                        return Err(invalid(
+                            "MERGE needs a direction for the relationship".to_string(),
+                        ))
+                    }
+                };
+                let target = Target::Relationship {
+                    rel_type: rel_type.clone(),
+                    source,
+                    target,
+                };
+                (variable, target, relationship.properties.iter().collect())
+            }
+        };
+
+        let mut properties = properties;
+        properties.sort_by(|a, b| a.0.cmp(b.0));
+        let mut on_create: Vec<(String, Value)> = properties
+            .into_iter()
+            .map(|(property, value)| {
+                let column = projection.column(ValueExpression::Literal(value.clone()));
+                (property.clone(), Value::Returned(column))
+            })
+            .collect();
+        on_create.extend(set_values(&clause.on_create, &variable, projection)?);
+        let on_match = set_values(&clause.on_match, &variable, projection)?;
+        Ok(Self {
+            variable,
+            target,
+            on_create,
+            on_match,
+            set_items: (clause.on_create.len(), clause.on_match.len()),
+        })
+    }
+
+    /// Queue the merge-insert for the rows returned by the reading clauses
+    pub(super) async fn apply(
+        &self,
+        rows: &RecordBatch,
+        config: &GraphConfig,
+        catalog: &LanceCatalog,
+        transaction: &mut WriteTransaction,
+        summary: &mut WriteSummary,
+    ) -> Result<()> {
+        let num_rows = rows.num_rows();
+        if num_rows == 0 {
+            return Ok(());
+        }
+        let table = self.table(rows, config, catalog)?;
+        let schema = Schema::from(table.dataset.schema());
+        for (column, _) in &table.keys {
+            let written = self
+                .on_create
+                .iter()
+                .chain(&self.on_match)
+                .any(|(property, _)| &table.columns[property] == column);
+            if written {
+                return Err(GraphError::InvalidPattern {
+                    message: format!(
+                        "MERGE cannot set the key column '{}' of '{}'",
+                        column, table.owner
+                    ),
+                    location: snafu::Location::new(file!(), line!(), column!()),
+                });
+            }
+        }
+
+        // The input rows: keys, returned values and row order
+        let mut fields = Vec::new();
+        let mut arrays = Vec::new();
+        for (i, (column, values)) in table.keys.iter().enumerate() {
+            if values.null_count() > 0 {
+                return Err(GraphError::ExecutionError {
+                    message: format!(
+                        "MERGE of '{}' cannot look up a NULL '{}'",
+                        table.owner, column
+                    ),
+                    location: snafu::Location::new(file!(), line!(), column!()),
+                });
+            }
+            let data_type = schema.field_with_name(column)?.data_type();
+            arrays.push(cast(values, data_type)?);
+            fields.push(Field::new(key_name(i), data_type.clone(), false));
+        }
+        for (_, value) in self.on_create.iter().chain(&self.on_match) {
+            if let Value::Returned(column) = value {
+                let values = rows.column(*column);
+                arrays.push(values.clone());
+                fields.push(Field::new(
+                    value_name(*column),
+                    values.data_type().clone(),
+                    true,
+                ));
+            }
+        }
+        arrays.push(Arc::new(UInt64Array::from_iter_values(0..num_rows as u64)));
+        fields.push(Field::new(ROW, DataType::UInt64, false));
+        let input = RecordBatch::try_new(Arc::new(Schema::new(fields)), arrays)?;
+        let keys: Vec<usize> = (0..table.keys.len()).collect();
+        let input = first_rows(&input, &keys)?;
+
+        // Joined with the existing rows of those keys
+        let existing_name =
+            |column: &str| format!("{}__{}", self.variable, table.properties[column]);
+        let ctx = SessionContext::new();
+        let existing = ctx
+            .read_table(Arc::new(LanceTableProvider::new(
+                table.dataset.clone(),
+                false,
+                false,
+            )))?
+            .select(
+                schema
+                    .fields()
+                    .iter()
+                    .map(|field| ident(field.name()).alias(existing_name(field.name())))
+                    .chain([lit(true).alias(MATCHED)])
+                    .collect::<Vec<_>>(),
+            )?;
+        let on: Vec<Expr> = table
+            .keys
+            .iter()
+            .enumerate()
+            .map(|(i, (column, _))| ident(key_name(i)).eq(ident(existing_name(column))))
+            .collect();
+        let joined = ctx
+            .read_batch(input)?
+            .join_on(existing, JoinType::Left, on)?
+            .sort(vec![ident(ROW).sort(true, false)])?;
+
+        let mut create = self.values(&self.on_create, &table);
+        let mut update = self.values(&self.on_match, &table);
+        let matched = ident(MATCHED).is_not_null();
+        let mut output = Vec::new();
+        for field in schema.fields() {
+            let name = field.name();
+            let data_type = field.data_type();
+            let (when_matched, when_created) =
+                match table.keys.iter().position(|(column, _)| column == name) {
+                    Some(i) => (ident(key_name(i)), ident(key_name(i))),
+                    None => (
+                        update
+                            .remove(name)
+                            .unwrap_or_else(|| ident(existing_name(name))),
+                        match create.remove(name) {
+                            Some(value) => value,
+                            None => lit(ScalarValue::try_from(data_type)?),
+                        },
+                    ),
+                };
+            output.push(
+                when(matched.clone(), cast_expr(when_matched, data_type.clone()))
+                    .otherwise(cast_expr(when_created, data_type.clone()))?
+                    .alias(name),
+            );
+        }
+        if let Some(column) = create.keys().chain(update.keys()).next() {
+            return Err(GraphError::InvalidPattern {
+                message: format!("The table of '{}' has no column '{}'", table.owner, column),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            });
+        }
+        output.push(matched.alias(MATCHED));
+        let frame = joined.select(output)?;
+        let output_schema = Arc::new(frame.schema().as_arrow().clone());
+        let batch = concat_batches(&output_schema, &frame.collect().await?)?;
+
+        let matched_rows = batch
+            .column(schema.fields().len())
+            .as_boolean()
+            .true_count();
+        let created_rows = batch.num_rows() - matched_rows;
+        let columns = batch.columns()[..schema.fields().len()].to_vec();
+        for (field, values) in schema.fields().iter().zip(&columns) {
+            if !field.is_nullable() && values.null_count() > 0 {
+                return Err(GraphError::InvalidPattern {
+                    message: format!("MERGE of '{}' must set '{}'", table.owner, field.name()),
+                    location: snafu::Location::new(file!(), line!(), column!()),
+                });
+            }
+        }
+        let batch = RecordBatch::try_new(Arc::new(schema.clone()), columns)?;
+        let on = table
+            .keys
+            .iter()
+            .map(|(column, _)| column.clone())
+            .collect();
+        transaction.merge(
+            table.kind,
+            &table.name,
+            on,
+            !self.on_match.is_empty(),
+            batch,
+        )?;
+
+        match self.target {
+            Target::Node { .. } => summary.nodes_created += created_rows,
+            Target::Relationship { .. } => summary.relationships_created += created_rows,
+        }
+        summary.properties_set += created_rows * self.set_items.0 + matched_rows * self.set_items.1;
+        Ok(())
+    }
+
+    /// Resolve the table of the merged node or relationship and its keys in every row
+    fn table(
+        &self,
+        rows: &RecordBatch,
+        config: &GraphConfig,
+        catalog: &LanceCatalog,
+    ) -> Result<Table> {
+        let num_rows = rows.num_rows();
+        let repeated =
+            |value: &str| -> ArrayRef { Arc::new(StringArray::from(vec![value; num_rows])) };
+        let written = self.on_create.iter().chain(&self.on_match);
+        match &self.target {
+            Target::Node { label, keys } => {
+                let mapping =
+                    config
+                        .get_node_mapping(label)
+                        .ok_or_else(|| GraphError::ConfigError {
+                            message: format!("No node mapping found for label '{}'", label),
+                            location: snafu::Location::new(file!(), line!(), column!()),
+                        })?;
+                let dataset = registered(catalog, SourceKind::Node, label)?;
+                let mut key_values: Vec<(String, ArrayRef)> = mapping
+                    .id_fields()
+                    .into_iter()
+                    .zip(keys)
+                    .map(|(field, column)| (field.to_string(), rows.column(*column).clone()))
+                    .collect();
+                if let Some((column, value)) = mapping.label_discriminator() {
+                    key_values.push((column.to_string(), repeated(value)));
+                }
+                let columns = written
+                    .map(|(property, _)| {
+                        settable(
+                            property,
+                            mapping.computed_properties.contains_key(property),
+                            label,
+                        )?;
+                        Ok((property.clone(), mapping.column_for(property).to_string()))
+                    })
+                    .collect::<Result<_>>()?;
+                let properties = dataset
+                    .schema()
+                    .fields
+                    .iter()
+                    .map(|f| (f.name.clone(), mapping.property_for(&f.name).to_string()))
+                    .collect();
+                Ok(Table {
+                    kind: SourceKind::Node,
+                    name: label.clone(),
+                    owner: label.clone(),
+                    dataset,
+                    keys: key_values,
+                    columns,
+                    properties,
+                })
+            }
+            Target::Relationship {
+                rel_type,
+                source,
+                target,
+            } => {
+                let mapping = config.get_relationship_mapping(rel_type).ok_or_else(|| {
+                    GraphError::ConfigError {
+                        message: format!("No relationship mapping found for type '{}'", rel_type),
+                        location: snafu::Location::new(file!(), line!(), column!()),
+                    }
+                })?;
+                if mapping.adjacency_column.is_some() {
+                    return Err(GraphError::UnsupportedFeature {
+                        feature: format!(
+                            "MERGE of '{}' relationships, stored in an adjacency column",
+                            rel_type
+                        ),
+                        location: snafu::Location::new(file!(), line!(), column!()),
+                    });
+                }
+                let dataset = registered(catalog, SourceKind::Relationship, mapping.table_name())?;
+                let mut key_values = Vec::new();
+                let endpoints = [
+                    (source, mapping.source_id_fields()),
+                    (target, mapping.target_id_fields()),
+                ];
+                for (endpoint, fields) in endpoints {
+                    let keys = endpoint.keys(rows, &HashMap::new())?;
+                    if keys.len() != fields.len() {
+                        return Err(GraphError::InvalidPattern {
+                            message: format!(
+                                "'{}' relationships connect nodes with {} key field(s), got {}",
+                                rel_type,
+                                fields.len(),
+                                keys.len()
+                            ),
+                            location: snafu::Location::new(file!(), line!(), column!()),
+                        });
+                    }
+                    key_values.extend(fields.into_iter().map(str::to_string).zip(keys));
+                }
+                if let Some((column, value)) = mapping.type_discriminator() {
+                    key_values.push((column.to_string(), repeated(value)));
+                }
+                let columns = written
+                    .map(|(property, _)| {
+                        settable(
+                            property,
+                            mapping.computed_properties.contains_key(property),
+                            rel_type,
+                        )?;
+                        Ok((property.clone(), mapping.column_for(property).to_string()))
+                    })
+                    .collect::<Result<_>>()?;
+                let properties = dataset
+                    .schema()
+                    .fields
+                    .iter()
+                    .map(|f| (f.name.clone(), mapping.property_for(&f.name).to_string()))
+                    .collect();
+                Ok(Table {
+                    kind: SourceKind::Relationship,
+                    name: mapping.table_name().to_string(),
+                    owner: rel_type.clone(),
+                    dataset,
+                    keys: key_values,
+                    columns,
+                    properties,
+                })
+            }
+        }
+    }
+
+    /// The expression computing each written column, later values winning
+    fn values(&self, values: &[(String, Value)], table: &Table) -> HashMap<String, Expr> {
+        values
+            .iter()
+            .map(|(property, value)| {
+                let expression = match value {
+                    Value::Returned(column) => ident(value_name(*column)),
+                    Value::Existing(expression) => to_df_value_expr(expression),
+                };
+                (table.columns[property].clone(), expression)
+            })
+            .collect()
+    }
+}
+
+/// Plan a relationship endpoint of MERGE, which must be a bare variable the reading
+/// clauses bind or an earlier MERGE clause merges
+fn endpoint(
+    node: &NodePattern,
+    bound: &[String],
+    merged: &HashMap<String, Vec<usize>>,
+    projection: &mut Projection,
+) -> Result<Endpoint> {
+    let bare =
+        node.labels.is_empty() && node.label_expression.is_none() && node.properties.is_empty();
+    match &node.variable {
+        Some(variable) if bare && merged.contains_key(variable) => {
+            Ok(Endpoint::Merged(merged[variable].clone()))
+        }
+        Some(variable) if bare && bound.contains(variable) => {
+            Ok(Endpoint::Bound(projection.id_column(variable)))
+        }
+        _ => Err(GraphError::UnsupportedFeature {
+            feature: "MERGE of a relationship to nodes not bound or merged before".to_string(),
+            location: snafu::Location::new(file!(), line!(), column!()),
+        }),
+    }
+}
+
+/// The values of SET items on the merged `variable`: returned by the reading clauses,
+/// or computed from the merged node or relationship when they read it
+fn set_values(
+    items: &[SetItem],
+    variable: &str,
+    projection: &mut Projection,
+) -> Result<Vec<(String, Value)>> {
+    let unsupported = |feature: String| GraphError::UnsupportedFeature {
+        feature,
+        location: snafu::Location::new(file!(), line!(), column!()),
+    };
+    items
+        .iter()
+        .map(|item| {
+            if item.property.variable != variable {
+                return Err(unsupported(format!(
+                    "SET of '{}' in MERGE of '{}'",
+                    item.property.variable, variable
+                )));
+            }
+            let mut referenced = Vec::new();
+            collect_referenced_variables(&item.value, &mut referenced);
+            let value = if !referenced.contains(&variable) {
+                Value::Returned(projection.column(item.value.clone()))
+            } else if referenced.iter().all(|v| *v == variable) {
+                Value::Existing(item.value.clone())
+            } else {
+                return Err(unsupported(format!(
+                    "SET values reading '{}' and other variables",
+                    variable
+                )));
+            };
+            Ok((item.property.property.clone(), value))
+        })
+        .collect()
+}
+
+fn key_name(index: usize) -> String {
+    format!("_merge_key_{}", index)
+}
+
+fn value_name(column: usize) -> String {
+    format!("_merge_value_{}", column)
+}
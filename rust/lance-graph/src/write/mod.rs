@@ -0,0 +1,283 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Write clauses
+//!
+//! A query ending in write clauses runs in two steps. Its reading clauses are planned
+//! and executed like any other query, returning the values the write clauses need
+//! instead of a RETURN clause; a query without reading clauses reads a single row. The
+//! write clauses then turn every returned row into rows of the mapped Lance datasets,
+//! which are committed together.
+//!
+//! The rows a read query returns can also be written to a dataset of their own with
+//! [`CypherQuery::write_to`].
+
+mod create;
+mod delete;
+mod merge;
+mod results;
+mod set;
+mod transaction;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use arrow_array::cast::AsArray;
+use arrow_array::{Array, ArrayRef, RecordBatch};
+use arrow_schema::DataType;
+use lance::Dataset;
+
+use crate::ast::{PropertyValue, ReturnItem, UnwindClause, ValueExpression, WriteClause};
+use crate::error::{GraphError, Result};
+use crate::lance_catalog::LanceCatalog;
+use crate::parameters::{bind_parameters, resolve_parameters};
+use crate::query::CypherQuery;
+use crate::source_catalog::SourceKind;
+use create::CreatePlan;
+use delete::DeletePlan;
+use merge::MergePlan;
+pub(crate) use results::write_results;
+use set::SetPlan;
+use transaction::WriteTransaction;
+
+/// The changes a write query made to the graph
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WriteSummary {
+    /// Nodes added by CREATE or MERGE
+    pub nodes_created: usize,
+    /// Relationships added by CREATE or MERGE
+    pub relationships_created: usize,
+    /// Properties assigned by SET items
+    pub properties_set: usize,
+    /// Nodes removed by DELETE
+    pub nodes_deleted: usize,
+    /// Relationships removed by DELETE, including those of detached nodes
+    pub relationships_deleted: usize,
+}
+
+/// The expressions the reading clauses return for the write clauses, one column each
+#[derive(Debug)]
+struct Projection {
+    items: Vec<ReturnItem>,
+    /// Returned `id()` column of each bound node variable
+    bound_ids: HashMap<String, usize>,
+}
+
+impl Projection {
+    fn new() -> Self {
+        // The first column keeps the projection non-empty when nothing else is needed
+        Self {
+            items: vec![ReturnItem {
+                expression: ValueExpression::Literal(PropertyValue::Integer(0)),
+                alias: Some("_write_row".to_string()),
+            }],
+            bound_ids: HashMap::new(),
+        }
+    }
+
+    /// Return `expression` from the reading clauses, giving its column
+    fn column(&mut self, expression: ValueExpression) -> usize {
+        self.items.push(ReturnItem {
+            expression,
+            alias: Some(format!("_write_{}", self.items.len())),
+        });
+        self.items.len() - 1
+    }
+
+    /// The column holding the `id()` of a bound node
+    fn id_column(&mut self, variable: &str) -> usize {
+        if let Some(column) = self.bound_ids.get(variable) {
+            return *column;
+        }
+        let column = self.column(ValueExpression::Function {
+            name: "id".to_string(),
+            distinct: false,
+            args: vec![ValueExpression::Variable(variable.to_string())],
+        });
+        self.bound_ids.insert(variable.to_string(), column);
+        column
+    }
+}
+
+/// A node a written relationship connects
+#[derive(Debug, Clone)]
+enum Endpoint {
+    /// A node created by the same query, by variable
+    Created(String),
+    /// A node bound by the reading clauses, by the returned column holding its `id()`
+    Bound(usize),
+    /// A node merged by the same query, by the returned columns holding its keys
+    Merged(Vec<usize>),
+}
+
+impl Endpoint {
+    /// The key columns of the node in every row; `created` holds those of created nodes
+    fn keys(
+        &self,
+        rows: &RecordBatch,
+        created: &HashMap<&str, Vec<ArrayRef>>,
+    ) -> Result<Vec<ArrayRef>> {
+        match self {
+            Self::Created(variable) => Ok(created[variable.as_str()].clone()),
+            Self::Merged(columns) => Ok(columns.iter().map(|c| rows.column(*c).clone()).collect()),
+            // `id()` is a struct for composite keys
+            Self::Bound(column) => {
+                let ids = rows.column(*column);
+                if ids.null_count() > 0 {
+                    return Err(GraphError::ExecutionError {
+                        message: "A relationship cannot connect a missing node".to_string(),
+                        location: snafu::Location::new(file!(), line!(), column!()),
+                    });
+                }
+                Ok(match ids.data_type() {
+                    DataType::Struct(_) => ids.as_struct().columns().to_vec(),
+                    _ => vec![ids.clone()],
+                })
+            }
+        }
+    }
+}
+
+/// Reject writing a computed property
+fn settable(property: &str, computed: bool, owner: &str) -> Result<()> {
+    if computed {
+        return Err(GraphError::InvalidPattern {
+            message: format!(
+                "Property '{}' of '{}' is computed and cannot be set",
+                property, owner
+            ),
+            location: snafu::Location::new(file!(), line!(), column!()),
+        });
+    }
+    Ok(())
+}
+
+/// The dataset registered for a label or relationship table
+fn registered(catalog: &LanceCatalog, kind: SourceKind, name: &str) -> Result<Arc<Dataset>> {
+    catalog
+        .dataset(kind, name)
+        .ok_or_else(|| GraphError::ConfigError {
+            message: format!("No Lance dataset is registered for '{}'", name),
+            location: snafu::Location::new(file!(), line!(), column!()),
+        })
+}
+
+/// Error for write clauses reaching a read-only execution path
+pub(crate) fn write_clauses_unsupported() -> GraphError {
+    GraphError::UnsupportedFeature {
+        feature: "Write clauses outside CypherQuery::execute_write".to_string(),
+        location: snafu::Location::new(file!(), line!(), column!()),
+    }
+}
+
+/// Run the reading clauses of `query`, then apply its write clauses to `catalog`
+pub(crate) async fn execute_write(
+    query: &CypherQuery,
+    catalog: &mut LanceCatalog,
+) -> Result<WriteSummary> {
+    let config = query
+        .config()
+        .cloned()
+        .ok_or_else(|| GraphError::ConfigError {
+            message: "Graph configuration is required for query execution".to_string(),
+            location: snafu::Location::new(file!(), line!(), column!()),
+        })?;
+    let ast = query.ast();
+    let unsupported = |feature: &str| GraphError::UnsupportedFeature {
+        feature: feature.to_string(),
+        location: snafu::Location::new(file!(), line!(), column!()),
+    };
+    if ast.write_clauses.is_empty() {
+        return Err(GraphError::PlanError {
+            message: "execute_write needs a query with write clauses".to_string(),
+            location: snafu::Location::new(file!(), line!(), column!()),
+        });
+    }
+    if !ast.return_clause.items.is_empty() {
+        return Err(unsupported("RETURN after write clauses"));
+    }
+    if ast.explain.is_some() {
+        return Err(unsupported(
+            "EXPLAIN and PROFILE of queries with write clauses",
+        ));
+    }
+    if !ast.unions.is_empty() {
+        return Err(unsupported("UNION of queries with write clauses"));
+    }
+
+    // Write clauses keep the values of their parameters, not just the returned ones
+    let parameters = resolve_parameters(query.parameters(), query.scalar_parameters())?;
+    let mut ast = ast.clone();
+    bind_parameters(&mut ast, &parameters)?;
+
+    let bound = query.variables();
+    let mut projection = Projection::new();
+    let mut merges = Vec::new();
+    let mut merged = HashMap::new();
+    let mut patterns = Vec::new();
+    let mut assignments = Vec::new();
+    let mut deletes = Vec::new();
+    for clause in &ast.write_clauses {
+        match clause {
+            WriteClause::Create(create) => patterns.extend(&create.patterns),
+            WriteClause::Set(set) => assignments.extend(
+                set.items
+                    .iter()
+                    .map(|item| (&item.property, Some(&item.value))),
+            ),
+            WriteClause::Remove(remove) => {
+                assignments.extend(remove.properties.iter().map(|p| (p, None)))
+            }
+            WriteClause::Delete(delete) => deletes.push(delete),
+            WriteClause::Merge(merge) => merges.push(MergePlan::try_new(
+                merge,
+                &config,
+                &bound,
+                &mut projection,
+                &mut merged,
+            )?),
+        }
+    }
+    let create = CreatePlan::try_new(patterns, &bound, &merged, &mut projection)?;
+    let set = SetPlan::try_new(assignments, &ast, &config, &mut projection)?;
+    let delete = DeletePlan::try_new(deletes, &ast, &config, &mut projection)?;
+
+    let mut read = ast;
+    read.write_clauses.clear();
+    read.return_clause.items = projection.items;
+    if read.match_clauses.is_empty()
+        && read.unwind_clauses.is_empty()
+        && read.call_clauses.is_empty()
+        && read.with_clauses.is_empty()
+    {
+        read.unwind_clauses.push(UnwindClause {
+            expression: ValueExpression::Literal(PropertyValue::List(vec![
+                PropertyValue::Integer(0),
+            ])),
+            alias: "_write_input".to_string(),
+            preceding_matches: 0,
+        });
+    }
+    let rows = query
+        .with_ast(read)
+        .execute_with_catalog(Arc::new(catalog.clone()))
+        .await?;
+
+    let mut transaction = WriteTransaction::new();
+    let mut summary = WriteSummary::default();
+    for merge in &merges {
+        merge
+            .apply(&rows, &config, catalog, &mut transaction, &mut summary)
+            .await?;
+    }
+    create
+        .apply(&rows, &config, catalog, &mut transaction, &mut summary)
+        .await?;
+    set.apply(&rows, catalog, &mut transaction, &mut summary)
+        .await?;
+    delete
+        .apply(&rows, &config, catalog, &mut transaction, &mut summary)
+        .await?;
+    transaction.commit(catalog).await?;
+    Ok(summary)
+}
@@ -0,0 +1,68 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Query results written to a Lance dataset
+//!
+//! The result batches are streamed into the dataset as they are computed, so a result
+//! larger than memory can be written. Lance column names cannot contain dots, so
+//! unaliased columns such as `p.name` are written as `p_name`.
+
+use std::sync::Arc;
+
+use arrow_array::RecordBatch;
+use arrow_schema::{Field, Schema};
+use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
+use datafusion::physical_plan::SendableRecordBatchStream;
+use futures::StreamExt;
+use lance::dataset::{InsertBuilder, WriteMode, WriteParams};
+use lance::Dataset;
+
+use crate::error::Result;
+use crate::query::CypherQuery;
+use crate::source_catalog::GraphSourceCatalog;
+
+/// Write the rows `query` returns from `catalog` to the dataset at `uri`
+pub(crate) async fn write_results(
+    query: &CypherQuery,
+    catalog: Arc<dyn GraphSourceCatalog>,
+    uri: &str,
+    mode: WriteMode,
+) -> Result<Dataset> {
+    let stream = query.execute_stream_with_catalog(catalog).await?;
+    let params = WriteParams {
+        mode,
+        ..Default::default()
+    };
+    let dataset = InsertBuilder::new(uri)
+        .with_params(&params)
+        .execute_stream(lance_columns(stream))
+        .await?;
+    Ok(dataset)
+}
+
+/// The stream with dots in its column names replaced by underscores
+fn lance_columns(stream: SendableRecordBatchStream) -> SendableRecordBatchStream {
+    let schema = stream.schema();
+    if !schema.fields().iter().any(|f| f.name().contains('.')) {
+        return stream;
+    }
+    let renamed = Arc::new(Schema::new_with_metadata(
+        schema
+            .fields()
+            .iter()
+            .map(|f| Field::clone(f).with_name(f.name().replace('.', "_")))
+            .collect::<Vec<_>>(),
+        schema.metadata().clone(),
+    ));
+    let batches = {
+        let renamed = renamed.clone();
+        stream.map(move |batch| -> datafusion::common::Result<RecordBatch> {
+            let batch = batch?;
+            Ok(RecordBatch::try_new(
+                renamed.clone(),
+                batch.columns().to_vec(),
+            )?)
+        })
+    };
+    Box::pin(RecordBatchStreamAdapter::new(renamed, batches))
+}
@@ -0,0 +1,682 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! SET and REMOVE
+//!
+//! SET and REMOVE change properties of the nodes and relationships the reading clauses
+//! bind; REMOVE sets them to NULL. The reading clauses return the keys of every changed
+//! row and its new values, and each bound variable becomes Lance updates of the rows
+//! with those keys: values shared by all rows are assigned by a SQL update filtered by
+//! the keys, others by a merge-insert that only updates rows whose key it is given. A
+//! node or relationship bound in several rows takes the values of the first one, and
+//! relationships are told apart by the nodes they connect, so all relationships of a
+//! type between two nodes change together.
+
+use std::sync::Arc;
+
+use arrow::compute::kernels::boolean::and;
+use arrow::compute::{cast, filter_record_batch, is_not_null};
+use arrow_array::{new_null_array, Array, ArrayRef, BooleanArray, RecordBatch, StringArray};
+use arrow_schema::{Field, Schema};
+use datafusion::common::ScalarValue;
+use datafusion::logical_expr::lit;
+use datafusion_sql::unparser::expr_to_sql;
+
+use crate::ast::{CypherQuery, GraphPattern, PropertyRef, ValueExpression};
+use crate::config::{GraphConfig, NodeMapping, RelationshipMapping};
+use crate::error::{GraphError, Result};
+use crate::lance_catalog::LanceCatalog;
+use crate::source_catalog::SourceKind;
+use crate::write::transaction::{first_rows, WriteTransaction};
+use crate::write::{registered, settable, Projection, WriteSummary};
+
+/// Most keys one SQL filter lists, so that the filters of large writes stay quick to
+/// parse and plan
+pub(super) const KEYS_PER_FILTER: usize = 1000;
+
+/// What a changed variable is bound to
+#[derive(Debug, Clone, PartialEq)]
+enum Target {
+    Node(String),
+    Relationship(String),
+}
+
+/// A node or relationship variable the reading clauses bind, by its dataset and keys
+#[derive(Debug)]
+pub(super) struct Bound {
+    pub(super) variable: String,
+    pub(super) kind: SourceKind,
+    /// Name of the dataset
+    pub(super) name: String,
+    /// Label or relationship type, for messages
+    pub(super) owner: String,
+    /// Key columns and the returned columns holding their values
+    pub(super) keys: Vec<(String, usize)>,
+    /// Label or type column of a shared table, and its value
+    pub(super) discriminator: Option<(String, String)>,
+}
+
+/// The changes to the properties of one bound variable
+#[derive(Debug)]
+struct Update {
+    bound: Bound,
+    /// Each changed property, its column and the returned column of its new value,
+    /// none to remove it
+    assignments: Vec<(String, String, Option<usize>)>,
+}
+
+/// What the SET and REMOVE clauses of a query change, and the values they need
+#[derive(Debug)]
+pub(crate) struct SetPlan {
+    updates: Vec<Update>,
+}
+
+impl SetPlan {
+    /// Plan the assignments of the query's SET and REMOVE clauses, in order, where a
+    /// missing value removes the property
+    pub(super) fn try_new<'a>(
+        assignments: impl IntoIterator<Item = (&'a PropertyRef, Option<&'a ValueExpression>)>,
+        ast: &CypherQuery,
+        config: &GraphConfig,
+        projection: &mut Projection,
+    ) -> Result<Self> {
+        let mut plan = Self {
+            updates: Vec::new(),
+        };
+        for (property, value) in assignments {
+            let index = match plan
+                .updates
+                .iter()
+                .position(|u| u.bound.variable == property.variable)
+            {
+                Some(index) => index,
+                None => {
+                    let bound = Bound::try_new(&property.variable, "SET", ast, config, projection)?;
+                    plan.updates.push(Update {
+                        bound,
+                        assignments: Vec::new(),
+                    });
+                    plan.updates.len() - 1
+                }
+            };
+            let update = &mut plan.updates[index];
+            let column = update.bound.settable_column(&property.property, config)?;
+            let value = value.map(|value| projection.column(value.clone()));
+            update
+                .assignments
+                .retain(|(p, _, _)| *p != property.property);
+            update
+                .assignments
+                .push((property.property.clone(), column, value));
+        }
+        Ok(plan)
+    }
+
+    /// Queue the updates for the rows returned by the reading clauses
+    pub(super) async fn apply(
+        &self,
+        rows: &RecordBatch,
+        catalog: &LanceCatalog,
+        transaction: &mut WriteTransaction,
+        summary: &mut WriteSummary,
+    ) -> Result<()> {
+        for Update { bound, assignments } in &self.updates {
+            let dataset = registered(catalog, bound.kind, &bound.name)?;
+            let schema = Schema::from(dataset.schema());
+            let field = |column: &str| bound.field(&schema, column);
+            for (property, column, value) in assignments {
+                if value.is_none() && !field(column)?.is_nullable() {
+                    return Err(GraphError::InvalidPattern {
+                        message: format!(
+                            "REMOVE cannot clear '{}' of '{}', which is required",
+                            property, bound.owner
+                        ),
+                        location: snafu::Location::new(file!(), line!(), column!()),
+                    });
+                }
+            }
+            let set: Vec<_> = assignments
+                .iter()
+                .filter_map(|(property, column, value)| value.map(|v| (property, column, v)))
+                .collect();
+            let values: Vec<_> = set.iter().map(|(_, c, v)| (c.as_str(), *v)).collect();
+            let batch = bound.rows(rows, &schema, &values)?;
+            if batch.num_rows() == 0 {
+                continue;
+            }
+            let num_rows = batch.num_rows();
+            summary.properties_set += num_rows * assignments.len();
+
+            let values = &batch.columns()[bound.keys.len()..];
+            for ((property, column, _), values) in set.iter().zip(values) {
+                if !field(column)?.is_nullable() && values.null_count() > 0 {
+                    return Err(GraphError::InvalidPattern {
+                        message: format!(
+                            "SET cannot clear '{}' of '{}', which is required",
+                            property, bound.owner
+                        ),
+                        location: snafu::Location::new(file!(), line!(), column!()),
+                    });
+                }
+            }
+
+            // Each row's own values, set by key
+            let shared = values
+                .iter()
+                .map(shared_value)
+                .collect::<Result<Vec<_>>>()?;
+            if shared.iter().any(Option::is_none) {
+                let mut columns: Vec<(&str, ArrayRef)> = bound
+                    .keys
+                    .iter()
+                    .map(|(column, _)| column.as_str())
+                    .chain(set.iter().map(|(_, column, _)| column.as_str()))
+                    .zip(batch.columns().iter().cloned())
+                    .collect();
+                for (_, column, value) in assignments {
+                    if value.is_none() {
+                        let field = field(column)?;
+                        columns.push((column, new_null_array(field.data_type(), num_rows)));
+                    }
+                }
+                if let Some((column, value)) = &bound.discriminator {
+                    let field = field(column)?;
+                    let values = StringArray::from(vec![value.as_str(); num_rows]);
+                    columns.push((column, cast(&values, field.data_type())?));
+                }
+                // Lance takes the columns in the order and with the fields of the dataset
+                columns.sort_by_key(|(column, _)| schema.index_of(column).ok());
+                let mut fields = Vec::new();
+                let mut arrays = Vec::new();
+                for (column, values) in columns {
+                    fields.push(field(column)?);
+                    arrays.push(values);
+                }
+                let mut on: Vec<String> = bound.keys.iter().map(|(c, _)| c.clone()).collect();
+                on.extend(bound.discriminator.iter().map(|(c, _)| c.clone()));
+                let batch = RecordBatch::try_new(Arc::new(Schema::new(fields)), arrays)?;
+                transaction.update_rows(bound.kind, &bound.name, on, batch);
+                continue;
+            }
+
+            // Shared values, assigned to the rows of all keys
+            let mut shared = shared.into_iter().flatten();
+            let assignments: Vec<_> = assignments
+                .iter()
+                .map(|(_, column, value)| {
+                    let value = match value {
+                        Some(_) => shared.next().expect("a returned column per SET value"),
+                        None => "NULL".to_string(),
+                    };
+                    (column.clone(), value)
+                })
+                .collect();
+            for filter in bound.filters(&batch)? {
+                transaction.update(bound.kind, &bound.name, filter, assignments.clone());
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Bound {
+    /// Resolve the table of `variable`, which `clause` changes, returning the values of
+    /// its key columns
+    pub(super) fn try_new(
+        variable: &str,
+        clause: &str,
+        ast: &CypherQuery,
+        config: &GraphConfig,
+        projection: &mut Projection,
+    ) -> Result<Self> {
+        let (kind, name, owner, key_fields, discriminator) = match binding(ast, variable, clause)? {
+            Target::Node(label) => {
+                let mapping = node_mapping(config, &label)?;
+                (
+                    SourceKind::Node,
+                    label.clone(),
+                    label.clone(),
+                    mapping.id_fields(),
+                    mapping.label_discriminator(),
+                )
+            }
+            Target::Relationship(rel_type) => {
+                let mapping = relationship_mapping(config, &rel_type)?;
+                if mapping.adjacency_column.is_some() {
+                    return Err(GraphError::UnsupportedFeature {
+                        feature: format!(
+                            "{} of '{}' relationships, stored in an adjacency column",
+                            clause, rel_type
+                        ),
+                        location: snafu::Location::new(file!(), line!(), column!()),
+                    });
+                }
+                let mut key_fields = mapping.source_id_fields();
+                key_fields.extend(mapping.target_id_fields());
+                (
+                    SourceKind::Relationship,
+                    mapping.table_name().to_string(),
+                    rel_type.clone(),
+                    key_fields,
+                    mapping.type_discriminator(),
+                )
+            }
+        };
+        let mut bound = Self {
+            variable: variable.to_string(),
+            kind,
+            name,
+            owner,
+            keys: Vec::new(),
+            discriminator: discriminator.map(|(c, v)| (c.to_string(), v.to_string())),
+        };
+        for field in key_fields {
+            let property = bound.property(field, config)?;
+            let column = projection.column(ValueExpression::Property(PropertyRef::new(
+                variable, &property,
+            )));
+            bound.keys.push((field.to_string(), column));
+        }
+        Ok(bound)
+    }
+
+    /// The column storing a property, which must be a stored one other than the keys
+    fn settable_column(&self, property: &str, config: &GraphConfig) -> Result<String> {
+        let (column, computed) = match self.kind {
+            SourceKind::Node => {
+                let mapping = node_mapping(config, &self.owner)?;
+                (
+                    mapping.column_for(property).to_string(),
+                    mapping.computed_properties.contains_key(property),
+                )
+            }
+            SourceKind::Relationship => {
+                let mapping = relationship_mapping(config, &self.owner)?;
+                (
+                    mapping.column_for(property).to_string(),
+                    mapping.computed_properties.contains_key(property),
+                )
+            }
+        };
+        settable(property, computed, &self.owner)?;
+        let discriminator = self.discriminator.as_ref().map(|(c, _)| c);
+        if self.keys.iter().any(|(key, _)| *key == column) || discriminator == Some(&column) {
+            return Err(GraphError::InvalidPattern {
+                message: format!(
+                    "SET cannot change the key property '{}' of '{}'",
+                    property, self.owner
+                ),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            });
+        }
+        Ok(column)
+    }
+
+    /// The property stored in a column
+    fn property(&self, column: &str, config: &GraphConfig) -> Result<String> {
+        Ok(match self.kind {
+            SourceKind::Node => node_mapping(config, &self.owner)?.property_for(column),
+            SourceKind::Relationship => {
+                relationship_mapping(config, &self.owner)?.property_for(column)
+            }
+        }
+        .to_string())
+    }
+
+    /// A column of the dataset
+    pub(super) fn field(&self, schema: &Schema, column: &str) -> Result<Field> {
+        schema
+            .field_with_name(column)
+            .cloned()
+            .map_err(|_| GraphError::InvalidPattern {
+                message: format!("The table of '{}' has no column '{}'", self.owner, column),
+                location: snafu::Location::new(file!(), line!(), column!()),
+            })
+    }
+
+    /// The key columns of every bound row, followed by the `values` columns, cast to
+    /// the dataset's columns; rows not binding the variable are dropped, and of rows
+    /// with the same keys only the first is kept
+    pub(super) fn rows(
+        &self,
+        rows: &RecordBatch,
+        schema: &Schema,
+        values: &[(&str, usize)],
+    ) -> Result<RecordBatch> {
+        let mut fields = Vec::new();
+        let mut arrays = Vec::new();
+        let keys = self.keys.iter().map(|(c, i)| (c.as_str(), *i));
+        for (column, index) in keys.chain(values.iter().copied()) {
+            let field = self.field(schema, column)?;
+            arrays.push(cast(rows.column(index), field.data_type())?);
+            fields.push(Field::new(column, field.data_type().clone(), true));
+        }
+        let batch = RecordBatch::try_new(Arc::new(Schema::new(fields)), arrays)?;
+        let mut bound = BooleanArray::from(vec![true; batch.num_rows()]);
+        for key in &batch.columns()[..self.keys.len()] {
+            bound = and(&bound, &is_not_null(key)?)?;
+        }
+        let batch = filter_record_batch(&batch, &bound)?;
+        let keys: Vec<usize> = (0..self.keys.len()).collect();
+        first_rows(&batch, &keys)
+    }
+
+    /// SQL filters that together match the rows of the dataset with the keys of
+    /// `batch`, which starts with the key columns
+    pub(super) fn filters(&self, batch: &RecordBatch) -> Result<Vec<String>> {
+        let columns: Vec<&str> = self.keys.iter().map(|(c, _)| c.as_str()).collect();
+        key_filters(&columns, &batch.columns()[..columns.len()])?
+            .into_iter()
+            .map(|filter| discriminated(filter, self.discriminator.as_ref()))
+            .collect()
+    }
+}
+
+fn node_mapping<'a>(config: &'a GraphConfig, label: &str) -> Result<&'a NodeMapping> {
+    config
+        .get_node_mapping(label)
+        .ok_or_else(|| GraphError::ConfigError {
+            message: format!("No node mapping found for label '{}'", label),
+            location: snafu::Location::new(file!(), line!(), column!()),
+        })
+}
+
+fn relationship_mapping<'a>(
+    config: &'a GraphConfig,
+    rel_type: &str,
+) -> Result<&'a RelationshipMapping> {
+    config
+        .get_relationship_mapping(rel_type)
+        .ok_or_else(|| GraphError::ConfigError {
+            message: format!("No relationship mapping found for type '{}'", rel_type),
+            location: snafu::Location::new(file!(), line!(), column!()),
+        })
+}
+
+/// The label or relationship type the reading clauses bind `variable` to
+fn binding(ast: &CypherQuery, variable: &str, clause: &str) -> Result<Target> {
+    let unsupported = |feature: String| GraphError::UnsupportedFeature {
+        feature,
+        location: snafu::Location::new(file!(), line!(), column!()),
+    };
+    let mut found = None;
+    for pattern in ast.all_match_clauses().flat_map(|m| &m.patterns) {
+        let (nodes, relationships) = match pattern {
+            GraphPattern::Node(node) => (vec![node], Vec::new()),
+            GraphPattern::Path(path) => (
+                std::iter::once(&path.start_node)
+                    .chain(path.segments.iter().map(|s| &s.end_node))
+                    .collect(),
+                path.segments.iter().map(|s| &s.relationship).collect(),
+            ),
+        };
+        for node in nodes {
+            if node.variable.as_deref() == Some(variable) && !node.labels.is_empty() {
+                let [label] = node.labels.as_slice() else {
+                    return Err(unsupported(format!(
+                        "{} of '{}', which has several labels",
+                        clause, variable
+                    )));
+                };
+                found = found.or(Some(Target::Node(label.clone())));
+            }
+        }
+        for relationship in relationships {
+            if relationship.variable.as_deref() == Some(variable) {
+                match relationship.types.as_slice() {
+                    [rel_type] if relationship.length.is_none() => {
+                        found = found.or(Some(Target::Relationship(rel_type.clone())))
+                    }
+                    _ => {
+                        return Err(unsupported(format!(
+                            "{} of '{}', which is not bound to one relationship type",
+                            clause, variable
+                        )))
+                    }
+                }
+            }
+        }
+    }
+    found.ok_or_else(|| GraphError::InvalidPattern {
+        message: format!(
+            "{} changes nodes and relationships a MATCH binds with a label or type, not '{}'",
+            clause, variable
+        ),
+        location: snafu::Location::new(file!(), line!(), column!()),
+    })
+}
+
+/// The SQL literal of the value every row has, if they all have the same one
+fn shared_value(values: &ArrayRef) -> Result<Option<String>> {
+    let first = sql_value(values, 0)?;
+    for row in 1..values.len() {
+        if sql_value(values, row)? != first {
+            return Ok(None);
+        }
+    }
+    Ok(Some(first))
+}
+
+/// SQL filters that together match the rows whose `columns` hold the values of one row
+/// of `keys`, each listing at most [`KEYS_PER_FILTER`] keys; rows with a NULL key match
+/// nothing and are left out
+pub(super) fn key_filters(columns: &[&str], keys: &[ArrayRef]) -> Result<Vec<String>> {
+    let num_rows = keys.first().map_or(0, |k| k.len());
+    let rows: Vec<usize> = (0..num_rows)
+        .filter(|row| keys.iter().all(|k| k.is_valid(*row)))
+        .collect();
+    rows.chunks(KEYS_PER_FILTER)
+        .map(|rows| key_filter(columns, keys, rows))
+        .collect()
+}
+
+/// A SQL filter matching the rows whose `columns` hold the values of `keys` at one of
+/// `rows`
+fn key_filter(columns: &[&str], keys: &[ArrayRef], rows: &[usize]) -> Result<String> {
+    if let ([column], [keys]) = (columns, keys) {
+        let values = rows
+            .iter()
+            .map(|row| sql_value(keys, *row))
+            .collect::<Result<Vec<_>>>()?;
+        return Ok(format!("{} IN ({})", quoted(column), values.join(", ")));
+    }
+    let matches = rows
+        .iter()
+        .map(|row| {
+            let conditions = columns
+                .iter()
+                .zip(keys)
+                .map(|(column, keys)| {
+                    Ok(format!("{} = {}", quoted(column), sql_value(keys, *row)?))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            Ok(format!("({})", conditions.join(" AND ")))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(matches.join(" OR "))
+}
+
+/// Restrict a SQL filter to the rows of one label or type of a shared table
+pub(super) fn discriminated(
+    filter: String,
+    discriminator: Option<&(String, String)>,
+) -> Result<String> {
+    match discriminator {
+        Some((column, value)) => {
+            let value = sql_literal(ScalarValue::from(value.as_str()))?;
+            Ok(format!("({}) AND {} = {}", filter, quoted(column), value))
+        }
+        None => Ok(filter),
+    }
+}
+
+/// One value of an array as a SQL literal
+fn sql_value(values: &ArrayRef, row: usize) -> Result<String> {
+    sql_literal(ScalarValue::try_from_array(values, row)?)
+}
+
+fn sql_literal(value: ScalarValue) -> Result<String> {
+    if value.is_null() {
+        return Ok("NULL".to_string());
+    }
+    Ok(expr_to_sql(&lit(value))?.to_string())
+}
+
+fn quoted(column: &str) -> String {
+    format!("`{}`", column)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::{Int32Array, Int64Array};
+    use arrow_schema::DataType;
+
+    fn ints(values: Vec<Option<i64>>) -> ArrayRef {
+        Arc::new(Int64Array::from(values))
+    }
+
+    fn strings(values: Vec<Option<&str>>) -> ArrayRef {
+        Arc::new(StringArray::from(values))
+    }
+
+    fn person() -> Bound {
+        Bound {
+            variable: "p".to_string(),
+            kind: SourceKind::Node,
+            name: "Person".to_string(),
+            owner: "Person".to_string(),
+            keys: vec![("id".to_string(), 1)],
+            discriminator: None,
+        }
+    }
+
+    #[test]
+    fn test_key_filters_of_one_column() {
+        let keys = [ints(vec![Some(1), Some(2), Some(3)])];
+        assert_eq!(
+            key_filters(&["id"], &keys).unwrap(),
+            vec!["`id` IN (1, 2, 3)"]
+        );
+    }
+
+    #[test]
+    fn test_key_filters_of_a_composite_key() {
+        let keys = [
+            ints(vec![Some(1), Some(2)]),
+            strings(vec![Some("a"), Some("b")]),
+        ];
+        assert_eq!(
+            key_filters(&["id", "region"], &keys).unwrap(),
+            vec!["(`id` = 1 AND `region` = 'a') OR (`id` = 2 AND `region` = 'b')"]
+        );
+    }
+
+    #[test]
+    fn test_key_filters_skip_null_keys() {
+        let keys = [ints(vec![Some(1), None, Some(3)])];
+        assert_eq!(key_filters(&["id"], &keys).unwrap(), vec!["`id` IN (1, 3)"]);
+
+        // A NULL in any column of a composite key matches no row
+        let keys = [ints(vec![Some(1), Some(2)]), strings(vec![None, Some("b")])];
+        assert_eq!(
+            key_filters(&["id", "region"], &keys).unwrap(),
+            vec!["(`id` = 2 AND `region` = 'b')"]
+        );
+
+        let keys = [ints(vec![None, None])];
+        assert!(key_filters(&["id"], &keys).unwrap().is_empty());
+        assert!(key_filters(&["id"], &[ints(vec![])]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_key_filters_are_chunked() {
+        let num_keys = 2 * KEYS_PER_FILTER + 5;
+        let keys = [ints((0..num_keys as i64).map(Some).collect())];
+        let filters = key_filters(&["id"], &keys).unwrap();
+        assert_eq!(filters.len(), 3);
+        let listed = |filter: &str| filter.matches(", ").count() + 1;
+        assert_eq!(listed(&filters[0]), KEYS_PER_FILTER);
+        assert_eq!(listed(&filters[1]), KEYS_PER_FILTER);
+        assert_eq!(filters[2], "`id` IN (2000, 2001, 2002, 2003, 2004)");
+    }
+
+    #[test]
+    fn test_discriminated() {
+        let filter = "`id` IN (1)".to_string();
+        assert_eq!(discriminated(filter.clone(), None).unwrap(), filter);
+        let discriminator = ("label".to_string(), "Person".to_string());
+        assert_eq!(
+            discriminated(filter, Some(&discriminator)).unwrap(),
+            "(`id` IN (1)) AND `label` = 'Person'"
+        );
+    }
+
+    #[test]
+    fn test_shared_value() {
+        assert_eq!(
+            shared_value(&ints(vec![Some(7), Some(7)])).unwrap(),
+            Some("7".to_string())
+        );
+        assert_eq!(shared_value(&ints(vec![Some(7), Some(8)])).unwrap(), None);
+        assert_eq!(shared_value(&ints(vec![Some(7), None])).unwrap(), None);
+        assert_eq!(
+            shared_value(&strings(vec![None, None])).unwrap(),
+            Some("NULL".to_string())
+        );
+    }
+
+    #[test]
+    fn test_bound_rows_keep_the_first_row_of_each_key() {
+        let rows = RecordBatch::try_from_iter(vec![
+            ("_write_row", ints(vec![Some(0); 4])),
+            ("id", ints(vec![Some(1), None, Some(1), Some(2)])),
+            (
+                "name",
+                strings(vec![
+                    Some("first"),
+                    Some("unbound"),
+                    Some("second"),
+                    Some("other"),
+                ]),
+            ),
+        ])
+        .unwrap();
+        let schema = Schema::new(vec![
+            Field::new("id", DataType::Int32, false),
+            Field::new("name", DataType::Utf8, true),
+        ]);
+        let batch = person().rows(&rows, &schema, &[("name", 2)]).unwrap();
+
+        assert_eq!(batch.schema().field(0).data_type(), &DataType::Int32);
+        let ids = batch
+            .column(0)
+            .as_any()
+            .downcast_ref::<Int32Array>()
+            .unwrap();
+        assert_eq!(ids, &Int32Array::from(vec![1, 2]));
+        let names = batch
+            .column(1)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        assert_eq!(names, &StringArray::from(vec!["first", "other"]));
+
+        let mut bound = person();
+        bound.discriminator = Some(("label".to_string(), "Person".to_string()));
+        assert_eq!(
+            bound.filters(&batch).unwrap(),
+            vec!["(`id` IN (1, 2)) AND `label` = 'Person'"]
+        );
+    }
+
+    #[test]
+    fn test_bound_rows_of_a_missing_column() {
+        let rows = RecordBatch::try_from_iter(vec![("id", ints(vec![Some(1)]))]).unwrap();
+        let mut bound = person();
+        bound.keys = vec![("id".to_string(), 0)];
+        let schema = Schema::new(vec![Field::new("key", DataType::Int64, false)]);
+        let err = bound.rows(&rows, &schema, &[]).unwrap_err();
+        assert!(err.to_string().contains("has no column 'id'"), "{}", err);
+    }
+}
@@ -0,0 +1,456 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Writes spanning several datasets
+//!
+//! Lance commits every dataset on its own. A write touching several of them therefore
+//! writes the data files of each first, which leaves the datasets unchanged, and only
+//! then commits them one after another. Lance updates write and commit in one step, so
+//! they run in that second step, on the versions committed before them. Should a commit
+//! fail, the datasets committed before it are restored to the versions they had, so no
+//! part of the write remains.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use arrow::compute::{concat_batches, take_record_batch};
+use arrow::row::{RowConverter, SortField};
+use arrow_array::{RecordBatch, RecordBatchIterator, UInt32Array};
+use lance::dataset::transaction::Transaction;
+use lance::dataset::{
+    CommitBuilder, InsertBuilder, MergeInsertBuilder, UpdateBuilder, WhenMatched, WhenNotMatched,
+    WriteMode, WriteParams,
+};
+use lance::Dataset;
+use lance_core::utils::mask::RowIdTreeMap;
+
+use crate::error::{GraphError, Result};
+use crate::lance_catalog::LanceCatalog;
+use crate::source_catalog::SourceKind;
+
+/// A change to one dataset
+#[derive(Debug)]
+enum Change {
+    /// Rows to append
+    Append(Vec<RecordBatch>),
+    /// Rows to insert, or to update the row with the same values of `on` when
+    /// `update` is set
+    Merge {
+        on: Vec<String>,
+        update: bool,
+        batch: RecordBatch,
+    },
+    /// Changes to rows in place
+    Update(Update),
+}
+
+/// Changes to rows the dataset holds, which Lance writes and commits in one step
+#[derive(Debug)]
+enum Update {
+    /// SQL values to assign to columns of the rows matching a SQL filter
+    Filtered {
+        filter: String,
+        assignments: Vec<(String, String)>,
+    },
+    /// New values for some columns of the rows with the same values of `on`
+    Rows { on: Vec<String>, batch: RecordBatch },
+    /// Rows matching a SQL filter, to delete
+    Deleted { filter: String },
+}
+
+/// A change ready to commit
+enum Staged {
+    /// Written data files, committed by a transaction
+    Written(Box<Transaction>, Option<RowIdTreeMap>),
+    /// An update, applied to the latest version when committing
+    Update(Update),
+}
+
+/// The changes of one write query, applied to the catalog's datasets together
+#[derive(Debug, Default)]
+pub(crate) struct WriteTransaction {
+    /// Changes to each dataset, in commit order
+    changes: Vec<(SourceKind, String, Change)>,
+}
+
+impl WriteTransaction {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue rows to append to the dataset of a node label or relationship table
+    pub(crate) fn append(&mut self, kind: SourceKind, name: &str, batch: RecordBatch) {
+        if batch.num_rows() == 0 {
+            return;
+        }
+        let queued = self
+            .changes
+            .iter_mut()
+            .find_map(|(k, n, change)| match change {
+                Change::Append(batches) if *k == kind && n == name => Some(batches),
+                _ => None,
+            });
+        match queued {
+            Some(batches) => batches.push(batch),
+            None => self
+                .changes
+                .push((kind, name.to_string(), Change::Append(vec![batch]))),
+        }
+    }
+
+    /// Queue rows to merge into a dataset by the key columns `on`: rows with a key the
+    /// dataset holds replace its row when `update` is set, the others are inserted.
+    /// Rows merged into the same dataset by earlier calls win over later ones.
+    pub(crate) fn merge(
+        &mut self,
+        kind: SourceKind,
+        name: &str,
+        on: Vec<String>,
+        update: bool,
+        batch: RecordBatch,
+    ) -> Result<()> {
+        if batch.num_rows() == 0 {
+            return Ok(());
+        }
+        let queued = self
+            .changes
+            .iter_mut()
+            .find_map(|(k, n, change)| match change {
+                Change::Merge {
+                    update: queued_update,
+                    batch: queued,
+                    ..
+                } if *k == kind && n == name => Some((queued_update, queued)),
+                _ => None,
+            });
+        match queued {
+            Some((queued_update, queued)) => {
+                *queued = concat_batches(&queued.schema(), [&*queued, &batch])?;
+                *queued_update |= update;
+            }
+            None => {
+                self.changes
+                    .push((kind, name.to_string(), Change::Merge { on, update, batch }))
+            }
+        }
+        Ok(())
+    }
+
+    /// Queue an update assigning SQL expressions to columns of the rows `filter` matches
+    pub(crate) fn update(
+        &mut self,
+        kind: SourceKind,
+        name: &str,
+        filter: String,
+        assignments: Vec<(String, String)>,
+    ) {
+        if assignments.is_empty() {
+            return;
+        }
+        self.changes.push((
+            kind,
+            name.to_string(),
+            Change::Update(Update::Filtered {
+                filter,
+                assignments,
+            }),
+        ));
+    }
+
+    /// Queue new values for some columns of the rows with the same values of `on`; the
+    /// batch holds the `on` columns and the changed ones
+    pub(crate) fn update_rows(
+        &mut self,
+        kind: SourceKind,
+        name: &str,
+        on: Vec<String>,
+        batch: RecordBatch,
+    ) {
+        if batch.num_rows() == 0 {
+            return;
+        }
+        self.changes.push((
+            kind,
+            name.to_string(),
+            Change::Update(Update::Rows { on, batch }),
+        ));
+    }
+
+    /// Queue deleting the rows `filter` matches
+    pub(crate) fn delete(&mut self, kind: SourceKind, name: &str, filter: String) {
+        self.changes.push((
+            kind,
+            name.to_string(),
+            Change::Update(Update::Deleted { filter }),
+        ));
+    }
+
+    /// Write and commit every queued change, pointing `catalog` at the new versions.
+    /// Nodes are committed before relationships.
+    pub(crate) async fn commit(mut self, catalog: &mut LanceCatalog) -> Result<()> {
+        self.changes
+            .sort_by_key(|(kind, _, _)| *kind == SourceKind::Relationship);
+        let mut staged = Vec::new();
+        for (kind, name, change) in self.changes {
+            let dataset = catalog
+                .dataset(kind, &name)
+                .ok_or_else(|| GraphError::ConfigError {
+                    message: format!("No Lance dataset is registered for '{}'", name),
+                    location: snafu::Location::new(file!(), line!(), column!()),
+                })?;
+            let staged_change = stage(&dataset, change).await?;
+            staged.push((kind, name, dataset, staged_change));
+        }
+
+        let mut committed = Vec::new();
+        for (kind, name, dataset, change) in staged {
+            let result = match change {
+                Staged::Written(transaction, affected_rows) => {
+                    let mut commit = CommitBuilder::new(dataset.clone());
+                    if let Some(affected_rows) = affected_rows {
+                        commit = commit.with_affected_rows(affected_rows);
+                    }
+                    commit.execute(*transaction).await
+                }
+                Staged::Update(change) => {
+                    // On top of the changes to the same dataset committed before
+                    let current = catalog.dataset(kind, &name).unwrap_or(dataset.clone());
+                    apply_update(current, change).await
+                }
+            };
+            match result {
+                Ok(updated) => {
+                    catalog.replace_dataset(kind, &name, Arc::new(updated));
+                    committed.push((kind, name, dataset));
+                }
+                Err(e) => {
+                    rollback(catalog, committed).await?;
+                    return Err(e.into());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Write the data files of a change without committing it
+async fn stage(dataset: &Arc<Dataset>, change: Change) -> Result<Staged> {
+    match change {
+        Change::Append(batches) => {
+            let params = WriteParams {
+                mode: WriteMode::Append,
+                ..Default::default()
+            };
+            let transaction = InsertBuilder::new(dataset.clone())
+                .with_params(&params)
+                .execute_uncommitted(batches)
+                .await?;
+            Ok(Staged::Written(Box::new(transaction), None))
+        }
+        Change::Merge { on, update, batch } => {
+            let keys = on
+                .iter()
+                .map(|column| batch.schema().index_of(column))
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+            let batch = first_rows(&batch, &keys)?;
+            let mut builder = MergeInsertBuilder::try_new(dataset.clone(), on)?;
+            builder
+                .when_matched(if update {
+                    WhenMatched::UpdateAll
+                } else {
+                    WhenMatched::DoNothing
+                })
+                .when_not_matched(WhenNotMatched::InsertAll);
+            let source = RecordBatchIterator::new(vec![Ok(batch.clone())], batch.schema());
+            let merged = builder.try_build()?.execute_uncommitted(source).await?;
+            Ok(Staged::Written(
+                Box::new(merged.transaction),
+                merged.affected_rows,
+            ))
+        }
+        Change::Update(update) => Ok(Staged::Update(update)),
+    }
+}
+
+/// Apply an update to `dataset`, committing a new version
+async fn apply_update(dataset: Arc<Dataset>, update: Update) -> lance::Result<Dataset> {
+    match update {
+        Update::Filtered {
+            filter,
+            assignments,
+        } => {
+            let mut builder = UpdateBuilder::new(dataset).update_where(&filter)?;
+            for (column, value) in &assignments {
+                builder = builder.set(column, value)?;
+            }
+            let result = builder.build()?.execute().await?;
+            Ok(result.new_dataset.as_ref().clone())
+        }
+        Update::Rows { on, batch } => {
+            let mut builder = MergeInsertBuilder::try_new(dataset, on)?;
+            builder
+                .when_matched(WhenMatched::UpdateAll)
+                .when_not_matched(WhenNotMatched::DoNothing);
+            let source = RecordBatchIterator::new(vec![Ok(batch.clone())], batch.schema());
+            let (updated, _) = builder.try_build()?.execute_reader(source).await?;
+            Ok(updated.as_ref().clone())
+        }
+        Update::Deleted { filter } => {
+            let mut dataset = dataset.as_ref().clone();
+            dataset.delete(&filter).await?;
+            Ok(dataset)
+        }
+    }
+}
+
+/// The rows of `batch` whose values of the `keys` columns no earlier row has
+pub(crate) fn first_rows(batch: &RecordBatch, keys: &[usize]) -> Result<RecordBatch> {
+    let columns: Vec<_> = keys.iter().map(|i| batch.column(*i).clone()).collect();
+    let converter = RowConverter::new(
+        columns
+            .iter()
+            .map(|c| SortField::new(c.data_type().clone()))
+            .collect(),
+    )?;
+    let rows = converter.convert_columns(&columns)?;
+    let mut seen = HashSet::new();
+    let first: Vec<u32> = (0..batch.num_rows())
+        .filter(|i| seen.insert(rows.row(*i)))
+        .map(|i| i as u32)
+        .collect();
+    if first.len() == batch.num_rows() {
+        return Ok(batch.clone());
+    }
+    Ok(take_record_batch(batch, &UInt32Array::from(first))?)
+}
+
+/// Restore committed datasets to the versions they had before the write
+async fn rollback(
+    catalog: &mut LanceCatalog,
+    committed: Vec<(SourceKind, String, Arc<Dataset>)>,
+) -> Result<()> {
+    for (kind, name, original) in committed.into_iter().rev() {
+        let mut restored = (*original).clone();
+        restored.restore().await?;
+        catalog.replace_dataset(kind, &name, Arc::new(restored));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::Int64Type;
+    use arrow_array::{ArrayRef, Int64Array};
+
+    fn ids(values: Vec<i64>) -> RecordBatch {
+        RecordBatch::try_from_iter(vec![("id", Arc::new(Int64Array::from(values)) as ArrayRef)])
+            .unwrap()
+    }
+
+    async fn dataset(dir: &tempfile::TempDir, name: &str, batch: RecordBatch) -> Arc<Dataset> {
+        let uri = dir.path().join(format!("{}.lance", name));
+        let reader = RecordBatchIterator::new(vec![Ok(batch.clone())], batch.schema());
+        Arc::new(
+            Dataset::write(reader, uri.to_str().unwrap(), None)
+                .await
+                .unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_appends_to_a_dataset_are_queued_together() {
+        let mut transaction = WriteTransaction::new();
+        transaction.append(SourceKind::Node, "Person", ids(vec![1]));
+        transaction.append(SourceKind::Node, "Person", ids(vec![]));
+        transaction.append(SourceKind::Relationship, "Person", ids(vec![2]));
+        transaction.append(SourceKind::Node, "Person", ids(vec![3]));
+        assert_eq!(transaction.changes.len(), 2);
+        let (_, _, Change::Append(batches)) = &transaction.changes[0] else {
+            panic!("an append, not {:?}", transaction.changes[0]);
+        };
+        assert_eq!(batches.len(), 2);
+    }
+
+    #[test]
+    fn test_merges_into_a_dataset_are_queued_together() {
+        let mut transaction = WriteTransaction::new();
+        let on = vec!["id".to_string()];
+        transaction
+            .merge(SourceKind::Node, "Person", on.clone(), false, ids(vec![1]))
+            .unwrap();
+        transaction
+            .merge(SourceKind::Node, "Person", on, true, ids(vec![2, 1]))
+            .unwrap();
+        let [(_, _, Change::Merge { update, batch, .. })] = &transaction.changes[..] else {
+            panic!("one merge, not {:?}", transaction.changes);
+        };
+        assert!(*update);
+        assert_eq!(batch.num_rows(), 3);
+    }
+
+    #[test]
+    fn test_first_rows_of_each_key() {
+        let batch = RecordBatch::try_from_iter(vec![
+            (
+                "id",
+                Arc::new(Int64Array::from(vec![
+                    Some(1),
+                    Some(2),
+                    Some(1),
+                    None,
+                    None,
+                ])) as ArrayRef,
+            ),
+            (
+                "region",
+                Arc::new(Int64Array::from(vec![10, 10, 10, 10, 10])) as ArrayRef,
+            ),
+            (
+                "row",
+                Arc::new(Int64Array::from(vec![0, 1, 2, 3, 4])) as ArrayRef,
+            ),
+        ])
+        .unwrap();
+        let rows = |keys: &[usize]| {
+            let first = first_rows(&batch, keys).unwrap();
+            first
+                .column(2)
+                .as_primitive::<Int64Type>()
+                .values()
+                .to_vec()
+        };
+        // NULL keys are equal to each other
+        assert_eq!(rows(&[0]), vec![0, 1, 3]);
+        assert_eq!(rows(&[0, 1]), vec![0, 1, 3]);
+        assert_eq!(rows(&[1]), vec![0]);
+        assert_eq!(rows(&[2]), vec![0, 1, 2, 3, 4]);
+        assert_eq!(first_rows(&ids(vec![]), &[0]).unwrap().num_rows(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_failed_commit_restores_committed_datasets() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut catalog = LanceCatalog::new()
+            .with_node_dataset("Person", dataset(&dir, "person", ids(vec![1, 2])).await)
+            .with_relationship_dataset("KNOWS", dataset(&dir, "knows", ids(vec![1])).await);
+
+        // Nodes commit first, so the failing relationship delete undoes their append
+        let mut transaction = WriteTransaction::new();
+        transaction.delete(SourceKind::Relationship, "KNOWS", "missing = 1".to_string());
+        transaction.append(SourceKind::Node, "Person", ids(vec![3]));
+        assert!(transaction.commit(&mut catalog).await.is_err());
+        let person = catalog.dataset(SourceKind::Node, "Person").unwrap();
+        assert_eq!(person.count_rows(None).await.unwrap(), 2);
+
+        let mut transaction = WriteTransaction::new();
+        transaction.delete(SourceKind::Relationship, "KNOWS", "id = 1".to_string());
+        transaction.append(SourceKind::Node, "Person", ids(vec![3]));
+        transaction.commit(&mut catalog).await.unwrap();
+        let person = catalog.dataset(SourceKind::Node, "Person").unwrap();
+        assert_eq!(person.count_rows(None).await.unwrap(), 3);
+        let knows = catalog.dataset(SourceKind::Relationship, "KNOWS").unwrap();
+        assert_eq!(knows.count_rows(None).await.unwrap(), 0);
+    }
+}
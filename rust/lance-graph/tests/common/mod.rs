@@ -0,0 +1,202 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Fixtures shared by the integration tests
+//!
+//! A [`TestGraph`] holds a graph configuration and the batches behind its labels and
+//! relationship types, and serves them from in-memory tables or Lance datasets.
+
+#![allow(dead_code)]
+
+use std::sync::Arc;
+
+use arrow_array::{Array, Float64Array, Int64Array, RecordBatch, RecordBatchIterator, StringArray};
+use arrow_schema::{DataType, Field, Schema};
+use datafusion::datasource::MemTable;
+use lance::Dataset;
+use lance_graph::source_catalog::{GraphSourceCatalog, ProviderCatalog};
+use lance_graph::{CypherQuery, GraphConfig, GraphProjection, LanceCatalog};
+
+/// A graph configuration with the batches of its nodes and relationships
+pub struct TestGraph {
+    config: GraphConfig,
+    nodes: Vec<(String, RecordBatch)>,
+    relationships: Vec<(String, RecordBatch)>,
+    projections: Vec<(String, Arc<GraphProjection>)>,
+}
+
+impl TestGraph {
+    pub fn new(config: GraphConfig) -> Self {
+        Self {
+            config,
+            nodes: Vec::new(),
+            relationships: Vec::new(),
+            projections: Vec::new(),
+        }
+    }
+
+    /// `Person` nodes keyed by `id` and relationship types from `src_id` to `dst_id`
+    pub fn people(rel_types: &[&str]) -> Self {
+        let mut builder = GraphConfig::builder().with_node_label("Person", "id");
+        for rel_type in rel_types {
+            builder = builder.with_relationship(*rel_type, "src_id", "dst_id");
+        }
+        Self::new(builder.build().unwrap())
+    }
+
+    pub fn with_nodes(mut self, label: &str, batch: RecordBatch) -> Self {
+        self.nodes.push((label.to_string(), batch));
+        self
+    }
+
+    pub fn with_relationships(mut self, rel_type: &str, batch: RecordBatch) -> Self {
+        self.relationships.push((rel_type.to_string(), batch));
+        self
+    }
+
+    /// Register `projection` under `name` with every query
+    pub fn with_projection(mut self, name: &str, projection: Arc<GraphProjection>) -> Self {
+        self.projections.push((name.to_string(), projection));
+        self
+    }
+
+    pub fn config(&self) -> GraphConfig {
+        self.config.clone()
+    }
+
+    /// Serve the batches from in-memory tables
+    pub fn memory_catalog(&self) -> Arc<dyn GraphSourceCatalog> {
+        let table = |batch: &RecordBatch| {
+            Arc::new(MemTable::try_new(batch.schema(), vec![vec![batch.clone()]]).unwrap())
+        };
+        let mut catalog = ProviderCatalog::new();
+        for (label, batch) in &self.nodes {
+            catalog = catalog.with_node_table(label, table(batch));
+        }
+        for (rel_type, batch) in &self.relationships {
+            catalog = catalog.with_relationship_table(rel_type, table(batch));
+        }
+        Arc::new(catalog)
+    }
+
+    /// Write the batches to Lance datasets under `dir` and serve them from there
+    pub async fn lance_catalog(&self, dir: &tempfile::TempDir) -> Arc<dyn GraphSourceCatalog> {
+        let mut catalog = LanceCatalog::new();
+        for (label, batch) in &self.nodes {
+            catalog = catalog.with_node_dataset(label, write_dataset(dir, label, batch).await);
+        }
+        for (rel_type, batch) in &self.relationships {
+            catalog = catalog
+                .with_relationship_dataset(rel_type, write_dataset(dir, rel_type, batch).await);
+        }
+        Arc::new(catalog)
+    }
+
+    /// Parse `cypher` against the graph configuration
+    pub fn query(&self, cypher: &str) -> lance_graph::Result<CypherQuery> {
+        let mut query = CypherQuery::new(cypher)?.with_config(self.config());
+        for (name, projection) in &self.projections {
+            query = query.with_projection(name.clone(), projection.clone());
+        }
+        Ok(query)
+    }
+
+    /// Run `cypher` over `catalog`
+    pub async fn run_with(
+        &self,
+        cypher: &str,
+        catalog: Arc<dyn GraphSourceCatalog>,
+    ) -> lance_graph::Result<RecordBatch> {
+        self.query(cypher)?.execute_with_catalog(catalog).await
+    }
+
+    /// Run `cypher` over in-memory tables
+    pub async fn run(&self, cypher: &str) -> lance_graph::Result<RecordBatch> {
+        self.run_with(cypher, self.memory_catalog()).await
+    }
+}
+
+/// Write `batch` to a Lance dataset named after `name` under `dir`
+pub async fn write_dataset(
+    dir: &tempfile::TempDir,
+    name: &str,
+    batch: &RecordBatch,
+) -> Arc<Dataset> {
+    let uri = dir.path().join(format!("{}.lance", name.to_lowercase()));
+    let reader = RecordBatchIterator::new(vec![Ok(batch.clone())], batch.schema());
+    Arc::new(
+        Dataset::write(reader, uri.to_str().unwrap(), None)
+            .await
+            .unwrap(),
+    )
+}
+
+/// Nodes with ids counting up from 1 and the given names
+pub fn named(names: &[&str]) -> RecordBatch {
+    named_with_ids(1..=names.len() as i64, names)
+}
+
+/// Nodes with the given ids and names
+pub fn named_with_ids(ids: impl IntoIterator<Item = i64>, names: &[&str]) -> RecordBatch {
+    RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("name", DataType::Utf8, false),
+        ])),
+        vec![
+            Arc::new(Int64Array::from_iter_values(ids)),
+            Arc::new(StringArray::from(names.to_vec())),
+        ],
+    )
+    .unwrap()
+}
+
+/// Relationships from `src_id` to `dst_id`
+pub fn edges(pairs: &[(i64, i64)]) -> RecordBatch {
+    RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("src_id", DataType::Int64, false),
+            Field::new("dst_id", DataType::Int64, false),
+        ])),
+        vec![
+            Arc::new(Int64Array::from_iter_values(pairs.iter().map(|p| p.0))),
+            Arc::new(Int64Array::from_iter_values(pairs.iter().map(|p| p.1))),
+        ],
+    )
+    .unwrap()
+}
+
+pub fn int64s(batch: &RecordBatch, column: usize) -> Vec<i64> {
+    let values = batch
+        .column(column)
+        .as_any()
+        .downcast_ref::<Int64Array>()
+        .unwrap();
+    values.values().to_vec()
+}
+
+pub fn strings(batch: &RecordBatch, column: usize) -> Vec<&str> {
+    let values = batch
+        .column(column)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+    (0..values.len()).map(|i| values.value(i)).collect()
+}
+
+/// Relationships from `src_id` to `dst_id` with a `Float64` property
+pub fn weighted_edges(property: &str, triples: &[(i64, i64, f64)]) -> RecordBatch {
+    RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("src_id", DataType::Int64, false),
+            Field::new("dst_id", DataType::Int64, false),
+            Field::new(property, DataType::Float64, false),
+        ])),
+        vec![
+            Arc::new(Int64Array::from_iter_values(triples.iter().map(|t| t.0))),
+            Arc::new(Int64Array::from_iter_values(triples.iter().map(|t| t.1))),
+            Arc::new(Float64Array::from_iter_values(triples.iter().map(|t| t.2))),
+        ],
+    )
+    .unwrap()
+}
@@ -0,0 +1,332 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Expansions read from materialized adjacency indexes
+
+use std::sync::Arc;
+
+use arrow_array::{Array, Int64Array, RecordBatch, RecordBatchIterator, StringArray};
+use arrow_schema::{DataType, Field, Schema};
+use lance::dataset::{WriteMode, WriteParams};
+use lance::Dataset;
+use lance_graph::adjacency_index::AdjacencyIndex;
+use lance_graph::directory_catalog::DirectoryCatalog;
+use lance_graph::source_catalog::{GraphSourceCatalog, NamespacedCatalog};
+use lance_graph::{CypherQuery, GraphConfig, LanceCatalog};
+
+fn graph_config() -> GraphConfig {
+    GraphConfig::builder()
+        .with_node_label("Person", "id")
+        .with_relationship("KNOWS", "src_id", "dst_id")
+        .build()
+        .unwrap()
+}
+
+fn people() -> RecordBatch {
+    RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("name", DataType::Utf8, false),
+        ])),
+        vec![
+            Arc::new(Int64Array::from(vec![1, 2, 3, 4])),
+            Arc::new(StringArray::from(vec!["Alice", "Bob", "Carol", "Dan"])),
+        ],
+    )
+    .unwrap()
+}
+
+fn knows(edges: &[(i64, i64, i64)]) -> RecordBatch {
+    RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("src_id", DataType::Int64, false),
+            Field::new("dst_id", DataType::Int64, false),
+            Field::new("since", DataType::Int64, false),
+        ])),
+        vec![
+            Arc::new(Int64Array::from_iter_values(edges.iter().map(|e| e.0))),
+            Arc::new(Int64Array::from_iter_values(edges.iter().map(|e| e.1))),
+            Arc::new(Int64Array::from_iter_values(edges.iter().map(|e| e.2))),
+        ],
+    )
+    .unwrap()
+}
+
+async fn write(uri: &str, batch: RecordBatch, mode: WriteMode) -> Arc<Dataset> {
+    let reader = RecordBatchIterator::new(vec![Ok(batch.clone())], batch.schema());
+    let params = WriteParams {
+        mode,
+        ..Default::default()
+    };
+    Arc::new(Dataset::write(reader, uri, Some(params)).await.unwrap())
+}
+
+async fn names(cypher: &str, catalog: &LanceCatalog) -> Vec<String> {
+    let result = CypherQuery::new(cypher)
+        .unwrap()
+        .with_config(graph_config())
+        .execute_with_catalog(Arc::new(catalog.clone()))
+        .await
+        .unwrap();
+    let names = result
+        .column(0)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+    (0..names.len())
+        .map(|i| names.value(i).to_string())
+        .collect()
+}
+
+/// Whether the physical plan of `cypher` reads an adjacency index
+async fn reads_adjacency_index(cypher: &str, catalog: Arc<dyn GraphSourceCatalog>) -> bool {
+    let result = CypherQuery::new(&format!("EXPLAIN {}", cypher))
+        .unwrap()
+        .with_config(graph_config())
+        .execute_with_catalog(catalog)
+        .await
+        .unwrap();
+    let plan_types = result
+        .column(0)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+    let plans = result
+        .column(1)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+    (0..result.num_rows())
+        .any(|i| plan_types.value(i) == "physical_plan" && plans.value(i).contains(".adj"))
+}
+
+const FRIENDS_OF_ALICE: &str = "MATCH (a:Person {id: 1})-[:KNOWS]->(b:Person) \
+                                RETURN b.name ORDER BY b.name";
+const RECENT_FRIENDS: &str = "MATCH (a:Person)-[r:KNOWS]->(b:Person) WHERE r.since > 2015 \
+                              RETURN b.name ORDER BY b.name";
+const FRIENDS_OF_FRIENDS: &str =
+    "MATCH (a:Person {id: 1})-[:KNOWS]->(m:Person)-[:KNOWS]->(c:Person) \
+     RETURN c.name ORDER BY c.name";
+
+#[tokio::test]
+async fn test_expansions_read_adjacency_index() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = |name: &str| dir.path().join(name).to_str().unwrap().to_string();
+    let person = write(&path("person.lance"), people(), WriteMode::Create).await;
+    let edges = knows(&[(1, 2, 2010), (1, 3, 2020), (2, 4, 2018), (3, 4, 2012)]);
+    let edges = write(&path("knows.lance"), edges, WriteMode::Create).await;
+    let catalog = LanceCatalog::new()
+        .with_node_dataset("Person", person)
+        .with_relationship_dataset("KNOWS", edges);
+    let expected = [
+        names(FRIENDS_OF_ALICE, &catalog).await,
+        names(RECENT_FRIENDS, &catalog).await,
+        names(FRIENDS_OF_FRIENDS, &catalog).await,
+    ];
+
+    let index = AdjacencyIndex::build(&catalog, &graph_config(), "KNOWS", &path("knows.adj"))
+        .await
+        .unwrap();
+    assert_eq!(index.relationship_type(), "KNOWS");
+    assert_eq!(index.dataset().count_rows(None).await.unwrap(), 3);
+    let indexed = catalog.with_adjacency_index(index);
+    assert!(indexed.adjacency_source("KNOWS").is_some());
+
+    assert_eq!(
+        names(FRIENDS_OF_ALICE, &indexed).await,
+        vec!["Bob", "Carol"]
+    );
+    assert_eq!(names(FRIENDS_OF_ALICE, &indexed).await, expected[0]);
+    assert_eq!(names(RECENT_FRIENDS, &indexed).await, expected[1]);
+    assert_eq!(names(FRIENDS_OF_FRIENDS, &indexed).await, expected[2]);
+}
+
+#[tokio::test]
+async fn test_stale_adjacency_index_is_ignored_until_refreshed() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = |name: &str| dir.path().join(name).to_str().unwrap().to_string();
+    let person = write(&path("person.lance"), people(), WriteMode::Create).await;
+    let edges = write(
+        &path("knows.lance"),
+        knows(&[(1, 2, 2010)]),
+        WriteMode::Create,
+    )
+    .await;
+    let catalog = LanceCatalog::new()
+        .with_node_dataset("Person", person.clone())
+        .with_relationship_dataset("KNOWS", edges);
+    let index = AdjacencyIndex::build(&catalog, &graph_config(), "KNOWS", &path("knows.adj"))
+        .await
+        .unwrap();
+    assert_eq!(index.edge_version(), 1);
+
+    let appended = write(
+        &path("knows.lance"),
+        knows(&[(1, 4, 2021)]),
+        WriteMode::Append,
+    )
+    .await;
+    let catalog = LanceCatalog::new()
+        .with_node_dataset("Person", person)
+        .with_relationship_dataset("KNOWS", appended)
+        .with_adjacency_index(AdjacencyIndex::open(&path("knows.adj")).await.unwrap());
+    assert!(!catalog
+        .adjacency_index("KNOWS")
+        .unwrap()
+        .is_current(&catalog));
+    assert!(catalog.adjacency_source("KNOWS").is_none());
+    assert_eq!(names(FRIENDS_OF_ALICE, &catalog).await, vec!["Bob", "Dan"]);
+
+    let refreshed = catalog
+        .adjacency_index("KNOWS")
+        .unwrap()
+        .refresh(&catalog, &graph_config())
+        .await
+        .unwrap();
+    assert_eq!(refreshed.edge_version(), 2);
+    let catalog = catalog.with_adjacency_index(refreshed);
+    assert!(catalog.adjacency_source("KNOWS").is_some());
+    assert_eq!(names(FRIENDS_OF_ALICE, &catalog).await, vec!["Bob", "Dan"]);
+}
+
+#[tokio::test]
+async fn test_refresh_reads_only_appended_fragments() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = |name: &str| dir.path().join(name).to_str().unwrap().to_string();
+    let person = write(&path("person.lance"), people(), WriteMode::Create).await;
+    let edges = knows(&[(1, 2, 2010), (2, 3, 2011)]);
+    let edges = write(&path("knows.lance"), edges, WriteMode::Create).await;
+    let catalog = LanceCatalog::new()
+        .with_node_dataset("Person", person.clone())
+        .with_relationship_dataset("KNOWS", edges);
+    let mut index = AdjacencyIndex::build(&catalog, &graph_config(), "KNOWS", &path("knows.adj"))
+        .await
+        .unwrap();
+
+    // Each append adds rows for the source nodes of its edges only
+    for (batch, version) in [(&[(1, 3, 2020)], 2), (&[(3, 4, 2021)], 3)] {
+        let edges = write(&path("knows.lance"), knows(batch), WriteMode::Append).await;
+        let catalog = catalog.clone().with_relationship_dataset("KNOWS", edges);
+        index = index.refresh(&catalog, &graph_config()).await.unwrap();
+        assert_eq!(index.edge_version(), version);
+        assert!(index.is_current(&catalog));
+    }
+    assert_eq!(index.dataset().count_rows(None).await.unwrap(), 4);
+    let reopened = AdjacencyIndex::open(&path("knows.adj")).await.unwrap();
+    assert_eq!(reopened.edge_version(), 3);
+
+    let edges = Arc::new(Dataset::open(&path("knows.lance")).await.unwrap());
+    let catalog = catalog
+        .with_relationship_dataset("KNOWS", edges)
+        .with_adjacency_index(index.clone());
+    assert!(catalog.adjacency_source("KNOWS").is_some());
+    assert_eq!(
+        names(FRIENDS_OF_ALICE, &catalog).await,
+        vec!["Bob", "Carol"]
+    );
+    assert_eq!(
+        names(FRIENDS_OF_FRIENDS, &catalog).await,
+        vec!["Carol", "Dan"]
+    );
+
+    // Rebuilding regroups the edges into one row per source node
+    let rebuilt = index.rebuild(&catalog, &graph_config()).await.unwrap();
+    assert_eq!(rebuilt.dataset().count_rows(None).await.unwrap(), 3);
+}
+
+#[tokio::test]
+async fn test_refresh_after_delete_rebuilds() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = |name: &str| dir.path().join(name).to_str().unwrap().to_string();
+    let person = write(&path("person.lance"), people(), WriteMode::Create).await;
+    let edges = knows(&[(1, 2, 2010), (1, 3, 2020)]);
+    let edges = write(&path("knows.lance"), edges, WriteMode::Create).await;
+    let catalog = LanceCatalog::new()
+        .with_node_dataset("Person", person)
+        .with_relationship_dataset("KNOWS", edges.clone());
+    let index = AdjacencyIndex::build(&catalog, &graph_config(), "KNOWS", &path("knows.adj"))
+        .await
+        .unwrap();
+
+    let mut edges = Dataset::clone(&edges);
+    edges.delete("dst_id = 2").await.unwrap();
+    let catalog = catalog.with_relationship_dataset("KNOWS", Arc::new(edges));
+    let index = index.refresh(&catalog, &graph_config()).await.unwrap();
+    let catalog = catalog.with_adjacency_index(index);
+    assert!(catalog.adjacency_source("KNOWS").is_some());
+    assert_eq!(names(FRIENDS_OF_ALICE, &catalog).await, vec!["Carol"]);
+}
+
+#[tokio::test]
+async fn test_adjacency_index_requires_edge_dataset() {
+    let dir = tempfile::tempdir().unwrap();
+    let uri = dir.path().join("knows.adj");
+    let error = AdjacencyIndex::build(
+        &LanceCatalog::new(),
+        &graph_config(),
+        "KNOWS",
+        uri.to_str().unwrap(),
+    )
+    .await
+    .unwrap_err();
+    assert!(
+        error.to_string().contains("No dataset registered"),
+        "{}",
+        error
+    );
+}
+
+#[tokio::test]
+async fn test_catalog_wrappers_expose_adjacency_index() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = |name: &str| dir.path().join(name).to_str().unwrap().to_string();
+    write(&path("graph/Person.lance"), people(), WriteMode::Create).await;
+    let edges = knows(&[(1, 2, 2010), (1, 3, 2020)]);
+    write(&path("graph/KNOWS.lance"), edges, WriteMode::Create).await;
+
+    let directory = DirectoryCatalog::open(&path("graph")).await.unwrap();
+    let index = AdjacencyIndex::build(
+        directory.catalog(),
+        directory.config(),
+        "KNOWS",
+        &path("knows.adj"),
+    )
+    .await
+    .unwrap();
+    let catalog = directory
+        .catalog()
+        .clone()
+        .with_adjacency_index(index.clone());
+    let directory = directory.with_adjacency_index(index);
+    assert!(directory.adjacency_source("KNOWS").is_some());
+
+    // Graphs selected with USE read the index of their own catalog
+    let namespaced = NamespacedCatalog::new().with_graph("social", Arc::new(catalog));
+    let wrapped: [(String, Arc<dyn GraphSourceCatalog>); 2] = [
+        (FRIENDS_OF_ALICE.to_string(), Arc::new(directory)),
+        (
+            format!("USE social {}", FRIENDS_OF_ALICE),
+            Arc::new(namespaced),
+        ),
+    ];
+    for (cypher, catalog) in wrapped {
+        assert!(
+            reads_adjacency_index(&cypher, catalog.clone()).await,
+            "{}",
+            cypher
+        );
+        let result = CypherQuery::new(&cypher)
+            .unwrap()
+            .with_config(graph_config())
+            .execute_with_catalog(catalog)
+            .await
+            .unwrap();
+        let names = result
+            .column(0)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        assert_eq!(names.value(0), "Bob");
+        assert_eq!(names.value(1), "Carol");
+    }
+}
@@ -0,0 +1,119 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Degree and betweenness centrality with `CALL graph.degree` and
+//! `CALL graph.betweenness`
+
+use arrow::array::AsArray;
+use arrow::datatypes::{Float64Type, Int64Type};
+
+mod common;
+use common::{named, weighted_edges, TestGraph};
+
+/// Alice (1) knows Bob (2), who knows Carol (3) and Dan (4); Carol knows Dan, who knows
+/// Eve (5). Each relationship has a strength.
+fn social() -> TestGraph {
+    TestGraph::people(&["KNOWS"])
+        .with_nodes("Person", named(&["Alice", "Bob", "Carol", "Dan", "Eve"]))
+        .with_relationships(
+            "KNOWS",
+            weighted_edges(
+                "strength",
+                &[
+                    (1, 2, 2.0),
+                    (2, 3, 1.0),
+                    (3, 4, 1.0),
+                    (2, 4, 0.5),
+                    (4, 5, 3.0),
+                ],
+            ),
+        )
+}
+
+/// Node ids and scores of a `nodeId, score` result
+async fn scores(cypher: &str) -> (Vec<i64>, Vec<f64>) {
+    let result = social().run(cypher).await.unwrap();
+    let ids = result.column(0).as_primitive::<Int64Type>();
+    let scores = result.column(1).as_primitive::<Float64Type>();
+    (ids.values().to_vec(), scores.values().to_vec())
+}
+
+#[tokio::test]
+async fn test_degree() {
+    assert_eq!(
+        scores("CALL graph.degree() YIELD nodeId, degree RETURN nodeId, degree").await,
+        (vec![2, 1, 3, 4, 5], vec![2.0, 1.0, 1.0, 1.0, 0.0])
+    );
+    assert_eq!(
+        scores(
+            "CALL graph.degree({direction: 'in', weightProperty: 'strength'}) \
+             YIELD nodeId, degree RETURN nodeId, degree"
+        )
+        .await,
+        (vec![5, 2, 4, 3, 1], vec![3.0, 2.0, 1.5, 1.0, 0.0])
+    );
+    assert_eq!(
+        scores(
+            "CALL graph.degree({direction: 'total', relationshipTypes: ['KNOWS']}) \
+             YIELD nodeId, degree RETURN nodeId, degree"
+        )
+        .await,
+        (vec![2, 4, 3, 1, 5], vec![3.0, 3.0, 2.0, 1.0, 1.0])
+    );
+}
+
+#[tokio::test]
+async fn test_betweenness() {
+    // Bob lies on the paths from Alice to Carol, Dan and Eve; Dan on those to Eve
+    let exact = scores("CALL graph.betweenness() YIELD nodeId, score RETURN nodeId, score").await;
+    assert_eq!(exact, (vec![2, 4, 1, 3, 5], vec![3.0, 3.0, 0.0, 0.0, 0.0]));
+    assert_eq!(
+        scores(
+            "CALL graph.betweenness({samplingSize: 10}) YIELD nodeId, score \
+             RETURN nodeId, score"
+        )
+        .await,
+        exact
+    );
+
+    let cypher = "CALL graph.betweenness({samplingSize: 2, samplingSeed: 7}) \
+                  YIELD nodeId, score RETURN nodeId, score";
+    let (ids, sampled) = scores(cypher).await;
+    assert_eq!(ids.len(), 5);
+    assert_eq!(scores(cypher).await, (ids, sampled));
+
+    let result = social()
+        .run(
+            "CALL graph.betweenness() YIELD nodeId, score \
+             MATCH (p:Person) WHERE p.id = nodeId AND score > 0 \
+             RETURN p.name ORDER BY p.name",
+        )
+        .await
+        .unwrap();
+    let names = result.column(0).as_string::<i32>();
+    assert_eq!(
+        names.iter().flatten().collect::<Vec<_>>(),
+        vec!["Bob", "Dan"]
+    );
+}
+
+#[tokio::test]
+async fn test_centrality_errors() {
+    for (cypher, message) in [
+        (
+            "CALL graph.degree({direction: 'sideways'}) YIELD degree RETURN degree",
+            "direction must be 'out', 'in' or 'total'",
+        ),
+        (
+            "CALL graph.degree({weightProperty: 'toll'}) YIELD degree RETURN degree",
+            "no relationship type has the property 'toll'",
+        ),
+        (
+            "CALL graph.betweenness({samplingSize: 0}) YIELD score RETURN score",
+            "samplingSize must be positive",
+        ),
+    ] {
+        let error = social().run(cypher).await.unwrap_err().to_string();
+        assert!(error.contains(message), "{}: {}", cypher, error);
+    }
+}
@@ -0,0 +1,92 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Community detection with `CALL graph.louvain` and `CALL graph.labelPropagation`
+
+use arrow::array::AsArray;
+use arrow::datatypes::Float64Type;
+
+mod common;
+use common::{edges, int64s, named, TestGraph};
+
+/// Two circles of friends, 1-2-3 and 4-5-6, that only Carol (3) and Dan (4) bridge;
+/// everyone follows Alice (1)
+fn social() -> TestGraph {
+    TestGraph::people(&["KNOWS", "FOLLOWS"])
+        .with_nodes(
+            "Person",
+            named(&["Alice", "Bob", "Carol", "Dan", "Eve", "Finn"]),
+        )
+        .with_relationships(
+            "KNOWS",
+            edges(&[(1, 2), (2, 3), (3, 1), (4, 5), (5, 6), (6, 4), (3, 4)]),
+        )
+        .with_relationships("FOLLOWS", edges(&[(2, 1), (3, 1), (4, 1), (5, 1), (6, 1)]))
+}
+
+#[tokio::test]
+async fn test_communities_of_friends() {
+    for procedure in ["graph.louvain", "graph.labelPropagation"] {
+        let cypher = format!(
+            "CALL {}({{relationshipTypes: ['KNOWS']}}) YIELD nodeId, communityId, modularity \
+             RETURN nodeId, communityId, modularity",
+            procedure
+        );
+        let result = social().run(&cypher).await.unwrap();
+        assert_eq!(int64s(&result, 0), vec![1, 2, 3, 4, 5, 6], "{}", procedure);
+        assert_eq!(int64s(&result, 1), vec![0, 0, 0, 1, 1, 1], "{}", procedure);
+        // Every member carries the modularity of the whole partition
+        let modularity = result.column(2).as_primitive::<Float64Type>();
+        assert!(modularity.value(0) > 0.0, "{}", procedure);
+        assert!(modularity
+            .values()
+            .iter()
+            .all(|&m| m == modularity.value(0)));
+    }
+}
+
+#[tokio::test]
+async fn test_community_members() {
+    let result = social()
+        .run(
+            "CALL graph.louvain({relationshipTypes: ['KNOWS'], maxLevels: 1}) \
+             YIELD nodeId, communityId \
+             MATCH (p:Person) WHERE p.id = nodeId AND communityId = 1 \
+             RETURN p.name ORDER BY p.name",
+        )
+        .await
+        .unwrap();
+    let names = result.column(0).as_string::<i32>();
+    assert_eq!(
+        names.iter().flatten().collect::<Vec<_>>(),
+        vec!["Dan", "Eve", "Finn"]
+    );
+
+    // Every relationship type by default
+    let result = social()
+        .run("CALL graph.louvain() YIELD modularity RETURN modularity")
+        .await
+        .unwrap();
+    assert_eq!(result.num_rows(), 6);
+}
+
+#[tokio::test]
+async fn test_community_errors() {
+    for (cypher, message) in [
+        (
+            "CALL graph.labelPropagation({maxLevels: 2}) YIELD nodeId RETURN nodeId",
+            "has no option 'maxLevels'",
+        ),
+        (
+            "CALL graph.louvain({maxIterations: 0}) YIELD nodeId RETURN nodeId",
+            "maxIterations must be positive",
+        ),
+        (
+            "CALL graph.louvain({relationshipTypes: ['LIKES']}) YIELD nodeId RETURN nodeId",
+            "unknown relationship type 'LIKES'",
+        ),
+    ] {
+        let error = social().run(cypher).await.unwrap_err().to_string();
+        assert!(error.contains(message), "{}: {}", cypher, error);
+    }
+}
@@ -0,0 +1,148 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Connected components with `CALL graph.wcc` and `CALL graph.scc`
+
+use arrow::array::AsArray;
+use arrow::datatypes::Int64Type;
+use lance::Dataset;
+
+mod common;
+use common::{edges, int64s, named, TestGraph};
+
+/// Alice (1) and Bob (2) know each other and Bob knows Carol (3); Dan (4) knows Eve
+/// (5); Finn (6) knows nobody
+fn social() -> TestGraph {
+    TestGraph::people(&["KNOWS"])
+        .with_nodes(
+            "Person",
+            named(&["Alice", "Bob", "Carol", "Dan", "Eve", "Finn"]),
+        )
+        .with_relationships("KNOWS", edges(&[(1, 2), (2, 1), (2, 3), (4, 5)]))
+}
+
+#[tokio::test]
+async fn test_weak_and_strong_components() {
+    let dir = tempfile::tempdir().unwrap();
+    let graph = social();
+    let catalog = graph.lance_catalog(&dir).await;
+
+    let result = graph
+        .run_with(
+            "CALL graph.wcc() YIELD nodeId, componentId RETURN nodeId, componentId",
+            catalog.clone(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(int64s(&result, 0), vec![1, 2, 3, 4, 5]);
+    assert_eq!(int64s(&result, 1), vec![0, 0, 0, 1, 1]);
+
+    let result = graph
+        .run_with(
+            "CALL graph.scc({relationshipTypes: ['KNOWS']}) YIELD nodeId, componentId \
+             RETURN nodeId, componentId",
+            catalog.clone(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(int64s(&result, 0), vec![1, 2, 3, 4, 5]);
+    assert_eq!(int64s(&result, 1), vec![0, 0, 1, 2, 3]);
+
+    // Components join back to the nodes they hold
+    let result = graph
+        .run_with(
+            "CALL graph.wcc() YIELD nodeId, componentId \
+             MATCH (p:Person) WHERE p.id = nodeId AND componentId = 1 \
+             RETURN p.name ORDER BY p.name",
+            catalog,
+        )
+        .await
+        .unwrap();
+    let names = result.column(0).as_string::<i32>();
+    assert_eq!(
+        names.iter().flatten().collect::<Vec<_>>(),
+        vec!["Dan", "Eve"]
+    );
+}
+
+#[tokio::test]
+async fn test_components_written_back() {
+    let dir = tempfile::tempdir().unwrap();
+    let graph = social();
+    let catalog = graph.lance_catalog(&dir).await;
+    let components = dir.path().join("components.lance");
+    let components = components.to_str().unwrap();
+
+    // Running twice replaces the property written the first time
+    for procedure in ["graph.scc", "graph.wcc"] {
+        let cypher = format!(
+            "CALL {}({{writeLabel: 'Person', writeProperty: 'component', \
+             writeDataset: '{}'}}) YIELD nodeId RETURN nodeId",
+            procedure, components
+        );
+        graph.run_with(&cypher, catalog.clone()).await.unwrap();
+    }
+
+    let person = Dataset::open(dir.path().join("person.lance").to_str().unwrap())
+        .await
+        .unwrap();
+    // One commit adds the column and one replaces it
+    assert_eq!(person.version().version, 3);
+    let mut scanner = person.scan();
+    scanner.project(&["id", "component"]).unwrap();
+    let rows = scanner.try_into_batch().await.unwrap();
+    assert_eq!(int64s(&rows, 0), vec![1, 2, 3, 4, 5, 6]);
+    let written = rows.column(1).as_primitive::<Int64Type>();
+    assert_eq!(
+        written.iter().collect::<Vec<_>>(),
+        vec![Some(0), Some(0), Some(0), Some(1), Some(1), None]
+    );
+
+    let rows = Dataset::open(components).await.unwrap();
+    assert_eq!(rows.count_rows(None).await.unwrap(), 5);
+}
+
+#[tokio::test]
+async fn test_component_write_options_go_together() {
+    let dir = tempfile::tempdir().unwrap();
+    let graph = social();
+    let error = graph
+        .run_with(
+            "CALL graph.wcc({writeProperty: 'component'}) YIELD nodeId RETURN nodeId",
+            graph.lance_catalog(&dir).await,
+        )
+        .await
+        .unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .contains("writeProperty and writeLabel must be given together"),
+        "{}",
+        error
+    );
+}
+
+#[tokio::test]
+async fn test_components_never_overwrite_other_columns() {
+    let dir = tempfile::tempdir().unwrap();
+    let graph = social();
+    let catalog = graph.lance_catalog(&dir).await;
+
+    for (property, reason) in [
+        ("id", "the key column of 'Person'"),
+        ("name", "not written by a components procedure"),
+    ] {
+        let cypher = format!(
+            "CALL graph.wcc({{writeLabel: 'Person', writeProperty: '{}'}}) \
+             YIELD nodeId RETURN nodeId",
+            property
+        );
+        let error = graph.run_with(&cypher, catalog.clone()).await.unwrap_err();
+        assert!(error.to_string().contains(reason), "{}", error);
+    }
+
+    let person = Dataset::open(dir.path().join("person.lance").to_str().unwrap())
+        .await
+        .unwrap();
+    assert_eq!(person.version().version, 1);
+}
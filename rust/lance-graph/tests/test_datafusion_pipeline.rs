@@ -1,5 +1,8 @@
-use arrow_array::{Array, Float64Array, Int64Array, RecordBatch, StringArray};
-use arrow_schema::{DataType, Field, Schema};
+use arrow_array::{
+    Array, Date32Array, Float64Array, Int64Array, ListArray, RecordBatch, StringArray,
+    TimestampMicrosecondArray,
+};
+use arrow_schema::{DataType, Field, Schema, TimeUnit};
 use lance_graph::config::GraphConfig;
 use lance_graph::{CypherQuery, ExecutionStrategy};
 use std::collections::HashMap;
@@ -320,348 +323,985 @@ async fn test_datafusion_relationship_traversal() {
 }
 
 #[tokio::test]
-async fn test_datafusion_relationship_with_variable() {
-    let config = create_graph_config();
+async fn test_datafusion_execute_with_provider_catalog() {
+    use datafusion::datasource::MemTable;
+    use lance_graph::source_catalog::ProviderCatalog;
+
     let person_batch = create_person_dataset();
     let knows_batch = create_knows_dataset();
+    let catalog = ProviderCatalog::new()
+        .with_node_table(
+            "Person",
+            Arc::new(MemTable::try_new(person_batch.schema(), vec![vec![person_batch]]).unwrap()),
+        )
+        .with_relationship_table(
+            "KNOWS",
+            Arc::new(MemTable::try_new(knows_batch.schema(), vec![vec![knows_batch]]).unwrap()),
+        );
 
-    // Test relationship traversal with strict count verification
-    let query = CypherQuery::new("MATCH (a:Person)-[:KNOWS]->(b:Person) RETURN a.name")
-        .unwrap()
-        .with_config(config);
-
-    let mut datasets = HashMap::new();
-    datasets.insert("Person".to_string(), person_batch);
-    datasets.insert("KNOWS".to_string(), knows_batch);
-
-    let result = query
-        .execute(datasets, Some(ExecutionStrategy::DataFusion))
-        .await
-        .unwrap();
-
-    assert_eq!(result.num_columns(), 1);
-    assert_eq!(result.num_rows(), 5);
-
-    // Verify exact counts
-    let names = result
-        .column(0)
-        .as_any()
-        .downcast_ref::<StringArray>()
-        .unwrap();
-    let mut counts = std::collections::HashMap::<String, usize>::new();
-    for i in 0..result.num_rows() {
-        *counts.entry(names.value(i).to_string()).or_insert(0) += 1;
-    }
+    // No SessionContext registration: the catalog's providers are scanned directly
+    let result = CypherQuery::new(
+        "MATCH (a:Person)-[:KNOWS]->(b:Person) WHERE a.name = 'Alice' RETURN b.name ORDER BY b.name",
+    )
+    .unwrap()
+    .with_config(create_graph_config())
+    .execute_with_catalog(Arc::new(catalog))
+    .await
+    .unwrap();
 
-    // Edges: 1->2, 2->3, 3->4, 4->5, 1->3
-    assert_eq!(counts.get("Alice"), Some(&2));
-    assert_eq!(counts.get("Bob"), Some(&1));
-    assert_eq!(counts.get("Charlie"), Some(&1));
-    assert_eq!(counts.get("David"), Some(&1));
-    assert!(!counts.contains_key("Eve"));
+    assert_eq!(get_string_column(&result, 0), vec!["Bob", "Charlie"]);
 }
 
 #[tokio::test]
-async fn test_datafusion_complex_filtering() {
-    let config = create_graph_config();
+async fn test_datafusion_execute_stream_with_provider_catalog() {
+    use datafusion::datasource::MemTable;
+    use futures::TryStreamExt;
+    use lance_graph::source_catalog::ProviderCatalog;
+
     let person_batch = create_person_dataset();
     let knows_batch = create_knows_dataset();
+    let catalog = ProviderCatalog::new()
+        .with_node_table(
+            "Person",
+            Arc::new(MemTable::try_new(person_batch.schema(), vec![vec![person_batch]]).unwrap()),
+        )
+        .with_relationship_table(
+            "KNOWS",
+            Arc::new(MemTable::try_new(knows_batch.schema(), vec![vec![knows_batch]]).unwrap()),
+        );
 
-    // WHERE a.age > 30 filters source, {age: 30} filters target
-    let query = CypherQuery::new(
-        "MATCH (a:Person)-[:KNOWS]->(b:Person {age: 30}) WHERE a.age > 30 RETURN a.name",
+    let stream = CypherQuery::new(
+        "MATCH (a:Person)-[:KNOWS]->(b:Person) WHERE a.name = 'Alice' RETURN b.name ORDER BY b.name",
     )
     .unwrap()
-    .with_config(config);
+    .with_config(create_graph_config())
+    .execute_stream_with_catalog(Arc::new(catalog))
+    .await
+    .unwrap();
+    let schema = stream.schema();
+    let batches: Vec<RecordBatch> = stream.try_collect().await.unwrap();
+    let result = arrow::compute::concat_batches(&schema, &batches).unwrap();
 
-    let mut datasets = HashMap::new();
-    datasets.insert("Person".to_string(), person_batch);
-    datasets.insert("KNOWS".to_string(), knows_batch);
+    assert_eq!(schema.field(0).name(), "b.name");
+    assert_eq!(get_string_column(&result, 0), vec!["Bob", "Charlie"]);
+}
 
-    let result = query
-        .execute(datasets, Some(ExecutionStrategy::DataFusion))
+#[tokio::test]
+async fn test_datafusion_query_cancellation() {
+    use futures::StreamExt;
+    use lance_graph::{CancellationToken, GraphError};
+
+    let datasets = || {
+        let mut datasets = HashMap::new();
+        datasets.insert("Person".to_string(), create_person_dataset());
+        datasets.insert("KNOWS".to_string(), create_knows_dataset());
+        datasets
+    };
+    let query = || {
+        CypherQuery::new("MATCH (a:Person)-[:KNOWS*1..3]->(b:Person) RETURN a.name, b.name")
+            .unwrap()
+            .with_config(create_graph_config())
+    };
+
+    // A cancelled token stops the query before it returns a row
+    let token = CancellationToken::new();
+    token.cancel();
+    let err = query()
+        .with_cancellation_token(token)
+        .execute(datasets(), None)
         .await
-        .unwrap();
+        .unwrap_err();
+    assert!(matches!(err, GraphError::Cancelled { .. }), "{}", err);
 
-    assert_eq!(result.num_columns(), 1);
-    // Only Bob (35) -> Charlie (30), David doesn't connect to anyone age 30
-    assert_eq!(result.num_rows(), 1);
+    // So does a deadline already passed
+    let err = query()
+        .with_timeout(std::time::Duration::ZERO)
+        .execute(datasets(), None)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("timed out"), "{}", err);
+
+    // A stream ends with the cancellation error once its token is cancelled
+    let token = CancellationToken::new();
+    let ctx = datafusion::execution::context::SessionContext::new();
+    ctx.register_batch("Person", create_person_dataset())
+        .unwrap();
+    ctx.register_batch("KNOWS", create_knows_dataset()).unwrap();
+    let catalog =
+        lance_graph::datafusion_catalog::catalog_from_session_context(&ctx, &create_graph_config())
+            .await
+            .unwrap();
+    let mut stream = query()
+        .with_cancellation_token(token.clone())
+        .execute_stream_with_catalog_and_context(Arc::new(catalog), ctx)
+        .await
+        .unwrap();
+    token.cancel();
+    let err = stream.next().await.unwrap().unwrap_err();
+    assert!(err.to_string().contains("Query cancelled"), "{}", err);
+    assert!(stream.next().await.is_none());
 
-    // Verify exact results
-    let source_names = result
-        .column(0)
-        .as_any()
-        .downcast_ref::<StringArray>()
+    // A timeout not reached leaves the query to finish
+    let out = query()
+        .with_timeout(std::time::Duration::from_secs(60))
+        .execute(datasets(), None)
+        .await
         .unwrap();
-    // Should only be Bob
-    assert_eq!(source_names.value(0), "Bob");
+    assert!(out.num_rows() > 0);
 }
 
 #[tokio::test]
-async fn test_datafusion_projection_multiple_properties() {
-    let config = create_graph_config();
-    let person_batch = create_person_dataset();
+async fn test_datafusion_execute_with_metrics() {
+    let ctx = datafusion::execution::context::SessionContext::new();
+    ctx.register_batch("Person", create_person_dataset())
+        .unwrap();
+    ctx.register_batch("KNOWS", create_knows_dataset()).unwrap();
+    let catalog =
+        lance_graph::datafusion_catalog::catalog_from_session_context(&ctx, &create_graph_config())
+            .await
+            .unwrap();
 
-    let query = CypherQuery::new("MATCH (p:Person) WHERE p.age >= 28 RETURN p.name, p.age")
+    let result = CypherQuery::new("MATCH (a:Person)-[:KNOWS]->(b:Person) RETURN a.name, b.name")
         .unwrap()
-        .with_config(config);
-
-    let mut datasets = HashMap::new();
-    datasets.insert("Person".to_string(), person_batch);
-
-    let result = query
-        .execute(datasets, Some(ExecutionStrategy::DataFusion))
+        .with_config(create_graph_config())
+        .execute_with_metrics(Arc::new(catalog), ctx)
         .await
         .unwrap();
 
-    // Should return people with age >= 28 (Bob:35, Charlie:30, Eve:28, David:40)
-    assert_eq!(result.num_rows(), 4);
-    assert_eq!(result.num_columns(), 2);
-
-    // Verify column types and data
-    let names = result
-        .column(0)
-        .as_any()
-        .downcast_ref::<StringArray>()
-        .unwrap();
-    let ages = result
-        .column(1)
-        .as_any()
-        .downcast_ref::<Int64Array>()
-        .unwrap();
+    let metrics = &result.metrics;
+    assert_eq!(metrics.output_rows, result.batch.num_rows());
+    assert!(metrics.output_batches >= 1);
+    let operators = metrics.operators();
+    assert!(operators.iter().any(|op| op.operator.contains("Join")));
+    // The root produced every returned row
+    assert_eq!(metrics.plan.output_rows, Some(result.batch.num_rows()));
+    assert!(metrics.slowest_operator().is_some());
+}
 
-    for i in 0..result.num_rows() {
-        let age = ages.value(i);
-        assert!(age >= 28);
+#[tokio::test]
+async fn test_datafusion_execution_config() {
+    use lance_graph::{ExecutionConfig, Repartitioning};
+
+    let run = |execution: ExecutionConfig| async move {
+        let config = GraphConfig::builder()
+            .with_node_label("Person", "id")
+            .with_relationship("KNOWS", "src_person_id", "dst_person_id")
+            .with_execution_config(execution)
+            .build()
+            .unwrap();
+        let ctx = datafusion::execution::context::SessionContext::new();
+        ctx.register_batch("Person", create_person_dataset())
+            .unwrap();
+        ctx.register_batch("KNOWS", create_knows_dataset()).unwrap();
+        let catalog = lance_graph::datafusion_catalog::catalog_from_session_context(&ctx, &config)
+            .await
+            .unwrap();
+        CypherQuery::new(
+            "MATCH (a:Person)-[:KNOWS]->(b:Person) RETURN a.name, b.name ORDER BY a.name, b.name",
+        )
+        .unwrap()
+        .with_config(config)
+        .execute_with_metrics(Arc::new(catalog), ctx)
+        .await
+        .unwrap()
+    };
 
-        let name = names.value(i);
-        assert!(["Bob", "Charlie", "Eve", "David"].contains(&name));
+    let default = run(ExecutionConfig::default()).await;
+    let single = run(ExecutionConfig::default()
+        .with_target_partitions(1)
+        .with_batch_size(1)
+        .with_repartitioning(Repartitioning::Disabled))
+    .await;
+    let wide = run(ExecutionConfig::default()
+        .with_target_partitions(8)
+        .with_repartitioning(Repartitioning::Enabled))
+    .await;
+    for result in [&single, &wide] {
+        assert_eq!(
+            get_string_column(&result.batch, 0),
+            get_string_column(&default.batch, 0)
+        );
+        assert_eq!(
+            get_string_column(&result.batch, 1),
+            get_string_column(&default.batch, 1)
+        );
     }
+    // One partition leaves nothing to repartition; eight spread the join over them
+    let repartitions = |result: &lance_graph::QueryResult| {
+        result
+            .metrics
+            .operators()
+            .iter()
+            .filter(|op| op.operator == "RepartitionExec")
+            .count()
+    };
+    assert_eq!(repartitions(&single), 0);
+    assert!(repartitions(&wide) > 0);
 }
 
 #[tokio::test]
-async fn test_datafusion_error_handling_missing_config() {
-    let person_batch = create_person_dataset();
+async fn test_datafusion_memory_limit() {
+    use lance_graph::GraphError;
+
+    let datasets = || {
+        let mut datasets = HashMap::new();
+        datasets.insert("Person".to_string(), create_person_dataset());
+        datasets.insert("KNOWS".to_string(), create_knows_dataset());
+        datasets
+    };
+    let query = || {
+        CypherQuery::new(
+            "MATCH (a:Person)-[:KNOWS]->(b:Person) RETURN a.name, b.name ORDER BY a.name, b.name",
+        )
+        .unwrap()
+        .with_config(create_graph_config())
+    };
+
+    // Within the limit, joins planned to spill return the same rows
+    let unlimited = query().execute(datasets(), None).await.unwrap();
+    let limited = query()
+        .with_memory_limit(64 * 1024 * 1024)
+        .execute(datasets(), None)
+        .await
+        .unwrap();
+    assert_eq!(
+        get_string_column(&limited, 0),
+        get_string_column(&unlimited, 0)
+    );
+    assert_eq!(
+        get_string_column(&limited, 1),
+        get_string_column(&unlimited, 1)
+    );
 
-    // Query without config should fail
-    let query = CypherQuery::new("MATCH (p:Person) RETURN p.name").unwrap();
+    // A sort that cannot reserve its merge buffer fails instead of growing past the pool
+    let err = query()
+        .with_memory_limit(0)
+        .execute(datasets(), None)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, GraphError::ExecutionError { .. }), "{}", err);
+    assert!(err.to_string().to_lowercase().contains("memory"), "{}", err);
+}
 
-    let mut datasets = HashMap::new();
-    datasets.insert("Person".to_string(), person_batch);
+#[tokio::test]
+async fn test_datafusion_composite_node_keys() {
+    use datafusion::datasource::MemTable;
+    use lance_graph::config::{NodeMapping, RelationshipMapping};
+    use lance_graph::source_catalog::ProviderCatalog;
+
+    // user_id 1 exists in both tenants; only (tenant_id, user_id) identifies a user
+    let users = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("tenant_id", DataType::Int64, false),
+            Field::new("user_id", DataType::Int64, false),
+            Field::new("name", DataType::Utf8, false),
+        ])),
+        vec![
+            Arc::new(Int64Array::from(vec![1, 1, 2, 2])),
+            Arc::new(Int64Array::from(vec![1, 2, 1, 2])),
+            Arc::new(StringArray::from(vec!["Alice", "Bob", "Carol", "Dan"])),
+        ],
+    )
+    .unwrap();
+    let follows = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("src_tenant", DataType::Int64, false),
+            Field::new("src_user", DataType::Int64, false),
+            Field::new("dst_tenant", DataType::Int64, false),
+            Field::new("dst_user", DataType::Int64, false),
+        ])),
+        vec![
+            Arc::new(Int64Array::from(vec![1, 2])),
+            Arc::new(Int64Array::from(vec![1, 2])),
+            Arc::new(Int64Array::from(vec![1, 2])),
+            Arc::new(Int64Array::from(vec![2, 1])),
+        ],
+    )
+    .unwrap();
 
-    let result = query
-        .execute(datasets, Some(ExecutionStrategy::DataFusion))
-        .await;
-    assert!(result.is_err());
+    let catalog = ProviderCatalog::new()
+        .with_node_table(
+            "User",
+            Arc::new(MemTable::try_new(users.schema(), vec![vec![users]]).unwrap()),
+        )
+        .with_relationship_table(
+            "FOLLOWS",
+            Arc::new(MemTable::try_new(follows.schema(), vec![vec![follows]]).unwrap()),
+        );
+    let config = GraphConfig::builder()
+        .with_node_mapping(
+            NodeMapping::new("User", "user_id")
+                .with_composite_id(vec!["tenant_id".to_string(), "user_id".to_string()]),
+        )
+        .with_relationship_mapping(
+            RelationshipMapping::new("FOLLOWS", "src_user", "dst_user").with_composite_endpoints(
+                vec!["src_tenant".to_string(), "src_user".to_string()],
+                vec!["dst_tenant".to_string(), "dst_user".to_string()],
+            ),
+        )
+        .build()
+        .unwrap();
 
-    let error_msg = format!("{:?}", result.unwrap_err());
-    assert!(error_msg.contains("Graph configuration is required"));
+    let result = CypherQuery::new(
+        "MATCH (a:User)-[:FOLLOWS]->(b:User) RETURN a.name, b.name ORDER BY a.name",
+    )
+    .unwrap()
+    .with_config(config)
+    .execute_with_catalog(Arc::new(catalog))
+    .await
+    .unwrap();
+
+    // A single-column join on user_id would also pair Alice->Dan and Dan->Alice
+    assert_eq!(get_string_column(&result, 0), vec!["Alice", "Dan"]);
+    assert_eq!(get_string_column(&result, 1), vec!["Bob", "Carol"]);
 }
 
 #[tokio::test]
-async fn test_datafusion_error_handling_empty_datasets() {
-    let config = create_graph_config();
-
-    let query = CypherQuery::new("MATCH (p:Person) RETURN p.name")
-        .unwrap()
-        .with_config(config);
+async fn test_datafusion_relationship_properties_with_unlabeled_endpoints() {
+    use lance_graph::config::RelationshipMapping;
 
-    let datasets = HashMap::new(); // Empty datasets
+    let config = GraphConfig::builder()
+        .with_node_label("Person", "id")
+        .with_relationship_mapping(
+            RelationshipMapping::new("KNOWS", "src_person_id", "dst_person_id")
+                .with_properties(vec!["since_year".to_string()])
+                .with_endpoint_labels("Person", "Person"),
+        )
+        .build()
+        .unwrap();
+    let datasets = || {
+        HashMap::from([
+            ("Person".to_string(), create_person_dataset()),
+            ("KNOWS".to_string(), create_knows_dataset()),
+        ])
+    };
 
-    let result = query
-        .execute(datasets, Some(ExecutionStrategy::DataFusion))
-        .await;
-    assert!(result.is_err());
+    // Endpoint labels come from the mapping, so the pattern needs none
+    let result = CypherQuery::new(
+        "MATCH ()-[r:KNOWS]->() WHERE r.since_year > 2019 RETURN r.since_year ORDER BY r.since_year",
+    )
+    .unwrap()
+    .with_config(config.clone())
+    .execute(datasets(), Some(ExecutionStrategy::DataFusion))
+    .await
+    .unwrap();
+    let years = result
+        .column(0)
+        .as_any()
+        .downcast_ref::<Int64Array>()
+        .unwrap();
+    assert_eq!(years.values(), &[2020, 2021]);
 
-    let error_msg = format!("{:?}", result.unwrap_err());
-    assert!(error_msg.contains("No input datasets provided"));
+    // Only declared relationship properties are addressable
+    let err = CypherQuery::new("MATCH (a:Person)-[r:KNOWS]->(b:Person) RETURN r.weight")
+        .unwrap()
+        .with_config(config)
+        .execute(datasets(), Some(ExecutionStrategy::DataFusion))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("weight"), "{}", err);
 }
 
 #[tokio::test]
-async fn test_datafusion_performance_large_dataset() {
-    let config = create_graph_config();
+async fn test_datafusion_undirected_relationship_mapping() {
+    use lance_graph::config::RelationshipMapping;
 
-    // Create a larger dataset for performance testing
-    let large_size = 1000;
-    let ids: Vec<i64> = (1..=large_size).collect();
-    let names: Vec<String> = (1..=large_size).map(|i| format!("Person{}", i)).collect();
-    let ages: Vec<i64> = (1..=large_size).map(|i| 20 + (i % 50)).collect();
+    let config = GraphConfig::builder()
+        .with_node_label("Person", "id")
+        .with_relationship_mapping(
+            RelationshipMapping::new("KNOWS", "src_person_id", "dst_person_id")
+                .with_undirected(true),
+        )
+        .build()
+        .unwrap();
+    let datasets = || {
+        HashMap::from([
+            ("Person".to_string(), create_person_dataset()),
+            ("KNOWS".to_string(), create_knows_dataset()),
+        ])
+    };
+
+    // Each row is one unordered pair, so Charlie matches both stored directions
+    for query in [
+        "MATCH (a:Person)-[:KNOWS]->(b:Person) WHERE a.name = 'Charlie' RETURN b.name ORDER BY b.name",
+        "MATCH (a:Person)-[:KNOWS]-(b:Person) WHERE a.name = 'Charlie' RETURN b.name ORDER BY b.name",
+    ] {
+        let result = CypherQuery::new(query)
+            .unwrap()
+            .with_config(config.clone())
+            .execute(datasets(), Some(ExecutionStrategy::DataFusion))
+            .await
+            .unwrap();
+        assert_eq!(
+            get_string_column(&result, 0),
+            vec!["Alice", "Bob", "David"],
+            "{}",
+            query
+        );
+    }
 
-    let schema = Arc::new(Schema::new(vec![
-        Field::new("id", DataType::Int64, false),
-        Field::new("name", DataType::Utf8, false),
-        Field::new("age", DataType::Int64, false),
-    ]));
+    // Expansion over an intermediate plan unions both directions too
+    let result = CypherQuery::new(
+        "MATCH (a:Person)-[:KNOWS]-(b:Person)-[:KNOWS]-(c:Person) \
+         WHERE a.name = 'Eve' RETURN c.name ORDER BY c.name",
+    )
+    .unwrap()
+    .with_config(config)
+    .execute(datasets(), Some(ExecutionStrategy::DataFusion))
+    .await
+    .unwrap();
+    assert_eq!(get_string_column(&result, 0), vec!["Charlie", "Eve"]);
+}
 
-    let large_batch = RecordBatch::try_new(
-        schema,
+#[tokio::test]
+async fn test_datafusion_relationship_types_sharing_table() {
+    use lance_graph::config::RelationshipMapping;
+
+    let edges = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("src", DataType::Int64, false),
+            Field::new("dst", DataType::Int64, false),
+            Field::new("rel_type", DataType::Utf8, false),
+        ])),
         vec![
-            Arc::new(Int64Array::from(ids)),
-            Arc::new(StringArray::from(names)),
-            Arc::new(Int64Array::from(ages)),
+            Arc::new(Int64Array::from(vec![1, 1, 2, 3])),
+            Arc::new(Int64Array::from(vec![2, 3, 3, 5])),
+            Arc::new(StringArray::from(vec!["KNOWS", "LIKES", "KNOWS", "LIKES"])),
         ],
     )
     .unwrap();
-
-    let query = CypherQuery::new("MATCH (p:Person) WHERE p.age > 40 RETURN p.name")
-        .unwrap()
-        .with_config(config);
-
-    let mut datasets = HashMap::new();
-    datasets.insert("Person".to_string(), large_batch);
-
-    let start = std::time::Instant::now();
-    let result = query
-        .execute(datasets, Some(ExecutionStrategy::DataFusion))
-        .await
+    let config = GraphConfig::builder()
+        .with_node_label("Person", "id")
+        .with_relationship_types(
+            RelationshipMapping::new("edges", "src", "dst").with_type_field("rel_type"),
+            ["KNOWS", "LIKES"],
+        )
+        .build()
         .unwrap();
-    let duration = start.elapsed();
-
-    // Should complete reasonably quickly (adjust threshold as needed)
-    assert!(
-        duration.as_millis() < 1000,
-        "Query took too long: {:?}",
-        duration
-    );
+    let datasets = || {
+        HashMap::from([
+            ("Person".to_string(), create_person_dataset()),
+            ("edges".to_string(), edges.clone()),
+        ])
+    };
 
-    // Verify correct filtering (ages 41-69 out of 20-69 range)
-    let actual_count = result.num_rows();
+    let result = CypherQuery::new(
+        "MATCH (a:Person)-[:LIKES]->(b:Person) RETURN a.name, b.name ORDER BY a.name",
+    )
+    .unwrap()
+    .with_config(config.clone())
+    .execute(datasets(), Some(ExecutionStrategy::DataFusion))
+    .await
+    .unwrap();
+    assert_eq!(get_string_column(&result, 0), vec!["Alice", "Charlie"]);
+    assert_eq!(get_string_column(&result, 1), vec!["Charlie", "Eve"]);
 
-    // Each age appears 20 times (1000 people, ages 20-69, so 50 different ages)
-    // Ages 41-69 = 29 ages * 20 people each = 580 people
-    assert_eq!(actual_count, 580);
+    // Two types over the same table in one pattern each keep their own rows
+    let result = CypherQuery::new(
+        "MATCH (a:Person)-[:KNOWS]->(b:Person)-[:LIKES]->(c:Person) RETURN a.name, c.name",
+    )
+    .unwrap()
+    .with_config(config)
+    .execute(datasets(), Some(ExecutionStrategy::DataFusion))
+    .await
+    .unwrap();
+    assert_eq!(get_string_column(&result, 0), vec!["Bob"]);
+    assert_eq!(get_string_column(&result, 1), vec!["Eve"]);
 }
 
 #[tokio::test]
-async fn test_datafusion_empty_result_set() {
-    let config = create_graph_config();
-    let person_batch = create_person_dataset();
-
-    // Query that should return no results
-    let query = CypherQuery::new("MATCH (p:Person) WHERE p.age > 100 RETURN p.name")
-        .unwrap()
-        .with_config(config);
+async fn test_datafusion_property_aliases() {
+    use lance_graph::config::{NodeMapping, RelationshipMapping};
 
-    let mut datasets = HashMap::new();
-    datasets.insert("Person".to_string(), person_batch);
-
-    let result = query
-        .execute(datasets, Some(ExecutionStrategy::DataFusion))
-        .await
+    let config = GraphConfig::builder()
+        .with_node_mapping(
+            NodeMapping::new("Person", "id")
+                .with_property_alias("fullName", "name")
+                .with_property_alias("hometown", "city"),
+        )
+        .with_relationship_mapping(
+            RelationshipMapping::new("KNOWS", "src_person_id", "dst_person_id")
+                .with_properties(vec!["since".to_string()])
+                .with_property_alias("since", "since_year"),
+        )
+        .build()
         .unwrap();
+    let datasets = || {
+        HashMap::from([
+            ("Person".to_string(), create_person_dataset()),
+            ("KNOWS".to_string(), create_knows_dataset()),
+        ])
+    };
 
-    // Should return empty result set
-    assert_eq!(result.num_rows(), 0);
-    // Note: Even with 0 rows, DataFusion still returns the expected column structure
-    assert!(result.num_columns() >= 1);
+    let result =
+        CypherQuery::new("MATCH (p:Person {fullName: 'Bob'}) RETURN p.fullName, p.hometown")
+            .unwrap()
+            .with_config(config.clone())
+            .execute(datasets(), Some(ExecutionStrategy::DataFusion))
+            .await
+            .unwrap();
+    assert_eq!(get_string_column(&result, 0), vec!["Bob"]);
+    assert_eq!(get_string_column(&result, 1), vec!["San Francisco"]);
+
+    let result = CypherQuery::new(
+        "MATCH (a:Person)-[r:KNOWS]->(b:Person {hometown: 'Chicago'}) \
+         WHERE r.since > 2018 RETURN a.fullName, r.since",
+    )
+    .unwrap()
+    .with_config(config)
+    .execute(datasets(), Some(ExecutionStrategy::DataFusion))
+    .await
+    .unwrap();
+    assert_eq!(get_string_column(&result, 0), vec!["Bob"]);
+    let since = result
+        .column(1)
+        .as_any()
+        .downcast_ref::<Int64Array>()
+        .unwrap();
+    assert_eq!(since.values(), &[2019]);
 }
 
 #[tokio::test]
-async fn test_datafusion_all_columns_projection() {
-    let config = create_graph_config();
-    let person_batch = create_person_dataset();
+async fn test_datafusion_computed_properties() {
+    use lance_graph::config::{NodeMapping, RelationshipMapping};
 
-    // Query that returns all columns
-    let query =
-        CypherQuery::new("MATCH (p:Person) WHERE p.id = 1 RETURN p.id, p.name, p.age, p.city")
-            .unwrap()
-            .with_config(config);
+    let config = GraphConfig::builder()
+        .with_node_mapping(
+            NodeMapping::new("Person", "id")
+                .with_properties(vec!["name".to_string()])
+                .with_computed_property("birth_year", "2025 - age")
+                .with_computed_property("shout", "upper(name)"),
+        )
+        .with_relationship_mapping(
+            RelationshipMapping::new("KNOWS", "src_person_id", "dst_person_id")
+                .with_computed_property("years_known", "2025 - since_year"),
+        )
+        .build()
+        .unwrap();
+    let datasets = || {
+        HashMap::from([
+            ("Person".to_string(), create_person_dataset()),
+            ("KNOWS".to_string(), create_knows_dataset()),
+        ])
+    };
 
-    let mut datasets = HashMap::new();
-    datasets.insert("Person".to_string(), person_batch);
+    let result = CypherQuery::new(
+        "MATCH (p:Person) WHERE p.birth_year < 1995 RETURN p.shout, p.birth_year ORDER BY p.birth_year",
+    )
+    .unwrap()
+    .with_config(config.clone())
+    .execute(datasets(), Some(ExecutionStrategy::DataFusion))
+    .await
+    .unwrap();
+    assert_eq!(get_string_column(&result, 0), vec!["DAVID", "BOB"]);
 
-    let result = query
-        .execute(datasets, Some(ExecutionStrategy::DataFusion))
+    // Computed properties also work in pattern filters and on relationships
+    let result = CypherQuery::new(
+        "MATCH (a:Person)-[r:KNOWS]->(b:Person {birth_year: 1995}) \
+         RETURN a.name, r.years_known ORDER BY a.name",
+    )
+    .unwrap()
+    .with_config(config.clone())
+    .execute(datasets(), Some(ExecutionStrategy::DataFusion))
+    .await
+    .unwrap();
+    assert_eq!(get_string_column(&result, 0), vec!["Alice", "Bob"]);
+    let years = result
+        .column(1)
+        .as_any()
+        .downcast_ref::<Int64Array>()
+        .unwrap();
+    assert_eq!(years.values(), &[7, 6]);
+
+    // Invalid expressions surface as configuration errors naming the property
+    let broken = GraphConfig::builder()
+        .with_node_mapping(NodeMapping::new("Person", "id").with_computed_property("x", "nope + 1"))
+        .build()
+        .unwrap();
+    let err = CypherQuery::new("MATCH (p:Person) RETURN p.x")
+        .unwrap()
+        .with_config(broken)
+        .execute(datasets(), Some(ExecutionStrategy::DataFusion))
         .await
+        .unwrap_err();
+    assert!(err.to_string().contains("computed property 'x'"), "{}", err);
+}
+
+#[tokio::test]
+async fn test_datafusion_adjacency_list_relationships() {
+    use arrow_array::builder::{Int64Builder, ListBuilder, StructBuilder};
+    use arrow_schema::Fields;
+    use lance_graph::config::RelationshipMapping;
+
+    // friends: list<struct<friend_id, since>> stored on each person row
+    let edge_fields = Fields::from(vec![
+        Field::new("friend_id", DataType::Int64, false),
+        Field::new("since", DataType::Int64, false),
+    ]);
+    let mut friends = ListBuilder::new(StructBuilder::from_fields(edge_fields, 0));
+    for edges in [vec![(2, 2015), (3, 2020)], vec![(3, 2018)], vec![]] {
+        for (friend_id, since) in edges {
+            let edge = friends.values();
+            edge.field_builder::<Int64Builder>(0)
+                .unwrap()
+                .append_value(friend_id);
+            edge.field_builder::<Int64Builder>(1)
+                .unwrap()
+                .append_value(since);
+            edge.append(true);
+        }
+        friends.append(true);
+    }
+    let friends = friends.finish();
+    let people = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("name", DataType::Utf8, false),
+            Field::new("friends", friends.data_type().clone(), true),
+        ])),
+        vec![
+            Arc::new(Int64Array::from(vec![1, 2, 3])),
+            Arc::new(StringArray::from(vec!["Alice", "Bob", "Carol"])),
+            Arc::new(friends),
+        ],
+    )
+    .unwrap();
+
+    let config = GraphConfig::builder()
+        .with_node_label("Person", "id")
+        .with_relationship_mapping(
+            RelationshipMapping::new("FRIEND", "id", "friend_id")
+                .with_endpoint_labels("Person", "Person")
+                .with_adjacency_column("friends"),
+        )
+        .build()
         .unwrap();
 
-    // Should return Alice's data
-    assert_eq!(result.num_rows(), 1);
-    assert_eq!(result.num_columns(), 4);
+    let result = CypherQuery::new(
+        "MATCH (a:Person)-[r:FRIEND]->(b:Person) WHERE r.since > 2016 \
+         RETURN a.name, b.name, r.since ORDER BY r.since",
+    )
+    .unwrap()
+    .with_config(config.clone())
+    .execute(
+        HashMap::from([("Person".to_string(), people.clone())]),
+        Some(ExecutionStrategy::DataFusion),
+    )
+    .await
+    .unwrap();
+    assert_eq!(get_string_column(&result, 0), vec!["Bob", "Alice"]);
+    assert_eq!(get_string_column(&result, 1), vec!["Carol", "Carol"]);
 
-    // Verify Alice's data
-    let ids = result
-        .column(0)
-        .as_any()
-        .downcast_ref::<Int64Array>()
+    // Two hops through the unnested edges
+    let result = CypherQuery::new(
+        "MATCH (a:Person)-[:FRIEND]->(b:Person)-[:FRIEND]->(c:Person) RETURN a.name, c.name",
+    )
+    .unwrap()
+    .with_config(config)
+    .execute(
+        HashMap::from([("Person".to_string(), people)]),
+        Some(ExecutionStrategy::DataFusion),
+    )
+    .await
+    .unwrap();
+    assert_eq!(get_string_column(&result, 0), vec!["Alice"]);
+    assert_eq!(get_string_column(&result, 1), vec!["Carol"]);
+}
+
+#[tokio::test]
+async fn test_datafusion_label_hierarchy() {
+    use lance_graph::config::NodeMapping;
+
+    let employees = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("employee_id", DataType::Int64, false),
+            Field::new("name", DataType::Utf8, false),
+            Field::new("salary", DataType::Int64, false),
+        ])),
+        vec![
+            Arc::new(Int64Array::from(vec![6, 7])),
+            Arc::new(StringArray::from(vec!["Frank", "Grace"])),
+            Arc::new(Int64Array::from(vec![120, 90])),
+        ],
+    )
+    .unwrap();
+    let knows = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("src_person_id", DataType::Int64, false),
+            Field::new("dst_person_id", DataType::Int64, false),
+        ])),
+        vec![
+            Arc::new(Int64Array::from(vec![1, 1, 6])),
+            Arc::new(Int64Array::from(vec![2, 6, 7])),
+        ],
+    )
+    .unwrap();
+    let config = GraphConfig::builder()
+        .with_node_label("Person", "id")
+        .with_node_mapping(NodeMapping::new("Employee", "employee_id").with_extends("Person"))
+        .with_relationship("KNOWS", "src_person_id", "dst_person_id")
+        .build()
         .unwrap();
-    let names = result
+    let datasets = || {
+        HashMap::from([
+            ("Person".to_string(), create_person_dataset()),
+            ("Employee".to_string(), employees.clone()),
+            ("KNOWS".to_string(), knows.clone()),
+        ])
+    };
+    let run = |query: &'static str| {
+        let config = config.clone();
+        async move {
+            CypherQuery::new(query)
+                .unwrap()
+                .with_config(config)
+                .execute(datasets(), Some(ExecutionStrategy::DataFusion))
+                .await
+                .unwrap()
+        }
+    };
+
+    // The parent label matches its own rows and every sub-label's rows
+    let result = run("MATCH (p:Person) WHERE p.id > 4 RETURN p.name, p.salary ORDER BY p.id").await;
+    assert_eq!(get_string_column(&result, 0), vec!["Eve", "Frank", "Grace"]);
+    let salaries = result
         .column(1)
         .as_any()
-        .downcast_ref::<StringArray>()
+        .downcast_ref::<Int64Array>()
         .unwrap();
-    let ages = result
-        .column(2)
+    assert!(salaries.is_null(0));
+    assert_eq!(salaries.value(1), 120);
+
+    // Sub-labels only match their own rows
+    let result = run("MATCH (e:Employee) RETURN e.name ORDER BY e.name").await;
+    assert_eq!(get_string_column(&result, 0), vec!["Frank", "Grace"]);
+
+    // Both endpoints of a traversal expand to the hierarchy
+    let result =
+        run("MATCH (a:Person)-[:KNOWS]->(b:Person) RETURN a.name, b.name ORDER BY a.name, b.name")
+            .await;
+    assert_eq!(
+        get_string_column(&result, 0),
+        vec!["Alice", "Alice", "Frank"]
+    );
+    assert_eq!(get_string_column(&result, 1), vec!["Bob", "Frank", "Grace"]);
+}
+
+#[tokio::test]
+async fn test_datafusion_optional_match() {
+    fn optional_names(batch: &RecordBatch, col_idx: usize) -> Vec<Option<String>> {
+        let array = batch
+            .column(col_idx)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        (0..array.len())
+            .map(|i| array.is_valid(i).then(|| array.value(i).to_string()))
+            .collect()
+    }
+
+    // Eve has no outgoing KNOWS edge but is kept with a NULL friend
+    let result = execute_test_query(
+        "MATCH (a:Person) OPTIONAL MATCH (a)-[:KNOWS]->(b:Person) \
+         RETURN a.name, b.name ORDER BY a.name, b.name",
+    )
+    .await;
+    assert_eq!(
+        get_string_column(&result, 0),
+        vec!["Alice", "Alice", "Bob", "Charlie", "David", "Eve"]
+    );
+    assert_eq!(
+        optional_names(&result, 1),
+        vec![
+            Some("Bob".to_string()),
+            Some("Charlie".to_string()),
+            Some("Charlie".to_string()),
+            Some("David".to_string()),
+            Some("Eve".to_string()),
+            None,
+        ]
+    );
+
+    // A WHERE on the OPTIONAL MATCH filters the matches, not the outer rows
+    let result = execute_test_query(
+        "MATCH (a:Person) OPTIONAL MATCH (a)-[:KNOWS]->(b:Person) WHERE b.age > 30 \
+         RETURN a.name, b.name ORDER BY a.name",
+    )
+    .await;
+    assert_eq!(
+        get_string_column(&result, 0),
+        vec!["Alice", "Bob", "Charlie", "David", "Eve"]
+    );
+    assert_eq!(
+        optional_names(&result, 1),
+        vec![
+            Some("Bob".to_string()),
+            None,
+            Some("David".to_string()),
+            None,
+            None,
+        ]
+    );
+
+    // count() skips the NULL-padded rows
+    let result = execute_test_query(
+        "MATCH (a:Person) OPTIONAL MATCH (a)-[:KNOWS]->(b:Person) \
+         RETURN a.name, count(b) AS friends ORDER BY a.name",
+    )
+    .await;
+    let friends = result
+        .column(1)
         .as_any()
         .downcast_ref::<Int64Array>()
         .unwrap();
-    let cities = result
-        .column(3)
+    assert_eq!(friends.values().to_vec(), vec![2, 1, 1, 1, 0]);
+}
+
+#[tokio::test]
+async fn test_datafusion_with_clause() {
+    // Aggregates carried forward and filtered by the WITH's WHERE
+    let result = execute_test_query(
+        "MATCH (a:Person)-[:KNOWS]->(b:Person) \
+         WITH a, count(b) AS friends WHERE friends > 1 \
+         RETURN a.name, friends",
+    )
+    .await;
+    assert_eq!(get_string_column(&result, 0), vec!["Alice"]);
+    let friends = result
+        .column(1)
         .as_any()
-        .downcast_ref::<StringArray>()
+        .downcast_ref::<Int64Array>()
         .unwrap();
+    assert_eq!(friends.values().to_vec(), vec![2]);
 
-    assert_eq!(ids.value(0), 1);
-    assert_eq!(names.value(0), "Alice");
-    assert_eq!(ages.value(0), 25);
-    assert_eq!(cities.value(0), "New York");
+    // A renamed node binding anchors the next MATCH
+    let result = execute_test_query(
+        "MATCH (a:Person {name: 'Alice'})-[:KNOWS]->(b:Person) \
+         WITH b AS friend \
+         MATCH (friend)-[:KNOWS]->(c:Person) \
+         RETURN friend.name, c.name ORDER BY friend.name, c.name",
+    )
+    .await;
+    assert_eq!(get_string_column(&result, 0), vec!["Bob", "Charlie"]);
+    assert_eq!(get_string_column(&result, 1), vec!["Charlie", "David"]);
+
+    // ORDER BY and LIMIT apply to the intermediate rows
+    let result = execute_test_query(
+        "MATCH (a:Person) WITH a ORDER BY a.age DESC LIMIT 2 \
+         RETURN a.name ORDER BY a.name",
+    )
+    .await;
+    assert_eq!(get_string_column(&result, 0), vec!["Bob", "David"]);
+
+    // Scalar bindings are referenced by their alias
+    let result = execute_test_query(
+        "MATCH (a:Person) WITH a.name AS name, a.age AS age WHERE age > 30 \
+         RETURN name ORDER BY name",
+    )
+    .await;
+    assert_eq!(get_string_column(&result, 0), vec!["Bob", "David"]);
 }
 
 #[tokio::test]
-async fn test_datafusion_relationship_count() {
-    let config = create_graph_config();
-    let person_batch = create_person_dataset();
-    let knows_batch = create_knows_dataset();
-
-    // Count relationships with strict verification
-    let query = CypherQuery::new("MATCH (a:Person)-[:KNOWS]->(b:Person) RETURN a.name")
-        .unwrap()
-        .with_config(config);
+async fn test_datafusion_unwind() {
+    // A literal list drives node lookups
+    let result = execute_test_query(
+        "UNWIND [1, 3, 5] AS id MATCH (n:Person {id: id}) RETURN n.name ORDER BY n.name",
+    )
+    .await;
+    assert_eq!(
+        get_string_column(&result, 0),
+        vec!["Alice", "Charlie", "Eve"]
+    );
 
+    // A list parameter anchors a traversal
+    let query = CypherQuery::new(
+        "UNWIND $ids AS id MATCH (n:Person {id: id})-[:KNOWS]->(m:Person) \
+         RETURN n.name, m.name ORDER BY n.name",
+    )
+    .unwrap()
+    .with_config(create_graph_config())
+    .with_parameter("ids", vec![2, 4]);
     let mut datasets = HashMap::new();
-    datasets.insert("Person".to_string(), person_batch);
-    datasets.insert("KNOWS".to_string(), knows_batch);
-
+    datasets.insert("Person".to_string(), create_person_dataset());
+    datasets.insert("KNOWS".to_string(), create_knows_dataset());
     let result = query
         .execute(datasets, Some(ExecutionStrategy::DataFusion))
         .await
         .unwrap();
+    assert_eq!(get_string_column(&result, 0), vec!["Bob", "David"]);
+    assert_eq!(get_string_column(&result, 1), vec!["Charlie", "Eve"]);
 
-    // Should return 5 relationships (as per create_knows_dataset)
-    assert_eq!(result.num_rows(), 5);
+    // collect() results unwind back into rows
+    let result = execute_test_query(
+        "MATCH (a:Person)-[:KNOWS]->(b:Person) WITH a, collect(b.name) AS friends \
+         UNWIND friends AS friend RETURN a.name, friend ORDER BY a.name, friend",
+    )
+    .await;
+    assert_eq!(
+        get_string_column(&result, 0),
+        vec!["Alice", "Alice", "Bob", "Charlie", "David"]
+    );
+    assert_eq!(
+        get_string_column(&result, 1),
+        vec!["Bob", "Charlie", "Charlie", "David", "Eve"]
+    );
 
-    // Verify exact source name counts
-    let names = result
+    // UNWIND without any MATCH expands a single row
+    let result = execute_test_query("UNWIND [3, 1, 2] AS x RETURN x ORDER BY x").await;
+    let values = result
         .column(0)
         .as_any()
-        .downcast_ref::<StringArray>()
+        .downcast_ref::<Int64Array>()
         .unwrap();
-    let mut name_counts = std::collections::HashMap::new();
+    assert_eq!(values.values().to_vec(), vec![1, 2, 3]);
+}
 
-    for i in 0..result.num_rows() {
-        let name = names.value(i);
-        *name_counts.entry(name.to_string()).or_insert(0) += 1;
-    }
+#[tokio::test]
+async fn test_datafusion_union() {
+    let older = "MATCH (p:Person) WHERE p.age > 30 RETURN p.name AS name";
+    let alice_knows =
+        "MATCH (p:Person)-[:KNOWS]->(q:Person) WHERE p.name = 'Alice' RETURN q.name AS name";
+
+    // UNION removes the duplicate Bob
+    let result = execute_test_query(&format!("{} UNION {}", older, alice_knows)).await;
+    let mut names = get_string_column(&result, 0);
+    names.sort();
+    assert_eq!(names, vec!["Bob", "Charlie", "David"]);
 
-    // Edges: 1->2, 2->3, 3->4, 4->5, 1->3
-    // Source name counts: Alice:2, Bob:1, Charlie:1, David:1
-    assert_eq!(name_counts.get("Alice"), Some(&2));
-    assert_eq!(name_counts.get("Bob"), Some(&1));
-    assert_eq!(name_counts.get("Charlie"), Some(&1));
-    assert_eq!(name_counts.get("David"), Some(&1));
-    assert!(!name_counts.contains_key("Eve"));
+    // UNION ALL keeps it
+    let result = execute_test_query(&format!("{} UNION ALL {}", older, alice_knows)).await;
+    let mut names = get_string_column(&result, 0);
+    names.sort();
+    assert_eq!(names, vec!["Bob", "Bob", "Charlie", "David"]);
 
-    // Verify total
-    let total_relationships: usize = name_counts.values().sum();
-    assert_eq!(total_relationships, 5);
+    // Parts returning different columns are rejected
+    let query = CypherQuery::new(&format!(
+        "{} UNION MATCH (p:Person) RETURN p.name AS person",
+        older
+    ))
+    .unwrap()
+    .with_config(create_graph_config());
+    let mut datasets = HashMap::new();
+    datasets.insert("Person".to_string(), create_person_dataset());
+    let err = query
+        .execute(datasets, Some(ExecutionStrategy::DataFusion))
+        .await
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("same columns"),
+        "unexpected error: {}",
+        err
+    );
 }
 
 #[tokio::test]
-async fn test_datafusion_one_hop_source_names_strict() {
+async fn test_datafusion_relationship_with_variable() {
     let config = create_graph_config();
     let person_batch = create_person_dataset();
     let knows_batch = create_knows_dataset();
 
+    // Test relationship traversal with strict count verification
     let query = CypherQuery::new("MATCH (a:Person)-[:KNOWS]->(b:Person) RETURN a.name")
         .unwrap()
         .with_config(config);
@@ -670,24 +1310,26 @@ async fn test_datafusion_one_hop_source_names_strict() {
     datasets.insert("Person".to_string(), person_batch);
     datasets.insert("KNOWS".to_string(), knows_batch);
 
-    let out = query
+    let result = query
         .execute(datasets, Some(ExecutionStrategy::DataFusion))
         .await
         .unwrap();
-    assert_eq!(out.num_columns(), 1);
-    assert_eq!(out.num_rows(), 5);
 
-    let names = out
+    assert_eq!(result.num_columns(), 1);
+    assert_eq!(result.num_rows(), 5);
+
+    // Verify exact counts
+    let names = result
         .column(0)
         .as_any()
         .downcast_ref::<StringArray>()
         .unwrap();
     let mut counts = std::collections::HashMap::<String, usize>::new();
-    for i in 0..out.num_rows() {
+    for i in 0..result.num_rows() {
         *counts.entry(names.value(i).to_string()).or_insert(0) += 1;
     }
+
     // Edges: 1->2, 2->3, 3->4, 4->5, 1->3
-    // Source name counts: Alice:2, Bob:1, Charlie:1, David:1
     assert_eq!(counts.get("Alice"), Some(&2));
     assert_eq!(counts.get("Bob"), Some(&1));
     assert_eq!(counts.get("Charlie"), Some(&1));
@@ -696,354 +1338,309 @@ async fn test_datafusion_one_hop_source_names_strict() {
 }
 
 #[tokio::test]
-async fn test_datafusion_one_hop_filtered_source_age_strict() {
+async fn test_datafusion_complex_filtering() {
     let config = create_graph_config();
     let person_batch = create_person_dataset();
     let knows_batch = create_knows_dataset();
 
-    let query =
-        CypherQuery::new("MATCH (a:Person)-[:KNOWS]->(b:Person) WHERE a.age > 30 RETURN a.name")
-            .unwrap()
-            .with_config(config);
+    // WHERE a.age > 30 filters source, {age: 30} filters target
+    let query = CypherQuery::new(
+        "MATCH (a:Person)-[:KNOWS]->(b:Person {age: 30}) WHERE a.age > 30 RETURN a.name",
+    )
+    .unwrap()
+    .with_config(config);
 
     let mut datasets = HashMap::new();
     datasets.insert("Person".to_string(), person_batch);
     datasets.insert("KNOWS".to_string(), knows_batch);
 
-    let out = query
+    let result = query
         .execute(datasets, Some(ExecutionStrategy::DataFusion))
         .await
         .unwrap();
-    assert_eq!(out.num_columns(), 1);
-    // Bob (35): 2->3, David (40): 4->5
-    assert_eq!(out.num_rows(), 2);
 
-    let names = out
+    assert_eq!(result.num_columns(), 1);
+    // Only Bob (35) -> Charlie (30), David doesn't connect to anyone age 30
+    assert_eq!(result.num_rows(), 1);
+
+    // Verify exact results
+    let source_names = result
         .column(0)
         .as_any()
         .downcast_ref::<StringArray>()
         .unwrap();
-    let set: std::collections::HashSet<String> = (0..out.num_rows())
-        .map(|i| names.value(i).to_string())
-        .collect();
-    let expected: std::collections::HashSet<String> = ["Bob", "David"]
-        .into_iter()
-        .map(|s| s.to_string())
-        .collect();
-    assert_eq!(set, expected);
+    // Should only be Bob
+    assert_eq!(source_names.value(0), "Bob");
 }
 
 #[tokio::test]
-async fn test_datafusion_one_hop_with_city_filter() {
+async fn test_datafusion_projection_multiple_properties() {
     let config = create_graph_config();
     let person_batch = create_person_dataset();
-    let knows_batch = create_knows_dataset();
 
-    // Query: Filter targets by city using inline property filter
-    // Tests inline property filter instead of WHERE clause
-    let query =
-        CypherQuery::new("MATCH (a:Person)-[:KNOWS]->(b:Person {city: 'Seattle'}) RETURN b.name")
-            .unwrap()
-            .with_config(config);
+    let query = CypherQuery::new("MATCH (p:Person) WHERE p.age >= 28 RETURN p.name, p.age")
+        .unwrap()
+        .with_config(config);
 
     let mut datasets = HashMap::new();
     datasets.insert("Person".to_string(), person_batch);
-    datasets.insert("KNOWS".to_string(), knows_batch);
 
-    let out = query
+    let result = query
         .execute(datasets, Some(ExecutionStrategy::DataFusion))
         .await
         .unwrap();
 
-    // Only Eve has city = 'Seattle' and is reachable (David->Eve)
-    assert_eq!(out.num_rows(), 1);
+    // Should return people with age >= 28 (Bob:35, Charlie:30, Eve:28, David:40)
+    assert_eq!(result.num_rows(), 4);
+    assert_eq!(result.num_columns(), 2);
 
-    let names = out
+    // Verify column types and data
+    let names = result
         .column(0)
         .as_any()
         .downcast_ref::<StringArray>()
         .unwrap();
-    assert_eq!(names.value(0), "Eve");
+    let ages = result
+        .column(1)
+        .as_any()
+        .downcast_ref::<Int64Array>()
+        .unwrap();
+
+    for i in 0..result.num_rows() {
+        let age = ages.value(i);
+        assert!(age >= 28);
+
+        let name = names.value(i);
+        assert!(["Bob", "Charlie", "Eve", "David"].contains(&name));
+    }
 }
 
 #[tokio::test]
-async fn test_datafusion_one_hop_multiple_properties() {
-    let config = create_graph_config();
+async fn test_datafusion_error_handling_missing_config() {
     let person_batch = create_person_dataset();
-    let knows_batch = create_knows_dataset();
 
-    // Query: Return multiple properties from both source and target
-    let query = CypherQuery::new(
-        "MATCH (a:Person)-[:KNOWS]->(b:Person) \
-         RETURN a.name, a.age, b.name, b.age",
-    )
-    .unwrap()
-    .with_config(config);
+    // Query without config should fail
+    let query = CypherQuery::new("MATCH (p:Person) RETURN p.name").unwrap();
 
     let mut datasets = HashMap::new();
     datasets.insert("Person".to_string(), person_batch);
-    datasets.insert("KNOWS".to_string(), knows_batch);
 
-    let out = query
+    let result = query
         .execute(datasets, Some(ExecutionStrategy::DataFusion))
-        .await
-        .unwrap();
+        .await;
+    assert!(result.is_err());
 
-    assert_eq!(out.num_columns(), 4);
-    assert_eq!(out.num_rows(), 5);
+    let error_msg = format!("{:?}", result.unwrap_err());
+    assert!(error_msg.contains("Graph configuration is required"));
+}
 
-    let a_names = out
-        .column(0)
-        .as_any()
-        .downcast_ref::<StringArray>()
-        .unwrap();
-    let a_ages = out.column(1).as_any().downcast_ref::<Int64Array>().unwrap();
-    let b_names = out
-        .column(2)
-        .as_any()
-        .downcast_ref::<StringArray>()
-        .unwrap();
-    let b_ages = out.column(3).as_any().downcast_ref::<Int64Array>().unwrap();
+#[tokio::test]
+async fn test_datafusion_error_handling_empty_datasets() {
+    let config = create_graph_config();
 
-    // Verify at least one row has correct data
-    let mut found_alice_bob = false;
-    for i in 0..out.num_rows() {
-        if a_names.value(i) == "Alice" && b_names.value(i) == "Bob" {
-            assert_eq!(a_ages.value(i), 25);
-            assert_eq!(b_ages.value(i), 35);
-            found_alice_bob = true;
-        }
-    }
-    assert!(found_alice_bob);
+    let query = CypherQuery::new("MATCH (p:Person) RETURN p.name")
+        .unwrap()
+        .with_config(config);
+
+    let datasets = HashMap::new(); // Empty datasets
+
+    let result = query
+        .execute(datasets, Some(ExecutionStrategy::DataFusion))
+        .await;
+    assert!(result.is_err());
+
+    let error_msg = format!("{:?}", result.unwrap_err());
+    assert!(error_msg.contains("No input datasets provided"));
 }
 
 #[tokio::test]
-async fn test_datafusion_one_hop_return_relationship_properties() {
+async fn test_datafusion_performance_large_dataset() {
     let config = create_graph_config();
-    let person_batch = create_person_dataset();
-    let knows_batch = create_knows_dataset();
 
-    // Query: Return both node and relationship properties in projection
-    // This validates qualified relationship columns and aliasing
-    let query = CypherQuery::new(
-        "MATCH (a:Person)-[r:KNOWS]->(b:Person) \
-         RETURN a.name, r.since_year, b.name \
-         ORDER BY a.name, b.name",
+    // Create a larger dataset for performance testing
+    let large_size = 1000;
+    let ids: Vec<i64> = (1..=large_size).collect();
+    let names: Vec<String> = (1..=large_size).map(|i| format!("Person{}", i)).collect();
+    let ages: Vec<i64> = (1..=large_size).map(|i| 20 + (i % 50)).collect();
+
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int64, false),
+        Field::new("name", DataType::Utf8, false),
+        Field::new("age", DataType::Int64, false),
+    ]));
+
+    let large_batch = RecordBatch::try_new(
+        schema,
+        vec![
+            Arc::new(Int64Array::from(ids)),
+            Arc::new(StringArray::from(names)),
+            Arc::new(Int64Array::from(ages)),
+        ],
     )
-    .unwrap()
-    .with_config(config);
+    .unwrap();
+
+    let query = CypherQuery::new("MATCH (p:Person) WHERE p.age > 40 RETURN p.name")
+        .unwrap()
+        .with_config(config);
 
     let mut datasets = HashMap::new();
-    datasets.insert("Person".to_string(), person_batch);
-    datasets.insert("KNOWS".to_string(), knows_batch);
+    datasets.insert("Person".to_string(), large_batch);
 
-    let out = query
+    let start = std::time::Instant::now();
+    let result = query
         .execute(datasets, Some(ExecutionStrategy::DataFusion))
         .await
         .unwrap();
+    let duration = start.elapsed();
 
-    // Should return 3 columns: a.name, r.since_year, b.name
-    assert_eq!(out.num_columns(), 3);
-    // Should return 5 edges
-    assert_eq!(out.num_rows(), 5);
-
-    let a_names = out
-        .column(0)
-        .as_any()
-        .downcast_ref::<StringArray>()
-        .unwrap();
-    let since_years = out.column(1).as_any().downcast_ref::<Int64Array>().unwrap();
-    let b_names = out
-        .column(2)
-        .as_any()
-        .downcast_ref::<StringArray>()
-        .unwrap();
-
-    // Verify first row: Alice -> Bob (2020)
-    assert_eq!(a_names.value(0), "Alice");
-    assert_eq!(since_years.value(0), 2020);
-    assert_eq!(b_names.value(0), "Bob");
-
-    // Verify second row: Alice -> Charlie (2018)
-    assert_eq!(a_names.value(1), "Alice");
-    assert_eq!(since_years.value(1), 2018);
-    assert_eq!(b_names.value(1), "Charlie");
-
-    // Verify third row: Bob -> Charlie (2019)
-    assert_eq!(a_names.value(2), "Bob");
-    assert_eq!(since_years.value(2), 2019);
-    assert_eq!(b_names.value(2), "Charlie");
+    // Should complete reasonably quickly (adjust threshold as needed)
+    assert!(
+        duration.as_millis() < 1000,
+        "Query took too long: {:?}",
+        duration
+    );
 
-    // Verify fourth row: Charlie -> David (2021)
-    assert_eq!(a_names.value(3), "Charlie");
-    assert_eq!(since_years.value(3), 2021);
-    assert_eq!(b_names.value(3), "David");
+    // Verify correct filtering (ages 41-69 out of 20-69 range)
+    let actual_count = result.num_rows();
 
-    // Verify fifth row: David -> Eve (NULL since_year)
-    assert_eq!(a_names.value(4), "David");
-    assert!(since_years.is_null(4)); // NULL value
-    assert_eq!(b_names.value(4), "Eve");
+    // Each age appears 20 times (1000 people, ages 20-69, so 50 different ages)
+    // Ages 41-69 = 29 ages * 20 people each = 580 people
+    assert_eq!(actual_count, 580);
 }
 
-// ============================================================================
-// Two-Hop Path Query Tests
-// ============================================================================
-
 #[tokio::test]
-async fn test_datafusion_two_hop_basic() {
-    // Query: Find friends of friends
-    // Edges: 1->2, 2->3, 3->4, 4->5, 1->3
-    // Two-hop paths: 1->2->3, 2->3->4, 3->4->5, 1->3->4
-    let out = execute_test_query(
-        "MATCH (a:Person)-[:KNOWS]->(b:Person)-[:KNOWS]->(c:Person) RETURN c.name",
-    )
-    .await;
+async fn test_datafusion_empty_result_set() {
+    let config = create_graph_config();
+    let person_batch = create_person_dataset();
 
-    // Should return: Charlie (from 1->2->3), David (from 2->3->4 and 1->3->4), Eve (from 3->4->5)
-    assert_eq!(out.num_columns(), 1);
-    assert_eq!(out.num_rows(), 4); // 4 two-hop paths
+    // Query that should return no results
+    let query = CypherQuery::new("MATCH (p:Person) WHERE p.age > 100 RETURN p.name")
+        .unwrap()
+        .with_config(config);
 
-    let names = get_string_column(&out, 0);
+    let mut datasets = HashMap::new();
+    datasets.insert("Person".to_string(), person_batch);
 
-    let mut counts = HashMap::<String, usize>::new();
-    for name in names {
-        *counts.entry(name).or_insert(0) += 1;
-    }
+    let result = query
+        .execute(datasets, Some(ExecutionStrategy::DataFusion))
+        .await
+        .unwrap();
 
-    // Verify counts: Charlie:1, David:2, Eve:1
-    assert_eq!(counts.get("Charlie"), Some(&1));
-    assert_eq!(counts.get("David"), Some(&2));
-    assert_eq!(counts.get("Eve"), Some(&1));
-    assert!(!counts.contains_key("Alice"));
-    assert!(!counts.contains_key("Bob"));
+    // Should return empty result set
+    assert_eq!(result.num_rows(), 0);
+    // Note: Even with 0 rows, DataFusion still returns the expected column structure
+    assert!(result.num_columns() >= 1);
 }
 
 #[tokio::test]
-async fn test_datafusion_two_hop_return_intermediate() {
+async fn test_datafusion_all_columns_projection() {
     let config = create_graph_config();
     let person_batch = create_person_dataset();
-    let knows_batch = create_knows_dataset();
 
-    // Query: Return the intermediate node in two-hop paths
-    let query = CypherQuery::new(
-        "MATCH (a:Person)-[:KNOWS]->(b:Person)-[:KNOWS]->(c:Person) RETURN b.name",
-    )
-    .unwrap()
-    .with_config(config);
+    // Query that returns all columns
+    let query =
+        CypherQuery::new("MATCH (p:Person) WHERE p.id = 1 RETURN p.id, p.name, p.age, p.city")
+            .unwrap()
+            .with_config(config);
 
     let mut datasets = HashMap::new();
     datasets.insert("Person".to_string(), person_batch);
-    datasets.insert("KNOWS".to_string(), knows_batch);
 
-    let out = query
+    let result = query
         .execute(datasets, Some(ExecutionStrategy::DataFusion))
         .await
         .unwrap();
-    assert_eq!(out.num_columns(), 1);
-    assert_eq!(out.num_rows(), 4);
 
-    let names = out
-        .column(0)
-        .as_any()
+    // Should return Alice's data
+    assert_eq!(result.num_rows(), 1);
+    assert_eq!(result.num_columns(), 4);
+
+    // Verify Alice's data
+    let ids = result
+        .column(0)
+        .as_any()
+        .downcast_ref::<Int64Array>()
+        .unwrap();
+    let names = result
+        .column(1)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+    let ages = result
+        .column(2)
+        .as_any()
+        .downcast_ref::<Int64Array>()
+        .unwrap();
+    let cities = result
+        .column(3)
+        .as_any()
         .downcast_ref::<StringArray>()
         .unwrap();
 
-    let mut counts = HashMap::<String, usize>::new();
-    for i in 0..out.num_rows() {
-        *counts.entry(names.value(i).to_string()).or_insert(0) += 1;
-    }
-
-    // Intermediate nodes: Bob (1->2->3), Charlie (2->3->4 and 1->3->4), David (3->4->5)
-    assert_eq!(counts.get("Bob"), Some(&1));
-    assert_eq!(counts.get("Charlie"), Some(&2));
-    assert_eq!(counts.get("David"), Some(&1));
+    assert_eq!(ids.value(0), 1);
+    assert_eq!(names.value(0), "Alice");
+    assert_eq!(ages.value(0), 25);
+    assert_eq!(cities.value(0), "New York");
 }
 
 #[tokio::test]
-async fn test_datafusion_two_hop_return_all_three() {
+async fn test_datafusion_relationship_count() {
     let config = create_graph_config();
     let person_batch = create_person_dataset();
     let knows_batch = create_knows_dataset();
 
-    // Query: Return all three nodes in the path
-    let query = CypherQuery::new(
-        "MATCH (a:Person)-[:KNOWS]->(b:Person)-[:KNOWS]->(c:Person) RETURN a.name, b.name, c.name",
-    )
-    .unwrap()
-    .with_config(config);
+    // Count relationships with strict verification
+    let query = CypherQuery::new("MATCH (a:Person)-[:KNOWS]->(b:Person) RETURN a.name")
+        .unwrap()
+        .with_config(config);
 
     let mut datasets = HashMap::new();
     datasets.insert("Person".to_string(), person_batch);
     datasets.insert("KNOWS".to_string(), knows_batch);
 
-    let out = query
+    let result = query
         .execute(datasets, Some(ExecutionStrategy::DataFusion))
         .await
         .unwrap();
-    assert_eq!(out.num_columns(), 3);
-    assert_eq!(out.num_rows(), 4);
 
-    let a_names = out
+    // Should return 5 relationships (as per create_knows_dataset)
+    assert_eq!(result.num_rows(), 5);
+
+    // Verify exact source name counts
+    let names = result
         .column(0)
         .as_any()
         .downcast_ref::<StringArray>()
         .unwrap();
-    let b_names = out
-        .column(1)
-        .as_any()
-        .downcast_ref::<StringArray>()
-        .unwrap();
-    let c_names = out
-        .column(2)
-        .as_any()
-        .downcast_ref::<StringArray>()
-        .unwrap();
+    let mut name_counts = std::collections::HashMap::new();
 
-    // Collect all paths
-    let mut paths = Vec::new();
-    for i in 0..out.num_rows() {
-        paths.push((
-            a_names.value(i).to_string(),
-            b_names.value(i).to_string(),
-            c_names.value(i).to_string(),
-        ));
+    for i in 0..result.num_rows() {
+        let name = names.value(i);
+        *name_counts.entry(name.to_string()).or_insert(0) += 1;
     }
 
-    // Expected paths: Alice->Bob->Charlie, Bob->Charlie->David, Charlie->David->Eve, Alice->Charlie->David
-    assert!(paths.contains(&(
-        "Alice".to_string(),
-        "Bob".to_string(),
-        "Charlie".to_string()
-    )));
-    assert!(paths.contains(&(
-        "Bob".to_string(),
-        "Charlie".to_string(),
-        "David".to_string()
-    )));
-    assert!(paths.contains(&(
-        "Charlie".to_string(),
-        "David".to_string(),
-        "Eve".to_string()
-    )));
-    assert!(paths.contains(&(
-        "Alice".to_string(),
-        "Charlie".to_string(),
-        "David".to_string()
-    )));
+    // Edges: 1->2, 2->3, 3->4, 4->5, 1->3
+    // Source name counts: Alice:2, Bob:1, Charlie:1, David:1
+    assert_eq!(name_counts.get("Alice"), Some(&2));
+    assert_eq!(name_counts.get("Bob"), Some(&1));
+    assert_eq!(name_counts.get("Charlie"), Some(&1));
+    assert_eq!(name_counts.get("David"), Some(&1));
+    assert!(!name_counts.contains_key("Eve"));
+
+    // Verify total
+    let total_relationships: usize = name_counts.values().sum();
+    assert_eq!(total_relationships, 5);
 }
 
 #[tokio::test]
-async fn test_datafusion_two_hop_with_filter() {
+async fn test_datafusion_one_hop_source_names_strict() {
     let config = create_graph_config();
     let person_batch = create_person_dataset();
     let knows_batch = create_knows_dataset();
 
-    // Query: Two-hop with filter on intermediate node
-    let query = CypherQuery::new(
-        "MATCH (a:Person)-[:KNOWS]->(b:Person)-[:KNOWS]->(c:Person) WHERE b.age > 30 RETURN c.name",
-    )
-    .unwrap()
-    .with_config(config);
+    let query = CypherQuery::new("MATCH (a:Person)-[:KNOWS]->(b:Person) RETURN a.name")
+        .unwrap()
+        .with_config(config);
 
     let mut datasets = HashMap::new();
     datasets.insert("Person".to_string(), person_batch);
@@ -1053,39 +1650,37 @@ async fn test_datafusion_two_hop_with_filter() {
         .execute(datasets, Some(ExecutionStrategy::DataFusion))
         .await
         .unwrap();
-
-    // Filter: b.age > 30 means b can be Bob(35), David(40)
-    // Paths with Bob as intermediate: 1->2->3 (Alice->Bob->Charlie)
-    // Paths with David as intermediate: 3->4->5 (Charlie->David->Eve)
-    // No paths with Charlie(30) as intermediate
-    assert_eq!(out.num_rows(), 2);
+    assert_eq!(out.num_columns(), 1);
+    assert_eq!(out.num_rows(), 5);
 
     let names = out
         .column(0)
         .as_any()
         .downcast_ref::<StringArray>()
         .unwrap();
-
-    let result_names: Vec<String> = (0..out.num_rows())
-        .map(|i| names.value(i).to_string())
-        .collect();
-
-    assert!(result_names.contains(&"Charlie".to_string()));
-    assert!(result_names.contains(&"Eve".to_string()));
+    let mut counts = std::collections::HashMap::<String, usize>::new();
+    for i in 0..out.num_rows() {
+        *counts.entry(names.value(i).to_string()).or_insert(0) += 1;
+    }
+    // Edges: 1->2, 2->3, 3->4, 4->5, 1->3
+    // Source name counts: Alice:2, Bob:1, Charlie:1, David:1
+    assert_eq!(counts.get("Alice"), Some(&2));
+    assert_eq!(counts.get("Bob"), Some(&1));
+    assert_eq!(counts.get("Charlie"), Some(&1));
+    assert_eq!(counts.get("David"), Some(&1));
+    assert!(!counts.contains_key("Eve"));
 }
 
 #[tokio::test]
-async fn test_datafusion_two_hop_with_relationship_variable() {
+async fn test_datafusion_one_hop_filtered_source_age_strict() {
     let config = create_graph_config();
     let person_batch = create_person_dataset();
     let knows_batch = create_knows_dataset();
 
-    // Query: Two-hop with relationship variables
-    let query = CypherQuery::new(
-        "MATCH (a:Person)-[r1:KNOWS]->(b:Person)-[r2:KNOWS]->(c:Person) RETURN a.name, c.name",
-    )
-    .unwrap()
-    .with_config(config);
+    let query =
+        CypherQuery::new("MATCH (a:Person)-[:KNOWS]->(b:Person) WHERE a.age > 30 RETURN a.name")
+            .unwrap()
+            .with_config(config);
 
     let mut datasets = HashMap::new();
     datasets.insert("Person".to_string(), person_batch);
@@ -1095,77 +1690,68 @@ async fn test_datafusion_two_hop_with_relationship_variable() {
         .execute(datasets, Some(ExecutionStrategy::DataFusion))
         .await
         .unwrap();
-    assert_eq!(out.num_columns(), 2);
-    assert_eq!(out.num_rows(), 4);
+    assert_eq!(out.num_columns(), 1);
+    // Bob (35): 2->3, David (40): 4->5
+    assert_eq!(out.num_rows(), 2);
 
-    let a_names = out
+    let names = out
         .column(0)
         .as_any()
         .downcast_ref::<StringArray>()
         .unwrap();
-    let c_names = out
-        .column(1)
-        .as_any()
-        .downcast_ref::<StringArray>()
-        .unwrap();
-
-    // Verify we get the correct source->target pairs
-    let mut pairs = Vec::new();
-    for i in 0..out.num_rows() {
-        pairs.push((a_names.value(i).to_string(), c_names.value(i).to_string()));
-    }
-
-    assert!(pairs.contains(&("Alice".to_string(), "Charlie".to_string())));
-    assert!(pairs.contains(&("Bob".to_string(), "David".to_string())));
-    assert!(pairs.contains(&("Charlie".to_string(), "Eve".to_string())));
-    assert!(pairs.contains(&("Alice".to_string(), "David".to_string())));
+    let set: std::collections::HashSet<String> = (0..out.num_rows())
+        .map(|i| names.value(i).to_string())
+        .collect();
+    let expected: std::collections::HashSet<String> = ["Bob", "David"]
+        .into_iter()
+        .map(|s| s.to_string())
+        .collect();
+    assert_eq!(set, expected);
 }
 
 #[tokio::test]
-async fn test_datafusion_two_hop_distinct() {
-    // Query: Get distinct final destinations in two-hop paths
-    let out = execute_test_query(
-        "MATCH (a:Person)-[:KNOWS]->(b:Person)-[:KNOWS]->(c:Person) RETURN DISTINCT c.name",
-    )
-    .await;
+async fn test_datafusion_one_hop_with_city_filter() {
+    let config = create_graph_config();
+    let person_batch = create_person_dataset();
+    let knows_batch = create_knows_dataset();
 
-    assert_eq!(out.num_columns(), 1);
-    // Three distinct targets: Charlie, David, Eve
-    assert_eq!(out.num_rows(), 3);
+    // Query: Filter targets by city using inline property filter
+    // Tests inline property filter instead of WHERE clause
+    let query =
+        CypherQuery::new("MATCH (a:Person)-[:KNOWS]->(b:Person {city: 'Seattle'}) RETURN b.name")
+            .unwrap()
+            .with_config(config);
 
-    let mut names = get_string_column(&out, 0);
-    names.sort();
+    let mut datasets = HashMap::new();
+    datasets.insert("Person".to_string(), person_batch);
+    datasets.insert("KNOWS".to_string(), knows_batch);
 
-    assert_eq!(names, vec!["Charlie", "David", "Eve"]);
-}
+    let out = query
+        .execute(datasets, Some(ExecutionStrategy::DataFusion))
+        .await
+        .unwrap();
 
-#[tokio::test]
-async fn test_datafusion_two_hop_no_results() {
-    // Query: Two-hop starting from Eve (who has no outgoing edges)
-    let out = execute_test_query(
-        "MATCH (a:Person)-[:KNOWS]->(b:Person)-[:KNOWS]->(c:Person) WHERE a.name = 'Eve' RETURN c.name"
-    )
-    .await;
+    // Only Eve has city = 'Seattle' and is reachable (David->Eve)
+    assert_eq!(out.num_rows(), 1);
 
-    // Eve has no outgoing edges, so no two-hop paths
-    assert_eq!(out.num_rows(), 0);
+    let names = out
+        .column(0)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+    assert_eq!(names.value(0), "Eve");
 }
 
-// ============================================================================
-// Complex Query Tests (Advanced Filtering & Multi-Condition)
-// ============================================================================
-
 #[tokio::test]
-async fn test_datafusion_two_hop_with_multiple_filters() {
+async fn test_datafusion_one_hop_multiple_properties() {
     let config = create_graph_config();
     let person_batch = create_person_dataset();
     let knows_batch = create_knows_dataset();
 
-    // Query: Two-hop with filters on source, intermediate, and target
+    // Query: Return multiple properties from both source and target
     let query = CypherQuery::new(
-        "MATCH (a:Person)-[:KNOWS]->(b:Person)-[:KNOWS]->(c:Person) \
-         WHERE a.age < 30 AND b.age >= 30 AND c.age > 25 \
-         RETURN a.name, b.name, c.name",
+        "MATCH (a:Person)-[:KNOWS]->(b:Person) \
+         RETURN a.name, a.age, b.name, b.age",
     )
     .unwrap()
     .with_config(config);
@@ -1179,104 +1765,46 @@ async fn test_datafusion_two_hop_with_multiple_filters() {
         .await
         .unwrap();
 
-    // a.age < 30: Alice(25), Eve(28)
-    // b.age >= 30: Bob(35), Charlie(30), David(40)
-    // c.age > 25: Bob(35), Charlie(30), David(40), Eve(28)
-    // Paths from Alice: Alice->Bob->Charlie, Alice->Charlie->David
-    // Valid: Alice(25)->Bob(35)->Charlie(30), Alice(25)->Charlie(30)->David(40)
-    assert_eq!(out.num_rows(), 2);
+    assert_eq!(out.num_columns(), 4);
+    assert_eq!(out.num_rows(), 5);
 
     let a_names = out
         .column(0)
         .as_any()
         .downcast_ref::<StringArray>()
         .unwrap();
+    let a_ages = out.column(1).as_any().downcast_ref::<Int64Array>().unwrap();
     let b_names = out
-        .column(1)
-        .as_any()
-        .downcast_ref::<StringArray>()
-        .unwrap();
-    let c_names = out
         .column(2)
         .as_any()
         .downcast_ref::<StringArray>()
         .unwrap();
+    let b_ages = out.column(3).as_any().downcast_ref::<Int64Array>().unwrap();
 
-    let mut paths = Vec::new();
+    // Verify at least one row has correct data
+    let mut found_alice_bob = false;
     for i in 0..out.num_rows() {
-        paths.push((
-            a_names.value(i).to_string(),
-            b_names.value(i).to_string(),
-            c_names.value(i).to_string(),
-        ));
+        if a_names.value(i) == "Alice" && b_names.value(i) == "Bob" {
+            assert_eq!(a_ages.value(i), 25);
+            assert_eq!(b_ages.value(i), 35);
+            found_alice_bob = true;
+        }
     }
-
-    assert!(paths.contains(&(
-        "Alice".to_string(),
-        "Bob".to_string(),
-        "Charlie".to_string()
-    )));
-    assert!(paths.contains(&(
-        "Alice".to_string(),
-        "Charlie".to_string(),
-        "David".to_string()
-    )));
-}
-
-#[tokio::test]
-async fn test_datafusion_two_hop_return_relationship_properties() {
-    let config = create_graph_config();
-    let person_batch = create_person_dataset();
-    let knows_batch = create_knows_dataset();
-
-    // Query: Filter two-hop paths by relationship property on first hop
-    // Only paths where first relationship has since_year = 2020
-    // Alice-[2020]->Bob-[2019]->Charlie is the only match
-    let query = CypherQuery::new(
-        "MATCH (a:Person)-[r1:KNOWS {since_year: 2020}]->(b:Person)-[r2:KNOWS]->(c:Person) \
-         RETURN a.name, c.name",
-    )
-    .unwrap()
-    .with_config(config);
-
-    let mut datasets = HashMap::new();
-    datasets.insert("Person".to_string(), person_batch);
-    datasets.insert("KNOWS".to_string(), knows_batch);
-
-    let out = query
-        .execute(datasets, Some(ExecutionStrategy::DataFusion))
-        .await
-        .unwrap();
-    assert_eq!(out.num_columns(), 2);
-    // Only Alice->Bob->Charlie (Alice-[2020]->Bob-[2019]->Charlie)
-    assert_eq!(out.num_rows(), 1);
-
-    let sources = out
-        .column(0)
-        .as_any()
-        .downcast_ref::<StringArray>()
-        .unwrap();
-    let targets = out
-        .column(1)
-        .as_any()
-        .downcast_ref::<StringArray>()
-        .unwrap();
-    assert_eq!(sources.value(0), "Alice");
-    assert_eq!(targets.value(0), "Charlie");
+    assert!(found_alice_bob);
 }
 
 #[tokio::test]
-async fn test_datafusion_two_hop_return_both_relationship_properties() {
+async fn test_datafusion_one_hop_return_relationship_properties() {
     let config = create_graph_config();
     let person_batch = create_person_dataset();
     let knows_batch = create_knows_dataset();
 
-    // Query: Return properties from both relationships in a two-hop path
-    // This validates qualified relationship columns for r1 and r2, and proper aliasing
+    // Query: Return both node and relationship properties in projection
+    // This validates qualified relationship columns and aliasing
     let query = CypherQuery::new(
-        "MATCH (a:Person)-[r1:KNOWS]->(b:Person)-[r2:KNOWS]->(c:Person) \
-         RETURN a.name, r1.since_year, b.name, r2.since_year, c.name \
-         ORDER BY a.name, c.name",
+        "MATCH (a:Person)-[r:KNOWS]->(b:Person) \
+         RETURN a.name, r.since_year, b.name \
+         ORDER BY a.name, b.name",
     )
     .unwrap()
     .with_config(config);
@@ -1290,68 +1818,91 @@ async fn test_datafusion_two_hop_return_both_relationship_properties() {
         .await
         .unwrap();
 
-    // Should return 5 columns: a.name, r1.since_year, b.name, r2.since_year, c.name
-    assert_eq!(out.num_columns(), 5);
-    // Should return 4 two-hop paths
-    assert_eq!(out.num_rows(), 4);
+    // Should return 3 columns: a.name, r.since_year, b.name
+    assert_eq!(out.num_columns(), 3);
+    // Should return 5 edges
+    assert_eq!(out.num_rows(), 5);
 
     let a_names = out
         .column(0)
         .as_any()
         .downcast_ref::<StringArray>()
         .unwrap();
-    let r1_years = out.column(1).as_any().downcast_ref::<Int64Array>().unwrap();
+    let since_years = out.column(1).as_any().downcast_ref::<Int64Array>().unwrap();
     let b_names = out
         .column(2)
         .as_any()
         .downcast_ref::<StringArray>()
         .unwrap();
-    let r2_years = out.column(3).as_any().downcast_ref::<Int64Array>().unwrap();
-    let c_names = out
-        .column(4)
-        .as_any()
-        .downcast_ref::<StringArray>()
-        .unwrap();
 
-    // Verify first path: Alice -[2020]-> Bob -[2019]-> Charlie
+    // Verify first row: Alice -> Bob (2020)
     assert_eq!(a_names.value(0), "Alice");
-    assert_eq!(r1_years.value(0), 2020);
+    assert_eq!(since_years.value(0), 2020);
     assert_eq!(b_names.value(0), "Bob");
-    assert_eq!(r2_years.value(0), 2019);
-    assert_eq!(c_names.value(0), "Charlie");
 
-    // Verify second path: Alice -[2018]-> Charlie -[2021]-> David
+    // Verify second row: Alice -> Charlie (2018)
     assert_eq!(a_names.value(1), "Alice");
-    assert_eq!(r1_years.value(1), 2018);
+    assert_eq!(since_years.value(1), 2018);
     assert_eq!(b_names.value(1), "Charlie");
-    assert_eq!(r2_years.value(1), 2021);
-    assert_eq!(c_names.value(1), "David");
 
-    // Verify third path: Bob -[2019]-> Charlie -[2021]-> David
+    // Verify third row: Bob -> Charlie (2019)
     assert_eq!(a_names.value(2), "Bob");
-    assert_eq!(r1_years.value(2), 2019);
+    assert_eq!(since_years.value(2), 2019);
     assert_eq!(b_names.value(2), "Charlie");
-    assert_eq!(r2_years.value(2), 2021);
-    assert_eq!(c_names.value(2), "David");
 
-    // Verify fourth path: Charlie -[2021]-> David -[NULL]-> Eve
+    // Verify fourth row: Charlie -> David (2021)
     assert_eq!(a_names.value(3), "Charlie");
-    assert_eq!(r1_years.value(3), 2021);
+    assert_eq!(since_years.value(3), 2021);
     assert_eq!(b_names.value(3), "David");
-    assert!(r2_years.is_null(3)); // NULL value for David -> Eve
-    assert_eq!(c_names.value(3), "Eve");
+
+    // Verify fifth row: David -> Eve (NULL since_year)
+    assert_eq!(a_names.value(4), "David");
+    assert!(since_years.is_null(4)); // NULL value
+    assert_eq!(b_names.value(4), "Eve");
 }
 
+// ============================================================================
+// Two-Hop Path Query Tests
+// ============================================================================
+
 #[tokio::test]
-async fn test_datafusion_two_hop_with_limit() {
+async fn test_datafusion_two_hop_basic() {
+    // Query: Find friends of friends
+    // Edges: 1->2, 2->3, 3->4, 4->5, 1->3
+    // Two-hop paths: 1->2->3, 2->3->4, 3->4->5, 1->3->4
+    let out = execute_test_query(
+        "MATCH (a:Person)-[:KNOWS]->(b:Person)-[:KNOWS]->(c:Person) RETURN c.name",
+    )
+    .await;
+
+    // Should return: Charlie (from 1->2->3), David (from 2->3->4 and 1->3->4), Eve (from 3->4->5)
+    assert_eq!(out.num_columns(), 1);
+    assert_eq!(out.num_rows(), 4); // 4 two-hop paths
+
+    let names = get_string_column(&out, 0);
+
+    let mut counts = HashMap::<String, usize>::new();
+    for name in names {
+        *counts.entry(name).or_insert(0) += 1;
+    }
+
+    // Verify counts: Charlie:1, David:2, Eve:1
+    assert_eq!(counts.get("Charlie"), Some(&1));
+    assert_eq!(counts.get("David"), Some(&2));
+    assert_eq!(counts.get("Eve"), Some(&1));
+    assert!(!counts.contains_key("Alice"));
+    assert!(!counts.contains_key("Bob"));
+}
+
+#[tokio::test]
+async fn test_datafusion_two_hop_return_intermediate() {
     let config = create_graph_config();
     let person_batch = create_person_dataset();
     let knows_batch = create_knows_dataset();
 
-    // Query: Two-hop with LIMIT
+    // Query: Return the intermediate node in two-hop paths
     let query = CypherQuery::new(
-        "MATCH (a:Person)-[:KNOWS]->(b:Person)-[:KNOWS]->(c:Person) \
-         RETURN c.name LIMIT 2",
+        "MATCH (a:Person)-[:KNOWS]->(b:Person)-[:KNOWS]->(c:Person) RETURN b.name",
     )
     .unwrap()
     .with_config(config);
@@ -1364,22 +1915,35 @@ async fn test_datafusion_two_hop_with_limit() {
         .execute(datasets, Some(ExecutionStrategy::DataFusion))
         .await
         .unwrap();
+    assert_eq!(out.num_columns(), 1);
+    assert_eq!(out.num_rows(), 4);
+
+    let names = out
+        .column(0)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
 
-    // Should return only 2 rows (limited from 4 total paths)
-    assert_eq!(out.num_rows(), 2);
+    let mut counts = HashMap::<String, usize>::new();
+    for i in 0..out.num_rows() {
+        *counts.entry(names.value(i).to_string()).or_insert(0) += 1;
+    }
+
+    // Intermediate nodes: Bob (1->2->3), Charlie (2->3->4 and 1->3->4), David (3->4->5)
+    assert_eq!(counts.get("Bob"), Some(&1));
+    assert_eq!(counts.get("Charlie"), Some(&2));
+    assert_eq!(counts.get("David"), Some(&1));
 }
 
 #[tokio::test]
-async fn test_datafusion_complex_boolean_expression() {
+async fn test_datafusion_two_hop_return_all_three() {
     let config = create_graph_config();
     let person_batch = create_person_dataset();
     let knows_batch = create_knows_dataset();
 
-    // Query: Complex boolean expression with AND/OR
+    // Query: Return all three nodes in the path
     let query = CypherQuery::new(
-        "MATCH (a:Person)-[:KNOWS]->(b:Person) \
-         WHERE (a.age > 30 AND b.age < 35) OR (a.name = 'Alice' AND b.name = 'Bob') \
-         RETURN a.name, b.name",
+        "MATCH (a:Person)-[:KNOWS]->(b:Person)-[:KNOWS]->(c:Person) RETURN a.name, b.name, c.name",
     )
     .unwrap()
     .with_config(config);
@@ -1392,12 +1956,8 @@ async fn test_datafusion_complex_boolean_expression() {
         .execute(datasets, Some(ExecutionStrategy::DataFusion))
         .await
         .unwrap();
-
-    // Matches:
-    // - Bob(35)->Charlie(30): age > 30 AND age < 35
-    // - David(40)->Eve(28): age > 30 AND age < 35
-    // - Alice(25)->Bob(35): name = 'Alice' AND name = 'Bob'
-    assert_eq!(out.num_rows(), 3);
+    assert_eq!(out.num_columns(), 3);
+    assert_eq!(out.num_rows(), 4);
 
     let a_names = out
         .column(0)
@@ -1409,28 +1969,54 @@ async fn test_datafusion_complex_boolean_expression() {
         .as_any()
         .downcast_ref::<StringArray>()
         .unwrap();
+    let c_names = out
+        .column(2)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
 
-    let mut pairs = Vec::new();
+    // Collect all paths
+    let mut paths = Vec::new();
     for i in 0..out.num_rows() {
-        pairs.push((a_names.value(i).to_string(), b_names.value(i).to_string()));
+        paths.push((
+            a_names.value(i).to_string(),
+            b_names.value(i).to_string(),
+            c_names.value(i).to_string(),
+        ));
     }
 
-    assert!(pairs.contains(&("Alice".to_string(), "Bob".to_string())));
-    assert!(pairs.contains(&("Bob".to_string(), "Charlie".to_string())));
-    assert!(pairs.contains(&("David".to_string(), "Eve".to_string())));
+    // Expected paths: Alice->Bob->Charlie, Bob->Charlie->David, Charlie->David->Eve, Alice->Charlie->David
+    assert!(paths.contains(&(
+        "Alice".to_string(),
+        "Bob".to_string(),
+        "Charlie".to_string()
+    )));
+    assert!(paths.contains(&(
+        "Bob".to_string(),
+        "Charlie".to_string(),
+        "David".to_string()
+    )));
+    assert!(paths.contains(&(
+        "Charlie".to_string(),
+        "David".to_string(),
+        "Eve".to_string()
+    )));
+    assert!(paths.contains(&(
+        "Alice".to_string(),
+        "Charlie".to_string(),
+        "David".to_string()
+    )));
 }
 
 #[tokio::test]
-async fn test_datafusion_two_hop_same_intermediate_node() {
+async fn test_datafusion_two_hop_with_filter() {
     let config = create_graph_config();
     let person_batch = create_person_dataset();
     let knows_batch = create_knows_dataset();
 
-    // Query: Find paths through Charlie specifically
+    // Query: Two-hop with filter on intermediate node
     let query = CypherQuery::new(
-        "MATCH (a:Person)-[:KNOWS]->(b:Person)-[:KNOWS]->(c:Person) \
-         WHERE b.name = 'Charlie' \
-         RETURN a.name, c.name",
+        "MATCH (a:Person)-[:KNOWS]->(b:Person)-[:KNOWS]->(c:Person) WHERE b.age > 30 RETURN c.name",
     )
     .unwrap()
     .with_config(config);
@@ -1444,9 +2030,50 @@ async fn test_datafusion_two_hop_same_intermediate_node() {
         .await
         .unwrap();
 
-    // Paths through Charlie: Bob->Charlie->David, Alice->Charlie->David
+    // Filter: b.age > 30 means b can be Bob(35), David(40)
+    // Paths with Bob as intermediate: 1->2->3 (Alice->Bob->Charlie)
+    // Paths with David as intermediate: 3->4->5 (Charlie->David->Eve)
+    // No paths with Charlie(30) as intermediate
     assert_eq!(out.num_rows(), 2);
 
+    let names = out
+        .column(0)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+
+    let result_names: Vec<String> = (0..out.num_rows())
+        .map(|i| names.value(i).to_string())
+        .collect();
+
+    assert!(result_names.contains(&"Charlie".to_string()));
+    assert!(result_names.contains(&"Eve".to_string()));
+}
+
+#[tokio::test]
+async fn test_datafusion_two_hop_with_relationship_variable() {
+    let config = create_graph_config();
+    let person_batch = create_person_dataset();
+    let knows_batch = create_knows_dataset();
+
+    // Query: Two-hop with relationship variables
+    let query = CypherQuery::new(
+        "MATCH (a:Person)-[r1:KNOWS]->(b:Person)-[r2:KNOWS]->(c:Person) RETURN a.name, c.name",
+    )
+    .unwrap()
+    .with_config(config);
+
+    let mut datasets = HashMap::new();
+    datasets.insert("Person".to_string(), person_batch);
+    datasets.insert("KNOWS".to_string(), knows_batch);
+
+    let out = query
+        .execute(datasets, Some(ExecutionStrategy::DataFusion))
+        .await
+        .unwrap();
+    assert_eq!(out.num_columns(), 2);
+    assert_eq!(out.num_rows(), 4);
+
     let a_names = out
         .column(0)
         .as_any()
@@ -1458,60 +2085,63 @@ async fn test_datafusion_two_hop_same_intermediate_node() {
         .downcast_ref::<StringArray>()
         .unwrap();
 
+    // Verify we get the correct source->target pairs
     let mut pairs = Vec::new();
     for i in 0..out.num_rows() {
         pairs.push((a_names.value(i).to_string(), c_names.value(i).to_string()));
     }
 
+    assert!(pairs.contains(&("Alice".to_string(), "Charlie".to_string())));
     assert!(pairs.contains(&("Bob".to_string(), "David".to_string())));
+    assert!(pairs.contains(&("Charlie".to_string(), "Eve".to_string())));
     assert!(pairs.contains(&("Alice".to_string(), "David".to_string())));
 }
 
 #[tokio::test]
-async fn test_datafusion_varlength_projection_correctness() {
-    // Test that variable-length path projection correctly handles qualified column names
-    // and doesn't accidentally include intermediate node columns
+async fn test_datafusion_two_hop_distinct() {
+    // Query: Get distinct final destinations in two-hop paths
     let out = execute_test_query(
-        "MATCH (a:Person {name: 'Alice'})-[:KNOWS*1..2]->(b:Person) RETURN b.name",
+        "MATCH (a:Person)-[:KNOWS]->(b:Person)-[:KNOWS]->(c:Person) RETURN DISTINCT c.name",
     )
     .await;
 
-    // Alice can reach: Bob (1-hop), Charlie (1-hop and 2-hop via Bob), David (2-hop via Charlie)
-    // Total: 4 results (Bob, Charlie, Charlie, David)
-    assert_eq!(out.num_rows(), 4);
+    assert_eq!(out.num_columns(), 1);
+    // Three distinct targets: Charlie, David, Eve
+    assert_eq!(out.num_rows(), 3);
 
-    // Verify schema only contains the requested column (now in Cypher dot notation)
-    let schema = out.schema();
-    let column_names: Vec<&str> = schema.fields().iter().map(|f| f.name().as_str()).collect();
+    let mut names = get_string_column(&out, 0);
+    names.sort();
 
-    // Should only have the 'b.name' column (Cypher dot notation)
-    assert_eq!(column_names.len(), 1);
-    assert_eq!(
-        column_names[0], "b.name",
-        "Expected Cypher dot notation 'b.name' column"
-    );
+    assert_eq!(names, vec!["Charlie", "David", "Eve"]);
+}
 
-    // Verify no DataFusion qualified names remain (no __)
-    for name in &column_names {
-        assert!(
-            !name.contains("__"),
-            "Column name should not contain DataFusion qualifiers: {}",
-            name
-        );
-    }
+#[tokio::test]
+async fn test_datafusion_two_hop_no_results() {
+    // Query: Two-hop starting from Eve (who has no outgoing edges)
+    let out = execute_test_query(
+        "MATCH (a:Person)-[:KNOWS]->(b:Person)-[:KNOWS]->(c:Person) WHERE a.name = 'Eve' RETURN c.name"
+    )
+    .await;
+
+    // Eve has no outgoing edges, so no two-hop paths
+    assert_eq!(out.num_rows(), 0);
 }
 
+// ============================================================================
+// Complex Query Tests (Advanced Filtering & Multi-Condition)
+// ============================================================================
+
 #[tokio::test]
-async fn test_datafusion_two_hop_count_paths_per_source() {
+async fn test_datafusion_two_hop_with_multiple_filters() {
     let config = create_graph_config();
     let person_batch = create_person_dataset();
     let knows_batch = create_knows_dataset();
 
-    // Query: Count two-hop paths from Alice
+    // Query: Two-hop with filters on source, intermediate, and target
     let query = CypherQuery::new(
         "MATCH (a:Person)-[:KNOWS]->(b:Person)-[:KNOWS]->(c:Person) \
-         WHERE a.name = 'Alice' \
-         RETURN c.name",
+         WHERE a.age < 30 AND b.age >= 30 AND c.age > 25 \
+         RETURN a.name, b.name, c.name",
     )
     .unwrap()
     .with_config(config);
@@ -1525,34 +2155,62 @@ async fn test_datafusion_two_hop_count_paths_per_source() {
         .await
         .unwrap();
 
-    // Alice's two-hop paths: Alice->Bob->Charlie, Alice->Charlie->David
+    // a.age < 30: Alice(25), Eve(28)
+    // b.age >= 30: Bob(35), Charlie(30), David(40)
+    // c.age > 25: Bob(35), Charlie(30), David(40), Eve(28)
+    // Paths from Alice: Alice->Bob->Charlie, Alice->Charlie->David
+    // Valid: Alice(25)->Bob(35)->Charlie(30), Alice(25)->Charlie(30)->David(40)
     assert_eq!(out.num_rows(), 2);
 
-    let names = out
+    let a_names = out
         .column(0)
         .as_any()
         .downcast_ref::<StringArray>()
         .unwrap();
-    let mut counts = HashMap::<String, usize>::new();
+    let b_names = out
+        .column(1)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+    let c_names = out
+        .column(2)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+
+    let mut paths = Vec::new();
     for i in 0..out.num_rows() {
-        *counts.entry(names.value(i).to_string()).or_insert(0) += 1;
+        paths.push((
+            a_names.value(i).to_string(),
+            b_names.value(i).to_string(),
+            c_names.value(i).to_string(),
+        ));
     }
 
-    assert_eq!(counts.get("Charlie"), Some(&1));
-    assert_eq!(counts.get("David"), Some(&1));
+    assert!(paths.contains(&(
+        "Alice".to_string(),
+        "Bob".to_string(),
+        "Charlie".to_string()
+    )));
+    assert!(paths.contains(&(
+        "Alice".to_string(),
+        "Charlie".to_string(),
+        "David".to_string()
+    )));
 }
 
 #[tokio::test]
-async fn test_datafusion_filter_on_both_nodes_and_edges() {
+async fn test_datafusion_two_hop_return_relationship_properties() {
     let config = create_graph_config();
     let person_batch = create_person_dataset();
     let knows_batch = create_knows_dataset();
 
-    // Query: Filter on both node properties and relationship existence
+    // Query: Filter two-hop paths by relationship property on first hop
+    // Only paths where first relationship has since_year = 2020
+    // Alice-[2020]->Bob-[2019]->Charlie is the only match
     let query = CypherQuery::new(
-        "MATCH (a:Person)-[:KNOWS]->(b:Person) \
-         WHERE a.age >= 25 AND a.age <= 30 AND b.age > 30 \
-         RETURN a.name, b.name",
+        "MATCH (a:Person)-[r1:KNOWS {since_year: 2020}]->(b:Person)-[r2:KNOWS]->(c:Person) \
+         RETURN a.name, c.name",
     )
     .unwrap()
     .with_config(config);
@@ -1565,42 +2223,36 @@ async fn test_datafusion_filter_on_both_nodes_and_edges() {
         .execute(datasets, Some(ExecutionStrategy::DataFusion))
         .await
         .unwrap();
+    assert_eq!(out.num_columns(), 2);
+    // Only Alice->Bob->Charlie (Alice-[2020]->Bob-[2019]->Charlie)
+    assert_eq!(out.num_rows(), 1);
 
-    // a: age 25-30 = Alice(25), Charlie(30), Eve(28)
-    // b: age > 30 = Bob(35), David(40)
-    // Edges: Alice->Bob, Charlie->David
-    assert_eq!(out.num_rows(), 2);
-
-    let a_names = out
+    let sources = out
         .column(0)
         .as_any()
         .downcast_ref::<StringArray>()
         .unwrap();
-    let b_names = out
+    let targets = out
         .column(1)
         .as_any()
         .downcast_ref::<StringArray>()
         .unwrap();
-
-    let mut pairs = Vec::new();
-    for i in 0..out.num_rows() {
-        pairs.push((a_names.value(i).to_string(), b_names.value(i).to_string()));
-    }
-
-    assert!(pairs.contains(&("Alice".to_string(), "Bob".to_string())));
-    assert!(pairs.contains(&("Charlie".to_string(), "David".to_string())));
+    assert_eq!(sources.value(0), "Alice");
+    assert_eq!(targets.value(0), "Charlie");
 }
 
 #[tokio::test]
-async fn test_datafusion_distinct_with_two_hop() {
+async fn test_datafusion_two_hop_return_both_relationship_properties() {
     let config = create_graph_config();
     let person_batch = create_person_dataset();
     let knows_batch = create_knows_dataset();
 
-    // Query: Get distinct source nodes that have two-hop paths
+    // Query: Return properties from both relationships in a two-hop path
+    // This validates qualified relationship columns for r1 and r2, and proper aliasing
     let query = CypherQuery::new(
-        "MATCH (a:Person)-[:KNOWS]->(b:Person)-[:KNOWS]->(c:Person) \
-         RETURN DISTINCT a.name",
+        "MATCH (a:Person)-[r1:KNOWS]->(b:Person)-[r2:KNOWS]->(c:Person) \
+         RETURN a.name, r1.since_year, b.name, r2.since_year, c.name \
+         ORDER BY a.name, c.name",
     )
     .unwrap()
     .with_config(config);
@@ -1614,237 +2266,269 @@ async fn test_datafusion_distinct_with_two_hop() {
         .await
         .unwrap();
 
-    // Sources with two-hop paths: Alice, Bob, Charlie
-    assert_eq!(out.num_rows(), 3);
+    // Should return 5 columns: a.name, r1.since_year, b.name, r2.since_year, c.name
+    assert_eq!(out.num_columns(), 5);
+    // Should return 4 two-hop paths
+    assert_eq!(out.num_rows(), 4);
 
-    let names = out
+    let a_names = out
         .column(0)
         .as_any()
         .downcast_ref::<StringArray>()
         .unwrap();
-    let result_set: std::collections::HashSet<String> = (0..out.num_rows())
-        .map(|i| names.value(i).to_string())
-        .collect();
+    let r1_years = out.column(1).as_any().downcast_ref::<Int64Array>().unwrap();
+    let b_names = out
+        .column(2)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+    let r2_years = out.column(3).as_any().downcast_ref::<Int64Array>().unwrap();
+    let c_names = out
+        .column(4)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
 
-    let expected: std::collections::HashSet<String> = ["Alice", "Bob", "Charlie"]
-        .into_iter()
-        .map(|s| s.to_string())
-        .collect();
+    // Verify first path: Alice -[2020]-> Bob -[2019]-> Charlie
+    assert_eq!(a_names.value(0), "Alice");
+    assert_eq!(r1_years.value(0), 2020);
+    assert_eq!(b_names.value(0), "Bob");
+    assert_eq!(r2_years.value(0), 2019);
+    assert_eq!(c_names.value(0), "Charlie");
 
-    assert_eq!(result_set, expected);
-}
+    // Verify second path: Alice -[2018]-> Charlie -[2021]-> David
+    assert_eq!(a_names.value(1), "Alice");
+    assert_eq!(r1_years.value(1), 2018);
+    assert_eq!(b_names.value(1), "Charlie");
+    assert_eq!(r2_years.value(1), 2021);
+    assert_eq!(c_names.value(1), "David");
 
-#[tokio::test]
-async fn test_datafusion_expand_with_both_relationship_and_target_filters() {
-    // Query: Find people Alice knows since 2018 who are age 30
-    // Alice-[2020]->Bob(35), Alice-[2018]->Charlie(30)
-    // Only Charlie matches both filters
-    let out = execute_test_query(
-        "MATCH (a:Person {name: 'Alice'})-[:KNOWS {since_year: 2018}]->(b:Person {age: 30}) \
-         RETURN b.name",
-    )
-    .await;
+    // Verify third path: Bob -[2019]-> Charlie -[2021]-> David
+    assert_eq!(a_names.value(2), "Bob");
+    assert_eq!(r1_years.value(2), 2019);
+    assert_eq!(b_names.value(2), "Charlie");
+    assert_eq!(r2_years.value(2), 2021);
+    assert_eq!(c_names.value(2), "David");
 
-    assert_eq!(out.num_rows(), 1);
-    let names = get_string_column(&out, 0);
-    assert_eq!(names[0], "Charlie");
+    // Verify fourth path: Charlie -[2021]-> David -[NULL]-> Eve
+    assert_eq!(a_names.value(3), "Charlie");
+    assert_eq!(r1_years.value(3), 2021);
+    assert_eq!(b_names.value(3), "David");
+    assert!(r2_years.is_null(3)); // NULL value for David -> Eve
+    assert_eq!(c_names.value(3), "Eve");
 }
 
-// ============================================================================
-// ORDER BY Tests
-// ============================================================================
-
 #[tokio::test]
-async fn test_datafusion_order_by_single_column_asc() {
+async fn test_datafusion_two_hop_with_limit() {
     let config = create_graph_config();
     let person_batch = create_person_dataset();
+    let knows_batch = create_knows_dataset();
 
-    // Query: ORDER BY name ascending
-    let query = CypherQuery::new("MATCH (p:Person) RETURN p.name ORDER BY p.name")
-        .unwrap()
-        .with_config(config);
+    // Query: Two-hop with LIMIT
+    let query = CypherQuery::new(
+        "MATCH (a:Person)-[:KNOWS]->(b:Person)-[:KNOWS]->(c:Person) \
+         RETURN c.name LIMIT 2",
+    )
+    .unwrap()
+    .with_config(config);
 
     let mut datasets = HashMap::new();
     datasets.insert("Person".to_string(), person_batch);
+    datasets.insert("KNOWS".to_string(), knows_batch);
 
     let out = query
         .execute(datasets, Some(ExecutionStrategy::DataFusion))
         .await
         .unwrap();
 
-    assert_eq!(out.num_rows(), 5);
-
-    let names = out
-        .column(0)
-        .as_any()
-        .downcast_ref::<StringArray>()
-        .unwrap();
-
-    // Verify alphabetical order: Alice, Bob, Charlie, David, Eve
-    assert_eq!(names.value(0), "Alice");
-    assert_eq!(names.value(1), "Bob");
-    assert_eq!(names.value(2), "Charlie");
-    assert_eq!(names.value(3), "David");
-    assert_eq!(names.value(4), "Eve");
+    // Should return only 2 rows (limited from 4 total paths)
+    assert_eq!(out.num_rows(), 2);
 }
 
 #[tokio::test]
-async fn test_datafusion_order_by_single_column_desc() {
+async fn test_datafusion_complex_boolean_expression() {
     let config = create_graph_config();
     let person_batch = create_person_dataset();
+    let knows_batch = create_knows_dataset();
 
-    // Query: ORDER BY age descending
-    let query = CypherQuery::new("MATCH (p:Person) RETURN p.name, p.age ORDER BY p.age DESC")
-        .unwrap()
-        .with_config(config);
+    // Query: Complex boolean expression with AND/OR
+    let query = CypherQuery::new(
+        "MATCH (a:Person)-[:KNOWS]->(b:Person) \
+         WHERE (a.age > 30 AND b.age < 35) OR (a.name = 'Alice' AND b.name = 'Bob') \
+         RETURN a.name, b.name",
+    )
+    .unwrap()
+    .with_config(config);
 
     let mut datasets = HashMap::new();
     datasets.insert("Person".to_string(), person_batch);
+    datasets.insert("KNOWS".to_string(), knows_batch);
 
     let out = query
         .execute(datasets, Some(ExecutionStrategy::DataFusion))
         .await
         .unwrap();
 
-    assert_eq!(out.num_rows(), 5);
+    // Matches:
+    // - Bob(35)->Charlie(30): age > 30 AND age < 35
+    // - David(40)->Eve(28): age > 30 AND age < 35
+    // - Alice(25)->Bob(35): name = 'Alice' AND name = 'Bob'
+    assert_eq!(out.num_rows(), 3);
 
-    let names = out
+    let a_names = out
         .column(0)
         .as_any()
         .downcast_ref::<StringArray>()
         .unwrap();
-    let ages = out.column(1).as_any().downcast_ref::<Int64Array>().unwrap();
+    let b_names = out
+        .column(1)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
 
-    // Verify descending age order: David(40), Bob(35), Charlie(30), Eve(28), Alice(25)
-    assert_eq!(names.value(0), "David");
-    assert_eq!(ages.value(0), 40);
-    assert_eq!(names.value(1), "Bob");
-    assert_eq!(ages.value(1), 35);
-    assert_eq!(names.value(2), "Charlie");
-    assert_eq!(ages.value(2), 30);
-    assert_eq!(names.value(3), "Eve");
-    assert_eq!(ages.value(3), 28);
-    assert_eq!(names.value(4), "Alice");
-    assert_eq!(ages.value(4), 25);
+    let mut pairs = Vec::new();
+    for i in 0..out.num_rows() {
+        pairs.push((a_names.value(i).to_string(), b_names.value(i).to_string()));
+    }
+
+    assert!(pairs.contains(&("Alice".to_string(), "Bob".to_string())));
+    assert!(pairs.contains(&("Bob".to_string(), "Charlie".to_string())));
+    assert!(pairs.contains(&("David".to_string(), "Eve".to_string())));
 }
 
 #[tokio::test]
-async fn test_datafusion_order_by_multiple_columns() {
+async fn test_datafusion_two_hop_same_intermediate_node() {
     let config = create_graph_config();
     let person_batch = create_person_dataset();
+    let knows_batch = create_knows_dataset();
 
-    // Query: ORDER BY age DESC, name ASC (secondary sort by name)
-    let query =
-        CypherQuery::new("MATCH (p:Person) RETURN p.name, p.age ORDER BY p.age DESC, p.name ASC")
-            .unwrap()
-            .with_config(config);
+    // Query: Find paths through Charlie specifically
+    let query = CypherQuery::new(
+        "MATCH (a:Person)-[:KNOWS]->(b:Person)-[:KNOWS]->(c:Person) \
+         WHERE b.name = 'Charlie' \
+         RETURN a.name, c.name",
+    )
+    .unwrap()
+    .with_config(config);
 
     let mut datasets = HashMap::new();
     datasets.insert("Person".to_string(), person_batch);
+    datasets.insert("KNOWS".to_string(), knows_batch);
 
     let out = query
         .execute(datasets, Some(ExecutionStrategy::DataFusion))
         .await
         .unwrap();
 
-    assert_eq!(out.num_rows(), 5);
+    // Paths through Charlie: Bob->Charlie->David, Alice->Charlie->David
+    assert_eq!(out.num_rows(), 2);
 
-    let _names = out
+    let a_names = out
         .column(0)
         .as_any()
         .downcast_ref::<StringArray>()
         .unwrap();
-    let ages = out.column(1).as_any().downcast_ref::<Int64Array>().unwrap();
+    let c_names = out
+        .column(1)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
 
-    // First by age DESC, then by name ASC
-    assert_eq!(ages.value(0), 40); // David
-    assert_eq!(ages.value(1), 35); // Bob
-    assert_eq!(ages.value(2), 30); // Charlie
-    assert_eq!(ages.value(3), 28); // Eve
-    assert_eq!(ages.value(4), 25); // Alice
+    let mut pairs = Vec::new();
+    for i in 0..out.num_rows() {
+        pairs.push((a_names.value(i).to_string(), c_names.value(i).to_string()));
+    }
+
+    assert!(pairs.contains(&("Bob".to_string(), "David".to_string())));
+    assert!(pairs.contains(&("Alice".to_string(), "David".to_string())));
 }
 
 #[tokio::test]
-async fn test_datafusion_order_by_with_limit() {
-    let config = create_graph_config();
-    let person_batch = create_person_dataset();
-
-    // Query: ORDER BY age DESC LIMIT 3 (top 3 oldest)
-    let query =
-        CypherQuery::new("MATCH (p:Person) RETURN p.name, p.age ORDER BY p.age DESC LIMIT 3")
-            .unwrap()
-            .with_config(config);
-
-    let mut datasets = HashMap::new();
-    datasets.insert("Person".to_string(), person_batch);
+async fn test_datafusion_varlength_projection_correctness() {
+    // Test that variable-length path projection correctly handles qualified column names
+    // and doesn't accidentally include intermediate node columns
+    let out = execute_test_query(
+        "MATCH (a:Person {name: 'Alice'})-[:KNOWS*1..2]->(b:Person) RETURN b.name",
+    )
+    .await;
 
-    let out = query
-        .execute(datasets, Some(ExecutionStrategy::DataFusion))
-        .await
-        .unwrap();
+    // Alice can reach: Bob (1-hop), Charlie (1-hop and 2-hop via Bob), David (2-hop via Charlie)
+    // Total: 4 results (Bob, Charlie, Charlie, David)
+    assert_eq!(out.num_rows(), 4);
 
-    // Should only return 3 rows
-    assert_eq!(out.num_rows(), 3);
+    // Verify schema only contains the requested column (now in Cypher dot notation)
+    let schema = out.schema();
+    let column_names: Vec<&str> = schema.fields().iter().map(|f| f.name().as_str()).collect();
 
-    let names = out
-        .column(0)
-        .as_any()
-        .downcast_ref::<StringArray>()
-        .unwrap();
-    let ages = out.column(1).as_any().downcast_ref::<Int64Array>().unwrap();
+    // Should only have the 'b.name' column (Cypher dot notation)
+    assert_eq!(column_names.len(), 1);
+    assert_eq!(
+        column_names[0], "b.name",
+        "Expected Cypher dot notation 'b.name' column"
+    );
 
-    // Top 3 oldest: David(40), Bob(35), Charlie(30)
-    assert_eq!(names.value(0), "David");
-    assert_eq!(ages.value(0), 40);
-    assert_eq!(names.value(1), "Bob");
-    assert_eq!(ages.value(1), 35);
-    assert_eq!(names.value(2), "Charlie");
-    assert_eq!(ages.value(2), 30);
+    // Verify no DataFusion qualified names remain (no __)
+    for name in &column_names {
+        assert!(
+            !name.contains("__"),
+            "Column name should not contain DataFusion qualifiers: {}",
+            name
+        );
+    }
 }
 
 #[tokio::test]
-async fn test_datafusion_order_by_with_filter() {
+async fn test_datafusion_two_hop_count_paths_per_source() {
     let config = create_graph_config();
     let person_batch = create_person_dataset();
+    let knows_batch = create_knows_dataset();
 
-    // Query: Filter then order
-    let query =
-        CypherQuery::new("MATCH (p:Person) WHERE p.age >= 30 RETURN p.name ORDER BY p.name")
-            .unwrap()
-            .with_config(config);
+    // Query: Count two-hop paths from Alice
+    let query = CypherQuery::new(
+        "MATCH (a:Person)-[:KNOWS]->(b:Person)-[:KNOWS]->(c:Person) \
+         WHERE a.name = 'Alice' \
+         RETURN c.name",
+    )
+    .unwrap()
+    .with_config(config);
 
     let mut datasets = HashMap::new();
     datasets.insert("Person".to_string(), person_batch);
+    datasets.insert("KNOWS".to_string(), knows_batch);
 
     let out = query
         .execute(datasets, Some(ExecutionStrategy::DataFusion))
         .await
         .unwrap();
 
-    // Age >= 30: Bob(35), Charlie(30), David(40)
-    assert_eq!(out.num_rows(), 3);
+    // Alice's two-hop paths: Alice->Bob->Charlie, Alice->Charlie->David
+    assert_eq!(out.num_rows(), 2);
 
     let names = out
         .column(0)
         .as_any()
         .downcast_ref::<StringArray>()
         .unwrap();
+    let mut counts = HashMap::<String, usize>::new();
+    for i in 0..out.num_rows() {
+        *counts.entry(names.value(i).to_string()).or_insert(0) += 1;
+    }
 
-    // Alphabetical: Bob, Charlie, David
-    assert_eq!(names.value(0), "Bob");
-    assert_eq!(names.value(1), "Charlie");
-    assert_eq!(names.value(2), "David");
+    assert_eq!(counts.get("Charlie"), Some(&1));
+    assert_eq!(counts.get("David"), Some(&1));
 }
 
 #[tokio::test]
-async fn test_datafusion_order_by_relationship_query() {
+async fn test_datafusion_filter_on_both_nodes_and_edges() {
     let config = create_graph_config();
     let person_batch = create_person_dataset();
     let knows_batch = create_knows_dataset();
 
-    // Query: Order relationship results by target name
+    // Query: Filter on both node properties and relationship existence
     let query = CypherQuery::new(
-        "MATCH (a:Person)-[:KNOWS]->(b:Person) RETURN a.name, b.name ORDER BY b.name",
+        "MATCH (a:Person)-[:KNOWS]->(b:Person) \
+         WHERE a.age >= 25 AND a.age <= 30 AND b.age > 30 \
+         RETURN a.name, b.name",
     )
     .unwrap()
     .with_config(config);
@@ -1858,32 +2542,41 @@ async fn test_datafusion_order_by_relationship_query() {
         .await
         .unwrap();
 
-    assert_eq!(out.num_rows(), 5);
+    // a: age 25-30 = Alice(25), Charlie(30), Eve(28)
+    // b: age > 30 = Bob(35), David(40)
+    // Edges: Alice->Bob, Charlie->David
+    assert_eq!(out.num_rows(), 2);
 
+    let a_names = out
+        .column(0)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
     let b_names = out
         .column(1)
         .as_any()
         .downcast_ref::<StringArray>()
         .unwrap();
 
-    // Targets ordered: Bob, Charlie(x2), David, Eve
-    assert_eq!(b_names.value(0), "Bob");
-    assert_eq!(b_names.value(1), "Charlie");
-    assert_eq!(b_names.value(2), "Charlie");
-    assert_eq!(b_names.value(3), "David");
-    assert_eq!(b_names.value(4), "Eve");
+    let mut pairs = Vec::new();
+    for i in 0..out.num_rows() {
+        pairs.push((a_names.value(i).to_string(), b_names.value(i).to_string()));
+    }
+
+    assert!(pairs.contains(&("Alice".to_string(), "Bob".to_string())));
+    assert!(pairs.contains(&("Charlie".to_string(), "David".to_string())));
 }
 
 #[tokio::test]
-async fn test_datafusion_order_by_two_hop_query() {
+async fn test_datafusion_distinct_with_two_hop() {
     let config = create_graph_config();
     let person_batch = create_person_dataset();
     let knows_batch = create_knows_dataset();
 
-    // Query: Two-hop with ORDER BY on final target
+    // Query: Get distinct source nodes that have two-hop paths
     let query = CypherQuery::new(
         "MATCH (a:Person)-[:KNOWS]->(b:Person)-[:KNOWS]->(c:Person) \
-         RETURN a.name, c.name ORDER BY c.name",
+         RETURN DISTINCT a.name",
     )
     .unwrap()
     .with_config(config);
@@ -1897,49 +2590,53 @@ async fn test_datafusion_order_by_two_hop_query() {
         .await
         .unwrap();
 
-    assert_eq!(out.num_rows(), 4);
+    // Sources with two-hop paths: Alice, Bob, Charlie
+    assert_eq!(out.num_rows(), 3);
 
-    let c_names = out
-        .column(1)
+    let names = out
+        .column(0)
         .as_any()
         .downcast_ref::<StringArray>()
         .unwrap();
+    let result_set: std::collections::HashSet<String> = (0..out.num_rows())
+        .map(|i| names.value(i).to_string())
+        .collect();
 
-    // Final targets ordered: Charlie, David(x2), Eve
-    assert_eq!(c_names.value(0), "Charlie");
-    assert_eq!(c_names.value(1), "David");
-    assert_eq!(c_names.value(2), "David");
-    assert_eq!(c_names.value(3), "Eve");
+    let expected: std::collections::HashSet<String> = ["Alice", "Bob", "Charlie"]
+        .into_iter()
+        .map(|s| s.to_string())
+        .collect();
+
+    assert_eq!(result_set, expected);
 }
 
 #[tokio::test]
-async fn test_datafusion_order_by_with_distinct() {
-    // Query: DISTINCT with ORDER BY
+async fn test_datafusion_expand_with_both_relationship_and_target_filters() {
+    // Query: Find people Alice knows since 2018 who are age 30
+    // Alice-[2020]->Bob(35), Alice-[2018]->Charlie(30)
+    // Only Charlie matches both filters
     let out = execute_test_query(
-        "MATCH (a:Person)-[:KNOWS]->(b:Person) RETURN DISTINCT b.name ORDER BY b.name",
+        "MATCH (a:Person {name: 'Alice'})-[:KNOWS {since_year: 2018}]->(b:Person {age: 30}) \
+         RETURN b.name",
     )
     .await;
 
-    // Distinct targets: Bob, Charlie, David, Eve
-    assert_eq!(out.num_rows(), 4);
-
+    assert_eq!(out.num_rows(), 1);
     let names = get_string_column(&out, 0);
-
-    // Alphabetical order
-    assert_eq!(names, vec!["Bob", "Charlie", "David", "Eve"]);
+    assert_eq!(names[0], "Charlie");
 }
 
 // ============================================================================
-// Column Alias Tests
+// ORDER BY Tests
 // ============================================================================
 
 #[tokio::test]
-async fn test_datafusion_return_with_single_alias() {
+async fn test_datafusion_order_by_single_column_asc() {
     let config = create_graph_config();
     let person_batch = create_person_dataset();
 
-    // Query: RETURN with alias
-    let query = CypherQuery::new("MATCH (p:Person) RETURN p.name AS person_name")
+    // Query: ORDER BY name ascending
+    let query = CypherQuery::new("MATCH (p:Person) RETURN p.name ORDER BY p.name")
         .unwrap()
         .with_config(config);
 
@@ -1953,29 +2650,29 @@ async fn test_datafusion_return_with_single_alias() {
 
     assert_eq!(out.num_rows(), 5);
 
-    // Check that the column is named "person_name" not "p__name"
-    let schema = out.schema();
-    assert_eq!(schema.fields().len(), 1);
-    assert_eq!(schema.field(0).name(), "person_name");
-
     let names = out
         .column(0)
         .as_any()
         .downcast_ref::<StringArray>()
         .unwrap();
-    assert!(!names.value(0).is_empty()); // Has data
+
+    // Verify alphabetical order: Alice, Bob, Charlie, David, Eve
+    assert_eq!(names.value(0), "Alice");
+    assert_eq!(names.value(1), "Bob");
+    assert_eq!(names.value(2), "Charlie");
+    assert_eq!(names.value(3), "David");
+    assert_eq!(names.value(4), "Eve");
 }
 
 #[tokio::test]
-async fn test_datafusion_return_with_multiple_aliases() {
+async fn test_datafusion_order_by_single_column_desc() {
     let config = create_graph_config();
     let person_batch = create_person_dataset();
 
-    // Query: Multiple columns with aliases
-    let query =
-        CypherQuery::new("MATCH (p:Person) WHERE p.age > 30 RETURN p.name AS name, p.age AS age")
-            .unwrap()
-            .with_config(config);
+    // Query: ORDER BY age descending
+    let query = CypherQuery::new("MATCH (p:Person) RETURN p.name, p.age ORDER BY p.age DESC")
+        .unwrap()
+        .with_config(config);
 
     let mut datasets = HashMap::new();
     datasets.insert("Person".to_string(), person_batch);
@@ -1985,14 +2682,7 @@ async fn test_datafusion_return_with_multiple_aliases() {
         .await
         .unwrap();
 
-    // Age > 30: Bob(35), Charlie(30 - excluded), David(40)
-    assert_eq!(out.num_rows(), 2);
-
-    // Check column names are aliased
-    let schema = out.schema();
-    assert_eq!(schema.fields().len(), 2);
-    assert_eq!(schema.field(0).name(), "name");
-    assert_eq!(schema.field(1).name(), "age");
+    assert_eq!(out.num_rows(), 5);
 
     let names = out
         .column(0)
@@ -2001,25 +2691,29 @@ async fn test_datafusion_return_with_multiple_aliases() {
         .unwrap();
     let ages = out.column(1).as_any().downcast_ref::<Int64Array>().unwrap();
 
-    // Verify data
-    let mut results: Vec<(String, i64)> = (0..out.num_rows())
-        .map(|i| (names.value(i).to_string(), ages.value(i)))
-        .collect();
-    results.sort_by_key(|r| r.1);
-
-    assert_eq!(results[0], ("Bob".to_string(), 35));
-    assert_eq!(results[1], ("David".to_string(), 40));
+    // Verify descending age order: David(40), Bob(35), Charlie(30), Eve(28), Alice(25)
+    assert_eq!(names.value(0), "David");
+    assert_eq!(ages.value(0), 40);
+    assert_eq!(names.value(1), "Bob");
+    assert_eq!(ages.value(1), 35);
+    assert_eq!(names.value(2), "Charlie");
+    assert_eq!(ages.value(2), 30);
+    assert_eq!(names.value(3), "Eve");
+    assert_eq!(ages.value(3), 28);
+    assert_eq!(names.value(4), "Alice");
+    assert_eq!(ages.value(4), 25);
 }
 
 #[tokio::test]
-async fn test_datafusion_return_mixed_with_and_without_alias() {
+async fn test_datafusion_order_by_multiple_columns() {
     let config = create_graph_config();
     let person_batch = create_person_dataset();
 
-    // Query: Mix of aliased and non-aliased columns
-    let query = CypherQuery::new("MATCH (p:Person) RETURN p.name AS full_name, p.age LIMIT 3")
-        .unwrap()
-        .with_config(config);
+    // Query: ORDER BY age DESC, name ASC (secondary sort by name)
+    let query =
+        CypherQuery::new("MATCH (p:Person) RETURN p.name, p.age ORDER BY p.age DESC, p.name ASC")
+            .unwrap()
+            .with_config(config);
 
     let mut datasets = HashMap::new();
     datasets.insert("Person".to_string(), person_batch);
@@ -2029,71 +2723,69 @@ async fn test_datafusion_return_mixed_with_and_without_alias() {
         .await
         .unwrap();
 
-    assert_eq!(out.num_rows(), 3);
+    assert_eq!(out.num_rows(), 5);
 
-    // Check column names
-    let schema = out.schema();
-    assert_eq!(schema.fields().len(), 2);
-    assert_eq!(schema.field(0).name(), "full_name"); // Aliased
-    assert_eq!(schema.field(1).name(), "p.age"); // Not aliased - Cypher dot notation
+    let _names = out
+        .column(0)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+    let ages = out.column(1).as_any().downcast_ref::<Int64Array>().unwrap();
+
+    // First by age DESC, then by name ASC
+    assert_eq!(ages.value(0), 40); // David
+    assert_eq!(ages.value(1), 35); // Bob
+    assert_eq!(ages.value(2), 30); // Charlie
+    assert_eq!(ages.value(3), 28); // Eve
+    assert_eq!(ages.value(4), 25); // Alice
 }
 
 #[tokio::test]
-async fn test_datafusion_return_alias_with_relationship() {
+async fn test_datafusion_order_by_with_limit() {
     let config = create_graph_config();
     let person_batch = create_person_dataset();
-    let knows_batch = create_knows_dataset();
 
-    // Query: Alias in relationship query
-    let query = CypherQuery::new(
-        "MATCH (a:Person)-[:KNOWS]->(b:Person) \
-         RETURN a.name AS source, b.name AS target \
-         ORDER BY source, target \
-         LIMIT 3",
-    )
-    .unwrap()
-    .with_config(config);
+    // Query: ORDER BY age DESC LIMIT 3 (top 3 oldest)
+    let query =
+        CypherQuery::new("MATCH (p:Person) RETURN p.name, p.age ORDER BY p.age DESC LIMIT 3")
+            .unwrap()
+            .with_config(config);
 
     let mut datasets = HashMap::new();
     datasets.insert("Person".to_string(), person_batch);
-    datasets.insert("KNOWS".to_string(), knows_batch);
 
     let out = query
         .execute(datasets, Some(ExecutionStrategy::DataFusion))
         .await
         .unwrap();
 
+    // Should only return 3 rows
     assert_eq!(out.num_rows(), 3);
 
-    // Check column names are aliased
-    let schema = out.schema();
-    assert_eq!(schema.field(0).name(), "source");
-    assert_eq!(schema.field(1).name(), "target");
-
-    let sources = out
+    let names = out
         .column(0)
         .as_any()
         .downcast_ref::<StringArray>()
         .unwrap();
-    let targets = out
-        .column(1)
-        .as_any()
-        .downcast_ref::<StringArray>()
-        .unwrap();
+    let ages = out.column(1).as_any().downcast_ref::<Int64Array>().unwrap();
 
-    // First 3 ordered by source, target
-    assert_eq!(sources.value(0), "Alice");
-    assert_eq!(targets.value(0), "Bob");
+    // Top 3 oldest: David(40), Bob(35), Charlie(30)
+    assert_eq!(names.value(0), "David");
+    assert_eq!(ages.value(0), 40);
+    assert_eq!(names.value(1), "Bob");
+    assert_eq!(ages.value(1), 35);
+    assert_eq!(names.value(2), "Charlie");
+    assert_eq!(ages.value(2), 30);
 }
 
 #[tokio::test]
-async fn test_datafusion_return_alias_with_order_by() {
+async fn test_datafusion_order_by_with_filter() {
     let config = create_graph_config();
     let person_batch = create_person_dataset();
 
-    // Query: Alias with ORDER BY (ORDER BY uses original property reference)
+    // Query: Filter then order
     let query =
-        CypherQuery::new("MATCH (p:Person) RETURN p.name AS name ORDER BY p.age DESC LIMIT 2")
+        CypherQuery::new("MATCH (p:Person) WHERE p.age >= 30 RETURN p.name ORDER BY p.name")
             .unwrap()
             .with_config(config);
 
@@ -2105,11 +2797,8 @@ async fn test_datafusion_return_alias_with_order_by() {
         .await
         .unwrap();
 
-    assert_eq!(out.num_rows(), 2);
-
-    // Check column name is aliased
-    let schema = out.schema();
-    assert_eq!(schema.field(0).name(), "name");
+    // Age >= 30: Bob(35), Charlie(30), David(40)
+    assert_eq!(out.num_rows(), 3);
 
     let names = out
         .column(0)
@@ -2117,25 +2806,24 @@ async fn test_datafusion_return_alias_with_order_by() {
         .downcast_ref::<StringArray>()
         .unwrap();
 
-    // Ordered by age DESC: David(40), Bob(35)
-    assert_eq!(names.value(0), "David");
-    assert_eq!(names.value(1), "Bob");
+    // Alphabetical: Bob, Charlie, David
+    assert_eq!(names.value(0), "Bob");
+    assert_eq!(names.value(1), "Charlie");
+    assert_eq!(names.value(2), "David");
 }
 
-// ============================================================================
-// Variable-Length Path Tests
-// ============================================================================
-
 #[tokio::test]
-async fn test_datafusion_varlength_single_hop() {
+async fn test_datafusion_order_by_relationship_query() {
     let config = create_graph_config();
     let person_batch = create_person_dataset();
     let knows_batch = create_knows_dataset();
 
-    // Query: MATCH (a:Person)-[:KNOWS*1..1]->(b:Person) - equivalent to single hop
-    let query = CypherQuery::new("MATCH (a:Person)-[:KNOWS*1..1]->(b:Person) RETURN b.name")
-        .unwrap()
-        .with_config(config);
+    // Query: Order relationship results by target name
+    let query = CypherQuery::new(
+        "MATCH (a:Person)-[:KNOWS]->(b:Person) RETURN a.name, b.name ORDER BY b.name",
+    )
+    .unwrap()
+    .with_config(config);
 
     let mut datasets = HashMap::new();
     datasets.insert("Person".to_string(), person_batch);
@@ -2146,36 +2834,35 @@ async fn test_datafusion_varlength_single_hop() {
         .await
         .unwrap();
 
-    // Same as single-hop: Alice→Bob, Alice→Charlie, Bob→Charlie, Charlie→David, David→Eve
     assert_eq!(out.num_rows(), 5);
 
-    let names = out
-        .column(0)
+    let b_names = out
+        .column(1)
         .as_any()
         .downcast_ref::<StringArray>()
         .unwrap();
 
-    // Collect all target names
-    let mut targets: Vec<String> = (0..out.num_rows())
-        .map(|i| names.value(i).to_string())
-        .collect();
-    targets.sort();
-
-    // Should have: Bob, Charlie(x2), David, Eve
-    assert_eq!(targets, vec!["Bob", "Charlie", "Charlie", "David", "Eve"]);
+    // Targets ordered: Bob, Charlie(x2), David, Eve
+    assert_eq!(b_names.value(0), "Bob");
+    assert_eq!(b_names.value(1), "Charlie");
+    assert_eq!(b_names.value(2), "Charlie");
+    assert_eq!(b_names.value(3), "David");
+    assert_eq!(b_names.value(4), "Eve");
 }
 
 #[tokio::test]
-async fn test_datafusion_varlength_two_hops() {
+async fn test_datafusion_order_by_two_hop_query() {
     let config = create_graph_config();
     let person_batch = create_person_dataset();
     let knows_batch = create_knows_dataset();
 
-    // Query: MATCH (a:Person)-[:KNOWS*2..2]->(b:Person) - exactly 2 hops
-    let query =
-        CypherQuery::new("MATCH (a:Person)-[:KNOWS*2..2]->(b:Person) RETURN a.name, b.name")
-            .unwrap()
-            .with_config(config);
+    // Query: Two-hop with ORDER BY on final target
+    let query = CypherQuery::new(
+        "MATCH (a:Person)-[:KNOWS]->(b:Person)-[:KNOWS]->(c:Person) \
+         RETURN a.name, c.name ORDER BY c.name",
+    )
+    .unwrap()
+    .with_config(config);
 
     let mut datasets = HashMap::new();
     datasets.insert("Person".to_string(), person_batch);
@@ -2186,34 +2873,823 @@ async fn test_datafusion_varlength_two_hops() {
         .await
         .unwrap();
 
-    // 2-hop paths: Alice→Bob→Charlie, Alice→Charlie→David, Bob→Charlie→David, Charlie→David→Eve
     assert_eq!(out.num_rows(), 4);
 
-    let sources = out
-        .column(0)
-        .as_any()
-        .downcast_ref::<StringArray>()
-        .unwrap();
-    let targets = out
+    let c_names = out
         .column(1)
         .as_any()
         .downcast_ref::<StringArray>()
         .unwrap();
 
-    // Collect all paths
-    let mut paths: Vec<(String, String)> = (0..out.num_rows())
-        .map(|i| (sources.value(i).to_string(), targets.value(i).to_string()))
-        .collect();
-    paths.sort();
+    // Final targets ordered: Charlie, David(x2), Eve
+    assert_eq!(c_names.value(0), "Charlie");
+    assert_eq!(c_names.value(1), "David");
+    assert_eq!(c_names.value(2), "David");
+    assert_eq!(c_names.value(3), "Eve");
+}
 
-    assert_eq!(
-        paths,
-        vec![
-            ("Alice".to_string(), "Charlie".to_string()),
-            ("Alice".to_string(), "David".to_string()),
-            ("Bob".to_string(), "David".to_string()),
-            ("Charlie".to_string(), "Eve".to_string()),
-        ]
+#[tokio::test]
+async fn test_datafusion_order_by_with_distinct() {
+    // Query: DISTINCT with ORDER BY
+    let out = execute_test_query(
+        "MATCH (a:Person)-[:KNOWS]->(b:Person) RETURN DISTINCT b.name ORDER BY b.name",
+    )
+    .await;
+
+    // Distinct targets: Bob, Charlie, David, Eve
+    assert_eq!(out.num_rows(), 4);
+
+    let names = get_string_column(&out, 0);
+
+    // Alphabetical order
+    assert_eq!(names, vec!["Bob", "Charlie", "David", "Eve"]);
+}
+
+#[tokio::test]
+async fn test_datafusion_order_by_aggregate_and_with_pagination() {
+    // An aggregate in ORDER BY sorts on the matching RETURN column
+    let out = execute_test_query(
+        "MATCH (a:Person)-[:KNOWS]->(b:Person) \
+         RETURN a.name, count(*) ORDER BY count(*) DESC, a.name LIMIT 2",
+    )
+    .await;
+    assert_eq!(get_string_column(&out, 0), vec!["Alice", "Bob"]);
+
+    // Pagination inside WITH applies before the next query part
+    let out = execute_test_query(
+        "MATCH (p:Person) WITH p ORDER BY p.age DESC SKIP 1 LIMIT 2 \
+         RETURN p.name ORDER BY p.name",
+    )
+    .await;
+    assert_eq!(get_string_column(&out, 0), vec!["Bob", "Charlie"]);
+}
+
+#[tokio::test]
+async fn test_datafusion_distinct_nulls_and_with() {
+    // Rows that are all NULL collapse into a single row
+    let out = execute_test_query(
+        "MATCH (p:Person) OPTIONAL MATCH (p)-[:KNOWS]->(q:Person) WHERE q.age > 100 \
+         RETURN DISTINCT q.name",
+    )
+    .await;
+    assert_eq!(out.num_rows(), 1);
+    assert!(out.column(0).is_null(0));
+
+    // A NULL city is one distinct value among the others
+    let out = execute_test_query(
+        "MATCH (a:Person)-[:KNOWS]->(b:Person) RETURN DISTINCT b.city ORDER BY b.city",
+    )
+    .await;
+    assert_eq!(out.num_rows(), 4);
+    assert_eq!(out.column(0).null_count(), 1);
+
+    // WITH DISTINCT deduplicates before ordering and paginating the next part
+    let out = execute_test_query(
+        "MATCH (a:Person)-[:KNOWS]->(b:Person) WITH DISTINCT b ORDER BY b.age LIMIT 2 \
+         RETURN b.name",
+    )
+    .await;
+    assert_eq!(get_string_column(&out, 0), vec!["Eve", "Charlie"]);
+}
+
+#[tokio::test]
+async fn test_datafusion_null_semantics() {
+    // Comparisons with NULL are unknown, so WHERE drops them, negated or not
+    for (cypher, expected) in [
+        (
+            "MATCH (a:Person)-[r:KNOWS]->(b:Person) WHERE r.since_year IS NULL RETURN a.name",
+            vec!["David"],
+        ),
+        (
+            "MATCH (a:Person)-[r:KNOWS]->(b:Person) WHERE r.since_year = null RETURN a.name",
+            vec![],
+        ),
+        (
+            "MATCH (a:Person)-[r:KNOWS]->(b:Person) WHERE NOT (r.since_year > 2019) \
+             RETURN a.name ORDER BY a.name",
+            vec!["Alice", "Bob"],
+        ),
+        (
+            "MATCH (a:Person)-[r:KNOWS]->(b:Person) WHERE NOT r.since_year IN [2019, null] \
+             RETURN a.name",
+            vec![],
+        ),
+        (
+            "MATCH (p:Person) WITH p, null AS n WHERE n = n RETURN p.name",
+            vec![],
+        ),
+        (
+            "MATCH (p:Person) WITH p, null AS n WHERE NOT (n = 1 AND p.age > 35) \
+             RETURN p.name ORDER BY p.name",
+            vec!["Alice", "Bob", "Charlie", "Eve"],
+        ),
+        // A whole node or relationship is NULL on the unmatched side of OPTIONAL MATCH
+        (
+            "MATCH (a:Person) OPTIONAL MATCH (a)-[:KNOWS]->(b:Person) WITH a, b \
+             WHERE b IS NULL RETURN a.name",
+            vec!["Eve"],
+        ),
+        (
+            "MATCH (a:Person) OPTIONAL MATCH (a)-[r:KNOWS]->(b:Person) WHERE b.age > 35 \
+             WITH a, r WHERE r IS NOT NULL RETURN a.name",
+            vec!["Charlie"],
+        ),
+    ] {
+        let out = execute_test_query(cypher).await;
+        assert_eq!(get_string_column(&out, 0), expected, "{}", cypher);
+    }
+
+    // NULL sorts last ascending and first descending
+    for (order, expected) in [
+        (
+            "",
+            vec![Some(2018), Some(2019), Some(2020), Some(2021), None],
+        ),
+        (
+            "DESC",
+            vec![None, Some(2021), Some(2020), Some(2019), Some(2018)],
+        ),
+    ] {
+        let out = execute_test_query(&format!(
+            "MATCH (a:Person)-[r:KNOWS]->(b:Person) RETURN r.since_year ORDER BY r.since_year {}",
+            order
+        ))
+        .await;
+        let years = out.column(0).as_any().downcast_ref::<Int64Array>().unwrap();
+        assert_eq!(years.iter().collect::<Vec<_>>(), expected);
+    }
+}
+
+#[tokio::test]
+async fn test_datafusion_null_functions() {
+    // coalesce takes the first non-NULL argument, so sparse properties get defaults
+    let out = execute_test_query(
+        "MATCH (p:Person) RETURN p.name, coalesce(p.city, 'unknown') AS city ORDER BY p.name",
+    )
+    .await;
+    assert_eq!(
+        get_string_column(&out, 1),
+        vec!["New York", "San Francisco", "Chicago", "unknown", "Seattle"]
+    );
+
+    let out = execute_test_query(
+        "MATCH (a:Person)-[r:KNOWS]->(b:Person) WHERE coalesce(r.since_year, 0) < 2019 \
+         RETURN b.name ORDER BY b.name",
+    )
+    .await;
+    assert_eq!(get_string_column(&out, 0), vec!["Charlie", "Eve"]);
+
+    // The NULL-padded rows of OPTIONAL MATCH fall through to the next argument
+    let out = execute_test_query(
+        "MATCH (a:Person) OPTIONAL MATCH (a)-[:KNOWS]->(b:Person) \
+         RETURN a.name, coalesce(b.name, a.name, 'nobody') AS friend ORDER BY a.name, friend",
+    )
+    .await;
+    assert_eq!(
+        get_string_column(&out, 1),
+        vec!["Bob", "Charlie", "Charlie", "David", "Eve", "Eve"]
+    );
+
+    // nullIf turns a sentinel back into NULL
+    let out = execute_test_query(
+        "MATCH (p:Person) RETURN nullIf(p.city, 'Seattle') AS city ORDER BY p.name",
+    )
+    .await;
+    let cities = out
+        .column(0)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+    assert_eq!(
+        cities.iter().collect::<Vec<_>>(),
+        vec![
+            Some("New York"),
+            Some("San Francisco"),
+            Some("Chicago"),
+            None,
+            None
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_datafusion_numeric_functions() {
+    let out = execute_test_query(
+        "MATCH (p:Person) WHERE p.name = 'Bob' \
+         RETURN abs(0 - p.age) AS abs, sign(0 - p.age) AS sign, ceil(2.1) AS ceil, \
+         floor(-2.1) AS floor, round(2.5) AS half, round(-2.5) AS negative_half, \
+         round(2.71828, 2) AS precise, sqrt(p.age + 1) AS root, exp(0) AS exp, \
+         log(1) AS log, log10(1000) AS log10, toInteger('3.7') AS parsed, \
+         toInteger(-2.9) AS truncated, toFloat(p.age) AS float, toFloat('x') AS invalid",
+    )
+    .await;
+    let ints = |column: &str| {
+        let values = out.column_by_name(column).unwrap();
+        values
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .unwrap()
+            .value(0)
+    };
+    let floats = |column: &str| {
+        let values = out.column_by_name(column).unwrap();
+        values
+            .as_any()
+            .downcast_ref::<Float64Array>()
+            .unwrap()
+            .value(0)
+    };
+    assert_eq!(ints("abs"), 35);
+    assert_eq!(ints("sign"), -1);
+    assert_eq!(ints("parsed"), 3);
+    assert_eq!(ints("truncated"), -2);
+    // Rounding functions return floats, and halves round up
+    for (column, expected) in [
+        ("ceil", 3.0),
+        ("floor", -3.0),
+        ("half", 3.0),
+        ("negative_half", -2.0),
+        ("precise", 2.72),
+        ("root", 6.0),
+        ("exp", 1.0),
+        ("log", 0.0),
+        ("log10", 3.0),
+        ("float", 35.0),
+    ] {
+        assert!(
+            (floats(column) - expected).abs() < 1e-9,
+            "{}: {}",
+            column,
+            floats(column)
+        );
+    }
+    assert!(out.column_by_name("invalid").unwrap().is_null(0));
+
+    // rand() is uniform in [0, 1)
+    let out = execute_test_query("MATCH (p:Person) RETURN rand() AS r").await;
+    let values = out
+        .column(0)
+        .as_any()
+        .downcast_ref::<Float64Array>()
+        .unwrap();
+    assert!(values.iter().all(|v| (0.0..1.0).contains(&v.unwrap())));
+
+    // Numeric functions compose into predicates
+    let out = execute_test_query(
+        "MATCH (p:Person) WHERE round(p.age / 10.0) = 3 RETURN p.name ORDER BY p.name",
+    )
+    .await;
+    assert_eq!(get_string_column(&out, 0), vec!["Alice", "Charlie", "Eve"]);
+}
+
+#[tokio::test]
+async fn test_datafusion_case_expressions() {
+    // Searched CASE takes the first matching branch
+    let out = execute_test_query(
+        "MATCH (p:Person) RETURN p.name, \
+         CASE WHEN p.age >= 35 THEN 'senior' WHEN p.age >= 28 THEN 'mid' ELSE 'junior' END \
+         AS band ORDER BY p.name",
+    )
+    .await;
+    assert_eq!(
+        get_string_column(&out, 1),
+        vec!["junior", "senior", "mid", "senior", "mid"]
+    );
+
+    // Simple CASE without ELSE yields NULL when nothing matches
+    let out = execute_test_query(
+        "MATCH (p:Person) RETURN p.name, \
+         CASE p.city WHEN 'Chicago' THEN 1 WHEN 'Seattle' THEN 2 END AS code ORDER BY p.name",
+    )
+    .await;
+    let codes = out.column(1).as_any().downcast_ref::<Int64Array>().unwrap();
+    let codes: Vec<Option<i64>> = codes.iter().collect();
+    assert_eq!(codes, vec![None, None, Some(1), None, Some(2)]);
+
+    // CASE works inside predicates too
+    let out = execute_test_query(
+        "MATCH (p:Person) \
+         WHERE CASE WHEN p.city IS NULL THEN 'unknown' ELSE p.city END = 'unknown' \
+         RETURN p.name",
+    )
+    .await;
+    assert_eq!(get_string_column(&out, 0), vec!["David"]);
+}
+
+#[tokio::test]
+async fn test_datafusion_list_expressions() {
+    let int_list = |batch: &RecordBatch, column: usize, row: usize| -> Vec<Option<i64>> {
+        let lists = batch
+            .column(column)
+            .as_any()
+            .downcast_ref::<ListArray>()
+            .unwrap();
+        let values = lists.value(row);
+        values
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .unwrap()
+            .iter()
+            .collect()
+    };
+
+    // Indexes are zero-based and negative ones count from the end; slices exclude the end
+    let out = execute_test_query(
+        "MATCH (p:Person {name: 'Bob'}) \
+         RETURN [p.age, 1] AS l, [1, 2, 3][0] AS first, [1, 2, 3][-1] AS last, \
+         [1, 2, 3][5] AS missing, [1, 2, 3, 4][1..3] AS middle, [1, 2, 3, 4][..-1] AS init",
+    )
+    .await;
+    assert_eq!(int_list(&out, 0, 0), vec![Some(35), Some(1)]);
+    let first = out.column(1).as_any().downcast_ref::<Int64Array>().unwrap();
+    let last = out.column(2).as_any().downcast_ref::<Int64Array>().unwrap();
+    let missing = out.column(3).as_any().downcast_ref::<Int64Array>().unwrap();
+    assert_eq!(first.value(0), 1);
+    assert_eq!(last.value(0), 3);
+    assert!(missing.is_null(0));
+    assert_eq!(int_list(&out, 4, 0), vec![Some(2), Some(3)]);
+    assert_eq!(int_list(&out, 5, 0), vec![Some(1), Some(2), Some(3)]);
+
+    // Comprehensions filter and map collected lists and can read outer columns
+    let out = execute_test_query(
+        "MATCH (p:Person)-[:KNOWS]->(f:Person) \
+         WITH p, collect(f.age) AS ages \
+         RETURN p.name, [x IN ages WHERE x > p.age] AS older, \
+         [x IN ages | p.age] AS own, collect(p.age)[0] AS age \
+         ORDER BY p.name",
+    )
+    .await;
+    assert_eq!(
+        get_string_column(&out, 0),
+        vec!["Alice", "Bob", "Charlie", "David"]
+    );
+    let mut alice_older = int_list(&out, 1, 0);
+    alice_older.sort();
+    assert_eq!(alice_older, vec![Some(30), Some(35)]);
+    assert_eq!(int_list(&out, 1, 1), Vec::<Option<i64>>::new());
+    assert_eq!(int_list(&out, 1, 2), vec![Some(40)]);
+    assert_eq!(int_list(&out, 1, 3), Vec::<Option<i64>>::new());
+    assert_eq!(int_list(&out, 2, 0), vec![Some(25), Some(25)]);
+    let age = out.column(3).as_any().downcast_ref::<Int64Array>().unwrap();
+    assert_eq!(age.values().to_vec(), vec![25, 35, 30, 40]);
+}
+
+#[tokio::test]
+async fn test_datafusion_exists_subqueries() {
+    let out = execute_test_query(
+        "MATCH (p:Person) WHERE EXISTS { MATCH (p)-[:KNOWS]->(:Person) } \
+         RETURN p.name ORDER BY p.name",
+    )
+    .await;
+    assert_eq!(
+        get_string_column(&out, 0),
+        vec!["Alice", "Bob", "Charlie", "David"]
+    );
+
+    let out = execute_test_query(
+        "MATCH (p:Person) WHERE NOT EXISTS { (p)-[:KNOWS]->(:Person) } RETURN p.name",
+    )
+    .await;
+    assert_eq!(get_string_column(&out, 0), vec!["Eve"]);
+
+    // The subquery's WHERE can compare inner and outer variables
+    let out = execute_test_query(
+        "MATCH (p:Person) \
+         WHERE p.age > 20 AND EXISTS { MATCH (p)-[:KNOWS]->(f:Person) WHERE f.age > p.age } \
+         RETURN p.name ORDER BY p.name",
+    )
+    .await;
+    assert_eq!(get_string_column(&out, 0), vec!["Alice", "Charlie"]);
+
+    // Nested subqueries: people who know someone with no outgoing KNOWS
+    let out = execute_test_query(
+        "MATCH (p:Person) \
+         WHERE EXISTS { MATCH (p)-[:KNOWS]->(f:Person) \
+                        WHERE NOT EXISTS { (f)-[:KNOWS]->(:Person) } } \
+         RETURN p.name",
+    )
+    .await;
+    assert_eq!(get_string_column(&out, 0), vec!["David"]);
+
+    let mut datasets = HashMap::new();
+    datasets.insert("Person".to_string(), create_person_dataset());
+    datasets.insert("KNOWS".to_string(), create_knows_dataset());
+    let err = CypherQuery::new(
+        "MATCH (p:Person) WHERE p.age > 30 OR EXISTS { (p)-[:KNOWS]->(:Person) } RETURN p.name",
+    )
+    .unwrap()
+    .with_config(create_graph_config())
+    .execute(datasets, Some(ExecutionStrategy::DataFusion))
+    .await;
+    assert!(err.is_err());
+}
+
+#[tokio::test]
+async fn test_datafusion_pattern_predicates() {
+    // Alice knows two people but is returned once
+    let out = execute_test_query(
+        "MATCH (p:Person) WHERE (p)-[:KNOWS]->(:Person) RETURN p.name ORDER BY p.name",
+    )
+    .await;
+    assert_eq!(
+        get_string_column(&out, 0),
+        vec!["Alice", "Bob", "Charlie", "David"]
+    );
+
+    let out = execute_test_query(
+        "MATCH (p:Person) \
+         WHERE p.age < 35 AND (p)-[:KNOWS]->(:Person)-[:KNOWS]->(:Person {name: 'David'}) \
+         RETURN p.name ORDER BY p.name",
+    )
+    .await;
+    assert_eq!(get_string_column(&out, 0), vec!["Alice"]);
+
+    // Both endpoints bound by the outer MATCH
+    let out = execute_test_query(
+        "MATCH (a:Person), (b:Person) WHERE a.age > 30 AND (a)-[:KNOWS]->(b) \
+         RETURN a.name, b.name ORDER BY a.name",
+    )
+    .await;
+    assert_eq!(get_string_column(&out, 0), vec!["Bob", "David"]);
+    assert_eq!(get_string_column(&out, 1), vec!["Charlie", "Eve"]);
+}
+
+#[tokio::test]
+async fn test_datafusion_negated_pattern_predicates() {
+    let out = execute_test_query(
+        "MATCH (a:Person), (b:Person) \
+         WHERE a.age > 30 AND b.age < 35 AND NOT (a)-[:KNOWS]->(b) \
+         RETURN a.name, b.name ORDER BY a.name, b.name",
+    )
+    .await;
+    // Bob knows Charlie and David knows Eve
+    assert_eq!(
+        get_string_column(&out, 0),
+        vec!["Bob", "Bob", "David", "David"]
+    );
+    assert_eq!(
+        get_string_column(&out, 1),
+        vec!["Alice", "Eve", "Alice", "Charlie"]
+    );
+
+    // Undirected: Alice knows Bob and Charlie knows David, so those pairs are dropped too
+    let out = execute_test_query(
+        "MATCH (a:Person), (b:Person) \
+         WHERE a.age > 30 AND b.age < 35 AND NOT (a)-[:KNOWS]-(b) \
+         RETURN a.name, b.name ORDER BY a.name, b.name",
+    )
+    .await;
+    assert_eq!(get_string_column(&out, 0), vec!["Bob", "David"]);
+    assert_eq!(get_string_column(&out, 1), vec!["Eve", "Alice"]);
+}
+
+#[tokio::test]
+async fn test_datafusion_count_subqueries() {
+    let counts = |batch: &RecordBatch, column: usize| -> Vec<i64> {
+        batch
+            .column(column)
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .unwrap()
+            .values()
+            .to_vec()
+    };
+
+    // People without a match count zero
+    let out = execute_test_query(
+        "MATCH (p:Person) RETURN p.name, COUNT { (p)-[:KNOWS]->(:Person) } AS degree \
+         ORDER BY p.name",
+    )
+    .await;
+    assert_eq!(
+        get_string_column(&out, 0),
+        vec!["Alice", "Bob", "Charlie", "David", "Eve"]
+    );
+    assert_eq!(counts(&out, 1), vec![2, 1, 1, 1, 0]);
+
+    // The subquery's WHERE can compare inner and outer variables
+    let out = execute_test_query(
+        "MATCH (p:Person) \
+         RETURN p.name, COUNT { MATCH (p)<-[:KNOWS]-(f:Person) WHERE f.age > p.age } AS older \
+         ORDER BY older DESC, p.name",
+    )
+    .await;
+    assert_eq!(
+        get_string_column(&out, 0),
+        vec!["Charlie", "Eve", "Alice", "Bob", "David"]
+    );
+    assert_eq!(counts(&out, 1), vec![1, 1, 0, 0, 0]);
+
+    let out = execute_test_query(
+        "MATCH (p:Person) WHERE COUNT { (p)-[:KNOWS]->(:Person) } >= 2 RETURN p.name",
+    )
+    .await;
+    assert_eq!(get_string_column(&out, 0), vec!["Alice"]);
+
+    // Duplicate outer rows are counted separately
+    let out = execute_test_query(
+        "MATCH (p:Person)-[:KNOWS]->(q:Person) WITH q.name AS name, q \
+         RETURN name, COUNT { (q)-[:KNOWS]->(:Person) } AS c ORDER BY name",
+    )
+    .await;
+    assert_eq!(
+        get_string_column(&out, 0),
+        vec!["Bob", "Charlie", "Charlie", "David", "Eve"]
+    );
+    assert_eq!(counts(&out, 1), vec![1, 1, 1, 1, 0]);
+
+    let out = execute_test_query(
+        "MATCH (p:Person) RETURN sum(COUNT { (p)-[:KNOWS]->(:Person) }) AS edges",
+    )
+    .await;
+    assert_eq!(counts(&out, 0), vec![5]);
+}
+
+// ============================================================================
+// Column Alias Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_datafusion_return_with_single_alias() {
+    let config = create_graph_config();
+    let person_batch = create_person_dataset();
+
+    // Query: RETURN with alias
+    let query = CypherQuery::new("MATCH (p:Person) RETURN p.name AS person_name")
+        .unwrap()
+        .with_config(config);
+
+    let mut datasets = HashMap::new();
+    datasets.insert("Person".to_string(), person_batch);
+
+    let out = query
+        .execute(datasets, Some(ExecutionStrategy::DataFusion))
+        .await
+        .unwrap();
+
+    assert_eq!(out.num_rows(), 5);
+
+    // Check that the column is named "person_name" not "p__name"
+    let schema = out.schema();
+    assert_eq!(schema.fields().len(), 1);
+    assert_eq!(schema.field(0).name(), "person_name");
+
+    let names = out
+        .column(0)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+    assert!(!names.value(0).is_empty()); // Has data
+}
+
+#[tokio::test]
+async fn test_datafusion_return_with_multiple_aliases() {
+    let config = create_graph_config();
+    let person_batch = create_person_dataset();
+
+    // Query: Multiple columns with aliases
+    let query =
+        CypherQuery::new("MATCH (p:Person) WHERE p.age > 30 RETURN p.name AS name, p.age AS age")
+            .unwrap()
+            .with_config(config);
+
+    let mut datasets = HashMap::new();
+    datasets.insert("Person".to_string(), person_batch);
+
+    let out = query
+        .execute(datasets, Some(ExecutionStrategy::DataFusion))
+        .await
+        .unwrap();
+
+    // Age > 30: Bob(35), Charlie(30 - excluded), David(40)
+    assert_eq!(out.num_rows(), 2);
+
+    // Check column names are aliased
+    let schema = out.schema();
+    assert_eq!(schema.fields().len(), 2);
+    assert_eq!(schema.field(0).name(), "name");
+    assert_eq!(schema.field(1).name(), "age");
+
+    let names = out
+        .column(0)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+    let ages = out.column(1).as_any().downcast_ref::<Int64Array>().unwrap();
+
+    // Verify data
+    let mut results: Vec<(String, i64)> = (0..out.num_rows())
+        .map(|i| (names.value(i).to_string(), ages.value(i)))
+        .collect();
+    results.sort_by_key(|r| r.1);
+
+    assert_eq!(results[0], ("Bob".to_string(), 35));
+    assert_eq!(results[1], ("David".to_string(), 40));
+}
+
+#[tokio::test]
+async fn test_datafusion_return_mixed_with_and_without_alias() {
+    let config = create_graph_config();
+    let person_batch = create_person_dataset();
+
+    // Query: Mix of aliased and non-aliased columns
+    let query = CypherQuery::new("MATCH (p:Person) RETURN p.name AS full_name, p.age LIMIT 3")
+        .unwrap()
+        .with_config(config);
+
+    let mut datasets = HashMap::new();
+    datasets.insert("Person".to_string(), person_batch);
+
+    let out = query
+        .execute(datasets, Some(ExecutionStrategy::DataFusion))
+        .await
+        .unwrap();
+
+    assert_eq!(out.num_rows(), 3);
+
+    // Check column names
+    let schema = out.schema();
+    assert_eq!(schema.fields().len(), 2);
+    assert_eq!(schema.field(0).name(), "full_name"); // Aliased
+    assert_eq!(schema.field(1).name(), "p.age"); // Not aliased - Cypher dot notation
+}
+
+#[tokio::test]
+async fn test_datafusion_return_alias_with_relationship() {
+    let config = create_graph_config();
+    let person_batch = create_person_dataset();
+    let knows_batch = create_knows_dataset();
+
+    // Query: Alias in relationship query
+    let query = CypherQuery::new(
+        "MATCH (a:Person)-[:KNOWS]->(b:Person) \
+         RETURN a.name AS source, b.name AS target \
+         ORDER BY source, target \
+         LIMIT 3",
+    )
+    .unwrap()
+    .with_config(config);
+
+    let mut datasets = HashMap::new();
+    datasets.insert("Person".to_string(), person_batch);
+    datasets.insert("KNOWS".to_string(), knows_batch);
+
+    let out = query
+        .execute(datasets, Some(ExecutionStrategy::DataFusion))
+        .await
+        .unwrap();
+
+    assert_eq!(out.num_rows(), 3);
+
+    // Check column names are aliased
+    let schema = out.schema();
+    assert_eq!(schema.field(0).name(), "source");
+    assert_eq!(schema.field(1).name(), "target");
+
+    let sources = out
+        .column(0)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+    let targets = out
+        .column(1)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+
+    // First 3 ordered by source, target
+    assert_eq!(sources.value(0), "Alice");
+    assert_eq!(targets.value(0), "Bob");
+}
+
+#[tokio::test]
+async fn test_datafusion_return_alias_with_order_by() {
+    let config = create_graph_config();
+    let person_batch = create_person_dataset();
+
+    // Query: Alias with ORDER BY (ORDER BY uses original property reference)
+    let query =
+        CypherQuery::new("MATCH (p:Person) RETURN p.name AS name ORDER BY p.age DESC LIMIT 2")
+            .unwrap()
+            .with_config(config);
+
+    let mut datasets = HashMap::new();
+    datasets.insert("Person".to_string(), person_batch);
+
+    let out = query
+        .execute(datasets, Some(ExecutionStrategy::DataFusion))
+        .await
+        .unwrap();
+
+    assert_eq!(out.num_rows(), 2);
+
+    // Check column name is aliased
+    let schema = out.schema();
+    assert_eq!(schema.field(0).name(), "name");
+
+    let names = out
+        .column(0)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+
+    // Ordered by age DESC: David(40), Bob(35)
+    assert_eq!(names.value(0), "David");
+    assert_eq!(names.value(1), "Bob");
+}
+
+// ============================================================================
+// Variable-Length Path Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_datafusion_varlength_single_hop() {
+    let config = create_graph_config();
+    let person_batch = create_person_dataset();
+    let knows_batch = create_knows_dataset();
+
+    // Query: MATCH (a:Person)-[:KNOWS*1..1]->(b:Person) - equivalent to single hop
+    let query = CypherQuery::new("MATCH (a:Person)-[:KNOWS*1..1]->(b:Person) RETURN b.name")
+        .unwrap()
+        .with_config(config);
+
+    let mut datasets = HashMap::new();
+    datasets.insert("Person".to_string(), person_batch);
+    datasets.insert("KNOWS".to_string(), knows_batch);
+
+    let out = query
+        .execute(datasets, Some(ExecutionStrategy::DataFusion))
+        .await
+        .unwrap();
+
+    // Same as single-hop: Alice→Bob, Alice→Charlie, Bob→Charlie, Charlie→David, David→Eve
+    assert_eq!(out.num_rows(), 5);
+
+    let names = out
+        .column(0)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+
+    // Collect all target names
+    let mut targets: Vec<String> = (0..out.num_rows())
+        .map(|i| names.value(i).to_string())
+        .collect();
+    targets.sort();
+
+    // Should have: Bob, Charlie(x2), David, Eve
+    assert_eq!(targets, vec!["Bob", "Charlie", "Charlie", "David", "Eve"]);
+}
+
+#[tokio::test]
+async fn test_datafusion_varlength_two_hops() {
+    let config = create_graph_config();
+    let person_batch = create_person_dataset();
+    let knows_batch = create_knows_dataset();
+
+    // Query: MATCH (a:Person)-[:KNOWS*2..2]->(b:Person) - exactly 2 hops
+    let query =
+        CypherQuery::new("MATCH (a:Person)-[:KNOWS*2..2]->(b:Person) RETURN a.name, b.name")
+            .unwrap()
+            .with_config(config);
+
+    let mut datasets = HashMap::new();
+    datasets.insert("Person".to_string(), person_batch);
+    datasets.insert("KNOWS".to_string(), knows_batch);
+
+    let out = query
+        .execute(datasets, Some(ExecutionStrategy::DataFusion))
+        .await
+        .unwrap();
+
+    // 2-hop paths: Alice→Bob→Charlie, Alice→Charlie→David, Bob→Charlie→David, Charlie→David→Eve
+    assert_eq!(out.num_rows(), 4);
+
+    let sources = out
+        .column(0)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+    let targets = out
+        .column(1)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+
+    // Collect all paths
+    let mut paths: Vec<(String, String)> = (0..out.num_rows())
+        .map(|i| (sources.value(i).to_string(), targets.value(i).to_string()))
+        .collect();
+    paths.sort();
+
+    assert_eq!(
+        paths,
+        vec![
+            ("Alice".to_string(), "Charlie".to_string()),
+            ("Alice".to_string(), "David".to_string()),
+            ("Bob".to_string(), "David".to_string()),
+            ("Charlie".to_string(), "Eve".to_string()),
+        ]
     );
 }
 
@@ -2438,6 +3914,103 @@ async fn test_datafusion_varlength_three_hops() {
     assert_eq!(targets, vec!["David", "Eve"]);
 }
 
+#[tokio::test]
+async fn test_datafusion_varlength_configured_max_hops() {
+    let config = GraphConfig::builder()
+        .with_node_label("Person", "id")
+        .with_relationship("KNOWS", "src_person_id", "dst_person_id")
+        .with_max_variable_length_hops(2)
+        .build()
+        .unwrap();
+    let mut datasets = HashMap::new();
+    datasets.insert("Person".to_string(), create_person_dataset());
+    datasets.insert("KNOWS".to_string(), create_knows_dataset());
+
+    // An unbounded pattern expands up to the configured depth
+    let query = CypherQuery::new(
+        "MATCH (a:Person {name: 'Alice'})-[:KNOWS*]->(b:Person) \
+         RETURN b.name ORDER BY b.name",
+    )
+    .unwrap()
+    .with_config(config.clone());
+    let out = query
+        .execute(datasets.clone(), Some(ExecutionStrategy::DataFusion))
+        .await
+        .unwrap();
+    // 1 hop: Bob, Charlie; 2 hops: Alice→Bob→Charlie, Alice→Charlie→David
+    assert_eq!(
+        get_string_column(&out, 0),
+        vec!["Bob", "Charlie", "Charlie", "David"]
+    );
+
+    // Explicit ranges beyond the limit are rejected
+    let query = CypherQuery::new("MATCH (a:Person)-[:KNOWS*1..3]->(b:Person) RETURN b.name")
+        .unwrap()
+        .with_config(config);
+    assert!(query
+        .execute(datasets, Some(ExecutionStrategy::DataFusion))
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_datafusion_shortest_path() {
+    // Alice→Charlie→David→Eve beats Alice→Bob→Charlie→David→Eve
+    let result = execute_test_query(
+        "MATCH p = shortestPath((a:Person {name: 'Alice'})-[:KNOWS*]->(b:Person)) \
+         RETURN b.name, length(p) AS hops ORDER BY b.name",
+    )
+    .await;
+    assert_eq!(
+        get_string_column(&result, 0),
+        vec!["Bob", "Charlie", "David", "Eve"]
+    );
+    let hops = result
+        .column(1)
+        .as_any()
+        .downcast_ref::<Int64Array>()
+        .unwrap();
+    assert_eq!(hops.values().to_vec(), vec![1, 1, 2, 3]);
+
+    // Both endpoints bound by an earlier MATCH
+    let result = execute_test_query(
+        "MATCH (a:Person {name: 'Alice'}), (b:Person {name: 'Eve'}) \
+         MATCH p = shortestPath((a)-[:KNOWS*]->(b)) RETURN nodes(p) AS path",
+    )
+    .await;
+    assert_eq!(result.num_rows(), 1);
+    let path = result
+        .column(0)
+        .as_any()
+        .downcast_ref::<ListArray>()
+        .unwrap()
+        .value(0);
+    let path = path.as_any().downcast_ref::<Int64Array>().unwrap();
+    assert_eq!(path.values().to_vec(), vec![1, 3, 4, 5]);
+
+    // allShortestPaths keeps every minimal path; Charlie is one hop away from Alice
+    // directly, Bob→Charlie is longer
+    let result = execute_test_query(
+        "MATCH p = allShortestPaths((a:Person {name: 'Alice'})-[:KNOWS*]->(b:Person {name: 'Charlie'})) \
+         RETURN length(p) AS hops",
+    )
+    .await;
+    assert_eq!(result.num_rows(), 1);
+
+    // A minimum length skips shorter routes
+    let result = execute_test_query(
+        "MATCH p = allShortestPaths((a:Person {name: 'Alice'})-[:KNOWS*2..]->(b:Person {name: 'Charlie'})) \
+         RETURN length(p) AS hops",
+    )
+    .await;
+    let hops = result
+        .column(0)
+        .as_any()
+        .downcast_ref::<Int64Array>()
+        .unwrap();
+    assert_eq!(hops.values().to_vec(), vec![2]);
+}
+
 #[tokio::test]
 async fn test_datafusion_varlength_no_results() {
     let config = create_graph_config();
@@ -2745,27 +4318,76 @@ async fn test_count_with_grouping() {
         .downcast_ref::<Int64Array>()
         .unwrap();
 
-    // NULL city: 1 person (David)
-    assert!(city_col.is_null(0));
+    // Chicago: 1 person (Charlie)
+    assert_eq!(city_col.value(0), "Chicago");
     assert_eq!(count_col.value(0), 1);
 
-    // Chicago: 1 person (Charlie)
-    assert_eq!(city_col.value(1), "Chicago");
+    // New York: 1 person (Alice)
+    assert_eq!(city_col.value(1), "New York");
     assert_eq!(count_col.value(1), 1);
 
-    // New York: 1 person (Alice)
-    assert_eq!(city_col.value(2), "New York");
+    // San Francisco: 1 person (Bob)
+    assert_eq!(city_col.value(2), "San Francisco");
     assert_eq!(count_col.value(2), 1);
 
-    // San Francisco: 1 person (Bob)
-    assert_eq!(city_col.value(3), "San Francisco");
+    // Seattle: 1 person (Eve)
+    assert_eq!(city_col.value(3), "Seattle");
     assert_eq!(count_col.value(3), 1);
 
-    // Seattle: 1 person (Eve)
-    assert_eq!(city_col.value(4), "Seattle");
+    // NULL city: 1 person (David)
+    assert!(city_col.is_null(4));
     assert_eq!(count_col.value(4), 1);
 }
 
+#[tokio::test]
+async fn test_distinct_aggregates() {
+    // Charlie is known twice; DISTINCT counts him once
+    let result = execute_test_query(
+        "MATCH (a:Person)-[:KNOWS]->(b:Person) \
+         RETURN count(b.name) AS total, count(DISTINCT b.name), sum(DISTINCT b.age) AS ages",
+    )
+    .await;
+    let int_column = |name: &str| {
+        result
+            .column_by_name(name)
+            .unwrap_or_else(|| panic!("missing column {}", name))
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .unwrap()
+            .value(0)
+    };
+    assert_eq!(int_column("total"), 5);
+    assert_eq!(int_column("count(DISTINCT b.name)"), 4);
+    assert_eq!(int_column("ages"), 35 + 30 + 40 + 28);
+
+    // Non-aggregated items form the grouping key
+    let result = execute_test_query(
+        "MATCH (a:Person)-[:KNOWS]->(b:Person) \
+         RETURN b.name, collect(DISTINCT a.name) AS sources ORDER BY b.name",
+    )
+    .await;
+    assert_eq!(
+        get_string_column(&result, 0),
+        vec!["Bob", "Charlie", "David", "Eve"]
+    );
+    let sources = result
+        .column(1)
+        .as_any()
+        .downcast_ref::<ListArray>()
+        .unwrap();
+    let mut charlie_sources: Vec<String> = sources
+        .value(1)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap()
+        .iter()
+        .flatten()
+        .map(str::to_string)
+        .collect();
+    charlie_sources.sort();
+    assert_eq!(charlie_sources, vec!["Alice", "Bob"]);
+}
+
 #[tokio::test]
 async fn test_count_without_alias_has_descriptive_name() {
     let person_batch = create_person_dataset();
@@ -2927,21 +4549,21 @@ async fn test_sum_with_grouping() {
         .downcast_ref::<Int64Array>()
         .unwrap();
 
-    // Verify grouping results (ordered by city, NULL comes first)
-    assert!(city_col.is_null(0)); // David: 40 (NULL city)
-    assert_eq!(sum_col.value(0), 40);
+    // Verify grouping results (ordered by city, NULL comes last)
+    assert_eq!(city_col.value(0), "Chicago"); // Charlie: 30
+    assert_eq!(sum_col.value(0), 30);
 
-    assert_eq!(city_col.value(1), "Chicago"); // Charlie: 30
-    assert_eq!(sum_col.value(1), 30);
+    assert_eq!(city_col.value(1), "New York"); // Alice: 25
+    assert_eq!(sum_col.value(1), 25);
 
-    assert_eq!(city_col.value(2), "New York"); // Alice: 25
-    assert_eq!(sum_col.value(2), 25);
+    assert_eq!(city_col.value(2), "San Francisco"); // Bob: 35
+    assert_eq!(sum_col.value(2), 35);
 
-    assert_eq!(city_col.value(3), "San Francisco"); // Bob: 35
-    assert_eq!(sum_col.value(3), 35);
+    assert_eq!(city_col.value(3), "Seattle"); // Eve: 28
+    assert_eq!(sum_col.value(3), 28);
 
-    assert_eq!(city_col.value(4), "Seattle"); // Eve: 28
-    assert_eq!(sum_col.value(4), 28);
+    assert!(city_col.is_null(4)); // David: 40 (NULL city)
+    assert_eq!(sum_col.value(4), 40);
 }
 
 #[tokio::test]
@@ -3076,21 +4698,21 @@ async fn test_avg_with_grouping() {
         .downcast_ref::<Float64Array>()
         .unwrap();
 
-    // Verify grouping results (ordered by city, NULL comes first)
-    assert!(city_col.is_null(0)); // David: 40 (NULL city)
-    assert_eq!(avg_col.value(0), 40.0);
+    // Verify grouping results (ordered by city, NULL comes last)
+    assert_eq!(city_col.value(0), "Chicago"); // Charlie: 30
+    assert_eq!(avg_col.value(0), 30.0);
 
-    assert_eq!(city_col.value(1), "Chicago"); // Charlie: 30
-    assert_eq!(avg_col.value(1), 30.0);
+    assert_eq!(city_col.value(1), "New York"); // Alice: 25
+    assert_eq!(avg_col.value(1), 25.0);
 
-    assert_eq!(city_col.value(2), "New York"); // Alice: 25
-    assert_eq!(avg_col.value(2), 25.0);
+    assert_eq!(city_col.value(2), "San Francisco"); // Bob: 35
+    assert_eq!(avg_col.value(2), 35.0);
 
-    assert_eq!(city_col.value(3), "San Francisco"); // Bob: 35
-    assert_eq!(avg_col.value(3), 35.0);
+    assert_eq!(city_col.value(3), "Seattle"); // Eve: 28
+    assert_eq!(avg_col.value(3), 28.0);
 
-    assert_eq!(city_col.value(4), "Seattle"); // Eve: 28
-    assert_eq!(avg_col.value(4), 28.0);
+    assert!(city_col.is_null(4)); // David: 40 (NULL city)
+    assert_eq!(avg_col.value(4), 40.0);
 }
 
 #[tokio::test]
@@ -3252,31 +4874,31 @@ async fn test_min_max_with_grouping() {
         .downcast_ref::<Int64Array>()
         .unwrap();
 
-    // ORDER BY p.city, NULL comes first per your other tests
-    assert!(city_col_min.is_null(0)); // David city NULL
-    assert!(city_col_max.is_null(0));
-    assert_eq!(min_col_min.value(0), 40);
-    assert_eq!(min_col_max.value(0), 40);
+    assert_eq!(city_col_min.value(0), "Chicago"); // Charlie
+    assert_eq!(city_col_max.value(0), "Chicago");
+    assert_eq!(min_col_min.value(0), 30);
+    assert_eq!(min_col_max.value(0), 30);
 
-    assert_eq!(city_col_min.value(1), "Chicago"); // Charlie
-    assert_eq!(city_col_max.value(1), "Chicago");
-    assert_eq!(min_col_min.value(1), 30);
-    assert_eq!(min_col_max.value(1), 30);
+    assert_eq!(city_col_min.value(1), "New York"); // Alice
+    assert_eq!(city_col_max.value(1), "New York");
+    assert_eq!(min_col_min.value(1), 25);
+    assert_eq!(min_col_max.value(1), 25);
 
-    assert_eq!(city_col_min.value(2), "New York"); // Alice
-    assert_eq!(city_col_max.value(2), "New York");
-    assert_eq!(min_col_min.value(2), 25);
-    assert_eq!(min_col_max.value(2), 25);
+    assert_eq!(city_col_min.value(2), "San Francisco"); // Bob
+    assert_eq!(city_col_max.value(2), "San Francisco");
+    assert_eq!(min_col_min.value(2), 35);
+    assert_eq!(min_col_max.value(2), 35);
 
-    assert_eq!(city_col_min.value(3), "San Francisco"); // Bob
-    assert_eq!(city_col_max.value(3), "San Francisco");
-    assert_eq!(min_col_min.value(3), 35);
-    assert_eq!(min_col_max.value(3), 35);
+    assert_eq!(city_col_min.value(3), "Seattle"); // Eve
+    assert_eq!(city_col_max.value(3), "Seattle");
+    assert_eq!(min_col_min.value(3), 28);
+    assert_eq!(min_col_max.value(3), 28);
 
-    assert_eq!(city_col_min.value(4), "Seattle"); // Eve
-    assert_eq!(city_col_max.value(4), "Seattle");
-    assert_eq!(min_col_min.value(4), 28);
-    assert_eq!(min_col_max.value(4), 28);
+    // ORDER BY p.city sorts the NULL city last
+    assert!(city_col_min.is_null(4)); // David city NULL
+    assert!(city_col_max.is_null(4));
+    assert_eq!(min_col_min.value(4), 40);
+    assert_eq!(min_col_max.value(4), 40);
 }
 
 // ============================================================================
@@ -3403,29 +5025,265 @@ async fn test_datafusion_mixed_connected_and_disconnected() {
     assert_eq!(result.num_rows(), 5);
     assert_eq!(result.num_columns(), 3);
 
-    let c_names = result
-        .column(2)
+    let c_names = result
+        .column(2)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+
+    // All c.name values should be "Alice" (age 25)
+    for i in 0..result.num_rows() {
+        assert_eq!(c_names.value(i), "Alice");
+    }
+}
+
+#[tokio::test]
+async fn test_datafusion_disconnected_with_distinct() {
+    // Test: Disconnected patterns with DISTINCT
+    // MATCH (a:Person), (b:Person) WHERE a.id < b.id RETURN DISTINCT a.name
+    let config = create_graph_config();
+    let person_batch = create_person_dataset();
+
+    let query =
+        CypherQuery::new("MATCH (a:Person), (b:Person) WHERE a.id < b.id RETURN DISTINCT a.name")
+            .unwrap()
+            .with_config(config);
+
+    let mut datasets = HashMap::new();
+    datasets.insert("Person".to_string(), person_batch);
+
+    let result = query
+        .execute(datasets, Some(ExecutionStrategy::DataFusion))
+        .await
+        .unwrap();
+
+    // IDs: 1,2,3,4,5
+    // Pairs where a.id < b.id: (1,2), (1,3), (1,4), (1,5), (2,3), (2,4), (2,5), (3,4), (3,5), (4,5)
+    // Distinct a names: Alice(1), Bob(2), Charlie(3), David(4)
+    assert_eq!(result.num_rows(), 4);
+    assert_eq!(result.num_columns(), 1);
+
+    let names = result
+        .column(0)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+    let name_set: std::collections::HashSet<String> = (0..result.num_rows())
+        .map(|i| names.value(i).to_string())
+        .collect();
+
+    let expected: std::collections::HashSet<String> = ["Alice", "Bob", "Charlie", "David"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    assert_eq!(name_set, expected);
+}
+
+#[tokio::test]
+async fn test_datafusion_shared_node_variable_join() {
+    // This should join on shared variable 'b' using b.id
+    let config = create_graph_config();
+    let person_batch = create_person_dataset();
+    let knows_batch = create_knows_dataset();
+
+    let query = CypherQuery::new(
+        "MATCH (a:Person)-[:KNOWS]->(b:Person), (b)-[:KNOWS]->(c:Person) \
+         RETURN a.name, b.name, c.name ORDER BY a.name, c.name",
+    )
+    .unwrap()
+    .with_config(config);
+
+    let mut datasets = HashMap::new();
+    datasets.insert("Person".to_string(), person_batch);
+    datasets.insert("KNOWS".to_string(), knows_batch);
+
+    let result = query
+        .execute(datasets, Some(ExecutionStrategy::DataFusion))
+        .await
+        .unwrap();
+
+    // This is a two-hop path query that should use join key inference on 'b'
+    // Alice(1) -> Bob(2) -> Charlie(3)
+    // Alice(1) -> Bob(2) -> David(4)
+    assert!(
+        result.num_rows() >= 2,
+        "Should have at least 2 two-hop paths"
+    );
+
+    let a_names = result
+        .column(0)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+    let b_names = result
+        .column(1)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+    let c_names = result
+        .column(2)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+
+    // Verify at least one path: Alice -> Bob -> Charlie
+    let mut found_path = false;
+    for i in 0..result.num_rows() {
+        if a_names.value(i) == "Alice" && b_names.value(i) == "Bob" && c_names.value(i) == "Charlie"
+        {
+            found_path = true;
+            break;
+        }
+    }
+    assert!(found_path, "Should find path: Alice -> Bob -> Charlie");
+}
+
+#[tokio::test]
+async fn test_datafusion_shared_variable_with_filter() {
+    let config = create_graph_config();
+    let person_batch = create_person_dataset();
+    let knows_batch = create_knows_dataset();
+
+    let query = CypherQuery::new(
+        "MATCH (a:Person)-[:KNOWS]->(b:Person), (b)-[:KNOWS]->(c:Person) \
+         WHERE a.age > 20 AND c.age < 40 \
+         RETURN a.name, b.name, c.name",
+    )
+    .unwrap()
+    .with_config(config);
+
+    let mut datasets = HashMap::new();
+    datasets.insert("Person".to_string(), person_batch);
+    datasets.insert("KNOWS".to_string(), knows_batch);
+
+    let result = query
+        .execute(datasets, Some(ExecutionStrategy::DataFusion))
+        .await
+        .unwrap();
+
+    // Should successfully execute with join key inference + filters
+    assert!(result.num_rows() > 0, "Should have results with filters");
+
+    // Verify all results satisfy the age constraints
+    // All 'a' nodes should have age > 20 (excludes no one in our dataset)
+    // All 'c' nodes should have age < 40 (excludes David who is 40)
+    for i in 0..result.num_rows() {
+        let c_name = result
+            .column(2)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap()
+            .value(i);
+        assert_ne!(c_name, "David", "David (age 40) should be filtered out");
+    }
+}
+
+#[tokio::test]
+async fn test_datafusion_multiple_shared_variables() {
+    let config = create_graph_config();
+    let person_batch = create_person_dataset();
+    let knows_batch = create_knows_dataset();
+
+    let query = CypherQuery::new(
+        "MATCH (a:Person)-[:KNOWS]->(b:Person), (b)-[:KNOWS]->(c:Person), (c)-[:KNOWS]->(d:Person) \
+         RETURN a.name, b.name, c.name, d.name",
+    )
+    .unwrap()
+    .with_config(config);
+
+    let mut datasets = HashMap::new();
+    datasets.insert("Person".to_string(), person_batch);
+    datasets.insert("KNOWS".to_string(), knows_batch);
+
+    let result = query
+        .execute(datasets, Some(ExecutionStrategy::DataFusion))
+        .await
+        .unwrap();
+
+    // This is a three-hop path query using join key inference on 'b' and 'c'
+    // Should successfully execute (may have 0 or more results depending on data)
+    assert_eq!(result.num_columns(), 4);
+}
+
+#[tokio::test]
+async fn test_datafusion_shared_variable_distinct() {
+    let config = create_graph_config();
+    let person_batch = create_person_dataset();
+    let knows_batch = create_knows_dataset();
+
+    let query = CypherQuery::new(
+        "MATCH (a:Person)-[:KNOWS]->(b:Person), (b)-[:KNOWS]->(c:Person) \
+         RETURN DISTINCT b.name ORDER BY b.name",
+    )
+    .unwrap()
+    .with_config(config);
+
+    let mut datasets = HashMap::new();
+    datasets.insert("Person".to_string(), person_batch);
+    datasets.insert("KNOWS".to_string(), knows_batch);
+
+    let result = query
+        .execute(datasets, Some(ExecutionStrategy::DataFusion))
+        .await
+        .unwrap();
+
+    // Should return distinct intermediate nodes that have both incoming and outgoing KNOWS edges
+    assert!(result.num_rows() > 0, "Should have intermediate nodes");
+    assert_eq!(result.num_columns(), 1);
+
+    let names = result
+        .column(0)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+
+    // Verify no duplicates
+    let name_set: std::collections::HashSet<String> = (0..result.num_rows())
+        .map(|i| names.value(i).to_string())
+        .collect();
+    assert_eq!(
+        name_set.len(),
+        result.num_rows(),
+        "DISTINCT should eliminate duplicates"
+    );
+}
+
+#[tokio::test]
+async fn test_datafusion_is_null_node_property() {
+    let config = create_graph_config();
+    let person_batch = create_person_dataset();
+
+    let query = CypherQuery::new("MATCH (p:Person) WHERE p.city IS NULL RETURN p.name")
+        .unwrap()
+        .with_config(config);
+
+    let mut datasets = HashMap::new();
+    datasets.insert("Person".to_string(), person_batch);
+
+    let result = query
+        .execute(datasets, Some(ExecutionStrategy::DataFusion))
+        .await
+        .unwrap();
+
+    assert_eq!(result.num_rows(), 1);
+    assert_eq!(result.num_columns(), 1);
+
+    let names = result
+        .column(0)
         .as_any()
         .downcast_ref::<StringArray>()
         .unwrap();
-
-    // All c.name values should be "Alice" (age 25)
-    for i in 0..result.num_rows() {
-        assert_eq!(c_names.value(i), "Alice");
-    }
+    assert_eq!(names.value(0), "David");
 }
 
 #[tokio::test]
-async fn test_datafusion_disconnected_with_distinct() {
-    // Test: Disconnected patterns with DISTINCT
-    // MATCH (a:Person), (b:Person) WHERE a.id < b.id RETURN DISTINCT a.name
+async fn test_datafusion_is_not_null_node_property() {
     let config = create_graph_config();
     let person_batch = create_person_dataset();
 
-    let query =
-        CypherQuery::new("MATCH (a:Person), (b:Person) WHERE a.id < b.id RETURN DISTINCT a.name")
-            .unwrap()
-            .with_config(config);
+    let query = CypherQuery::new("MATCH (p:Person) WHERE p.city IS NOT NULL RETURN p.name")
+        .unwrap()
+        .with_config(config);
 
     let mut datasets = HashMap::new();
     datasets.insert("Person".to_string(), person_batch);
@@ -3435,9 +5293,6 @@ async fn test_datafusion_disconnected_with_distinct() {
         .await
         .unwrap();
 
-    // IDs: 1,2,3,4,5
-    // Pairs where a.id < b.id: (1,2), (1,3), (1,4), (1,5), (2,3), (2,4), (2,5), (3,4), (3,5), (4,5)
-    // Distinct a names: Alice(1), Bob(2), Charlie(3), David(4)
     assert_eq!(result.num_rows(), 4);
     assert_eq!(result.num_columns(), 1);
 
@@ -3446,11 +5301,11 @@ async fn test_datafusion_disconnected_with_distinct() {
         .as_any()
         .downcast_ref::<StringArray>()
         .unwrap();
+
     let name_set: std::collections::HashSet<String> = (0..result.num_rows())
         .map(|i| names.value(i).to_string())
         .collect();
-
-    let expected: std::collections::HashSet<String> = ["Alice", "Bob", "Charlie", "David"]
+    let expected: std::collections::HashSet<String> = ["Alice", "Bob", "Charlie", "Eve"]
         .iter()
         .map(|s| s.to_string())
         .collect();
@@ -3458,15 +5313,15 @@ async fn test_datafusion_disconnected_with_distinct() {
 }
 
 #[tokio::test]
-async fn test_datafusion_shared_node_variable_join() {
-    // This should join on shared variable 'b' using b.id
+async fn test_datafusion_is_null_relationship_property() {
     let config = create_graph_config();
     let person_batch = create_person_dataset();
     let knows_batch = create_knows_dataset();
 
     let query = CypherQuery::new(
-        "MATCH (a:Person)-[:KNOWS]->(b:Person), (b)-[:KNOWS]->(c:Person) \
-         RETURN a.name, b.name, c.name ORDER BY a.name, c.name",
+        "MATCH (a:Person)-[r:KNOWS]->(b:Person) \
+         WHERE r.since_year IS NULL \
+         RETURN a.name, b.name",
     )
     .unwrap()
     .with_config(config);
@@ -3480,13 +5335,8 @@ async fn test_datafusion_shared_node_variable_join() {
         .await
         .unwrap();
 
-    // This is a two-hop path query that should use join key inference on 'b'
-    // Alice(1) -> Bob(2) -> Charlie(3)
-    // Alice(1) -> Bob(2) -> David(4)
-    assert!(
-        result.num_rows() >= 2,
-        "Should have at least 2 two-hop paths"
-    );
+    assert_eq!(result.num_rows(), 1);
+    assert_eq!(result.num_columns(), 2);
 
     let a_names = result
         .column(0)
@@ -3498,34 +5348,21 @@ async fn test_datafusion_shared_node_variable_join() {
         .as_any()
         .downcast_ref::<StringArray>()
         .unwrap();
-    let c_names = result
-        .column(2)
-        .as_any()
-        .downcast_ref::<StringArray>()
-        .unwrap();
 
-    // Verify at least one path: Alice -> Bob -> Charlie
-    let mut found_path = false;
-    for i in 0..result.num_rows() {
-        if a_names.value(i) == "Alice" && b_names.value(i) == "Bob" && c_names.value(i) == "Charlie"
-        {
-            found_path = true;
-            break;
-        }
-    }
-    assert!(found_path, "Should find path: Alice -> Bob -> Charlie");
+    assert_eq!(a_names.value(0), "David");
+    assert_eq!(b_names.value(0), "Eve");
 }
 
 #[tokio::test]
-async fn test_datafusion_shared_variable_with_filter() {
+async fn test_datafusion_is_not_null_relationship_property() {
     let config = create_graph_config();
     let person_batch = create_person_dataset();
     let knows_batch = create_knows_dataset();
 
     let query = CypherQuery::new(
-        "MATCH (a:Person)-[:KNOWS]->(b:Person), (b)-[:KNOWS]->(c:Person) \
-         WHERE a.age > 20 AND c.age < 40 \
-         RETURN a.name, b.name, c.name",
+        "MATCH (a:Person)-[r:KNOWS]->(b:Person) \
+         WHERE r.since_year IS NOT NULL \
+         RETURN a.name, b.name",
     )
     .unwrap()
     .with_config(config);
@@ -3539,240 +5376,935 @@ async fn test_datafusion_shared_variable_with_filter() {
         .await
         .unwrap();
 
-    // Should successfully execute with join key inference + filters
-    assert!(result.num_rows() > 0, "Should have results with filters");
+    assert_eq!(result.num_rows(), 4);
+
+    let a_names = result
+        .column(0)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+    let b_names = result
+        .column(1)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
 
-    // Verify all results satisfy the age constraints
-    // All 'a' nodes should have age > 20 (excludes no one in our dataset)
-    // All 'c' nodes should have age < 40 (excludes David who is 40)
     for i in 0..result.num_rows() {
-        let c_name = result
-            .column(2)
-            .as_any()
-            .downcast_ref::<StringArray>()
-            .unwrap()
-            .value(i);
-        assert_ne!(c_name, "David", "David (age 40) should be filtered out");
+        let a = a_names.value(i);
+        let b = b_names.value(i);
+        assert!(
+            !(a == "David" && b == "Eve"),
+            "David -> Eve should be filtered out by IS NOT NULL"
+        );
     }
 }
 
 #[tokio::test]
-async fn test_datafusion_multiple_shared_variables() {
-    let config = create_graph_config();
-    let person_batch = create_person_dataset();
-    let knows_batch = create_knows_dataset();
+async fn test_datafusion_typed_parameters() {
+    use datafusion::scalar::ScalarValue;
+
+    let execute = |query: CypherQuery| async move {
+        let mut datasets = HashMap::new();
+        datasets.insert("Person".to_string(), create_person_dataset());
+        datasets.insert("KNOWS".to_string(), create_knows_dataset());
+        query
+            .with_config(create_graph_config())
+            .execute(datasets, Some(ExecutionStrategy::DataFusion))
+            .await
+    };
+
+    let query =
+        CypherQuery::new("MATCH (p:Person) WHERE p.age > $min RETURN p.name ORDER BY p.name")
+            .unwrap()
+            .with_scalar_parameter("min", ScalarValue::Int32(Some(30)));
+    let out = execute(query).await.unwrap();
+    assert_eq!(get_string_column(&out, 0), vec!["Bob", "David"]);
+
+    // A list parameter supplies the whole IN list
+    let query =
+        CypherQuery::new("MATCH (p:Person) WHERE p.id IN $ids RETURN p.name ORDER BY p.name")
+            .unwrap()
+            .with_list_parameter("ids", Arc::new(Int64Array::from(vec![1, 4])));
+    let out = execute(query).await.unwrap();
+    assert_eq!(get_string_column(&out, 0), vec!["Alice", "David"]);
+
+    let query = CypherQuery::new("MATCH (p:Person) WHERE p.name IN $names RETURN p.id")
+        .unwrap()
+        .with_parameter("names", Vec::<String>::new());
+    let out = execute(query).await.unwrap();
+    assert_eq!(out.num_rows(), 0);
 
     let query = CypherQuery::new(
-        "MATCH (a:Person)-[:KNOWS]->(b:Person), (b)-[:KNOWS]->(c:Person), (c)-[:KNOWS]->(d:Person) \
-         RETURN a.name, b.name, c.name, d.name",
+        "MATCH (p:Person {name: $name})-[:KNOWS]->(f:Person) RETURN f.name ORDER BY f.name",
     )
     .unwrap()
-    .with_config(config);
+    .with_scalar_parameter("name", ScalarValue::Utf8(Some("Alice".to_string())));
+    let out = execute(query).await.unwrap();
+    assert_eq!(get_string_column(&out, 0), vec!["Bob", "Charlie"]);
 
-    let mut datasets = HashMap::new();
-    datasets.insert("Person".to_string(), person_batch);
-    datasets.insert("KNOWS".to_string(), knows_batch);
+    // Parameters whose type cannot be compared with the property are rejected
+    let query = CypherQuery::new("MATCH (p:Person) WHERE p.age = $age RETURN p.name")
+        .unwrap()
+        .with_scalar_parameter("age", ScalarValue::Utf8(Some("thirty".to_string())));
+    let err = execute(query).await.unwrap_err().to_string();
+    assert!(
+        err.contains("Parameter '$age' is bound to a string"),
+        "{}",
+        err
+    );
 
-    let result = query
-        .execute(datasets, Some(ExecutionStrategy::DataFusion))
-        .await
-        .unwrap();
+    let query = CypherQuery::new("MATCH (p:Person) WHERE p.name IN $names RETURN p.id")
+        .unwrap()
+        .with_parameter("names", vec![1, 2]);
+    let err = execute(query).await.unwrap_err().to_string();
+    assert!(err.contains("p.name of type Utf8"), "{}", err);
 
-    // This is a three-hop path query using join key inference on 'b' and 'c'
-    // Should successfully execute (may have 0 or more results depending on data)
-    assert_eq!(result.num_columns(), 4);
+    let query = CypherQuery::new("UNWIND $ids AS id MATCH (p:Person {id: id}) RETURN p.name")
+        .unwrap()
+        .with_parameter("ids", 3);
+    let err = execute(query).await.unwrap_err().to_string();
+    assert!(err.contains("must be a list"), "{}", err);
 }
 
 #[tokio::test]
-async fn test_datafusion_shared_variable_distinct() {
-    let config = create_graph_config();
-    let person_batch = create_person_dataset();
-    let knows_batch = create_knows_dataset();
+async fn test_datafusion_named_paths() {
+    let lengths = |batch: &RecordBatch, column: usize| -> Vec<i64> {
+        batch
+            .column(column)
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .unwrap()
+            .values()
+            .to_vec()
+    };
+    let lists = |batch: &RecordBatch, column: usize| -> Vec<Vec<Option<i64>>> {
+        let lists = batch
+            .column(column)
+            .as_any()
+            .downcast_ref::<ListArray>()
+            .unwrap();
+        (0..lists.len())
+            .map(|i| {
+                let values = lists.value(i);
+                let values = values.as_any().downcast_ref::<Int64Array>().unwrap();
+                values.iter().collect()
+            })
+            .collect()
+    };
 
-    let query = CypherQuery::new(
-        "MATCH (a:Person)-[:KNOWS]->(b:Person), (b)-[:KNOWS]->(c:Person) \
-         RETURN DISTINCT b.name ORDER BY b.name",
+    let out = execute_test_query(
+        "MATCH p = (a:Person {name: 'Alice'})-[:KNOWS*1..3]->(b:Person) \
+         RETURN b.name, nodes(p) AS path, length(p) AS hops ORDER BY hops, b.name",
     )
-    .unwrap()
-    .with_config(config);
+    .await;
+    assert_eq!(
+        get_string_column(&out, 0),
+        vec!["Bob", "Charlie", "Charlie", "David", "David", "Eve"]
+    );
+    assert_eq!(
+        lists(&out, 1),
+        vec![
+            vec![Some(1), Some(2)],
+            vec![Some(1), Some(3)],
+            vec![Some(1), Some(2), Some(3)],
+            vec![Some(1), Some(3), Some(4)],
+            vec![Some(1), Some(2), Some(3), Some(4)],
+            vec![Some(1), Some(3), Some(4), Some(5)],
+        ]
+    );
+    assert_eq!(lengths(&out, 2), vec![1, 1, 2, 2, 3, 3]);
 
-    let mut datasets = HashMap::new();
-    datasets.insert("Person".to_string(), person_batch);
-    datasets.insert("KNOWS".to_string(), knows_batch);
+    // Fixed and variable-length hops combine; relationships keep their properties
+    let out = execute_test_query(
+        "MATCH p = (a:Person {name: 'Alice'})-[:KNOWS]->(b:Person)-[:KNOWS*1..2]->(c:Person) \
+         WHERE c.name = 'Eve' \
+         RETURN nodes(p) AS path, [r IN relationships(p) | r.since_year] AS since",
+    )
+    .await;
+    assert_eq!(
+        lists(&out, 0),
+        vec![vec![Some(1), Some(3), Some(4), Some(5)]]
+    );
+    assert_eq!(lists(&out, 1), vec![vec![Some(2018), Some(2021), None]]);
 
-    let result = query
-        .execute(datasets, Some(ExecutionStrategy::DataFusion))
-        .await
-        .unwrap();
+    // Path variables carry through WITH
+    let out = execute_test_query(
+        "MATCH p = (a:Person)-[:KNOWS]->(b:Person)-[:KNOWS]->(c:Person) \
+         WITH p, a WHERE length(p) = 2 RETURN a.name, nodes(p) AS path ORDER BY path",
+    )
+    .await;
+    assert_eq!(
+        get_string_column(&out, 0),
+        vec!["Alice", "Alice", "Bob", "Charlie"]
+    );
+    assert_eq!(
+        lists(&out, 1),
+        vec![
+            vec![Some(1), Some(2), Some(3)],
+            vec![Some(1), Some(3), Some(4)],
+            vec![Some(2), Some(3), Some(4)],
+            vec![Some(3), Some(4), Some(5)],
+        ]
+    );
 
-    // Should return distinct intermediate nodes that have both incoming and outgoing KNOWS edges
-    assert!(result.num_rows() > 0, "Should have intermediate nodes");
-    assert_eq!(result.num_columns(), 1);
+    // Shortest paths expose their relationships too
+    let out = execute_test_query(
+        "MATCH p = shortestPath((a:Person {name: 'Alice'})-[:KNOWS*]->(b:Person {name: 'David'})) \
+         RETURN [r IN relationships(p) | r.since_year] AS since",
+    )
+    .await;
+    assert_eq!(lists(&out, 0), vec![vec![Some(2018), Some(2021)]]);
+}
 
-    let names = result
+#[tokio::test]
+async fn test_datafusion_string_functions() {
+    let out = execute_test_query(
+        "MATCH (p:Person) WHERE p.name STARTS WITH 'A' OR p.name ENDS WITH 've' \
+         RETURN p.name ORDER BY p.name",
+    )
+    .await;
+    assert_eq!(get_string_column(&out, 0), vec!["Alice", "Eve"]);
+
+    let out = execute_test_query(
+        "MATCH (p:Person) WHERE p.name CONTAINS 'li' RETURN p.name ORDER BY p.name",
+    )
+    .await;
+    assert_eq!(get_string_column(&out, 0), vec!["Alice", "Charlie"]);
+
+    let out = execute_test_query(
+        "MATCH (p:Person) WHERE p.name = 'Charlie' \
+         RETURN toUpper(p.name) AS upper, toLower(p.name) AS lower, \
+         substring(p.name, 1, 3) AS middle, substring(p.name, 4) AS tail, \
+         replace(p.name, 'e', 'E') AS replaced, trim('  x ') AS trimmed, \
+         left(p.name, 2) AS head, right(p.name, 2) AS last",
+    )
+    .await;
+    let expected = [
+        "CHARLIE", "charlie", "har", "lie", "CharliE", "x", "Ch", "ie",
+    ];
+    for (column, expected) in expected.iter().enumerate() {
+        let values = arrow::compute::cast(out.column(column), &DataType::Utf8).unwrap();
+        let values = values.as_any().downcast_ref::<StringArray>().unwrap();
+        assert_eq!(values.value(0), *expected, "column {}", column);
+    }
+
+    // Functions compose with the string operators
+    let out = execute_test_query(
+        "MATCH (p:Person) WHERE toLower(p.city) STARTS WITH 'new' RETURN p.name",
+    )
+    .await;
+    assert_eq!(get_string_column(&out, 0), vec!["Alice"]);
+
+    let out = execute_test_query(
+        "MATCH (p:Person) WHERE p.name = 'David' RETURN split(p.name, 'a') AS parts",
+    )
+    .await;
+    let parts = out
         .column(0)
         .as_any()
-        .downcast_ref::<StringArray>()
+        .downcast_ref::<ListArray>()
+        .unwrap()
+        .value(0);
+    let parts = parts.as_any().downcast_ref::<StringArray>().unwrap();
+    assert_eq!(
+        parts.iter().collect::<Vec<_>>(),
+        vec![Some("D"), Some("vid")]
+    );
+}
+
+#[tokio::test]
+async fn test_datafusion_regex_match() {
+    // The pattern has to match the whole string
+    let out =
+        execute_test_query("MATCH (p:Person) WHERE p.name =~ 'A.*' RETURN p.name ORDER BY p.name")
+            .await;
+    assert_eq!(get_string_column(&out, 0), vec!["Alice"]);
+
+    let out =
+        execute_test_query("MATCH (p:Person) WHERE p.name =~ 'li' RETURN p.name ORDER BY p.name")
+            .await;
+    assert_eq!(out.num_rows(), 0);
+
+    let out = execute_test_query(
+        "MATCH (p:Person) WHERE p.name =~ '.*li.*|Bob' RETURN p.name ORDER BY p.name",
+    )
+    .await;
+    assert_eq!(get_string_column(&out, 0), vec!["Alice", "Bob", "Charlie"]);
+
+    // Inline flags make the match case-insensitive
+    let out = execute_test_query(
+        "MATCH (p:Person) WHERE p.name =~ '(?i)[a-d].*e' RETURN p.name ORDER BY p.name",
+    )
+    .await;
+    assert_eq!(get_string_column(&out, 0), vec!["Alice", "Charlie"]);
+
+    // NULL properties never match
+    let out = execute_test_query(
+        "MATCH (p:Person) WHERE NOT p.city =~ 'New.*' RETURN p.name ORDER BY p.name",
+    )
+    .await;
+    assert!(!get_string_column(&out, 0).contains(&"David".to_string()));
+}
+
+#[tokio::test]
+async fn test_datafusion_arithmetic() {
+    let out = execute_test_query(
+        "MATCH (p:Person) WHERE p.age * 2 > 60 \
+         RETURN p.name, p.age + 1 - 2 * (p.id % 3) AS score ORDER BY p.name",
+    )
+    .await;
+    assert_eq!(get_string_column(&out, 0), vec!["Bob", "David"]);
+    let scores = out.column(1).as_any().downcast_ref::<Int64Array>().unwrap();
+    assert_eq!(scores.values(), &[32, 39]);
+}
+
+#[tokio::test]
+async fn test_datafusion_temporal_functions() {
+    // 2019-12-31T01:00, 2020-01-15T00:00 and an event without a day on 2020-03-01
+    const DAY_MICROS: i64 = 86_400_000_000;
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int64, false),
+        Field::new("name", DataType::Utf8, false),
+        Field::new("day", DataType::Date32, true),
+        Field::new(
+            "at",
+            DataType::Timestamp(TimeUnit::Microsecond, None),
+            false,
+        ),
+    ]));
+    let events = RecordBatch::try_new(
+        schema,
+        vec![
+            Arc::new(Int64Array::from(vec![1, 2, 3])),
+            Arc::new(StringArray::from(vec!["launch", "review", "release"])),
+            Arc::new(Date32Array::from(vec![Some(18261), Some(18276), None])),
+            Arc::new(TimestampMicrosecondArray::from(vec![
+                18261 * DAY_MICROS + 3_600_000_000,
+                18276 * DAY_MICROS,
+                18322 * DAY_MICROS,
+            ])),
+        ],
+    )
+    .unwrap();
+    let config = GraphConfig::builder()
+        .with_node_label("Event", "id")
+        .build()
         .unwrap();
+    let run = |cypher: &'static str| {
+        let config = config.clone();
+        let events = events.clone();
+        async move {
+            let mut datasets = HashMap::new();
+            datasets.insert("Event".to_string(), events);
+            CypherQuery::new(cypher)
+                .unwrap()
+                .with_config(config)
+                .execute(datasets, Some(ExecutionStrategy::DataFusion))
+                .await
+                .unwrap()
+        }
+    };
+    let strings = |batch: &RecordBatch, column: usize| -> Vec<Option<String>> {
+        let values = arrow::compute::cast(batch.column(column), &DataType::Utf8).unwrap();
+        let values = values.as_any().downcast_ref::<StringArray>().unwrap();
+        values.iter().map(|v| v.map(str::to_string)).collect()
+    };
+
+    let out = run("MATCH (e:Event) WHERE e.day > date('2020-01-01') RETURN e.name").await;
+    assert_eq!(get_string_column(&out, 0), vec!["review"]);
+
+    let out = run(
+        "MATCH (e:Event) WHERE e.at >= localdatetime('2020-01-15T00:00:00') \
+         RETURN e.name ORDER BY e.name",
+    )
+    .await;
+    assert_eq!(get_string_column(&out, 0), vec!["release", "review"]);
+
+    // datetime() values are in UTC and compare with timestamps without a time zone
+    let out =
+        run("MATCH (e:Event) WHERE e.at < datetime('2020-01-15T02:00:00+02:00') RETURN e.name")
+            .await;
+    assert_eq!(get_string_column(&out, 0), vec!["launch"]);
+
+    // Arithmetic with durations, which may count months, days and time
+    let out = run("MATCH (e:Event) \
+         RETURN e.day + duration('P1M2D') AS later, e.at - duration({hours: 2}) AS earlier \
+         ORDER BY e.id")
+    .await;
+    assert_eq!(
+        strings(&out, 0),
+        vec![
+            Some("2020-02-02".to_string()),
+            Some("2020-02-17".to_string()),
+            None
+        ]
+    );
+    assert_eq!(
+        strings(&out, 1),
+        vec![
+            Some("2019-12-30T23:00:00".to_string()),
+            Some("2020-01-14T22:00:00".to_string()),
+            Some("2020-02-29T22:00:00".to_string()),
+        ]
+    );
 
-    // Verify no duplicates
-    let name_set: std::collections::HashSet<String> = (0..result.num_rows())
-        .map(|i| names.value(i).to_string())
-        .collect();
+    let out =
+        run("MATCH (e:Event) WHERE e.day + duration('P14D') < date('2020-01-20') RETURN e.name")
+            .await;
+    assert_eq!(get_string_column(&out, 0), vec!["launch"]);
+
+    let out = run(
+        "MATCH (e:Event) WHERE duration.between(e.day, date('2020-01-17')) = duration('PT48H') \
+         RETURN e.name, date({year: 2020, month: 2, day: 29}) AS leap, \
+         localdatetime({year: 2020, month: 2, day: 29, hour: 13, minute: 5, millisecond: 250}) \
+         AS moment",
+    )
+    .await;
+    assert_eq!(get_string_column(&out, 0), vec!["review"]);
+    assert_eq!(strings(&out, 1), vec![Some("2020-02-29".to_string())]);
     assert_eq!(
-        name_set.len(),
-        result.num_rows(),
-        "DISTINCT should eliminate duplicates"
+        strings(&out, 2),
+        vec![Some("2020-02-29T13:05:00.250".to_string())]
     );
 }
 
 #[tokio::test]
-async fn test_datafusion_is_null_node_property() {
-    let config = create_graph_config();
-    let person_batch = create_person_dataset();
+async fn test_datafusion_map_projection() {
+    let out = execute_test_query(
+        "MATCH (p:Person)-[r:KNOWS]->(f:Person) WHERE p.name = 'Alice' \
+         RETURN p { .name, .age, friend: f.name, since: r.since_year } AS person \
+         ORDER BY f.name",
+    )
+    .await;
+    let person = out
+        .column(0)
+        .as_any()
+        .downcast_ref::<arrow_array::StructArray>()
+        .unwrap();
+    let field_names: Vec<_> = person.fields().iter().map(|f| f.name().as_str()).collect();
+    assert_eq!(field_names, vec!["name", "age", "friend", "since"]);
+    let strings = |name: &str| -> Vec<String> {
+        let values = person.column_by_name(name).unwrap();
+        let values = values.as_any().downcast_ref::<StringArray>().unwrap();
+        values.iter().map(|v| v.unwrap().to_string()).collect()
+    };
+    let ints = |name: &str| -> Vec<i64> {
+        let values = person.column_by_name(name).unwrap();
+        values
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .unwrap()
+            .values()
+            .to_vec()
+    };
+    assert_eq!(strings("name"), vec!["Alice", "Alice"]);
+    assert_eq!(ints("age"), vec![25, 25]);
+    assert_eq!(strings("friend"), vec!["Bob", "Charlie"]);
+    assert_eq!(ints("since"), vec![2020, 2018]);
+
+    // Projections nest and carry through WITH
+    let out = execute_test_query(
+        "MATCH (p:Person) \
+         WITH p { .name, details: { age: p.age, city: p.city } } AS person, p.age AS age \
+         RETURN person ORDER BY age LIMIT 1",
+    )
+    .await;
+    let person = out
+        .column(0)
+        .as_any()
+        .downcast_ref::<arrow_array::StructArray>()
+        .unwrap();
+    let name = person.column_by_name("name").unwrap();
+    let name = name.as_any().downcast_ref::<StringArray>().unwrap();
+    assert_eq!(name.value(0), "Alice");
+    let details = person.column_by_name("details").unwrap();
+    let details = details
+        .as_any()
+        .downcast_ref::<arrow_array::StructArray>()
+        .unwrap();
+    let city = details.column_by_name("city").unwrap();
+    let city = city.as_any().downcast_ref::<StringArray>().unwrap();
+    assert_eq!(city.value(0), "New York");
+}
 
-    let query = CypherQuery::new("MATCH (p:Person) WHERE p.city IS NULL RETURN p.name")
-        .unwrap()
-        .with_config(config);
+/// `test.range(start, end = 3)`: the integers from `start` to `end`, as `value`
+#[derive(Debug)]
+struct RangeProcedure;
 
-    let mut datasets = HashMap::new();
-    datasets.insert("Person".to_string(), person_batch);
+#[async_trait::async_trait]
+impl lance_graph::procedures::Procedure for RangeProcedure {
+    fn name(&self) -> &str {
+        "test.range"
+    }
 
-    let result = query
-        .execute(datasets, Some(ExecutionStrategy::DataFusion))
-        .await
-        .unwrap();
+    fn parameters(&self) -> Vec<lance_graph::procedures::ProcedureParameter> {
+        use lance_graph::procedures::ProcedureParameter;
+        vec![
+            ProcedureParameter::new("start", DataType::Int64),
+            ProcedureParameter::new("end", DataType::Int64)
+                .with_default(datafusion::scalar::ScalarValue::Int64(Some(3))),
+        ]
+    }
 
-    assert_eq!(result.num_rows(), 1);
-    assert_eq!(result.num_columns(), 1);
+    fn output_schema(
+        &self,
+        _ctx: &lance_graph::procedures::ProcedureContext,
+    ) -> lance_graph::Result<arrow_schema::SchemaRef> {
+        Ok(Arc::new(Schema::new(vec![Field::new(
+            "value",
+            DataType::Int64,
+            false,
+        )])))
+    }
 
-    let names = result
-        .column(0)
-        .as_any()
-        .downcast_ref::<StringArray>()
-        .unwrap();
-    assert_eq!(names.value(0), "David");
+    async fn call(
+        &self,
+        ctx: &lance_graph::procedures::ProcedureContext,
+        args: Vec<datafusion::scalar::ScalarValue>,
+    ) -> lance_graph::Result<RecordBatch> {
+        use datafusion::scalar::ScalarValue;
+        let (ScalarValue::Int64(Some(start)), ScalarValue::Int64(Some(end))) = (&args[0], &args[1])
+        else {
+            panic!("Expected integer arguments, got {:?}", args);
+        };
+        let values = Int64Array::from((*start..=*end).collect::<Vec<_>>());
+        Ok(RecordBatch::try_new(self.output_schema(ctx)?, vec![Arc::new(values)]).unwrap())
+    }
 }
 
 #[tokio::test]
-async fn test_datafusion_is_not_null_node_property() {
-    let config = create_graph_config();
-    let person_batch = create_person_dataset();
-
-    let query = CypherQuery::new("MATCH (p:Person) WHERE p.city IS NOT NULL RETURN p.name")
-        .unwrap()
-        .with_config(config);
-
+async fn test_datafusion_call_procedures() {
     let mut datasets = HashMap::new();
-    datasets.insert("Person".to_string(), person_batch);
+    datasets.insert("Person".to_string(), create_person_dataset());
+    datasets.insert("KNOWS".to_string(), create_knows_dataset());
+    let run = |cypher: &str| {
+        let query = CypherQuery::new(cypher)
+            .unwrap()
+            .with_config(create_graph_config())
+            .with_procedure(Arc::new(RangeProcedure))
+            .with_parameter("from", 2);
+        let datasets = datasets.clone();
+        async move {
+            query
+                .execute(datasets, Some(ExecutionStrategy::DataFusion))
+                .await
+        }
+    };
 
-    let result = query
-        .execute(datasets, Some(ExecutionStrategy::DataFusion))
+    // A CALL standing alone returns every column of the procedure
+    let result = run("CALL db.labels()").await.unwrap();
+    assert_eq!(result.schema().field(0).name(), "label");
+    assert_eq!(get_string_column(&result, 0), vec!["Person"]);
+
+    let result = run("CALL db.relationshipTypes() YIELD relationshipType AS type RETURN type")
         .await
         .unwrap();
+    assert_eq!(get_string_column(&result, 0), vec!["KNOWS"]);
 
-    assert_eq!(result.num_rows(), 4);
-    assert_eq!(result.num_columns(), 1);
-
-    let names = result
-        .column(0)
+    // Yielded rows are joined to the matched ones; the end argument takes its default
+    let result = run(
+        "MATCH (p:Person {name: 'David'}) CALL test.range(1) YIELD value \
+         RETURN p.name, value ORDER BY value",
+    )
+    .await
+    .unwrap();
+    assert_eq!(get_string_column(&result, 0), vec!["David"; 3]);
+    let values = result
+        .column(1)
         .as_any()
-        .downcast_ref::<StringArray>()
+        .downcast_ref::<Int64Array>()
         .unwrap();
+    assert_eq!(values.values(), &[1, 2, 3]);
 
-    let name_set: std::collections::HashSet<String> = (0..result.num_rows())
-        .map(|i| names.value(i).to_string())
-        .collect();
-    let expected: std::collections::HashSet<String> = ["Alice", "Bob", "Charlie", "Eve"]
-        .iter()
-        .map(|s| s.to_string())
-        .collect();
-    assert_eq!(name_set, expected);
+    // Arguments can be parameters, and WHERE filters what is yielded
+    let result = run("CALL test.range($from, 5) YIELD value AS v WHERE v <> 4 \
+         MATCH (p:Person) WHERE p.age = v * 10 RETURN p.name, v ORDER BY v")
+    .await
+    .unwrap();
+    assert_eq!(get_string_column(&result, 0), vec!["Charlie"]);
+
+    for (cypher, message) in [
+        ("CALL db.missing()", "Unknown procedure 'db.missing'"),
+        ("CALL test.range()", "takes 1 to 2 argument(s), got 0"),
+        ("CALL db.labels() YIELD name", "yields no column 'name'"),
+        (
+            "MATCH (p:Person) CALL db.labels() RETURN p.name",
+            "must YIELD the columns it binds",
+        ),
+        (
+            "MATCH (p:Person) CALL test.range(p.age) YIELD value RETURN value",
+            "arguments referencing variables",
+        ),
+    ] {
+        let error = run(cypher).await.unwrap_err().to_string();
+        assert!(error.contains(message), "{}: {}", cypher, error);
+    }
 }
 
 #[tokio::test]
-async fn test_datafusion_is_null_relationship_property() {
-    let config = create_graph_config();
-    let person_batch = create_person_dataset();
-    let knows_batch = create_knows_dataset();
+async fn test_datafusion_relationship_type_alternatives() {
+    let likes = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("liker_id", DataType::Int64, false),
+            Field::new("liked_id", DataType::Int64, false),
+            Field::new("weight", DataType::Int64, true),
+        ])),
+        vec![
+            Arc::new(Int64Array::from(vec![1, 5])),
+            Arc::new(Int64Array::from(vec![4, 1])),
+            Arc::new(Int64Array::from(vec![7, 9])),
+        ],
+    )
+    .unwrap();
+    let config = GraphConfig::builder()
+        .with_node_label("Person", "id")
+        .with_relationship("KNOWS", "src_person_id", "dst_person_id")
+        .with_relationship("LIKES", "liker_id", "liked_id")
+        .build()
+        .unwrap();
+    let mut datasets = HashMap::new();
+    datasets.insert("Person".to_string(), create_person_dataset());
+    datasets.insert("KNOWS".to_string(), create_knows_dataset());
+    datasets.insert("LIKES".to_string(), likes);
+    let run = |cypher: &str| {
+        let query = CypherQuery::new(cypher)
+            .unwrap()
+            .with_config(config.clone());
+        let datasets = datasets.clone();
+        async move {
+            query
+                .execute(datasets, Some(ExecutionStrategy::DataFusion))
+                .await
+                .unwrap()
+        }
+    };
 
-    let query = CypherQuery::new(
-        "MATCH (a:Person)-[r:KNOWS]->(b:Person) \
-         WHERE r.since_year IS NULL \
-         RETURN a.name, b.name",
+    // Properties only one of the types has are NULL on the other's rows
+    let result = run(
+        "MATCH (a:Person {name: 'Alice'})-[r:KNOWS|LIKES]->(b:Person) \
+         RETURN b.name, r.since_year, r.weight ORDER BY b.name",
     )
-    .unwrap()
-    .with_config(config);
+    .await;
+    assert_eq!(
+        get_string_column(&result, 0),
+        vec!["Bob", "Charlie", "David"]
+    );
+    let since = result
+        .column(1)
+        .as_any()
+        .downcast_ref::<Int64Array>()
+        .unwrap();
+    let weight = result
+        .column(2)
+        .as_any()
+        .downcast_ref::<Int64Array>()
+        .unwrap();
+    assert_eq!(
+        (0..3).map(|i| since.is_valid(i)).collect::<Vec<_>>(),
+        vec![true, true, false]
+    );
+    assert_eq!(
+        (0..3).map(|i| weight.is_valid(i)).collect::<Vec<_>>(),
+        vec![false, false, true]
+    );
+    assert_eq!(weight.value(2), 7);
 
-    let mut datasets = HashMap::new();
-    datasets.insert("Person".to_string(), person_batch);
-    datasets.insert("KNOWS".to_string(), knows_batch);
+    let result = run(
+        "MATCH (a:Person)<-[:KNOWS|:LIKES]-(b:Person) WHERE a.name = 'Alice' \
+         RETURN b.name",
+    )
+    .await;
+    assert_eq!(get_string_column(&result, 0), vec!["Eve"]);
 
-    let result = query
-        .execute(datasets, Some(ExecutionStrategy::DataFusion))
-        .await
+    // Each hop of a variable-length path may use any of the types
+    let result = run(
+        "MATCH (a:Person {name: 'Eve'})-[:KNOWS|LIKES*1..2]->(b:Person) \
+         RETURN b.name ORDER BY b.name",
+    )
+    .await;
+    assert_eq!(
+        get_string_column(&result, 0),
+        vec!["Alice", "Bob", "Charlie", "David"]
+    );
+}
+
+#[tokio::test]
+async fn test_datafusion_label_expressions() {
+    let banned = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![Field::new(
+            "person_id",
+            DataType::Int64,
+            false,
+        )])),
+        vec![Arc::new(Int64Array::from(vec![2, 4]))],
+    )
+    .unwrap();
+    let config = GraphConfig::builder()
+        .with_node_label("Person", "id")
+        .with_node_label("Banned", "person_id")
+        .with_relationship("KNOWS", "src_person_id", "dst_person_id")
+        .build()
         .unwrap();
+    let mut datasets = HashMap::new();
+    datasets.insert("Person".to_string(), create_person_dataset());
+    datasets.insert("KNOWS".to_string(), create_knows_dataset());
+    datasets.insert("Banned".to_string(), banned);
+    let run = |cypher: &str| {
+        let query = CypherQuery::new(cypher)
+            .unwrap()
+            .with_config(config.clone());
+        let datasets = datasets.clone();
+        async move {
+            query
+                .execute(datasets, Some(ExecutionStrategy::DataFusion))
+                .await
+        }
+    };
+
+    // A node has another label when that label's table holds its id
+    for (cypher, expected) in [
+        (
+            "MATCH (n:Person&!Banned) RETURN n.name ORDER BY n.name",
+            vec!["Alice", "Charlie", "Eve"],
+        ),
+        (
+            "MATCH (n:Person:Banned) RETURN n.name ORDER BY n.name",
+            vec!["Bob", "David"],
+        ),
+        (
+            "MATCH (n:Person) WHERE n:Banned OR n.age < 26 RETURN n.name ORDER BY n.name",
+            vec!["Alice", "Bob", "David"],
+        ),
+        (
+            "MATCH (a:Person)-[:KNOWS]->(b:Person&!Banned) WHERE a.name = 'Alice' \
+             RETURN b.name",
+            vec!["Charlie"],
+        ),
+        (
+            "MATCH (n:Person) WITH n WHERE NOT n:Banned AND n:Person|Ghost \
+             RETURN n.name ORDER BY n.name",
+            vec!["Alice", "Charlie", "Eve"],
+        ),
+        ("MATCH (n:Person) WHERE n:Ghost RETURN n.name", vec![]),
+    ] {
+        let result = run(cypher).await.unwrap();
+        assert_eq!(get_string_column(&result, 0), expected, "{}", cypher);
+    }
 
-    assert_eq!(result.num_rows(), 1);
-    assert_eq!(result.num_columns(), 2);
+    for (cypher, message) in [
+        (
+            "MATCH (n:Banned|Person) RETURN n",
+            "without a label every match has",
+        ),
+        (
+            "MATCH (n:Person) RETURN CASE WHEN n:Banned THEN 1 ELSE 0 END AS b",
+            "label predicates inside expressions",
+        ),
+    ] {
+        let error = run(cypher).await.unwrap_err().to_string();
+        assert!(error.contains(message), "{}: {}", cypher, error);
+    }
+}
 
-    let a_names = result
-        .column(0)
-        .as_any()
-        .downcast_ref::<StringArray>()
+#[tokio::test]
+async fn test_datafusion_entity_functions() {
+    use lance_graph::config::NodeMapping;
+
+    let employees = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("employee_id", DataType::Int64, false),
+            Field::new("name", DataType::Utf8, false),
+        ])),
+        vec![
+            Arc::new(Int64Array::from(vec![6])),
+            Arc::new(StringArray::from(vec!["Frank"])),
+        ],
+    )
+    .unwrap();
+    let likes = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("liker_id", DataType::Int64, false),
+            Field::new("liked_id", DataType::Int64, false),
+        ])),
+        vec![
+            Arc::new(Int64Array::from(vec![1])),
+            Arc::new(Int64Array::from(vec![6])),
+        ],
+    )
+    .unwrap();
+    let config = GraphConfig::builder()
+        .with_node_label("Person", "id")
+        .with_node_mapping(NodeMapping::new("Employee", "employee_id").with_extends("Person"))
+        .with_relationship("KNOWS", "src_person_id", "dst_person_id")
+        .with_relationship("LIKES", "liker_id", "liked_id")
+        .build()
         .unwrap();
-    let b_names = result
+    let mut datasets = HashMap::new();
+    datasets.insert("Person".to_string(), create_person_dataset());
+    datasets.insert("Employee".to_string(), employees);
+    datasets.insert("KNOWS".to_string(), create_knows_dataset());
+    datasets.insert("LIKES".to_string(), likes);
+    let run = |cypher: &str| {
+        let query = CypherQuery::new(cypher)
+            .unwrap()
+            .with_config(config.clone());
+        let datasets = datasets.clone();
+        async move {
+            query
+                .execute(datasets, Some(ExecutionStrategy::DataFusion))
+                .await
+                .unwrap()
+        }
+    };
+    let string_list = |batch: &RecordBatch, column: usize, row: usize| -> Vec<String> {
+        let lists = batch
+            .column(column)
+            .as_any()
+            .downcast_ref::<ListArray>()
+            .unwrap();
+        let values = lists.value(row);
+        let values = values.as_any().downcast_ref::<StringArray>().unwrap();
+        values.iter().map(|v| v.unwrap().to_string()).collect()
+    };
+
+    let result = run(
+        "MATCH (a:Person {name: 'Alice'})-[r:KNOWS|LIKES]->(b:Person) \
+         RETURN type(r), id(b), elementId(b), labels(b) ORDER BY id(b)",
+    )
+    .await;
+    assert_eq!(
+        get_string_column(&result, 0),
+        vec!["KNOWS", "KNOWS", "LIKES"]
+    );
+    let ids = result
         .column(1)
         .as_any()
-        .downcast_ref::<StringArray>()
+        .downcast_ref::<Int64Array>()
         .unwrap();
-
-    assert_eq!(a_names.value(0), "David");
-    assert_eq!(b_names.value(0), "Eve");
+    assert_eq!(ids.values(), &[2, 3, 6]);
+    assert_eq!(
+        get_string_column(&result, 2),
+        vec!["Person:2", "Person:3", "Employee:6"]
+    );
+    // A node has its table's label and every label that label extends
+    assert_eq!(string_list(&result, 3, 0), vec!["Person"]);
+    assert_eq!(string_list(&result, 3, 2), vec!["Employee", "Person"]);
+
+    // Element ids are unique across labels and can be matched on
+    let result = run("MATCH (p:Person) WHERE elementId(p) = 'Employee:6' RETURN p.name").await;
+    assert_eq!(get_string_column(&result, 0), vec!["Frank"]);
+
+    // Ids survive WITH and variable-length paths
+    let result = run(
+        "MATCH (a:Person {name: 'Alice'})-[:KNOWS*2..2]->(b:Person) WITH b \
+         RETURN elementId(b) ORDER BY elementId(b)",
+    )
+    .await;
+    assert_eq!(get_string_column(&result, 0), vec!["Person:3", "Person:4"]);
 }
 
 #[tokio::test]
-async fn test_datafusion_is_not_null_relationship_property() {
-    let config = create_graph_config();
-    let person_batch = create_person_dataset();
-    let knows_batch = create_knows_dataset();
+async fn test_datafusion_inline_property_maps() {
+    // A variable-length relationship's map constrains every hop of the path
+    let out = execute_test_query(
+        "MATCH (a:Person)-[:KNOWS*1..2 {since_year: 2020}]->(b:Person) RETURN a.name, b.name",
+    )
+    .await;
+    assert_eq!(get_string_column(&out, 0), vec!["Alice"]);
+    assert_eq!(get_string_column(&out, 1), vec!["Bob"]);
+
+    let out = execute_test_query(
+        "MATCH (a:Person)-[:KNOWS*2..2 {since_year: 2019}]->(b:Person) RETURN b.name",
+    )
+    .await;
+    assert_eq!(out.num_rows(), 0);
+
+    // A map on a variable bound by an earlier clause filters its rows
+    let out =
+        execute_test_query("MATCH (a:Person) WITH a MATCH (a {name: 'Eve'}) RETURN a.age").await;
+    assert_eq!(
+        out.column(0)
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .unwrap()
+            .values(),
+        &[28]
+    );
+
+    let out = execute_test_query(
+        "MATCH (a:Person {name: 'Alice'}) MATCH (a {age: 30})-[:KNOWS]->(b:Person) RETURN b.name",
+    )
+    .await;
+    assert_eq!(out.num_rows(), 0);
 
+    // Parameters in a relationship map
     let query = CypherQuery::new(
-        "MATCH (a:Person)-[r:KNOWS]->(b:Person) \
-         WHERE r.since_year IS NOT NULL \
-         RETURN a.name, b.name",
+        "MATCH (a:Person)-[:KNOWS {since_year: $year}]->(b:Person) RETURN a.name, b.name",
     )
     .unwrap()
-    .with_config(config);
-
+    .with_config(create_graph_config())
+    .with_parameter("year", 2021);
     let mut datasets = HashMap::new();
-    datasets.insert("Person".to_string(), person_batch);
-    datasets.insert("KNOWS".to_string(), knows_batch);
-
-    let result = query
+    datasets.insert("Person".to_string(), create_person_dataset());
+    datasets.insert("KNOWS".to_string(), create_knows_dataset());
+    let out = query
         .execute(datasets, Some(ExecutionStrategy::DataFusion))
         .await
         .unwrap();
+    assert_eq!(get_string_column(&out, 0), vec!["Charlie"]);
+    assert_eq!(get_string_column(&out, 1), vec!["David"]);
+}
 
-    assert_eq!(result.num_rows(), 4);
+#[tokio::test]
+async fn test_datafusion_relationship_uniqueness() {
+    use lance_graph::config::RelationshipUniqueness;
+
+    let run = |cypher: &'static str, uniqueness: RelationshipUniqueness| async move {
+        let config = GraphConfig::builder()
+            .with_node_label("Person", "id")
+            .with_relationship("KNOWS", "src_person_id", "dst_person_id")
+            .with_relationship_uniqueness(uniqueness)
+            .build()
+            .unwrap();
+        let mut datasets = HashMap::new();
+        datasets.insert("Person".to_string(), create_person_dataset());
+        datasets.insert("KNOWS".to_string(), create_knows_dataset());
+        CypherQuery::new(cypher)
+            .unwrap()
+            .with_config(config)
+            .execute(datasets, Some(ExecutionStrategy::DataFusion))
+            .await
+            .unwrap()
+    };
 
-    let a_names = result
-        .column(0)
-        .as_any()
-        .downcast_ref::<StringArray>()
-        .unwrap();
-    let b_names = result
-        .column(1)
-        .as_any()
-        .downcast_ref::<StringArray>()
-        .unwrap();
+    // Alice knows Bob and Charlie; walking back over either relationship reaches Alice
+    let two_hops = "MATCH (a:Person {name: 'Alice'})-[:KNOWS]-(b:Person)-[:KNOWS]-(c:Person) \
+                    RETURN c.name ORDER BY c.name";
+    let out = run(two_hops, RelationshipUniqueness::Homomorphism).await;
+    assert_eq!(
+        get_string_column(&out, 0),
+        vec!["Alice", "Alice", "Bob", "Charlie", "David"]
+    );
+    let out = run(two_hops, RelationshipUniqueness::Isomorphism).await;
+    assert_eq!(get_string_column(&out, 0), vec!["Bob", "Charlie", "David"]);
 
-    for i in 0..result.num_rows() {
-        let a = a_names.value(i);
-        let b = b_names.value(i);
-        assert!(
-            !(a == "David" && b == "Eve"),
-            "David -> Eve should be filtered out by IS NOT NULL"
-        );
-    }
+    // The hops of a variable-length relationship are distinct too
+    let out = run(
+        "MATCH (a:Person {name: 'Alice'})-[:KNOWS*2..2]-(c:Person) RETURN c.name ORDER BY c.name",
+        RelationshipUniqueness::Isomorphism,
+    )
+    .await;
+    assert_eq!(get_string_column(&out, 0), vec!["Bob", "Charlie", "David"]);
+
+    // Separate MATCH clauses may bind the same relationship
+    let out = run(
+        "MATCH (a:Person {name: 'Alice'})-[:KNOWS]-(b:Person) MATCH (b)-[:KNOWS]-(c:Person) \
+         RETURN c.name ORDER BY c.name",
+        RelationshipUniqueness::Isomorphism,
+    )
+    .await;
+    assert_eq!(
+        get_string_column(&out, 0),
+        vec!["Alice", "Alice", "Bob", "Charlie", "David"]
+    );
 }
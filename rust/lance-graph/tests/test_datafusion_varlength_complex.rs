@@ -834,3 +834,69 @@ async fn test_varlength_all_pairs_reachability() {
         "Should find at least 15 connected pairs"
     );
 }
+
+#[tokio::test]
+async fn test_varlength_frontier_expansion() {
+    use lance_graph::config::VariableLengthExpansion;
+
+    let names = |out: &RecordBatch| -> Vec<String> {
+        out.column(0)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap()
+            .iter()
+            .map(|name| name.unwrap().to_string())
+            .collect()
+    };
+    let run = |query: &'static str, expansion: VariableLengthExpansion| async move {
+        let config = GraphConfig::builder()
+            .with_node_label("Person", "id")
+            .with_relationship("KNOWS", "src_person_id", "dst_person_id")
+            .with_variable_length_expansion(expansion)
+            .build()
+            .unwrap();
+        let mut datasets = HashMap::new();
+        datasets.insert("Person".to_string(), create_complex_person_dataset());
+        datasets.insert("KNOWS".to_string(), create_complex_knows_dataset());
+        CypherQuery::new(query)
+            .unwrap()
+            .with_config(config)
+            .execute(datasets, Some(ExecutionStrategy::DataFusion))
+            .await
+            .unwrap()
+    };
+
+    // Diana is two hops from Alice along two paths but reached once
+    let two_hops = "MATCH (a:Person {name: 'Alice'})-[:KNOWS*1..2]->(b:Person) \
+                    RETURN b.name ORDER BY b.name";
+    let out = run(two_hops, VariableLengthExpansion::Frontier).await;
+    assert_eq!(
+        names(&out),
+        vec!["Bob", "Charlie", "Diana", "Eve", "Frank", "Henry"]
+    );
+    let out = run(two_hops, VariableLengthExpansion::Unrolled).await;
+    assert_eq!(out.num_rows(), 7);
+
+    // Unbounded: the cycle through Henry leads back to Alice
+    let out = run(
+        "MATCH (a:Person {name: 'Alice'})-[:KNOWS*]->(b:Person) RETURN b.name ORDER BY b.name",
+        VariableLengthExpansion::Frontier,
+    )
+    .await;
+    assert_eq!(
+        names(&out),
+        vec!["Alice", "Bob", "Charlie", "Diana", "Eve", "Frank", "Grace", "Henry", "Iris", "Jack"]
+    );
+
+    // Reverse lookup: everyone who reaches Jack within three hops
+    let out = run(
+        "MATCH (a:Person)-[:KNOWS*1..3]->(b:Person {name: 'Jack'}) \
+         RETURN a.name ORDER BY a.name",
+        VariableLengthExpansion::Frontier,
+    )
+    .await;
+    assert_eq!(
+        names(&out),
+        vec!["Alice", "Bob", "Charlie", "Diana", "Eve", "Frank", "Grace", "Iris"]
+    );
+}
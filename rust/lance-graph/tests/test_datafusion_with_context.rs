@@ -1,7 +1,35 @@
+use std::sync::Arc;
+
+use arrow_array::{ArrayRef, Int64Array, RecordBatch, StringArray};
+use arrow_schema::{DataType, Field, Schema};
+use datafusion::datasource::{provider_as_source, MemTable};
 use datafusion::execution::context::SessionContext;
+use datafusion::logical_expr::TableSource;
 use lance_graph::config::GraphConfig;
 use lance_graph::query::CypherQuery;
 
+/// An in-memory table of Int64 columns, followed by a `name` column when `names` are
+/// given
+fn table(columns: Vec<(&str, Vec<i64>)>, names: Option<Vec<&str>>) -> Arc<dyn TableSource> {
+    let mut fields: Vec<Field> = columns
+        .iter()
+        .map(|(name, _)| Field::new(*name, DataType::Int64, false))
+        .collect();
+    let mut arrays: Vec<ArrayRef> = columns
+        .into_iter()
+        .map(|(_, values)| Arc::new(Int64Array::from(values)) as _)
+        .collect();
+    if let Some(names) = names {
+        fields.push(Field::new("name", DataType::Utf8, false));
+        arrays.push(Arc::new(StringArray::from(names)));
+    }
+    let schema = Arc::new(Schema::new(fields));
+    let batch = RecordBatch::try_new(schema.clone(), arrays).unwrap();
+    provider_as_source(Arc::new(
+        MemTable::try_new(schema, vec![vec![batch]]).unwrap(),
+    ))
+}
+
 #[tokio::test]
 async fn test_execute_with_context_csv_simple() {
     // Create temporary CSV files for testing
@@ -338,3 +366,780 @@ async fn test_execute_with_context_aliases() {
     assert_eq!(names.value(0), "Alice");
     assert_eq!(names.value(1), "Bob");
 }
+
+#[tokio::test]
+async fn test_execute_with_context_shared_relationship_table() {
+    use lance_graph::config::RelationshipMapping;
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let person_csv_path = temp_dir.path().join("persons.csv");
+    let edges_csv_path = temp_dir.path().join("edges.csv");
+    std::fs::write(
+        &person_csv_path,
+        "id,name\n\
+         1,Alice\n\
+         2,Bob\n\
+         3,Carol\n",
+    )
+    .unwrap();
+    std::fs::write(
+        &edges_csv_path,
+        "src_id,dst_id,kind\n\
+         1,2,KNOWS\n\
+         1,3,LIKES\n\
+         2,3,KNOWS\n",
+    )
+    .unwrap();
+
+    // Both relationship types live in the one table registered as "edges"
+    let shared = |rel_type: &str| {
+        RelationshipMapping::new(rel_type, "src_id", "dst_id")
+            .with_table("edges")
+            .with_type_field("kind")
+    };
+    let config = GraphConfig::builder()
+        .with_node_label("Person", "id")
+        .with_relationship_mapping(shared("KNOWS"))
+        .with_relationship_mapping(shared("LIKES"))
+        .build()
+        .unwrap();
+
+    let ctx = SessionContext::new();
+    ctx.register_csv(
+        "Person",
+        person_csv_path.to_str().unwrap(),
+        Default::default(),
+    )
+    .await
+    .unwrap();
+    ctx.register_csv(
+        "edges",
+        edges_csv_path.to_str().unwrap(),
+        Default::default(),
+    )
+    .await
+    .unwrap();
+
+    let query = CypherQuery::new("MATCH (a:Person)-[:LIKES]->(b:Person) RETURN a.name, b.name")
+        .unwrap()
+        .with_config(config);
+    let result = query.execute_with_context(ctx).await.unwrap();
+
+    assert_eq!(result.num_rows(), 1);
+    let names = |column: usize| {
+        result
+            .column(column)
+            .as_any()
+            .downcast_ref::<arrow_array::StringArray>()
+            .unwrap()
+            .value(0)
+            .to_string()
+    };
+    assert_eq!(names(0), "Alice");
+    assert_eq!(names(1), "Carol");
+}
+
+#[tokio::test]
+async fn test_use_clause_selects_graph_namespace() {
+    use lance_graph::source_catalog::{
+        ChainedCatalog, GraphSourceCatalog, InMemoryCatalog, NamespacedCatalog,
+    };
+
+    let people = |names: Vec<&str>| {
+        let ids: Vec<i64> = (1..=names.len() as i64).collect();
+        table(vec![("id", ids)], Some(names))
+    };
+
+    let catalog = Arc::new(
+        NamespacedCatalog::new()
+            .with_graph(
+                "social",
+                Arc::new(InMemoryCatalog::new().with_node_source("Person", people(vec!["Alice"]))),
+            )
+            .with_graph(
+                "hr",
+                Arc::new(
+                    InMemoryCatalog::new().with_node_source("Person", people(vec!["Bob", "Carol"])),
+                ),
+            ),
+    );
+    let config = GraphConfig::builder()
+        .with_node_label("Person", "id")
+        .build()
+        .unwrap();
+
+    let social = CypherQuery::new("USE social MATCH (p:Person) RETURN p.name")
+        .unwrap()
+        .with_config(config.clone())
+        .execute_with_catalog_and_context(catalog.clone(), SessionContext::new())
+        .await
+        .unwrap();
+    assert_eq!(social.num_rows(), 1);
+
+    let hr = CypherQuery::new("USE hr MATCH (p:Person) RETURN p.name")
+        .unwrap()
+        .with_config(config.clone())
+        .execute_with_catalog_and_context(catalog.clone(), SessionContext::new())
+        .await
+        .unwrap();
+    assert_eq!(hr.num_rows(), 2);
+
+    // Graphs stay reachable through a chain with the namespaced catalog behind another
+    let chained = Arc::new(ChainedCatalog::new(vec![
+        Arc::new(InMemoryCatalog::new().with_node_source("Person", people(vec!["Dan"]))),
+        catalog.clone() as Arc<dyn GraphSourceCatalog>,
+    ]));
+    let hr = CypherQuery::new("USE hr MATCH (p:Person) RETURN p.name")
+        .unwrap()
+        .with_config(config.clone())
+        .execute_with_catalog_and_context(chained, SessionContext::new())
+        .await
+        .unwrap();
+    assert_eq!(hr.num_rows(), 2);
+
+    let missing = CypherQuery::new("USE finance MATCH (p:Person) RETURN p.name")
+        .unwrap()
+        .with_config(config)
+        .execute_with_catalog_and_context(catalog, SessionContext::new())
+        .await;
+    assert!(missing.is_err());
+}
+
+#[tokio::test]
+async fn test_use_clause_selects_graph_config() {
+    use arrow_array::Array;
+    use lance_graph::source_catalog::{InMemoryCatalog, NamespacedCatalog};
+
+    // Both graphs define Person and a relationship, keyed by different columns
+    let social = InMemoryCatalog::new()
+        .with_node_source(
+            "Person",
+            table(vec![("id", vec![1, 2])], Some(vec!["Alice", "Bob"])),
+        )
+        .with_relationship_source(
+            "LINK",
+            table(vec![("src_id", vec![1]), ("dst_id", vec![2])], None),
+        );
+    let hr = InMemoryCatalog::new()
+        .with_node_source(
+            "Person",
+            table(
+                vec![("employee_id", vec![10, 20, 30])],
+                Some(vec!["Carol", "Dan", "Erin"]),
+            ),
+        )
+        .with_relationship_source(
+            "LINK",
+            table(
+                vec![("employee_id", vec![10, 20]), ("manager_id", vec![30, 30])],
+                None,
+            ),
+        );
+    let catalog = Arc::new(
+        NamespacedCatalog::new()
+            .with_graph("social", Arc::new(social))
+            .with_graph("hr", Arc::new(hr))
+            .with_default_graph("social"),
+    );
+    let social_config = GraphConfig::builder()
+        .with_node_label("Person", "id")
+        .with_relationship("LINK", "src_id", "dst_id")
+        .build()
+        .unwrap();
+    let hr_config = GraphConfig::builder()
+        .with_node_label("Person", "employee_id")
+        .with_relationship("LINK", "employee_id", "manager_id")
+        .build()
+        .unwrap();
+
+    let names = |batch: &RecordBatch, column: usize| -> Vec<String> {
+        let values = batch
+            .column(column)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        (0..values.len())
+            .map(|i| values.value(i).to_string())
+            .collect()
+    };
+    let run = |cypher: &str| {
+        let query = CypherQuery::new(cypher)
+            .unwrap()
+            .with_config(social_config.clone())
+            .with_graph_config("hr", hr_config.clone());
+        let catalog = catalog.clone();
+        async move {
+            query
+                .execute_with_catalog_and_context(catalog, SessionContext::new())
+                .await
+                .unwrap()
+        }
+    };
+
+    let hr =
+        run("USE hr MATCH (e:Person)-[:LINK]->(m:Person) RETURN e.name, m.name ORDER BY e.name")
+            .await;
+    assert_eq!(names(&hr, 0), vec!["Carol", "Dan"]);
+    assert_eq!(names(&hr, 1), vec!["Erin", "Erin"]);
+
+    // Queries without USE, or using a graph without a config of its own, keep the default
+    let social = run("MATCH (a:Person)-[:LINK]->(b:Person) RETURN a.name, b.name").await;
+    assert_eq!(names(&social, 0), vec!["Alice"]);
+    assert_eq!(names(&social, 1), vec!["Bob"]);
+    let social = run("USE social MATCH (a:Person)-[:LINK]->(b:Person) RETURN a.name, b.name").await;
+    assert_eq!(names(&social, 1), vec!["Bob"]);
+}
+
+#[tokio::test]
+async fn test_catalog_statistics_do_not_change_results() {
+    use lance_graph::source_catalog::{InMemoryCatalog, SourceStatistics};
+
+    let person = || {
+        table(
+            vec![("id", vec![1, 2, 3])],
+            Some(vec!["Alice", "Bob", "Carol"]),
+        )
+    };
+    let company = || table(vec![("id", vec![10])], Some(vec!["Acme"]));
+    let works_for = || table(vec![("src_id", vec![1, 3]), ("dst_id", vec![10, 10])], None);
+
+    let config = GraphConfig::builder()
+        .with_node_label("Person", "id")
+        .with_node_label("Company", "id")
+        .with_relationship("WORKS_FOR", "src_id", "dst_id")
+        .build()
+        .unwrap();
+    let query = CypherQuery::new(
+        "MATCH (p:Person)-[:WORKS_FOR]->(c:Company) RETURN p.name, c.name ORDER BY p.name",
+    )
+    .unwrap()
+    .with_config(config.clone());
+    // Anchored at the company in the middle, expanding out to both colleagues
+    let colleagues = CypherQuery::new(
+        "MATCH (a:Person)-[:WORKS_FOR]->(:Company)<-[:WORKS_FOR]-(b:Person) \
+         RETURN a.name, b.name ORDER BY a.name, b.name",
+    )
+    .unwrap()
+    .with_config(config);
+
+    let base = InMemoryCatalog::new()
+        .with_node_source("Person", person())
+        .with_node_source("Company", company())
+        .with_relationship_source("WORKS_FOR", works_for());
+    let with_stats = InMemoryCatalog::new()
+        .with_node_source("Person", person())
+        .with_node_source("Company", company())
+        .with_relationship_source("WORKS_FOR", works_for())
+        .with_statistics("Person", SourceStatistics::with_row_count(3))
+        .with_statistics("Company", SourceStatistics::with_row_count(1));
+
+    let (base, with_stats) = (Arc::new(base), Arc::new(with_stats));
+    let plain = query
+        .execute_with_catalog_and_context(base.clone(), SessionContext::new())
+        .await
+        .unwrap();
+    let reordered = query
+        .execute_with_catalog_and_context(with_stats.clone(), SessionContext::new())
+        .await
+        .unwrap();
+
+    assert_eq!(plain.num_rows(), 2);
+    assert_eq!(plain, reordered);
+
+    let plain = colleagues
+        .execute_with_catalog_and_context(base, SessionContext::new())
+        .await
+        .unwrap();
+    let reordered = colleagues
+        .execute_with_catalog_and_context(with_stats, SessionContext::new())
+        .await
+        .unwrap();
+
+    assert_eq!(plain.num_rows(), 4);
+    assert_eq!(plain, reordered);
+}
+
+#[tokio::test]
+async fn test_filtered_view_source_per_label() {
+    use arrow_array::{Int64Array, RecordBatch, StringArray};
+    use arrow_schema::{DataType, Field, Schema};
+    use datafusion::datasource::{provider_as_source, MemTable};
+    use datafusion::logical_expr::{col, lit};
+    use lance_graph::source_catalog::{FilteredTableSource, InMemoryCatalog};
+    use std::sync::Arc;
+
+    // One wide table holding both people and bots
+    let users = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("name", DataType::Utf8, false),
+            Field::new("kind", DataType::Utf8, false),
+        ])),
+        vec![
+            Arc::new(Int64Array::from(vec![1, 2, 3])),
+            Arc::new(StringArray::from(vec!["Alice", "crawler", "Bob"])),
+            Arc::new(StringArray::from(vec!["person", "bot", "person"])),
+        ],
+    )
+    .unwrap();
+    let users_source = provider_as_source(Arc::new(
+        MemTable::try_new(users.schema(), vec![vec![users]]).unwrap(),
+    ));
+
+    let person_view = FilteredTableSource::try_new(
+        "users",
+        users_source,
+        Some(col("kind").eq(lit("person"))),
+        Some(vec!["id".to_string(), "name".to_string()]),
+    )
+    .unwrap();
+    let catalog = InMemoryCatalog::new().with_node_source("Person", Arc::new(person_view));
+
+    let config = GraphConfig::builder()
+        .with_node_label("Person", "id")
+        .build()
+        .unwrap();
+    let result = CypherQuery::new("MATCH (p:Person) RETURN p.name ORDER BY p.name")
+        .unwrap()
+        .with_config(config)
+        .execute_with_catalog_and_context(Arc::new(catalog), SessionContext::new())
+        .await
+        .unwrap();
+
+    let names = result
+        .column(0)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+    assert_eq!(result.num_rows(), 2);
+    assert_eq!(names.value(0), "Alice");
+    assert_eq!(names.value(1), "Bob");
+}
+
+#[tokio::test]
+async fn test_labels_sharing_table_via_label_column() {
+    use arrow_array::{Int64Array, RecordBatch, StringArray};
+    use arrow_schema::{DataType, Field, Schema};
+    use datafusion::datasource::{provider_as_source, MemTable};
+    use lance_graph::config::NodeMapping;
+    use lance_graph::source_catalog::InMemoryCatalog;
+    use std::sync::Arc;
+
+    let users = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("name", DataType::Utf8, false),
+            Field::new("kind", DataType::Utf8, false),
+        ])),
+        vec![
+            Arc::new(Int64Array::from(vec![1, 2, 3])),
+            Arc::new(StringArray::from(vec!["Alice", "crawler", "Bob"])),
+            Arc::new(StringArray::from(vec!["person", "bot", "person"])),
+        ],
+    )
+    .unwrap();
+    let users_source = provider_as_source(Arc::new(
+        MemTable::try_new(users.schema(), vec![vec![users]]).unwrap(),
+    ));
+
+    let follows = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("src_id", DataType::Int64, false),
+            Field::new("dst_id", DataType::Int64, false),
+        ])),
+        vec![
+            Arc::new(Int64Array::from(vec![1, 1, 3])),
+            Arc::new(Int64Array::from(vec![2, 3, 2])),
+        ],
+    )
+    .unwrap();
+    let follows_source = provider_as_source(Arc::new(
+        MemTable::try_new(follows.schema(), vec![vec![follows]]).unwrap(),
+    ));
+
+    let catalog = Arc::new(
+        InMemoryCatalog::new()
+            .with_node_source("Person", users_source.clone())
+            .with_node_source("Bot", users_source)
+            .with_relationship_source("FOLLOWS", follows_source),
+    );
+    let config = GraphConfig::builder()
+        .with_node_mapping(
+            NodeMapping::new("Person", "id")
+                .with_label_column("kind")
+                .with_label_value("person"),
+        )
+        .with_node_mapping(
+            NodeMapping::new("Bot", "id")
+                .with_label_column("kind")
+                .with_label_value("bot"),
+        )
+        .with_relationship("FOLLOWS", "src_id", "dst_id")
+        .build()
+        .unwrap();
+
+    let people = CypherQuery::new("MATCH (p:Person) RETURN p.name ORDER BY p.name")
+        .unwrap()
+        .with_config(config.clone())
+        .execute_with_catalog_and_context(catalog.clone(), SessionContext::new())
+        .await
+        .unwrap();
+    let names = people
+        .column(0)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+    assert_eq!(people.num_rows(), 2);
+    assert_eq!(names.value(0), "Alice");
+    assert_eq!(names.value(1), "Bob");
+
+    // The target label's discriminator applies to the joined side too
+    let followed_bots = CypherQuery::new(
+        "MATCH (p:Person)-[:FOLLOWS]->(b:Bot) RETURN p.name, b.name ORDER BY p.name",
+    )
+    .unwrap()
+    .with_config(config)
+    .execute_with_catalog_and_context(catalog, SessionContext::new())
+    .await
+    .unwrap();
+    let followers = followed_bots
+        .column(0)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+    assert_eq!(followed_bots.num_rows(), 2);
+    assert_eq!(followers.value(0), "Alice");
+    assert_eq!(followers.value(1), "Bob");
+}
+
+#[tokio::test]
+async fn test_mapping_filter_conditions() {
+    use arrow_array::{Int64Array, RecordBatch, StringArray};
+    use arrow_schema::{DataType, Field, Schema};
+    use lance_graph::config::{NodeMapping, RelationshipMapping};
+    use std::sync::Arc;
+
+    let ctx = SessionContext::new();
+    let people = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("name", DataType::Utf8, false),
+            Field::new("age", DataType::Int64, false),
+        ])),
+        vec![
+            Arc::new(Int64Array::from(vec![1, 2, 3])),
+            Arc::new(StringArray::from(vec!["Alice", "Bob", "Carol"])),
+            Arc::new(Int64Array::from(vec![28, 12, 41])),
+        ],
+    )
+    .unwrap();
+    ctx.register_batch("Person", people).unwrap();
+    let knows = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("src_id", DataType::Int64, false),
+            Field::new("dst_id", DataType::Int64, false),
+            Field::new("since", DataType::Int64, false),
+        ])),
+        vec![
+            Arc::new(Int64Array::from(vec![1, 1, 3])),
+            Arc::new(Int64Array::from(vec![2, 3, 1])),
+            Arc::new(Int64Array::from(vec![2015, 2021, 2022])),
+        ],
+    )
+    .unwrap();
+    ctx.register_batch("KNOWS", knows).unwrap();
+
+    // Only adults are people, and only recent acquaintances know each other
+    let config = |filter: &str| {
+        GraphConfig::builder()
+            .with_node_mapping(NodeMapping::new("Person", "id").with_filter(filter))
+            .with_relationship_mapping(
+                RelationshipMapping::new("KNOWS", "src_id", "dst_id").with_filter("since >= 2020"),
+            )
+            .build()
+            .unwrap()
+    };
+    let names = |batch: &RecordBatch, column: usize| {
+        let values = batch
+            .column(column)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        values
+            .iter()
+            .flatten()
+            .map(str::to_string)
+            .collect::<Vec<_>>()
+    };
+
+    let people = CypherQuery::new("MATCH (p:Person) RETURN p.name ORDER BY p.name")
+        .unwrap()
+        .with_config(config("age >= 18"))
+        .execute_with_context(ctx.clone())
+        .await
+        .unwrap();
+    assert_eq!(names(&people, 0), vec!["Alice", "Carol"]);
+
+    let friends = CypherQuery::new(
+        "MATCH (a:Person)-[:KNOWS]->(b:Person) RETURN a.name, b.name ORDER BY a.name",
+    )
+    .unwrap()
+    .with_config(config("age >= 18"))
+    .execute_with_context(ctx.clone())
+    .await
+    .unwrap();
+    assert_eq!(names(&friends, 0), vec!["Alice", "Carol"]);
+    assert_eq!(names(&friends, 1), vec!["Carol", "Alice"]);
+
+    let error = CypherQuery::new("MATCH (p:Person) RETURN p.name")
+        .unwrap()
+        .with_config(config("height >= 18"))
+        .execute_with_context(ctx)
+        .await
+        .unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .contains("Invalid filter of node label 'Person'"),
+        "{}",
+        error
+    );
+}
+
+#[tokio::test]
+async fn test_rewrite_rule_injects_filter() {
+    use arrow_array::{Int64Array, RecordBatch, StringArray};
+    use arrow_schema::{DataType, Field, Schema};
+    use datafusion::common::tree_node::{Transformed, TreeNode, TreeNodeRecursion};
+    use datafusion::common::Column;
+    use datafusion::datasource::{provider_as_source, MemTable};
+    use datafusion::logical_expr::{lit, Expr, Filter, LogicalPlan};
+    use datafusion::optimizer::{OptimizerConfig, OptimizerRule};
+    use lance_graph::source_catalog::InMemoryCatalog;
+    use std::sync::Arc;
+
+    /// Keeps only the rows of one tenant in every scan with a `tenant` column
+    #[derive(Debug)]
+    struct TenantFilter;
+
+    impl OptimizerRule for TenantFilter {
+        fn name(&self) -> &str {
+            "tenant_filter"
+        }
+
+        fn rewrite(
+            &self,
+            plan: LogicalPlan,
+            _config: &dyn OptimizerConfig,
+        ) -> datafusion::error::Result<Transformed<LogicalPlan>> {
+            plan.transform_down(|node| match &node {
+                // Scans filtered by an earlier pass are left alone
+                LogicalPlan::Filter(filter)
+                    if matches!(*filter.input, LogicalPlan::TableScan(_)) =>
+                {
+                    Ok(Transformed::new(node, false, TreeNodeRecursion::Jump))
+                }
+                LogicalPlan::TableScan(scan) => {
+                    let Ok((qualifier, field)) = scan
+                        .projected_schema
+                        .qualified_field_with_unqualified_name("tenant")
+                    else {
+                        return Ok(Transformed::no(node));
+                    };
+                    let tenant = Expr::Column(Column::new(qualifier.cloned(), field.name()));
+                    let filter = Filter::try_new(tenant.eq(lit("acme")), Arc::new(node))?;
+                    Ok(Transformed::new(
+                        LogicalPlan::Filter(filter),
+                        true,
+                        TreeNodeRecursion::Jump,
+                    ))
+                }
+                _ => Ok(Transformed::no(node)),
+            })
+        }
+    }
+
+    /// A rule that cannot rewrite anything
+    #[derive(Debug)]
+    struct Broken;
+
+    impl OptimizerRule for Broken {
+        fn name(&self) -> &str {
+            "broken"
+        }
+    }
+
+    let person = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("name", DataType::Utf8, false),
+            Field::new("tenant", DataType::Utf8, false),
+        ])),
+        vec![
+            Arc::new(Int64Array::from(vec![1, 2, 3])),
+            Arc::new(StringArray::from(vec!["Alice", "Bob", "Carol"])),
+            Arc::new(StringArray::from(vec!["acme", "other", "acme"])),
+        ],
+    )
+    .unwrap();
+    let catalog = Arc::new(InMemoryCatalog::new().with_node_source(
+        "Person",
+        provider_as_source(Arc::new(
+            MemTable::try_new(person.schema(), vec![vec![person]]).unwrap(),
+        )),
+    ));
+    let config = GraphConfig::builder()
+        .with_node_label("Person", "id")
+        .build()
+        .unwrap();
+    let query = CypherQuery::new("MATCH (p:Person) RETURN p.name ORDER BY p.name")
+        .unwrap()
+        .with_config(config);
+
+    let result = query
+        .clone()
+        .with_rewrite_rule(Arc::new(TenantFilter))
+        .execute_with_catalog_and_context(catalog.clone(), SessionContext::new())
+        .await
+        .unwrap();
+    let names = result
+        .column(0)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+    assert_eq!(
+        names.iter().flatten().collect::<Vec<_>>(),
+        vec!["Alice", "Carol"]
+    );
+
+    let err = query
+        .with_rewrite_rule(Arc::new(Broken))
+        .execute_with_catalog_and_context(catalog, SessionContext::new())
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("broken"), "{}", err);
+}
+
+#[tokio::test]
+async fn test_repeated_relationship_scans_are_shared() {
+    use arrow_array::{Int64Array, RecordBatch, StringArray};
+    use arrow_schema::{DataType, Field, Schema, SchemaRef};
+    use datafusion::catalog::Session;
+    use datafusion::datasource::{provider_as_source, MemTable, TableProvider, TableType};
+    use datafusion::logical_expr::Expr;
+    use datafusion::physical_plan::ExecutionPlan;
+    use lance_graph::source_catalog::InMemoryCatalog;
+    use std::any::Any;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// A table counting how often it is scanned
+    #[derive(Debug)]
+    struct Counting {
+        inner: MemTable,
+        scans: Arc<AtomicUsize>,
+    }
+
+    #[async_trait::async_trait]
+    impl TableProvider for Counting {
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn schema(&self) -> SchemaRef {
+            self.inner.schema()
+        }
+
+        fn table_type(&self) -> TableType {
+            TableType::Base
+        }
+
+        async fn scan(
+            &self,
+            state: &dyn Session,
+            projection: Option<&Vec<usize>>,
+            filters: &[Expr],
+            limit: Option<usize>,
+        ) -> datafusion::error::Result<Arc<dyn ExecutionPlan>> {
+            self.scans.fetch_add(1, Ordering::SeqCst);
+            self.inner.scan(state, projection, filters, limit).await
+        }
+    }
+
+    let person = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("name", DataType::Utf8, false),
+        ])),
+        vec![
+            Arc::new(Int64Array::from(vec![1, 2, 3, 4])),
+            Arc::new(StringArray::from(vec!["Alice", "Bob", "Carol", "David"])),
+        ],
+    )
+    .unwrap();
+    let knows = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("src_id", DataType::Int64, false),
+            Field::new("dst_id", DataType::Int64, false),
+        ])),
+        vec![
+            Arc::new(Int64Array::from(vec![1, 2, 2])),
+            Arc::new(Int64Array::from(vec![2, 3, 4])),
+        ],
+    )
+    .unwrap();
+    let knows_scans = Arc::new(AtomicUsize::new(0));
+    let catalog = Arc::new(
+        InMemoryCatalog::new()
+            .with_node_source(
+                "Person",
+                provider_as_source(Arc::new(
+                    MemTable::try_new(person.schema(), vec![vec![person]]).unwrap(),
+                )),
+            )
+            .with_relationship_source(
+                "KNOWS",
+                provider_as_source(Arc::new(Counting {
+                    inner: MemTable::try_new(knows.schema(), vec![vec![knows]]).unwrap(),
+                    scans: knows_scans.clone(),
+                })),
+            ),
+    );
+    let config = GraphConfig::builder()
+        .with_node_label("Person", "id")
+        .with_relationship("KNOWS", "src_id", "dst_id")
+        .build()
+        .unwrap();
+
+    let query = CypherQuery::new(
+        "MATCH (a:Person)-[:KNOWS]->(:Person)-[:KNOWS]->(c:Person) \
+         RETURN a.name, c.name ORDER BY c.name",
+    )
+    .unwrap()
+    .with_config(config);
+    let result = query
+        .execute_with_catalog_and_context(catalog.clone(), SessionContext::new())
+        .await
+        .unwrap();
+    let names = |column: usize| {
+        result
+            .column(column)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap()
+            .iter()
+            .flatten()
+            .map(str::to_string)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(names(0), vec!["Alice", "Alice"]);
+    assert_eq!(names(1), vec!["Carol", "David"]);
+    assert_eq!(knows_scans.load(Ordering::SeqCst), 1);
+
+    // Each execution reads the table again
+    query
+        .execute_with_catalog_and_context(catalog, SessionContext::new())
+        .await
+        .unwrap();
+    assert_eq!(knows_scans.load(Ordering::SeqCst), 2);
+}
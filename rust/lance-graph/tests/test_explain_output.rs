@@ -321,3 +321,88 @@ async fn test_explain_relationship_properties() {
 
     assert!(plan.contains("since") || plan.contains("Filter"));
 }
+
+/// The `plan` of each `plan_type` in the output of an EXPLAIN or PROFILE statement
+fn plans(batch: &RecordBatch) -> HashMap<String, String> {
+    let column = |i: usize| {
+        batch
+            .column(i)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap()
+            .clone()
+    };
+    let (plan_types, plans) = (column(0), column(1));
+    (0..batch.num_rows())
+        .map(|i| (plan_types.value(i).to_string(), plans.value(i).to_string()))
+        .collect()
+}
+
+#[tokio::test]
+async fn test_explain_statement() {
+    let config = GraphConfig::builder()
+        .with_node_label("Person", "id")
+        .with_relationship("KNOWS", "src_id", "dst_id")
+        .build()
+        .unwrap();
+    let query = CypherQuery::new(
+        "EXPLAIN MATCH (a:Person)-[:KNOWS]->(b:Person) WHERE a.age > 30 RETURN b.name",
+    )
+    .unwrap()
+    .with_config(config);
+
+    let mut datasets = HashMap::new();
+    datasets.insert("Person".to_string(), create_person_dataset());
+    datasets.insert("KNOWS".to_string(), create_knows_dataset());
+
+    let out = query.execute(datasets, None).await.unwrap();
+    assert_eq!(out.schema().field(0).name(), "plan_type");
+    assert_eq!(out.schema().field(1).name(), "plan");
+    let plans = plans(&out);
+    let mut plan_types: Vec<_> = plans.keys().map(String::as_str).collect();
+    plan_types.sort();
+    assert_eq!(
+        plan_types,
+        vec![
+            "graph_logical_plan",
+            "graph_logical_plan_json",
+            "index_usage",
+            "logical_plan",
+            "logical_plan_json",
+            "physical_plan",
+        ]
+    );
+    assert_eq!(plans["index_usage"], "none");
+    assert!(plans["graph_logical_plan"].contains("Expand"));
+    let graph_plan: serde_json::Value =
+        serde_json::from_str(&plans["graph_logical_plan_json"]).unwrap();
+    assert!(graph_plan.is_object());
+    let logical_plan: serde_json::Value =
+        serde_json::from_str(&plans["logical_plan_json"]).unwrap();
+    assert!(logical_plan.is_array());
+    assert!(plans["physical_plan"].contains("Join"));
+}
+
+#[tokio::test]
+async fn test_profile_statement() {
+    let config = GraphConfig::builder()
+        .with_node_label("Person", "id")
+        .build()
+        .unwrap();
+    let query = CypherQuery::new("PROFILE MATCH (p:Person) WHERE p.age > 30 RETURN p.name")
+        .unwrap()
+        .with_config(config);
+
+    let mut datasets = HashMap::new();
+    datasets.insert("Person".to_string(), create_person_dataset());
+
+    let out = query.execute(datasets, None).await.unwrap();
+    let plans = plans(&out);
+    assert!(plans["physical_plan_with_metrics"].contains("output_rows"));
+    let profile: serde_json::Value = serde_json::from_str(&plans["profile_json"]).unwrap();
+    // Bob, David and Eve
+    assert_eq!(profile["rows"], 3);
+    assert!(profile["elapsed_ns"].is_u64());
+    assert_eq!(profile["plan"]["output_rows"], 3);
+    assert!(profile["plan"]["children"].is_array());
+}
@@ -0,0 +1,124 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Cypher queries over Arrow Flight SQL
+#![cfg(feature = "flight")]
+
+use std::sync::Arc;
+
+use arrow::compute::concat_batches;
+use arrow_array::{Int64Array, RecordBatch, StringArray};
+use arrow_flight::sql::client::FlightSqlServiceClient;
+use arrow_schema::{DataType, Field, Schema};
+use datafusion::datasource::MemTable;
+use futures::TryStreamExt;
+use lance_graph::flight::GraphFlightService;
+use lance_graph::source_catalog::ProviderCatalog;
+use lance_graph::GraphConfig;
+use tokio::net::TcpListener;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::transport::{Channel, Server};
+
+fn graph_config() -> GraphConfig {
+    GraphConfig::builder()
+        .with_node_label("Person", "id")
+        .with_relationship("KNOWS", "src_id", "dst_id")
+        .build()
+        .unwrap()
+}
+
+/// Alice (1, 28) knows Bob (2, 34)
+fn catalog() -> Arc<ProviderCatalog> {
+    let person = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("name", DataType::Utf8, false),
+            Field::new("age", DataType::Int64, false),
+        ])),
+        vec![
+            Arc::new(Int64Array::from(vec![1, 2])),
+            Arc::new(StringArray::from(vec!["Alice", "Bob"])),
+            Arc::new(Int64Array::from(vec![28, 34])),
+        ],
+    )
+    .unwrap();
+    let knows = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("src_id", DataType::Int64, false),
+            Field::new("dst_id", DataType::Int64, false),
+        ])),
+        vec![
+            Arc::new(Int64Array::from(vec![1])),
+            Arc::new(Int64Array::from(vec![2])),
+        ],
+    )
+    .unwrap();
+    let table = |batch: RecordBatch| {
+        Arc::new(MemTable::try_new(batch.schema(), vec![vec![batch]]).unwrap())
+    };
+    Arc::new(
+        ProviderCatalog::new()
+            .with_node_table("Person", table(person))
+            .with_relationship_table("KNOWS", table(knows)),
+    )
+}
+
+/// Start a server on a free local port and connect a client to it
+async fn client() -> FlightSqlServiceClient<Channel> {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let service = GraphFlightService::new(graph_config(), catalog()).into_server();
+    tokio::spawn(
+        Server::builder()
+            .add_service(service)
+            .serve_with_incoming(TcpListenerStream::new(listener)),
+    );
+    let channel = Channel::from_shared(format!("http://{}", addr))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    FlightSqlServiceClient::new(channel)
+}
+
+#[tokio::test]
+async fn test_flight_sql_statement_returns_rows() {
+    let mut client = client().await;
+    let info = client
+        .execute(
+            "MATCH (a:Person)-[:KNOWS]->(b:Person) RETURN a.name, b.name AS friend".to_string(),
+            None,
+        )
+        .await
+        .unwrap();
+    let schema = info.clone().try_decode_schema().unwrap();
+    assert_eq!(schema.field(0).name(), "a.name");
+    assert_eq!(schema.field(1).name(), "friend");
+
+    let ticket = info.endpoint[0].ticket.clone().unwrap();
+    let batches: Vec<RecordBatch> = client
+        .do_get(ticket)
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+    let batch = concat_batches(&batches[0].schema(), &batches).unwrap();
+    assert_eq!(batch.num_rows(), 1);
+    let friends = batch
+        .column(1)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+    assert_eq!(friends.value(0), "Bob");
+}
+
+#[tokio::test]
+async fn test_flight_sql_rejects_invalid_queries() {
+    let mut client = client().await;
+    let err = client
+        .execute("MATCH (p:Person RETURN p".to_string(), None)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("InvalidArgument"), "{}", err);
+}
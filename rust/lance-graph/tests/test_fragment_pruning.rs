@@ -0,0 +1,208 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Scans of Lance datasets skipping fragments that cannot match
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use arrow_array::{Array, Int64Array, RecordBatch, RecordBatchIterator, StringArray};
+use arrow_schema::{DataType, Field, Schema};
+use lance::dataset::WriteParams;
+use lance::Dataset;
+use lance_graph::source_catalog::GraphSourceCatalog;
+use lance_graph::{CypherQuery, GraphConfig, LanceCatalog};
+
+fn graph_config() -> GraphConfig {
+    GraphConfig::builder()
+        .with_node_label("Person", "id")
+        .with_relationship("KNOWS", "src_id", "dst_id")
+        .build()
+        .unwrap()
+}
+
+/// A dataset of two rows per fragment
+async fn write(uri: &str, batch: RecordBatch) -> Arc<Dataset> {
+    let reader = RecordBatchIterator::new(vec![Ok(batch.clone())], batch.schema());
+    let params = WriteParams {
+        max_rows_per_file: 2,
+        max_rows_per_group: 2,
+        ..Default::default()
+    };
+    Arc::new(Dataset::write(reader, uri, Some(params)).await.unwrap())
+}
+
+/// Eight people in four fragments, and edges sorted by source in four fragments
+async fn lance_catalog(dir: &tempfile::TempDir) -> LanceCatalog {
+    let path = |name: &str| dir.path().join(name).to_str().unwrap().to_string();
+    let people = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("name", DataType::Utf8, false),
+        ])),
+        vec![
+            Arc::new(Int64Array::from_iter_values(1..=8)),
+            Arc::new(StringArray::from(vec![
+                "Alice", "Bob", "Carol", "Dan", "Eve", "Finn", "Gus", "Hana",
+            ])),
+        ],
+    )
+    .unwrap();
+    let knows = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("src_id", DataType::Int64, false),
+            Field::new("dst_id", DataType::Int64, false),
+            Field::new("since", DataType::Int64, false),
+        ])),
+        vec![
+            Arc::new(Int64Array::from(vec![1, 1, 2, 3, 4, 5, 6, 7])),
+            Arc::new(Int64Array::from(vec![2, 3, 3, 4, 5, 6, 7, 8])),
+            Arc::new(Int64Array::from(vec![
+                2010, 2011, 2012, 2013, 2014, 2015, 2016, 2017,
+            ])),
+        ],
+    )
+    .unwrap();
+    let person = write(&path("person.lance"), people).await;
+    let knows = write(&path("knows.lance"), knows).await;
+    assert_eq!(person.fragments().len(), 4);
+    LanceCatalog::new()
+        .with_node_dataset("Person", person)
+        .with_relationship_dataset("KNOWS", knows)
+}
+
+async fn execute(cypher: &str, catalog: Arc<dyn GraphSourceCatalog>) -> RecordBatch {
+    CypherQuery::new(cypher)
+        .unwrap()
+        .with_config(graph_config())
+        .execute_with_catalog(catalog)
+        .await
+        .unwrap()
+}
+
+fn strings(batch: &RecordBatch, column: usize) -> Vec<&str> {
+    let values = batch
+        .column(column)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+    (0..values.len()).map(|i| values.value(i)).collect()
+}
+
+async fn plans(statement: &str, catalog: Arc<dyn GraphSourceCatalog>) -> HashMap<String, String> {
+    let out = execute(statement, catalog).await;
+    strings(&out, 0)
+        .into_iter()
+        .zip(strings(&out, 1))
+        .map(|(plan_type, plan)| (plan_type.to_string(), plan.to_string()))
+        .collect()
+}
+
+const ANCHORED: &str =
+    "MATCH (a:Person {id: 1})-[:KNOWS]->(b:Person) RETURN b.name ORDER BY b.name";
+
+#[tokio::test]
+async fn test_anchored_scans_skip_fragments() {
+    let dir = tempfile::tempdir().unwrap();
+    let catalog = lance_catalog(&dir).await;
+    catalog
+        .build_fragment_statistics("Person", &["id"])
+        .await
+        .unwrap();
+    catalog
+        .build_fragment_statistics("KNOWS", &["src_id", "dst_id", "since"])
+        .await
+        .unwrap();
+    let catalog: Arc<dyn GraphSourceCatalog> = Arc::new(catalog);
+
+    let explain = plans(&format!("EXPLAIN {}", ANCHORED), catalog.clone()).await;
+    assert!(
+        explain["physical_plan"].contains("FragmentPruningExec: fragments=1/4"),
+        "{}",
+        explain["physical_plan"]
+    );
+    let profile = plans(&format!("PROFILE {}", ANCHORED), catalog.clone()).await;
+    assert!(
+        profile["physical_plan_with_metrics"].contains("fragments_pruned=3"),
+        "{}",
+        profile["physical_plan_with_metrics"]
+    );
+
+    let result = execute(ANCHORED, catalog.clone()).await;
+    assert_eq!(strings(&result, 0), vec!["Bob", "Carol"]);
+
+    let query = "MATCH (a:Person)-[r:KNOWS]->(b:Person) WHERE r.since >= 2016 \
+                 RETURN b.name ORDER BY b.name";
+    let explain = plans(&format!("EXPLAIN {}", query), catalog.clone()).await;
+    assert!(
+        explain["physical_plan"].contains("FragmentPruningExec: fragments=1/4"),
+        "{}",
+        explain["physical_plan"]
+    );
+    let result = execute(query, catalog).await;
+    assert_eq!(strings(&result, 0), vec!["Gus", "Hana"]);
+}
+
+#[tokio::test]
+async fn test_unfiltered_scans_read_every_fragment() {
+    let dir = tempfile::tempdir().unwrap();
+    let catalog: Arc<dyn GraphSourceCatalog> = Arc::new(lance_catalog(&dir).await);
+
+    let query = "MATCH (p:Person) WHERE p.id > 0 RETURN p.name";
+    let explain = plans(&format!("EXPLAIN {}", query), catalog.clone()).await;
+    assert!(
+        !explain["physical_plan"].contains("FragmentPruningExec"),
+        "{}",
+        explain["physical_plan"]
+    );
+    assert_eq!(execute(query, catalog).await.num_rows(), 8);
+}
+
+#[tokio::test]
+async fn test_statistics_computed_in_the_background() {
+    let dir = tempfile::tempdir().unwrap();
+    let catalog: Arc<dyn GraphSourceCatalog> = Arc::new(lance_catalog(&dir).await);
+    let explain = format!("EXPLAIN {}", ANCHORED);
+
+    // The first query reads every fragment rather than wait for the statistics
+    let plan = plans(&explain, catalog.clone()).await["physical_plan"].clone();
+    assert!(!plan.contains("FragmentPruningExec"), "{}", plan);
+    assert_eq!(
+        strings(&execute(ANCHORED, catalog.clone()).await, 0),
+        vec!["Bob", "Carol"]
+    );
+
+    let mut pruned = false;
+    for _ in 0..100 {
+        let plan = plans(&explain, catalog.clone()).await["physical_plan"].clone();
+        if plan.contains("FragmentPruningExec: fragments=1/4") {
+            pruned = true;
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    }
+    assert!(pruned, "statistics were never computed");
+    assert_eq!(
+        strings(&execute(ANCHORED, catalog).await, 0),
+        vec!["Bob", "Carol"]
+    );
+}
+
+#[tokio::test]
+async fn test_build_statistics_of_unknown_column() {
+    let dir = tempfile::tempdir().unwrap();
+    let catalog = lance_catalog(&dir).await;
+    let error = catalog
+        .build_fragment_statistics("KNOWS", &["weight"])
+        .await
+        .unwrap_err();
+    assert!(
+        error.to_string().contains("has no column 'weight'"),
+        "{}",
+        error
+    );
+    assert!(catalog
+        .build_fragment_statistics("LIKES", &["src_id"])
+        .await
+        .is_err());
+}
@@ -0,0 +1,118 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Full-text search with `fts(property, 'terms')`
+
+use std::sync::Arc;
+
+use arrow_array::{Int64Array, RecordBatch, StringArray};
+use arrow_schema::{DataType, Field, Schema};
+use lance_graph::source_catalog::GraphSourceCatalog;
+use lance_graph::LanceCatalog;
+use lance_index::scalar::InvertedIndexParams;
+use lance_index::{DatasetIndexExt, IndexType};
+
+mod common;
+use common::{strings, write_dataset, TestGraph};
+
+fn bios() -> RecordBatch {
+    RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("name", DataType::Utf8, false),
+            Field::new("bio", DataType::Utf8, true),
+        ])),
+        vec![
+            Arc::new(Int64Array::from(vec![1, 2, 3, 4])),
+            Arc::new(StringArray::from(vec!["Alice", "Bob", "Carol", "Dan"])),
+            Arc::new(StringArray::from(vec![
+                Some("Builds graph databases"),
+                Some("Writes poetry"),
+                Some("Studies GRAPH theory and music"),
+                None,
+            ])),
+        ],
+    )
+    .unwrap()
+}
+
+fn people() -> TestGraph {
+    TestGraph::people(&[]).with_nodes("Person", bios())
+}
+
+/// A Lance dataset of the people with an inverted index on their bios
+async fn indexed_catalog(dir: &tempfile::TempDir) -> Arc<dyn GraphSourceCatalog> {
+    let mut dataset = Arc::unwrap_or_clone(write_dataset(dir, "Person", &bios()).await);
+    dataset
+        .create_index(
+            &["bio"],
+            IndexType::Inverted,
+            None,
+            &InvertedIndexParams::default(),
+            true,
+        )
+        .await
+        .unwrap();
+    Arc::new(LanceCatalog::new().with_node_dataset("Person", Arc::new(dataset)))
+}
+
+async fn names(cypher: &str, catalog: Arc<dyn GraphSourceCatalog>) -> Vec<String> {
+    let result = people()
+        .query(cypher)
+        .unwrap()
+        .with_parameter("terms", "graph")
+        .execute_with_catalog(catalog)
+        .await
+        .unwrap();
+    strings(&result, 0).into_iter().map(String::from).collect()
+}
+
+async fn assert_full_text_matches(catalog: Arc<dyn GraphSourceCatalog>) {
+    let query = "MATCH (p:Person) WHERE fts(p.bio, $terms) RETURN p.name ORDER BY p.name";
+    assert_eq!(names(query, catalog.clone()).await, vec!["Alice", "Carol"]);
+
+    let query = "MATCH (p:Person) WHERE fts(p.bio, 'poetry music') AND p.id > 1 \
+                 RETURN p.name ORDER BY p.name";
+    assert_eq!(names(query, catalog.clone()).await, vec!["Bob", "Carol"]);
+
+    let query = "MATCH (p:Person) WHERE NOT fts(p.bio, 'graph') RETURN p.name";
+    assert_eq!(names(query, catalog).await, vec!["Bob"]);
+}
+
+#[tokio::test]
+async fn test_full_text_search_over_in_memory_tables() {
+    assert_full_text_matches(people().memory_catalog()).await;
+}
+
+#[tokio::test]
+async fn test_full_text_search_over_unindexed_lance_datasets() {
+    let dir = tempfile::tempdir().unwrap();
+    assert_full_text_matches(people().lance_catalog(&dir).await).await;
+}
+
+#[tokio::test]
+async fn test_full_text_search_through_lance_index() {
+    let dir = tempfile::tempdir().unwrap();
+    assert_full_text_matches(indexed_catalog(&dir).await).await;
+}
+
+#[tokio::test]
+async fn test_full_text_search_without_index_matches_substrings() {
+    let query = "MATCH (p:Person) WHERE fts(p.bio, 'poe') RETURN p.name";
+    assert_eq!(names(query, people().memory_catalog()).await, vec!["Bob"]);
+}
+
+#[tokio::test]
+async fn test_full_text_query_must_be_a_string() {
+    let error = people()
+        .run("MATCH (p:Person) WHERE fts(p.bio, 42) RETURN p.name")
+        .await
+        .unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .contains("fts takes its query as a string constant"),
+        "{}",
+        error
+    );
+}
@@ -0,0 +1,220 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Algorithm procedures run on an in-memory `GraphProjection`
+
+use std::sync::Arc;
+
+use arrow::array::AsArray;
+use arrow::datatypes::{Float64Type, Int64Type};
+use arrow_array::RecordBatch;
+use datafusion::scalar::ScalarValue;
+use lance_graph::{GraphConfig, GraphProjection, NodeMapping};
+
+mod common;
+use common::{named, weighted_edges, TestGraph};
+
+/// Alice (1) knows Bob (2), who knows Carol (3) and Dan (4); Carol knows Dan, who knows
+/// Eve (5). Each relationship has a strength. Fay (6) knows nobody.
+fn social() -> TestGraph {
+    TestGraph::people(&["KNOWS"])
+        .with_nodes(
+            "Person",
+            named(&["Alice", "Bob", "Carol", "Dan", "Eve", "Fay"]),
+        )
+        .with_relationships(
+            "KNOWS",
+            weighted_edges(
+                "strength",
+                &[
+                    (1, 2, 2.0),
+                    (2, 3, 1.0),
+                    (3, 4, 1.0),
+                    (2, 4, 0.5),
+                    (4, 5, 3.0),
+                ],
+            ),
+        )
+}
+
+async fn projection() -> Arc<GraphProjection> {
+    let graph = social();
+    let projection = GraphProjection::builder(graph.config())
+        .build(graph.memory_catalog())
+        .await
+        .unwrap();
+    Arc::new(projection)
+}
+
+/// Run `cypher` with `projection` registered as `social`
+async fn run(cypher: &str, projection: Arc<GraphProjection>) -> lance_graph::Result<RecordBatch> {
+    social()
+        .with_projection("social", projection)
+        .run(cypher)
+        .await
+}
+
+/// Node ids and values of a two-column result
+fn rows(batch: &RecordBatch) -> (Vec<i64>, Vec<f64>) {
+    let ids = batch.column(0).as_primitive::<Int64Type>();
+    let values = batch.column(1).as_primitive::<Float64Type>();
+    (ids.values().to_vec(), values.values().to_vec())
+}
+
+#[tokio::test]
+async fn test_projection_layout() {
+    let projection = projection().await;
+    assert_eq!(projection.node_count(), 5);
+    assert_eq!(projection.relationship_count(), 5);
+    assert_eq!(projection.relationship_types(), &["KNOWS".to_string()]);
+    let key = |k: i64| ScalarValue::Int64(Some(k));
+    let mut neighbors: Vec<ScalarValue> = projection.neighbors(&key(2)).cloned().collect();
+    neighbors.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    assert_eq!(neighbors, vec![key(3), key(4)]);
+    assert_eq!(
+        projection.breadth_first(&[key(1)], 2),
+        vec![(key(1), 0), (key(2), 1), (key(3), 2), (key(4), 2)]
+    );
+}
+
+#[tokio::test]
+async fn test_algorithms_on_projection() {
+    let projection = projection().await;
+    for (direct, projected) in [
+        (
+            "CALL graph.wcc() YIELD nodeId, componentId RETURN nodeId, componentId",
+            "CALL graph.wcc({projection: 'social'}) YIELD nodeId, componentId \
+             RETURN nodeId, componentId",
+        ),
+        (
+            "CALL graph.betweenness() YIELD nodeId, score RETURN nodeId, score",
+            "CALL graph.betweenness({projection: 'social'}) YIELD nodeId, score \
+             RETURN nodeId, score",
+        ),
+    ] {
+        let direct = run(direct, projection.clone()).await.unwrap();
+        let projected = run(projected, projection.clone()).await.unwrap();
+        assert_eq!(projected, direct);
+    }
+
+    let (direct_ids, direct_scores) = rows(
+        &run(
+            "CALL graph.pageRank() YIELD nodeId, score RETURN nodeId, score",
+            projection.clone(),
+        )
+        .await
+        .unwrap(),
+    );
+    let (ids, scores) = rows(
+        &run(
+            "CALL graph.pageRank({projection: 'social'}) YIELD nodeId, score \
+             RETURN nodeId, score",
+            projection.clone(),
+        )
+        .await
+        .unwrap(),
+    );
+    assert_eq!(ids, direct_ids);
+    for (score, direct) in scores.iter().zip(&direct_scores) {
+        assert!((score - direct).abs() < 1e-12);
+    }
+}
+
+#[tokio::test]
+async fn test_projection_of_labels_keeps_isolated_nodes() {
+    let graph = social();
+    let projection = GraphProjection::builder(graph.config())
+        .with_node_labels(["Person"])
+        .with_relationship_types(["KNOWS"])
+        .build(graph.memory_catalog())
+        .await
+        .unwrap();
+    assert_eq!(projection.node_count(), 6);
+    let result = run(
+        "CALL graph.wcc({projection: 'social'}) YIELD nodeId, componentId \
+         RETURN nodeId, componentId",
+        Arc::new(projection),
+    )
+    .await
+    .unwrap();
+    let ids = result.column(0).as_primitive::<Int64Type>();
+    let components = result.column(1).as_primitive::<Int64Type>();
+    assert_eq!(ids.values(), &[1, 2, 3, 4, 5, 6]);
+    assert!(components.values()[..5]
+        .iter()
+        .all(|&c| c == components.value(0)));
+    assert_ne!(components.value(5), components.value(0));
+}
+
+#[tokio::test]
+async fn test_weighted_projection() {
+    let graph = social();
+    let projection = GraphProjection::builder(graph.config())
+        .with_weight_property("strength")
+        .build(graph.memory_catalog())
+        .await
+        .unwrap();
+    assert_eq!(projection.weight_property(), Some("strength"));
+    let result = run(
+        "CALL graph.degree({projection: 'social', direction: 'in', weightProperty: 'strength'}) \
+         YIELD nodeId, degree RETURN nodeId, degree",
+        Arc::new(projection),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        rows(&result),
+        (vec![5, 2, 4, 3, 1], vec![3.0, 2.0, 1.5, 1.0, 0.0])
+    );
+}
+
+#[tokio::test]
+async fn test_projection_errors() {
+    let projection = projection().await;
+    for (cypher, message) in [
+        (
+            "CALL graph.pageRank({projection: 'other'}) YIELD nodeId RETURN nodeId",
+            "unknown projection 'other'",
+        ),
+        (
+            "CALL graph.wcc({projection: 'social', relationshipTypes: ['KNOWS']}) \
+             YIELD nodeId RETURN nodeId",
+            "relationshipTypes cannot be given with a projection",
+        ),
+        (
+            "CALL graph.degree({projection: 'social', weightProperty: 'strength'}) \
+             YIELD degree RETURN degree",
+            "projection 'social' is not weighted by 'strength'",
+        ),
+    ] {
+        let error = run(cypher, projection.clone())
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains(message), "{}: {}", cypher, error);
+    }
+}
+
+#[tokio::test]
+async fn test_projection_of_labels_with_a_composite_key() {
+    // Nodes of the labels are projected by one key column
+    let config = GraphConfig::builder()
+        .with_node_mapping(
+            NodeMapping::new("Person", "id").with_composite_id(vec!["id".into(), "name".into()]),
+        )
+        .with_relationship("KNOWS", "src_id", "dst_id")
+        .build()
+        .unwrap();
+    let error = GraphProjection::builder(config)
+        .with_node_labels(["Person"])
+        .build(social().memory_catalog())
+        .await
+        .unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .contains("node label 'Person' has a composite key"),
+        "{}",
+        error
+    );
+}
@@ -0,0 +1,102 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Breadth-first and depth-first traversals with `CALL graph.bfs` and `CALL graph.dfs`
+
+use std::sync::Arc;
+
+use lance_graph::source_catalog::GraphSourceCatalog;
+use lance_graph::{GraphConfig, RelationshipMapping};
+
+mod common;
+use common::{edges, int64s, named, strings, TestGraph};
+
+/// Six people; Alice (1) knows Bob (2) and Carol (3), who both know Dan (4), who knows
+/// Eve (5); Finn (6) is a friend of Eve
+fn social() -> TestGraph {
+    let config = GraphConfig::builder()
+        .with_node_label("Person", "id")
+        .with_relationship("KNOWS", "src_id", "dst_id")
+        .with_relationship_mapping(
+            RelationshipMapping::new("FRIEND", "src_id", "dst_id").with_undirected(true),
+        )
+        .build()
+        .unwrap();
+    TestGraph::new(config)
+        .with_nodes(
+            "Person",
+            named(&["Alice", "Bob", "Carol", "Dan", "Eve", "Finn"]),
+        )
+        .with_relationships("KNOWS", edges(&[(1, 2), (1, 3), (2, 4), (3, 4), (4, 5)]))
+        .with_relationships("FRIEND", edges(&[(6, 5)]))
+}
+
+async fn assert_traversals(catalog: Arc<dyn GraphSourceCatalog>) {
+    let graph = social();
+    let result = graph
+        .run_with(
+            "CALL graph.bfs([1], ['KNOWS'], 2) YIELD node, depth \
+             MATCH (p:Person) WHERE p.id = node \
+             RETURN p.name, depth ORDER BY depth, p.name",
+            catalog.clone(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(strings(&result, 0), vec!["Alice", "Bob", "Carol", "Dan"]);
+    assert_eq!(int64s(&result, 1), vec![0, 1, 1, 2]);
+
+    let result = graph
+        .run_with(
+            "CALL graph.dfs([1], ['KNOWS'], 3) YIELD node, depth \
+             RETURN node, depth ORDER BY node",
+            catalog.clone(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(int64s(&result, 0), vec![1, 2, 3, 4, 5]);
+    assert_eq!(int64s(&result, 1), vec![0, 1, 1, 2, 3]);
+
+    // Undirected relationship types are traversed both ways
+    let result = graph
+        .run_with(
+            "CALL graph.bfs([4], ['KNOWS', 'FRIEND'], 5) YIELD node, depth \
+             RETURN node, depth ORDER BY depth, node",
+            catalog,
+        )
+        .await
+        .unwrap();
+    assert_eq!(int64s(&result, 0), vec![4, 5, 6]);
+    assert_eq!(int64s(&result, 1), vec![0, 1, 2]);
+}
+
+#[tokio::test]
+async fn test_traversals_over_in_memory_tables() {
+    assert_traversals(social().memory_catalog()).await;
+}
+
+#[tokio::test]
+async fn test_traversals_over_lance_datasets() {
+    let dir = tempfile::tempdir().unwrap();
+    assert_traversals(social().lance_catalog(&dir).await).await;
+}
+
+#[tokio::test]
+async fn test_traversal_errors() {
+    for (cypher, message) in [
+        (
+            "CALL graph.bfs([1], ['LIKES'], 2) YIELD node RETURN node",
+            "unknown relationship type 'LIKES'",
+        ),
+        (
+            "CALL graph.dfs([1], ['KNOWS'], -1) YIELD node RETURN node",
+            "maxDepth must be a non-negative integer",
+        ),
+        (
+            "CALL graph.bfs([1], 'KNOWS', 2) YIELD node RETURN node",
+            "list of string literals",
+        ),
+    ] {
+        let error = social().run(cypher).await.unwrap_err().to_string();
+        assert!(error.contains(message), "{}: {}", cypher, error);
+    }
+}
@@ -0,0 +1,123 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Property lookups through Lance scalar indexes
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use arrow_array::{Array, Int64Array, RecordBatch, RecordBatchIterator, StringArray};
+use arrow_schema::{DataType, Field, Schema};
+use lance::Dataset;
+use lance_graph::source_catalog::GraphSourceCatalog;
+use lance_graph::{CypherQuery, GraphConfig, LanceCatalog};
+use lance_index::scalar::{BuiltinIndexType, ScalarIndexParams};
+use lance_index::{DatasetIndexExt, IndexType};
+
+fn graph_config() -> GraphConfig {
+    GraphConfig::builder()
+        .with_node_label("Person", "id")
+        .build()
+        .unwrap()
+}
+
+/// People with a BTree index on `age` and a Bitmap index on `city` when `indexed`
+async fn lance_catalog(dir: &tempfile::TempDir, indexed: bool) -> Arc<dyn GraphSourceCatalog> {
+    let batch = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("name", DataType::Utf8, false),
+            Field::new("age", DataType::Int64, false),
+            Field::new("city", DataType::Utf8, false),
+        ])),
+        vec![
+            Arc::new(Int64Array::from(vec![1, 2, 3, 4])),
+            Arc::new(StringArray::from(vec!["Alice", "Bob", "Carol", "Dan"])),
+            Arc::new(Int64Array::from(vec![25, 35, 45, 30])),
+            Arc::new(StringArray::from(vec!["Paris", "Oslo", "Paris", "Rome"])),
+        ],
+    )
+    .unwrap();
+    let uri = dir.path().join("person.lance");
+    let reader = RecordBatchIterator::new(vec![Ok(batch.clone())], batch.schema());
+    let mut dataset = Dataset::write(reader, uri.to_str().unwrap(), None)
+        .await
+        .unwrap();
+    if indexed {
+        dataset
+            .create_index(
+                &["age"],
+                IndexType::BTree,
+                None,
+                &ScalarIndexParams::default(),
+                true,
+            )
+            .await
+            .unwrap();
+        dataset
+            .create_index(
+                &["city"],
+                IndexType::Bitmap,
+                None,
+                &ScalarIndexParams::for_builtin(BuiltinIndexType::Bitmap),
+                true,
+            )
+            .await
+            .unwrap();
+    }
+    Arc::new(LanceCatalog::new().with_node_dataset("Person", Arc::new(dataset)))
+}
+
+async fn execute(cypher: &str, catalog: Arc<dyn GraphSourceCatalog>) -> RecordBatch {
+    CypherQuery::new(cypher)
+        .unwrap()
+        .with_config(graph_config())
+        .execute_with_catalog(catalog)
+        .await
+        .unwrap()
+}
+
+fn strings(batch: &RecordBatch, column: usize) -> Vec<&str> {
+    let values = batch
+        .column(column)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+    (0..values.len()).map(|i| values.value(i)).collect()
+}
+
+async fn index_usage(cypher: &str, catalog: Arc<dyn GraphSourceCatalog>) -> String {
+    let out = execute(&format!("EXPLAIN {}", cypher), catalog).await;
+    let plans: HashMap<&str, &str> = strings(&out, 0).into_iter().zip(strings(&out, 1)).collect();
+    plans["index_usage"].to_string()
+}
+
+const POINT_LOOKUP: &str = "MATCH (p:Person {city: 'Paris'}) RETURN p.name ORDER BY p.name";
+const RANGE_LOOKUP: &str =
+    "MATCH (p:Person) WHERE p.age >= 30 AND p.age < 40 RETURN p.name ORDER BY p.name";
+
+#[tokio::test]
+async fn test_lookups_read_scalar_indexes() {
+    let dir = tempfile::tempdir().unwrap();
+    let catalog = lance_catalog(&dir, true).await;
+
+    let usage = index_usage(POINT_LOOKUP, catalog.clone()).await;
+    assert!(usage.contains("city"), "{}", usage);
+    let usage = index_usage(RANGE_LOOKUP, catalog.clone()).await;
+    assert!(usage.contains("age"), "{}", usage);
+
+    let result = execute(POINT_LOOKUP, catalog.clone()).await;
+    assert_eq!(strings(&result, 0), vec!["Alice", "Carol"]);
+    let result = execute(RANGE_LOOKUP, catalog).await;
+    assert_eq!(strings(&result, 0), vec!["Bob", "Dan"]);
+}
+
+#[tokio::test]
+async fn test_lookups_without_indexes_scan() {
+    let dir = tempfile::tempdir().unwrap();
+    let catalog = lance_catalog(&dir, false).await;
+
+    assert_eq!(index_usage(POINT_LOOKUP, catalog.clone()).await, "none");
+    let result = execute(RANGE_LOOKUP, catalog).await;
+    assert_eq!(strings(&result, 0), vec!["Bob", "Dan"]);
+}
@@ -0,0 +1,643 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Write clauses against Lance datasets
+
+use std::sync::Arc;
+
+use arrow_array::{Int64Array, RecordBatch, RecordBatchIterator, StringArray};
+use arrow_schema::{DataType, Field, Schema};
+use lance::dataset::WriteMode;
+use lance::Dataset;
+use lance_graph::write::WriteSummary;
+use lance_graph::{CypherQuery, GraphConfig, LanceCatalog, RelationshipMapping};
+use tempfile::TempDir;
+
+fn graph_config() -> GraphConfig {
+    GraphConfig::builder()
+        .with_node_label("Person", "id")
+        .with_relationship("KNOWS", "src_id", "dst_id")
+        .with_relationship("FOLLOWS", "src_id", "dst_id")
+        .build()
+        .unwrap()
+}
+
+async fn write_dataset(dir: &TempDir, name: &str, batch: RecordBatch) -> Arc<Dataset> {
+    let uri = dir.path().join(format!("{}.lance", name));
+    let reader = RecordBatchIterator::new(vec![Ok(batch.clone())], batch.schema());
+    Arc::new(
+        Dataset::write(reader, uri.to_str().unwrap(), None)
+            .await
+            .unwrap(),
+    )
+}
+
+/// Alice (1), Bob (2) and Carol (3), with Alice knowing Bob since 2020
+async fn create_catalog(dir: &TempDir) -> LanceCatalog {
+    let person_schema = Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int64, false),
+        Field::new("name", DataType::Utf8, true),
+        Field::new("age", DataType::Int64, true),
+    ]));
+    let person = RecordBatch::try_new(
+        person_schema,
+        vec![
+            Arc::new(Int64Array::from(vec![1, 2, 3])),
+            Arc::new(StringArray::from(vec!["Alice", "Bob", "Carol"])),
+            Arc::new(Int64Array::from(vec![28, 34, 41])),
+        ],
+    )
+    .unwrap();
+    let knows_schema = Arc::new(Schema::new(vec![
+        Field::new("src_id", DataType::Int64, false),
+        Field::new("dst_id", DataType::Int64, false),
+        Field::new("since_year", DataType::Int64, true),
+    ]));
+    let knows = RecordBatch::try_new(
+        knows_schema,
+        vec![
+            Arc::new(Int64Array::from(vec![1])),
+            Arc::new(Int64Array::from(vec![2])),
+            Arc::new(Int64Array::from(vec![2020])),
+        ],
+    )
+    .unwrap();
+
+    LanceCatalog::new()
+        .with_node_dataset("Person", write_dataset(dir, "person", person).await)
+        .with_relationship_dataset("KNOWS", write_dataset(dir, "knows", knows).await)
+}
+
+async fn write(catalog: &mut LanceCatalog, query: &str) -> lance_graph::Result<WriteSummary> {
+    CypherQuery::new(query)
+        .unwrap()
+        .with_config(graph_config())
+        .execute_write(catalog)
+        .await
+}
+
+async fn read(catalog: &LanceCatalog, query: &str) -> RecordBatch {
+    CypherQuery::new(query)
+        .unwrap()
+        .with_config(graph_config())
+        .execute_with_catalog(Arc::new(catalog.clone()))
+        .await
+        .unwrap()
+}
+
+fn strings(batch: &RecordBatch, column: usize) -> Vec<String> {
+    let values = batch
+        .column(column)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+    values.iter().map(|v| v.unwrap().to_string()).collect()
+}
+
+fn ints(batch: &RecordBatch, column: usize) -> Vec<i64> {
+    let values = batch
+        .column(column)
+        .as_any()
+        .downcast_ref::<Int64Array>()
+        .unwrap();
+    values.values().to_vec()
+}
+
+#[tokio::test]
+async fn test_create_nodes() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut catalog = create_catalog(&dir).await;
+
+    let summary = write(
+        &mut catalog,
+        "CREATE (n:Person {id: 10, name: 'Dave', age: 52})",
+    )
+    .await
+    .unwrap();
+    assert_eq!(summary.nodes_created, 1);
+    assert_eq!(summary.relationships_created, 0);
+    let out = read(
+        &catalog,
+        "MATCH (p:Person) WHERE p.age > 40 RETURN p.id, p.name ORDER BY p.id",
+    )
+    .await;
+    assert_eq!(ints(&out, 0), vec![3, 10]);
+    assert_eq!(strings(&out, 1), vec!["Carol", "Dave"]);
+
+    // A left-out integer key is numbered on from the largest one, once per node
+    write(
+        &mut catalog,
+        "CREATE (:Person {name: 'Erin'}), (:Person {name: 'Finn', age: 19})",
+    )
+    .await
+    .unwrap();
+    let out = read(
+        &catalog,
+        "MATCH (p:Person) WHERE p.id > 10 RETURN p.id, p.name ORDER BY p.id",
+    )
+    .await;
+    assert_eq!(ints(&out, 0), vec![11, 12]);
+    assert_eq!(strings(&out, 1), vec!["Erin", "Finn"]);
+    let out = read(&catalog, "MATCH (p:Person {name: 'Erin'}) RETURN p.age").await;
+    assert!(out.column(0).is_null(0));
+}
+
+#[tokio::test]
+async fn test_create_relationships() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut catalog = create_catalog(&dir).await;
+
+    // Between matched nodes, with a parameter property
+    let summary = CypherQuery::new(
+        "MATCH (a:Person {name: 'Bob'}), (b:Person {name: 'Carol'}) \
+         CREATE (a)-[:KNOWS {since_year: $year}]->(b)",
+    )
+    .unwrap()
+    .with_config(graph_config())
+    .with_parameter("year", 2024)
+    .execute_write(&mut catalog)
+    .await
+    .unwrap();
+    assert_eq!(summary.relationships_created, 1);
+    let out = read(
+        &catalog,
+        "MATCH (a:Person)-[r:KNOWS]->(b:Person) RETURN a.name, b.name, r.since_year \
+         ORDER BY r.since_year",
+    )
+    .await;
+    assert_eq!(strings(&out, 0), vec!["Alice", "Bob"]);
+    assert_eq!(strings(&out, 1), vec!["Bob", "Carol"]);
+    assert_eq!(ints(&out, 2), vec![2020, 2024]);
+
+    // New nodes are added before the relationships referencing their generated keys
+    let summary = write(
+        &mut catalog,
+        "CREATE (a:Person {name: 'Gus'})<-[:KNOWS]-(b:Person {name: 'Hana'})",
+    )
+    .await
+    .unwrap();
+    assert_eq!(summary.nodes_created, 2);
+    assert_eq!(summary.relationships_created, 1);
+    let out = read(
+        &catalog,
+        "MATCH (a:Person)-[:KNOWS]->(b:Person {name: 'Gus'}) RETURN a.name, a.id, b.id",
+    )
+    .await;
+    assert_eq!(strings(&out, 0), vec!["Hana"]);
+    assert_eq!(ints(&out, 1), vec![5]);
+    assert_eq!(ints(&out, 2), vec![4]);
+
+    // Once per matched row, with values of the row
+    let summary = write(
+        &mut catalog,
+        "MATCH (p:Person) WHERE p.age > 30 \
+         CREATE (p)-[:KNOWS]->(:Person {name: 'Friend', age: p.age})",
+    )
+    .await
+    .unwrap();
+    assert_eq!(summary.nodes_created, 2);
+    assert_eq!(summary.relationships_created, 2);
+    let out = read(
+        &catalog,
+        "MATCH (a:Person)-[:KNOWS]->(b:Person {name: 'Friend'}) RETURN a.name, b.age \
+         ORDER BY a.name",
+    )
+    .await;
+    assert_eq!(strings(&out, 0), vec!["Bob", "Carol"]);
+    assert_eq!(ints(&out, 1), vec![34, 41]);
+}
+
+#[tokio::test]
+async fn test_create_failures_leave_datasets_unchanged() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut catalog = create_catalog(&dir).await;
+    let version = catalog.node_dataset("Person").unwrap().version().version;
+
+    // FOLLOWS has no dataset, so the node created with it is not kept either
+    let result = write(
+        &mut catalog,
+        "MATCH (a:Person {name: 'Alice'}) CREATE (a)-[:FOLLOWS]->(:Person {name: 'Ivy'})",
+    )
+    .await;
+    assert!(result.is_err());
+    for query in [
+        "CREATE (n {name: 'Ivy'})",
+        "CREATE (n:Person {nickname: 'Ivy'})",
+        "MATCH (a:Person) CREATE (a:Person {name: 'Ivy'})",
+        "MATCH (a:Person), (b:Person) CREATE (a)-[:KNOWS]-(b)",
+        "CREATE (n:Person {name: 'Ivy'}) RETURN n.name",
+        "MERGE (n:Person {name: 'Ivy'})",
+        "MERGE (n:Person {id: 1}) ON MATCH SET n.id = 2",
+        "MATCH (a:Person) MERGE (a)-[:KNOWS]->(:Person {id: 9})",
+        "CREATE (n:Person {name: 'Ivy'}) SET n.age = 1",
+        "MATCH (p:Person) SET p.id = 5",
+        "MATCH (p:Person) REMOVE p.nickname",
+    ] {
+        assert!(write(&mut catalog, query).await.is_err(), "{}", query);
+    }
+    assert_eq!(
+        catalog.node_dataset("Person").unwrap().version().version,
+        version
+    );
+
+    // Read-only execution does not run write clauses
+    let result = CypherQuery::new("CREATE (n:Person {name: 'Ivy'})")
+        .unwrap()
+        .with_config(graph_config())
+        .execute_with_catalog(Arc::new(catalog.clone()))
+        .await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn test_merge_nodes() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut catalog = create_catalog(&dir).await;
+
+    // An existing key only runs ON MATCH; the other pattern properties are for creation
+    let summary = write(
+        &mut catalog,
+        "MERGE (n:Person {id: 1, name: 'Ignored'}) \
+         ON CREATE SET n.age = 0 ON MATCH SET n.age = n.age + 1",
+    )
+    .await
+    .unwrap();
+    assert_eq!(summary.nodes_created, 0);
+    assert_eq!(summary.properties_set, 1);
+    let out = read(&catalog, "MATCH (p:Person {id: 1}) RETURN p.name, p.age").await;
+    assert_eq!(strings(&out, 0), vec!["Alice"]);
+    assert_eq!(ints(&out, 1), vec![29]);
+
+    // Once per row, with a repeated key written once
+    let summary = write(
+        &mut catalog,
+        "UNWIND [2, 7, 7] AS i MERGE (n:Person {id: i, name: 'New'}) \
+         ON CREATE SET n.age = i * 10 ON MATCH SET n.name = 'Seen'",
+    )
+    .await
+    .unwrap();
+    assert_eq!(summary.nodes_created, 1);
+    assert_eq!(summary.properties_set, 2);
+    let out = read(
+        &catalog,
+        "MATCH (p:Person) RETURN p.id, p.name, p.age ORDER BY p.id",
+    )
+    .await;
+    assert_eq!(ints(&out, 0), vec![1, 2, 3, 7]);
+    assert_eq!(strings(&out, 1), vec!["Alice", "Seen", "Carol", "New"]);
+    assert_eq!(ints(&out, 2), vec![29, 34, 41, 70]);
+
+    // Running it again finds the node
+    for _ in 0..2 {
+        let summary = write(&mut catalog, "MERGE (n:Person {id: 7})")
+            .await
+            .unwrap();
+        assert_eq!(summary.nodes_created, 0);
+    }
+    let out = read(&catalog, "MATCH (p:Person) RETURN count(p)").await;
+    assert_eq!(ints(&out, 0), vec![4]);
+}
+
+#[tokio::test]
+async fn test_merge_relationships() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut catalog = create_catalog(&dir).await;
+
+    // Between bound nodes, found by their keys
+    let summary = write(
+        &mut catalog,
+        "MATCH (a:Person {id: 1}), (b:Person {id: 2}) \
+         MERGE (a)-[r:KNOWS {since_year: 1999}]->(b) \
+         ON MATCH SET r.since_year = r.since_year + 1",
+    )
+    .await
+    .unwrap();
+    assert_eq!(summary.relationships_created, 0);
+    let out = read(
+        &catalog,
+        "MATCH (:Person)-[r:KNOWS]->(:Person) RETURN r.since_year",
+    )
+    .await;
+    assert_eq!(ints(&out, 0), vec![2021]);
+
+    // Between merged nodes, and CREATE connecting a merged node
+    let summary = write(
+        &mut catalog,
+        "MERGE (a:Person {id: 1}) MERGE (c:Person {id: 20, name: 'Zed'}) \
+         MERGE (a)-[:KNOWS {since_year: 2025}]->(c) \
+         CREATE (c)-[:KNOWS]->(:Person {name: 'Kid'})",
+    )
+    .await
+    .unwrap();
+    assert_eq!(summary.nodes_created, 2);
+    assert_eq!(summary.relationships_created, 2);
+    let out = read(
+        &catalog,
+        "MATCH (a:Person)-[r:KNOWS]->(b:Person {id: 20}) RETURN a.name, b.name, r.since_year",
+    )
+    .await;
+    assert_eq!(strings(&out, 0), vec!["Alice"]);
+    assert_eq!(strings(&out, 1), vec!["Zed"]);
+    assert_eq!(ints(&out, 2), vec![2025]);
+    let out = read(
+        &catalog,
+        "MATCH (a:Person {name: 'Zed'})-[:KNOWS]->(b:Person) RETURN b.name",
+    )
+    .await;
+    assert_eq!(strings(&out, 0), vec!["Kid"]);
+
+    // Both once more finds everything
+    let summary = write(
+        &mut catalog,
+        "MERGE (a:Person {id: 1}) MERGE (c:Person {id: 20}) MERGE (a)-[:KNOWS]->(c)",
+    )
+    .await
+    .unwrap();
+    assert_eq!(summary, WriteSummary::default());
+}
+
+#[tokio::test]
+async fn test_set_and_remove_properties() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut catalog = create_catalog(&dir).await;
+
+    // Values differing per row, and one shared by all rows
+    let summary = write(
+        &mut catalog,
+        "MATCH (p:Person) WHERE p.age > 30 SET p.age = p.age + 1, p.name = 'Older'",
+    )
+    .await
+    .unwrap();
+    assert_eq!(summary.properties_set, 4);
+    assert_eq!(summary.nodes_created, 0);
+    let out = read(
+        &catalog,
+        "MATCH (p:Person) RETURN p.name, p.age ORDER BY p.id",
+    )
+    .await;
+    assert_eq!(strings(&out, 0), vec!["Alice", "Older", "Older"]);
+    assert_eq!(ints(&out, 1), vec![28, 35, 42]);
+
+    // REMOVE clears a property; a later assignment to it wins
+    write(
+        &mut catalog,
+        "MATCH (p:Person {id: 1}) SET p.name = 'Al' REMOVE p.age SET p.name = 'Ali'",
+    )
+    .await
+    .unwrap();
+    let out = read(&catalog, "MATCH (p:Person {id: 1}) RETURN p.name, p.age").await;
+    assert_eq!(strings(&out, 0), vec!["Ali"]);
+    assert!(out.column(1).is_null(0));
+
+    // Relationships, by the nodes they connect, with a parameter
+    let summary =
+        CypherQuery::new("MATCH (a:Person)-[r:KNOWS]->(b:Person) SET r.since_year = $year")
+            .unwrap()
+            .with_config(graph_config())
+            .with_parameter("year", 1990)
+            .execute_write(&mut catalog)
+            .await
+            .unwrap();
+    assert_eq!(summary.properties_set, 1);
+    let out = read(
+        &catalog,
+        "MATCH (:Person)-[r:KNOWS]->(:Person) RETURN r.since_year",
+    )
+    .await;
+    assert_eq!(ints(&out, 0), vec![1990]);
+
+    // Nothing matched changes nothing
+    let version = catalog.node_dataset("Person").unwrap().version().version;
+    let summary = write(&mut catalog, "MATCH (p:Person {id: 99}) SET p.age = 1")
+        .await
+        .unwrap();
+    assert_eq!(summary, WriteSummary::default());
+    assert_eq!(
+        catalog.node_dataset("Person").unwrap().version().version,
+        version
+    );
+}
+
+#[tokio::test]
+async fn test_delete_nodes_and_relationships() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut catalog = create_catalog(&dir).await;
+    write(
+        &mut catalog,
+        "MATCH (b:Person {id: 2}), (c:Person {id: 3}) CREATE (b)-[:KNOWS {since_year: 2022}]->(c)",
+    )
+    .await
+    .unwrap();
+
+    // A node with relationships needs DETACH, unless they are deleted too
+    let version = catalog.node_dataset("Person").unwrap().version().version;
+    let result = write(&mut catalog, "MATCH (p:Person {id: 2}) DELETE p").await;
+    assert!(result.is_err());
+    assert_eq!(
+        catalog.node_dataset("Person").unwrap().version().version,
+        version
+    );
+
+    let summary = write(
+        &mut catalog,
+        "MATCH (a:Person {id: 1})-[r:KNOWS]->(b:Person) DELETE r",
+    )
+    .await
+    .unwrap();
+    assert_eq!(summary.relationships_deleted, 1);
+    assert_eq!(summary.nodes_deleted, 0);
+
+    let summary = write(
+        &mut catalog,
+        "MATCH (a:Person {id: 1})-[r:KNOWS]->(b:Person) DELETE r",
+    )
+    .await
+    .unwrap();
+    assert_eq!(summary, WriteSummary::default());
+
+    // Alice has no relationships left
+    let summary = write(&mut catalog, "MATCH (p:Person {name: 'Alice'}) DELETE p")
+        .await
+        .unwrap();
+    assert_eq!(summary.nodes_deleted, 1);
+
+    // Bob's relationship to Carol goes with him
+    let summary = write(&mut catalog, "MATCH (p:Person {id: 2}) DETACH DELETE p")
+        .await
+        .unwrap();
+    assert_eq!(summary.nodes_deleted, 1);
+    assert_eq!(summary.relationships_deleted, 1);
+
+    let out = read(&catalog, "MATCH (p:Person) RETURN p.name").await;
+    assert_eq!(strings(&out, 0), vec!["Carol"]);
+    let out = read(
+        &catalog,
+        "MATCH (:Person)-[r:KNOWS]->(:Person) RETURN r.since_year",
+    )
+    .await;
+    assert_eq!(out.num_rows(), 0);
+
+    assert!(write(&mut catalog, "MATCH (p:Person) DELETE q")
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_delete_leaves_relationships_of_other_labels() {
+    let dir = tempfile::tempdir().unwrap();
+    let company = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("name", DataType::Utf8, true),
+        ])),
+        vec![
+            Arc::new(Int64Array::from(vec![1])),
+            Arc::new(StringArray::from(vec!["Acme"])),
+        ],
+    )
+    .unwrap();
+    let mut catalog = create_catalog(&dir)
+        .await
+        .with_node_dataset("Company", write_dataset(&dir, "company", company).await);
+    let delete = |config: GraphConfig| {
+        CypherQuery::new("MATCH (c:Company {id: 1}) DETACH DELETE c")
+            .unwrap()
+            .with_config(config)
+    };
+
+    // KNOWS names no endpoint labels, so its rows with key 1 may be Alice's or Acme's
+    let config = GraphConfig::builder()
+        .with_node_label("Person", "id")
+        .with_node_label("Company", "id")
+        .with_relationship("KNOWS", "src_id", "dst_id")
+        .build()
+        .unwrap();
+    let error = delete(config)
+        .execute_write(&mut catalog)
+        .await
+        .unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .contains("'Person' nodes or 'Company' nodes")
+            || error
+                .to_string()
+                .contains("'Company' nodes or 'Person' nodes"),
+        "{}",
+        error
+    );
+
+    let config = GraphConfig::builder()
+        .with_node_label("Person", "id")
+        .with_node_label("Company", "id")
+        .with_relationship_mapping(
+            RelationshipMapping::new("KNOWS", "src_id", "dst_id")
+                .with_endpoint_labels("Person", "Person"),
+        )
+        .build()
+        .unwrap();
+    let summary = delete(config).execute_write(&mut catalog).await.unwrap();
+    assert_eq!(summary.nodes_deleted, 1);
+    assert_eq!(summary.relationships_deleted, 0);
+    let out = read(
+        &catalog,
+        "MATCH (a:Person)-[r:KNOWS]->(b:Person) RETURN a.name, b.name",
+    )
+    .await;
+    assert_eq!(strings(&out, 0), vec!["Alice"]);
+}
+
+#[tokio::test]
+async fn test_write_more_rows_than_one_filter_lists() {
+    // A chain of 2500 people, each knowing the next, more than one SQL filter lists
+    let dir = tempfile::tempdir().unwrap();
+    let ids: Vec<i64> = (0..2500).collect();
+    let person = RecordBatch::try_from_iter(vec![
+        ("id", Arc::new(Int64Array::from(ids.clone())) as _),
+        ("age", Arc::new(Int64Array::from(vec![30; ids.len()])) as _),
+    ])
+    .unwrap();
+    let knows = RecordBatch::try_from_iter(vec![
+        (
+            "src_id",
+            Arc::new(Int64Array::from(ids[..2499].to_vec())) as _,
+        ),
+        ("dst_id", Arc::new(Int64Array::from(ids[1..].to_vec())) as _),
+    ])
+    .unwrap();
+    let mut catalog = LanceCatalog::new()
+        .with_node_dataset("Person", write_dataset(&dir, "person", person).await)
+        .with_relationship_dataset("KNOWS", write_dataset(&dir, "knows", knows).await);
+
+    let summary = write(&mut catalog, "MATCH (p:Person) SET p.age = 31")
+        .await
+        .unwrap();
+    assert_eq!(summary.properties_set, 2500);
+    let out = read(
+        &catalog,
+        "MATCH (p:Person) WHERE p.age = 31 RETURN count(*) AS n",
+    )
+    .await;
+    assert_eq!(ints(&out, 0), vec![2500]);
+
+    // The relationship from 99 to 100 stays, so 100 cannot be deleted without it
+    let result = write(&mut catalog, "MATCH (p:Person) WHERE p.id >= 100 DELETE p").await;
+    assert!(result.is_err());
+
+    // Relationships between two deleted nodes are counted once
+    let summary = write(
+        &mut catalog,
+        "MATCH (p:Person) WHERE p.id >= 100 DETACH DELETE p",
+    )
+    .await
+    .unwrap();
+    assert_eq!(summary.nodes_deleted, 2400);
+    assert_eq!(summary.relationships_deleted, 2400);
+    let out = read(&catalog, "MATCH (p:Person) RETURN count(*) AS n").await;
+    assert_eq!(ints(&out, 0), vec![100]);
+    let out = read(
+        &catalog,
+        "MATCH (:Person)-[r:KNOWS]->(:Person) RETURN count(*) AS n",
+    )
+    .await;
+    assert_eq!(ints(&out, 0), vec![99]);
+}
+
+#[tokio::test]
+async fn test_write_results_to_dataset() {
+    let dir = tempfile::tempdir().unwrap();
+    let catalog = Arc::new(create_catalog(&dir).await);
+    let uri = dir.path().join("older.lance");
+    let uri = uri.to_str().unwrap();
+    let query = CypherQuery::new("MATCH (p:Person) WHERE p.age > 30 RETURN p.name, p.age AS age")
+        .unwrap()
+        .with_config(graph_config());
+
+    let dataset = query
+        .write_to(catalog.clone(), uri, WriteMode::Create)
+        .await
+        .unwrap();
+    assert_eq!(dataset.count_rows(None).await.unwrap(), 2);
+    let names: Vec<&str> = dataset
+        .schema()
+        .fields
+        .iter()
+        .map(|f| f.name.as_str())
+        .collect();
+    assert_eq!(names, vec!["p_name", "age"]);
+
+    assert!(query
+        .write_to(catalog.clone(), uri, WriteMode::Create)
+        .await
+        .is_err());
+    let dataset = query
+        .write_to(catalog.clone(), uri, WriteMode::Append)
+        .await
+        .unwrap();
+    assert_eq!(dataset.count_rows(None).await.unwrap(), 4);
+    let dataset = query
+        .write_to(catalog, uri, WriteMode::Overwrite)
+        .await
+        .unwrap();
+    assert_eq!(dataset.count_rows(None).await.unwrap(), 2);
+}
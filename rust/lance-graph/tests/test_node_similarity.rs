@@ -0,0 +1,125 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Node similarity with `CALL graph.nodeSimilarity`
+
+use std::sync::Arc;
+
+use arrow::array::AsArray;
+use arrow::datatypes::{Float64Type, Int64Type};
+use arrow_array::{Int64Array, RecordBatch};
+use arrow_schema::{DataType, Field, Schema};
+use lance_graph::GraphConfig;
+
+mod common;
+use common::{named_with_ids, TestGraph};
+
+/// Ann (1) bought a lamp, a desk and a chair (101-103); Ben (2) the lamp and the desk;
+/// Cid (3) the chair and a rug (104)
+fn purchases() -> TestGraph {
+    let config = GraphConfig::builder()
+        .with_node_label("Customer", "id")
+        .with_node_label("Product", "id")
+        .with_relationship("BOUGHT", "customer_id", "product_id")
+        .build()
+        .unwrap();
+    let bought = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("customer_id", DataType::Int64, false),
+            Field::new("product_id", DataType::Int64, false),
+        ])),
+        vec![
+            Arc::new(Int64Array::from(vec![1, 1, 1, 2, 2, 3, 3])),
+            Arc::new(Int64Array::from(vec![101, 102, 103, 101, 102, 103, 104])),
+        ],
+    )
+    .unwrap();
+    TestGraph::new(config)
+        .with_nodes("Customer", named_with_ids(1..=3, &["Ann", "Ben", "Cid"]))
+        .with_nodes(
+            "Product",
+            named_with_ids(101..=104, &["lamp", "desk", "chair", "rug"]),
+        )
+        .with_relationships("BOUGHT", bought)
+}
+
+/// The `node1`, `node2` and `similarity` rows of a result
+fn pairs(batch: &RecordBatch) -> Vec<(i64, i64, f64)> {
+    let node1 = batch.column(0).as_primitive::<Int64Type>();
+    let node2 = batch.column(1).as_primitive::<Int64Type>();
+    let similarity = batch.column(2).as_primitive::<Float64Type>();
+    (0..batch.num_rows())
+        .map(|row| (node1.value(row), node2.value(row), similarity.value(row)))
+        .collect()
+}
+
+#[tokio::test]
+async fn test_node_similarity() {
+    let result = purchases()
+        .run(
+            "CALL graph.nodeSimilarity() YIELD node1, node2, similarity \
+             RETURN node1, node2, similarity",
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        pairs(&result),
+        vec![
+            (1, 2, 2.0 / 3.0),
+            (1, 3, 0.25),
+            (2, 1, 2.0 / 3.0),
+            (3, 1, 0.25),
+        ]
+    );
+
+    let result = purchases()
+        .run(
+            "CALL graph.nodeSimilarity({similarityMetric: 'overlap', topK: 1}) \
+             YIELD node1, node2, similarity RETURN node1, node2, similarity",
+        )
+        .await
+        .unwrap();
+    // Only the best match of each customer
+    let best: Vec<_> = pairs(&result).iter().map(|p| (p.0, p.1)).collect();
+    assert_eq!(best, vec![(1, 2), (2, 1), (3, 1)]);
+}
+
+#[tokio::test]
+async fn test_customers_also_bought() {
+    // Products bought by the customers most like Ben
+    let result = purchases()
+        .run(
+            "CALL graph.nodeSimilarity({similarityCutoff: 0.5}) YIELD node1, node2 \
+             MATCH (c:Customer)-[:BOUGHT]->(p:Product) \
+             WHERE node1 = 2 AND c.id = node2 \
+             RETURN p.name ORDER BY p.name",
+        )
+        .await
+        .unwrap();
+    let names = result.column(0).as_string::<i32>();
+    assert_eq!(
+        names.iter().flatten().collect::<Vec<_>>(),
+        vec!["chair", "desk", "lamp"]
+    );
+}
+
+#[tokio::test]
+async fn test_node_similarity_errors() {
+    for (cypher, message) in [
+        (
+            "CALL graph.nodeSimilarity({similarityMetric: 'cosine'}) YIELD node1 RETURN node1",
+            "similarityMetric must be 'jaccard' or 'overlap'",
+        ),
+        (
+            "CALL graph.nodeSimilarity({topK: 0}) YIELD node1 RETURN node1",
+            "topK must be positive",
+        ),
+        (
+            "CALL graph.nodeSimilarity({similarityCutoff: 2.0}) YIELD node1 RETURN node1",
+            "similarityCutoff must be between 0 and 1",
+        ),
+    ] {
+        let error = purchases().run(cypher).await.unwrap_err().to_string();
+        assert!(error.contains(message), "{}: {}", cypher, error);
+    }
+}
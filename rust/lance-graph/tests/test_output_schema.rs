@@ -0,0 +1,125 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Output schemas of queries, planned without executing them
+
+use std::sync::Arc;
+
+use arrow_array::{Int64Array, RecordBatch, StringArray};
+use arrow_schema::{DataType, Field, Schema};
+use datafusion::datasource::MemTable;
+use lance_graph::source_catalog::ProviderCatalog;
+use lance_graph::{CypherQuery, GraphConfig};
+use serde_json::json;
+
+fn graph_config() -> GraphConfig {
+    GraphConfig::builder()
+        .with_node_label("Person", "id")
+        .with_relationship("KNOWS", "src_id", "dst_id")
+        .build()
+        .unwrap()
+}
+
+/// Alice (1, 28) knows Bob (2, 34)
+fn catalog() -> Arc<ProviderCatalog> {
+    let person = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("name", DataType::Utf8, false),
+            Field::new("age", DataType::Int64, false),
+        ])),
+        vec![
+            Arc::new(Int64Array::from(vec![1, 2])),
+            Arc::new(StringArray::from(vec!["Alice", "Bob"])),
+            Arc::new(Int64Array::from(vec![28, 34])),
+        ],
+    )
+    .unwrap();
+    let knows = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("src_id", DataType::Int64, false),
+            Field::new("dst_id", DataType::Int64, false),
+        ])),
+        vec![
+            Arc::new(Int64Array::from(vec![1])),
+            Arc::new(Int64Array::from(vec![2])),
+        ],
+    )
+    .unwrap();
+    let table = |batch: RecordBatch| {
+        Arc::new(MemTable::try_new(batch.schema(), vec![vec![batch]]).unwrap())
+    };
+    Arc::new(
+        ProviderCatalog::new()
+            .with_node_table("Person", table(person))
+            .with_relationship_table("KNOWS", table(knows)),
+    )
+}
+
+fn columns(schema: &Schema) -> Vec<(String, DataType)> {
+    schema
+        .fields()
+        .iter()
+        .map(|field| (field.name().clone(), field.data_type().clone()))
+        .collect()
+}
+
+#[tokio::test]
+async fn test_output_schema_matches_executed_results() {
+    let query = CypherQuery::new(
+        "MATCH (a:Person)-[:KNOWS]->(b:Person) \
+         RETURN a.name, b.age AS age, count(*) AS friends",
+    )
+    .unwrap()
+    .with_config(graph_config());
+
+    let schema = query.output_schema(catalog()).unwrap();
+    assert_eq!(
+        columns(&schema),
+        vec![
+            ("a.name".to_string(), DataType::Utf8),
+            ("age".to_string(), DataType::Int64),
+            ("friends".to_string(), DataType::Int64),
+        ]
+    );
+
+    let out = query.execute_with_catalog(catalog()).await.unwrap();
+    assert_eq!(columns(&schema), columns(&out.schema()));
+}
+
+#[test]
+fn test_output_schema_with_parameters() {
+    let query = CypherQuery::new("MATCH (p:Person) WHERE p.age > $min RETURN p.name")
+        .unwrap()
+        .with_config(graph_config());
+    let unbound = query.output_schema(catalog()).unwrap();
+    let bound = query
+        .with_parameter("min", json!(30))
+        .output_schema(catalog())
+        .unwrap();
+    assert_eq!(columns(&unbound), columns(&bound));
+    assert_eq!(
+        columns(&bound),
+        vec![("p.name".to_string(), DataType::Utf8)]
+    );
+}
+
+#[test]
+fn test_output_schema_of_explain() {
+    let query = CypherQuery::new("EXPLAIN MATCH (p:Person) RETURN p.name")
+        .unwrap()
+        .with_config(graph_config());
+    let schema = query.output_schema(catalog()).unwrap();
+    assert_eq!(schema.field(0).name(), "plan_type");
+    assert_eq!(schema.field(1).name(), "plan");
+}
+
+#[test]
+fn test_output_schema_needs_every_parameter_once_any_is_bound() {
+    let query =
+        CypherQuery::new("MATCH (p:Person) WHERE p.age > $min AND p.name <> $name RETURN p.name")
+            .unwrap()
+            .with_config(graph_config())
+            .with_parameter("min", json!(30));
+    assert!(query.output_schema(catalog()).is_err());
+}
@@ -0,0 +1,126 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! PageRank centrality with `CALL graph.pageRank`
+
+use std::sync::Arc;
+
+use arrow::array::AsArray;
+use arrow::datatypes::{Float64Type, Int64Type};
+use lance_graph::source_catalog::GraphSourceCatalog;
+use lance_graph::{GraphConfig, RelationshipMapping};
+
+mod common;
+use common::{edges, named, TestGraph};
+
+/// Alice (1), Bob (2) and Carol (3) know each other in a cycle; Dan (4) knows Carol
+fn social() -> TestGraph {
+    TestGraph::people(&["KNOWS"])
+        .with_nodes("Person", named(&["Alice", "Bob", "Carol", "Dan"]))
+        .with_relationships("KNOWS", edges(&[(1, 2), (2, 3), (3, 1), (4, 3)]))
+}
+
+async fn assert_page_rank(catalog: Arc<dyn GraphSourceCatalog>) {
+    let graph = social();
+    let result = graph
+        .run_with(
+            "CALL graph.pageRank() YIELD nodeId, score RETURN nodeId, score",
+            catalog.clone(),
+        )
+        .await
+        .unwrap();
+    let ids = result.column(0).as_primitive::<Int64Type>();
+    let scores = result.column(1).as_primitive::<Float64Type>();
+    assert_eq!(ids.values(), &[3, 1, 2, 4]);
+    assert!((scores.values().iter().sum::<f64>() - 1.0).abs() < 1e-9);
+
+    let result = graph
+        .run_with(
+            "CALL graph.pageRank({dampingFactor: 0.5, maxIterations: 1}) YIELD nodeId, score \
+             RETURN score ORDER BY score",
+            catalog.clone(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        result.column(0).as_primitive::<Float64Type>().value(0),
+        0.125
+    );
+
+    let result = graph
+        .run_with(
+            "CALL graph.pageRank({relationshipTypes: ['KNOWS']}) YIELD nodeId, score \
+             MATCH (p:Person) WHERE p.id = nodeId \
+             RETURN p.name ORDER BY score DESC LIMIT 1",
+            catalog,
+        )
+        .await
+        .unwrap();
+    assert_eq!(result.column(0).as_string::<i32>().value(0), "Carol");
+}
+
+#[tokio::test]
+async fn test_page_rank_over_in_memory_tables() {
+    assert_page_rank(social().memory_catalog()).await;
+}
+
+#[tokio::test]
+async fn test_page_rank_over_lance_datasets() {
+    let dir = tempfile::tempdir().unwrap();
+    assert_page_rank(social().lance_catalog(&dir).await).await;
+}
+
+#[tokio::test]
+async fn test_page_rank_errors() {
+    for (cypher, message) in [
+        (
+            "CALL graph.pageRank({damping: 0.5}) YIELD score RETURN score",
+            "has no option 'damping'",
+        ),
+        (
+            "CALL graph.pageRank({dampingFactor: 1.5}) YIELD score RETURN score",
+            "dampingFactor must be at least 0 and less than 1",
+        ),
+        (
+            "CALL graph.pageRank({relationshipTypes: ['LIKES']}) YIELD score RETURN score",
+            "unknown relationship type 'LIKES'",
+        ),
+    ] {
+        let error = social().run(cypher).await.unwrap_err().to_string();
+        assert!(error.contains(message), "{}: {}", cypher, error);
+    }
+}
+
+#[tokio::test]
+async fn test_page_rank_over_every_stored_type() {
+    // LIKES has no table and is left out; PAIRED has one, which cannot be read
+    let config = GraphConfig::builder()
+        .with_node_label("Person", "id")
+        .with_relationship("KNOWS", "src_id", "dst_id")
+        .with_relationship("LIKES", "src_id", "dst_id")
+        .with_relationship_mapping(
+            RelationshipMapping::new("PAIRED", "src_id", "dst_id").with_composite_endpoints(
+                vec!["src_id".to_string(), "src_id".to_string()],
+                vec!["dst_id".to_string(), "dst_id".to_string()],
+            ),
+        )
+        .build()
+        .unwrap();
+    let graph = TestGraph::new(config)
+        .with_nodes("Person", named(&["Alice", "Bob", "Carol", "Dan"]))
+        .with_relationships("KNOWS", edges(&[(1, 2), (2, 3), (3, 1), (4, 3)]));
+    let cypher = "CALL graph.pageRank() YIELD nodeId RETURN nodeId";
+    let result = graph.run(cypher).await.unwrap();
+    assert_eq!(result.num_rows(), 4);
+
+    let error = graph
+        .with_relationships("PAIRED", edges(&[(1, 2)]))
+        .run(cypher)
+        .await
+        .unwrap_err();
+    assert!(
+        error.to_string().contains("'PAIRED' has a composite key"),
+        "{}",
+        error
+    );
+}
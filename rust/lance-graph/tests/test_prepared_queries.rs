@@ -0,0 +1,235 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Prepared queries and the plan cache
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use arrow_array::{Int64Array, RecordBatch, StringArray};
+use arrow_schema::{DataType, Field, Schema};
+use datafusion::datasource::MemTable;
+use lance_graph::source_catalog::ProviderCatalog;
+use lance_graph::{CypherQuery, GraphConfig, PlanCache};
+use serde_json::json;
+
+fn graph_config() -> GraphConfig {
+    GraphConfig::builder()
+        .with_node_label("Person", "id")
+        .with_relationship("KNOWS", "src_id", "dst_id")
+        .build()
+        .unwrap()
+}
+
+/// Alice (1, 28), Bob (2, 34), Carol (3, 41) and Dave (4, 45); Alice knows Bob and
+/// Carol
+fn catalog() -> Arc<ProviderCatalog> {
+    let person = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("name", DataType::Utf8, false),
+            Field::new("age", DataType::Int64, false),
+        ])),
+        vec![
+            Arc::new(Int64Array::from(vec![1, 2, 3, 4])),
+            Arc::new(StringArray::from(vec!["Alice", "Bob", "Carol", "Dave"])),
+            Arc::new(Int64Array::from(vec![28, 34, 41, 45])),
+        ],
+    )
+    .unwrap();
+    let knows = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("src_id", DataType::Int64, false),
+            Field::new("dst_id", DataType::Int64, false),
+        ])),
+        vec![
+            Arc::new(Int64Array::from(vec![1, 1])),
+            Arc::new(Int64Array::from(vec![2, 3])),
+        ],
+    )
+    .unwrap();
+    let table = |batch: RecordBatch| {
+        Arc::new(MemTable::try_new(batch.schema(), vec![vec![batch]]).unwrap())
+    };
+    Arc::new(
+        ProviderCatalog::new()
+            .with_node_table("Person", table(person))
+            .with_relationship_table("KNOWS", table(knows)),
+    )
+}
+
+fn params(values: &[(&str, serde_json::Value)]) -> HashMap<String, serde_json::Value> {
+    values
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.clone()))
+        .collect()
+}
+
+fn strings(batch: &RecordBatch, column: usize) -> Vec<String> {
+    let values = batch
+        .column(column)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+    values.iter().map(|v| v.unwrap().to_string()).collect()
+}
+
+#[tokio::test]
+async fn test_prepared_query_executes_with_different_parameters() {
+    let prepared = CypherQuery::new(
+        "MATCH (a:Person)-[:KNOWS]->(b:Person) WHERE b.age > $min AND a.name = $name \
+         RETURN b.name ORDER BY b.name",
+    )
+    .unwrap()
+    .with_config(graph_config())
+    .prepare(catalog())
+    .unwrap();
+    assert!(prepared.is_planned());
+
+    let out = prepared
+        .execute(params(&[("min", json!(30)), ("name", json!("Alice"))]))
+        .await
+        .unwrap();
+    assert_eq!(strings(&out, 0), vec!["Bob", "Carol"]);
+    let out = prepared
+        .execute(params(&[("min", json!(40)), ("name", json!("Alice"))]))
+        .await
+        .unwrap();
+    assert_eq!(strings(&out, 0), vec!["Carol"]);
+    let out = prepared
+        .execute(params(&[("min", json!(0)), ("name", json!("Bob"))]))
+        .await
+        .unwrap();
+    assert_eq!(out.num_rows(), 0);
+
+    // Every parameter needs a value
+    assert!(prepared
+        .execute(params(&[("min", json!(0))]))
+        .await
+        .is_err());
+    // Values are checked against the properties they are compared with
+    assert!(prepared
+        .execute(params(&[("min", json!("old")), ("name", json!("Alice"))]))
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_prepared_point_lookup() {
+    let prepared = CypherQuery::new("MATCH (p:Person {id: $id}) RETURN p.name")
+        .unwrap()
+        .with_config(graph_config())
+        .prepare(catalog())
+        .unwrap();
+    assert!(prepared.is_planned());
+    for (id, name) in [(1, "Alice"), (4, "Dave")] {
+        let out = prepared
+            .execute(params(&[("id", json!(id))]))
+            .await
+            .unwrap();
+        assert_eq!(strings(&out, 0), vec![name]);
+    }
+
+    // Returned parameters take the type of each execution's value
+    let prepared = CypherQuery::new("MATCH (p:Person {id: $id}) RETURN p.name, $tag AS tag")
+        .unwrap()
+        .with_config(graph_config())
+        .prepare(catalog())
+        .unwrap();
+    let out = prepared
+        .execute(params(&[("id", json!(3)), ("tag", json!("x"))]))
+        .await
+        .unwrap();
+    assert_eq!(strings(&out, 1), vec!["x"]);
+    let out = prepared
+        .execute(params(&[("id", json!(3)), ("tag", json!(7))]))
+        .await
+        .unwrap();
+    assert_eq!(out.schema().field(1).data_type(), &DataType::Int64);
+}
+
+#[tokio::test]
+async fn test_prepared_query_streams_results() {
+    use futures::TryStreamExt;
+
+    let prepared = CypherQuery::new("MATCH (p:Person) WHERE p.age > $min RETURN p.name")
+        .unwrap()
+        .with_config(graph_config())
+        .prepare(catalog())
+        .unwrap();
+    let stream = prepared
+        .execute_stream(params(&[("min", json!(40))]))
+        .await
+        .unwrap();
+    let schema = stream.schema();
+    let batches: Vec<RecordBatch> = stream.try_collect().await.unwrap();
+    let out = arrow::compute::concat_batches(&schema, &batches).unwrap();
+    let mut names = strings(&out, 0);
+    names.sort();
+    assert_eq!(names, vec!["Carol", "Dave"]);
+}
+
+#[tokio::test]
+async fn test_prepared_query_with_list_parameter() {
+    let prepared = CypherQuery::new(
+        "UNWIND $ids AS id MATCH (p:Person {id: id}) RETURN p.name ORDER BY p.name",
+    )
+    .unwrap()
+    .with_config(graph_config())
+    .prepare(catalog())
+    .unwrap();
+    let out = prepared
+        .execute(params(&[("ids", json!([3, 1]))]))
+        .await
+        .unwrap();
+    assert_eq!(strings(&out, 0), vec!["Alice", "Carol"]);
+    let out = prepared
+        .execute(params(&[("ids", json!([2]))]))
+        .await
+        .unwrap();
+    assert_eq!(strings(&out, 0), vec!["Bob"]);
+
+    // Errors of queries without parameters are reported when preparing
+    let result = CypherQuery::new("MATCH (c:Company) RETURN c.name")
+        .unwrap()
+        .with_config(graph_config())
+        .prepare(catalog());
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn test_plan_cache() {
+    let cache = PlanCache::new(graph_config(), catalog(), 2);
+    assert!(cache.is_empty());
+
+    let first = cache
+        .prepare("MATCH (p:Person {id: $id})  RETURN p.name")
+        .unwrap();
+    let again = cache
+        .prepare("  MATCH (p:Person {id: $id})\n RETURN p.name")
+        .unwrap();
+    assert!(Arc::ptr_eq(&first, &again));
+    assert_eq!((cache.hits(), cache.misses()), (1, 1));
+    let out = again.execute(params(&[("id", json!(2))])).await.unwrap();
+    assert_eq!(strings(&out, 0), vec!["Bob"]);
+
+    // Whitespace inside string literals is kept
+    cache
+        .prepare("MATCH (p:Person {name: 'Alice  Smith'}) RETURN p.id")
+        .unwrap();
+    cache
+        .prepare("MATCH (p:Person {name: 'Alice Smith'}) RETURN p.id")
+        .unwrap();
+    assert_eq!(cache.misses(), 3);
+
+    // The least recently used query is dropped beyond the capacity
+    assert_eq!(cache.len(), 2);
+    cache
+        .prepare("MATCH (p:Person {id: $id}) RETURN p.name")
+        .unwrap();
+    assert_eq!(cache.misses(), 4);
+
+    assert!(cache.prepare("MATCH (p:Person RETURN p").is_err());
+    cache.clear();
+    assert!(cache.is_empty());
+}
@@ -0,0 +1,87 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Random walks with `CALL graph.randomWalk`
+
+use std::collections::HashSet;
+
+use arrow::array::AsArray;
+use arrow::datatypes::Int64Type;
+use arrow_array::RecordBatch;
+
+mod common;
+use common::{edges, named, TestGraph};
+
+/// Alice (1), Bob (2) and Carol (3) know each other both ways; Carol knows Dan (4), who
+/// knows nobody. Eve (5) has no relationships.
+fn social() -> TestGraph {
+    TestGraph::people(&["KNOWS"])
+        .with_nodes("Person", named(&["Alice", "Bob", "Carol", "Dan", "Eve"]))
+        .with_relationships(
+            "KNOWS",
+            edges(&[(1, 2), (2, 1), (1, 3), (3, 1), (2, 3), (3, 2), (3, 4)]),
+        )
+}
+
+/// The walks of a `startNode, walk` result
+fn walks(batch: &RecordBatch) -> Vec<Vec<i64>> {
+    let walks = batch.column(1).as_list::<i32>();
+    walks
+        .iter()
+        .flatten()
+        .map(|walk| walk.as_primitive::<Int64Type>().values().to_vec())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_random_walks() {
+    let knows: HashSet<(i64, i64)> = [(1, 2), (2, 1), (1, 3), (3, 1), (2, 3), (3, 2), (3, 4)]
+        .into_iter()
+        .collect();
+    let cypher = "CALL graph.randomWalk([1, 5], 6, 1.0, 0.5, {walksPerNode: 3, seed: 11}) \
+                  YIELD startNode, walk RETURN startNode, walk";
+    let result = social().run(cypher).await.unwrap();
+    let starts = result.column(0).as_primitive::<Int64Type>();
+    assert_eq!(starts.values(), &[1, 1, 1, 5, 5, 5]);
+    let taken = walks(&result);
+    for walk in &taken[..3] {
+        assert_eq!(walk[0], 1);
+        assert!(walk.len() == 7 || walk.last() == Some(&4), "{:?}", walk);
+        for step in walk.windows(2) {
+            assert!(knows.contains(&(step[0], step[1])), "{:?}", walk);
+        }
+    }
+    // Eve has nowhere to go
+    assert!(taken[3..].iter().all(|walk| walk == &vec![5]));
+
+    // The same seed takes the same walks
+    assert_eq!(walks(&social().run(cypher).await.unwrap()), taken);
+}
+
+#[tokio::test]
+async fn test_random_walks_from_every_node() {
+    let result = social()
+        .run("CALL graph.randomWalk([], 2) YIELD startNode, walk RETURN startNode, walk")
+        .await
+        .unwrap();
+    let starts = result.column(0).as_primitive::<Int64Type>();
+    assert_eq!(starts.values(), &[1, 2, 3, 4]);
+    assert_eq!(walks(&result)[3], vec![4]);
+}
+
+#[tokio::test]
+async fn test_random_walk_errors() {
+    for (cypher, message) in [
+        (
+            "CALL graph.randomWalk([1], 3, 0.0) YIELD walk RETURN walk",
+            "p must be a positive number",
+        ),
+        (
+            "CALL graph.randomWalk([1], 3, 1.0, 1.0, {walksPerNode: 0}) YIELD walk RETURN walk",
+            "walksPerNode must be positive",
+        ),
+    ] {
+        let error = social().run(cypher).await.unwrap_err().to_string();
+        assert!(error.contains(message), "{}: {}", cypher, error);
+    }
+}
@@ -0,0 +1,133 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Weighted shortest paths with `CALL graph.shortestPath.dijkstra`
+
+use std::sync::Arc;
+
+use arrow::array::AsArray;
+use arrow::datatypes::{Float64Type, Int64Type};
+use arrow_array::RecordBatch;
+use lance_graph::source_catalog::GraphSourceCatalog;
+use lance_graph::GraphConfig;
+
+mod common;
+use common::{edges, named, weighted_edges, TestGraph};
+
+/// Roads with a distance; the shortest road route from Aachen (1) to Essen (5) goes
+/// through Celle (3) and Fulda (6), 20 long. A ferry without a distance goes straight
+/// from Aachen to Essen.
+fn cities(extra_roads: &[(i64, i64, f64)]) -> TestGraph {
+    let config = GraphConfig::builder()
+        .with_node_label("City", "id")
+        .with_relationship("ROAD", "src_id", "dst_id")
+        .with_relationship("FERRY", "src_id", "dst_id")
+        .build()
+        .unwrap();
+    let mut roads = vec![
+        (1, 2, 7.0),
+        (1, 3, 9.0),
+        (1, 6, 14.0),
+        (2, 3, 10.0),
+        (2, 4, 15.0),
+        (3, 4, 11.0),
+        (3, 6, 2.0),
+        (6, 5, 9.0),
+        (4, 5, 6.0),
+    ];
+    roads.extend_from_slice(extra_roads);
+    TestGraph::new(config)
+        .with_nodes(
+            "City",
+            named(&["Aachen", "Bonn", "Celle", "Dorsten", "Essen", "Fulda"]),
+        )
+        .with_relationships("ROAD", weighted_edges("distance", &roads))
+        .with_relationships("FERRY", edges(&[(1, 5)]))
+}
+
+fn path_nodes(batch: &RecordBatch) -> Vec<i64> {
+    let nodes = batch.column(0).as_list::<i32>().value(0);
+    nodes.as_primitive::<Int64Type>().values().to_vec()
+}
+
+fn cost(batch: &RecordBatch) -> f64 {
+    batch.column(2).as_primitive::<Float64Type>().value(0)
+}
+
+async fn assert_shortest_paths(catalog: Arc<dyn GraphSourceCatalog>) {
+    let graph = cities(&[]);
+    let result = graph
+        .run_with(
+            "CALL graph.shortestPath.dijkstra(1, 5, 'distance') \
+             YIELD nodes, relationships, cost RETURN nodes, relationships, cost",
+            catalog.clone(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(result.num_rows(), 1);
+    assert_eq!(path_nodes(&result), vec![1, 3, 6, 5]);
+    assert_eq!(cost(&result), 20.0);
+    let relationships = result.column(1).as_list::<i32>().value(0);
+    let relationships = relationships.as_struct();
+    let types = relationships
+        .column_by_name("type")
+        .unwrap()
+        .as_string::<i32>();
+    assert_eq!(types.iter().flatten().collect::<Vec<_>>(), ["ROAD"; 3]);
+    let weights = relationships
+        .column_by_name("weight")
+        .unwrap()
+        .as_primitive::<Float64Type>();
+    assert_eq!(weights.values(), &[9.0, 2.0, 9.0]);
+
+    let result = graph
+        .run_with(
+            "CALL graph.shortestPath.dijkstra(2, 2, 'distance', ['ROAD']) \
+             YIELD nodes, cost RETURN nodes, cost",
+            catalog.clone(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(path_nodes(&result), vec![2]);
+    assert_eq!(result.column(1).as_primitive::<Float64Type>().value(0), 0.0);
+
+    // Roads only lead away from Aachen
+    let result = graph
+        .run_with(
+            "CALL graph.shortestPath.dijkstra(5, 1, 'distance') YIELD cost RETURN cost",
+            catalog,
+        )
+        .await
+        .unwrap();
+    assert_eq!(result.num_rows(), 0);
+}
+
+#[tokio::test]
+async fn test_shortest_paths_over_in_memory_tables() {
+    assert_shortest_paths(cities(&[]).memory_catalog()).await;
+}
+
+#[tokio::test]
+async fn test_shortest_paths_over_lance_datasets() {
+    let dir = tempfile::tempdir().unwrap();
+    assert_shortest_paths(cities(&[]).lance_catalog(&dir).await).await;
+}
+
+#[tokio::test]
+async fn test_shortest_path_errors() {
+    for (cypher, graph, message) in [
+        (
+            "CALL graph.shortestPath.dijkstra(1, 5, 'toll') YIELD cost RETURN cost",
+            cities(&[]),
+            "no relationship type has the property 'toll'",
+        ),
+        (
+            "CALL graph.shortestPath.dijkstra(5, 2, 'distance') YIELD cost RETURN cost",
+            cities(&[(5, 1, -1.0)]),
+            "negative weight",
+        ),
+    ] {
+        let error = graph.run(cypher).await.unwrap_err().to_string();
+        assert!(error.contains(message), "{}: {}", cypher, error);
+    }
+}
@@ -0,0 +1,170 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! k-hop subgraph extraction with `SubgraphQuery` and `CALL graph.subgraph`
+
+use arrow::array::AsArray;
+use arrow::datatypes::Int64Type;
+use datafusion::scalar::ScalarValue;
+use lance::dataset::WriteMode;
+use lance_graph::ast::RelationshipDirection;
+use lance_graph::{GraphConfig, RelationshipMapping, Subgraph, SubgraphQuery};
+
+mod common;
+use common::{edges, named, TestGraph};
+
+/// Alice (1) knows Bob (2), who knows Carol (3); Carol knows Alice and Dan (4), who
+/// knows Eve (5). Eve follows Alice.
+fn social() -> TestGraph {
+    TestGraph::people(&["KNOWS", "FOLLOWS"])
+        .with_nodes("Person", named(&["Alice", "Bob", "Carol", "Dan", "Eve"]))
+        .with_relationships("KNOWS", edges(&[(1, 2), (2, 3), (3, 1), (3, 4), (4, 5)]))
+        .with_relationships("FOLLOWS", edges(&[(5, 1)]))
+}
+
+/// The `(node, depth)` rows of a subgraph
+fn nodes(subgraph: &Subgraph) -> Vec<(i64, i64)> {
+    let nodes = subgraph.nodes.column(0).as_primitive::<Int64Type>();
+    let depths = subgraph.nodes.column(1).as_primitive::<Int64Type>();
+    nodes
+        .values()
+        .iter()
+        .copied()
+        .zip(depths.values().iter().copied())
+        .collect()
+}
+
+/// The `(type, source, target)` rows of a subgraph
+fn relationships(subgraph: &Subgraph) -> Vec<(String, i64, i64)> {
+    let batch = &subgraph.relationships;
+    let types = batch.column(0).as_string::<i32>();
+    let sources = batch.column(1).as_primitive::<Int64Type>();
+    let targets = batch.column(2).as_primitive::<Int64Type>();
+    (0..batch.num_rows())
+        .map(|row| {
+            (
+                types.value(row).to_string(),
+                sources.value(row),
+                targets.value(row),
+            )
+        })
+        .collect()
+}
+
+fn rel(rel_type: &str, source: i64, target: i64) -> (String, i64, i64) {
+    (rel_type.to_string(), source, target)
+}
+
+#[tokio::test]
+async fn test_subgraph_within_one_hop() {
+    let graph = social();
+    let subgraph = SubgraphQuery::new(graph.config(), [ScalarValue::Int64(Some(1))], 1)
+        .execute_with_catalog(graph.memory_catalog())
+        .await
+        .unwrap();
+    assert_eq!(nodes(&subgraph), vec![(1, 0), (2, 1), (3, 1), (5, 1)]);
+    // Bob knows Carol, both a hop from Alice
+    assert_eq!(
+        relationships(&subgraph),
+        vec![
+            rel("FOLLOWS", 5, 1),
+            rel("KNOWS", 1, 2),
+            rel("KNOWS", 2, 3),
+            rel("KNOWS", 3, 1),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_outgoing_subgraph_of_one_type() {
+    let graph = social();
+    let subgraph = SubgraphQuery::new(graph.config(), [ScalarValue::Int64(Some(1))], 2)
+        .with_relationship_types(["KNOWS"])
+        .with_direction(RelationshipDirection::Outgoing)
+        .execute_with_catalog(graph.memory_catalog())
+        .await
+        .unwrap();
+    assert_eq!(nodes(&subgraph), vec![(1, 0), (2, 1), (3, 2)]);
+    assert_eq!(
+        relationships(&subgraph),
+        vec![rel("KNOWS", 1, 2), rel("KNOWS", 2, 3), rel("KNOWS", 3, 1)]
+    );
+}
+
+#[tokio::test]
+async fn test_subgraph_written_to_lance() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = |name: &str| dir.path().join(name).to_str().unwrap().to_string();
+    let graph = social();
+    let subgraph = SubgraphQuery::new(graph.config(), [ScalarValue::Int64(Some(4))], 1)
+        .execute_with_catalog(graph.memory_catalog())
+        .await
+        .unwrap();
+    let (nodes, relationships) = subgraph
+        .write_to(
+            &path("nodes.lance"),
+            &path("relationships.lance"),
+            WriteMode::Create,
+        )
+        .await
+        .unwrap();
+    assert_eq!(nodes.count_rows(None).await.unwrap(), 3);
+    assert_eq!(relationships.count_rows(None).await.unwrap(), 2);
+}
+
+#[tokio::test]
+async fn test_subgraph_procedure() {
+    let result = social()
+        .run(
+            "CALL graph.subgraph([1], 2, {relationshipTypes: ['KNOWS'], direction: 'out'}) \
+             YIELD nodes, relationships RETURN nodes, relationships",
+        )
+        .await
+        .unwrap();
+    assert_eq!(result.num_rows(), 1);
+    let nodes = result.column(0).as_list::<i32>().value(0);
+    assert_eq!(nodes.as_primitive::<Int64Type>().values(), &[1, 2, 3]);
+    let relationships = result.column(1).as_list::<i32>().value(0);
+    let relationships = relationships.as_struct();
+    let sources = relationships.column(1).as_primitive::<Int64Type>();
+    let targets = relationships.column(2).as_primitive::<Int64Type>();
+    assert_eq!(sources.values(), &[1, 2, 3]);
+    assert_eq!(targets.values(), &[2, 3, 1]);
+}
+
+#[tokio::test]
+async fn test_subgraph_errors() {
+    let error = social()
+        .run("CALL graph.subgraph([1], 1, {direction: 'sideways'}) YIELD nodes RETURN nodes")
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(
+        error.contains("direction must be 'out', 'in' or 'both'"),
+        "{}",
+        error
+    );
+
+    // Relationships are extracted by one key column at each end
+    let config = GraphConfig::builder()
+        .with_node_label("Person", "id")
+        .with_relationship_mapping(
+            RelationshipMapping::new("KNOWS", "src_id", "dst_id").with_composite_endpoints(
+                vec!["src_id".into(), "src_id".into()],
+                vec!["dst_id".into(), "dst_id".into()],
+            ),
+        )
+        .build()
+        .unwrap();
+    let graph = social();
+    let error = SubgraphQuery::new(config, [ScalarValue::Int64(Some(1))], 1)
+        .execute_with_catalog(graph.memory_catalog())
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(
+        error.contains("relationship type 'KNOWS' has a composite key"),
+        "{}",
+        error
+    );
+}
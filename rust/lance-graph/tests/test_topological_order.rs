@@ -0,0 +1,109 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Topological ordering and cycle detection with `graph.topologicalSort` and
+//! `graph.findCycle`
+
+use std::sync::Arc;
+
+use arrow::array::AsArray;
+use arrow::datatypes::Int64Type;
+use arrow_array::RecordBatch;
+use lance_graph::{GraphConfig, GraphProjection};
+
+mod common;
+use common::{edges, named, TestGraph};
+
+/// Tasks 4 and 3 precede 2 and 1, 2 precedes 1, which precedes 5. Blocking goes round
+/// from 1 to 2 to 3 and back to 1, and from 3 to 4.
+fn plan() -> TestGraph {
+    let config = GraphConfig::builder()
+        .with_node_label("Task", "id")
+        .with_relationship("PRECEDES", "src_id", "dst_id")
+        .with_relationship("BLOCKS", "src_id", "dst_id")
+        .build()
+        .unwrap();
+    TestGraph::new(config)
+        .with_nodes(
+            "Task",
+            named(&["deploy", "test", "fetch", "compile", "release"]),
+        )
+        .with_relationships("PRECEDES", edges(&[(4, 2), (2, 1), (3, 1), (1, 5)]))
+        .with_relationships("BLOCKS", edges(&[(1, 2), (2, 3), (3, 1), (3, 4)]))
+}
+
+/// Node ids and positions of a result
+fn rows(batch: &RecordBatch) -> (Vec<i64>, Vec<i64>) {
+    let ids = batch.column(0).as_primitive::<Int64Type>();
+    let positions = batch.column(1).as_primitive::<Int64Type>();
+    (ids.values().to_vec(), positions.values().to_vec())
+}
+
+#[tokio::test]
+async fn test_topological_sort() {
+    let result = plan()
+        .run(
+            "CALL graph.topologicalSort({relationshipTypes: ['PRECEDES']}) \
+             YIELD nodeId, position RETURN nodeId, position",
+        )
+        .await
+        .unwrap();
+    assert_eq!(rows(&result), (vec![3, 4, 2, 1, 5], vec![0, 1, 2, 3, 4]));
+}
+
+#[tokio::test]
+async fn test_topological_sort_of_projection() {
+    let graph = plan();
+    let projection = GraphProjection::builder(graph.config())
+        .with_relationship_types(["PRECEDES"])
+        .build(graph.memory_catalog())
+        .await
+        .unwrap();
+    let result = graph
+        .with_projection("plan", Arc::new(projection))
+        .run(
+            "CALL graph.topologicalSort({projection: 'plan'}) \
+             YIELD nodeId, position RETURN nodeId, position",
+        )
+        .await
+        .unwrap();
+    assert_eq!(rows(&result).0, vec![3, 4, 2, 1, 5]);
+}
+
+#[tokio::test]
+async fn test_find_cycle() {
+    let result = plan()
+        .run(
+            "CALL graph.findCycle({relationshipTypes: ['BLOCKS']}) \
+             YIELD nodeId, position RETURN nodeId, position",
+        )
+        .await
+        .unwrap();
+    assert_eq!(rows(&result), (vec![1, 2, 3], vec![0, 1, 2]));
+
+    let result = plan()
+        .run(
+            "CALL graph.findCycle({relationshipTypes: ['PRECEDES']}) \
+             YIELD nodeId, position RETURN nodeId, position",
+        )
+        .await
+        .unwrap();
+    assert_eq!(result.num_rows(), 0);
+}
+
+#[tokio::test]
+async fn test_topological_sort_of_cyclic_graph() {
+    let error = plan()
+        .run(
+            "CALL graph.topologicalSort({relationshipTypes: ['BLOCKS']}) \
+             YIELD nodeId RETURN nodeId",
+        )
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(
+        error.contains("the graph has a cycle: 1 -> 2 -> 3 -> 1"),
+        "{}",
+        error
+    );
+}
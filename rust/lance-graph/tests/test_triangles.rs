@@ -0,0 +1,87 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Triangle counts and clustering coefficients with `CALL graph.triangleCount` and
+//! `CALL graph.localClusteringCoefficient`
+
+use arrow::array::AsArray;
+use arrow::datatypes::{Float64Type, Int64Type};
+
+mod common;
+use common::{edges, named, TestGraph};
+
+/// Alice (1), Bob (2), Carol (3) and Dan (4) all know each other, some both ways; Eve
+/// (5) knows Dan and herself. Alice likes Eve.
+fn social() -> TestGraph {
+    TestGraph::people(&["KNOWS", "LIKES"])
+        .with_nodes("Person", named(&["Alice", "Bob", "Carol", "Dan", "Eve"]))
+        .with_relationships(
+            "KNOWS",
+            edges(&[
+                (1, 2),
+                (2, 1),
+                (1, 3),
+                (1, 4),
+                (2, 3),
+                (2, 4),
+                (4, 3),
+                (3, 4),
+                (5, 4),
+                (5, 5),
+            ]),
+        )
+        .with_relationships("LIKES", edges(&[(1, 5)]))
+}
+
+#[tokio::test]
+async fn test_triangle_count() {
+    let result = social()
+        .run(
+            "CALL graph.triangleCount({relationshipTypes: ['KNOWS']}) YIELD triangleCount \
+             RETURN triangleCount",
+        )
+        .await
+        .unwrap();
+    assert_eq!(result.num_rows(), 1);
+    assert_eq!(result.column(0).as_primitive::<Int64Type>().value(0), 4);
+
+    // Alice liking Eve closes the triangle Alice, Dan, Eve
+    let result = social()
+        .run("CALL graph.triangleCount() YIELD triangleCount RETURN triangleCount")
+        .await
+        .unwrap();
+    assert_eq!(result.column(0).as_primitive::<Int64Type>().value(0), 5);
+}
+
+#[tokio::test]
+async fn test_local_clustering_coefficient() {
+    let result = social()
+        .run(
+            "CALL graph.localClusteringCoefficient({relationshipTypes: ['KNOWS']}) \
+             YIELD nodeId, triangleCount, coefficient \
+             RETURN nodeId, triangleCount, coefficient",
+        )
+        .await
+        .unwrap();
+    let ids = result.column(0).as_primitive::<Int64Type>();
+    let triangles = result.column(1).as_primitive::<Int64Type>();
+    let coefficients = result.column(2).as_primitive::<Float64Type>();
+    assert_eq!(ids.values(), &[1, 2, 3, 4, 5]);
+    assert_eq!(triangles.values(), &[3, 3, 3, 3, 0]);
+    // Dan's four neighbors make six pairs, of which Eve is in none that are connected
+    assert_eq!(coefficients.values(), &[1.0, 1.0, 1.0, 0.5, 0.0]);
+
+    let result = social()
+        .run(
+            "CALL graph.localClusteringCoefficient() YIELD nodeId, coefficient \
+             MATCH (p:Person) WHERE p.id = nodeId AND coefficient = 1.0 \
+             RETURN p.name ORDER BY p.name",
+        )
+        .await
+        .unwrap();
+    let names = result.column(0).as_string::<i32>();
+    assert_eq!(
+        names.iter().flatten().collect::<Vec<_>>(),
+        vec!["Bob", "Carol", "Eve"]
+    );
+}
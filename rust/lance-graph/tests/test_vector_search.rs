@@ -0,0 +1,190 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Nearest neighbour search with `CALL vector.knn`
+
+use std::sync::Arc;
+
+use arrow::datatypes::Float32Type;
+use arrow_array::{Array, FixedSizeListArray, Float32Array, Int64Array, RecordBatch, StringArray};
+use arrow_schema::{DataType, Field, Schema};
+use lance_graph::source_catalog::GraphSourceCatalog;
+use lance_graph::{GraphConfig, NodeMapping};
+
+mod common;
+use common::{edges, strings, TestGraph};
+
+/// Alice (1) at [1, 0], Bob (2) at [0, 1] and Carol (3) at [0.9, 0.1]; Alice knows
+/// Bob and Carol knows Alice
+fn social() -> TestGraph {
+    let embeddings = FixedSizeListArray::from_iter_primitive::<Float32Type, _, _>(
+        vec![
+            Some(vec![Some(1.0), Some(0.0)]),
+            Some(vec![Some(0.0), Some(1.0)]),
+            Some(vec![Some(0.9), Some(0.1)]),
+        ],
+        2,
+    );
+    let person = RecordBatch::try_new(
+        Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("name", DataType::Utf8, false),
+            Field::new("embedding", embeddings.data_type().clone(), true),
+        ])),
+        vec![
+            Arc::new(Int64Array::from(vec![1, 2, 3])),
+            Arc::new(StringArray::from(vec!["Alice", "Bob", "Carol"])),
+            Arc::new(embeddings),
+        ],
+    )
+    .unwrap();
+    TestGraph::people(&["KNOWS"])
+        .with_nodes("Person", person)
+        .with_relationships("KNOWS", edges(&[(1, 2), (3, 1)]))
+}
+
+/// Run `cypher` over `catalog` with `$q` bound to [1, 0]
+async fn run(
+    cypher: &str,
+    catalog: Arc<dyn GraphSourceCatalog>,
+) -> lance_graph::Result<RecordBatch> {
+    social()
+        .query(cypher)?
+        .with_parameter("q", vec![1.0, 0.0])
+        .execute_with_catalog(catalog)
+        .await
+}
+
+async fn assert_nearest_seed_patterns(catalog: Arc<dyn GraphSourceCatalog>) {
+    let result = run(
+        "CALL vector.knn('Person', 'embedding', $q, 2) YIELD id AS seed, distance \
+         MATCH (p:Person)-[:KNOWS]->(f:Person) WHERE p.id = seed \
+         RETURN p.name, f.name, distance ORDER BY distance",
+        catalog.clone(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(strings(&result, 0), vec!["Alice", "Carol"]);
+    assert_eq!(strings(&result, 1), vec!["Bob", "Alice"]);
+    let distances = result
+        .column(2)
+        .as_any()
+        .downcast_ref::<Float32Array>()
+        .unwrap();
+    assert_eq!(distances.value(0), 0.0);
+    assert!(distances.value(1) > 0.0 && distances.value(1) < 0.1);
+
+    let result = run(
+        "CALL vector.knn('Person', 'embedding', [0.0, 1.0], 1, 'cosine') YIELD id RETURN id",
+        catalog,
+    )
+    .await
+    .unwrap();
+    let ids = result
+        .column(0)
+        .as_any()
+        .downcast_ref::<Int64Array>()
+        .unwrap();
+    assert_eq!(ids.values(), &[2]);
+}
+
+#[tokio::test]
+async fn test_vector_knn_over_in_memory_tables() {
+    assert_nearest_seed_patterns(social().memory_catalog()).await;
+}
+
+#[tokio::test]
+async fn test_vector_knn_over_lance_datasets() {
+    let dir = tempfile::tempdir().unwrap();
+    assert_nearest_seed_patterns(social().lance_catalog(&dir).await).await;
+}
+
+#[tokio::test]
+async fn test_vector_knn_errors() {
+    for (cypher, message) in [
+        (
+            "CALL vector.knn('Person', 'embedding', $q, 1, 'manhattan') YIELD id RETURN id",
+            "unknown metric 'manhattan'",
+        ),
+        (
+            "CALL vector.knn('Person', 'embedding', [1.0, 0.0, 0.0], 1) YIELD id RETURN id",
+            "query has 3 dimensions",
+        ),
+        (
+            "CALL vector.knn('Company', 'embedding', $q, 1) YIELD id RETURN id",
+            "unknown node label 'Company'",
+        ),
+    ] {
+        let error = run(cypher, social().memory_catalog())
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains(message), "{}: {}", cypher, error);
+    }
+
+    // Found nodes are yielded by one key column
+    let config = GraphConfig::builder()
+        .with_node_mapping(
+            NodeMapping::new("Person", "id").with_composite_id(vec!["id".into(), "name".into()]),
+        )
+        .build()
+        .unwrap();
+    let graph = social();
+    let error = TestGraph::new(config)
+        .query("CALL vector.knn('Person', 'embedding', [1.0, 0.0], 1) YIELD id RETURN id")
+        .unwrap()
+        .execute_with_catalog(graph.memory_catalog())
+        .await
+        .unwrap_err();
+    assert!(
+        error.to_string().contains("'Person' has a composite key"),
+        "{}",
+        error
+    );
+}
+
+#[tokio::test]
+async fn test_vector_distance_ranks_a_neighbourhood() {
+    // Carol knows Alice, who knows Bob; Bob's embedding is the nearest to [0, 1]
+    let graph = social();
+    let result = graph
+        .query(
+            "MATCH (c:Person {name: 'Carol'})-[:KNOWS*1..2]->(m:Person) \
+             RETURN m.name ORDER BY vector.distance(m.embedding, $q) LIMIT 1",
+        )
+        .unwrap()
+        .with_parameter("q", vec![0.0, 1.0])
+        .execute_with_catalog(graph.memory_catalog())
+        .await
+        .unwrap();
+    assert_eq!(strings(&result, 0), vec!["Bob"]);
+
+    let result = run(
+        "MATCH (p:Person) \
+         RETURN p.name, vector.distance(p.embedding, $q, 'cosine') AS d ORDER BY d",
+        social().memory_catalog(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(strings(&result, 0), vec!["Alice", "Carol", "Bob"]);
+    let distances = result
+        .column(1)
+        .as_any()
+        .downcast_ref::<Float32Array>()
+        .unwrap();
+    assert!(distances.value(0).abs() < 1e-6);
+    assert!((distances.value(2) - 1.0).abs() < 1e-6);
+
+    let error = run(
+        "MATCH (p:Person) RETURN vector.distance(p.embedding, $q, 'manhattan')",
+        social().memory_catalog(),
+    )
+    .await
+    .unwrap_err()
+    .to_string();
+    assert!(
+        error.contains("unknown metric 'manhattan', expected 'l2', 'cosine' or 'dot'"),
+        "{}",
+        error
+    );
+}